@@ -0,0 +1,173 @@
+//! The `#[derive(FromHtml)]` macro of `spider_scraper`, re-exported as
+//! `scraper::extract::FromHtml` with the `derive` feature. See the `scraper::extract`
+//! module for the attribute syntax.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::{
+    parse_macro_input, Data, DeriveInput, Error, Fields, GenericArgument, Ident, LitStr,
+    PathArguments, Token, Type,
+};
+
+/// What a field takes from its matched elements.
+enum Directive {
+    Text,
+    Html,
+    InnerHtml,
+    Attr(LitStr),
+    Nested,
+}
+
+/// The arguments of `#[selector("css", directive)]`.
+struct SelectorAttr {
+    selector: LitStr,
+    directive: Directive,
+}
+
+impl Parse for SelectorAttr {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let selector: LitStr = input.parse()?;
+        let mut directive = Directive::Text;
+        if input.parse::<Option<Token![,]>>()?.is_some() && !input.is_empty() {
+            let name: Ident = input.parse()?;
+            directive = match name.to_string().as_str() {
+                "text" => Directive::Text,
+                "html" => Directive::Html,
+                "inner_html" => Directive::InnerHtml,
+                "nested" => Directive::Nested,
+                "attr" => {
+                    input.parse::<Token![=]>()?;
+                    Directive::Attr(input.parse()?)
+                }
+                _ => {
+                    return Err(Error::new(
+                        name.span(),
+                        "expected `text`, `html`, `inner_html`, `attr = \"..\"` or `nested`",
+                    ))
+                }
+            };
+            input.parse::<Option<Token![,]>>()?;
+        }
+        if !input.is_empty() {
+            return Err(input.error("unexpected tokens after the selector directive"));
+        }
+        Ok(SelectorAttr {
+            selector,
+            directive,
+        })
+    }
+}
+
+/// How many values a field holds, from its type.
+enum Cardinality {
+    Required,
+    Optional,
+    All,
+}
+
+fn cardinality(ty: &Type) -> Cardinality {
+    let Type::Path(path) = ty else {
+        return Cardinality::Required;
+    };
+    let Some(last) = path.path.segments.last() else {
+        return Cardinality::Required;
+    };
+    let has_one_argument = matches!(
+        &last.arguments,
+        PathArguments::AngleBracketed(args)
+            if args.args.len() == 1 && matches!(args.args[0], GenericArgument::Type(_))
+    );
+    match last.ident.to_string().as_str() {
+        "Option" if has_one_argument => Cardinality::Optional,
+        "Vec" if has_one_argument => Cardinality::All,
+        _ => Cardinality::Required,
+    }
+}
+
+/// Derives `scraper::extract::FromHtml` for a struct with named fields, each carrying a
+/// `#[selector(...)]` attribute.
+#[proc_macro_derive(FromHtml, attributes(selector))]
+pub fn derive_from_html(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let Data::Struct(data) = &input.data else {
+        return Err(Error::new_spanned(
+            &input.ident,
+            "FromHtml can only be derived for structs",
+        ));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(Error::new_spanned(
+            &input.ident,
+            "FromHtml can only be derived for structs with named fields",
+        ));
+    };
+
+    let private = quote!(::scraper::extract::__private);
+    let mut values = Vec::new();
+    for field in &fields.named {
+        let ident = field.ident.as_ref().expect("named field");
+        let attr = field
+            .attrs
+            .iter()
+            .find(|attr| attr.path().is_ident("selector"))
+            .ok_or_else(|| Error::new_spanned(ident, "missing #[selector(...)] attribute"))?;
+        let SelectorAttr {
+            selector,
+            directive,
+        } = attr.parse_args()?;
+
+        let name = ident.to_string();
+        let name = name.strip_prefix("r#").unwrap_or(&name);
+        let how = match cardinality(&field.ty) {
+            Cardinality::Required => "required",
+            Cardinality::Optional => "optional",
+            Cardinality::All => "all",
+        };
+        let call = match directive {
+            Directive::Nested => {
+                let method = Ident::new(&format!("{}_nested", how), ident.span());
+                quote!(FIELD.#method(element))
+            }
+            directive => {
+                let extract = match directive {
+                    Directive::Html => quote!(#private::Extract::Html),
+                    Directive::InnerHtml => quote!(#private::Extract::InnerHtml),
+                    Directive::Attr(name) => {
+                        quote!(#private::Extract::Attr(::std::string::String::from(#name)))
+                    }
+                    _ => quote!(#private::Extract::Text),
+                };
+                let method = Ident::new(how, ident.span());
+                quote!(FIELD.#method(element, #extract))
+            }
+        };
+        values.push(quote! {
+            #ident: {
+                static FIELD: #private::Field = #private::Field::new(#name, #selector);
+                #call?
+            }
+        });
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::scraper::extract::FromHtml for #ident #ty_generics #where_clause {
+            fn from_element(
+                element: ::scraper::ElementRef<'_>,
+            ) -> ::std::result::Result<Self, ::scraper::error::ExtractError> {
+                ::std::result::Result::Ok(Self {
+                    #(#values,)*
+                })
+            }
+        }
+    })
+}
@@ -0,0 +1,213 @@
+//! Standoff annotations over the normalized text of a document.
+//!
+//! Annotations are byte ranges of [`NormalizedText`] carrying a label, recorded together
+//! with [`NodeAnchor`]s into the text nodes they cover. The layer is stored apart from the
+//! document (as JSON with the `serde_json` feature), and restoring it against a re-parsed
+//! or lightly edited copy of the page maps every annotation back onto the tree.
+//!
+//! ```
+//! use scraper::annotations::AnnotationLayer;
+//! use scraper::Html;
+//!
+//! let html = Html::parse_document("<p>Ada Lovelace wrote the first program.</p>");
+//! let text = html.normalized_text();
+//!
+//! let mut layer = AnnotationLayer::new(&text);
+//! layer.annotate(&text, 0..12, "PERSON").unwrap();
+//!
+//! let ranges = layer.resolve(&text).unwrap();
+//! assert_eq!("Ada Lovelace", &text.as_str()[ranges[0].clone().unwrap()]);
+//! ```
+
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+use crate::error::AnnotationError;
+use crate::html::hash::fnv1a;
+use crate::text::{NodeAnchor, NormalizedText};
+
+/// Hashes text with 64-bit FNV-1a, which is stable across platforms and releases.
+fn digest(text: &str) -> u64 {
+    fnv1a(0xcbf2_9ce4_8422_2325, text.as_bytes())
+}
+
+/// A labelled range of the normalized text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Annotation {
+    /// The start byte offset in the normalized text.
+    pub start: usize,
+    /// The end byte offset in the normalized text.
+    pub end: usize,
+    /// The annotation label, such as an entity type.
+    pub label: String,
+    /// Free-form properties.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "BTreeMap::is_empty")
+    )]
+    pub attrs: BTreeMap<String, String>,
+    /// The text node ranges the annotation covers.
+    pub anchors: Vec<NodeAnchor>,
+}
+
+impl Annotation {
+    /// Sets a property.
+    pub fn attr(&mut self, key: &str, value: &str) -> &mut Self {
+        self.attrs.insert(key.to_owned(), value.to_owned());
+        self
+    }
+}
+
+/// A set of annotations recorded against one normalized text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AnnotationLayer {
+    /// A digest of the normalized text the offsets refer to.
+    pub digest: u64,
+    /// The annotations, in the order they were recorded.
+    pub annotations: Vec<Annotation>,
+}
+
+impl AnnotationLayer {
+    /// Creates an empty layer for `text`.
+    pub fn new(text: &NormalizedText) -> Self {
+        AnnotationLayer {
+            digest: digest(text.as_str()),
+            annotations: Vec::new(),
+        }
+    }
+
+    /// Records an annotation over a byte range of `text`.
+    pub fn annotate(
+        &mut self,
+        text: &NormalizedText,
+        range: Range<usize>,
+        label: &str,
+    ) -> Result<&mut Annotation, AnnotationError> {
+        if digest(text.as_str()) != self.digest {
+            return Err(AnnotationError::TextMismatch);
+        }
+        let source = text.as_str();
+        if range.start > range.end
+            || range.end > source.len()
+            || !source.is_char_boundary(range.start)
+            || !source.is_char_boundary(range.end)
+        {
+            return Err(AnnotationError::InvalidRange {
+                start: range.start,
+                end: range.end,
+            });
+        }
+
+        self.annotations.push(Annotation {
+            start: range.start,
+            end: range.end,
+            label: label.to_owned(),
+            attrs: BTreeMap::new(),
+            anchors: text.anchors(range),
+        });
+        Ok(self.annotations.last_mut().unwrap())
+    }
+
+    /// Records an annotation over a range of char offsets, as produced by most NLP tools.
+    pub fn annotate_chars(
+        &mut self,
+        text: &NormalizedText,
+        chars: Range<usize>,
+        label: &str,
+    ) -> Result<&mut Annotation, AnnotationError> {
+        let range = text
+            .char_range(chars.clone())
+            .ok_or(AnnotationError::InvalidRange {
+                start: chars.start,
+                end: chars.end,
+            })?;
+        self.annotate(text, range, label)
+    }
+
+    /// Maps every annotation onto `text`, returning its byte range in the same order.
+    ///
+    /// When `text` is the one the layer was recorded against the stored offsets are used
+    /// as-is. Otherwise each annotation is relocated through its node anchors, and is `None`
+    /// when its first or last anchor no longer resolves.
+    pub fn resolve(
+        &self,
+        text: &NormalizedText,
+    ) -> Result<Vec<Option<Range<usize>>>, AnnotationError> {
+        if digest(text.as_str()) == self.digest {
+            return Ok(self
+                .annotations
+                .iter()
+                .map(|a| Some(a.start..a.end))
+                .collect());
+        }
+        if self.annotations.iter().all(|a| a.anchors.is_empty()) {
+            return Err(AnnotationError::TextMismatch);
+        }
+
+        Ok(self
+            .annotations
+            .iter()
+            .map(|a| {
+                let start = text.locate(a.anchors.first()?)?.start;
+                let end = text.locate(a.anchors.last()?)?.end;
+                (start <= end).then_some(start..end)
+            })
+            .collect())
+    }
+
+    /// Serializes the layer as JSON.
+    #[cfg(feature = "serde_json")]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("annotation layers always serialize")
+    }
+
+    /// Deserializes a layer from JSON.
+    #[cfg(feature = "serde_json")]
+    pub fn from_json(source: &str) -> Result<Self, AnnotationError> {
+        serde_json::from_str(source).map_err(|e| AnnotationError::Decode(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AnnotationLayer;
+    use crate::error::AnnotationError;
+    use crate::html::Html;
+
+    #[test]
+    fn relocates_through_anchors_after_edits() {
+        let original = Html::parse_document("<h1>News</h1><p>Café in Paris</p>");
+        let text = original.normalized_text();
+        assert_eq!("News Café in Paris", text.as_str());
+
+        let mut layer = AnnotationLayer::new(&text);
+        layer
+            .annotate_chars(&text, 13..18, "LOC")
+            .unwrap()
+            .attr("wikidata", "Q90");
+        assert_eq!(
+            Err(AnnotationError::InvalidRange { start: 9, end: 10 }),
+            layer.annotate(&text, 9..10, "X").map(|_| ())
+        );
+
+        let edited = Html::parse_document("<h1>Breaking news</h1><p>Café in Paris</p>");
+        let edited_text = edited.normalized_text();
+        let ranges = layer.resolve(&edited_text).unwrap();
+        assert_eq!("Paris", &edited_text.as_str()[ranges[0].clone().unwrap()]);
+    }
+
+    #[cfg(feature = "serde_json")]
+    #[test]
+    fn json_round_trip() {
+        let html = Html::parse_document("<p>a b</p>");
+        let text = html.normalized_text();
+        let mut layer = AnnotationLayer::new(&text);
+        layer.annotate(&text, 2..3, "B").unwrap();
+
+        let json = layer.to_json();
+        assert!(!json.contains("attrs"));
+        assert_eq!(layer, AnnotationLayer::from_json(&json).unwrap());
+    }
+}
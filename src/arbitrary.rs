@@ -0,0 +1,275 @@
+//! Random document generation for property-based tests.
+//!
+//! With the `proptest` feature, `Html` implements `proptest::arbitrary::Arbitrary` (see
+//! [`SizeBounds`] for its parameters); with the `quickcheck` feature it implements
+//! `quickcheck::Arbitrary`, bounded by the generator size.
+//!
+//! Generated documents respect HTML content models: block containers hold blocks, paragraphs
+//! and inline elements hold phrasing content, lists hold `<li>`s, links never nest and adjacent
+//! text is merged. Serializing and re-parsing a generated document therefore yields the same
+//! tree, so the documents are safe inputs for round-trip properties.
+//!
+//! ```ignore
+//! use proptest::prelude::*;
+//! use scraper::Html;
+//!
+//! proptest! {
+//!     #[test]
+//!     fn never_panics(html in any::<Html>()) {
+//!         my_extractor(&html);
+//!     }
+//! }
+//! ```
+
+use crate::builder::{doc, ElementBuilder};
+use crate::html::Html;
+
+const BLOCK_TAGS: &[&str] = &["div", "section", "article"];
+const INLINE_TAGS: &[&str] = &["span", "em", "strong", "code"];
+const CLASSES: &[&str] = &["item", "title", "price", "link", "active"];
+
+/// Bounds on generated documents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SizeBounds {
+    /// Maximum element nesting below `<body>`.
+    pub depth: u32,
+    /// Maximum children per element.
+    pub max_children: usize,
+    /// Target number of elements in the whole document.
+    pub max_nodes: u32,
+}
+
+impl Default for SizeBounds {
+    fn default() -> Self {
+        SizeBounds {
+            depth: 4,
+            max_children: 4,
+            max_nodes: 64,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Spec {
+    Text(String),
+    Element {
+        tag: &'static str,
+        attrs: Vec<(&'static str, String)>,
+        children: Vec<Spec>,
+    },
+}
+
+impl Spec {
+    fn element(tag: &'static str, attrs: Vec<(&'static str, String)>, children: Vec<Spec>) -> Self {
+        // The parser merges adjacent text nodes, so the generator must too.
+        let mut merged: Vec<Spec> = Vec::with_capacity(children.len());
+        for child in children {
+            match (merged.last_mut(), child) {
+                (Some(Spec::Text(prev)), Spec::Text(text)) => prev.push_str(&text),
+                (_, child) => merged.push(child),
+            }
+        }
+        Spec::Element {
+            tag,
+            attrs,
+            children: merged,
+        }
+    }
+
+    fn build(self, parent: ElementBuilder) -> ElementBuilder {
+        match self {
+            Spec::Text(text) => parent.text(&text),
+            Spec::Element {
+                tag,
+                attrs,
+                children,
+            } => parent.child(tag, |e| {
+                let e = attrs.iter().fold(e, |e, (name, value)| e.attr(name, value));
+                children.into_iter().fold(e, |e, c| c.build(e))
+            }),
+        }
+    }
+}
+
+fn document(body: Vec<Spec>) -> Html {
+    doc()
+        .body(|b| body.into_iter().fold(b, |b, spec| spec.build(b)))
+        .build()
+}
+
+fn class_attr(class: Option<&'static str>) -> Vec<(&'static str, String)> {
+    class.map(|c| ("class", c.to_owned())).into_iter().collect()
+}
+
+#[cfg(feature = "proptest")]
+mod proptest_impl {
+    use proptest::arbitrary::Arbitrary;
+    use proptest::collection::vec;
+    use proptest::prelude::*;
+    use proptest::sample::select;
+
+    use super::{class_attr, document, SizeBounds, Spec, BLOCK_TAGS, CLASSES, INLINE_TAGS};
+    use crate::html::Html;
+
+    fn text() -> impl Strategy<Value = Spec> {
+        "[a-zA-Z0-9&<>\"][a-zA-Z0-9 &<>\"]{0,11}".prop_map(Spec::Text)
+    }
+
+    fn class() -> impl Strategy<Value = Option<&'static str>> {
+        proptest::option::of(select(CLASSES))
+    }
+
+    fn inline(bounds: SizeBounds) -> BoxedStrategy<Spec> {
+        let link = ("[a-z]{1,8}", text()).prop_map(|(path, text)| {
+            Spec::element("a", vec![("href", format!("/{}", path))], vec![text])
+        });
+        let leaf = prop_oneof![3 => text(), 1 => link];
+        let max = bounds.max_children.max(1);
+
+        leaf.prop_recursive(2, bounds.max_nodes / 4 + 1, max as u32, move |inner| {
+            (select(INLINE_TAGS), class(), vec(inner, 1..=max))
+                .prop_map(|(tag, class, c)| Spec::element(tag, class_attr(class), c))
+        })
+        .boxed()
+    }
+
+    fn flow(bounds: SizeBounds) -> BoxedStrategy<Spec> {
+        let max = bounds.max_children.max(1);
+        let paragraph = (class(), vec(inline(bounds), 0..=max))
+            .prop_map(|(class, c)| Spec::element("p", class_attr(class), c));
+
+        paragraph
+            .prop_recursive(bounds.depth, bounds.max_nodes, max as u32, move |inner| {
+                let block = (select(BLOCK_TAGS), class(), vec(inner.clone(), 1..=max))
+                    .prop_map(|(tag, class, c)| Spec::element(tag, class_attr(class), c));
+                let item = inner.prop_map(|c| Spec::element("li", Vec::new(), vec![c]));
+                let list = (select(&["ul", "ol"][..]), vec(item, 1..=max))
+                    .prop_map(|(tag, items)| Spec::element(tag, Vec::new(), items));
+                prop_oneof![3 => block, 1 => list]
+            })
+            .boxed()
+    }
+
+    impl Arbitrary for Html {
+        type Parameters = SizeBounds;
+        type Strategy = BoxedStrategy<Html>;
+
+        fn arbitrary_with(bounds: SizeBounds) -> Self::Strategy {
+            vec(flow(bounds), 0..=bounds.max_children.max(1))
+                .prop_map(document)
+                .boxed()
+        }
+    }
+}
+
+#[cfg(feature = "quickcheck")]
+mod quickcheck_impl {
+    use quickcheck::{Arbitrary, Gen};
+
+    use super::{class_attr, document, SizeBounds, Spec, BLOCK_TAGS, CLASSES, INLINE_TAGS};
+    use crate::html::Html;
+
+    const TEXT_CHARS: &[char] = &['a', 'b', 'c', 'x', 'y', 'z', '0', '7', '&', '<', '>', '"'];
+
+    fn below(g: &mut Gen, n: usize) -> usize {
+        usize::arbitrary(g) % n.max(1)
+    }
+
+    fn pick<T: Copy>(g: &mut Gen, items: &[T]) -> T {
+        *g.choose(items).expect("non-empty choices")
+    }
+
+    fn class(g: &mut Gen) -> Option<&'static str> {
+        bool::arbitrary(g).then(|| pick(g, CLASSES))
+    }
+
+    fn text(g: &mut Gen) -> Spec {
+        let len = 1 + below(g, 12);
+        let mut text: String = (0..len).map(|_| pick(g, TEXT_CHARS)).collect();
+        if len > 3 && bool::arbitrary(g) {
+            text.insert(len / 2, ' ');
+        }
+        Spec::Text(text)
+    }
+
+    fn inline(g: &mut Gen, depth: u32, bounds: &SizeBounds) -> Spec {
+        match below(g, 4) {
+            0 if depth > 0 => {
+                let children = (0..1 + below(g, bounds.max_children))
+                    .map(|_| inline(g, depth - 1, bounds))
+                    .collect();
+                Spec::element(pick(g, INLINE_TAGS), class_attr(class(g)), children)
+            }
+            1 => {
+                let text = text(g);
+                Spec::element(
+                    "a",
+                    vec![("href", format!("/{}", below(g, 100)))],
+                    vec![text],
+                )
+            }
+            _ => text(g),
+        }
+    }
+
+    fn flow(g: &mut Gen, depth: u32, bounds: &SizeBounds) -> Spec {
+        let children = 1 + below(g, bounds.max_children);
+        match below(g, 4) {
+            0 | 1 if depth > 0 => {
+                let children = (0..children).map(|_| flow(g, depth - 1, bounds)).collect();
+                Spec::element(pick(g, BLOCK_TAGS), class_attr(class(g)), children)
+            }
+            2 if depth > 0 => {
+                let items = (0..children)
+                    .map(|_| Spec::element("li", Vec::new(), vec![flow(g, depth - 1, bounds)]))
+                    .collect();
+                Spec::element(pick(g, &["ul", "ol"]), Vec::new(), items)
+            }
+            _ => {
+                let children = (0..children).map(|_| inline(g, 2, bounds)).collect();
+                Spec::element("p", class_attr(class(g)), children)
+            }
+        }
+    }
+
+    impl Arbitrary for Html {
+        fn arbitrary(g: &mut Gen) -> Self {
+            let bounds = SizeBounds {
+                max_children: g.size().clamp(1, 8),
+                ..SizeBounds::default()
+            };
+            let body = (0..below(g, bounds.max_children + 1))
+                .map(|_| flow(g, bounds.depth, &bounds))
+                .collect();
+            document(body)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::html::Html;
+
+    fn reparses_identically(html: &Html) -> bool {
+        let source = html.html();
+        Html::parse_document(&source).html() == source
+    }
+
+    #[cfg(feature = "proptest")]
+    proptest::proptest! {
+        #![proptest_config(proptest::prelude::ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn proptest_documents_round_trip(html in proptest::prelude::any::<Html>()) {
+            proptest::prop_assert!(reparses_identically(&html));
+        }
+    }
+
+    #[cfg(feature = "quickcheck")]
+    #[test]
+    fn quickcheck_documents_round_trip() {
+        quickcheck::QuickCheck::new()
+            .tests(64)
+            .quickcheck((|html: Html| reparses_identically(&html)) as fn(Html) -> bool);
+    }
+}
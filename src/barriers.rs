@@ -0,0 +1,465 @@
+//! Detection of login walls, paywalls, CAPTCHAs and anti-bot challenges that keep a
+//! crawler from the content of a page.
+
+use crate::element_ref::ElementRef;
+use crate::html::Html;
+use crate::selector::Selector;
+
+lazy_static! {
+    static ref PASSWORDS: Selector = Selector::parse("input[type=\"password\" i]").unwrap();
+    static ref LD_JSON: Selector = Selector::parse("script[type=\"application/ld+json\"]").unwrap();
+}
+
+/// Class and id fragments of paywall and metering containers.
+const PAYWALL_MARKERS: &[&str] = &[
+    "paywall",
+    "regwall",
+    "registration-wall",
+    "subscription-wall",
+    "subscriber-only",
+    "premium-content",
+    "metered",
+    "meter-wall",
+    "tp-modal",
+];
+
+/// Class and id fragments of overlays that cover the page.
+const OVERLAY_MARKERS: &[&str] = &["modal", "overlay", "popup", "dialog"];
+
+/// Words of overlay class and ids too short to match inside other words, such as
+/// `gateway` or `wallet`.
+const OVERLAY_WORDS: &[&str] = &["gate", "wall"];
+
+/// The number of words outside forms below which a page is mostly a login form.
+const THIN_CONTENT_WORDS: usize = 150;
+
+/// The kind of an [`AccessBarrier`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum BarrierKind {
+    /// A sign-in form standing in for the content, or shown over it.
+    Login,
+    /// A paywall or metering marker, or content declared not free to access.
+    Paywall,
+    /// A CAPTCHA widget.
+    Captcha,
+}
+
+/// A sign that the page withholds its content.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AccessBarrier {
+    /// What kind of barrier it is.
+    pub kind: BarrierKind,
+    /// What gave it away: the CSS path of the element, or the structured data property.
+    pub evidence: String,
+}
+
+/// The vendor of an [`AntibotWidget`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum AntibotVendor {
+    /// Google reCAPTCHA.
+    Recaptcha,
+    /// hCaptcha.
+    Hcaptcha,
+    /// Cloudflare Turnstile.
+    Turnstile,
+    /// PerimeterX, now HUMAN Security.
+    PerimeterX,
+    /// DataDome.
+    DataDome,
+}
+
+/// A CAPTCHA or anti-bot challenge embedded in a page.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AntibotWidget {
+    /// Who provides it.
+    pub vendor: AntibotVendor,
+    /// The version or variant, where the markup tells: `v2`, `v2-invisible`, `v3` or
+    /// `enterprise` for reCAPTCHA, the API version for hCaptcha and Turnstile,
+    /// `press-and-hold` for PerimeterX, and `captcha` or `interstitial` for DataDome.
+    pub version: Option<String>,
+    /// The public site key, or the application id of PerimeterX.
+    pub site_key: Option<String>,
+    /// The CSS path of the first element that gave it away.
+    pub evidence: String,
+}
+
+/// Returns the value of a query parameter of a URL as written, without decoding.
+fn query_param<'a>(url: &'a str, name: &str) -> Option<&'a str> {
+    let (_, query) = url.split_once('?')?;
+    query
+        .split(['&', '#'])
+        .find_map(|pair| pair.strip_prefix(name)?.strip_prefix('='))
+        .filter(|value| !value.is_empty())
+}
+
+/// Returns the text of the first quoted string after `key` in inline script.
+fn quoted_after<'a>(script: &'a str, key: &str) -> Option<&'a str> {
+    let rest = &script[script.find(key)? + key.len()..];
+    let start = rest.find(['"', '\''])?;
+    let quote = rest[start..].chars().next()?;
+    let value = &rest[start + 1..];
+    let value = &value[..value.find(quote)?];
+    (!value.is_empty() && value.len() < 64).then_some(value)
+}
+
+/// Recognises the markup of one vendor in an element.
+fn fingerprint(element: ElementRef) -> Option<(AntibotVendor, Option<String>, Option<String>)> {
+    let value = element.value();
+    let owned = |s: Option<&str>| s.map(str::to_owned);
+    let site_key = owned(value.attr("data-sitekey"));
+    let has_class = |class: &str| value.classes().any(|c| c == class);
+    let src = value.attr("src").unwrap_or_default();
+
+    if has_class("g-recaptcha") {
+        let invisible = value.attr("data-size") == Some("invisible");
+        let version = if invisible { "v2-invisible" } else { "v2" };
+        return Some((AntibotVendor::Recaptcha, Some(version.to_owned()), site_key));
+    }
+    if src.contains("recaptcha/enterprise") {
+        let key = query_param(src, "render").filter(|r| *r != "explicit");
+        return Some((
+            AntibotVendor::Recaptcha,
+            Some("enterprise".to_owned()),
+            owned(key),
+        ));
+    }
+    if src.contains("/recaptcha/api") {
+        // `render=<site key>` loads the score-based v3 API.
+        let key = query_param(src, "render").filter(|r| *r != "explicit" && *r != "onload");
+        let version = key.map(|_| "v3".to_owned());
+        return Some((AntibotVendor::Recaptcha, version, owned(key)));
+    }
+    if has_class("h-captcha") {
+        return Some((AntibotVendor::Hcaptcha, None, site_key));
+    }
+    if src.contains("hcaptcha.com/") {
+        let version = src
+            .split("hcaptcha.com/")
+            .nth(1)
+            .and_then(|path| path.split('/').next())
+            .filter(|v| v.chars().all(|c| c.is_ascii_digit()) && !v.is_empty());
+        return Some((AntibotVendor::Hcaptcha, owned(version), None));
+    }
+    if has_class("cf-turnstile") {
+        return Some((AntibotVendor::Turnstile, None, site_key));
+    }
+    if src.contains("challenges.cloudflare.com/turnstile/") {
+        let version = src
+            .split("/turnstile/")
+            .nth(1)
+            .and_then(|path| path.split('/').next())
+            .filter(|v| v.starts_with('v'));
+        return Some((AntibotVendor::Turnstile, owned(version), None));
+    }
+    if value.id() == Some("px-captcha") {
+        return Some((
+            AntibotVendor::PerimeterX,
+            Some("press-and-hold".to_owned()),
+            None,
+        ));
+    }
+    if src.contains("perimeterx.net") || src.contains("px-cdn.net") || src.contains("px-cloud.net")
+    {
+        let app = src
+            .split('/')
+            .find(|part| part.starts_with("PX") && part.len() > 2)
+            .map(str::to_owned);
+        return Some((AntibotVendor::PerimeterX, None, app));
+    }
+    if src.contains("captcha-delivery.com") || src.contains("datadome.co") {
+        let version = if src.contains("/interstitial/") {
+            Some("interstitial")
+        } else if src.contains("/captcha/") {
+            Some("captcha")
+        } else {
+            None
+        };
+        return Some((AntibotVendor::DataDome, owned(version), None));
+    }
+
+    if value.name() == "script" && value.attr("src").is_none() {
+        let script: String = element
+            .children()
+            .filter_map(|child| child.value().as_text())
+            .map(|text| &**text)
+            .collect();
+        if script.contains("_pxAppId") {
+            let app = owned(quoted_after(&script, "_pxAppId"));
+            return Some((AntibotVendor::PerimeterX, None, app));
+        }
+        // The DataDome challenge page configures itself with `var dd={'rt':'c',...}`.
+        if script.contains("captcha-delivery.com") || script.contains("var dd=") {
+            let version =
+                match quoted_after(&script, "'rt':").or_else(|| quoted_after(&script, "\"rt\":")) {
+                    Some("c") => Some("captcha"),
+                    Some("i") => Some("interstitial"),
+                    _ => None,
+                };
+            return Some((AntibotVendor::DataDome, owned(version), None));
+        }
+    }
+    None
+}
+
+/// Returns true if a class or the id of the element contains one of the markers.
+fn marked(element: ElementRef, markers: &[&str]) -> bool {
+    let value = element.value();
+    value.classes().chain(value.id()).any(|name| {
+        let name = name.to_ascii_lowercase();
+        markers.iter().any(|marker| name.contains(marker))
+    })
+}
+
+/// Returns true if a class or the id of the element has one of the words, alone or
+/// between hyphens and underscores.
+fn has_word(element: ElementRef, words: &[&str]) -> bool {
+    let value = element.value();
+    value.classes().chain(value.id()).any(|name| {
+        name.split(['-', '_'])
+            .any(|part| words.iter().any(|word| part.eq_ignore_ascii_case(word)))
+    })
+}
+
+/// Counts the words of the page outside forms, scripts and styles.
+fn content_words(html: &Html) -> usize {
+    html.tree
+        .root()
+        .descendants()
+        .filter_map(|node| Some((node, node.value().as_text()?)))
+        .filter(|(node, _)| {
+            !node.ancestors().any(|a| {
+                a.value().as_element().is_some_and(|e| {
+                    matches!(
+                        e.name(),
+                        "form" | "script" | "style" | "noscript" | "template"
+                    )
+                })
+            })
+        })
+        .map(|(_, text)| text.split_whitespace().count())
+        .sum()
+}
+
+/// Returns true if a JSON-LD block declares `"isAccessibleForFree": false`.
+fn not_free(json: &str) -> bool {
+    json.match_indices("\"isAccessibleForFree\"")
+        .any(|(i, key)| {
+            let rest = json[i + key.len()..].trim_start();
+            let Some(value) = rest.strip_prefix(':') else {
+                return false;
+            };
+            let value = value.trim_start().trim_start_matches('"');
+            value
+                .get(..5)
+                .is_some_and(|v| v.eq_ignore_ascii_case("false"))
+        })
+}
+
+impl Html {
+    /// Looks for login walls, paywalls and CAPTCHAs, so that a crawler can tell a gated page
+    /// from a complete one.
+    ///
+    /// A password form is a login wall when it sits in an overlay or when the rest of the
+    /// page has little text. Paywalls are recognised from the class names of common
+    /// paywall and metering scripts and from JSON-LD `isAccessibleForFree` set to false.
+    /// Only the outermost of nested markers is reported. CAPTCHAs are the widgets of
+    /// [`Html::detect_antibot`].
+    ///
+    /// ```
+    /// use scraper::barriers::BarrierKind;
+    /// use scraper::Html;
+    ///
+    /// let html = Html::parse_document(r#"
+    ///     <script type="application/ld+json">
+    ///       {"@type": "NewsArticle", "isAccessibleForFree": "False"}
+    ///     </script>
+    ///     <article><p>The first paragraph is free.</p>
+    ///       <div class="paywall-prompt"><p>Subscribe to keep reading.</p></div></article>
+    ///     <div class="login-modal"><form action="/login">
+    ///       <input name="user"><input type="password" name="pass"></form></div>
+    /// "#);
+    /// let kinds: Vec<_> = html.access_barriers().iter().map(|b| b.kind).collect();
+    /// assert_eq!(vec![BarrierKind::Login, BarrierKind::Paywall, BarrierKind::Paywall], kinds);
+    /// assert_eq!("isAccessibleForFree: false", html.access_barriers()[2].evidence);
+    /// ```
+    pub fn access_barriers(&self) -> Vec<AccessBarrier> {
+        let root = self.root_element();
+        let mut barriers = Vec::new();
+        let found = |barriers: &mut Vec<AccessBarrier>, kind, element: ElementRef| {
+            barriers.push(AccessBarrier {
+                kind,
+                evidence: element.css_path(),
+            })
+        };
+
+        let thin = content_words(self) < THIN_CONTENT_WORDS;
+        let mut forms = Vec::new();
+        for password in root.select(&PASSWORDS) {
+            let form = password
+                .ancestors()
+                .filter_map(ElementRef::wrap)
+                .find(|a| a.value().name() == "form")
+                .unwrap_or(password);
+            if forms.contains(&form.id()) {
+                continue;
+            }
+            forms.push(form.id());
+            let overlay = form
+                .ancestors()
+                .filter_map(ElementRef::wrap)
+                .any(|a| marked(a, OVERLAY_MARKERS) || has_word(a, OVERLAY_WORDS));
+            if thin || overlay {
+                found(&mut barriers, BarrierKind::Login, form);
+            }
+        }
+
+        let paywalls = root.descendants().filter_map(ElementRef::wrap).filter(|e| {
+            marked(*e, PAYWALL_MARKERS)
+                && !e
+                    .ancestors()
+                    .filter_map(ElementRef::wrap)
+                    .any(|a| marked(a, PAYWALL_MARKERS))
+        });
+        for paywall in paywalls {
+            found(&mut barriers, BarrierKind::Paywall, paywall);
+        }
+
+        for widget in self.detect_antibot() {
+            barriers.push(AccessBarrier {
+                kind: BarrierKind::Captcha,
+                evidence: widget.evidence,
+            });
+        }
+
+        let declared = root.select(&LD_JSON).any(|script| {
+            // `text()` skips script content, so read the raw text children.
+            let json: String = script
+                .children()
+                .filter_map(|child| child.value().as_text())
+                .map(|text| &**text)
+                .collect();
+            not_free(&json)
+        });
+        if declared {
+            barriers.push(AccessBarrier {
+                kind: BarrierKind::Paywall,
+                evidence: "isAccessibleForFree: false".to_owned(),
+            });
+        }
+        barriers
+    }
+
+    /// Fingerprints the CAPTCHA and anti-bot challenges of a page: reCAPTCHA, hCaptcha,
+    /// Turnstile, PerimeterX and DataDome, from their widget elements, loader scripts,
+    /// challenge iframes and inline configuration. Each vendor is reported once, at its
+    /// first appearance, with the version and site key of any of its elements.
+    ///
+    /// ```
+    /// use scraper::barriers::AntibotVendor;
+    /// use scraper::Html;
+    ///
+    /// let html = Html::parse_document(r#"
+    ///     <script src="https://www.google.com/recaptcha/api.js?render=6Lc_key"></script>
+    ///     <form><div class="cf-turnstile" data-sitekey="0x4AAA"></div></form>
+    ///     <script src="https://challenges.cloudflare.com/turnstile/v0/api.js" async></script>
+    /// "#);
+    /// let widgets = html.detect_antibot();
+    /// assert_eq!(AntibotVendor::Recaptcha, widgets[0].vendor);
+    /// assert_eq!(Some("v3"), widgets[0].version.as_deref());
+    /// assert_eq!(Some("6Lc_key"), widgets[0].site_key.as_deref());
+    /// assert_eq!(
+    ///     (Some("v0"), Some("0x4AAA")),
+    ///     (widgets[1].version.as_deref(), widgets[1].site_key.as_deref())
+    /// );
+    /// ```
+    pub fn detect_antibot(&self) -> Vec<AntibotWidget> {
+        let mut widgets: Vec<AntibotWidget> = Vec::new();
+        for element in self
+            .root_element()
+            .descendants()
+            .filter_map(ElementRef::wrap)
+        {
+            let Some((vendor, version, site_key)) = fingerprint(element) else {
+                continue;
+            };
+            match widgets.iter_mut().find(|w| w.vendor == vendor) {
+                Some(widget) => {
+                    widget.version = widget.version.take().or(version);
+                    widget.site_key = widget.site_key.take().or(site_key);
+                }
+                None => widgets.push(AntibotWidget {
+                    vendor,
+                    version,
+                    site_key,
+                    evidence: element.css_path(),
+                }),
+            }
+        }
+        widgets
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AntibotVendor, BarrierKind};
+    use crate::html::Html;
+
+    #[test]
+    fn header_logins_on_full_pages_are_not_walls() {
+        let article = "word ".repeat(200);
+        let html = Html::parse_document(&format!(
+            "<header><form><input type='password'></form></header><p>{article}</p>\
+             <div class='g-recaptcha' data-sitekey='x'></div>\
+             <p class='parameter'>not a meter</p>"
+        ));
+        let barriers = html.access_barriers();
+        assert_eq!(1, barriers.len());
+        assert_eq!(BarrierKind::Captcha, barriers[0].kind);
+        assert_eq!("html > body > div", barriers[0].evidence);
+
+        let gated = Html::parse_document("<form><input type='PASSWORD'></form>");
+        assert_eq!(BarrierKind::Login, gated.access_barriers()[0].kind);
+
+        let wall = |class: &str| {
+            let html = Html::parse_document(&format!(
+                "<div class='{class}'><form><input type='password'></form></div><p>{article}</p>"
+            ));
+            html.access_barriers().len()
+        };
+        assert_eq!(1, wall("login_wall"));
+        assert_eq!(1, wall("Gate"));
+        assert_eq!(0, wall("payment-gateway"));
+        assert_eq!(0, wall("wallet"));
+    }
+
+    #[test]
+    fn challenge_pages() {
+        let datadome = Html::parse_document(
+            "<script>var dd={'rt':'c','cid':'AHrl','hsh':'1','t':'fe','host':'geo.captcha-delivery.com'}</script>\
+             <script src='https://ct.captcha-delivery.com/c.js'></script>",
+        );
+        let widgets = datadome.detect_antibot();
+        assert_eq!(1, widgets.len());
+        assert_eq!(AntibotVendor::DataDome, widgets[0].vendor);
+        assert_eq!(Some("captcha"), widgets[0].version.as_deref());
+
+        let px = Html::parse_document(
+            "<script>window._pxAppId = 'PXa1b2c3';</script><div id='px-captcha'></div>",
+        );
+        let widgets = px.detect_antibot();
+        assert_eq!(
+            (Some("press-and-hold"), Some("PXa1b2c3")),
+            (
+                widgets[0].version.as_deref(),
+                widgets[0].site_key.as_deref()
+            )
+        );
+        assert_eq!(BarrierKind::Captcha, px.access_barriers()[0].kind);
+    }
+}
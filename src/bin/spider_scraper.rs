@@ -0,0 +1,117 @@
+//! Command line tools for inspecting documents with spider_scraper.
+//!
+//! ```text
+//! spider_scraper tree [--select SELECTOR] [--depth N] [--text N] [--color|--no-color] [FILE]
+//! ```
+//!
+//! Reads the document from `FILE`, or from stdin when omitted, in the encoding its bytes
+//! or `<meta charset>` declare.
+
+use std::io::{self, IsTerminal, Read, Write};
+use std::process::ExitCode;
+
+use scraper::element_ref::TreeView;
+use scraper::{Html, Selector};
+
+const USAGE: &str = "usage: spider_scraper tree [--select SELECTOR] [--depth N] [--text N] [--color|--no-color] [FILE]";
+
+struct TreeArgs {
+    select: Option<String>,
+    depth: Option<usize>,
+    text: usize,
+    color: Option<bool>,
+    file: Option<String>,
+}
+
+fn parse_tree_args(mut args: impl Iterator<Item = String>) -> Result<TreeArgs, String> {
+    let mut parsed = TreeArgs {
+        select: None,
+        depth: None,
+        text: TreeView::default().max_text,
+        color: None,
+        file: None,
+    };
+
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or(format!("{} needs a value", name));
+        match arg.as_str() {
+            "--select" | "-s" => parsed.select = Some(value(&arg)?),
+            "--depth" | "-d" => {
+                parsed.depth = Some(
+                    value(&arg)?
+                        .parse()
+                        .map_err(|e| format!("--depth: {}", e))?,
+                )
+            }
+            "--text" | "-t" => {
+                parsed.text = value(&arg)?.parse().map_err(|e| format!("--text: {}", e))?
+            }
+            "--color" => parsed.color = Some(true),
+            "--no-color" => parsed.color = Some(false),
+            flag if flag.starts_with('-') && flag != "-" => {
+                return Err(format!("unknown option {}", flag))
+            }
+            _ if parsed.file.is_some() => return Err("only one FILE may be given".into()),
+            _ => parsed.file = Some(arg),
+        }
+    }
+
+    Ok(parsed)
+}
+
+fn tree(args: TreeArgs) -> Result<(), String> {
+    let source = match args.file.as_deref() {
+        None | Some("-") => {
+            let mut source = Vec::new();
+            io::stdin()
+                .read_to_end(&mut source)
+                .map_err(|e| format!("stdin: {}", e))?;
+            source
+        }
+        Some(path) => std::fs::read(path).map_err(|e| format!("{}: {}", path, e))?,
+    };
+
+    let selector = match args.select.as_deref() {
+        Some(s) => Some(Selector::parse(s).map_err(|e| format!("--select {:?}: {:?}", s, e))?),
+        None => None,
+    };
+    let stdout = io::stdout();
+    let color = args
+        .color
+        .unwrap_or_else(|| stdout.is_terminal() && std::env::var_os("NO_COLOR").is_none());
+
+    let html = Html::parse_document_bytes(&source);
+    let view = TreeView {
+        max_depth: args.depth,
+        max_text: args.text,
+        color,
+        highlight: selector.as_ref(),
+    };
+
+    let mut out = stdout.lock();
+    let _ = out.write_all(html.root_element().tree_view(&view).as_bytes());
+    if let Some(selector) = &selector {
+        let _ = writeln!(out, "{} match(es)", html.select(selector).count());
+    }
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let mut args = std::env::args().skip(1);
+    let result = match args.next().as_deref() {
+        Some("tree") => parse_tree_args(args).and_then(tree),
+        Some("--help") | Some("-h") | None => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Some(other) => Err(format!("unknown command {:?}\n{}", other, USAGE)),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("spider_scraper: {}", message);
+            ExitCode::FAILURE
+        }
+    }
+}
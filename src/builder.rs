@@ -0,0 +1,303 @@
+//! Programmatic construction of documents.
+//!
+//! Builds an [`Html`] tree directly, without writing and parsing markup, which keeps test
+//! fixtures readable and structurally valid. Every builder method takes and returns the
+//! builder by value, and nested content is described by closures doing the same.
+//!
+//! ```
+//! use scraper::builder::doc;
+//! use scraper::Selector;
+//!
+//! let html = doc()
+//!     .title("Shop")
+//!     .body(|b| {
+//!         b.div(|d| d.class("item").a("/widget", |a| a.text("Widget")))
+//!             .div(|d| d.class("item").a("/gadget", |a| a.text("Gadget")))
+//!     })
+//!     .build();
+//!
+//! let links = Selector::parse(".item > a").unwrap();
+//! let hrefs: Vec<_> = html.select(&links).map(|a| a.value().attr("href").unwrap()).collect();
+//! assert_eq!(vec!["/widget", "/gadget"], hrefs);
+//! ```
+
+use ego_tree::{NodeMut, Tree};
+use html5ever::tendril::StrTendril;
+use html5ever::tree_builder::QuirksMode;
+use html5ever::{Attribute, LocalName, QualName};
+
+use crate::html::Html;
+use crate::node::{Comment, Doctype, Element, Node, Text};
+
+/// Starts building a full document with a doctype, `<head>` and `<body>`.
+pub fn doc() -> DocumentBuilder {
+    DocumentBuilder {
+        lang: None,
+        head: ElementBuilder::new("head"),
+        body: ElementBuilder::new("body"),
+    }
+}
+
+/// Builds a fragment, shaped like the output of [`Html::parse_fragment`].
+pub fn fragment(build: impl FnOnce(ElementBuilder) -> ElementBuilder) -> Html {
+    let root = build(ElementBuilder::new("html"));
+
+    let mut tree = Tree::new(Node::Fragment);
+    root.attach(&mut tree.root_mut());
+
+    Html::from_parts(QuirksMode::NoQuirks, tree)
+}
+
+/// Builder for a full document.
+#[derive(Debug, Clone)]
+pub struct DocumentBuilder {
+    lang: Option<String>,
+    head: ElementBuilder,
+    body: ElementBuilder,
+}
+
+impl DocumentBuilder {
+    /// Sets the `lang` attribute of the `<html>` element.
+    pub fn lang(mut self, lang: &str) -> Self {
+        self.lang = Some(lang.to_owned());
+        self
+    }
+
+    /// Adds a `<title>` to the head.
+    pub fn title(mut self, title: &str) -> Self {
+        self.head = self.head.child("title", |t| t.text(title));
+        self
+    }
+
+    /// Adds content to the `<head>`.
+    pub fn head(mut self, build: impl FnOnce(ElementBuilder) -> ElementBuilder) -> Self {
+        self.head = build(self.head);
+        self
+    }
+
+    /// Adds content to the `<body>`.
+    pub fn body(mut self, build: impl FnOnce(ElementBuilder) -> ElementBuilder) -> Self {
+        self.body = build(self.body);
+        self
+    }
+
+    /// Finishes the document.
+    pub fn build(self) -> Html {
+        let mut html = ElementBuilder::new("html");
+        if let Some(lang) = &self.lang {
+            html = html.attr("lang", lang);
+        }
+        html.children.push(Child::Element(self.head));
+        html.children.push(Child::Element(self.body));
+
+        let mut tree = Tree::new(Node::Document);
+        {
+            let mut root = tree.root_mut();
+            root.append(Node::Doctype(Doctype {
+                name: "html".into(),
+                public_id: "".into(),
+                system_id: "".into(),
+            }));
+            html.attach(&mut root);
+        }
+
+        Html {
+            lang: self.lang.unwrap_or_default(),
+            ..Html::from_parts(QuirksMode::NoQuirks, tree)
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Child {
+    Element(ElementBuilder),
+    Text(String),
+    Comment(String),
+}
+
+/// Builder for an element and its descendants.
+#[derive(Debug, Clone)]
+pub struct ElementBuilder {
+    name: String,
+    attrs: Vec<(String, String)>,
+    children: Vec<Child>,
+}
+
+macro_rules! container_elements {
+    ($($tag:ident),* $(,)?) => {
+        $(
+            #[doc = concat!("Appends a `<", stringify!($tag), ">` child.")]
+            #[allow(clippy::should_implement_trait)]
+            pub fn $tag(self, build: impl FnOnce(ElementBuilder) -> ElementBuilder) -> Self {
+                self.child(stringify!($tag), build)
+            }
+        )*
+    };
+}
+
+impl ElementBuilder {
+    fn new(name: &str) -> Self {
+        ElementBuilder {
+            name: name.to_owned(),
+            attrs: Vec::new(),
+            children: Vec::new(),
+        }
+    }
+
+    /// Sets an attribute, replacing any previous value.
+    pub fn attr(mut self, name: &str, value: &str) -> Self {
+        match self.attrs.iter_mut().find(|(n, _)| n == name) {
+            Some((_, v)) => *v = value.to_owned(),
+            None => self.attrs.push((name.to_owned(), value.to_owned())),
+        }
+        self
+    }
+
+    /// Sets the `id` attribute.
+    pub fn id(self, id: &str) -> Self {
+        self.attr("id", id)
+    }
+
+    /// Adds a class to the `class` attribute.
+    pub fn class(mut self, class: &str) -> Self {
+        match self.attrs.iter_mut().find(|(n, _)| n == "class") {
+            Some((_, v)) => {
+                v.push(' ');
+                v.push_str(class);
+            }
+            None => self.attrs.push(("class".to_owned(), class.to_owned())),
+        }
+        self
+    }
+
+    /// Appends a text node.
+    pub fn text(mut self, text: &str) -> Self {
+        self.children.push(Child::Text(text.to_owned()));
+        self
+    }
+
+    /// Appends a comment.
+    pub fn comment(mut self, comment: &str) -> Self {
+        self.children.push(Child::Comment(comment.to_owned()));
+        self
+    }
+
+    /// Appends a child element with any tag name.
+    pub fn child(
+        mut self,
+        name: &str,
+        build: impl FnOnce(ElementBuilder) -> ElementBuilder,
+    ) -> Self {
+        let child = build(ElementBuilder::new(name));
+        self.children.push(Child::Element(child));
+        self
+    }
+
+    /// Appends a child element without content, such as `<br>` or `<input>`.
+    pub fn void(self, name: &str, attrs: &[(&str, &str)]) -> Self {
+        self.child(name, |e| {
+            attrs.iter().fold(e, |e, (name, value)| e.attr(name, value))
+        })
+    }
+
+    container_elements!(
+        div, span, p, section, article, header, footer, nav, main, aside, ul, ol, li, dl, dt, dd,
+        table, thead, tbody, tfoot, tr, th, td, form, label, button, select, option, textarea, h1,
+        h2, h3, h4, h5, h6, strong, em, b, i, small, pre, code, blockquote, figure, figcaption,
+        script, style,
+    );
+
+    /// Appends an `<a href>` child.
+    pub fn a(self, href: &str, build: impl FnOnce(ElementBuilder) -> ElementBuilder) -> Self {
+        self.child("a", |a| build(a.attr("href", href)))
+    }
+
+    /// Appends an `<img src alt>` child.
+    pub fn img(self, src: &str, alt: &str) -> Self {
+        self.void("img", &[("src", src), ("alt", alt)])
+    }
+
+    /// Appends an `<input type name>` child.
+    pub fn input(self, kind: &str, name: &str) -> Self {
+        self.void("input", &[("type", kind), ("name", name)])
+    }
+
+    /// Appends a `<meta name content>` child.
+    pub fn meta(self, name: &str, content: &str) -> Self {
+        let key = if name.contains(':') {
+            "property"
+        } else {
+            "name"
+        };
+        self.void("meta", &[(key, name), ("content", content)])
+    }
+
+    /// Appends a `<link rel href>` child.
+    pub fn link(self, rel: &str, href: &str) -> Self {
+        self.void("link", &[("rel", rel), ("href", href)])
+    }
+
+    /// Appends a `<br>` child.
+    pub fn br(self) -> Self {
+        self.void("br", &[])
+    }
+
+    fn attach(self, parent: &mut NodeMut<Node>) {
+        let name = QualName::new(None, ns!(html), LocalName::from(self.name.as_str()));
+        let attrs = self
+            .attrs
+            .iter()
+            .map(|(name, value)| Attribute {
+                name: QualName::new(None, ns!(), LocalName::from(name.as_str())),
+                value: StrTendril::from_slice(value),
+            })
+            .collect();
+
+        let mut node = parent.append(Node::Element(Element::new(name, attrs)));
+        for child in self.children {
+            match child {
+                Child::Element(element) => element.attach(&mut node),
+                Child::Text(text) => {
+                    node.append(Node::Text(Text {
+                        text: text.as_str().into(),
+                    }));
+                }
+                Child::Comment(comment) => {
+                    node.append(Node::Comment(Comment {
+                        comment: comment.as_str().into(),
+                    }));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{doc, fragment};
+    use crate::html::Html;
+
+    #[test]
+    fn document_matches_parsed_markup() {
+        let built = doc()
+            .lang("en")
+            .title("T")
+            .body(|b| b.p(|p| p.class("a").class("b").text("x").br()))
+            .build();
+
+        let parsed = Html::parse_document(
+            r#"<!DOCTYPE html><html lang="en"><head><title>T</title></head><body><p class="a b">x<br></p></body></html>"#,
+        );
+        assert_eq!(parsed.html(), built.html());
+        assert_eq!("en", built.get_lang());
+    }
+
+    #[test]
+    fn fragment_has_html_root() {
+        let html = fragment(|f| f.ul(|u| u.li(|l| l.text("1")).li(|l| l.text("2"))));
+        assert_eq!(
+            "<ul><li>1</li><li>2</li></ul>",
+            html.root_element().inner_html()
+        );
+    }
+}
@@ -0,0 +1,153 @@
+//! Token counting and token-budgeted chunking of the visible text.
+//!
+//! Counting is delegated to a [`Tokenizer`], so callers plug in the tokenizer of their model,
+//! e.g. with `tiktoken-rs`:
+//!
+//! ```ignore
+//! let bpe = tiktoken_rs::cl100k_base().unwrap();
+//! let tokenizer = |text: &str| bpe.encode_ordinary(text).len();
+//! let chunks = html.chunks(512, &tokenizer);
+//! ```
+
+use std::ops::Range;
+
+use crate::html::Html;
+
+/// Counts the tokens of a text.
+pub trait Tokenizer {
+    /// Returns the number of tokens in `text`.
+    fn count(&self, text: &str) -> usize;
+}
+
+impl<F: Fn(&str) -> usize> Tokenizer for F {
+    fn count(&self, text: &str) -> usize {
+        self(text)
+    }
+}
+
+/// A tokenizer that counts whitespace-separated words, for tests and rough budgets.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WhitespaceTokenizer;
+
+impl Tokenizer for WhitespaceTokenizer {
+    fn count(&self, text: &str) -> usize {
+        text.split_whitespace().count()
+    }
+}
+
+/// A piece of the normalized text that fits a token budget.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chunk {
+    /// The chunk text.
+    pub text: String,
+    /// The token count of `text`.
+    pub tokens: usize,
+    /// The byte range of the chunk in [`Html::normalized_text`].
+    pub range: Range<usize>,
+}
+
+impl Html {
+    /// Counts the tokens of the visible text.
+    ///
+    /// ```
+    /// use scraper::chunk::WhitespaceTokenizer;
+    /// use scraper::Html;
+    ///
+    /// let html = Html::parse_document("<p>one two</p><script>ignored()</script><p>three</p>");
+    /// assert_eq!(3, html.count_tokens(&WhitespaceTokenizer));
+    /// ```
+    pub fn count_tokens(&self, tokenizer: &impl Tokenizer) -> usize {
+        tokenizer.count(self.normalized_text().as_str())
+    }
+
+    /// Splits the visible text into chunks of at most `max_tokens` tokens each.
+    ///
+    /// Whole blocks (paragraphs, headings, list items, ...) are packed together while they
+    /// fit; a block larger than the budget is split between words. Each chunk carries its
+    /// exact token count, so no second counting pass is needed. A single word over the budget
+    /// still becomes its own chunk.
+    ///
+    /// ```
+    /// use scraper::chunk::WhitespaceTokenizer;
+    /// use scraper::Html;
+    ///
+    /// let html = Html::parse_document("<h1>Title</h1><p>a b c</p><p>d e f g h</p>");
+    /// let chunks = html.chunks(4, &WhitespaceTokenizer);
+    /// let texts: Vec<_> = chunks.iter().map(|c| (c.text.as_str(), c.tokens)).collect();
+    /// assert_eq!(vec![("Title a b c", 4), ("d e f g", 4), ("h", 1)], texts);
+    /// ```
+    pub fn chunks(&self, max_tokens: usize, tokenizer: &impl Tokenizer) -> Vec<Chunk> {
+        let normalized = self.normalized_text();
+        let text = normalized.as_str();
+        let mut chunks: Vec<Chunk> = Vec::new();
+        let mut current: Option<Range<usize>> = None;
+
+        let flush = |range: Range<usize>, chunks: &mut Vec<Chunk>| {
+            let piece = &text[range.clone()];
+            chunks.push(Chunk {
+                text: piece.to_owned(),
+                tokens: tokenizer.count(piece),
+                range,
+            });
+        };
+
+        for block in normalized.blocks() {
+            let candidate = current
+                .clone()
+                .map_or(block.clone(), |c| c.start..block.end);
+            if tokenizer.count(&text[candidate.clone()]) <= max_tokens {
+                current = Some(candidate);
+                continue;
+            }
+            if let Some(range) = current.take() {
+                flush(range, &mut chunks);
+            }
+            if tokenizer.count(&text[block.clone()]) <= max_tokens {
+                current = Some(block);
+                continue;
+            }
+
+            // Too large on its own: split between words.
+            let mut start = block.start;
+            let mut end = start;
+            for (offset, _) in text[block.clone()]
+                .match_indices(' ')
+                .chain(Some((block.len(), "")))
+            {
+                let word_end = block.start + offset;
+                if end > start && tokenizer.count(&text[start..word_end]) > max_tokens {
+                    flush(start..end, &mut chunks);
+                    start = end + 1;
+                }
+                end = word_end;
+            }
+            current = (start < end).then_some(start..end);
+        }
+        if let Some(range) = current {
+            flush(range, &mut chunks);
+        }
+        chunks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Tokenizer, WhitespaceTokenizer};
+    use crate::html::Html;
+
+    #[test]
+    fn closures_are_tokenizers_and_ranges_map_back() {
+        let chars = |text: &str| text.chars().count();
+        assert_eq!(3, chars.count("abc"));
+
+        let html = Html::parse_document("<ul><li>alpha</li><li>beta gamma</li></ul><p>delta</p>");
+        let chunks = html.chunks(2, &WhitespaceTokenizer);
+        let text = html.normalized_text();
+        for chunk in &chunks {
+            assert_eq!(chunk.text, &text.as_str()[chunk.range.clone()]);
+            assert!(chunk.tokens <= 2);
+        }
+        let texts: Vec<_> = chunks.iter().map(|c| c.text.as_str()).collect();
+        assert_eq!(vec!["alpha", "beta gamma", "delta"], texts);
+    }
+}
@@ -0,0 +1,395 @@
+//! Favicon candidates and ranking.
+
+use std::cmp::Reverse;
+
+use url::Url;
+
+use super::has_rel;
+use crate::html::Html;
+use crate::selector::Selector;
+use crate::url_utils::{normalize, UrlPolicy};
+
+lazy_static! {
+    static ref ICON_SOURCES: Selector = Selector::parse("link[href][rel], meta[content]").unwrap();
+}
+
+/// Where an icon candidate was declared.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IconSource {
+    /// `<link rel="icon">`, including `shortcut icon`.
+    Icon,
+    /// `<link rel="apple-touch-icon">` and its `-precomposed` variant.
+    AppleTouch,
+    /// `<link rel="mask-icon">`, a monochrome SVG for Safari pinned tabs.
+    MaskIcon,
+    /// An `msapplication-*` tile meta tag.
+    MsTile,
+    /// The implicit `/favicon.ico`, reported when nothing else is declared.
+    Default,
+}
+
+/// The image format of an icon, from its declared type or its URL extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IconFormat {
+    /// SVG, which scales to any size.
+    Svg,
+    /// PNG.
+    Png,
+    /// Windows ICO, which may hold several sizes.
+    Ico,
+    /// WebP.
+    Webp,
+    /// JPEG.
+    Jpeg,
+    /// GIF.
+    Gif,
+    /// Anything else or unknown.
+    Other,
+}
+
+impl IconFormat {
+    fn from_mime(mime: &str) -> Option<Self> {
+        Some(match mime.trim().to_ascii_lowercase().as_str() {
+            "image/svg+xml" => IconFormat::Svg,
+            "image/png" => IconFormat::Png,
+            "image/x-icon" | "image/vnd.microsoft.icon" | "image/ico" => IconFormat::Ico,
+            "image/webp" => IconFormat::Webp,
+            "image/jpeg" | "image/jpg" => IconFormat::Jpeg,
+            "image/gif" => IconFormat::Gif,
+            _ => return None,
+        })
+    }
+
+    fn detect(mime: Option<&str>, url: &Url) -> Self {
+        if let Some(format) = mime.and_then(IconFormat::from_mime) {
+            return format;
+        }
+        if url.scheme() == "data" {
+            let mime = url.path().split([';', ',']).next().unwrap_or_default();
+            return IconFormat::from_mime(mime).unwrap_or(IconFormat::Other);
+        }
+        let ext = url
+            .path()
+            .rsplit_once('.')
+            .map(|(_, ext)| ext.to_ascii_lowercase());
+        match ext.as_deref() {
+            Some("svg") => IconFormat::Svg,
+            Some("png") => IconFormat::Png,
+            Some("ico") => IconFormat::Ico,
+            Some("webp") => IconFormat::Webp,
+            Some("jpg" | "jpeg") => IconFormat::Jpeg,
+            Some("gif") => IconFormat::Gif,
+            _ => IconFormat::Other,
+        }
+    }
+
+    /// Lower is better when sizes tie.
+    fn preference(self) -> u8 {
+        match self {
+            IconFormat::Svg => 0,
+            IconFormat::Png => 1,
+            IconFormat::Webp => 2,
+            IconFormat::Ico => 3,
+            IconFormat::Jpeg | IconFormat::Gif => 4,
+            IconFormat::Other => 5,
+        }
+    }
+}
+
+/// An icon candidate with its absolute URL.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Favicon {
+    /// The resolved URL.
+    pub url: Url,
+    /// Where the icon was declared.
+    pub source: IconSource,
+    /// The image format.
+    pub format: IconFormat,
+    /// The declared `(width, height)` sizes; tile metas carry their implied size.
+    pub sizes: Vec<(u32, u32)>,
+    /// True for `sizes="any"` and for SVG icons.
+    pub scalable: bool,
+    /// The `color` attribute of mask icons.
+    pub color: Option<String>,
+}
+
+impl Favicon {
+    /// Returns the largest declared edge length.
+    pub fn largest(&self) -> Option<u32> {
+        self.sizes.iter().map(|&(w, h)| w.max(h)).max()
+    }
+
+    /// Ranks how well the icon fits a square of `size` pixels; lower is better.
+    fn fit(&self, size: u32) -> (u8, u32) {
+        if self.sizes.iter().any(|&(w, h)| w == size && h == size) {
+            return (0, 0);
+        }
+        if self.scalable {
+            return (1, 0);
+        }
+        let edges = self.sizes.iter().map(|&(w, h)| w.max(h));
+        if let Some(larger) = edges.clone().filter(|&e| e > size).min() {
+            return (2, larger - size);
+        }
+        match edges.max() {
+            Some(smaller) => (3, size - smaller),
+            None => (4, 0),
+        }
+    }
+}
+
+/// The icon candidates of a document, best first.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Favicons {
+    /// The candidates, ranked by scalability, size and format, with mask icons and the
+    /// implicit `/favicon.ico` last.
+    pub icons: Vec<Favicon>,
+}
+
+impl Favicons {
+    /// Returns the best icon for display as a square of `size` pixels: an exact size match,
+    /// then a scalable icon, then the closest larger icon, then the largest smaller one.
+    /// Mask icons are only chosen when nothing else is available.
+    pub fn best(&self, size: u32) -> Option<&Favicon> {
+        self.icons
+            .iter()
+            .enumerate()
+            .min_by_key(|(index, icon)| {
+                (
+                    icon.source == IconSource::MaskIcon,
+                    icon.fit(size),
+                    icon.format.preference(),
+                    *index,
+                )
+            })
+            .map(|(_, icon)| icon)
+    }
+
+    /// Returns an iterator over the candidates, best first.
+    pub fn iter(&self) -> std::slice::Iter<'_, Favicon> {
+        self.icons.iter()
+    }
+}
+
+fn parse_sizes(sizes: &str) -> (Vec<(u32, u32)>, bool) {
+    let mut parsed = Vec::new();
+    let mut any = false;
+    for token in sizes.split_ascii_whitespace() {
+        if token.eq_ignore_ascii_case("any") {
+            any = true;
+        } else if let Some((w, h)) = token.to_ascii_lowercase().split_once('x') {
+            if let (Ok(w), Ok(h)) = (w.parse(), h.parse()) {
+                parsed.push((w, h));
+            }
+        }
+    }
+    (parsed, any)
+}
+
+fn tile_size(name: &str) -> Option<(u32, u32)> {
+    Some(match name {
+        "msapplication-tileimage" => (144, 144),
+        "msapplication-square70x70logo" => (70, 70),
+        "msapplication-square150x150logo" => (150, 150),
+        "msapplication-wide310x150logo" => (310, 150),
+        "msapplication-square310x310logo" => (310, 310),
+        _ => return None,
+    })
+}
+
+impl Html {
+    /// Returns every icon candidate of the document with URLs resolved against the
+    /// [base URL](Html::base_url) of `base`, ranked best first. When the document declares
+    /// no icons the implicit `/favicon.ico` of the origin of `base` is returned.
+    ///
+    /// ```
+    /// use scraper::Html;
+    /// use url::Url;
+    ///
+    /// let html = Html::parse_document(r#"
+    ///     <link rel="icon" href="/favicon-32.png" sizes="32x32">
+    ///     <link rel="apple-touch-icon" href="/touch.png" sizes="180x180">
+    /// "#);
+    /// let icons = html.favicons(&Url::parse("https://example.com/page").unwrap());
+    /// assert_eq!("https://example.com/touch.png", icons.best(128).unwrap().url.as_str());
+    /// assert_eq!("https://example.com/favicon-32.png", icons.best(16).unwrap().url.as_str());
+    /// ```
+    pub fn favicons(&self, base: &Url) -> Favicons {
+        self.favicons_as(base, None)
+    }
+
+    /// Returns every icon candidate of the document, as [`Html::favicons`] does, with each
+    /// URL normalized by `policy` before duplicates are removed.
+    pub fn favicons_with(&self, base: &Url, policy: &UrlPolicy) -> Favicons {
+        self.favicons_as(base, Some(policy))
+    }
+
+    fn favicons_as(&self, base: &Url, policy: Option<&UrlPolicy>) -> Favicons {
+        let normalized = |url: Url| match policy {
+            Some(policy) => normalize(&url, policy),
+            None => url,
+        };
+        let resolve = self.base_url(base);
+        let mut icons: Vec<Favicon> = Vec::new();
+
+        for element in self.select(&ICON_SOURCES) {
+            let value = element.value();
+            let (source, href, mut sizes, mut scalable) = if value.name() == "meta" {
+                let name = value.attr("name").unwrap_or_default().to_ascii_lowercase();
+                let Some(size) = tile_size(name.trim()) else {
+                    continue;
+                };
+                let content = value.attr("content").unwrap_or_default();
+                (IconSource::MsTile, content, vec![size], false)
+            } else {
+                let rel = value.attr("rel").unwrap_or_default();
+                let source = if has_rel(rel, "mask-icon") {
+                    IconSource::MaskIcon
+                } else if has_rel(rel, "apple-touch-icon")
+                    || has_rel(rel, "apple-touch-icon-precomposed")
+                {
+                    IconSource::AppleTouch
+                } else if has_rel(rel, "icon") {
+                    IconSource::Icon
+                } else {
+                    continue;
+                };
+                let (sizes, any) = parse_sizes(value.attr("sizes").unwrap_or_default());
+                let href = value.attr("href").unwrap_or_default();
+                (source, href, sizes, any)
+            };
+
+            let href = href.trim();
+            let Some(url) = (!href.is_empty())
+                .then(|| resolve.join(href).ok())
+                .flatten()
+            else {
+                continue;
+            };
+            let url = normalized(url);
+            if icons.iter().any(|icon| icon.url == url) {
+                continue;
+            }
+
+            let format = IconFormat::detect(value.attr("type"), &url);
+            scalable |= format == IconFormat::Svg;
+            if source == IconSource::MaskIcon {
+                sizes.clear();
+            }
+            icons.push(Favicon {
+                url,
+                source,
+                format,
+                sizes,
+                scalable,
+                color: value.attr("color").map(str::to_owned),
+            });
+        }
+
+        if icons.is_empty() {
+            if let Ok(url) = base.join("/favicon.ico") {
+                icons.push(Favicon {
+                    url: normalized(url),
+                    source: IconSource::Default,
+                    format: IconFormat::Ico,
+                    sizes: Vec::new(),
+                    scalable: false,
+                    color: None,
+                });
+            }
+        }
+
+        icons.sort_by_key(|icon| {
+            (
+                matches!(icon.source, IconSource::MaskIcon | IconSource::Default),
+                !icon.scalable,
+                Reverse(icon.largest().unwrap_or(0)),
+                icon.format.preference(),
+            )
+        });
+
+        Favicons { icons }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use url::Url;
+
+    use super::{IconFormat, IconSource};
+    use crate::html::Html;
+    use crate::url_utils::UrlPolicy;
+
+    fn base() -> Url {
+        Url::parse("https://example.com/a/b").unwrap()
+    }
+
+    #[test]
+    fn ranks_candidates() {
+        let html = Html::parse_document(
+            r##"
+            <link rel="shortcut icon" href="favicon.ico">
+            <link rel="mask-icon" href="/pin.svg" color="#000">
+            <link rel="icon" type="image/png" href="/i/48.png" sizes="16x16 48X48">
+            <link rel="icon" href="/logo.svg" sizes="any">
+            <meta name="msapplication-TileImage" content="/tile.png">
+            "##,
+        );
+        let icons = html.favicons(&base());
+        let order: Vec<_> = icons.iter().map(|i| i.url.path()).collect();
+        assert_eq!(
+            vec![
+                "/logo.svg",
+                "/tile.png",
+                "/i/48.png",
+                "/a/favicon.ico",
+                "/pin.svg"
+            ],
+            order
+        );
+
+        assert_eq!("/i/48.png", icons.best(16).unwrap().url.path());
+        assert_eq!("/logo.svg", icons.best(64).unwrap().url.path());
+        assert_eq!(Some("#000"), icons.icons[4].color.as_deref());
+        assert_eq!(IconFormat::Ico, icons.icons[3].format);
+    }
+
+    #[test]
+    fn falls_back_to_default_icon() {
+        let html = Html::parse_document(r#"<link rel="mask-icon" href="">"#);
+        let icons = html.favicons(&base());
+        assert_eq!(1, icons.icons.len());
+        assert_eq!(IconSource::Default, icons.icons[0].source);
+        assert_eq!(
+            "https://example.com/favicon.ico",
+            icons.icons[0].url.as_str()
+        );
+    }
+
+    #[test]
+    fn policy_applies_to_declared_and_default_icons() {
+        let policy = UrlPolicy {
+            strip_www: true,
+            ..UrlPolicy::default()
+        };
+        let base = Url::parse("https://www.example.com/").unwrap();
+        let html = Html::parse_document("<link rel='icon' href='/i.png?v=1&utm_medium=x'>");
+        assert_eq!(
+            "https://example.com/i.png?v=1",
+            html.favicons_with(&base, &policy)
+                .best(16)
+                .unwrap()
+                .url
+                .as_str()
+        );
+        let html = Html::parse_document("<p>No icons</p>");
+        assert_eq!(
+            "https://example.com/favicon.ico",
+            html.favicons_with(&base, &policy)
+                .best(16)
+                .unwrap()
+                .url
+                .as_str()
+        );
+    }
+}
@@ -0,0 +1,143 @@
+//! Discovery of site-level resources linked from a document.
+
+mod favicons;
+
+pub use self::favicons::{Favicon, Favicons, IconFormat, IconSource};
+
+use crate::html::Html;
+use crate::selector::Selector;
+
+lazy_static! {
+    static ref LINK_OR_META: Selector = Selector::parse("link[href], meta[content]").unwrap();
+}
+
+/// The kind of a discovered manifest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ManifestKind {
+    /// An OpenSearch description document.
+    OpenSearch,
+    /// A web app (PWA) manifest.
+    WebAppManifest,
+    /// A Microsoft `browserconfig.xml` file.
+    BrowserConfig,
+}
+
+/// A link to a manifest file, as written in the document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestLink {
+    /// What the file describes.
+    pub kind: ManifestKind,
+    /// The unresolved URL.
+    pub href: String,
+    /// The `title` attribute, which names OpenSearch engines.
+    pub title: Option<String>,
+    /// The declared MIME type, if any.
+    pub mime: Option<String>,
+}
+
+/// Returns true if the space-separated `rel` list contains `token`, ignoring ASCII case.
+pub(crate) fn has_rel(rel: &str, token: &str) -> bool {
+    rel.split_ascii_whitespace()
+        .any(|t| t.eq_ignore_ascii_case(token))
+}
+
+fn classify(name: &str, rel: &str, mime: Option<&str>) -> Option<ManifestKind> {
+    let mime = mime.map(|m| m.trim().to_ascii_lowercase());
+    match name {
+        "link" if has_rel(rel, "manifest") => Some(ManifestKind::WebAppManifest),
+        "link"
+            if has_rel(rel, "search")
+                && mime.as_deref() == Some("application/opensearchdescription+xml") =>
+        {
+            Some(ManifestKind::OpenSearch)
+        }
+        _ => None,
+    }
+}
+
+impl Html {
+    /// Returns the OpenSearch descriptors, web app manifests and browserconfig files the
+    /// document links to, in document order and without duplicates.
+    ///
+    /// `<meta name="msapplication-config" content="none">`, which opts out of the default
+    /// `/browserconfig.xml` lookup, is not reported.
+    ///
+    /// ```
+    /// use scraper::discovery::ManifestKind;
+    /// use scraper::Html;
+    ///
+    /// let html = Html::parse_document(r#"
+    ///     <link rel="manifest" href="/site.webmanifest">
+    ///     <link rel="search" type="application/opensearchdescription+xml" title="Docs" href="/os.xml">
+    /// "#);
+    /// let kinds: Vec<_> = html.discover_manifests().into_iter().map(|m| m.kind).collect();
+    /// assert_eq!(vec![ManifestKind::WebAppManifest, ManifestKind::OpenSearch], kinds);
+    /// ```
+    pub fn discover_manifests(&self) -> Vec<ManifestLink> {
+        let mut found: Vec<ManifestLink> = Vec::new();
+
+        for element in self.select(&LINK_OR_META) {
+            let value = element.value();
+            let mime = value.attr("type");
+            let (kind, href) = if value.name() == "meta" {
+                let is_config = value
+                    .attr("name")
+                    .is_some_and(|n| n.trim().eq_ignore_ascii_case("msapplication-config"));
+                let content = value.attr("content").unwrap_or_default().trim();
+                if !is_config || content.is_empty() || content.eq_ignore_ascii_case("none") {
+                    continue;
+                }
+                (ManifestKind::BrowserConfig, content)
+            } else {
+                let rel = value.attr("rel").unwrap_or_default();
+                match classify(value.name(), rel, mime) {
+                    Some(kind) => (kind, value.attr("href").unwrap_or_default().trim()),
+                    None => continue,
+                }
+            };
+
+            if href.is_empty() || found.iter().any(|m| m.kind == kind && m.href == href) {
+                continue;
+            }
+            found.push(ManifestLink {
+                kind,
+                href: href.to_owned(),
+                title: value.attr("title").map(str::to_owned),
+                mime: mime.map(str::to_owned),
+            });
+        }
+
+        found
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ManifestKind;
+    use crate::html::Html;
+
+    #[test]
+    fn discovers_each_kind_once() {
+        let html = Html::parse_document(
+            r#"
+            <link rel="Manifest" href=" /app.json ">
+            <link rel="manifest" href="/app.json">
+            <link rel="search" href="/feed.xml">
+            <link rel="search" type="application/opensearchdescription+xml" href="/os.xml" title="Site">
+            <meta name="msapplication-config" content="/ie/browserconfig.xml">
+            <meta name="msapplication-config" content="none">
+            "#,
+        );
+        let found = html.discover_manifests();
+        let summary: Vec<_> = found.iter().map(|m| (m.kind, m.href.as_str())).collect();
+        assert_eq!(
+            vec![
+                (ManifestKind::WebAppManifest, "/app.json"),
+                (ManifestKind::OpenSearch, "/os.xml"),
+                (ManifestKind::BrowserConfig, "/ie/browserconfig.xml"),
+            ],
+            summary
+        );
+        assert_eq!(Some("Site"), found[1].title.as_deref());
+    }
+}
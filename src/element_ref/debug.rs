@@ -0,0 +1,181 @@
+//! Indented outlines of element subtrees for debugging.
+
+use std::fmt::Write;
+
+use ego_tree::NodeRef;
+
+use super::ElementRef;
+use crate::html::graph::node_label;
+use crate::html::{GraphOptions, NodeLabel};
+use crate::node::Node;
+use crate::selector::Selector;
+
+impl<'a> ElementRef<'a> {
+    /// Returns an indented outline of this element's subtree for logs.
+    ///
+    /// Elements are shown as `tag#id.class`, text nodes as quoted strings cut to `max_text`
+    /// characters, and whitespace-only text is skipped. Elements at `max_depth` show how many
+    /// descendants were left out instead of their children.
+    ///
+    /// ```
+    /// use scraper::{Html, Selector};
+    ///
+    /// let html = Html::parse_fragment(r#"<ul id="menu"><li>Home</li><li><a>About us</a></li></ul>"#);
+    /// let ul = html.select(&Selector::parse("ul").unwrap()).next().unwrap();
+    ///
+    /// assert_eq!(
+    ///     "ul#menu\n  li\n    \"Home\"\n  li\n    a (+1)\n",
+    ///     ul.debug_tree(2, 10)
+    /// );
+    /// ```
+    pub fn debug_tree(&self, max_depth: usize, max_text: usize) -> String {
+        self.tree_view(&TreeView {
+            max_depth: Some(max_depth),
+            max_text,
+            ..TreeView::default()
+        })
+    }
+
+    /// Returns an outline of this element's subtree, like [`debug_tree`](Self::debug_tree),
+    /// optionally colored with ANSI escapes and with the elements matching a selector marked.
+    pub fn tree_view(&self, view: &TreeView) -> String {
+        let mut out = String::new();
+        outline(**self, 0, view, &mut out);
+        out
+    }
+}
+
+/// Options for [`ElementRef::tree_view`].
+#[derive(Debug, Clone)]
+pub struct TreeView<'s> {
+    /// Elements at this depth show a count instead of their children.
+    pub max_depth: Option<usize>,
+    /// Text nodes are cut to this many characters.
+    pub max_text: usize,
+    /// Color tags, ids, classes and text with ANSI escapes.
+    pub color: bool,
+    /// Mark elements matching this selector with `>` in the gutter.
+    pub highlight: Option<&'s Selector>,
+}
+
+impl Default for TreeView<'_> {
+    fn default() -> Self {
+        TreeView {
+            max_depth: None,
+            max_text: 40,
+            color: false,
+            highlight: None,
+        }
+    }
+}
+
+const RESET: &str = "\x1b[0m";
+const TAG: &str = "\x1b[34m";
+const ID: &str = "\x1b[33m";
+const CLASS: &str = "\x1b[32m";
+const DIM: &str = "\x1b[2m";
+const MATCH: &str = "\x1b[1;7;35m";
+
+fn paint(out: &mut String, color: bool, style: &str, text: &str) {
+    if color {
+        let _ = write!(out, "{}{}{}", style, text, RESET);
+    } else {
+        out.push_str(text);
+    }
+}
+
+fn outline(node: NodeRef<Node>, depth: usize, view: &TreeView, out: &mut String) {
+    let opts = GraphOptions {
+        max_depth: view.max_depth,
+        label: NodeLabel::Selector,
+        text: true,
+        max_text: view.max_text,
+    };
+    let Some(label) = node_label(node.value(), &opts) else {
+        return;
+    };
+
+    let matched = view.highlight.is_some_and(|selector| {
+        ElementRef::wrap(node).is_some_and(|element| selector.matches(&element))
+    });
+    if view.highlight.is_some() {
+        out.push_str(if matched { "> " } else { "  " });
+    }
+    let _ = write!(out, "{:indent$}", "", indent = depth * 2);
+
+    match node.value() {
+        Node::Text(_) => {
+            paint(out, view.color, DIM, &format!("{:?}", label));
+            out.push('\n');
+            return;
+        }
+        Node::Element(element) if view.color => {
+            paint(out, true, if matched { MATCH } else { TAG }, element.name());
+            if let Some(id) = element.id() {
+                paint(out, true, ID, &format!("#{}", id));
+            }
+            let mut classes: Vec<_> = element.classes().collect();
+            classes.sort_unstable();
+            for class in classes {
+                paint(out, true, CLASS, &format!(".{}", class));
+            }
+        }
+        _ => out.push_str(&label),
+    }
+
+    if view.max_depth.is_some_and(|max| depth >= max) {
+        let hidden = node
+            .descendants()
+            .skip(1)
+            .filter(|d| node_label(d.value(), &opts).is_some())
+            .count();
+        if hidden > 0 {
+            paint(out, view.color, DIM, &format!(" (+{})", hidden));
+        }
+        out.push('\n');
+        return;
+    }
+
+    out.push('\n');
+    for child in node.children() {
+        outline(child, depth + 1, view, out);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TreeView;
+    use crate::html::Html;
+    use crate::selector::Selector;
+
+    #[test]
+    fn truncates_text_and_skips_whitespace() {
+        let html = Html::parse_fragment("<div>\n  <p>a very long paragraph</p>\n</div>");
+        let div = html
+            .select(&Selector::parse("div").unwrap())
+            .next()
+            .unwrap();
+        assert_eq!("div\n  p\n    \"a very…\"\n", div.debug_tree(5, 6));
+    }
+
+    #[test]
+    fn highlights_matches() {
+        let html = Html::parse_fragment(r#"<ul><li class="on">a</li><li>b</li></ul>"#);
+        let ul = html.select(&Selector::parse("ul").unwrap()).next().unwrap();
+        let selector = Selector::parse(".on").unwrap();
+        let view = TreeView {
+            highlight: Some(&selector),
+            ..TreeView::default()
+        };
+        assert_eq!(
+            "  ul\n>   li.on\n      \"a\"\n    li\n      \"b\"\n",
+            ul.tree_view(&view)
+        );
+
+        let colored = ul.tree_view(&TreeView {
+            color: true,
+            ..view
+        });
+        assert!(colored.contains("\x1b[1;7;35mli\x1b[0m\x1b[32m.on\x1b[0m"));
+    }
+}
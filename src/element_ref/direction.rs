@@ -0,0 +1,164 @@
+use super::ElementRef;
+use crate::node::Node;
+
+/// The directionality of an element's text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    /// Left to right.
+    Ltr,
+    /// Right to left, as in Arabic and Hebrew.
+    Rtl,
+}
+
+impl Direction {
+    /// Returns the direction of the first strongly directional character of `text`, the
+    /// way a plain-text consumer guesses paragraph direction.
+    pub fn of_text(text: &str) -> Option<Direction> {
+        text.chars().find_map(strong_direction)
+    }
+
+    /// Returns the invisible mark that forces this direction: U+200E LEFT-TO-RIGHT MARK or
+    /// U+200F RIGHT-TO-LEFT MARK.
+    pub fn mark(self) -> char {
+        match self {
+            Direction::Ltr => '\u{200E}',
+            Direction::Rtl => '\u{200F}',
+        }
+    }
+}
+
+/// Returns the direction of a strongly directional character.
+fn strong_direction(c: char) -> Option<Direction> {
+    match c {
+        '\u{200E}' => Some(Direction::Ltr),
+        '\u{200F}' | '\u{061C}' => Some(Direction::Rtl),
+        // Hebrew, Arabic, Syriac, Thaana, NKo, Samaritan, Mandaic and their extensions and
+        // presentation forms.
+        '\u{0590}'..='\u{08FF}'
+        | '\u{FB1D}'..='\u{FDFF}'
+        | '\u{FE70}'..='\u{FEFF}'
+        | '\u{10800}'..='\u{10FFF}'
+        | '\u{1E800}'..='\u{1EFFF}' => c.is_alphabetic().then_some(Direction::Rtl),
+        c if c.is_alphabetic() => Some(Direction::Ltr),
+        _ => None,
+    }
+}
+
+/// Returns the explicit direction of a `dir` attribute value, or `None` for `auto` and
+/// invalid values.
+fn explicit(dir: &str) -> Option<Direction> {
+    if dir.eq_ignore_ascii_case("ltr") {
+        Some(Direction::Ltr)
+    } else if dir.eq_ignore_ascii_case("rtl") {
+        Some(Direction::Rtl)
+    } else {
+        None
+    }
+}
+
+impl<'a> ElementRef<'a> {
+    /// Resolves the directionality of the element from its own or its nearest ancestor's
+    /// `dir` attribute.
+    ///
+    /// `dir="auto"`, and `bdi` elements without `dir`, take the direction of the first
+    /// strongly directional character of their text, skipping scripts, styles and
+    /// descendants with their own `dir`; without one they inherit. Elements without any
+    /// `dir` in scope are left to right.
+    ///
+    /// ```
+    /// use scraper::element_ref::Direction;
+    /// use scraper::{Html, Selector};
+    ///
+    /// let html = Html::parse_document(
+    ///     r#"<html dir="rtl"><p>مرحبا</p><p dir="auto">2024: Rust</p></html>"#,
+    /// );
+    /// let p = Selector::parse("p").unwrap();
+    /// let dirs: Vec<_> = html.select(&p).map(|p| p.direction()).collect();
+    /// assert_eq!(vec![Direction::Rtl, Direction::Ltr], dirs);
+    /// ```
+    pub fn direction(&self) -> Direction {
+        let mut current = Some(*self);
+        while let Some(element) = current {
+            let value = element.value();
+            let dir = value.attr("dir").map(str::trim);
+            if let Some(direction) = dir.and_then(explicit) {
+                return direction;
+            }
+            let auto = match dir {
+                Some(dir) => dir.eq_ignore_ascii_case("auto"),
+                None => value.name() == "bdi",
+            };
+            if auto {
+                if let Some(direction) = element.auto_direction() {
+                    return direction;
+                }
+            }
+            current = element.parent().and_then(ElementRef::wrap);
+        }
+        Direction::Ltr
+    }
+
+    /// Returns the direction of the first strongly directional character of the text,
+    /// skipping content that does not contribute to `dir="auto"`.
+    fn auto_direction(&self) -> Option<Direction> {
+        let mut stack: Vec<_> = self.children().collect();
+        stack.reverse();
+        while let Some(node) = stack.pop() {
+            match node.value() {
+                Node::Text(text) => {
+                    if let Some(direction) = Direction::of_text(text) {
+                        return Some(direction);
+                    }
+                }
+                Node::Element(element) => {
+                    let skipped = matches!(element.name(), "bdi" | "script" | "style" | "textarea")
+                        || element.attr("dir").is_some_and(|d| {
+                            explicit(d.trim()).is_some() || d.trim().eq_ignore_ascii_case("auto")
+                        });
+                    if !skipped {
+                        let start = stack.len();
+                        stack.extend(node.children());
+                        stack[start..].reverse();
+                    }
+                }
+                _ => {}
+            }
+        }
+        None
+    }
+}
+
+/// Returns the mark to prefix a block of text with so that a plain-text consumer, which
+/// guesses direction from the first strong character, shows it in `direction`.
+pub(crate) fn bidi_mark(direction: Direction, text: &str) -> Option<char> {
+    let guessed = Direction::of_text(text).unwrap_or(Direction::Ltr);
+    (guessed != direction).then(|| direction.mark())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Direction;
+    use crate::html::Html;
+    use crate::selector::Selector;
+
+    #[test]
+    fn auto_skips_isolated_content_and_inherits() {
+        let html = Html::parse_fragment(
+            r#"<div dir="rtl">
+                <p id="a" dir="auto"><span dir="ltr">Hello</span> שלום</p>
+                <p id="b" dir="auto">123</p>
+                <bdi id="c">Rust</bdi>
+                <p id="d" dir="LTR"><b id="e">x</b></p>
+            </div>"#,
+        );
+        let dir = |id: &str| {
+            let selector = Selector::parse(&format!("#{}", id)).unwrap();
+            html.select(&selector).next().unwrap().direction()
+        };
+        assert_eq!(Direction::Rtl, dir("a"));
+        assert_eq!(Direction::Rtl, dir("b"));
+        assert_eq!(Direction::Ltr, dir("c"));
+        assert_eq!(Direction::Ltr, dir("e"));
+        assert_eq!(Direction::Ltr, html.root_element().direction());
+    }
+}
@@ -27,7 +27,9 @@ impl<'a> Element for ElementRef<'a> {
     }
 
     fn first_element_child(&self) -> Option<Self> {
-        self.prev_siblings().nth(0).and_then(ElementRef::wrap)
+        self.children()
+            .find(|child| child.value().is_element())
+            .map(ElementRef::new)
     }
 
     fn apply_selector_flags(&self, _: selectors::matching::ElementSelectorFlags) {
@@ -131,7 +133,7 @@ impl<'a> Element for ElementRef<'a> {
 
     fn is_root(&self) -> bool {
         self.parent()
-            .map_or(false, |parent| parent.value().is_document())
+            .is_some_and(|parent| parent.value().is_document())
     }
 }
 
@@ -1,15 +1,22 @@
 //! Element references.
 
+use std::io;
 use std::ops::Deref;
 
 use ego_tree::iter::{Edge, Traverse};
 use ego_tree::NodeRef;
 use html5ever::serialize::{serialize, SerializeOpts, TraversalScope};
 
+pub(crate) use self::direction::bidi_mark;
+use crate::error::UniqueError;
+use crate::html::with_scratch;
 use crate::node::Element;
 use crate::node::Node;
 use crate::selector::Selector;
 
+pub use self::debug::TreeView;
+pub use self::direction::Direction;
+
 /// Wrapper around a reference to an element node.
 ///
 /// This wrapper implements the `Element` trait from the `selectors` crate, which allows it to be
@@ -52,16 +59,56 @@ impl<'a> ElementRef<'a> {
         }
     }
 
+    /// Returns the first descendent element matching a selector, the same element as
+    /// `select(selector).next()`.
+    pub fn select_first(&self, selector: &Selector) -> Option<ElementRef<'a>> {
+        self.descendants()
+            .skip(1)
+            .filter_map(ElementRef::wrap)
+            .find(|element| selector.matches_with_scope(element, Some(*self)))
+    }
+
+    /// Returns the only descendant element matching a selector, or an error with the
+    /// number of matches if there are none or several.
+    pub fn select_unique(&self, selector: &Selector) -> Result<ElementRef<'a>, UniqueError> {
+        crate::html::unique(self.select(selector))
+    }
+
     fn serialize(&self, traversal_scope: TraversalScope) -> String {
+        with_scratch(|buf| {
+            let _ = self.write(&mut *buf, traversal_scope);
+            // we need to get the initial encoding of the html lang if used.
+            auto_encoder::auto_encode_bytes(buf)
+        })
+    }
+
+    fn write<W: io::Write>(&self, writer: W, traversal_scope: TraversalScope) -> io::Result<()> {
         let opts = SerializeOpts {
             scripting_enabled: false, // It's not clear what this does.
             traversal_scope,
             create_missing_parent: false,
         };
-        let mut buf = Vec::new();
-        let _ = serialize(&mut buf, self, opts);
-        // we need to get the initial encoding of the html lang if used.
-        auto_encoder::auto_encode_bytes(&buf)
+        serialize(writer, self, opts)
+    }
+
+    /// Serializes this element with the given html5ever options, as
+    /// [`Html::serialize_with`](crate::Html::serialize_with) does.
+    pub fn serialize_with(&self, opts: &SerializeOpts) -> String {
+        with_scratch(|buf| {
+            let _ = serialize(&mut *buf, self, opts.clone());
+            auto_encoder::auto_encode_bytes(buf)
+        })
+    }
+
+    /// Serializes the HTML of this element as UTF-8 into a writer, without buffering it.
+    pub fn write_html<W: io::Write>(&self, writer: W) -> io::Result<()> {
+        self.write(writer, TraversalScope::IncludeNode)
+    }
+
+    /// Serializes the inner HTML of this element as UTF-8 into a writer, without buffering
+    /// it.
+    pub fn write_inner_html<W: io::Write>(&self, writer: W) -> io::Result<()> {
+        self.write(writer, TraversalScope::ChildrenOnly(None))
     }
 
     /// Returns the HTML of this element.
@@ -69,11 +116,73 @@ impl<'a> ElementRef<'a> {
         self.serialize(TraversalScope::IncludeNode)
     }
 
+    /// Returns the HTML of this element with one block-level element per line, as
+    /// [`Html::html_pretty`](crate::Html::html_pretty) writes it.
+    pub fn html_pretty(&self, indent: usize) -> String {
+        crate::node::pretty::pretty(**self, indent, true)
+    }
+
     /// Returns the inner HTML of this element.
     pub fn inner_html(&self) -> String {
         self.serialize(TraversalScope::ChildrenOnly(None))
     }
 
+    /// Returns the HTML of this element as the UTF-8 the serializer writes, without the
+    /// encoding detection [`ElementRef::html`] runs on it. For hot extraction paths over
+    /// documents known to be UTF-8, such as those parsed from strings or with
+    /// [`Html::parse_document_utf8`](crate::Html::parse_document_utf8).
+    pub fn html_utf8(&self) -> String {
+        let mut html = String::new();
+        self.html_into(&mut html);
+        html
+    }
+
+    /// Returns the inner HTML of this element without encoding detection, as
+    /// [`ElementRef::html_utf8`] does.
+    ///
+    /// ```
+    /// use scraper::{Html, Selector};
+    ///
+    /// let html = Html::parse_document_utf8("<meta charset=shift_jis><p>é<b>ü</b></p>".as_bytes());
+    /// let p = html.select_first(&Selector::parse("p").unwrap()).unwrap();
+    /// assert_eq!("é<b>ü</b>", p.inner_html_utf8());
+    /// ```
+    pub fn inner_html_utf8(&self) -> String {
+        let mut html = String::new();
+        self.inner_html_into(&mut html);
+        html
+    }
+
+    /// Appends the HTML of this element to `out`, as [`ElementRef::html_utf8`] returns it.
+    /// Reusing one string across the elements of a loop, and the serialization buffer this
+    /// thread keeps, avoids allocating per element.
+    ///
+    /// ```
+    /// use scraper::{Html, Selector};
+    ///
+    /// let html = Html::parse_fragment("<ul><li>A</li><li>B</li></ul>");
+    /// let mut out = String::new();
+    /// for li in html.select(&Selector::parse("li").unwrap()) {
+    ///     out.clear();
+    ///     li.html_into(&mut out);
+    ///     assert!(out.starts_with("<li>"));
+    /// }
+    /// ```
+    pub fn html_into(&self, out: &mut String) {
+        with_scratch(|buf| {
+            let _ = self.write_html(&mut *buf);
+            out.push_str(&String::from_utf8_lossy(buf));
+        })
+    }
+
+    /// Appends the inner HTML of this element to `out`, as [`ElementRef::html_into`] does.
+    pub fn inner_html_into(&self, out: &mut String) {
+        with_scratch(|buf| {
+            let _ = self.write_inner_html(&mut *buf);
+            out.push_str(&String::from_utf8_lossy(buf));
+        })
+    }
+
     /// Returns the value of an attribute.
     pub fn attr(&self, attr: &str) -> Option<&str> {
         self.value().attr(attr)
@@ -160,11 +269,15 @@ impl<'a> Iterator for Text<'a> {
     }
 }
 
+mod debug;
+mod direction;
 mod element;
+mod path;
 mod serializable;
 
 #[cfg(test)]
 mod tests {
+    use crate::error::UniqueError;
     use crate::html::Html;
     use crate::selector::Selector;
 
@@ -186,6 +299,16 @@ mod tests {
         let element1 = fragment.select(&sel1).next().unwrap();
         let element2 = element1.select(&sel2).next().unwrap();
         assert_eq!(element2.inner_html(), "3");
+        assert_eq!(element1.select_first(&sel2), Some(element2));
+        assert_eq!(element2.select_first(&sel2), None);
+        assert_eq!(element1.select_unique(&sel2), Ok(element2));
+        let b = Selector::parse("b").unwrap();
+        assert_eq!(element1.select_unique(&b), Err(UniqueError::Multiple(2)));
+        assert_eq!(element2.select_unique(&b), Err(UniqueError::NoMatch));
+
+        let mut out = Vec::new();
+        element1.write_inner_html(&mut out).unwrap();
+        assert_eq!(element1.inner_html().as_bytes(), &out[..]);
     }
 
     #[test]
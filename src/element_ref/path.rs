@@ -0,0 +1,91 @@
+use cssparser::serialize_identifier;
+
+use super::ElementRef;
+
+impl<'a> ElementRef<'a> {
+    /// Returns a CSS selector path that matches this element, such as
+    /// `html > body > div:nth-of-type(2) > p`.
+    ///
+    /// The path starts at the nearest ancestor (or self) with an `id` that no other element
+    /// of the document shares, and `:nth-of-type` is only added where a sibling shares the
+    /// tag name.
+    ///
+    /// ```
+    /// use scraper::{Html, Selector};
+    ///
+    /// let html = Html::parse_document(r#"<div id="main"><p>a</p><p>b</p></div>"#);
+    /// let selector = Selector::parse("p:last-child").unwrap();
+    /// let p = html.select(&selector).next().unwrap();
+    /// assert_eq!("#main > p:nth-of-type(2)", p.css_path());
+    /// ```
+    pub fn css_path(&self) -> String {
+        let root = self.ancestors().last().unwrap_or(**self);
+        let unique = |id: &str| {
+            root.descendants()
+                .filter_map(|node| node.value().as_element())
+                .filter(|element| element.id() == Some(id))
+                .nth(1)
+                .is_none()
+        };
+        let mut parts = Vec::new();
+        let mut current = Some(*self);
+
+        while let Some(element) = current {
+            let value = element.value();
+            if let Some(id) = value.id().filter(|id| !id.is_empty() && unique(id)) {
+                let mut part = String::from("#");
+                let _ = serialize_identifier(id, &mut part);
+                parts.push(part);
+                break;
+            }
+
+            let name = value.name();
+            let same_tag = |sibling: &ElementRef| sibling.value().name() == name;
+            let before = element
+                .prev_siblings()
+                .filter_map(ElementRef::wrap)
+                .filter(same_tag)
+                .count();
+            let after = element
+                .next_siblings()
+                .filter_map(ElementRef::wrap)
+                .any(|s| same_tag(&s));
+
+            if before > 0 || after {
+                parts.push(format!("{}:nth-of-type({})", name, before + 1));
+            } else {
+                parts.push(name.to_owned());
+            }
+            current = element.parent().and_then(ElementRef::wrap);
+        }
+
+        parts.reverse();
+        parts.join(" > ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::html::Html;
+    use crate::selector::Selector;
+
+    #[test]
+    fn paths_select_the_element() {
+        let html = Html::parse_document(
+            r#"<ul><li>a</li><li><span>b</span><b id="1x">c</b></li></ul><ul><li>d</li></ul>
+            <div id="a"><p>e</p></div><div id="a"><p>f</p></div>"#,
+        );
+        let all = Selector::parse("*").unwrap();
+        for element in html.select(&all) {
+            let path = element.css_path();
+            let selector = Selector::parse(&path).unwrap();
+            let found: Vec<_> = html.select(&selector).collect();
+            assert_eq!(vec![element], found, "{}", path);
+        }
+
+        let b = html.select(&Selector::parse("b").unwrap()).next().unwrap();
+        assert_eq!("#\\31 x", b.css_path());
+        let f = html.select(&Selector::parse("p").unwrap()).nth(1).unwrap();
+        assert_eq!("html > body > div:nth-of-type(2) > p", f.css_path());
+    }
+}
@@ -3,6 +3,7 @@
 
 use cssparser::{BasicParseErrorKind, ParseErrorKind, Token};
 use selectors::parser::SelectorParseErrorKind;
+use std::fmt;
 
 /// Error type that is returned when calling `Selector::parse`
 #[derive(Debug, Clone)]
@@ -68,3 +69,317 @@ impl<'a> From<SelectorParseErrorKind<'a>> for SelectorErrorKind<'a> {
         }
     }
 }
+
+/// Error type that is returned when building or loading a `RulePack`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RuleError {
+    /// A rule selector could not be parsed
+    InvalidSelector {
+        /// The name of the offending rule
+        rule: String,
+        /// The selector source
+        selector: String,
+    },
+
+    /// The pack declares a format version newer than this crate understands
+    UnsupportedFormat(u32),
+
+    /// Two rules in the same pack share a name
+    DuplicateRule(String),
+
+    /// A fallback chain was created without any source
+    NoSources(String),
+
+    /// A rule validator is malformed, such as a regular expression that does not compile
+    InvalidValidator {
+        /// The name of the offending rule
+        rule: String,
+        /// What is wrong with the validator
+        reason: String,
+    },
+
+    /// The pack source could not be decoded
+    Decode(String),
+}
+
+impl fmt::Display for RuleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidSelector { rule, selector } => {
+                write!(f, "rule {:?} has an invalid selector {:?}", rule, selector)
+            }
+            Self::UnsupportedFormat(v) => write!(f, "unsupported rule pack format {}", v),
+            Self::DuplicateRule(name) => write!(f, "duplicate rule {:?}", name),
+            Self::NoSources(name) => write!(f, "rule {:?} has no sources", name),
+            Self::InvalidValidator { rule, reason } => {
+                write!(f, "rule {:?} has an invalid validator: {}", rule, reason)
+            }
+            Self::Decode(msg) => write!(f, "failed to decode rule pack: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for RuleError {}
+
+/// Error type that is returned when recording or restoring standoff annotations
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AnnotationError {
+    /// The range is out of bounds or does not fall on character boundaries
+    InvalidRange {
+        /// The start offset
+        start: usize,
+        /// The end offset
+        end: usize,
+    },
+
+    /// The annotations were recorded against a different text
+    TextMismatch,
+
+    /// The annotation layer could not be decoded
+    Decode(String),
+}
+
+impl fmt::Display for AnnotationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidRange { start, end } => write!(f, "invalid text range {}..{}", start, end),
+            Self::TextMismatch => write!(f, "annotations do not match the document text"),
+            Self::Decode(msg) => write!(f, "failed to decode annotations: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for AnnotationError {}
+
+/// Error type that is returned when importing a Chrome DevTools Protocol DOM payload
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CdpError {
+    /// The payload is neither a `DOM.getDocument` nor a `DOMSnapshot.captureSnapshot` result
+    UnknownPayload,
+
+    /// A node refers to a parent or string that does not exist
+    InvalidIndex(usize),
+
+    /// The payload is not valid JSON
+    Decode(String),
+}
+
+impl fmt::Display for CdpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownPayload => write!(f, "unrecognized DevTools DOM payload"),
+            Self::InvalidIndex(i) => write!(f, "invalid node or string index {}", i),
+            Self::Decode(msg) => write!(f, "failed to decode DevTools payload: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for CdpError {}
+
+/// Error type that is returned when attaching a map of layout boxes to a document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LayoutError {
+    /// The map is not valid JSON
+    Decode(String),
+
+    /// The JSON is not an object mapping keys to boxes
+    NotAMap,
+
+    /// The box for a key is neither `[x, y, width, height]` nor an object with those fields
+    InvalidBox(String),
+}
+
+impl fmt::Display for LayoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Decode(msg) => write!(f, "failed to decode layout map: {}", msg),
+            Self::NotAMap => write!(f, "layout map is not a JSON object"),
+            Self::InvalidBox(key) => write!(f, "invalid layout box for {:?}", key),
+        }
+    }
+}
+
+impl std::error::Error for LayoutError {}
+
+/// Error type that is returned when reading a document out of a WARC record
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WarcError {
+    /// The input does not start with a WARC record header
+    NotWarc,
+
+    /// The record is shorter than its `Content-Length`
+    Truncated,
+
+    /// The record is neither a `response` nor a `resource` record
+    UnsupportedRecord(String),
+
+    /// The response record does not hold an HTTP response
+    NotHttp,
+
+    /// The payload uses a content coding that cannot be decoded
+    UnsupportedEncoding(String),
+
+    /// The payload could not be decoded
+    Decode(String),
+}
+
+impl fmt::Display for WarcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotWarc => write!(f, "not a WARC record"),
+            Self::Truncated => write!(f, "truncated WARC record"),
+            Self::UnsupportedRecord(kind) => write!(f, "unsupported WARC record type {:?}", kind),
+            Self::NotHttp => write!(f, "WARC response record without an HTTP response"),
+            Self::UnsupportedEncoding(coding) => {
+                write!(f, "unsupported content encoding {:?}", coding)
+            }
+            Self::Decode(msg) => write!(f, "failed to decode WARC payload: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for WarcError {}
+
+/// Error type that is returned when loading an MHTML archive
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MhtmlError {
+    /// The archive is multipart but declares no boundary
+    MissingBoundary,
+
+    /// No part of the archive is an HTML document
+    NoDocument,
+
+    /// A part could not be decoded
+    Decode(String),
+}
+
+impl fmt::Display for MhtmlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingBoundary => write!(f, "multipart archive without a boundary"),
+            Self::NoDocument => write!(f, "archive without an HTML document"),
+            Self::Decode(msg) => write!(f, "failed to decode archive part: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for MhtmlError {}
+
+/// Error type that is returned when a rewriter handler is registered for a selector that a
+/// streaming pass cannot match
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RewriteError {
+    /// The selector needs content after the start tag, such as `:last-child`, with the
+    /// selector source
+    Unstreamable(String),
+}
+
+impl fmt::Display for RewriteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unstreamable(selector) => write!(
+                f,
+                "selector {:?} needs content after the start tag and cannot be streamed",
+                selector
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RewriteError {}
+
+/// Error type that is returned when a selector that should match exactly one element does not
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UniqueError {
+    /// No element matches
+    NoMatch,
+
+    /// More than one element matches, with the number of matches
+    Multiple(usize),
+}
+
+impl fmt::Display for UniqueError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoMatch => write!(f, "no element matches the selector"),
+            Self::Multiple(count) => {
+                write!(f, "{} elements match the selector, expected one", count)
+            }
+        }
+    }
+}
+
+impl std::error::Error for UniqueError {}
+
+/// Error type that is returned when importing a document from its JSON DOM form
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DomJsonError {
+    /// A node is not an object with a `tag`, `text`, `comment` or `doctype` field, with its
+    /// JSON pointer
+    InvalidNode(String),
+
+    /// The input is not valid JSON
+    Decode(String),
+}
+
+impl fmt::Display for DomJsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidNode(pointer) => write!(f, "invalid DOM node at {:?}", pointer),
+            Self::Decode(msg) => write!(f, "failed to decode DOM JSON: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for DomJsonError {}
+
+/// Error type that is returned when extracting a struct with [`FromHtml`](crate::extract::FromHtml)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExtractError {
+    /// A field selector could not be parsed
+    InvalidSelector {
+        /// The name of the offending field
+        field: String,
+        /// The selector source
+        selector: String,
+    },
+
+    /// A required field matched no element, or its element had no such value
+    Missing(String),
+
+    /// A field value could not be converted to the field type
+    Parse {
+        /// The name of the offending field
+        field: String,
+        /// The extracted value
+        value: String,
+        /// Why the conversion failed
+        reason: String,
+    },
+}
+
+impl fmt::Display for ExtractError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidSelector { field, selector } => {
+                write!(
+                    f,
+                    "field {:?} has an invalid selector {:?}",
+                    field, selector
+                )
+            }
+            Self::Missing(field) => write!(f, "no value for field {:?}", field),
+            Self::Parse {
+                field,
+                value,
+                reason,
+            } => write!(
+                f,
+                "invalid value {:?} for field {:?}: {}",
+                value, field, reason
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ExtractError {}
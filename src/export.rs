@@ -0,0 +1,414 @@
+//! Packaging of an extracted article as a standalone HTML file or a minimal EPUB book.
+//!
+//! Both formats take the content of the document's `<body>` as the article, so they are
+//! meant for documents already reduced to the article, and the title, author, date and
+//! source from a [`PageMetadata`], usually the [`Html::metadata`] of the original page.
+//! Scripts, frames, plugins, event handler attributes and URL attributes with a
+//! `javascript:` or other unknown scheme are left out. Images keep their URLs; nothing is
+//! downloaded.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use ego_tree::NodeRef;
+
+use crate::html::Html;
+use crate::metadata::PageMetadata;
+use crate::node::Node;
+use crate::sanitize::Policy;
+use crate::security::URL_ATTRIBUTES;
+use crate::selector::Selector;
+
+lazy_static! {
+    static ref BODY: Selector = Selector::parse("body").unwrap();
+    /// The URL schemes kept in URL attributes; relative URLs are always kept.
+    static ref URLS: Policy =
+        Policy::new().allow_url_schemes(&["http", "https", "ftp", "mailto", "tel", "data"]);
+}
+
+/// The stylesheet of both formats, a readable single column.
+const STYLESHEET: &str = "body { margin: 0 auto; max-width: 38em; padding: 1em; \
+font-family: Georgia, serif; font-size: 1.1em; line-height: 1.6; color: #222; }
+h1, h2, h3, h4, h5, h6 { line-height: 1.25; }
+img, video, svg { max-width: 100%; height: auto; }
+pre { overflow-x: auto; white-space: pre-wrap; }
+blockquote { margin-left: 0; padding-left: 1em; border-left: 3px solid #ccc; color: #555; }
+.byline, .source { color: #666; font-size: 0.9em; }
+";
+
+/// Elements left out with their content.
+const DROPPED: &[&str] = &[
+    "script", "noscript", "template", "iframe", "frame", "frameset", "object", "embed", "applet",
+    "style", "link", "meta", "base",
+];
+
+/// Elements without content, written as `<br/>`.
+const VOID: &[&str] = &[
+    "area", "br", "col", "hr", "img", "input", "source", "track", "wbr",
+];
+
+fn escape(text: &str, attribute: bool, out: &mut String) {
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' if attribute => out.push_str("&quot;"),
+            // Control characters other than white space are not allowed in XML.
+            c if c.is_control() && !matches!(c, '\t' | '\n' | '\r') => {}
+            c => out.push(c),
+        }
+    }
+}
+
+/// Returns true if `name` can be written as an XML attribute name.
+fn is_xml_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+/// Writes a node and its descendants as XHTML, which HTML parsers read the same way.
+fn write_xhtml(node: NodeRef<Node>, out: &mut String) {
+    match node.value() {
+        Node::Text(text) => escape(text, false, out),
+        Node::Element(element) => {
+            let name = element.name();
+            if DROPPED.contains(&name) {
+                return;
+            }
+            out.push('<');
+            out.push_str(name);
+            let foreign_root = match &*element.name.ns {
+                "http://www.w3.org/2000/svg" | "http://www.w3.org/1998/Math/MathML" => node
+                    .parent()
+                    .and_then(|p| p.value().as_element().map(|e| e.name.ns != element.name.ns))
+                    .unwrap_or(true),
+                _ => false,
+            };
+            if foreign_root {
+                out.push_str(" xmlns=\"");
+                out.push_str(&element.name.ns);
+                out.push('"');
+            }
+            let mut attrs: Vec<_> = element
+                .attrs()
+                .filter(|(name, value)| {
+                    is_xml_name(name)
+                        && !name.starts_with("on")
+                        && *name != "xmlns"
+                        && (!URL_ATTRIBUTES.contains(name) || URLS.allows_url(value))
+                })
+                .collect();
+            attrs.sort_unstable();
+            for (name, value) in attrs {
+                out.push(' ');
+                out.push_str(name);
+                out.push_str("=\"");
+                escape(value, true, out);
+                out.push('"');
+            }
+            let foreign = element.name.ns != ns!(html);
+            if VOID.contains(&name) || (foreign && !node.has_children()) {
+                out.push_str("/>");
+                return;
+            }
+            out.push('>');
+            for child in node.children() {
+                write_xhtml(child, out);
+            }
+            out.push_str("</");
+            out.push_str(name);
+            out.push('>');
+        }
+        _ => {
+            for child in node.children() {
+                write_xhtml(child, out);
+            }
+        }
+    }
+}
+
+/// Returns the article markup, the content of the `<body>`.
+fn content(html: &Html) -> String {
+    let body = html
+        .select_first(&BODY)
+        .unwrap_or_else(|| html.root_element());
+    let mut out = String::new();
+    for child in body.children() {
+        write_xhtml(child, &mut out);
+    }
+    out
+}
+
+/// Returns the title, the `<header>` of the article and its `<footer>`.
+fn frame(metadata: &PageMetadata) -> (String, String, String) {
+    let mut title = String::new();
+    escape(
+        metadata.title.as_deref().unwrap_or("Untitled"),
+        false,
+        &mut title,
+    );
+
+    let mut header = format!("<header><h1>{}</h1>", title);
+    let byline: Vec<&str> = [&metadata.author, &metadata.published]
+        .into_iter()
+        .filter_map(|field| field.as_deref())
+        .collect();
+    if !byline.is_empty() {
+        header.push_str("<p class=\"byline\">");
+        escape(&byline.join(" · "), false, &mut header);
+        header.push_str("</p>");
+    }
+    header.push_str("</header>");
+
+    let mut footer = String::new();
+    if let Some(url) = &metadata.canonical {
+        footer.push_str("<footer><p class=\"source\">Source: <a href=\"");
+        escape(url, true, &mut footer);
+        footer.push_str("\">");
+        escape(url, false, &mut footer);
+        footer.push_str("</a></p></footer>");
+    }
+    (title, header, footer)
+}
+
+fn language(html: &Html) -> String {
+    let mut lang = String::new();
+    match html.get_lang().trim() {
+        "" => lang.push_str("und"),
+        tag => escape(tag, true, &mut lang),
+    }
+    lang
+}
+
+/// Formats a time as `CCYY-MM-DDThh:mm:ssZ`, the form of `dcterms:modified`.
+fn utc_timestamp(time: SystemTime) -> String {
+    let secs = time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    let (days, rem) = (secs / 86_400, secs % 86_400);
+    // Converts days since 1970-01-01 to a civil date, after Howard Hinnant's algorithm.
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        rem / 3_600,
+        rem % 3_600 / 60,
+        rem % 60
+    )
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+/// Writes uncompressed files into a ZIP archive, in order.
+fn zip(files: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut out = Vec::new();
+    let mut directory = Vec::new();
+    for (name, data) in files {
+        let offset = out.len() as u32;
+        let crc = crc32(data);
+        let size = data.len() as u32;
+        let name_len = name.len() as u16;
+        // Version 1.0, no flags, stored, 1980-01-01 00:00, then CRC and sizes.
+        let fields = |out: &mut Vec<u8>| {
+            out.extend_from_slice(&0u16.to_le_bytes());
+            out.extend_from_slice(&0u16.to_le_bytes());
+            out.extend_from_slice(&0u16.to_le_bytes());
+            out.extend_from_slice(&0x0021u16.to_le_bytes());
+            out.extend_from_slice(&crc.to_le_bytes());
+            out.extend_from_slice(&size.to_le_bytes());
+            out.extend_from_slice(&size.to_le_bytes());
+            out.extend_from_slice(&name_len.to_le_bytes());
+            out.extend_from_slice(&0u16.to_le_bytes());
+        };
+
+        out.extend_from_slice(&0x0403_4b50u32.to_le_bytes());
+        out.extend_from_slice(&10u16.to_le_bytes());
+        fields(&mut out);
+        out.extend_from_slice(name.as_bytes());
+        out.extend_from_slice(data);
+
+        directory.extend_from_slice(&0x0201_4b50u32.to_le_bytes());
+        directory.extend_from_slice(&10u16.to_le_bytes());
+        directory.extend_from_slice(&10u16.to_le_bytes());
+        fields(&mut directory);
+        // No comment, disk 0, no attributes, then the offset of the local header.
+        directory.extend_from_slice(&[0; 10]);
+        directory.extend_from_slice(&offset.to_le_bytes());
+        directory.extend_from_slice(name.as_bytes());
+    }
+
+    let directory_offset = out.len() as u32;
+    let count = files.len() as u16;
+    out.extend_from_slice(&directory);
+    out.extend_from_slice(&0x0605_4b50u32.to_le_bytes());
+    out.extend_from_slice(&[0; 4]);
+    out.extend_from_slice(&count.to_le_bytes());
+    out.extend_from_slice(&count.to_le_bytes());
+    out.extend_from_slice(&(directory.len() as u32).to_le_bytes());
+    out.extend_from_slice(&directory_offset.to_le_bytes());
+    out.extend_from_slice(&0u16.to_le_bytes());
+    out
+}
+
+impl Html {
+    /// Packages the article as one HTML file with an embedded stylesheet and a header
+    /// with the title and byline, for reading offline.
+    ///
+    /// ```
+    /// use scraper::metadata::PageMetadata;
+    /// use scraper::Html;
+    ///
+    /// let article = Html::parse_document("<p>Body <script>track()</script>text.</p>");
+    /// let metadata = PageMetadata {
+    ///     title: Some("A & B".into()),
+    ///     author: Some("Ada".into()),
+    ///     ..Default::default()
+    /// };
+    /// let file = article.to_standalone_html(&metadata);
+    /// assert!(file.contains("<h1>A &amp; B</h1><p class=\"byline\">Ada</p>"));
+    /// assert!(file.contains("<p>Body text.</p>"));
+    /// ```
+    pub fn to_standalone_html(&self, metadata: &PageMetadata) -> String {
+        let (title, header, footer) = frame(metadata);
+        format!(
+            "<!DOCTYPE html>\n<html lang=\"{}\">\n<head>\n<meta charset=\"utf-8\">\n\
+             <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+             <title>{}</title>\n<style>\n{}</style>\n</head>\n\
+             <body>\n<article>\n{}\n{}\n{}\n</article>\n</body>\n</html>\n",
+            language(self),
+            title,
+            STYLESHEET,
+            header,
+            content(self),
+            footer
+        )
+    }
+
+    /// Packages the article as an EPUB 3 book with a single chapter, as the bytes of the
+    /// `.epub` file.
+    ///
+    /// The book's identifier is the canonical URL when there is one, else derived from the
+    /// content, and its modification date is the current time.
+    pub fn to_epub(&self, metadata: &PageMetadata) -> Vec<u8> {
+        let (title, header, footer) = frame(metadata);
+        let lang = language(self);
+        let body = content(self);
+
+        let mut identifier = String::new();
+        match &metadata.canonical {
+            Some(url) => escape(url, false, &mut identifier),
+            None => identifier = format!("urn:scraper:{:08x}", crc32(body.as_bytes())),
+        }
+        let mut creator = String::new();
+        if let Some(author) = &metadata.author {
+            creator.push_str("<dc:creator>");
+            escape(author, false, &mut creator);
+            creator.push_str("</dc:creator>\n");
+        }
+
+        let container = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+            <container version=\"1.0\" xmlns=\"urn:oasis:names:tc:opendocument:xmlns:container\">\n\
+            <rootfiles><rootfile full-path=\"OEBPS/content.opf\" \
+            media-type=\"application/oebps-package+xml\"/></rootfiles>\n</container>\n";
+        let package = format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <package xmlns=\"http://www.idpf.org/2007/opf\" version=\"3.0\" unique-identifier=\"id\">\n\
+             <metadata xmlns:dc=\"http://purl.org/dc/elements/1.1/\">\n\
+             <dc:identifier id=\"id\">{}</dc:identifier>\n<dc:title>{}</dc:title>\n\
+             <dc:language>{}</dc:language>\n{}\
+             <meta property=\"dcterms:modified\">{}</meta>\n</metadata>\n\
+             <manifest>\n\
+             <item id=\"nav\" href=\"nav.xhtml\" media-type=\"application/xhtml+xml\" properties=\"nav\"/>\n\
+             <item id=\"article\" href=\"article.xhtml\" media-type=\"application/xhtml+xml\"/>\n\
+             <item id=\"style\" href=\"style.css\" media-type=\"text/css\"/>\n\
+             </manifest>\n<spine><itemref idref=\"article\"/></spine>\n</package>\n",
+            identifier,
+            title,
+            lang,
+            creator,
+            utc_timestamp(SystemTime::now())
+        );
+        let document = |title: &str, body: &str| {
+            format!(
+                "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<!DOCTYPE html>\n\
+                 <html xmlns=\"http://www.w3.org/1999/xhtml\" \
+                 xmlns:epub=\"http://www.idpf.org/2007/ops\" lang=\"{0}\" xml:lang=\"{0}\">\n\
+                 <head><meta charset=\"utf-8\"/><title>{1}</title>\
+                 <link rel=\"stylesheet\" href=\"style.css\"/></head>\n\
+                 <body>\n{2}\n</body>\n</html>\n",
+                lang, title, body
+            )
+        };
+        let nav = document(
+            &title,
+            &format!(
+                "<nav epub:type=\"toc\"><ol><li><a href=\"article.xhtml\">{}</a></li></ol></nav>",
+                title
+            ),
+        );
+        let article = document(
+            &title,
+            &format!("<article>\n{}\n{}\n{}\n</article>", header, body, footer),
+        );
+
+        // The `mimetype` file must come first and be stored uncompressed.
+        zip(&[
+            ("mimetype", b"application/epub+zip"),
+            ("META-INF/container.xml", container.as_bytes()),
+            ("OEBPS/content.opf", package.as_bytes()),
+            ("OEBPS/nav.xhtml", nav.as_bytes()),
+            ("OEBPS/article.xhtml", article.as_bytes()),
+            ("OEBPS/style.css", STYLESHEET.as_bytes()),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use super::{crc32, utc_timestamp};
+    use crate::html::Html;
+    use crate::metadata::PageMetadata;
+
+    #[test]
+    fn epub_container() {
+        assert_eq!(0xCBF4_3926, crc32(b"123456789"));
+        let leap = UNIX_EPOCH + Duration::from_secs(951_827_696);
+        assert_eq!("2000-02-29T12:34:56Z", utc_timestamp(leap));
+
+        let html = Html::parse_document(
+            "<p onclick='x()'>A<br>B</p><a href=' JavaScript:x()'>C</a><a href='/d'>D</a><svg viewBox='0 0 1 1'><circle r='1'/></svg>",
+        );
+        let epub = html.to_epub(&PageMetadata::default());
+        assert_eq!(b"PK\x03\x04", &epub[..4]);
+        assert_eq!(b"mimetypeapplication/epub+zip", &epub[30..58]);
+        let text = String::from_utf8_lossy(&epub);
+        assert!(text.contains(
+            "<p>A<br/>B</p><a>C</a><a href=\"/d\">D</a><svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 1 1\"><circle r=\"1\"/></svg>"
+        ));
+        assert!(text.contains("<dc:language>und</dc:language>"));
+    }
+}
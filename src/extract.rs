@@ -0,0 +1,256 @@
+//! Declarative extraction of structs from documents.
+//!
+//! A type implementing [`FromHtml`] is built from the elements its fields select. With the
+//! `derive` feature, `#[derive(FromHtml)]` generates the implementation from a
+//! `#[selector(...)]` attribute on every field:
+//!
+//! - `#[selector("h1.title")]` or `#[selector("h1.title", text)]` takes the trimmed text;
+//! - `html` and `inner_html` take the outer or inner HTML;
+//! - `attr = "href"` takes an attribute;
+//! - `nested` builds the field, itself a `FromHtml` type, from the matched element.
+//!
+//! A field of type `Option<T>` takes the first value if there is one and a `Vec<T>` every
+//! value; any other field requires a value. Values are converted with [`FromStr`], so
+//! numeric fields parse the extracted text. Selectors match descendants of the element
+//! being extracted, and are parsed once per field.
+
+use std::str::FromStr;
+
+use crate::element_ref::ElementRef;
+use crate::error::ExtractError;
+use crate::html::Html;
+
+#[cfg(feature = "derive")]
+pub use spider_scraper_derive::FromHtml;
+
+/// A type that can be extracted from an element and its descendants.
+pub trait FromHtml: Sized {
+    /// Extracts the value from an element.
+    fn from_element(element: ElementRef<'_>) -> Result<Self, ExtractError>;
+}
+
+impl Html {
+    /// Extracts a [`FromHtml`] type from the document.
+    ///
+    /// ```
+    /// # #[cfg(feature = "derive")]
+    /// # {
+    /// use scraper::extract::FromHtml;
+    /// use scraper::Html;
+    ///
+    /// #[derive(FromHtml)]
+    /// struct Product {
+    ///     #[selector("h1.title")]
+    ///     name: String,
+    ///     #[selector(".price", text)]
+    ///     price: f64,
+    ///     #[selector("a.vendor", attr = "href")]
+    ///     vendor: Option<String>,
+    ///     #[selector(".review", nested)]
+    ///     reviews: Vec<Review>,
+    /// }
+    ///
+    /// #[derive(FromHtml)]
+    /// struct Review {
+    ///     #[selector(".stars")]
+    ///     stars: u8,
+    /// }
+    ///
+    /// let html = Html::parse_document(
+    ///     r#"<h1 class="title"> Lamp </h1><span class="price">19.5</span>
+    ///        <div class="review"><b class="stars">4</b></div>
+    ///        <div class="review"><b class="stars">5</b></div>"#,
+    /// );
+    /// let product: Product = html.extract().unwrap();
+    /// assert_eq!("Lamp", product.name);
+    /// assert_eq!(19.5, product.price);
+    /// assert_eq!(None, product.vendor);
+    /// assert_eq!(vec![4, 5], product.reviews.iter().map(|r| r.stars).collect::<Vec<_>>());
+    /// # }
+    /// ```
+    pub fn extract<T: FromHtml>(&self) -> Result<T, ExtractError> {
+        T::from_element(self.root_element())
+    }
+}
+
+impl ElementRef<'_> {
+    /// Extracts a [`FromHtml`] type from the element, as [`Html::extract`] does.
+    pub fn extract<T: FromHtml>(&self) -> Result<T, ExtractError> {
+        T::from_element(*self)
+    }
+}
+
+/// Support for the code `#[derive(FromHtml)]` generates. Not part of the public API.
+#[doc(hidden)]
+pub mod __private {
+    use std::sync::OnceLock;
+
+    use super::*;
+    pub use crate::rules::Extract;
+    use crate::selector::Selector;
+
+    /// A field of a derived type, with its selector parsed on first use.
+    pub struct Field {
+        pub name: &'static str,
+        pub selector: &'static str,
+        pub parsed: OnceLock<Option<Selector>>,
+    }
+
+    impl Field {
+        pub const fn new(name: &'static str, selector: &'static str) -> Self {
+            Field {
+                name,
+                selector,
+                parsed: OnceLock::new(),
+            }
+        }
+
+        fn matches<'a>(
+            &self,
+            element: ElementRef<'a>,
+        ) -> Result<Vec<ElementRef<'a>>, ExtractError> {
+            let selector = self
+                .parsed
+                .get_or_init(|| Selector::parse(self.selector).ok())
+                .as_ref()
+                .ok_or_else(|| ExtractError::InvalidSelector {
+                    field: self.name.to_owned(),
+                    selector: self.selector.to_owned(),
+                })?;
+            Ok(element.select(selector).collect())
+        }
+
+        fn parse<T: FromStr>(&self, value: String) -> Result<T, ExtractError>
+        where
+            T::Err: std::fmt::Display,
+        {
+            value.parse().map_err(|err: T::Err| ExtractError::Parse {
+                field: self.name.to_owned(),
+                reason: err.to_string(),
+                value,
+            })
+        }
+
+        fn values<'a>(
+            &'a self,
+            element: ElementRef<'a>,
+            extract: &'a Extract,
+        ) -> Result<impl Iterator<Item = String> + 'a, ExtractError> {
+            Ok(self
+                .matches(element)?
+                .into_iter()
+                .filter_map(|e| extract.apply(&e)))
+        }
+
+        pub fn required<T: FromStr>(
+            &self,
+            element: ElementRef,
+            extract: Extract,
+        ) -> Result<T, ExtractError>
+        where
+            T::Err: std::fmt::Display,
+        {
+            self.optional(element, extract)?
+                .ok_or_else(|| ExtractError::Missing(self.name.to_owned()))
+        }
+
+        pub fn optional<T: FromStr>(
+            &self,
+            element: ElementRef,
+            extract: Extract,
+        ) -> Result<Option<T>, ExtractError>
+        where
+            T::Err: std::fmt::Display,
+        {
+            let value = self.values(element, &extract)?.next();
+            value.map(|value| self.parse(value)).transpose()
+        }
+
+        pub fn all<T: FromStr>(
+            &self,
+            element: ElementRef,
+            extract: Extract,
+        ) -> Result<Vec<T>, ExtractError>
+        where
+            T::Err: std::fmt::Display,
+        {
+            let values: Vec<String> = self.values(element, &extract)?.collect();
+            values.into_iter().map(|value| self.parse(value)).collect()
+        }
+
+        pub fn required_nested<T: FromHtml>(&self, element: ElementRef) -> Result<T, ExtractError> {
+            self.optional_nested(element)?
+                .ok_or_else(|| ExtractError::Missing(self.name.to_owned()))
+        }
+
+        pub fn optional_nested<T: FromHtml>(
+            &self,
+            element: ElementRef,
+        ) -> Result<Option<T>, ExtractError> {
+            let first = self.matches(element)?.into_iter().next();
+            first.map(T::from_element).transpose()
+        }
+
+        pub fn all_nested<T: FromHtml>(&self, element: ElementRef) -> Result<Vec<T>, ExtractError> {
+            self.matches(element)?
+                .into_iter()
+                .map(T::from_element)
+                .collect()
+        }
+    }
+}
+
+#[cfg(all(test, feature = "derive"))]
+mod tests {
+    use crate::error::ExtractError;
+    use crate::extract::FromHtml;
+    use crate::html::Html;
+
+    #[derive(Debug, FromHtml)]
+    struct Link {
+        #[selector("a", attr = "href")]
+        href: String,
+        #[selector("a", inner_html)]
+        label: String,
+    }
+
+    #[derive(Debug, FromHtml)]
+    struct Page {
+        #[selector("li", nested)]
+        links: Vec<Link>,
+        #[selector("h2")]
+        headings: Vec<String>,
+    }
+
+    #[derive(Debug, FromHtml)]
+    struct Broken {
+        #[selector("p[")]
+        _text: Option<String>,
+    }
+
+    #[test]
+    fn extracts_nested_fields_and_reports_errors() {
+        let html = Html::parse_fragment(
+            r#"<ul><li><a href="/a"><b>A</b></a></li><li><a href="/b">B</a></li></ul>"#,
+        );
+        let page: Page = html.extract().unwrap();
+        assert!(page.headings.is_empty());
+        let links: Vec<_> = page
+            .links
+            .iter()
+            .map(|l| (l.href.as_str(), l.label.as_str()))
+            .collect();
+        assert_eq!(vec![("/a", "<b>A</b>"), ("/b", "B")], links);
+
+        assert_eq!(
+            ExtractError::Missing("href".into()),
+            Html::parse_fragment("<a>x</a>")
+                .extract::<Link>()
+                .unwrap_err()
+        );
+        assert!(matches!(
+            html.extract::<Broken>(),
+            Err(ExtractError::InvalidSelector { .. })
+        ));
+    }
+}
@@ -0,0 +1,249 @@
+//! Detection of footnote references and the notes they point at.
+//!
+//! A footnote reference is a `sup > a[href^="#"]` or `a[href^="#"] > sup` whose target is
+//! part of a note list: an `li`, or an element inside a list or a container marked as
+//! footnotes by its `role` or class, as produced by MediaWiki, Pandoc and most blog
+//! engines.
+
+use std::collections::{HashMap, HashSet};
+
+use ego_tree::{NodeId, NodeRef};
+
+use crate::html::Html;
+use crate::node::Node;
+
+/// How the Markdown and plaintext converters render footnotes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum FootnoteStyle {
+    /// Renders references and note lists like any other content.
+    #[default]
+    Keep,
+    /// Replaces each reference with the note text in parentheses and drops the notes.
+    Inline,
+    /// Replaces each reference with a `[^n]` marker and lists the notes as `[^n]: text`
+    /// at the end.
+    Collect,
+}
+
+/// A note that the document references.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Footnote {
+    /// The label of the first reference, without brackets, such as `1` or `a`.
+    pub label: String,
+    /// The `id` of the note element.
+    pub id: String,
+    /// The text of the note, without back links.
+    pub text: String,
+    /// The number of references to the note.
+    pub references: usize,
+}
+
+/// The footnotes of a document and the nodes that make them up.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct FootnoteIndex {
+    pub(crate) notes: Vec<Footnote>,
+    /// The reference nodes, each with the index of its note.
+    references: HashMap<NodeId, usize>,
+    /// The note elements.
+    targets: HashSet<NodeId>,
+}
+
+impl FootnoteIndex {
+    pub(crate) fn new(html: &Html) -> Self {
+        Self::from_root(html.tree.root())
+    }
+
+    /// Indexes the notes of the tree that `root` is the root of.
+    pub(crate) fn from_root(root: NodeRef<Node>) -> Self {
+        let mut ids: HashMap<&str, NodeRef<Node>> = HashMap::new();
+        for node in root.descendants() {
+            if let Some(id) = node.value().as_element().and_then(|e| e.id()) {
+                ids.entry(id).or_insert(node);
+            }
+        }
+
+        let mut index = FootnoteIndex::default();
+        for node in root.descendants() {
+            let Some((reference, href)) = reference_link(node) else {
+                continue;
+            };
+            let Some(&target) = ids.get(href) else {
+                continue;
+            };
+            if !is_note(target) || target.ancestors().any(|a| a.id() == node.id()) {
+                continue;
+            }
+
+            let position = match index.notes.iter().position(|n| n.id == href) {
+                Some(position) => position,
+                None => {
+                    let mut text = String::new();
+                    note_text(target, &mut text);
+                    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+                    let text = text.trim_start_matches(['^', '↑', '↩', ' ']);
+                    let text = text.trim_end_matches(['↩', ' ']);
+                    let label: String = reference
+                        .descendants()
+                        .filter_map(|n| n.value().as_text())
+                        .map(|t| &**t)
+                        .collect();
+                    let label = label.trim().trim_matches(['[', ']', '(', ')']).trim();
+                    index.notes.push(Footnote {
+                        label: label.to_owned(),
+                        id: href.to_owned(),
+                        text: text.to_owned(),
+                        references: 0,
+                    });
+                    index.targets.insert(target.id());
+                    index.notes.len() - 1
+                }
+            };
+            index.notes[position].references += 1;
+            index.references.insert(reference.id(), position);
+        }
+        index
+    }
+
+    /// Returns the index of the note a reference node points at.
+    pub(crate) fn reference(&self, node: NodeId) -> Option<usize> {
+        self.references.get(&node).copied()
+    }
+
+    /// Returns true if the node is a referenced note.
+    pub(crate) fn is_note(&self, node: NodeId) -> bool {
+        self.targets.contains(&node)
+    }
+}
+
+/// Returns the outermost node of a footnote reference and its target id.
+fn reference_link(node: NodeRef<'_, Node>) -> Option<(NodeRef<'_, Node>, &str)> {
+    let element = node.value().as_element()?;
+    let only_child = |name: &str| {
+        let mut children = node
+            .children()
+            .filter(|c| !c.value().as_text().is_some_and(|t| t.trim().is_empty()));
+        let child = children.next()?;
+        let matches = child.value().as_element()?.name() == name;
+        (matches && children.next().is_none()).then_some(child)
+    };
+    let link = match element.name() {
+        "sup" => only_child("a")?,
+        "a" if only_child("sup").is_some() => node,
+        _ => return None,
+    };
+    // `a > sup` inside a `sup` is handled from the outer `sup`.
+    if element.name() == "a" && node.parent().is_some_and(is_sup) {
+        return None;
+    }
+    let href = link.value().as_element()?.attr("href")?.trim();
+    let id = href.strip_prefix('#').filter(|id| !id.is_empty())?;
+    Some((node, id))
+}
+
+fn is_sup(node: NodeRef<Node>) -> bool {
+    node.value().as_element().is_some_and(|e| e.name() == "sup")
+}
+
+/// Returns true if the element is part of a note list.
+fn is_note(target: NodeRef<Node>) -> bool {
+    std::iter::once(target)
+        .chain(target.ancestors())
+        .filter_map(|n| n.value().as_element())
+        .any(|e| {
+            matches!(e.name(), "li" | "ol" | "ul" | "dl")
+                || e.attr("role").is_some_and(|role| {
+                    role.split_ascii_whitespace().any(|role| {
+                        matches!(
+                            role,
+                            "doc-footnote" | "doc-endnote" | "doc-endnotes" | "doc-noteref"
+                        )
+                    })
+                })
+                || e.classes().any(|class| {
+                    let class = class.to_ascii_lowercase();
+                    class.contains("footnote") || class.contains("references")
+                })
+        })
+}
+
+/// Collects the text of a note, skipping back links.
+fn note_text(node: NodeRef<Node>, out: &mut String) {
+    for child in node.children() {
+        match child.value() {
+            Node::Text(text) => out.push_str(text),
+            Node::Element(e) => {
+                let back_link = e.name() == "a"
+                    && e.attr("href").is_some_and(|h| h.starts_with('#'))
+                    || e.classes()
+                        .any(|c| c == "mw-cite-backlink" || c.contains("backref"));
+                if !back_link && !matches!(e.name(), "script" | "style") {
+                    note_text(child, out);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Html {
+    /// Returns the footnotes the document references, in order of first reference.
+    ///
+    /// ```
+    /// use scraper::Html;
+    ///
+    /// let html = Html::parse_document(r##"
+    ///     <p>Water boils at 100 °C.<sup id="r1"><a href="#n1">[1]</a></sup></p>
+    ///     <ol class="references">
+    ///       <li id="n1"><a href="#r1">^</a> At sea level.</li>
+    ///     </ol>
+    /// "##);
+    /// let notes = html.footnotes();
+    /// assert_eq!("1", notes[0].label);
+    /// assert_eq!("At sea level.", notes[0].text);
+    /// ```
+    pub fn footnotes(&self) -> Vec<Footnote> {
+        FootnoteIndex::new(self).notes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::html::Html;
+
+    #[test]
+    fn ignores_plain_anchors_and_counts_repeats() {
+        let html = Html::parse_document(
+            "<p><a href='#top'>top</a><sup><a href='#missing'>2</a></sup>\
+             <a href='#fn1' class='footnote-ref'><sup>1</sup></a> again\
+             <a href='#fn1'><sup>1</sup></a></p><h2 id='top'>Title</h2>\
+             <section class='footnotes'><p id='fn1'>Note. <a href='#fnref1'>↩</a></p></section>",
+        );
+        let notes = html.footnotes();
+        assert_eq!(1, notes.len());
+        assert_eq!(
+            ("fn1", "Note.", 2),
+            (
+                notes[0].id.as_str(),
+                notes[0].text.as_str(),
+                notes[0].references
+            )
+        );
+    }
+
+    #[test]
+    fn only_note_roles_mark_notes() {
+        let html = Html::parse_document(
+            "<p>See<sup><a href='#c2'>2</a></sup> and<sup><a href='#n1'>1</a></sup>.</p>\
+             <section role='doc-chapter' id='c2'>Chapter two.</section>\
+             <aside role='doc-footnote' id='n1'>A note.</aside>",
+        );
+        let notes = html.footnotes();
+        assert_eq!(
+            vec!["n1"],
+            notes.iter().map(|n| n.id.as_str()).collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,358 @@
+//! Forms and their controls.
+
+use crate::element_ref::ElementRef;
+use crate::selector::Selector;
+
+lazy_static! {
+    static ref OPTIONS: Selector = Selector::parse("option").unwrap();
+}
+
+/// How a form is submitted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum FormMethod {
+    /// Parameters go in the query string of the action URL.
+    #[default]
+    Get,
+    /// Parameters go in the request body.
+    Post,
+    /// The form closes the `<dialog>` around it without a request.
+    Dialog,
+}
+
+impl FormMethod {
+    /// Parses a `method` or `formmethod` attribute, where invalid values mean `GET`.
+    fn parse(value: &str) -> Self {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "post" => FormMethod::Post,
+            "dialog" => FormMethod::Dialog,
+            _ => FormMethod::Get,
+        }
+    }
+}
+
+/// An `<option>` of a `<select>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SelectOption {
+    /// The submitted value, from `value` or else the text.
+    pub value: String,
+    /// The displayed label, from `label` or else the text.
+    pub label: String,
+    /// Whether the option is selected by default.
+    pub selected: bool,
+    /// Whether the option, or the `<optgroup>` around it, is disabled.
+    pub disabled: bool,
+}
+
+/// A control of a [`Form`]: an `<input>`, `<select>`, `<textarea>` or `<button>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FormControl {
+    /// The `name`, possibly empty.
+    pub name: String,
+    /// The lowercased type: the `type` of an `<input>` (`text` by default) or `<button>`
+    /// (`submit` by default), else `select`, `select-multiple` or `textarea`.
+    pub kind: String,
+    /// The default value: the `value` attribute, or the text of a `<textarea>`. `None` for
+    /// selects, whose value comes from their options.
+    pub value: Option<String>,
+    /// Whether a checkbox or radio button is checked by default.
+    pub checked: bool,
+    /// Whether the control, or a `<fieldset>` around it, is disabled.
+    pub disabled: bool,
+    /// Whether the control is required.
+    pub required: bool,
+    /// The options of a select.
+    pub options: Vec<SelectOption>,
+    /// The `formaction` of a submit button, overriding the form's action.
+    pub action: Option<String>,
+    /// The `formmethod` of a submit button, overriding the form's method.
+    pub method: Option<FormMethod>,
+}
+
+impl FormControl {
+    /// Returns true for the buttons that submit the form.
+    pub fn is_submit(&self) -> bool {
+        matches!(self.kind.as_str(), "submit" | "image")
+    }
+
+    /// Appends the parameters this control submits, following the HTML form data set
+    /// rules without a submitter.
+    fn params(&self, params: &mut Vec<(String, String)>) {
+        if self.disabled || self.name.is_empty() {
+            return;
+        }
+        match self.kind.as_str() {
+            "submit" | "image" | "reset" | "button" | "file" => {}
+            "checkbox" | "radio" => {
+                if self.checked {
+                    let value = self.value.clone().unwrap_or_else(|| "on".to_owned());
+                    params.push((self.name.clone(), value));
+                }
+            }
+            "select" | "select-multiple" => {
+                let enabled = || self.options.iter().filter(|o| !o.disabled);
+                let selected: Vec<_> = enabled().filter(|o| o.selected).collect();
+                if selected.is_empty() && self.kind == "select" {
+                    // A single select shows, and submits, its first option.
+                    if let Some(first) = enabled().next() {
+                        params.push((self.name.clone(), first.value.clone()));
+                    }
+                }
+                let selected = match self.kind.as_str() {
+                    "select" => &selected[selected.len().saturating_sub(1)..],
+                    _ => &selected[..],
+                };
+                for option in selected {
+                    params.push((self.name.clone(), option.value.clone()));
+                }
+            }
+            _ => params.push((self.name.clone(), self.value.clone().unwrap_or_default())),
+        }
+    }
+}
+
+/// A form and its controls, as written in the markup.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Form {
+    /// The unresolved `action`; the page itself when `None`.
+    pub action: Option<String>,
+    /// The method.
+    pub method: FormMethod,
+    /// The lowercased `enctype`, `application/x-www-form-urlencoded` by default.
+    pub enctype: String,
+    /// The `name`.
+    pub name: Option<String>,
+    /// The controls owned by the form, in document order.
+    pub controls: Vec<FormControl>,
+}
+
+impl Form {
+    /// Returns the name and value pairs the form submits with its default values, in
+    /// order, ready to be URL-encoded.
+    ///
+    /// Disabled and unnamed controls, unchecked checkboxes and radio buttons, buttons and
+    /// file inputs are left out.
+    pub fn to_params(&self) -> Vec<(String, String)> {
+        let mut params = Vec::new();
+        for control in &self.controls {
+            control.params(&mut params);
+        }
+        params
+    }
+
+    /// Returns the first control with a name.
+    pub fn control(&self, name: &str) -> Option<&FormControl> {
+        self.controls.iter().find(|control| control.name == name)
+    }
+
+    /// Returns the buttons that submit the form, each possibly with its own action and
+    /// method.
+    pub fn submit_buttons(&self) -> impl Iterator<Item = &FormControl> {
+        self.controls.iter().filter(|control| control.is_submit())
+    }
+}
+
+/// Returns the text of an element with whitespace collapsed.
+fn collapsed_text(element: ElementRef) -> String {
+    let text: String = element.text().collect();
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Returns true if the element or a `<fieldset>` around it is disabled.
+fn is_disabled(element: ElementRef) -> bool {
+    element.value().attr("disabled").is_some()
+        || element
+            .ancestors()
+            .filter_map(ElementRef::wrap)
+            .any(|a| a.value().name() == "fieldset" && a.value().attr("disabled").is_some())
+}
+
+fn control(element: ElementRef) -> Option<FormControl> {
+    let value = element.value();
+    let attr = |name: &str| value.attr(name).map(str::to_owned);
+    let kind = match value.name() {
+        "input" => value
+            .attr("type")
+            .map(|t| t.trim().to_ascii_lowercase())
+            .filter(|t| !t.is_empty())
+            .unwrap_or_else(|| "text".to_owned()),
+        "button" => match value.attr("type").map(|t| t.trim().to_ascii_lowercase()) {
+            Some(t) if t == "reset" || t == "button" => t,
+            _ => "submit".to_owned(),
+        },
+        "select" if value.attr("multiple").is_some() => "select-multiple".to_owned(),
+        "select" => "select".to_owned(),
+        "textarea" => "textarea".to_owned(),
+        _ => return None,
+    };
+
+    let options = match value.name() {
+        "select" => element
+            .select(&OPTIONS)
+            .map(|option| {
+                let text = collapsed_text(option);
+                let group_disabled = option.parent().and_then(ElementRef::wrap).is_some_and(|p| {
+                    p.value().name() == "optgroup" && p.value().attr("disabled").is_some()
+                });
+                SelectOption {
+                    value: option
+                        .value()
+                        .attr("value")
+                        .map_or_else(|| text.clone(), str::to_owned),
+                    label: option
+                        .value()
+                        .attr("label")
+                        .map_or_else(|| text.clone(), str::to_owned),
+                    selected: option.value().attr("selected").is_some(),
+                    disabled: option.value().attr("disabled").is_some() || group_disabled,
+                }
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+    let default = match value.name() {
+        "textarea" => Some(element.text().collect()),
+        "select" => None,
+        _ => attr("value"),
+    };
+    let is_submit = matches!(kind.as_str(), "submit" | "image");
+    Some(FormControl {
+        name: attr("name").unwrap_or_default(),
+        value: default,
+        checked: value.attr("checked").is_some(),
+        disabled: is_disabled(element),
+        required: value.attr("required").is_some(),
+        options,
+        action: attr("formaction").filter(|_| is_submit),
+        method: value
+            .attr("formmethod")
+            .filter(|_| is_submit)
+            .map(FormMethod::parse),
+        kind,
+    })
+}
+
+impl ElementRef<'_> {
+    /// Collects a `form` element's attributes and controls. Returns `None` for other
+    /// elements.
+    ///
+    /// The controls are those inside the form, except ones assigned to another form with a
+    /// `form` attribute, plus those elsewhere in the document assigned to it by its `id`.
+    ///
+    /// ```
+    /// use scraper::form::FormMethod;
+    /// use scraper::{Html, Selector};
+    ///
+    /// let html = Html::parse_document(r#"
+    ///     <form id="search" action="/find" method="POST">
+    ///       <input name="q" value="rust">
+    ///       <input type="checkbox" name="exact" checked>
+    ///       <select name="sort"><option>relevance</option><option value="new" selected>Newest</option></select>
+    ///       <button name="go" formaction="/find/all">All</button>
+    ///     </form>
+    ///     <input form="search" type="hidden" name="page" value="2">
+    /// "#);
+    /// let form = html.select_first(&Selector::parse("form").unwrap()).unwrap();
+    /// let form = form.as_form().unwrap();
+    ///
+    /// assert_eq!((Some("/find"), FormMethod::Post), (form.action.as_deref(), form.method));
+    /// assert_eq!(
+    ///     vec![("q", "rust"), ("exact", "on"), ("sort", "new"), ("page", "2")],
+    ///     form.to_params().iter().map(|(k, v)| (k.as_str(), v.as_str())).collect::<Vec<_>>()
+    /// );
+    /// assert_eq!(Some("/find/all"), form.submit_buttons().next().unwrap().action.as_deref());
+    /// ```
+    pub fn as_form(&self) -> Option<Form> {
+        let value = self.value();
+        if value.name() != "form" {
+            return None;
+        }
+        let id = value.id().filter(|id| !id.is_empty());
+        let root = self.ancestors().last().unwrap_or(**self);
+
+        let controls = root
+            .descendants()
+            .filter_map(ElementRef::wrap)
+            .filter(|element| {
+                if !matches!(
+                    element.value().name(),
+                    "input" | "select" | "textarea" | "button"
+                ) {
+                    return false;
+                }
+                match element.value().attr("form") {
+                    Some(owner) => Some(owner) == id,
+                    None => element
+                        .ancestors()
+                        .filter_map(ElementRef::wrap)
+                        .find(|a| a.value().name() == "form")
+                        .is_some_and(|form| form == *self),
+                }
+            })
+            .filter_map(control)
+            .collect();
+
+        Some(Form {
+            action: value.attr("action").map(|a| a.trim().to_owned()),
+            method: value
+                .attr("method")
+                .map_or_else(FormMethod::default, FormMethod::parse),
+            enctype: value
+                .attr("enctype")
+                .map(|e| e.trim().to_ascii_lowercase())
+                .filter(|e| matches!(e.as_str(), "multipart/form-data" | "text/plain"))
+                .unwrap_or_else(|| "application/x-www-form-urlencoded".to_owned()),
+            name: value.attr("name").map(str::to_owned),
+            controls,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::html::Html;
+    use crate::selector::Selector;
+
+    #[test]
+    fn defaults_follow_the_form_data_set_rules() {
+        let html = Html::parse_document(
+            r#"<form enctype="Multipart/Form-Data">
+              <fieldset disabled><input name="off" value="x"></fieldset>
+              <input name="a" form="other">
+              <input type="radio" name="r" value="1"><input type="radio" name="r" value="2" checked>
+              <select name="s"><option disabled>-</option><optgroup label="g"><option>one</option></optgroup></select>
+              <select name="m" multiple><option selected>x</option><option selected value="y">Y</option></select>
+              <textarea name="t">Hi
+            there</textarea>
+              <input type="file" name="f"><input type="submit" name="go" value="Go">
+            </form>"#,
+        );
+        let form = html
+            .select_first(&Selector::parse("form").unwrap())
+            .unwrap()
+            .as_form()
+            .unwrap();
+        assert_eq!("multipart/form-data", form.enctype);
+        assert!(form.control("off").unwrap().disabled);
+        assert!(form.control("a").is_none());
+        let params: Vec<_> = form.to_params();
+        let params: Vec<_> = params
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+            .collect();
+        assert_eq!(
+            vec![
+                ("r", "2"),
+                ("s", "one"),
+                ("m", "x"),
+                ("m", "y"),
+                ("t", "Hi\n            there"),
+            ],
+            params
+        );
+    }
+}
@@ -0,0 +1,388 @@
+//! Import of documents captured through the Chrome DevTools Protocol.
+
+use std::collections::HashMap;
+
+use ego_tree::{NodeId, Tree};
+use html5ever::tendril::StrTendril;
+use html5ever::tree_builder::QuirksMode;
+use html5ever::{Attribute, LocalName, Namespace, QualName};
+use serde_json::Value;
+
+use super::layout::{BOUNDS_ATTRIBUTE, VISIBLE_ATTRIBUTE};
+use super::Html;
+use crate::error::CdpError;
+use crate::node::{Comment, Doctype, Element, Node, Text};
+
+const ELEMENT: u64 = 1;
+const TEXT: u64 = 3;
+const CDATA: u64 = 4;
+const COMMENT: u64 = 8;
+const DOCUMENT: u64 = 9;
+const DOCTYPE: u64 = 10;
+const FRAGMENT: u64 = 11;
+
+/// Returns the namespace of an element from its name and its parent's namespace.
+fn namespace(name: &str, parent: &Namespace) -> Namespace {
+    if name.eq_ignore_ascii_case("svg") {
+        ns!(svg)
+    } else if name.eq_ignore_ascii_case("math") {
+        ns!(mathml)
+    } else if *parent == ns!(svg) || *parent == ns!(mathml) {
+        parent.clone()
+    } else {
+        ns!(html)
+    }
+}
+
+/// Returns the namespace children of an element are in.
+pub(super) fn child_namespace(element: &Element) -> Namespace {
+    let ns = element.name.ns.clone();
+    if ns == ns!(svg) && &*element.name.local == "foreignObject" {
+        ns!(html)
+    } else {
+        ns
+    }
+}
+
+pub(super) fn element(name: &str, parent: &Namespace, attrs: Vec<(String, String)>) -> Element {
+    let ns = namespace(name, parent);
+    // HTML names are reported uppercase; foreign names keep their case.
+    let local = if ns == ns!(html) {
+        name.to_ascii_lowercase()
+    } else {
+        name.to_owned()
+    };
+    let attrs = attrs
+        .into_iter()
+        .map(|(name, value)| Attribute {
+            name: QualName::new(None, ns!(), LocalName::from(name.as_str())),
+            value: StrTendril::from_slice(&value),
+        })
+        .collect();
+    Element::new(QualName::new(None, ns, LocalName::from(local)), attrs)
+}
+
+pub(super) fn doctype(name: &str, public_id: &str, system_id: &str) -> Node {
+    Node::Doctype(Doctype {
+        name: name.into(),
+        public_id: public_id.into(),
+        system_id: system_id.into(),
+    })
+}
+
+pub(super) fn text(value: &str) -> Node {
+    Node::Text(Text { text: value.into() })
+}
+
+pub(super) fn document(tree: Tree<Node>) -> Html {
+    Html::from_parts(QuirksMode::NoQuirks, tree)
+}
+
+/// Builds the tree of a `DOM.getDocument` result, whose nodes nest.
+fn from_dom(root: &Value) -> Result<Html, CdpError> {
+    fn str_field<'v>(node: &'v Value, key: &str) -> &'v str {
+        node.get(key).and_then(Value::as_str).unwrap_or_default()
+    }
+
+    fn append(tree: &mut Tree<Node>, parent: NodeId, node: &Value) {
+        let parent_ns = match tree.get(parent).map(|p| p.value()) {
+            Some(Node::Element(e)) => child_namespace(e),
+            _ => ns!(html),
+        };
+        let child = match node.get("nodeType").and_then(Value::as_u64) {
+            Some(ELEMENT) => {
+                let attrs = node
+                    .get("attributes")
+                    .and_then(Value::as_array)
+                    .map(|attrs| {
+                        attrs
+                            .chunks(2)
+                            .map(|pair| {
+                                let at = |i: usize| pair.get(i).and_then(Value::as_str);
+                                (
+                                    at(0).unwrap_or_default().to_owned(),
+                                    at(1).unwrap_or_default().to_owned(),
+                                )
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                let name = match str_field(node, "localName") {
+                    "" => str_field(node, "nodeName"),
+                    name => name,
+                };
+                Node::Element(element(name, &parent_ns, attrs))
+            }
+            Some(TEXT | CDATA) => text(str_field(node, "nodeValue")),
+            Some(COMMENT) => Node::Comment(Comment {
+                comment: str_field(node, "nodeValue").into(),
+            }),
+            Some(DOCTYPE) => doctype(
+                str_field(node, "nodeName"),
+                str_field(node, "publicId"),
+                str_field(node, "systemId"),
+            ),
+            _ => return,
+        };
+        let Some(mut parent) = tree.get_mut(parent) else {
+            return;
+        };
+        let id = parent.append(child).id();
+
+        // Template contents belong inside the template in markup.
+        let children = node.get("children").and_then(Value::as_array);
+        let content = node
+            .get("templateContent")
+            .and_then(|c| c.get("children"))
+            .and_then(Value::as_array);
+        for child in children.into_iter().chain(content).flatten() {
+            append(tree, id, child);
+        }
+    }
+
+    if root.get("nodeType").and_then(Value::as_u64) != Some(DOCUMENT) {
+        return Err(CdpError::UnknownPayload);
+    }
+    let mut tree = Tree::new(Node::Document);
+    let root_id = tree.root().id();
+    for child in root
+        .get("children")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        append(&mut tree, root_id, child);
+    }
+    Ok(document(tree))
+}
+
+/// Builds the tree of the first document of a `DOMSnapshot.captureSnapshot` result, whose
+/// nodes are flat arrays indexing into a shared string table.
+fn from_snapshot(snapshot: &Value) -> Result<Html, CdpError> {
+    let strings: Vec<&str> = snapshot
+        .get("strings")
+        .and_then(Value::as_array)
+        .ok_or(CdpError::UnknownPayload)?
+        .iter()
+        .map(|s| s.as_str().unwrap_or_default())
+        .collect();
+    let document_value = snapshot
+        .get("documents")
+        .and_then(|d| d.get(0))
+        .ok_or(CdpError::UnknownPayload)?;
+    let nodes = document_value
+        .get("nodes")
+        .ok_or(CdpError::UnknownPayload)?;
+
+    let numbers = |value: Option<&Value>| -> Vec<i64> {
+        value
+            .and_then(Value::as_array)
+            .map(|a| a.iter().map(|v| v.as_i64().unwrap_or(-1)).collect())
+            .unwrap_or_default()
+    };
+    let string = |index: i64| -> Result<&str, CdpError> {
+        if index < 0 {
+            return Ok("");
+        }
+        strings
+            .get(index as usize)
+            .copied()
+            .ok_or(CdpError::InvalidIndex(index as usize))
+    };
+    // Sparse per-node data: `{"index": [...], "value": [...]}`.
+    let rare = |key: &str| -> HashMap<i64, i64> {
+        let data = nodes.get(key);
+        let index = numbers(data.and_then(|d| d.get("index")));
+        let value = numbers(data.and_then(|d| d.get("value")));
+        index
+            .into_iter()
+            .enumerate()
+            .map(|(i, node)| (node, value.get(i).copied().unwrap_or(-1)))
+            .collect()
+    };
+
+    let parents = numbers(nodes.get("parentIndex"));
+    let types = numbers(nodes.get("nodeType"));
+    let names = numbers(nodes.get("nodeName"));
+    let values = numbers(nodes.get("nodeValue"));
+    let attributes = nodes.get("attributes").and_then(Value::as_array);
+    let input_values = rare("inputValue");
+    let checked = rare("inputChecked");
+
+    let layout = document_value.get("layout");
+    let layout_nodes = numbers(layout.and_then(|l| l.get("nodeIndex")));
+    let bounds = layout
+        .and_then(|l| l.get("bounds"))
+        .and_then(Value::as_array);
+    let mut boxes: HashMap<i64, Vec<f64>> = HashMap::new();
+    for (i, node) in layout_nodes.iter().enumerate() {
+        let rect = bounds
+            .and_then(|b| b.get(i))
+            .and_then(Value::as_array)
+            .map(|r| r.iter().filter_map(Value::as_f64).collect())
+            .unwrap_or_default();
+        boxes.entry(*node).or_insert(rect);
+    }
+    let has_layout = layout.is_some();
+
+    let mut tree = Tree::new(Node::Document);
+    let mut ids: Vec<Option<NodeId>> = Vec::with_capacity(types.len());
+    for (index, &kind) in types.iter().enumerate() {
+        let parent = parents.get(index).copied().unwrap_or(-1);
+        let parent_id = if parent < 0 {
+            None
+        } else {
+            *ids.get(parent as usize)
+                .ok_or(CdpError::InvalidIndex(parent as usize))?
+        };
+        let name = string(names.get(index).copied().unwrap_or(-1))?;
+        let value = string(values.get(index).copied().unwrap_or(-1))?;
+
+        if kind as u64 == DOCUMENT && parent < 0 {
+            ids.push(Some(tree.root().id()));
+            continue;
+        }
+        // Nodes under skipped nodes, such as shadow roots, are skipped too.
+        let Some(parent_id) = parent_id else {
+            ids.push(None);
+            continue;
+        };
+        let parent_node = tree.get(parent_id).map(|p| p.value().clone());
+        let parent_ns = match &parent_node {
+            Some(Node::Element(e)) => child_namespace(e),
+            _ => ns!(html),
+        };
+        let node = match kind as u64 {
+            ELEMENT => {
+                let mut attrs: Vec<(String, String)> = Vec::new();
+                let pairs = numbers(attributes.and_then(|a| a.get(index)));
+                for pair in pairs.chunks(2) {
+                    let name = string(pair[0])?;
+                    let value = string(pair.get(1).copied().unwrap_or(-1))?;
+                    attrs.push((name.to_owned(), value.to_owned()));
+                }
+                if let Some(&value) = input_values.get(&(index as i64)) {
+                    attrs.retain(|(name, _)| name != "value");
+                    attrs.push(("value".to_owned(), string(value)?.to_owned()));
+                }
+                if checked.contains_key(&(index as i64))
+                    && !attrs.iter().any(|(n, _)| n == "checked")
+                {
+                    attrs.push(("checked".to_owned(), String::new()));
+                }
+                if has_layout {
+                    let rect = boxes.get(&(index as i64));
+                    let visible = rect.is_some_and(|r| r.len() == 4 && r[2] > 0.0 && r[3] > 0.0);
+                    attrs.push((VISIBLE_ATTRIBUTE.to_owned(), visible.to_string()));
+                    if let Some(rect) = rect.filter(|r| r.len() == 4) {
+                        let rect: Vec<String> = rect.iter().map(f64::to_string).collect();
+                        attrs.push((BOUNDS_ATTRIBUTE.to_owned(), rect.join(",")));
+                    }
+                }
+                Node::Element(element(name, &parent_ns, attrs))
+            }
+            TEXT | CDATA => text(value),
+            COMMENT => Node::Comment(Comment {
+                comment: value.into(),
+            }),
+            DOCTYPE => doctype(name, "", ""),
+            // Template contents belong inside the template in markup; shadow roots and
+            // other fragments are left out.
+            FRAGMENT => {
+                let template =
+                    matches!(&parent_node, Some(Node::Element(e)) if &*e.name.local == "template");
+                ids.push(template.then_some(parent_id));
+                continue;
+            }
+            _ => {
+                ids.push(None);
+                continue;
+            }
+        };
+        let mut parent = tree
+            .get_mut(parent_id)
+            .ok_or(CdpError::InvalidIndex(parent as usize))?;
+        ids.push(Some(parent.append(node).id()));
+    }
+    Ok(document(tree))
+}
+
+impl Html {
+    /// Builds a document from the JSON result of a Chrome DevTools Protocol
+    /// `DOM.getDocument` (called with `depth: -1`) or `DOMSnapshot.captureSnapshot` command,
+    /// so that the DOM after scripts ran can be queried without serializing it to markup
+    /// and parsing it again. Either the result object or the whole response message with
+    /// its `result` field is accepted.
+    ///
+    /// Snapshots keep what markup cannot: the current value and checked state of form
+    /// controls become `value` and `checked` attributes, and with layout captured each
+    /// element gets a [`VISIBLE_ATTRIBUTE`] and, when rendered, a [`BOUNDS_ATTRIBUTE`].
+    /// Only the top-level document is imported; frames and shadow roots are left out.
+    ///
+    /// ```
+    /// use scraper::{Html, Selector};
+    ///
+    /// let snapshot = r##"{
+    ///     "strings": ["#document", "HTML", "BODY", "P", "#text", "Hello", "hidden", "", "INPUT", "typed"],
+    ///     "documents": [{
+    ///         "nodes": {
+    ///             "parentIndex": [-1, 0, 1, 2, 3, 2, 2],
+    ///             "nodeType": [9, 1, 1, 1, 3, 1, 1],
+    ///             "nodeName": [0, 1, 2, 3, 4, 3, 8],
+    ///             "nodeValue": [-1, -1, -1, -1, 5, -1, -1],
+    ///             "attributes": [[], [], [], [], [], [6, 7], []],
+    ///             "inputValue": {"index": [6], "value": [9]}
+    ///         },
+    ///         "layout": {"nodeIndex": [1, 2, 3, 4, 6], "bounds": [[0, 0, 800, 600], [8, 8, 784, 40], [8, 8, 784, 18], [8, 8, 40, 18], [8, 30, 150, 20]]}
+    ///     }]
+    /// }"##;
+    /// let html = Html::from_cdp_snapshot(snapshot).unwrap();
+    /// let hidden = Selector::parse(r#"p[data-cdp-visible="false"]"#).unwrap();
+    /// assert_eq!(1, html.select(&hidden).count());
+    /// let input = html.select_first(&Selector::parse("input").unwrap()).unwrap();
+    /// assert_eq!(Some("typed"), input.value().attr("value"));
+    /// ```
+    pub fn from_cdp_snapshot(json: &str) -> Result<Self, CdpError> {
+        let value: Value =
+            serde_json::from_str(json).map_err(|err| CdpError::Decode(err.to_string()))?;
+        let value = value.get("result").unwrap_or(&value);
+        if let Some(root) = value.get("root") {
+            from_dom(root)
+        } else if value.get("documents").is_some() {
+            from_snapshot(value)
+        } else {
+            Err(CdpError::UnknownPayload)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::CdpError;
+    use crate::html::Html;
+
+    #[test]
+    fn get_document_matches_parsed_markup() {
+        let json = r##"{"id": 1, "result": {"root": {"nodeId": 1, "nodeType": 9, "nodeName": "#document", "children": [
+            {"nodeType": 10, "nodeName": "html", "publicId": "", "systemId": ""},
+            {"nodeType": 1, "nodeName": "HTML", "localName": "html", "attributes": ["lang", "en"], "children": [
+                {"nodeType": 1, "nodeName": "HEAD", "localName": "head", "children": []},
+                {"nodeType": 1, "nodeName": "BODY", "localName": "body", "children": [
+                    {"nodeType": 1, "nodeName": "TEMPLATE", "localName": "template", "children": [],
+                     "templateContent": {"nodeType": 11, "children": [{"nodeType": 3, "nodeValue": "t"}]}},
+                    {"nodeType": 1, "nodeName": "svg", "localName": "svg", "children": [
+                        {"nodeType": 1, "nodeName": "linearGradient", "localName": "linearGradient"}]}
+                ]}
+            ]}
+        ]}}}"##;
+        let html = Html::from_cdp_snapshot(json).unwrap();
+        let parsed = Html::parse_document(
+            r#"<!DOCTYPE html><html lang="en"><head></head><body><template>t</template><svg><linearGradient></linearGradient></svg></body></html>"#,
+        );
+        assert_eq!(parsed.html(), html.html());
+        assert_eq!(
+            Err(CdpError::UnknownPayload),
+            Html::from_cdp_snapshot("{}").map(|_| ())
+        );
+    }
+}
@@ -0,0 +1,164 @@
+//! Mutable access to one element of a document.
+
+use ego_tree::NodeId;
+use html5ever::QualName;
+
+use super::Html;
+use crate::element_ref::ElementRef;
+use crate::node::{Element, Node};
+
+/// An element of an [`Html`] borrowed for changes, as returned by [`Html::element_mut`].
+///
+/// Every change goes through the mutation methods of `Html`, so observers registered with
+/// [`Html::on_mutation`] are notified of it.
+#[derive(Debug)]
+pub struct ElementMut<'a> {
+    html: &'a mut Html,
+    id: NodeId,
+}
+
+impl Html {
+    /// Returns an element for changes, or `None` if the node is not an element.
+    ///
+    /// ```
+    /// use scraper::node::{Node, Text};
+    /// use scraper::{Html, Selector};
+    ///
+    /// let mut html = Html::parse_fragment(r#"<ul><li class="old">One</li></ul>"#);
+    /// let ul = html.select_first(&Selector::parse("ul").unwrap()).unwrap().id();
+    /// let mut ul = html.element_mut(ul).unwrap();
+    /// ul.remove_children();
+    /// let li = ul.append_element("li");
+    /// ul.set_attr("id", "list");
+    ///
+    /// let mut li = html.element_mut(li).unwrap();
+    /// li.append_child(Node::Text(Text { text: "Two".into() }));
+    /// li.insert_after(Node::Text(Text { text: "!".into() }));
+    /// assert_eq!(r#"<html><ul id="list"><li>Two</li>!</ul></html>"#, html.html());
+    /// ```
+    pub fn element_mut(&mut self, id: NodeId) -> Option<ElementMut<'_>> {
+        self.tree.get(id)?.value().as_element()?;
+        Some(ElementMut { html: self, id })
+    }
+}
+
+impl ElementMut<'_> {
+    /// Returns the ID of the element.
+    pub fn id(&self) -> NodeId {
+        self.id
+    }
+
+    /// Returns the element for reading.
+    pub fn element(&self) -> ElementRef<'_> {
+        self.html
+            .tree
+            .get(self.id)
+            .and_then(ElementRef::wrap)
+            .expect("element_mut takes elements")
+    }
+
+    /// Returns the value of an attribute.
+    pub fn attr(&self, name: &str) -> Option<&str> {
+        self.element().value().attr(name)
+    }
+
+    /// Sets an attribute, as [`Html::set_attr`] does.
+    pub fn set_attr(&mut self, name: &str, value: &str) {
+        self.html.set_attr(self.id, name, value);
+    }
+
+    /// Removes an attribute, as [`Html::remove_attr`] does.
+    pub fn remove_attr(&mut self, name: &str) {
+        self.html.remove_attr(self.id, name);
+    }
+
+    /// Appends a node to the children of the element and returns its ID.
+    pub fn append_child(&mut self, node: Node) -> NodeId {
+        self.html
+            .append_child(self.id, node)
+            .expect("element_mut takes elements")
+    }
+
+    /// Appends an empty HTML element with the given local name and returns its ID.
+    pub fn append_element(&mut self, name: &str) -> NodeId {
+        self.append_child(Node::Element(Element::new(
+            QualName::new(None, ns!(html), name.into()),
+            Vec::new(),
+        )))
+    }
+
+    /// Inserts a node before the element and returns its ID, or `None` if the element has
+    /// no parent.
+    pub fn insert_before(&mut self, node: Node) -> Option<NodeId> {
+        self.html.insert_before(self.id, node)
+    }
+
+    /// Inserts a node after the element and returns its ID, or `None` if the element has
+    /// no parent.
+    pub fn insert_after(&mut self, node: Node) -> Option<NodeId> {
+        self.html.insert_after(self.id, node)
+    }
+
+    /// Takes every child of the element out of the tree.
+    pub fn remove_children(&mut self) {
+        self.html.remove_children(self.id);
+    }
+
+    /// Takes the element and its subtree out of the tree.
+    pub fn detach(self) {
+        self.html.detach(self.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::html::{Html, Mutation};
+    use crate::node::{Comment, Node};
+    use crate::selector::Selector;
+
+    #[test]
+    fn changes_are_observed() {
+        let mut html = Html::parse_fragment("<p>a<b>b</b></p>");
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&log);
+        html.on_mutation(move |mutation| sink.lock().unwrap().push(mutation.clone()));
+
+        let root = html.root_element().id();
+        let p = html
+            .select_first(&Selector::parse("p").unwrap())
+            .unwrap()
+            .id();
+        let text = html.tree.get(p).unwrap().first_child().unwrap().id();
+        assert!(html.element_mut(text).is_none());
+
+        let mut p = html.element_mut(p).unwrap();
+        p.remove_children();
+        let comment = Node::Comment(Comment {
+            comment: "c".into(),
+        });
+        let comment = p.insert_before(comment).unwrap();
+        p.set_attr("class", "x");
+        assert_eq!(Some("x"), p.attr("class"));
+        let p = p.id();
+        html.element_mut(p).unwrap().detach();
+        let mut detached = html.element_mut(p).unwrap();
+        assert!(detached.insert_after(Node::Fragment).is_none());
+
+        assert_eq!("<html><!--c--></html>", html.html());
+        let log = log.lock().unwrap();
+        assert!(matches!(
+            log[..2],
+            [Mutation::Detached { .. }, Mutation::Detached { .. }]
+        ));
+        assert_eq!(
+            Mutation::Attached {
+                node: comment,
+                parent: root
+            },
+            log[2]
+        );
+        assert_eq!(5, log.len());
+    }
+}
@@ -0,0 +1,460 @@
+//! Character encoding detection for documents given as bytes, following the WHATWG
+//! encoding sniffing algorithm.
+
+use encoding_rs::{Encoding, UTF_8, WINDOWS_1252, X_USER_DEFINED};
+
+use super::{Html, ParseOptions};
+
+/// The number of bytes the `<meta>` prescan looks at.
+const PRESCAN_BYTES: usize = 1024;
+
+fn is_space(byte: u8) -> bool {
+    matches!(byte, b'\t' | b'\n' | b'\x0C' | b'\r' | b' ')
+}
+
+/// Reads the next attribute of a tag, advancing `i` past it, as the prescan's "get an
+/// attribute" steps do. Names and values are lowercased.
+fn attribute(bytes: &[u8], i: &mut usize) -> Option<(Vec<u8>, Vec<u8>)> {
+    while *i < bytes.len() && (is_space(bytes[*i]) || bytes[*i] == b'/') {
+        *i += 1;
+    }
+    if *i >= bytes.len() || bytes[*i] == b'>' {
+        return None;
+    }
+
+    let mut name = Vec::new();
+    while *i < bytes.len() {
+        let byte = bytes[*i];
+        if (byte == b'=' && !name.is_empty()) || is_space(byte) || byte == b'/' || byte == b'>' {
+            break;
+        }
+        name.push(byte.to_ascii_lowercase());
+        *i += 1;
+    }
+    while *i < bytes.len() && is_space(bytes[*i]) {
+        *i += 1;
+    }
+    if bytes.get(*i) != Some(&b'=') {
+        return Some((name, Vec::new()));
+    }
+    *i += 1;
+    while *i < bytes.len() && is_space(bytes[*i]) {
+        *i += 1;
+    }
+
+    let mut value = Vec::new();
+    match bytes.get(*i) {
+        Some(&quote @ (b'"' | b'\'')) => {
+            *i += 1;
+            while *i < bytes.len() && bytes[*i] != quote {
+                value.push(bytes[*i].to_ascii_lowercase());
+                *i += 1;
+            }
+            *i += 1;
+        }
+        _ => {
+            while *i < bytes.len() && !is_space(bytes[*i]) && bytes[*i] != b'>' {
+                value.push(bytes[*i].to_ascii_lowercase());
+                *i += 1;
+            }
+        }
+    }
+    Some((name, value))
+}
+
+/// Extracts the encoding from a `content` value such as `text/html; charset=utf-8`.
+pub(super) fn content_charset(content: &[u8]) -> Option<&'static Encoding> {
+    let mut rest = content;
+    loop {
+        let at = rest.windows(7).position(|w| w == b"charset")?;
+        rest = &rest[at + 7..];
+        let trimmed = rest
+            .iter()
+            .position(|b| !is_space(*b))
+            .map_or(&[][..], |p| &rest[p..]);
+        let Some(value) = trimmed.strip_prefix(b"=") else {
+            rest = trimmed;
+            continue;
+        };
+        let value = value
+            .iter()
+            .position(|b| !is_space(*b))
+            .map_or(&[][..], |p| &value[p..]);
+        let label = match value.first() {
+            Some(&quote @ (b'"' | b'\'')) => {
+                let end = value[1..].iter().position(|b| *b == quote)?;
+                &value[1..1 + end]
+            }
+            _ => {
+                let end = value
+                    .iter()
+                    .position(|b| is_space(*b) || *b == b';')
+                    .unwrap_or(value.len());
+                &value[..end]
+            }
+        };
+        return Encoding::for_label(label);
+    }
+}
+
+/// Looks for a `<meta charset>` or `<meta http-equiv="content-type">` declaration in the
+/// first bytes of the document.
+fn prescan(bytes: &[u8]) -> Option<&'static Encoding> {
+    let bytes = &bytes[..bytes.len().min(PRESCAN_BYTES)];
+    let starts = |i: usize, prefix: &[u8]| {
+        bytes
+            .get(i..i + prefix.len())
+            .is_some_and(|b| b.eq_ignore_ascii_case(prefix))
+    };
+    let mut i = 0;
+    while i < bytes.len() {
+        if starts(i, b"<!--") {
+            let end = bytes[i + 4..].windows(3).position(|w| w == b"-->")?;
+            i += 4 + end + 3;
+            continue;
+        }
+        if starts(i, b"<meta") && bytes.get(i + 5).is_some_and(|b| is_space(*b) || *b == b'/') {
+            i += 5;
+            let (mut pragma, mut need_pragma, mut charset) = (false, None, None);
+            let mut seen: Vec<Vec<u8>> = Vec::new();
+            while let Some((name, value)) = attribute(bytes, &mut i) {
+                if seen.contains(&name) {
+                    continue;
+                }
+                match name.as_slice() {
+                    b"http-equiv" => pragma |= value == b"content-type",
+                    b"content" if charset.is_none() => {
+                        if let Some(encoding) = content_charset(&value) {
+                            charset = Some(encoding);
+                            need_pragma = Some(true);
+                        }
+                    }
+                    b"charset" => {
+                        charset = Encoding::for_label(&value);
+                        need_pragma = Some(false);
+                    }
+                    _ => {}
+                }
+                seen.push(name);
+            }
+            let declared = match need_pragma {
+                Some(true) => pragma,
+                Some(false) => true,
+                None => false,
+            };
+            if let (true, Some(encoding)) = (declared, charset) {
+                // UTF-16 labels in ASCII markup can only be wrong, so they mean UTF-8.
+                return Some(if encoding == X_USER_DEFINED {
+                    WINDOWS_1252
+                } else {
+                    encoding.output_encoding()
+                });
+            }
+            continue;
+        }
+        let tag = bytes.get(i) == Some(&b'<')
+            && match bytes.get(i + 1) {
+                Some(b'/') => bytes.get(i + 2).is_some_and(u8::is_ascii_alphabetic),
+                Some(b) => b.is_ascii_alphabetic(),
+                None => false,
+            };
+        if tag {
+            while i < bytes.len() && !is_space(bytes[i]) && bytes[i] != b'>' {
+                i += 1;
+            }
+            while attribute(bytes, &mut i).is_some() {}
+        } else if starts(i, b"<!") || starts(i, b"</") || starts(i, b"<?") {
+            i += bytes[i..].iter().position(|b| *b == b'>')?;
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Where the encoding of a document given as bytes came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum EncodingSource {
+    /// Forced with [`ParseOptions::encoding`], or asserted with
+    /// [`Html::parse_document_utf8`].
+    Override,
+    /// A byte order mark.
+    ByteOrderMark,
+    /// The `charset` of the `Content-Type` the document was served or archived with.
+    Transport,
+    /// A `<meta charset>` or `<meta http-equiv="Content-Type">` in the first 1024 bytes.
+    Meta,
+    /// None was declared and the bytes are valid UTF-8.
+    Utf8,
+    /// None was declared and the bytes are not valid UTF-8, so windows-1252 was assumed.
+    Fallback,
+}
+
+impl EncodingSource {
+    /// How likely an encoding from this source is to be right before decoding, from
+    /// certain for a byte order mark to a guess for the fallback.
+    fn confidence(self) -> f32 {
+        match self {
+            EncodingSource::Override | EncodingSource::ByteOrderMark => 1.0,
+            EncodingSource::Transport => 0.9,
+            EncodingSource::Utf8 => 0.9,
+            EncodingSource::Meta => 0.8,
+            EncodingSource::Fallback => 0.3,
+        }
+    }
+}
+
+/// How a document given as bytes was decoded, as returned by [`Html::encoding_info`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EncodingInfo {
+    /// The encoding the bytes were decoded as.
+    pub encoding: &'static Encoding,
+    /// Where the encoding came from.
+    pub source: EncodingSource,
+    /// How likely the encoding is to be right, from 0 to 1. It depends on the source and
+    /// is halved when the bytes were malformed in the encoding.
+    pub confidence: f32,
+    /// Some bytes were not valid in the encoding and were decoded as U+FFFD, a sign of
+    /// mojibake.
+    pub malformed: bool,
+}
+
+/// Detects the encoding of a document, the length of its byte order mark and where the
+/// encoding came from, given the encoding it was `declared` with outside the document.
+///
+/// A byte order mark wins, then the declared encoding, then a `<meta>` declaration in the
+/// first 1024 bytes. Without any, valid UTF-8 is read as UTF-8 and anything else as
+/// windows-1252, the fallback browsers use for most locales.
+fn detect(
+    bytes: &[u8],
+    declared: Option<&'static Encoding>,
+) -> (&'static Encoding, usize, EncodingSource) {
+    if let Some((encoding, bom)) = Encoding::for_bom(bytes) {
+        return (encoding, bom, EncodingSource::ByteOrderMark);
+    }
+    if let Some(encoding) = declared {
+        return (encoding, 0, EncodingSource::Transport);
+    }
+    if let Some(encoding) = prescan(bytes) {
+        return (encoding, 0, EncodingSource::Meta);
+    }
+    match std::str::from_utf8(bytes) {
+        Ok(_) => (UTF_8, 0, EncodingSource::Utf8),
+        Err(_) => (WINDOWS_1252, 0, EncodingSource::Fallback),
+    }
+}
+
+/// Decodes and parses a document given as bytes. The encoding is the one forced with
+/// [`ParseOptions::encoding`], else a byte order mark, else the one the document was
+/// `declared` with outside it, such as in a `Content-Type` header, else as in [`Html::parse_document_bytes`].
+pub(crate) fn parse_bytes(
+    bytes: &[u8],
+    declared: Option<&'static Encoding>,
+    options: &ParseOptions,
+) -> Html {
+    let (encoding, bom, source) = match options.encoding {
+        Some(encoding) => {
+            let bom = match Encoding::for_bom(bytes) {
+                Some((bom_encoding, len)) if bom_encoding == encoding => len,
+                _ => 0,
+            };
+            (encoding, bom, EncodingSource::Override)
+        }
+        None => detect(bytes, declared),
+    };
+    let (text, malformed) = encoding.decode_without_bom_handling(&bytes[bom..]);
+    let mut html = Html::parse_document_with(&text, options);
+    let confidence = source.confidence();
+    html.encoding = Some(EncodingInfo {
+        encoding,
+        source,
+        confidence: if malformed {
+            confidence / 2.0
+        } else {
+            confidence
+        },
+        malformed,
+    });
+    html
+}
+
+impl Html {
+    /// Parses a document given as bytes in any encoding.
+    ///
+    /// The encoding comes from a byte order mark, else a `<meta charset>` or
+    /// `<meta http-equiv="Content-Type">` in the first 1024 bytes. Without either, the bytes
+    /// are read as UTF-8 if they are valid UTF-8 and as windows-1252 otherwise. Which one was
+    /// used is reported by [`Html::encoding_info`].
+    ///
+    /// ```
+    /// use scraper::{Html, Selector};
+    ///
+    /// // "Grüße" in ISO-8859-1, which decodes as its superset windows-1252.
+    /// let bytes = b"<meta charset=iso-8859-1><p>Gr\xFC\xDFe</p>";
+    /// let html = Html::parse_document_bytes(bytes);
+    /// let p = html.select_first(&Selector::parse("p").unwrap()).unwrap();
+    /// assert_eq!("Grüße", p.text().collect::<String>());
+    /// ```
+    pub fn parse_document_bytes(bytes: &[u8]) -> Self {
+        Self::parse_document_bytes_with(bytes, &ParseOptions::default())
+    }
+
+    /// Parses a document given as bytes, with options. [`ParseOptions::encoding`] forces
+    /// the encoding, for pages whose declarations are known to be wrong.
+    ///
+    /// ```
+    /// use scraper::encoding_rs::{KOI8_R, WINDOWS_1252};
+    /// use scraper::html::{EncodingSource, ParseOptions};
+    /// use scraper::Html;
+    ///
+    /// let bytes = b"<meta charset=windows-1252><p>\xF0\xD2\xC9\xD7\xC5\xD4</p>";
+    /// let html = Html::parse_document_bytes(bytes);
+    /// let info = html.encoding_info().unwrap();
+    /// assert_eq!((WINDOWS_1252, EncodingSource::Meta), (info.encoding, info.source));
+    ///
+    /// let options = ParseOptions {
+    ///     encoding: Some(KOI8_R),
+    ///     ..ParseOptions::default()
+    /// };
+    /// let html = Html::parse_document_bytes_with(bytes, &options);
+    /// assert_eq!("Привет", html.root_element().text().collect::<String>());
+    /// assert_eq!(EncodingSource::Override, html.encoding_info().unwrap().source);
+    /// ```
+    pub fn parse_document_bytes_with(bytes: &[u8], options: &ParseOptions) -> Self {
+        parse_bytes(bytes, None, options)
+    }
+
+    /// Parses a document the caller knows to be UTF-8, with no detection pass: a UTF-8
+    /// byte order mark is skipped and `<meta charset>` is ignored. Invalid sequences are
+    /// decoded as U+FFFD and reported as [`EncodingInfo::malformed`].
+    ///
+    /// As for every document decoded from bytes, [`Html::html`] returns the serializer
+    /// output as it is rather than detecting its encoding again.
+    ///
+    /// ```
+    /// use scraper::Html;
+    ///
+    /// let html = Html::parse_document_utf8("<meta charset=windows-1251><p>Привет</p>".as_bytes());
+    /// assert!(html.html().contains("<p>Привет</p>"));
+    /// ```
+    pub fn parse_document_utf8(bytes: &[u8]) -> Self {
+        let options = ParseOptions {
+            encoding: Some(UTF_8),
+            ..ParseOptions::default()
+        };
+        parse_bytes(bytes, None, &options)
+    }
+
+    /// Returns how the document was decoded, for documents parsed from bytes: the
+    /// encoding, where it came from and how confident the detection was. Documents parsed
+    /// from strings return `None`.
+    ///
+    /// ```
+    /// use scraper::encoding_rs::WINDOWS_1252;
+    /// use scraper::html::EncodingSource;
+    /// use scraper::Html;
+    ///
+    /// let html = Html::parse_document_bytes(b"<p>caf\xE9</p>");
+    /// let info = html.encoding_info().unwrap();
+    /// assert_eq!(WINDOWS_1252, info.encoding);
+    /// assert_eq!(EncodingSource::Fallback, info.source);
+    /// assert!(info.confidence < 0.5);
+    /// assert!(Html::parse_document("<p>café</p>").encoding_info().is_none());
+    /// ```
+    pub fn encoding_info(&self) -> Option<&EncodingInfo> {
+        self.encoding.as_ref()
+    }
+
+    /// Serializes the entire document as HTML in the given encoding, for writing pages back
+    /// out in the legacy encoding they were served in. Characters the encoding cannot
+    /// represent are written as numeric character references. `<meta charset>` declarations
+    /// are left as they are.
+    ///
+    /// Encodings that cannot be used for output, UTF-16 and replacement, produce UTF-8.
+    ///
+    /// ```
+    /// use scraper::encoding_rs::SHIFT_JIS;
+    /// use scraper::Html;
+    ///
+    /// let html = Html::parse_fragment("<p>日本 ☃</p>");
+    /// assert_eq!(
+    ///     b"<html><p>\x93\xFA\x96{ &#9731;</p></html>".to_vec(),
+    ///     html.html_encoded(SHIFT_JIS)
+    /// );
+    /// ```
+    pub fn html_encoded(&self, encoding: &'static Encoding) -> Vec<u8> {
+        let mut buf = Vec::new();
+        let _ = self.write_html(&mut buf);
+        let text = String::from_utf8_lossy(&buf);
+        let (bytes, _, _) = encoding.encode(&text);
+        bytes.into_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use encoding_rs::{SHIFT_JIS, UTF_16LE, UTF_8, WINDOWS_1251, WINDOWS_1252};
+
+    use super::{detect, EncodingSource};
+    use crate::html::{Html, ParseOptions};
+
+    fn sniff(bytes: &[u8]) -> (&'static encoding_rs::Encoding, usize) {
+        let (encoding, bom, _) = detect(bytes, None);
+        (encoding, bom)
+    }
+
+    #[test]
+    fn sniffing_order() {
+        assert_eq!((UTF_16LE, 2), sniff(b"\xFF\xFE<\x00p\x00"));
+        assert_eq!((UTF_8, 3), sniff(b"\xEF\xBB\xBF<meta charset=shift_jis>"));
+        let pragma = b"<!-- <meta charset=koi8-r> --><META HTTP-EQUIV='Content-Type' \
+                       CONTENT='text/html; charset=\"Shift_JIS\"'>";
+        assert_eq!((SHIFT_JIS, 0), sniff(pragma));
+        assert_eq!(
+            (WINDOWS_1252, 0),
+            sniff(b"<meta content='text/html; charset=shift_jis'>\xFF")
+        );
+        assert_eq!((UTF_8, 0), sniff(b"<meta charset=utf-16le>"));
+        assert_eq!((UTF_8, 0), sniff("<p>日本</p>".as_bytes()));
+    }
+
+    #[test]
+    fn encoded_output_round_trips() {
+        let bytes = b"<html><head><meta charset=\"windows-1251\"></head><body>\xCF\xF0\xE8\xE2\xE5\xF2</body></html>";
+        let html = Html::parse_document_bytes(bytes);
+        assert_eq!(bytes.to_vec(), html.html_encoded(WINDOWS_1251));
+        let mut utf8 = Vec::new();
+        html.write_html(&mut utf8).unwrap();
+        assert_eq!(utf8, html.html_encoded(UTF_16LE));
+    }
+
+    #[test]
+    fn reports_detection_and_override() {
+        let bytes = b"<meta charset=windows-1251><p>\xCF\xF0\xE8\xE2\xE5\xF2</p>";
+        let html = Html::parse_document_bytes(bytes);
+        let info = html.encoding_info().unwrap();
+        assert_eq!(
+            (WINDOWS_1251, EncodingSource::Meta),
+            (info.encoding, info.source)
+        );
+        assert!(!info.malformed);
+        assert!(html.html().contains("<p>Привет</p>"));
+
+        let options = ParseOptions {
+            encoding: Some(UTF_8),
+            ..ParseOptions::default()
+        };
+        let forced = Html::parse_document_bytes_with(bytes, &options);
+        let info = forced.encoding_info().unwrap();
+        assert_eq!(
+            (UTF_8, EncodingSource::Override),
+            (info.encoding, info.source)
+        );
+        assert!(info.malformed);
+        assert_eq!(0.5, info.confidence);
+
+        let bom = Html::parse_document_bytes_with(b"\xEF\xBB\xBF<p>x</p>", &options);
+        assert_eq!("x", bom.root_element().text().collect::<String>());
+    }
+}
@@ -0,0 +1,98 @@
+//! The fetch context of a document: where it came from, when and with what status.
+
+use std::time::SystemTime;
+
+use url::Url;
+
+use super::Html;
+
+/// How a document was fetched, kept on the [`Html`] so records derived from it carry
+/// where and when it came from.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FetchContext {
+    /// The URL the document was served from, after any redirects. It is the URL relative
+    /// links resolve against, unless the document has a `<base href>`.
+    pub url: Url,
+    /// The URL that was requested.
+    pub original_url: Url,
+    /// When the response was received.
+    pub fetched_at: SystemTime,
+    /// The HTTP status of the final response.
+    pub status: u16,
+}
+
+impl FetchContext {
+    /// Returns true if the request was redirected to another URL.
+    pub fn redirected(&self) -> bool {
+        self.url != self.original_url
+    }
+}
+
+impl Html {
+    /// Parses a string of HTML as a document fetched as `fetch` describes, and keeps the
+    /// context in the `fetch` field.
+    ///
+    /// ```
+    /// use std::time::SystemTime;
+    ///
+    /// use scraper::html::FetchContext;
+    /// use scraper::Html;
+    /// use url::Url;
+    ///
+    /// let fetch = FetchContext {
+    ///     url: Url::parse("https://example.com/docs/").unwrap(),
+    ///     original_url: Url::parse("http://example.com/docs").unwrap(),
+    ///     fetched_at: SystemTime::now(),
+    ///     status: 200,
+    /// };
+    /// let html = Html::parse_document_with_base(r#"<a href="intro">Intro</a>"#, fetch);
+    /// assert!(html.fetch.as_ref().unwrap().redirected());
+    /// assert_eq!(
+    ///     "https://example.com/docs/",
+    ///     html.document_base_url().unwrap().as_str()
+    /// );
+    /// ```
+    pub fn parse_document_with_base(document: &str, fetch: FetchContext) -> Self {
+        let mut html = Self::parse_document(document);
+        html.fetch = Some(fetch);
+        html
+    }
+
+    /// Returns the [base URL](Html::base_url) of the document against the URL it was
+    /// fetched from, or `None` without a fetch context.
+    pub fn document_base_url(&self) -> Option<Url> {
+        let fetch = self.fetch.as_ref()?;
+        Some(self.base_url(&fetch.url))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use url::Url;
+
+    use super::FetchContext;
+    use crate::html::Html;
+    use crate::rules::{Extract, Rule, RulePack};
+
+    #[test]
+    fn extractions_carry_the_fetch_context() {
+        let url = Url::parse("https://example.com/a").unwrap();
+        let fetch = FetchContext {
+            url: url.clone(),
+            original_url: url,
+            fetched_at: UNIX_EPOCH + Duration::from_secs(1_700_000_000),
+            status: 404,
+        };
+        let html = Html::parse_document_with_base("<h1>Not found</h1>", fetch.clone());
+        assert!(!fetch.redirected());
+
+        let mut pack = RulePack::new("page", "1");
+        pack.push(Rule::new("title", "h1", Extract::Text).unwrap())
+            .unwrap();
+        assert_eq!(Some(fetch), pack.execute(&html).fetch);
+        assert_eq!(None, pack.execute(&Html::parse_document("")).fetch);
+    }
+}
@@ -0,0 +1,316 @@
+//! Graph exports of the parsed tree for visualization.
+
+use std::fmt::Write;
+
+use ego_tree::NodeRef;
+
+use super::Html;
+use crate::node::Node;
+
+/// What each node is labelled with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NodeLabel {
+    /// The tag name only.
+    Tag,
+    /// The tag name with `#id` and `.class` suffixes, as in a selector.
+    #[default]
+    Selector,
+    /// The tag name with every attribute.
+    Attributes,
+}
+
+/// Options for graph exports.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GraphOptions {
+    /// Nodes deeper than this are dropped; their count is noted on the ancestor.
+    pub max_depth: Option<usize>,
+    /// How element nodes are labelled.
+    pub label: NodeLabel,
+    /// Include non-whitespace text nodes.
+    pub text: bool,
+    /// Text labels longer than this many characters are truncated.
+    pub max_text: usize,
+}
+
+impl Default for GraphOptions {
+    fn default() -> Self {
+        GraphOptions {
+            max_depth: None,
+            label: NodeLabel::default(),
+            text: true,
+            max_text: 24,
+        }
+    }
+}
+
+/// A flattened node ready to be written out by an exporter.
+struct GraphNode {
+    index: usize,
+    parent: Option<usize>,
+    label: String,
+    is_text: bool,
+    /// Descendants dropped because of `max_depth`.
+    hidden: usize,
+}
+
+/// Collapses whitespace and cuts `text` to at most `max` characters.
+pub(crate) fn truncate(text: &str, max: usize) -> String {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    match text.char_indices().nth(max) {
+        Some((idx, _)) => format!("{}…", &text[..idx]),
+        None => text,
+    }
+}
+
+/// Returns the display label of a node, or `None` if the exporters skip it.
+pub(crate) fn node_label(node: &Node, opts: &GraphOptions) -> Option<String> {
+    match node {
+        Node::Document => Some("#document".into()),
+        Node::Fragment => Some("#fragment".into()),
+        Node::Text(text) if opts.text && !text.trim().is_empty() => {
+            Some(truncate(text, opts.max_text))
+        }
+        Node::Element(element) => {
+            let mut label = element.name().to_owned();
+            match opts.label {
+                NodeLabel::Tag => {}
+                NodeLabel::Selector => {
+                    if let Some(id) = element.id() {
+                        let _ = write!(label, "#{}", id);
+                    }
+                    let mut classes: Vec<_> = element.classes().collect();
+                    classes.sort_unstable();
+                    for class in classes {
+                        let _ = write!(label, ".{}", class);
+                    }
+                }
+                NodeLabel::Attributes => {
+                    let mut attrs: Vec<_> = element.attrs().collect();
+                    attrs.sort_unstable();
+                    for (name, value) in attrs {
+                        let _ = write!(label, " {}=\"{}\"", name, truncate(value, opts.max_text));
+                    }
+                }
+            }
+            Some(label)
+        }
+        _ => None,
+    }
+}
+
+fn flatten(root: NodeRef<Node>, opts: &GraphOptions) -> Vec<GraphNode> {
+    fn visit(
+        node: NodeRef<Node>,
+        parent: Option<usize>,
+        depth: usize,
+        opts: &GraphOptions,
+        out: &mut Vec<GraphNode>,
+    ) {
+        let Some(label) = node_label(node.value(), opts) else {
+            return;
+        };
+        let index = out.len();
+        out.push(GraphNode {
+            index,
+            parent,
+            is_text: node.value().is_text(),
+            label,
+            hidden: 0,
+        });
+
+        if opts.max_depth.is_some_and(|max| depth >= max) {
+            out[index].hidden = node
+                .descendants()
+                .skip(1)
+                .filter(|d| node_label(d.value(), opts).is_some())
+                .count();
+            return;
+        }
+        for child in node.children() {
+            visit(child, Some(index), depth + 1, opts, out);
+        }
+    }
+
+    let mut out = Vec::new();
+    visit(root, None, 0, opts, &mut out);
+    out
+}
+
+fn escape_dot(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+impl Html {
+    /// Renders the tree as a Graphviz DOT digraph with default options.
+    pub fn to_dot(&self) -> String {
+        self.to_dot_with(&GraphOptions::default())
+    }
+
+    /// Renders the tree as a Graphviz DOT digraph.
+    ///
+    /// ```
+    /// use scraper::Html;
+    ///
+    /// let html = Html::parse_fragment(r#"<p class="x">hi</p>"#);
+    /// let dot = html.to_dot();
+    /// assert!(dot.starts_with("digraph dom {"));
+    /// assert!(dot.contains(r#"[label="p.x"]"#));
+    /// ```
+    pub fn to_dot_with(&self, opts: &GraphOptions) -> String {
+        let mut out = String::from("digraph dom {\n  node [shape=box];\n");
+        for node in flatten(self.tree.root(), opts) {
+            let mut label = escape_dot(&node.label);
+            if node.hidden > 0 {
+                let _ = write!(label, " (+{})", node.hidden);
+            }
+            let style = if node.is_text {
+                ", shape=plaintext"
+            } else {
+                ""
+            };
+            let _ = writeln!(out, "  n{} [label=\"{}\"{}];", node.index, label, style);
+            if let Some(parent) = node.parent {
+                let _ = writeln!(out, "  n{} -> n{};", parent, node.index);
+            }
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// Renders the tree as a GraphML document with default options.
+    pub fn to_graphml(&self) -> String {
+        self.to_graphml_with(&GraphOptions::default())
+    }
+
+    /// Renders the tree as a GraphML document. Nodes carry `label`, `kind` and `hidden` data.
+    pub fn to_graphml_with(&self, opts: &GraphOptions) -> String {
+        let mut out = String::from(concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n",
+            "  <key id=\"label\" for=\"node\" attr.name=\"label\" attr.type=\"string\"/>\n",
+            "  <key id=\"kind\" for=\"node\" attr.name=\"kind\" attr.type=\"string\"/>\n",
+            "  <key id=\"hidden\" for=\"node\" attr.name=\"hidden\" attr.type=\"int\"/>\n",
+            "  <graph id=\"dom\" edgedefault=\"directed\">\n",
+        ));
+        for node in flatten(self.tree.root(), opts) {
+            let kind = if node.is_text { "text" } else { "element" };
+            let _ = writeln!(
+                out,
+                "    <node id=\"n{}\"><data key=\"label\">{}</data><data key=\"kind\">{}</data><data key=\"hidden\">{}</data></node>",
+                node.index,
+                escape_xml(&node.label),
+                kind,
+                node.hidden
+            );
+            if let Some(parent) = node.parent {
+                let _ = writeln!(
+                    out,
+                    "    <edge source=\"n{}\" target=\"n{}\"/>",
+                    parent, node.index
+                );
+            }
+        }
+        out.push_str("  </graph>\n</graphml>\n");
+        out
+    }
+
+    /// Renders the tree as nested JSON with default options.
+    #[cfg(feature = "serde_json")]
+    pub fn to_json_tree(&self) -> serde_json::Value {
+        self.to_json_tree_with(&GraphOptions::default())
+    }
+
+    /// Renders the tree as nested JSON objects of the form
+    /// `{"label": ..., "children": [...]}`, with `"text": true` on text nodes and
+    /// `"hidden": n` on nodes whose descendants were cut by `max_depth`.
+    #[cfg(feature = "serde_json")]
+    pub fn to_json_tree_with(&self, opts: &GraphOptions) -> serde_json::Value {
+        use serde_json::{json, Map, Value};
+
+        let nodes = flatten(self.tree.root(), opts);
+        let mut children: Vec<Vec<usize>> = vec![Vec::new(); nodes.len()];
+        for node in &nodes {
+            if let Some(parent) = node.parent {
+                children[parent].push(node.index);
+            }
+        }
+
+        fn build(index: usize, nodes: &[GraphNode], children: &[Vec<usize>]) -> Value {
+            let node = &nodes[index];
+            let mut object = Map::new();
+            object.insert("label".into(), json!(node.label));
+            if node.is_text {
+                object.insert("text".into(), json!(true));
+            }
+            if node.hidden > 0 {
+                object.insert("hidden".into(), json!(node.hidden));
+            }
+            if !children[index].is_empty() {
+                let kids = children[index]
+                    .iter()
+                    .map(|&c| build(c, nodes, children))
+                    .collect();
+                object.insert("children".into(), Value::Array(kids));
+            }
+            Value::Object(object)
+        }
+
+        if nodes.is_empty() {
+            Value::Null
+        } else {
+            build(0, &nodes, &children)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GraphOptions, NodeLabel};
+    use crate::html::Html;
+
+    #[test]
+    fn dot_respects_depth_and_labels() {
+        let html = Html::parse_fragment(r#"<div id="main"><p>a <b>"b"</b></p></div>"#);
+        let opts = GraphOptions {
+            max_depth: Some(2),
+            label: NodeLabel::Selector,
+            ..GraphOptions::default()
+        };
+        let dot = html.to_dot_with(&opts);
+        assert!(dot.contains(r#"n2 [label="div#main (+4)"];"#), "{}", dot);
+        assert!(!dot.contains("label=\"p\""));
+
+        let dot = html.to_dot();
+        assert!(
+            dot.contains(r#"[label="\"b\"", shape=plaintext]"#),
+            "{}",
+            dot
+        );
+    }
+
+    #[test]
+    fn graphml_escapes_labels() {
+        let html = Html::parse_fragment("<p>a &lt; b</p>");
+        let graphml = html.to_graphml();
+        assert!(graphml.contains("<data key=\"label\">a &lt; b</data>"));
+        assert_eq!(4, graphml.matches("<node ").count());
+    }
+
+    #[cfg(feature = "serde_json")]
+    #[test]
+    fn json_tree_nests_children() {
+        let html = Html::parse_fragment("<ul><li>1</li></ul>");
+        let tree = html.to_json_tree();
+        assert_eq!("#fragment", tree["label"]);
+        let ul = &tree["children"][0]["children"][0];
+        assert_eq!("ul", ul["label"]);
+        assert_eq!("1", ul["children"][0]["children"][0]["label"]);
+    }
+}
@@ -0,0 +1,124 @@
+//! Node handles that stop resolving once their node leaves the document.
+
+use std::mem::{discriminant, Discriminant};
+
+use ego_tree::{NodeId, NodeMut, NodeRef};
+
+use super::Html;
+use crate::element_ref::ElementRef;
+use crate::node::Node;
+
+/// A checked reference to a node of an [`Html`] that outlives borrows of it.
+///
+/// A bare [`NodeId`] keeps resolving after its node is detached or its subtree removed, since
+/// the tree never frees or reuses its slots, so code that interleaves selection and mutation
+/// can silently work on nodes no longer in the document. A handle resolves only while the
+/// node is still attached under the document root, and only to a node of the same kind,
+/// element name included, as when it was taken.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NodeHandle {
+    id: NodeId,
+    kind: Discriminant<Node>,
+    /// The element name, or `None` for other nodes.
+    name: Option<String>,
+}
+
+impl NodeHandle {
+    fn of(node: NodeRef<Node>) -> Self {
+        NodeHandle {
+            id: node.id(),
+            kind: discriminant(node.value()),
+            name: node.value().as_element().map(|e| e.name().to_owned()),
+        }
+    }
+
+    /// Returns the ID of the node, without any checks.
+    pub fn id(&self) -> NodeId {
+        self.id
+    }
+
+    fn matches(&self, node: NodeRef<Node>) -> bool {
+        discriminant(node.value()) == self.kind
+            && node.value().as_element().map(|e| e.name()) == self.name.as_deref()
+    }
+}
+
+impl ElementRef<'_> {
+    /// Returns a handle to the element for [`Html::resolve`].
+    pub fn handle(&self) -> NodeHandle {
+        NodeHandle::of(**self)
+    }
+}
+
+impl Html {
+    /// Returns a handle to a node, or `None` if there is no such node.
+    pub fn handle(&self, id: NodeId) -> Option<NodeHandle> {
+        self.tree.get(id).map(NodeHandle::of)
+    }
+
+    /// Returns the node of a handle, or `None` if it has been detached from the document,
+    /// along with any ancestor, or no longer matches the handle.
+    ///
+    /// ```
+    /// use scraper::{Html, Selector};
+    ///
+    /// let mut html = Html::parse_fragment("<ul><li>One</li><li>Two</li></ul>");
+    /// let li = Selector::parse("li").unwrap();
+    /// let first = html.select_first(&li).unwrap().handle();
+    /// assert!(html.resolve(&first).is_some());
+    ///
+    /// let ul = Selector::parse("ul").unwrap();
+    /// let ul = html.select_first(&ul).unwrap().id();
+    /// html.tree.get_mut(ul).unwrap().detach();
+    /// assert!(html.resolve(&first).is_none());
+    /// ```
+    pub fn resolve(&self, handle: &NodeHandle) -> Option<NodeRef<'_, Node>> {
+        let node = self.tree.get(handle.id)?;
+        let root = node.ancestors().last().unwrap_or(node);
+        (root.id() == self.tree.root().id() && handle.matches(node)).then_some(node)
+    }
+
+    /// Returns the element of a handle, as [`Html::resolve`] does.
+    pub fn resolve_element(&self, handle: &NodeHandle) -> Option<ElementRef<'_>> {
+        self.resolve(handle).and_then(ElementRef::wrap)
+    }
+
+    /// Returns the node of a handle for mutation, as [`Html::resolve`] does.
+    pub fn resolve_mut(&mut self, handle: &NodeHandle) -> Option<NodeMut<'_, Node>> {
+        let id = self.resolve(handle)?.id();
+        self.tree.get_mut(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::html::Html;
+    use crate::node::Node;
+    use crate::selector::Selector;
+
+    #[test]
+    fn handles_detect_replaced_and_reattached_nodes() {
+        let mut html = Html::parse_fragment("<p>A</p><div></div>");
+        let p = html
+            .select_first(&Selector::parse("p").unwrap())
+            .unwrap()
+            .handle();
+        let text = html.tree.get(p.id()).unwrap().first_child().unwrap().id();
+        let text = html.handle(text).unwrap();
+
+        let div = html
+            .select_first(&Selector::parse("div").unwrap())
+            .unwrap()
+            .id();
+        html.tree.get_mut(div).unwrap().append_id(p.id());
+        assert_eq!(
+            Some("p"),
+            html.resolve_element(&p).map(|e| e.value().name())
+        );
+
+        *html.resolve_mut(&text).unwrap().value() = Node::Fragment;
+        assert!(html.resolve(&text).is_none());
+        html.tree.get_mut(p.id()).unwrap().detach();
+        assert!(html.resolve_mut(&p).is_none());
+    }
+}
@@ -0,0 +1,122 @@
+//! Content hashes of every subtree, for cheap comparison of documents.
+
+use std::collections::{HashMap, HashSet};
+
+use ego_tree::iter::Edge;
+use ego_tree::NodeId;
+
+use super::Html;
+use crate::node::Node;
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+
+/// Feeds bytes into a 64-bit FNV-1a hash, which is stable across platforms and releases.
+pub(crate) fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// A content hash of every node's subtree.
+///
+/// Two subtrees have the same hash when they have the same element names, attributes (in
+/// any order) and text, so unchanged regions of two versions of a page can be
+/// recognised without comparing them node by node.
+#[derive(Debug, Clone)]
+pub struct SubtreeHashes {
+    hashes: HashMap<NodeId, u64>,
+    root: u64,
+    all: HashSet<u64>,
+}
+
+impl SubtreeHashes {
+    /// Returns the hash of the subtree rooted at a node.
+    pub fn get(&self, node: NodeId) -> Option<u64> {
+        self.hashes.get(&node).copied()
+    }
+
+    /// Returns the hash of the whole document.
+    pub fn root(&self) -> u64 {
+        self.root
+    }
+
+    /// Returns true if some subtree of the document has this hash.
+    pub fn contains(&self, hash: u64) -> bool {
+        self.all.contains(&hash)
+    }
+}
+
+impl Html {
+    /// Hashes every subtree of the document.
+    ///
+    /// ```
+    /// use scraper::{Html, Selector};
+    ///
+    /// let old = Html::parse_document(r#"<p class="a" id="x">one</p><p>two</p>"#);
+    /// let new = Html::parse_document(r#"<p id="x" class="a">one</p><p>three</p>"#);
+    /// let (old_hashes, new_hashes) = (old.subtree_hashes(), new.subtree_hashes());
+    /// assert_ne!(old_hashes.root(), new_hashes.root());
+    ///
+    /// let first = new.select(&Selector::parse("p").unwrap()).next().unwrap();
+    /// assert!(old_hashes.contains(new_hashes.get(first.id()).unwrap()));
+    /// ```
+    pub fn subtree_hashes(&self) -> SubtreeHashes {
+        let mut hashes = HashMap::new();
+        // The hashes of the children of each open node.
+        let mut stack: Vec<Vec<u64>> = Vec::new();
+
+        for edge in self.tree.root().traverse() {
+            match edge {
+                Edge::Open(_) => stack.push(Vec::new()),
+                Edge::Close(node) => {
+                    let children = stack.pop().unwrap_or_default();
+                    let mut hash = match node.value() {
+                        Node::Element(element) => {
+                            let mut attrs: Vec<_> = element.attrs().collect();
+                            attrs.sort_unstable();
+                            let mut hash = fnv1a(FNV_OFFSET, b"e");
+                            hash = fnv1a(hash, element.name().as_bytes());
+                            for (name, value) in attrs {
+                                hash = fnv1a(hash, b"\0");
+                                hash = fnv1a(hash, name.as_bytes());
+                                hash = fnv1a(hash, b"=");
+                                hash = fnv1a(hash, value.as_bytes());
+                            }
+                            hash
+                        }
+                        Node::Text(text) => fnv1a(fnv1a(FNV_OFFSET, b"t"), text.as_bytes()),
+                        Node::Comment(comment) => {
+                            fnv1a(fnv1a(FNV_OFFSET, b"c"), comment.as_bytes())
+                        }
+                        _ => fnv1a(FNV_OFFSET, b"n"),
+                    };
+                    for child in children {
+                        hash = fnv1a(hash, &child.to_le_bytes());
+                    }
+                    hashes.insert(node.id(), hash);
+                    if let Some(parent) = stack.last_mut() {
+                        parent.push(hash);
+                    }
+                }
+            }
+        }
+
+        let root = hashes[&self.tree.root().id()];
+        let all = hashes.values().copied().collect();
+        SubtreeHashes { hashes, root, all }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::html::Html;
+
+    #[test]
+    fn equal_markup_hashes_equal() {
+        let a = Html::parse_document("<div><b>x</b>y</div>");
+        let b = Html::parse_document("<div><b>x</b>y</div>");
+        let c = Html::parse_document("<div><b>xy</b></div>");
+        assert_eq!(a.subtree_hashes().root(), b.subtree_hashes().root());
+        assert_ne!(a.subtree_hashes().root(), c.subtree_hashes().root());
+    }
+}
@@ -0,0 +1,348 @@
+//! Lookup tables of elements by ID, class and attribute that follow document changes.
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex, Weak};
+
+use ego_tree::{NodeId, NodeRef};
+use selectors::attr::CaseSensitivity;
+
+use super::observe::{Mutation, Observer};
+use super::{is_attached, Html};
+use crate::element_ref::ElementRef;
+use crate::node::Node;
+
+/// How an [`ElementIndex`] catches up with changes to its document.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum IndexPolicy {
+    /// Re-indexes only the changed nodes and their subtrees at the next query. Results
+    /// after an update are not necessarily in document order.
+    #[default]
+    Update,
+    /// Rebuilds the whole index at the next query after any change. Results are always in
+    /// document order.
+    Rebuild,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Key {
+    Id(String),
+    Class(String),
+    Attribute(String),
+}
+
+/// Changes not yet applied to the index.
+#[derive(Debug, Default)]
+struct Journal {
+    touched: Vec<NodeId>,
+    stale: bool,
+}
+
+/// Elements of a document by ID, class and attribute name, built by [`Html::index`].
+///
+/// The index learns of changes through [`Html::on_mutation`], so every change made through
+/// the mutation methods of `Html` is applied before the next query, as the
+/// [`IndexPolicy`] says. Changes made through `tree` directly must be reported with
+/// [`Html::notify`] or [`ElementIndex::invalidate`]. If the observer is removed with
+/// [`Html::clear_observers`], every query rebuilds the index. Results are checked against
+/// the document before they are returned, so they never include an element that is
+/// detached or no longer matches.
+pub struct ElementIndex {
+    policy: IndexPolicy,
+    buckets: HashMap<Key, Vec<NodeId>>,
+    keys: HashMap<NodeId, Vec<Key>>,
+    journal: Arc<Mutex<Journal>>,
+    observer: Observer,
+}
+
+impl fmt::Debug for ElementIndex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ElementIndex")
+            .field("policy", &self.policy)
+            .field("buckets", &self.buckets)
+            .field("journal", &self.journal)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ElementIndex {
+    /// Returns the policy the index follows.
+    pub fn policy(&self) -> IndexPolicy {
+        self.policy
+    }
+
+    /// Returns true if changes are waiting to be applied at the next query.
+    pub fn is_stale(&self) -> bool {
+        self.journal
+            .lock()
+            .map_or(true, |journal| journal.stale || !journal.touched.is_empty())
+    }
+
+    /// Marks the whole index stale, so the next query rebuilds it.
+    pub fn invalidate(&self) {
+        if let Ok(mut journal) = self.journal.lock() {
+            journal.stale = true;
+        }
+    }
+
+    /// Returns the elements with this ID.
+    pub fn by_id<'a>(&mut self, html: &'a Html, id: &str) -> Vec<ElementRef<'a>> {
+        self.query(html, Key::Id(id.to_owned()), |e| e.id() == Some(id))
+    }
+
+    /// Returns the elements with this class, compared case-sensitively.
+    pub fn by_class<'a>(&mut self, html: &'a Html, class: &str) -> Vec<ElementRef<'a>> {
+        self.query(html, Key::Class(class.to_owned()), |e| {
+            e.has_class(class, CaseSensitivity::CaseSensitive)
+        })
+    }
+
+    /// Returns the elements with this attribute, whatever its value.
+    pub fn with_attribute<'a>(&mut self, html: &'a Html, name: &str) -> Vec<ElementRef<'a>> {
+        self.query(html, Key::Attribute(name.to_owned()), |e| {
+            e.attr(name).is_some()
+        })
+    }
+
+    fn query<'a>(
+        &mut self,
+        html: &'a Html,
+        key: Key,
+        matches: impl Fn(&crate::node::Element) -> bool,
+    ) -> Vec<ElementRef<'a>> {
+        self.sync(html);
+        self.buckets
+            .get(&key)
+            .into_iter()
+            .flatten()
+            .filter_map(|&id| html.tree.get(id))
+            .filter(|&node| is_attached(html, node))
+            .filter_map(ElementRef::wrap)
+            .filter(|element| matches(element.value()))
+            .collect()
+    }
+
+    /// Applies the journal to the index, as the policy says.
+    fn sync(&mut self, html: &Html) {
+        let Journal { touched, stale } = match self.journal.lock() {
+            Ok(mut journal) => std::mem::take(&mut *journal),
+            Err(_) => Journal {
+                touched: Vec::new(),
+                stale: true,
+            },
+        };
+        let rebuild = stale
+            || !html.observers.contains(&self.observer)
+            || (self.policy == IndexPolicy::Rebuild && !touched.is_empty());
+        if rebuild {
+            self.buckets.clear();
+            self.keys.clear();
+            self.insert(html.tree.root());
+            return;
+        }
+        for id in touched {
+            if let Some(node) = html.tree.get(id) {
+                for descendant in node.descendants() {
+                    self.remove(descendant.id());
+                }
+                if is_attached(html, node) {
+                    self.insert(node);
+                }
+            }
+        }
+    }
+
+    fn insert(&mut self, node: NodeRef<Node>) {
+        for descendant in node.descendants() {
+            let Some(element) = descendant.value().as_element() else {
+                continue;
+            };
+            let keys: Vec<Key> = element
+                .id()
+                .map(|id| Key::Id(id.to_owned()))
+                .into_iter()
+                .chain(element.classes().map(|class| Key::Class(class.to_owned())))
+                .chain(
+                    element
+                        .attrs()
+                        .map(|(name, _)| Key::Attribute(name.to_owned())),
+                )
+                .collect();
+            for key in &keys {
+                self.buckets
+                    .entry(key.clone())
+                    .or_default()
+                    .push(descendant.id());
+            }
+            self.keys.insert(descendant.id(), keys);
+        }
+    }
+
+    fn remove(&mut self, id: NodeId) {
+        for key in self.keys.remove(&id).into_iter().flatten() {
+            if let Some(bucket) = self.buckets.get_mut(&key) {
+                bucket.retain(|&node| node != id);
+                if bucket.is_empty() {
+                    self.buckets.remove(&key);
+                }
+            }
+        }
+    }
+}
+
+impl Html {
+    /// Indexes the elements of the document by ID, class and attribute name, and registers
+    /// an observer that keeps the index in step with later changes.
+    ///
+    /// ```
+    /// use scraper::html::IndexPolicy;
+    /// use scraper::Html;
+    ///
+    /// let mut html = Html::parse_fragment(r#"<p class="note">One</p><p>Two</p>"#);
+    /// let mut index = html.index(IndexPolicy::Update);
+    /// let second = html.root_element().last_child().unwrap().id();
+    ///
+    /// html.set_attr(second, "class", "note");
+    /// assert!(index.is_stale());
+    /// let notes = index.by_class(&html, "note");
+    /// let text: Vec<String> = notes.iter().map(|p| p.text().collect()).collect();
+    /// assert_eq!(vec!["One", "Two"], text);
+    ///
+    /// html.detach(second);
+    /// assert_eq!(1, index.by_class(&html, "note").len());
+    /// ```
+    pub fn index(&mut self, policy: IndexPolicy) -> ElementIndex {
+        let journal = Arc::new(Mutex::new(Journal::default()));
+        let sink: Weak<Mutex<Journal>> = Arc::downgrade(&journal);
+        let owner: Weak<dyn Any + Send + Sync> = sink.clone();
+        let observer: Observer = Arc::new(move |mutation: &Mutation| {
+            let Some(shared) = sink.upgrade() else {
+                return;
+            };
+            if let Ok(mut journal) = shared.lock() {
+                journal.touched.push(match *mutation {
+                    Mutation::Attached { node, .. }
+                    | Mutation::Detached { node, .. }
+                    | Mutation::AttributeChanged { node, .. } => node,
+                });
+            };
+        });
+        self.observers.push_owned(Arc::clone(&observer), owner);
+        let mut index = ElementIndex {
+            policy,
+            buckets: HashMap::new(),
+            keys: HashMap::new(),
+            journal,
+            observer,
+        };
+        index.insert(self.tree.root());
+        index
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IndexPolicy;
+    use crate::html::{Html, MergeStrategy, Mutation};
+    use crate::node::{Element, Node};
+
+    #[test]
+    fn indexes_follow_attached_subtrees_and_unobserved_edits() {
+        for policy in [IndexPolicy::Update, IndexPolicy::Rebuild] {
+            let mut html = Html::parse_fragment(r#"<div id="a"><span data-x="1"></span></div>"#);
+            let mut index = html.index(policy);
+            let div = html.root_element().first_child().unwrap().id();
+            assert_eq!(1, index.with_attribute(&html, "data-x").len());
+
+            html.detach(div);
+            assert!(index.by_id(&html, "a").is_empty());
+            assert!(index.with_attribute(&html, "data-x").is_empty());
+
+            let root = html.root_element().id();
+            html.tree.get_mut(root).unwrap().append_id(div);
+            assert!(index.by_id(&html, "a").is_empty());
+            html.notify(&Mutation::Attached {
+                node: div,
+                parent: root,
+            });
+            assert_eq!(1, index.with_attribute(&html, "data-x").len());
+
+            let mut b = Element::new(
+                html5ever::QualName::new(None, ns!(html), local_name!("b")),
+                Vec::new(),
+            );
+            b.set_attr("id", "a");
+            html.tree.get_mut(root).unwrap().append(Node::Element(b));
+            index.invalidate();
+            assert_eq!(2, index.by_id(&html, "a").len());
+
+            html.clear_observers();
+            html.remove_attr(div, "id");
+            assert_eq!(1, index.by_id(&html, "a").len());
+        }
+    }
+
+    #[test]
+    fn index_follows_generated_heading_ids() {
+        let mut html = Html::parse_document("<h2>Intro</h2>");
+        let mut index = html.index(IndexPolicy::Update);
+        assert!(index.by_id(&html, "intro").is_empty());
+        html.generate_toc(3);
+        assert!(index.is_stale());
+        assert_eq!(1, index.by_id(&html, "intro").len());
+        assert!(!index.is_stale());
+    }
+
+    #[test]
+    fn index_follows_highlights() {
+        let mut html = Html::parse_document(r#"<p class="x">a cat</p>"#);
+        let mut index = html.index(IndexPolicy::Update);
+        assert_eq!(1, index.by_class(&html, "x").len());
+        assert_eq!(1, html.highlight(&["cat"], "mark"));
+        assert!(index.is_stale());
+        assert_eq!(1, index.by_class(&html, "x").len());
+        assert!(!index.is_stale());
+    }
+
+    #[test]
+    fn index_follows_resolved_lazy_images() {
+        let mut html = Html::parse_document(r#"<img data-src="/a.jpg">"#);
+        let mut index = html.index(IndexPolicy::Update);
+        assert!(index.with_attribute(&html, "src").is_empty());
+        html.resolve_lazy_images();
+        assert!(index.is_stale());
+        assert_eq!(1, index.with_attribute(&html, "src").len());
+        assert!(!index.is_stale());
+    }
+
+    #[test]
+    fn index_follows_merged_blocks() {
+        let mut html = Html::parse_document("<h1>T</h1><p>One.</p><p>Three.</p>");
+        let amp = Html::parse_document(
+            r#"<h1>T</h1><p>One.</p><p class="new">Two <b id="b">x</b></p><p>Three.</p>"#,
+        );
+        let mut index = html.index(IndexPolicy::Update);
+        assert!(index.by_class(&html, "new").is_empty());
+        assert_eq!(1, html.merge_variant(&amp, MergeStrategy::FillGaps));
+        assert!(index.is_stale());
+        assert_eq!(1, index.by_class(&html, "new").len());
+        assert_eq!(1, index.by_id(&html, "b").len());
+        assert!(!index.is_stale());
+    }
+
+    #[test]
+    fn dropped_indexes_are_unregistered() {
+        let mut html = Html::parse_fragment("<p>One</p>");
+        for _ in 0..10 {
+            drop(html.index(IndexPolicy::Update));
+        }
+        let index = html.index(IndexPolicy::Update);
+        assert_eq!(1, html.observers.len());
+        drop(index);
+        html.on_mutation(|_| {});
+        assert_eq!(1, html.observers.len());
+    }
+}
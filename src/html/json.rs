@@ -0,0 +1,235 @@
+//! A nested JSON form of the DOM, for tools outside Rust.
+//!
+//! Each node is an object:
+//!
+//! - the root: `{"children": [...]}`, with `"fragment": true` for fragments;
+//! - elements: `{"tag": "p", "attrs": {"class": "intro"}, "children": [...]}`, where
+//!   `attrs` and `children` are left out when empty;
+//! - text: `{"text": "..."}`;
+//! - comments: `{"comment": "..."}`;
+//! - doctypes: `{"doctype": "html"}`, with `publicId` and `systemId` when set.
+//!
+//! HTML tag names are lowercase and attributes are keyed by their qualified name, such as
+//! `xlink:href`. Namespaces are not written: on import, `svg` and `math` elements and their
+//! descendants are put in the SVG and MathML namespaces, as the parser would. Processing
+//! instructions are left out, and template contents are written as children of the
+//! template.
+
+use ego_tree::{NodeId, NodeRef, Tree};
+use serde_json::{Map, Value};
+
+use super::cdp::{child_namespace, doctype, document, element, text};
+use super::Html;
+use crate::error::DomJsonError;
+use crate::node::{Comment, Node};
+
+/// Writes a node to `out`. Fragments below the root, which hold template contents and
+/// stand in for dropped comments, are replaced by their children.
+fn push(node: NodeRef<Node>, out: &mut Vec<Value>) {
+    let mut object = Map::new();
+    match node.value() {
+        Node::Document | Node::Fragment if node.parent().is_some() => {
+            node.children().for_each(|child| push(child, out));
+            return;
+        }
+        Node::Document => {}
+        Node::Fragment => {
+            object.insert("fragment".into(), Value::Bool(true));
+        }
+        Node::Doctype(doctype) => {
+            object.insert("doctype".into(), doctype.name().into());
+            for (key, value) in [
+                ("publicId", doctype.public_id()),
+                ("systemId", doctype.system_id()),
+            ] {
+                if !value.is_empty() {
+                    object.insert(key.into(), value.into());
+                }
+            }
+        }
+        Node::Comment(comment) => {
+            object.insert("comment".into(), (**comment).into());
+        }
+        Node::Text(text) => {
+            object.insert("text".into(), (**text).into());
+        }
+        Node::Element(element) => {
+            object.insert("tag".into(), element.name().into());
+            let attrs: Map<String, Value> = element
+                .attrs
+                .iter()
+                .map(|(name, value)| {
+                    let prefix = if name.ns == ns!(xml) {
+                        "xml:"
+                    } else if name.ns == ns!(xlink) {
+                        "xlink:"
+                    } else if name.ns == ns!(xmlns) && name.local != local_name!("xmlns") {
+                        "xmlns:"
+                    } else {
+                        ""
+                    };
+                    (format!("{}{}", prefix, name.local), (**value).into())
+                })
+                .collect();
+            if !attrs.is_empty() {
+                object.insert("attrs".into(), Value::Object(attrs));
+            }
+        }
+        Node::ProcessingInstruction(_) => return,
+    }
+    let mut children = Vec::new();
+    node.children().for_each(|child| push(child, &mut children));
+    if !children.is_empty() {
+        object.insert("children".into(), Value::Array(children));
+    }
+    out.push(Value::Object(object));
+}
+
+fn append(
+    tree: &mut Tree<Node>,
+    parent: NodeId,
+    value: &Value,
+    pointer: &str,
+) -> Result<(), DomJsonError> {
+    let invalid = || DomJsonError::InvalidNode(pointer.to_owned());
+    let object = value.as_object().ok_or_else(invalid)?;
+    let str_field = |key: &str| object.get(key).and_then(Value::as_str);
+
+    let node = if let Some(tag) = str_field("tag") {
+        let parent_ns = match tree.get(parent).map(|p| p.value()) {
+            Some(Node::Element(e)) => child_namespace(e),
+            _ => ns!(html),
+        };
+        let attrs = match object.get("attrs") {
+            None => Vec::new(),
+            Some(attrs) => attrs
+                .as_object()
+                .ok_or_else(invalid)?
+                .iter()
+                .map(|(name, value)| Some((name.clone(), value.as_str()?.to_owned())))
+                .collect::<Option<_>>()
+                .ok_or_else(invalid)?,
+        };
+        Node::Element(element(tag, &parent_ns, attrs))
+    } else if let Some(value) = str_field("text") {
+        text(value)
+    } else if let Some(value) = str_field("comment") {
+        Node::Comment(Comment {
+            comment: value.into(),
+        })
+    } else if let Some(name) = str_field("doctype") {
+        doctype(
+            name,
+            str_field("publicId").unwrap_or_default(),
+            str_field("systemId").unwrap_or_default(),
+        )
+    } else {
+        return Err(invalid());
+    };
+    let id = tree.get_mut(parent).ok_or_else(invalid)?.append(node).id();
+    children(tree, id, object, pointer)
+}
+
+fn children(
+    tree: &mut Tree<Node>,
+    parent: NodeId,
+    object: &Map<String, Value>,
+    pointer: &str,
+) -> Result<(), DomJsonError> {
+    let Some(children) = object.get("children") else {
+        return Ok(());
+    };
+    let children = children
+        .as_array()
+        .ok_or_else(|| DomJsonError::InvalidNode(pointer.to_owned()))?;
+    for (i, child) in children.iter().enumerate() {
+        append(tree, parent, child, &format!("{}/children/{}", pointer, i))?;
+    }
+    Ok(())
+}
+
+impl Html {
+    /// Writes the document in the nested JSON form described in [`Html::to_json_value`].
+    pub fn to_json(&self) -> String {
+        self.to_json_value().to_string()
+    }
+
+    /// Returns the document as nested JSON objects with `tag`, `attrs`, `children`, `text`,
+    /// `comment` and `doctype` fields, the form DOM-to-JSON tools in browsers produce, so
+    /// JavaScript code can work on documents parsed here. Keys are in a stable order.
+    ///
+    /// ```
+    /// use scraper::Html;
+    ///
+    /// let html = Html::parse_fragment(r#"<p class="intro">Hi <b>there</b></p>"#);
+    /// assert_eq!(
+    ///     r#"{"children":[{"children":[{"attrs":{"class":"intro"},"children":[{"text":"Hi "},{"children":[{"text":"there"}],"tag":"b"}],"tag":"p"}],"tag":"html"}],"fragment":true}"#,
+    ///     html.to_json()
+    /// );
+    /// ```
+    pub fn to_json_value(&self) -> Value {
+        let mut out = Vec::new();
+        push(self.tree.root(), &mut out);
+        out.pop().unwrap_or_default()
+    }
+
+    /// Builds a document from its nested JSON form, as written by [`Html::to_json`].
+    ///
+    /// Each level of the document takes two levels of JSON nesting, and the JSON parser
+    /// rejects input nested more than 128 levels deep. Deeper documents can be read with
+    /// [`Html::from_json_value`] from a value parsed without that limit.
+    ///
+    /// ```
+    /// use scraper::{Html, Selector};
+    ///
+    /// let json = r#"{"children": [{"tag": "svg", "children": [{"tag": "circle", "attrs": {"r": "4"}}]}]}"#;
+    /// let html = Html::from_json(json).unwrap();
+    /// let circle = html.select_first(&Selector::parse("svg circle").unwrap()).unwrap();
+    /// assert_eq!(Some("4"), circle.value().attr("r"));
+    /// ```
+    pub fn from_json(json: &str) -> Result<Self, DomJsonError> {
+        let value: Value =
+            serde_json::from_str(json).map_err(|err| DomJsonError::Decode(err.to_string()))?;
+        Self::from_json_value(&value)
+    }
+
+    /// Builds a document from its nested JSON form, as returned by [`Html::to_json_value`].
+    pub fn from_json_value(value: &Value) -> Result<Self, DomJsonError> {
+        let object = value
+            .as_object()
+            .ok_or_else(|| DomJsonError::InvalidNode(String::new()))?;
+        let root = match object.get("fragment").and_then(Value::as_bool) {
+            Some(true) => Node::Fragment,
+            _ => Node::Document,
+        };
+        let mut tree = Tree::new(root);
+        let root_id = tree.root().id();
+        children(&mut tree, root_id, object, "")?;
+        Ok(document(tree))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::DomJsonError;
+    use crate::html::Html;
+
+    #[test]
+    fn round_trips_and_reports_invalid_nodes() {
+        let source = r#"<!DOCTYPE html><html lang="en"><head></head><body><svg xlink:href="x"><foreignObject><p>A</p></foreignObject></svg></body></html>"#;
+        let html = Html::parse_document(source);
+        let restored = Html::from_json(&html.to_json()).unwrap();
+        assert_eq!(source, restored.html());
+        assert_eq!(html.to_json(), restored.to_json());
+
+        let comment = r#"{"fragment": true, "children": [{"comment": "c"}, {"tag": "template", "children": [{"tag": "td"}]}]}"#;
+        let comment = Html::from_json(comment).unwrap();
+        assert_eq!("<!--c--><template><td></td></template>", comment.html());
+
+        let bad = r#"{"children": [{"tag": "div", "children": [{"text": "a"}, {"bogus": 1}]}]}"#;
+        assert_eq!(
+            Err(DomJsonError::InvalidNode("/children/0/children/1".into())),
+            Html::from_json(bad).map(|_| ())
+        );
+    }
+}
@@ -0,0 +1,232 @@
+//! Layout boxes supplied by a headless browser, joined to the elements they were measured
+//! on.
+//!
+//! A box is stored on its element as a [`BOUNDS_ATTRIBUTE`], which
+//! [`Html::from_cdp_snapshot`](super::Html::from_cdp_snapshot) writes for snapshots with
+//! layout and a browser script can set itself before the page is serialized. Boxes measured
+//! separately are attached with [`Html::set_layout_box`], or with
+//! [`Html::apply_layout_map`] from the JSON map a script returned.
+
+use ego_tree::NodeId;
+
+use super::Html;
+use crate::element_ref::ElementRef;
+#[cfg(feature = "serde_json")]
+use crate::error::LayoutError;
+
+/// The attribute recording whether an element was rendered with a non-empty layout box,
+/// `"true"` or `"false"`. Only `DOMSnapshot.captureSnapshot` payloads carry layout.
+pub const VISIBLE_ATTRIBUTE: &str = "data-cdp-visible";
+
+/// The attribute recording the layout box of a rendered element as `x,y,width,height` in
+/// CSS pixels.
+pub const BOUNDS_ATTRIBUTE: &str = "data-cdp-bounds";
+
+/// The layout box of an element in CSS pixels, relative to the top left of the page.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LayoutBox {
+    /// Distance of the left edge from the left of the page.
+    pub x: f64,
+    /// Distance of the top edge from the top of the page.
+    pub y: f64,
+    /// Width of the box.
+    pub width: f64,
+    /// Height of the box.
+    pub height: f64,
+}
+
+impl LayoutBox {
+    /// Returns true if the box has no area, as for elements that are not rendered.
+    pub fn is_empty(&self) -> bool {
+        self.width <= 0.0 || self.height <= 0.0
+    }
+
+    /// Returns true if the box contains a point, such as a click position.
+    pub fn contains(&self, x: f64, y: f64) -> bool {
+        x >= self.x && x < self.x + self.width && y >= self.y && y < self.y + self.height
+    }
+
+    /// Returns true if the boxes overlap, such as an element and the viewport.
+    pub fn intersects(&self, other: &LayoutBox) -> bool {
+        self.x < other.x + other.width
+            && other.x < self.x + self.width
+            && self.y < other.y + other.height
+            && other.y < self.y + self.height
+    }
+
+    /// Parses the `x,y,width,height` form of a [`BOUNDS_ATTRIBUTE`].
+    fn parse(value: &str) -> Option<Self> {
+        let mut numbers = value.split(',').map(|n| n.trim().parse::<f64>());
+        let layout = LayoutBox {
+            x: numbers.next()?.ok()?,
+            y: numbers.next()?.ok()?,
+            width: numbers.next()?.ok()?,
+            height: numbers.next()?.ok()?,
+        };
+        numbers.next().is_none().then_some(layout)
+    }
+
+    /// Reads a box from a JSON array `[x, y, width, height]` or an object with `x`, `y`,
+    /// `width` and `height` fields.
+    #[cfg(feature = "serde_json")]
+    fn from_json(value: &serde_json::Value) -> Option<Self> {
+        let numbers: Vec<f64> = match value {
+            serde_json::Value::Array(items) => {
+                items.iter().map(|n| n.as_f64()).collect::<Option<_>>()?
+            }
+            serde_json::Value::Object(fields) => ["x", "y", "width", "height"]
+                .iter()
+                .map(|&name| fields.get(name).and_then(serde_json::Value::as_f64))
+                .collect::<Option<_>>()?,
+            _ => return None,
+        };
+        match numbers[..] {
+            [x, y, width, height] => Some(LayoutBox {
+                x,
+                y,
+                width,
+                height,
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl ElementRef<'_> {
+    /// Returns the layout box recorded on the element, if it has a well-formed
+    /// [`BOUNDS_ATTRIBUTE`].
+    ///
+    /// ```
+    /// use scraper::{Html, Selector};
+    ///
+    /// let html = Html::parse_fragment(r#"<button data-cdp-bounds="8,30,120.5,24">Buy</button>"#);
+    /// let button = html.select_first(&Selector::parse("button").unwrap()).unwrap();
+    /// let layout = button.layout_box().unwrap();
+    /// assert_eq!(120.5, layout.width);
+    /// assert!(layout.contains(10.0, 40.0));
+    /// ```
+    pub fn layout_box(&self) -> Option<LayoutBox> {
+        LayoutBox::parse(self.value().attr(BOUNDS_ATTRIBUTE)?)
+    }
+}
+
+impl Html {
+    /// Records the layout box of an element as its [`BOUNDS_ATTRIBUTE`], along with a
+    /// [`VISIBLE_ATTRIBUTE`] telling whether the box has an area. Does nothing for other
+    /// nodes.
+    pub fn set_layout_box(&mut self, node_id: NodeId, layout: LayoutBox) {
+        let bounds = [layout.x, layout.y, layout.width, layout.height].map(|n| n.to_string());
+        self.set_attr(node_id, BOUNDS_ATTRIBUTE, &bounds.join(","));
+        self.set_attr(
+            node_id,
+            VISIBLE_ATTRIBUTE,
+            if layout.is_empty() { "false" } else { "true" },
+        );
+    }
+
+    /// Attaches layout boxes measured by a browser script, from a JSON object mapping the
+    /// value of `key_attribute` on an element to its box. Boxes are arrays
+    /// `[x, y, width, height]` or objects with those fields, as `getBoundingClientRect`
+    /// returns. Returns the number of elements given a box; keys matching no element are
+    /// ignored, and every element with a matching key gets the box.
+    ///
+    /// ```
+    /// use scraper::{Html, Selector};
+    ///
+    /// let mut html = Html::parse_fragment(r#"<p data-node="1">A</p><p data-node="2">B</p>"#);
+    /// let boxes = r#"{"1": [0, 0, 300, 20], "2": {"x": 0, "y": 20, "width": 0, "height": 0}}"#;
+    /// assert_eq!(2, html.apply_layout_map(boxes, "data-node").unwrap());
+    ///
+    /// let hidden = Selector::parse(r#"p[data-cdp-visible="false"]"#).unwrap();
+    /// let hidden = html.select_first(&hidden).unwrap();
+    /// assert_eq!("B", hidden.inner_html());
+    /// assert_eq!(Some(20.0), hidden.layout_box().map(|b| b.y));
+    /// ```
+    #[cfg(feature = "serde_json")]
+    pub fn apply_layout_map(
+        &mut self,
+        json: &str,
+        key_attribute: &str,
+    ) -> Result<usize, LayoutError> {
+        let value: serde_json::Value =
+            serde_json::from_str(json).map_err(|err| LayoutError::Decode(err.to_string()))?;
+        let map = value.as_object().ok_or(LayoutError::NotAMap)?;
+
+        let mut updates = Vec::new();
+        for element in self.tree.nodes().filter_map(ElementRef::wrap) {
+            let Some(key) = element.value().attr(key_attribute) else {
+                continue;
+            };
+            if let Some(value) = map.get(key) {
+                let layout = LayoutBox::from_json(value)
+                    .ok_or_else(|| LayoutError::InvalidBox(key.to_owned()))?;
+                updates.push((element.id(), layout));
+            }
+        }
+        for &(id, layout) in &updates {
+            self.set_layout_box(id, layout);
+        }
+        Ok(updates.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LayoutBox;
+    use crate::element_ref::ElementRef;
+    use crate::html::Html;
+    use crate::selector::Selector;
+
+    #[test]
+    fn boxes_round_trip_through_attributes() {
+        let mut html = Html::parse_fragment(r#"<a data-cdp-bounds="1, 2,3">x</a><b>y</b>"#);
+        let a = Selector::parse("a").unwrap();
+        assert_eq!(None, html.select_first(&a).unwrap().layout_box());
+
+        let b = html
+            .select_first(&Selector::parse("b").unwrap())
+            .unwrap()
+            .id();
+        let layout = LayoutBox {
+            x: -4.0,
+            y: 1200.25,
+            width: 50.0,
+            height: 10.0,
+        };
+        html.set_layout_box(b, layout);
+        let b = html.tree.get(b).and_then(ElementRef::wrap).unwrap();
+        assert_eq!(Some(layout), b.layout_box());
+        assert_eq!(Some("true"), b.value().attr(super::VISIBLE_ATTRIBUTE));
+
+        let viewport = LayoutBox {
+            width: 800.0,
+            height: 600.0,
+            ..LayoutBox::default()
+        };
+        assert!(!layout.intersects(&viewport));
+        assert!(layout.intersects(&LayoutBox {
+            y: 1000.0,
+            ..viewport
+        }));
+    }
+
+    #[cfg(feature = "serde_json")]
+    #[test]
+    fn invalid_maps_are_rejected() {
+        use crate::error::LayoutError;
+
+        let mut html = Html::parse_fragment(r#"<p id="a">A</p>"#);
+        assert_eq!(Ok(0), html.apply_layout_map(r#"{"b": [0, 0, 1, 1]}"#, "id"));
+        assert_eq!(
+            Err(LayoutError::InvalidBox("a".into())),
+            html.apply_layout_map(r#"{"a": [0, 0, 1]}"#, "id")
+        );
+        assert_eq!(Err(LayoutError::NotAMap), html.apply_layout_map("[]", "id"));
+        assert!(html
+            .root_element()
+            .select(&Selector::parse("[data-cdp-bounds]").unwrap())
+            .next()
+            .is_none());
+    }
+}
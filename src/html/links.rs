@@ -0,0 +1,496 @@
+//! Links of a document and their statistics.
+
+use std::collections::{HashMap, HashSet};
+
+use url::Url;
+
+use super::Html;
+use crate::discovery::has_rel;
+use crate::element_ref::ElementRef;
+use crate::selector::Selector;
+use crate::url_utils::{normalize, UrlPolicy};
+
+lazy_static! {
+    static ref LINKS: Selector = Selector::parse("a[href], area[href]").unwrap();
+    static ref BASE: Selector = Selector::parse("base[href]").unwrap();
+}
+
+/// The attributes holding a URL, or a list of URLs for `ping`, `srcset` and
+/// `imagesrcset`, in the order [`Html::links`] reports them for an element.
+const URL_ATTRIBUTES: &[&str] = &[
+    "href",
+    "src",
+    "srcset",
+    "imagesrcset",
+    "action",
+    "formaction",
+    "poster",
+    "data",
+    "cite",
+    "longdesc",
+    "background",
+    "manifest",
+    "ping",
+];
+
+/// A URL found in an attribute of the document, as yielded by [`Html::links`].
+#[derive(Debug, Clone)]
+pub struct Link<'a> {
+    /// The absolute URL.
+    pub url: Url,
+    /// The element with the attribute.
+    pub element: ElementRef<'a>,
+    /// The name of the attribute, such as `href` or `srcset`.
+    pub attribute: &'static str,
+    /// The `rel` attribute of the element.
+    pub rel: Option<&'a str>,
+}
+
+impl Link<'_> {
+    /// Returns true if `rel` contains `token`, ignoring ASCII case.
+    pub fn has_rel(&self, token: &str) -> bool {
+        self.rel.is_some_and(|rel| has_rel(rel, token))
+    }
+}
+
+/// Returns the URLs of the image candidates of a `srcset` value, following the parsing
+/// rules of HTML, where URLs may contain commas but not spaces.
+pub(crate) fn srcset_urls(srcset: &str) -> Vec<&str> {
+    let mut urls = Vec::new();
+    let mut rest = srcset;
+    loop {
+        rest = rest.trim_start_matches(|c: char| c.is_ascii_whitespace() || c == ',');
+        if rest.is_empty() {
+            return urls;
+        }
+        let end = rest
+            .find(|c: char| c.is_ascii_whitespace())
+            .unwrap_or(rest.len());
+        let url = &rest[..end];
+        rest = &rest[end..];
+        let trimmed = url.trim_end_matches(',');
+        if trimmed.len() == url.len() {
+            // Skip the descriptors, up to the comma that ends the candidate; commas inside
+            // parentheses belong to the descriptors.
+            let mut depth = 0usize;
+            let end = rest
+                .char_indices()
+                .find(|&(_, c)| match c {
+                    '(' => {
+                        depth += 1;
+                        false
+                    }
+                    ')' => {
+                        depth = depth.saturating_sub(1);
+                        false
+                    }
+                    ',' => depth == 0,
+                    _ => false,
+                })
+                .map_or(rest.len(), |(i, _)| i);
+            rest = &rest[end..];
+        }
+        if !trimmed.is_empty() {
+            urls.push(trimmed);
+        }
+    }
+}
+
+/// A summary of the links of a page, as computed by [`Html::link_profile`].
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LinkProfile {
+    /// The number of HTTP(S) links.
+    pub total: usize,
+    /// The links to the host of the page.
+    pub internal: usize,
+    /// The links to other hosts.
+    pub external: usize,
+    /// The links with `rel` `nofollow`, `sponsored` or `ugc`.
+    pub nofollow: usize,
+    /// The external links with `rel` `nofollow`, `sponsored` or `ugc`.
+    pub external_nofollow: usize,
+    /// The hosts of external links and their link counts, most linked first.
+    pub domains: Vec<(String, usize)>,
+    /// The anchor texts and how often they occur, most frequent first. Image links count
+    /// with their `alt` text; links without any text are left out.
+    pub anchors: Vec<(String, usize)>,
+}
+
+impl LinkProfile {
+    /// Returns the share of links that are nofollow, between 0 and 1.
+    pub fn nofollow_ratio(&self) -> f64 {
+        ratio(self.nofollow, self.total)
+    }
+
+    /// Returns the share of external links that are nofollow, between 0 and 1.
+    pub fn external_nofollow_ratio(&self) -> f64 {
+        ratio(self.external_nofollow, self.external)
+    }
+}
+
+/// A hyperlink of a page as an edge of a link graph, as returned by [`Html::link_edges`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LinkEdge {
+    /// The URL of the page, without its fragment.
+    pub source: Url,
+    /// The URL linked to, without its fragment.
+    pub target: Url,
+    /// The text of the link, with whitespace collapsed, or the `alt` text of its images.
+    pub anchor_text: String,
+    /// The `rel` tokens of the link, lowercased and separated by single spaces.
+    pub rel: String,
+    /// The link has `rel` `nofollow`, `sponsored` or `ugc`.
+    pub nofollow: bool,
+}
+
+/// Returns true if `rel` asks crawlers not to follow the link.
+fn is_nofollow(rel: &str) -> bool {
+    ["nofollow", "sponsored", "ugc"]
+        .iter()
+        .any(|token| has_rel(rel, token))
+}
+
+/// Returns the text of a link, or the `alt` text of its images if it has no text.
+fn anchor_text(link: ElementRef) -> String {
+    let text = link.inner_text();
+    if !text.is_empty() {
+        return text;
+    }
+    let alts = link
+        .descendants()
+        .filter_map(|n| n.value().as_element())
+        .filter(|e| matches!(e.name(), "img" | "area"))
+        .filter_map(|e| e.attr("alt"));
+    alts.flat_map(str::split_whitespace)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn ratio(part: usize, whole: usize) -> f64 {
+    if whole == 0 {
+        0.0
+    } else {
+        part as f64 / whole as f64
+    }
+}
+
+/// Returns the host without a leading `www.`, so that both forms count as internal.
+fn site(url: &Url) -> Option<&str> {
+    let host = url.host_str()?;
+    Some(host.strip_prefix("www.").unwrap_or(host))
+}
+
+/// Sorts counts by descending count, then by key.
+fn ranked(counts: HashMap<String, usize>) -> Vec<(String, usize)> {
+    let mut counts: Vec<_> = counts.into_iter().collect();
+    counts.sort_unstable_by(|(a, m), (b, n)| n.cmp(m).then_with(|| a.cmp(b)));
+    counts
+}
+
+impl Html {
+    /// Returns the base URL of the document: the first `<base href>` resolved against
+    /// `url`, the URL the document was fetched from, or `url` itself without one.
+    pub fn base_url(&self, url: &Url) -> Url {
+        self.select_first(&BASE)
+            .and_then(|base| url.join(base.value().attr("href")?.trim()).ok())
+            .filter(|base| !matches!(base.scheme(), "data" | "javascript"))
+            .unwrap_or_else(|| url.clone())
+    }
+
+    /// Returns every URL in the attributes of the document, resolved against its
+    /// [base URL](Html::base_url), in document order.
+    ///
+    /// The attributes are `href`, `src`, `srcset`, `imagesrcset`, `action`, `formaction`,
+    /// `poster`, `cite`, `longdesc`, `background`, `manifest`, `ping` and the `data` of
+    /// `<object>`. `url` is the URL the document was fetched from. Values that do not
+    /// resolve to a URL are skipped, and so is the `href` of `<base>` itself.
+    ///
+    /// ```
+    /// use scraper::Html;
+    /// use url::Url;
+    ///
+    /// let html = Html::parse_document(r#"
+    ///     <base href="/docs/">
+    ///     <a href="intro" rel="next">Intro</a>
+    ///     <img src="logo.png" srcset="logo-2x.png 2x, logo-3x.png 3x">
+    /// "#);
+    /// let links: Vec<_> = html.links(&Url::parse("https://example.com/").unwrap()).collect();
+    /// assert_eq!("https://example.com/docs/intro", links[0].url.as_str());
+    /// assert!(links[0].has_rel("next"));
+    /// let srcset: Vec<_> = links.iter().filter(|l| l.attribute == "srcset").collect();
+    /// assert_eq!("https://example.com/docs/logo-3x.png", srcset[1].url.as_str());
+    /// ```
+    pub fn links(&self, url: &Url) -> impl Iterator<Item = Link<'_>> {
+        self.links_as(url, None)
+    }
+
+    /// Returns every URL in the attributes of the document, as [`Html::links`] does, with
+    /// each URL normalized by `policy`.
+    pub fn links_with<'a>(
+        &'a self,
+        url: &Url,
+        policy: &'a UrlPolicy,
+    ) -> impl Iterator<Item = Link<'a>> + 'a {
+        self.links_as(url, Some(policy))
+    }
+
+    fn links_as<'a>(
+        &'a self,
+        url: &Url,
+        policy: Option<&'a UrlPolicy>,
+    ) -> impl Iterator<Item = Link<'a>> + 'a {
+        let base = self.base_url(url);
+        self.tree
+            .root()
+            .descendants()
+            .filter_map(ElementRef::wrap)
+            .flat_map(move |element| {
+                let value = element.value();
+                let mut links = Vec::new();
+                for &attribute in URL_ATTRIBUTES {
+                    let skip = match attribute {
+                        "href" => value.name() == "base",
+                        "data" => value.name() != "object",
+                        _ => false,
+                    };
+                    let Some(raw) = value.attr(attribute).filter(|_| !skip) else {
+                        continue;
+                    };
+                    let urls = match attribute {
+                        "srcset" | "imagesrcset" => srcset_urls(raw),
+                        "ping" => raw.split_ascii_whitespace().collect(),
+                        _ => vec![raw.trim()],
+                    };
+                    for href in urls.into_iter().filter(|href| !href.is_empty()) {
+                        if let Ok(url) = base.join(href) {
+                            links.push(Link {
+                                url: match policy {
+                                    Some(policy) => normalize(&url, policy),
+                                    None => url,
+                                },
+                                element,
+                                attribute,
+                                rel: value.attr("rel"),
+                            });
+                        }
+                    }
+                }
+                links
+            })
+    }
+
+    /// Summarizes the links of the page in one pass: internal and external counts, the
+    /// external domains, the anchor texts and the share of nofollow links.
+    ///
+    /// Links are resolved against the [base URL](Html::base_url) of `base`, the URL of the
+    /// page, and only HTTP(S) targets are counted. A link is internal when its host is the
+    /// host of `base`, ignoring `www.`.
+    ///
+    /// ```
+    /// use scraper::Html;
+    /// use url::Url;
+    ///
+    /// let html = Html::parse_document(r#"
+    ///     <a href="/pricing">Pricing</a>
+    ///     <a href="https://www.shop.example/docs">Docs</a>
+    ///     <a href="https://partner.example/" rel="sponsored">Partner</a>
+    ///     <a href="https://partner.example/blog"><img alt="Partner"></a>
+    ///     <a href="mailto:hi@shop.example">Mail</a>
+    /// "#);
+    /// let profile = html.link_profile(&Url::parse("https://shop.example/").unwrap());
+    /// assert_eq!((4, 2, 2), (profile.total, profile.internal, profile.external));
+    /// assert_eq!(vec![("partner.example".to_owned(), 2)], profile.domains);
+    /// assert_eq!(("Partner".to_owned(), 2), profile.anchors[0]);
+    /// assert_eq!(0.5, profile.external_nofollow_ratio());
+    /// ```
+    pub fn link_profile(&self, base: &Url) -> LinkProfile {
+        self.link_profile_as(base, None)
+    }
+
+    /// Summarizes the links of the page, as [`Html::link_profile`] does, with each target
+    /// normalized by `policy` before it is counted.
+    pub fn link_profile_with(&self, base: &Url, policy: &UrlPolicy) -> LinkProfile {
+        self.link_profile_as(base, Some(policy))
+    }
+
+    fn link_profile_as(&self, base: &Url, policy: Option<&UrlPolicy>) -> LinkProfile {
+        let resolve = self.base_url(base);
+        let mut profile = LinkProfile::default();
+        let mut domains: HashMap<String, usize> = HashMap::new();
+        let mut anchors: HashMap<String, usize> = HashMap::new();
+        let own = site(base);
+
+        for link in self.root_element().select(&LINKS) {
+            let value = link.value();
+            let Ok(url) = resolve.join(value.attr("href").unwrap_or_default().trim()) else {
+                continue;
+            };
+            if !matches!(url.scheme(), "http" | "https") {
+                continue;
+            }
+            let url = match policy {
+                Some(policy) => normalize(&url, policy),
+                None => url,
+            };
+            let nofollow = is_nofollow(value.attr("rel").unwrap_or_default());
+
+            profile.total += 1;
+            profile.nofollow += usize::from(nofollow);
+            if site(&url) == own {
+                profile.internal += 1;
+            } else {
+                profile.external += 1;
+                profile.external_nofollow += usize::from(nofollow);
+                if let Some(host) = url.host_str() {
+                    *domains.entry(host.to_owned()).or_default() += 1;
+                }
+            }
+
+            let text = anchor_text(link);
+            if !text.is_empty() {
+                *anchors.entry(text).or_default() += 1;
+            }
+        }
+
+        profile.domains = ranked(domains);
+        profile.anchors = ranked(anchors);
+        profile
+    }
+
+    /// Returns the hyperlinks of the page as the edges of a link graph, from `base`, the
+    /// URL of the page, to HTTP(S) targets resolved against its [base URL](Html::base_url).
+    ///
+    /// Both ends are normalized with the default [`UrlPolicy`], which among others removes
+    /// fragments, so links to parts of a page link to the page. Edges are in document
+    /// order, and links repeating the target, anchor text and `rel` of an earlier one are
+    /// left out.
+    ///
+    /// ```
+    /// use scraper::Html;
+    /// use url::Url;
+    ///
+    /// let html = Html::parse_document(r#"
+    ///     <a href="/docs#intro">Docs</a>
+    ///     <a href="/docs">Docs</a>
+    ///     <a href="https://Partner.example/" rel="Sponsored  noopener">Partner</a>
+    /// "#);
+    /// let edges = html.link_edges(&Url::parse("https://shop.example/#top").unwrap());
+    /// assert_eq!(2, edges.len());
+    /// assert_eq!("https://shop.example/", edges[0].source.as_str());
+    /// assert_eq!("https://shop.example/docs", edges[0].target.as_str());
+    /// assert_eq!("https://partner.example/", edges[1].target.as_str());
+    /// assert_eq!(("sponsored noopener", true), (edges[1].rel.as_str(), edges[1].nofollow));
+    /// ```
+    pub fn link_edges(&self, base: &Url) -> Vec<LinkEdge> {
+        self.link_edges_with(base, &UrlPolicy::default())
+    }
+
+    /// Returns the hyperlinks of the page as the edges of a link graph, as
+    /// [`Html::link_edges`] does, with both ends normalized by `policy`.
+    pub fn link_edges_with(&self, base: &Url, policy: &UrlPolicy) -> Vec<LinkEdge> {
+        let source = normalize(base, policy);
+        let resolve = self.base_url(base);
+        let mut seen = HashSet::new();
+        let mut edges = Vec::new();
+
+        for link in self.root_element().select(&LINKS) {
+            let value = link.value();
+            let Ok(target) = resolve.join(value.attr("href").unwrap_or_default().trim()) else {
+                continue;
+            };
+            if !matches!(target.scheme(), "http" | "https") {
+                continue;
+            }
+            let target = normalize(&target, policy);
+            let rel = value
+                .attr("rel")
+                .unwrap_or_default()
+                .split_ascii_whitespace()
+                .map(str::to_ascii_lowercase)
+                .collect::<Vec<_>>()
+                .join(" ");
+            let edge = LinkEdge {
+                source: source.clone(),
+                target,
+                anchor_text: anchor_text(link),
+                nofollow: is_nofollow(&rel),
+                rel,
+            };
+            if seen.insert((
+                edge.target.clone(),
+                edge.anchor_text.clone(),
+                edge.rel.clone(),
+            )) {
+                edges.push(edge);
+            }
+        }
+        edges
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use url::Url;
+
+    use crate::html::Html;
+    use crate::url_utils::UrlPolicy;
+
+    #[test]
+    fn srcset_candidates() {
+        assert_eq!(
+            vec!["a.jpg", "b,c.jpg", "d.jpg"],
+            super::srcset_urls(" a.jpg 1x,b,c.jpg 2x , d.jpg,")
+        );
+        assert_eq!(vec!["e.jpg"], super::srcset_urls("e.jpg (a, b) 100w"));
+    }
+
+    #[test]
+    fn empty_pages_have_zero_ratios() {
+        let html = Html::parse_document("<a href='javascript:go()'>Go</a><a name='top'></a>");
+        let profile = html.link_profile(&Url::parse("https://a.example/").unwrap());
+        assert_eq!(0, profile.total);
+        assert_eq!(0.0, profile.nofollow_ratio());
+        assert!(profile.anchors.is_empty());
+    }
+
+    #[test]
+    fn profile_resolves_against_base_href() {
+        let html = Html::parse_document("<base href='https://cdn.example/'><a href='x'>X</a>");
+        let profile = html.link_profile(&Url::parse("https://a.example/").unwrap());
+        assert_eq!((1, 1), (profile.total, profile.external));
+        assert_eq!(vec![("cdn.example".to_owned(), 1)], profile.domains);
+    }
+
+    #[test]
+    fn edges_resolve_against_base_href() {
+        let html = Html::parse_document(
+            r#"<base href="https://cdn.example/a/"><a href="b"><img alt=" Logo  B "></a><a href="mailto:x@y">x</a>"#,
+        );
+        let edges = html.link_edges(&Url::parse("https://site.example/page").unwrap());
+        assert_eq!(1, edges.len());
+        assert_eq!("https://site.example/page", edges[0].source.as_str());
+        assert_eq!("https://cdn.example/a/b", edges[0].target.as_str());
+        assert_eq!("Logo B", edges[0].anchor_text);
+        assert!(!edges[0].nofollow);
+    }
+
+    #[test]
+    fn links_are_normalized_by_policy() {
+        let html = Html::parse_document(r#"<a href="/p?utm_id=1&b=2&a=1#x">p</a>"#);
+        let base = Url::parse("https://a.example/").unwrap();
+        let policy = UrlPolicy::default();
+        let urls: Vec<String> = html
+            .links_with(&base, &policy)
+            .map(|link| link.url.into())
+            .collect();
+        assert_eq!(vec!["https://a.example/p?a=1&b=2"], urls);
+
+        let edges = html.link_edges_with(&base, &UrlPolicy::minimal());
+        assert_eq!(
+            "https://a.example/p?utm_id=1&b=2&a=1",
+            edges[0].target.as_str()
+        );
+    }
+}
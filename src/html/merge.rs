@@ -0,0 +1,224 @@
+//! Filling in content from another variant of the same page.
+
+use ego_tree::{NodeId, NodeRef};
+use html5ever::QualName;
+
+use super::Html;
+use crate::media::lazy::image_source;
+use crate::node::Node;
+use crate::selector::Selector;
+
+lazy_static! {
+    static ref UNITS: Selector = Selector::parse(
+        "p, h1, h2, h3, h4, h5, h6, li, dt, dd, pre, blockquote, figcaption, \
+         img, amp-img, amp-anim"
+    )
+    .unwrap();
+    static ref BLOCKS: Selector =
+        Selector::parse("p, h1, h2, h3, h4, h5, h6, li, dt, dd, pre, blockquote, figcaption")
+            .unwrap();
+    static ref BODY: Selector = Selector::parse("body").unwrap();
+}
+
+/// Which content [`Html::merge_variant`] copies from the variant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum MergeStrategy {
+    /// Text blocks and images, only where this document has nothing between the
+    /// neighbouring content both versions share, so reworded passages are not duplicated.
+    #[default]
+    FillGaps,
+    /// Images only, wherever the variant has one this document lacks.
+    Images,
+    /// Every text block and image this document lacks.
+    Union,
+}
+
+/// A paragraph-like block or an image, compared across the two documents by its key.
+struct Unit {
+    id: NodeId,
+    key: String,
+    is_image: bool,
+}
+
+/// Returns the text blocks without nested blocks and the images, in document order.
+fn units(html: &Html) -> Vec<Unit> {
+    html.select(&UNITS)
+        .filter_map(|element| {
+            let value = element.value();
+            let is_image = matches!(value.name(), "img" | "amp-img" | "amp-anim");
+            let key = if is_image {
+                // Variants often serve the same file from other hosts or in other sizes.
+                let src = image_source(value)?;
+                let path = src.split(['?', '#']).next().unwrap_or_default();
+                path.rsplit('/').next().unwrap_or_default().to_owned()
+            } else {
+                if element.select_first(&BLOCKS).is_some() {
+                    return None;
+                }
+                let text = element.text().collect::<String>();
+                text.split_whitespace().collect::<Vec<_>>().join(" ")
+            };
+            (!key.is_empty()).then(|| Unit {
+                id: element.id(),
+                key,
+                is_image,
+            })
+        })
+        .collect()
+}
+
+/// Returns the index pairs of a longest common subsequence of the two key sequences.
+fn align(ours: &[Unit], theirs: &[Unit]) -> Vec<(usize, usize)> {
+    let width = theirs.len() + 1;
+    let mut lengths = vec![0u32; (ours.len() + 1) * width];
+    for i in (0..ours.len()).rev() {
+        for j in (0..theirs.len()).rev() {
+            lengths[i * width + j] = if ours[i].key == theirs[j].key {
+                lengths[(i + 1) * width + j + 1] + 1
+            } else {
+                lengths[(i + 1) * width + j].max(lengths[i * width + j + 1])
+            };
+        }
+    }
+    let (mut i, mut j, mut pairs) = (0, 0, Vec::new());
+    while i < ours.len() && j < theirs.len() {
+        if ours[i].key == theirs[j].key {
+            pairs.push((i, j));
+            i += 1;
+            j += 1;
+        } else if lengths[(i + 1) * width + j] >= lengths[i * width + j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    pairs
+}
+
+/// Appends copies of the children of `from` to `to`.
+fn copy_children(html: &mut Html, to: NodeId, from: NodeRef<Node>) {
+    for child in from.children() {
+        let id = html.append_child(to, child.value().clone()).unwrap();
+        copy_children(html, id, child);
+    }
+}
+
+impl Html {
+    /// Fills in content missing from this document with that of a variant of the same
+    /// page, such as its AMP or mobile version or the contents of a `<noscript>`, and
+    /// returns the number of blocks and images copied.
+    ///
+    /// Paragraph-like blocks are matched by their text and images by their file name, and
+    /// the two sequences are aligned so each copied unit lands next to the counterpart of
+    /// its neighbour in the variant. URLs are copied as written, and AMP images become
+    /// `<img>` elements.
+    ///
+    /// ```
+    /// use scraper::html::MergeStrategy;
+    /// use scraper::Html;
+    ///
+    /// let mut html = Html::parse_document("<h1>Title</h1><p>First.</p><p>Third.</p>");
+    /// let amp = Html::parse_document(
+    ///     r#"<h1>Title</h1><p>First.</p><amp-img src="/photo.jpg"></amp-img><p>Third.</p>"#,
+    /// );
+    /// assert_eq!(1, html.merge_variant(&amp, MergeStrategy::FillGaps));
+    ///
+    /// let html = html.root_element().html();
+    /// assert!(html.contains(r#"<p>First.</p><img src="/photo.jpg"><p>Third.</p>"#));
+    /// ```
+    pub fn merge_variant(&mut self, variant: &Html, strategy: MergeStrategy) -> usize {
+        let ours = units(self);
+        let theirs = units(variant);
+        let pairs = align(&ours, &theirs);
+        let body = self
+            .select_first(&BODY)
+            .map_or_else(|| self.root_element().id(), |body| body.id());
+
+        // Each gap runs between two matched pairs, or the start or end of the documents.
+        let mut copied = 0;
+        let (mut i, mut j) = (0, 0);
+        let ends = pairs.iter().copied().map(Some).chain([None]);
+        for end in ends {
+            let (next_i, next_j) = end.unwrap_or((ours.len(), theirs.len()));
+            let our_gap = &ours[i..next_i];
+            let mut after = i.checked_sub(1).map(|_| ours[i - 1].id);
+            let before = end.map(|(next_i, _)| ours[next_i].id);
+            let mut blocks: Vec<NodeId> = Vec::new();
+
+            for unit in &theirs[j..next_j] {
+                let wanted = match strategy {
+                    MergeStrategy::FillGaps => our_gap.iter().all(|ours| ours.is_image),
+                    MergeStrategy::Images => unit.is_image,
+                    MergeStrategy::Union => true,
+                };
+                let source = variant.tree.get(unit.id).unwrap();
+                // Images are copied along with the blocks around them.
+                let inside_copy = source.ancestors().any(|a| blocks.contains(&a.id()));
+                let present = our_gap.iter().any(|ours| ours.key == unit.key);
+                if !wanted || inside_copy || present {
+                    continue;
+                }
+
+                let mut value = source.value().clone();
+                if let Node::Element(element) = &mut value {
+                    if unit.is_image {
+                        element.name = QualName::new(None, ns!(html), local_name!("img"));
+                    }
+                }
+                let id = match (after, before) {
+                    (Some(after), _) => self.insert_after(after, value),
+                    (None, Some(before)) => self.insert_before(before, value),
+                    (None, None) => self.append_child(body, value),
+                }
+                .unwrap();
+                if !unit.is_image {
+                    copy_children(self, id, source);
+                    blocks.push(unit.id);
+                }
+                if after.is_some() {
+                    after = Some(id);
+                }
+                copied += 1;
+            }
+            i = next_i + 1;
+            j = next_j + 1;
+        }
+        copied
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MergeStrategy;
+    use crate::html::Html;
+
+    fn merged(ours: &str, theirs: &str, strategy: MergeStrategy) -> (usize, String) {
+        let mut html = Html::parse_fragment(ours);
+        let count = html.merge_variant(&Html::parse_fragment(theirs), strategy);
+        (count, html.root_element().inner_html())
+    }
+
+    #[test]
+    fn fills_gaps_without_duplicating_rewordings() {
+        let ours = r#"<p>Intro.</p><p>Old wording.</p><p>End.</p>"#;
+        let theirs = r#"<p>Lead.</p><p>Intro.</p><p>New wording.</p><p>End.</p><p>More <img src="//cdn/1.jpg?w=2"> here.</p><img src="/2.jpg">"#;
+
+        let (count, html) = merged(ours, theirs, MergeStrategy::FillGaps);
+        assert_eq!(3, count);
+        assert_eq!(
+            r#"<p>Lead.</p><p>Intro.</p><p>Old wording.</p><p>End.</p><p>More <img src="//cdn/1.jpg?w=2"> here.</p><img src="/2.jpg">"#,
+            html
+        );
+
+        let (count, html) = merged(ours, theirs, MergeStrategy::Images);
+        assert_eq!(2, count);
+        assert!(html.ends_with(r#"<p>End.</p><img src="//cdn/1.jpg?w=2"><img src="/2.jpg">"#));
+
+        let (count, _) = merged(
+            r#"<img data-src="/img/1.jpg"><p>Intro.</p>"#,
+            theirs,
+            MergeStrategy::Union,
+        );
+        assert_eq!(5, count);
+    }
+}
@@ -0,0 +1,310 @@
+//! Compact serialization.
+
+use ego_tree::NodeRef;
+
+use super::Html;
+use crate::element_ref::ElementRef;
+use crate::node::pretty::PHRASING;
+use crate::node::{Element, Node};
+
+/// Elements whose text is written without escaping.
+const RAW_TEXT: &[&str] = &[
+    "script",
+    "style",
+    "xmp",
+    "iframe",
+    "noembed",
+    "noframes",
+    "plaintext",
+    "noscript",
+];
+
+/// Elements inside which whitespace is rendered as written.
+const PRESERVE_WHITESPACE: &[&str] = &["pre", "textarea", "listing", "plaintext"];
+
+/// Elements without content or end tag.
+const VOID: &[&str] = &[
+    "area", "base", "basefont", "bgsound", "br", "col", "embed", "frame", "hr", "img", "input",
+    "keygen", "link", "meta", "param", "source", "track", "wbr",
+];
+
+/// Attribute values that are the default, as `(element, attribute, value)`.
+const DEFAULT_VALUES: &[(&str, &str, &str)] = &[
+    ("script", "type", "text/javascript"),
+    ("script", "language", "javascript"),
+    ("style", "type", "text/css"),
+    ("link", "type", "text/css"),
+    ("form", "method", "get"),
+    ("input", "type", "text"),
+    ("button", "type", "submit"),
+    ("area", "shape", "rect"),
+];
+
+/// Options for [`Html::minify_with`] and [`ElementRef::minify_with`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct MinifyOptions {
+    /// Collapses runs of whitespace to one space and drops whitespace next to block-level
+    /// elements, outside `pre` and `textarea`.
+    pub collapse_whitespace: bool,
+    /// Drops comments.
+    pub remove_comments: bool,
+    /// Leaves attribute values unquoted where that parses the same, and writes empty ones
+    /// as the bare attribute name.
+    pub remove_attribute_quotes: bool,
+    /// Drops attributes set to their default, such as `type="text/javascript"` on scripts
+    /// and `method="get"` on forms.
+    pub remove_default_attributes: bool,
+}
+
+impl Default for MinifyOptions {
+    fn default() -> Self {
+        MinifyOptions {
+            collapse_whitespace: true,
+            remove_comments: true,
+            remove_attribute_quotes: true,
+            remove_default_attributes: true,
+        }
+    }
+}
+
+/// Returns true if the node flows with the text around it.
+fn is_inline(node: NodeRef<Node>) -> bool {
+    match node.value() {
+        Node::Text(_) => true,
+        Node::Element(element) => PHRASING.contains(&element.name()),
+        _ => false,
+    }
+}
+
+/// Returns true if whitespace at this end of a text node is not rendered: the
+/// neighbouring node is block-level, or there is none and the parent is block-level.
+/// Comments and other nodes that are not rendered are looked past.
+fn bounded<'a>(
+    mut siblings: impl Iterator<Item = NodeRef<'a, Node>>,
+    parent: Option<NodeRef<'a, Node>>,
+) -> bool {
+    let neighbour = siblings.find(|node| matches!(node.value(), Node::Text(_) | Node::Element(_)));
+    match neighbour {
+        Some(node) => !is_inline(node),
+        None => parent.is_none_or(|parent| !is_inline(parent)),
+    }
+}
+
+fn escape(text: &str, attr_mode: bool, out: &mut String) {
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '\u{00A0}' => out.push_str("&nbsp;"),
+            '"' if attr_mode => out.push_str("&quot;"),
+            '<' if !attr_mode => out.push_str("&lt;"),
+            '>' if !attr_mode => out.push_str("&gt;"),
+            c => out.push(c),
+        }
+    }
+}
+
+struct Minifier<'o> {
+    options: &'o MinifyOptions,
+    out: String,
+}
+
+impl Minifier<'_> {
+    fn attributes(&mut self, element: &Element) {
+        let mut attrs: Vec<_> = element.attrs.iter().collect();
+        attrs.sort_unstable_by(|(a, _), (b, _)| (&a.prefix, &a.local).cmp(&(&b.prefix, &b.local)));
+        for (name, value) in attrs {
+            let default = DEFAULT_VALUES.iter().any(|&(tag, attr, default)| {
+                tag == element.name() && attr == &*name.local && value.eq_ignore_ascii_case(default)
+            });
+            if self.options.remove_default_attributes && default {
+                continue;
+            }
+            self.out.push(' ');
+            if name.ns == ns!(xml) {
+                self.out.push_str("xml:");
+            } else if name.ns == ns!(xlink) {
+                self.out.push_str("xlink:");
+            } else if name.ns == ns!(xmlns) && name.local != local_name!("xmlns") {
+                self.out.push_str("xmlns:");
+            }
+            self.out.push_str(&name.local);
+            let unquoted = !value.is_empty()
+                && !value.contains(|c: char| {
+                    c.is_ascii_whitespace() || matches!(c, '"' | '\'' | '=' | '<' | '>' | '`')
+                });
+            if self.options.remove_attribute_quotes && value.is_empty() {
+                continue;
+            }
+            if self.options.remove_attribute_quotes && unquoted {
+                self.out.push('=');
+                escape(value, true, &mut self.out);
+            } else {
+                self.out.push_str("=\"");
+                escape(value, true, &mut self.out);
+                self.out.push('"');
+            }
+        }
+    }
+
+    fn text(&mut self, node: NodeRef<Node>, text: &str, preserve: bool) {
+        let parent = node.parent();
+        let raw = parent
+            .and_then(|parent| parent.value().as_element().map(Element::name))
+            .is_some_and(|name| RAW_TEXT.contains(&name));
+        if raw {
+            self.out.push_str(text);
+            return;
+        }
+        if preserve || !self.options.collapse_whitespace {
+            escape(text, false, &mut self.out);
+            return;
+        }
+
+        let words: Vec<&str> = text.split_whitespace().collect();
+        let leading = text.starts_with(|c: char| c.is_ascii_whitespace())
+            && !bounded(node.prev_siblings(), parent)
+            && !self.out.ends_with(' ');
+        let trailing = text.ends_with(|c: char| c.is_ascii_whitespace())
+            && !bounded(node.next_siblings(), parent);
+        if words.is_empty() {
+            if leading && trailing {
+                self.out.push(' ');
+            }
+            return;
+        }
+        if leading {
+            self.out.push(' ');
+        }
+        escape(&words.join(" "), false, &mut self.out);
+        if trailing {
+            self.out.push(' ');
+        }
+    }
+
+    fn node(&mut self, node: NodeRef<Node>, preserve: bool) {
+        match node.value() {
+            Node::Doctype(doctype) => {
+                self.out.push_str("<!DOCTYPE ");
+                self.out.push_str(doctype.name());
+                self.out.push('>');
+            }
+            Node::Comment(comment) if !self.options.remove_comments => {
+                self.out.push_str("<!--");
+                self.out.push_str(comment);
+                self.out.push_str("-->");
+            }
+            Node::Text(text) => self.text(node, text, preserve),
+            Node::Element(element) => {
+                let name = element.name();
+                self.out.push('<');
+                self.out.push_str(name);
+                self.attributes(element);
+                self.out.push('>');
+                if VOID.contains(&name) && element.name.ns == ns!(html) {
+                    return;
+                }
+                let preserve = preserve || PRESERVE_WHITESPACE.contains(&name);
+                for child in node.children() {
+                    self.node(child, preserve);
+                }
+                self.out.push_str("</");
+                self.out.push_str(name);
+                self.out.push('>');
+            }
+            Node::Document | Node::Fragment => {
+                for child in node.children() {
+                    self.node(child, preserve);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn minify(node: NodeRef<Node>, options: &MinifyOptions) -> String {
+    let mut minifier = Minifier {
+        options,
+        out: String::new(),
+    };
+    let preserve = node
+        .ancestors()
+        .filter_map(|a| a.value().as_element().map(Element::name))
+        .any(|name| PRESERVE_WHITESPACE.contains(&name));
+    minifier.node(node, preserve);
+    minifier.out
+}
+
+impl Html {
+    /// Serializes the document as compactly as [`MinifyOptions::default`] allows.
+    pub fn minify(&self) -> String {
+        self.minify_with(&MinifyOptions::default())
+    }
+
+    /// Serializes the document compactly, in one pass over the tree. Attributes are
+    /// written in name order, so equal documents give equal output.
+    ///
+    /// ```
+    /// use scraper::html::MinifyOptions;
+    /// use scraper::Html;
+    ///
+    /// let html = Html::parse_document(
+    ///     "<div>\n  <p class=\"intro\">Hello,   <b>world</b> !</p>\n  <pre> a\n b</pre>\n</div>\
+    ///      <script type=\"text/javascript\">x = 1 <  2</script>",
+    /// );
+    /// assert_eq!(
+    ///     "<html><head></head><body><div><p class=intro>Hello, <b>world</b> !</p><pre> a\n b</pre>\
+    ///      </div><script>x = 1 <  2</script></body></html>",
+    ///     html.minify()
+    /// );
+    /// ```
+    pub fn minify_with(&self, options: &MinifyOptions) -> String {
+        minify(self.tree.root(), options)
+    }
+}
+
+impl ElementRef<'_> {
+    /// Serializes the element as compactly as [`MinifyOptions::default`] allows.
+    pub fn minify(&self) -> String {
+        self.minify_with(&MinifyOptions::default())
+    }
+
+    /// Serializes the element compactly, as [`Html::minify_with`] does.
+    pub fn minify_with(&self, options: &MinifyOptions) -> String {
+        minify(**self, options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MinifyOptions;
+    use crate::html::Html;
+
+    #[test]
+    fn drops_defaults_and_quotes_only_where_safe() {
+        let html = Html::parse_fragment(
+            r#"<form method="GET" action="/s?a=1&amp;b=2"> <input type="text" name="q" disabled>
+               <input type="submit" value="Go now"> </form><span> a </span> <i>b</i>"#,
+        );
+        assert_eq!(
+            r#"<html><form action="/s?a=1&amp;b=2"><input disabled name=q> <input type=submit value="Go now"></form><span> a </span> <i>b</i></html>"#,
+            html.minify()
+        );
+        let options = MinifyOptions {
+            remove_attribute_quotes: false,
+            remove_default_attributes: false,
+            ..MinifyOptions::default()
+        };
+        assert_eq!(
+            r#"<html><form action="/s?a=1&amp;b=2" method="GET"><input disabled="" name="q" type="text"> <input type="submit" value="Go now"></form><span> a </span> <i>b</i></html>"#,
+            html.minify_with(&options)
+        );
+    }
+
+    #[test]
+    fn whitespace_around_comments_is_kept() {
+        let html = Html::parse_fragment("<p>a <!-- c --> b</p><p><!-- c --> d <?x?></p>");
+        assert_eq!("<html><p>a b</p><p>d</p></html>", html.minify());
+    }
+}
@@ -1,7 +1,11 @@
 //! HTML documents and fragments.
 
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::HashMap;
+
 use ego_tree::iter::Nodes;
-use ego_tree::{NodeId, Tree};
+use ego_tree::{NodeId, NodeRef, Tree};
 use html5ever::serialize::SerializeOpts;
 use html5ever::tree_builder::QuirksMode;
 use html5ever::QualName;
@@ -9,21 +13,97 @@ use html5ever::{driver, serialize};
 use tendril::TendrilSink;
 
 use crate::element_ref::ElementRef;
+use crate::error::UniqueError;
 use crate::node::Node;
 use crate::selector::Selector;
 
+pub use self::element_mut::ElementMut;
+pub use self::encoding::{EncodingInfo, EncodingSource};
+pub use self::fetch::FetchContext;
+pub use self::graph::{GraphOptions, NodeLabel};
+pub use self::handle::NodeHandle;
+pub use self::hash::SubtreeHashes;
+pub use self::index::{ElementIndex, IndexPolicy};
+pub use self::layout::{LayoutBox, BOUNDS_ATTRIBUTE, VISIBLE_ATTRIBUTE};
+pub use self::links::{Link, LinkEdge, LinkProfile};
+pub use self::merge::MergeStrategy;
+pub use self::minify::MinifyOptions;
+pub use self::observe::Mutation;
+pub(crate) use self::observe::Observers;
+pub use self::options::{ParseOptions, TextOverflow};
+#[cfg(feature = "rayon")]
+pub use self::parallel::SharedElement;
+pub use self::patch::{Patch, PatchOp};
+pub use self::recovery::{RecoveryEvent, RecoveryKind};
+pub use self::shared::SharedHtml;
+pub use self::spans::SourceSpan;
+pub use self::streaming::StreamingParser;
 use self::tree_sink::HtmlBuilder;
 
 lazy_static! {
     static ref HTML_SELECTOR: Selector = Selector::parse("html").unwrap();
 }
 
+/// Returns the only element of `matches`.
+pub(crate) fn unique<'a>(
+    mut matches: impl Iterator<Item = ElementRef<'a>>,
+) -> Result<ElementRef<'a>, UniqueError> {
+    let first = matches.next().ok_or(UniqueError::NoMatch)?;
+    match matches.count() {
+        0 => Ok(first),
+        others => Err(UniqueError::Multiple(others + 1)),
+    }
+}
+
+/// Turns serializer output, which is always UTF-8, into a string without detecting its
+/// encoding.
+pub(crate) fn utf8_string(buf: &[u8]) -> String {
+    String::from_utf8_lossy(buf).into_owned()
+}
+
+/// The most bytes a thread keeps in its serialization buffer between calls, so serializing
+/// one huge document does not pin its size for the life of the thread.
+const SCRATCH_RETAINED: usize = 1 << 20;
+
+thread_local! {
+    static SCRATCH: RefCell<Vec<u8>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Runs `f` with an empty buffer for serializer output, reusing the buffer of the current
+/// thread so loops over many elements do not allocate one per call. Nested calls get a
+/// fresh buffer.
+pub(crate) fn with_scratch<R>(f: impl FnOnce(&mut Vec<u8>) -> R) -> R {
+    SCRATCH.with(|scratch| match scratch.try_borrow_mut() {
+        Ok(mut buf) => {
+            buf.clear();
+            let result = f(&mut buf);
+            if buf.capacity() > SCRATCH_RETAINED {
+                *buf = Vec::new();
+            }
+            result
+        }
+        Err(_) => f(&mut Vec::new()),
+    })
+}
+
+/// Returns the html5ever options for parsing with `options`.
+fn driver_options(options: &ParseOptions) -> driver::ParseOpts {
+    let mut opts = driver::ParseOpts::default();
+    opts.tokenizer.exact_errors = options.record_recoveries;
+    opts.tree_builder.exact_errors = options.record_recoveries;
+    opts
+}
+
 /// An HTML tree.
 ///
-/// Parsing does not fail hard. Instead, the `quirks_mode` is set and errors are added to the
-/// `errors` field. The `tree` will still be populated as best as possible.
+/// Parsing does not fail hard. Instead, the `quirks_mode` is set and, when parsing with
+/// [`ParseOptions::record_recoveries`], errors are added to the `recoveries` field. The
+/// `tree` will still be populated as best as possible.
 ///
 /// Implements the `TreeSink` trait from the `html5ever` crate, which allows HTML to be parsed.
+/// With the `serde` feature, it also implements `Serialize` and `Deserialize`, so parsed
+/// documents can be cached and restored without parsing them again. Restored documents
+/// leave out detached nodes, number their nodes anew and have no observers.
 #[derive(Debug, Clone)]
 pub struct Html {
     /// The quirks mode.
@@ -32,54 +112,94 @@ pub struct Html {
     pub tree: Tree<Node>,
     /// The html language of the document.
     pub lang: String,
+    /// The text cut from text nodes when parsing with [`TextOverflow::Spill`], by node.
+    pub spilled_text: HashMap<NodeId, String>,
+    /// The markup errors the parser recovered from, in input order, when parsing with
+    /// [`ParseOptions::record_recoveries`].
+    pub recoveries: Vec<RecoveryEvent>,
+    /// Where text nodes came from in the parsed input, by node, when parsed with
+    /// [`Html::parse_document_with_spans`] or [`Html::parse_fragment_with_spans`]. Elements
+    /// keep their spans themselves; see [`Html::source_span`].
+    pub text_spans: HashMap<NodeId, SourceSpan>,
+    /// How the document was fetched, when parsed with [`Html::parse_document_with_base`].
+    pub fetch: Option<FetchContext>,
+    pub(crate) encoding: Option<EncodingInfo>,
+    pub(crate) observers: Observers,
 }
 
 impl Html {
-    /// Creates an empty HTML document.
-    pub fn new_document() -> Self {
+    /// Creates a document of the given tree, with no language, spans, recoveries, fetch
+    /// context, encoding or observers.
+    pub(crate) fn from_parts(quirks_mode: QuirksMode, tree: Tree<Node>) -> Self {
         Html {
-            quirks_mode: QuirksMode::NoQuirks,
-            tree: Tree::new(Node::Document),
-            lang: Default::default(),
+            quirks_mode,
+            tree,
+            lang: String::new(),
+            spilled_text: HashMap::new(),
+            recoveries: Vec::new(),
+            text_spans: HashMap::new(),
+            fetch: None,
+            encoding: None,
+            observers: Observers::default(),
         }
     }
 
+    /// Creates an empty HTML document.
+    pub fn new_document() -> Self {
+        Html::from_parts(QuirksMode::NoQuirks, Tree::new(Node::Document))
+    }
+
     /// Creates an empty HTML fragment.
     pub fn new_fragment() -> Self {
-        Html {
-            quirks_mode: QuirksMode::NoQuirks,
-            tree: Tree::new(Node::Fragment),
-            lang: Default::default(),
-        }
+        Html::from_parts(QuirksMode::NoQuirks, Tree::new(Node::Fragment))
     }
 
     /// Parses a string of HTML as a document.
     ///
-    /// This is a convenience method for the following:
-    ///
     /// ```
-    /// # extern crate html5ever;
-    /// # extern crate tendril;
-    /// # fn main() {
-    /// # let document = "";
-    /// use html5ever::driver::{self, ParseOpts};
     /// use scraper::Html;
-    /// use tendril::TendrilSink;
     ///
-    /// let parser = driver::parse_document(Html::new_document(), ParseOpts::default());
-    /// let html = parser.one(document);
-    /// # }
+    /// let html = Html::parse_document("<title>Hello</title>");
+    /// assert_eq!("html", html.root_element().value().name());
     /// ```
     pub fn parse_document(document: &str) -> Self {
-        let parser = driver::parse_document(HtmlBuilder::new_document(), Default::default());
+        Self::parse_document_with(document, &ParseOptions::default())
+    }
+
+    /// Parses a string of HTML as a document, with options.
+    ///
+    /// ```
+    /// use scraper::html::{ParseOptions, TextOverflow};
+    /// use scraper::Html;
+    ///
+    /// let options = ParseOptions {
+    ///     max_text_len: Some(4),
+    ///     text_overflow: TextOverflow::Spill,
+    ///     ..ParseOptions::default()
+    /// };
+    /// let html = Html::parse_document_with("<p>Hello, world</p>", &options);
+    /// let text = html.root_element().text().collect::<String>();
+    /// assert_eq!("Hell", text);
+    ///
+    /// let node = html.spilled_text.keys().next().copied().unwrap();
+    /// assert_eq!("Hello, world", html.full_text(node).unwrap());
+    /// ```
+    pub fn parse_document_with(document: &str, options: &ParseOptions) -> Self {
+        let builder = HtmlBuilder::with_options(Node::Document, options.clone());
+        let parser = driver::parse_document(builder, driver_options(options));
         parser.one(document)
     }
 
     /// Parses a string of HTML as a fragment.
     pub fn parse_fragment(fragment: &str) -> Self {
+        Self::parse_fragment_with(fragment, &ParseOptions::default())
+    }
+
+    /// Parses a string of HTML as a fragment, with options.
+    pub fn parse_fragment_with(fragment: &str, options: &ParseOptions) -> Self {
         let parser = driver::parse_fragment(
-            HtmlBuilder::new_fragment(),
-            Default::default(),
+            HtmlBuilder::with_options(Node::Fragment, options.clone()),
+            driver_options(options),
             QualName::new(None, ns!(html), local_name!("body")),
             Vec::new(),
             false,
@@ -90,13 +210,46 @@ impl Html {
     /// Returns an iterator over elements matching a selector.
     pub fn select<'a, 'b>(&'a self, selector: &'b Selector) -> Select<'a, 'b> {
         Select {
+            html: self,
             inner: self.tree.nodes(),
             selector,
         }
     }
 
+    /// Returns the first element matching a selector, the same element as
+    /// `select(selector).next()`.
+    ///
+    /// ```
+    /// use scraper::{Html, Selector};
+    ///
+    /// let html = Html::parse_document("<h1>Title</h1><h1>Other</h1>");
+    /// let h1 = Selector::parse("h1").unwrap();
+    /// assert_eq!("Title", html.select_first(&h1).unwrap().inner_html());
+    /// ```
+    pub fn select_first(&self, selector: &Selector) -> Option<ElementRef<'_>> {
+        self.select(selector).next()
+    }
+
+    /// Returns the only element matching a selector, or an error with the number of
+    /// matches if there are none or several, for extraction that must not silently take
+    /// the first of many.
+    ///
+    /// ```
+    /// use scraper::error::UniqueError;
+    /// use scraper::{Html, Selector};
+    ///
+    /// let html = Html::parse_document("<h1>Title</h1><p>One</p><p>Two</p>");
+    /// let h1 = Selector::parse("h1").unwrap();
+    /// assert_eq!("Title", html.select_unique(&h1).unwrap().inner_html());
+    /// let p = Selector::parse("p").unwrap();
+    /// assert_eq!(Err(UniqueError::Multiple(2)), html.select_unique(&p));
+    /// ```
+    pub fn select_unique(&self, selector: &Selector) -> Result<ElementRef<'_>, UniqueError> {
+        unique(self.select(selector))
+    }
+
     /// Returns the root `<html>` element.
-    pub fn root_element(&self) -> ElementRef {
+    pub fn root_element(&self) -> ElementRef<'_> {
         let root_node = self
             .tree
             .root()
@@ -127,27 +280,148 @@ impl Html {
 
     /// Serialize entire document into HTML.
     pub fn html(&self) -> String {
+        with_scratch(|buf| {
+            let _ = self.write_html(&mut *buf);
+            self.decode_serialized(buf)
+        })
+    }
+
+    /// Serializes the entire document into HTML without detecting the encoding of the
+    /// output, as [`ElementRef::html_utf8`] does. For documents decoded from bytes, such as
+    /// with [`Html::parse_document_utf8`], this is what [`Html::html`] does too.
+    pub fn html_utf8(&self) -> String {
+        with_scratch(|buf| {
+            let _ = self.write_html(&mut *buf);
+            utf8_string(buf)
+        })
+    }
+
+    /// Turns serializer output into a string. Documents decoded from bytes are already
+    /// UTF-8, so their output is not detected again, which could misread it as the
+    /// encoding their `<meta charset>` declares.
+    fn decode_serialized(&self, buf: &[u8]) -> String {
+        match self.encoding {
+            Some(_) => utf8_string(buf),
+            None => auto_encoder::auto_encode_bytes(buf),
+        }
+    }
+
+    /// Serializes the entire document as UTF-8 HTML into a writer, such as a file or
+    /// socket, without buffering it. Wrap unbuffered writers in a [`std::io::BufWriter`].
+    ///
+    /// ```
+    /// use scraper::Html;
+    ///
+    /// let html = Html::parse_document("<p>Hi</p>");
+    /// let mut out = Vec::new();
+    /// html.write_html(&mut out).unwrap();
+    /// assert_eq!(html.html().as_bytes(), &out[..]);
+    /// ```
+    pub fn write_html<W: std::io::Write>(&self, writer: W) -> std::io::Result<()> {
         let opts = SerializeOpts {
             scripting_enabled: false, // It's not clear what this does.
             traversal_scope: html5ever::serialize::TraversalScope::IncludeNode,
             create_missing_parent: false,
         };
-        let mut buf = Vec::new();
-        let _ = serialize(&mut buf, self, opts);
-        auto_encoder::auto_encode_bytes(&buf)
+        serialize(writer, self, opts)
+    }
+
+    /// Serializes the document with the given html5ever options, rather than the fixed
+    /// ones of [`Html::html`]. With scripting enabled, the content of `<noscript>` is
+    /// written as raw text; [`ElementRef::serialize_with`] takes the same options, so a
+    /// `ChildrenOnly` scope writes the content of an element without its tags.
+    ///
+    /// ```
+    /// use scraper::html5ever::serialize::{SerializeOpts, TraversalScope};
+    /// use scraper::{Html, Selector};
+    ///
+    /// let html = Html::parse_document("<p>One</p><p>Two</p>");
+    /// let body = html.select_first(&Selector::parse("body").unwrap()).unwrap();
+    /// let opts = SerializeOpts {
+    ///     traversal_scope: TraversalScope::ChildrenOnly(None),
+    ///     ..SerializeOpts::default()
+    /// };
+    /// assert_eq!("<p>One</p><p>Two</p>", body.serialize_with(&opts));
+    /// ```
+    pub fn serialize_with(&self, opts: &SerializeOpts) -> String {
+        with_scratch(|buf| {
+            let _ = serialize(&mut *buf, self, opts.clone());
+            self.decode_serialized(buf)
+        })
+    }
+
+    /// Serializes the entire document with one block-level element per line, each nested
+    /// element indented by `indent` further spaces.
+    ///
+    /// Elements with text or phrasing content, such as paragraphs, stay on one line as
+    /// they are, as do `pre`, `textarea`, `script` and `style`, so only whitespace that is
+    /// not rendered changes.
+    ///
+    /// ```
+    /// use scraper::Html;
+    ///
+    /// let html = Html::parse_document("<ul><li>One</li><li><b>Two</b> items</li></ul><pre> x\n y</pre>");
+    /// assert_eq!(
+    ///     "<html>\n  <head></head>\n  <body>\n    <ul>\n      <li>One</li>\n      \
+    ///      <li><b>Two</b> items</li>\n    </ul>\n    <pre> x\n y</pre>\n  </body>\n</html>\n",
+    ///     html.html_pretty(2)
+    /// );
+    /// ```
+    pub fn html_pretty(&self, indent: usize) -> String {
+        crate::node::pretty::pretty(self.tree.root(), indent, false)
+    }
+
+    /// Runs a mutation pass on a staged copy of the document and commits it only if the
+    /// pass returns `Ok`, so a pass that fails or panics partway leaves the document as it
+    /// was. Observers are notified of the pass's mutations once it is committed. Staging
+    /// copies the tree, which costs about as much as the pass itself for most rewrites.
+    ///
+    /// ```
+    /// use scraper::{Html, Selector};
+    ///
+    /// let mut html = Html::parse_fragment("<p>One</p><p>Two</p>");
+    /// let p = Selector::parse("p").unwrap();
+    /// let result: Result<(), &str> = html.transaction(|tx| {
+    ///     let first = tx.select_first(&p).unwrap().id();
+    ///     tx.tree.get_mut(first).unwrap().detach();
+    ///     Err("second pass failed")
+    /// });
+    /// assert!(result.is_err());
+    /// assert_eq!(2, html.select(&p).count());
+    /// ```
+    pub fn transaction<T, E>(
+        &mut self,
+        pass: impl FnOnce(&mut Html) -> Result<T, E>,
+    ) -> Result<T, E> {
+        let mut staged = self.clone();
+        let (observers, record) = staged.observers.record();
+        let value = pass(&mut staged)?;
+        staged.observers = observers;
+        *self = staged;
+        self.observers.replay(&record);
+        Ok(value)
+    }
+
+    /// Returns the whole text of a text node, including any text spilled out of the tree
+    /// when parsing with [`TextOverflow::Spill`]. Returns `None` for other nodes.
+    pub fn full_text(&self, node_id: NodeId) -> Option<Cow<'_, str>> {
+        let text = self.tree.get(node_id)?.value().as_text()?;
+        Some(match self.spilled_text.get(&node_id) {
+            Some(spilled) => Cow::Owned(format!("{}{}", &**text, spilled)),
+            None => Cow::Borrowed(&**text),
+        })
     }
 
     /// Find and remove a node
     pub fn remove_node(&mut self, node_id: NodeId) {
-        if let Some(mut node) = self.tree.get_mut(node_id) {
-            node.detach();
-        }
+        self.detach(node_id);
     }
 }
 
 /// Iterator over elements matching a selector.
 #[derive(Debug)]
 pub struct Select<'a, 'b> {
+    html: &'a Html,
     inner: Nodes<'a, Node>,
     selector: &'b Selector,
 }
@@ -158,7 +432,7 @@ impl<'a, 'b> Iterator for Select<'a, 'b> {
     fn next(&mut self) -> Option<ElementRef<'a>> {
         for node in self.inner.by_ref() {
             if let Some(element) = ElementRef::wrap(node) {
-                if element.parent().is_some() && self.selector.matches(&element) {
+                if self.selector.matches(&element) && is_attached(self.html, *element) {
                     return Some(element);
                 }
             }
@@ -171,7 +445,7 @@ impl<'a, 'b> DoubleEndedIterator for Select<'a, 'b> {
     fn next_back(&mut self) -> Option<Self::Item> {
         for node in self.inner.by_ref().rev() {
             if let Some(element) = ElementRef::wrap(node) {
-                if element.parent().is_some() && self.selector.matches(&element) {
+                if self.selector.matches(&element) && is_attached(self.html, *element) {
                     return Some(element);
                 }
             }
@@ -180,13 +454,75 @@ impl<'a, 'b> DoubleEndedIterator for Select<'a, 'b> {
     }
 }
 
+/// Returns true if the node is attached under the root of the document. Detached
+/// subtrees stay in the arena that [`Html::select`] walks, so their nodes are skipped.
+pub(crate) fn is_attached(html: &Html, node: NodeRef<Node>) -> bool {
+    node.ancestors().last().unwrap_or(node).id() == html.tree.root().id()
+}
+
+#[cfg(feature = "serde_json")]
+mod cdp;
+mod element_mut;
+pub(crate) mod encoding;
+mod fetch;
+pub(crate) mod graph;
+mod handle;
+pub(crate) mod hash;
+mod index;
+#[cfg(feature = "serde_json")]
+mod json;
+mod layout;
+pub(crate) mod links;
+mod merge;
+mod minify;
+mod observe;
+mod options;
+#[cfg(feature = "rayon")]
+mod parallel;
+mod patch;
+#[cfg(feature = "serde")]
+mod persist;
+mod recovery;
 mod serializable;
+mod shared;
+mod spans;
+mod streaming;
 mod tree_sink;
+#[cfg(feature = "warc")]
+mod warc;
 
 #[cfg(test)]
 mod tests {
     use super::Html;
     use super::Selector;
+    use super::UniqueError;
+    use super::{with_scratch, SCRATCH_RETAINED};
+
+    #[test]
+    fn scratch_buffer_is_reused_and_bounded() {
+        let capacity = with_scratch(|buf| {
+            buf.extend_from_slice(b"<p>");
+            let nested = with_scratch(|inner| {
+                assert!(inner.is_empty());
+                inner.len()
+            });
+            assert_eq!(0, nested);
+            buf.capacity()
+        });
+        assert!(with_scratch(
+            |buf| buf.is_empty() && buf.capacity() == capacity
+        ));
+
+        with_scratch(|buf| buf.resize(SCRATCH_RETAINED + 1, b' '));
+        assert_eq!(0, with_scratch(|buf| buf.capacity()));
+
+        let html = Html::parse_fragment("<p>a</p><p>b</p>");
+        let p = Selector::parse("p").unwrap();
+        let mut out = String::new();
+        html.select(&p).for_each(|e| e.html_into(&mut out));
+        assert_eq!("<p>a</p><p>b</p>", out);
+        assert_eq!("<html><p>a</p><p>b</p></html>", html.html());
+    }
 
     /// Compile-time assertion that the parsed `Html` is `Send`.
     /// This is the whole point of the spider-html5ever / spider-tendril
@@ -249,4 +585,91 @@ mod tests {
             .collect();
         assert_eq!(result, vec!["element3", "element2", "element1"]);
     }
+
+    #[test]
+    fn long_text_is_split_on_char_boundaries() {
+        let options = super::ParseOptions {
+            max_text_len: Some(4),
+            ..Default::default()
+        };
+        let html = Html::parse_fragment_with("<script>aé€bcdefg</script>", &options);
+        let script = html
+            .select(&Selector::parse("script").unwrap())
+            .next()
+            .unwrap();
+        let segments: Vec<_> = script
+            .children()
+            .filter_map(|child| child.value().as_text())
+            .map(|text| &**text)
+            .collect();
+        assert_eq!(vec!["aé", "€b", "cdef", "g"], segments);
+        assert!(html.spilled_text.is_empty());
+    }
+
+    #[test]
+    fn script_and_style_content_can_be_dropped() {
+        let options = super::ParseOptions {
+            drop_script_and_style_content: true,
+            ..Default::default()
+        };
+        let html = Html::parse_document_with(
+            "<style>p { color: red }</style><script>var x = '</p>';</script><p>Kept</p>",
+            &options,
+        );
+        assert_eq!(
+            "<html><head><style></style><script></script></head><body><p>Kept</p></body></html>",
+            html.root_element().html()
+        );
+    }
+
+    #[test]
+    fn skipped_elements_are_left_out_with_their_content() {
+        let options = super::ParseOptions {
+            skip_elements: vec!["iframe".into(), "OBJECT".into(), "embed".into()],
+            ..Default::default()
+        };
+        let html = Html::parse_fragment_with(
+            "<p>A<iframe src='/x'><b>raw</b></iframe>B</p>\
+             <object data='/y'><param name='p'><div>Fallback <embed src='/z'></div></object><p>C</p>",
+            &options,
+        );
+        assert_eq!("<p>AB</p><p>C</p>", html.root_element().inner_html());
+    }
+
+    #[test]
+    fn transactions_commit_only_on_success() {
+        let mut html = Html::parse_fragment("<p>One</p>");
+        let p = Selector::parse("p").unwrap();
+        let detached = html.transaction(|tx| {
+            let first = tx.select_unique(&p)?.id();
+            tx.tree.get_mut(first).unwrap().detach();
+            tx.select_unique(&p).map(|_| ())
+        });
+        assert_eq!(Err(UniqueError::NoMatch), detached);
+        assert_eq!(1, html.select(&p).count());
+
+        let ok: Result<_, UniqueError> = html.transaction(|tx| {
+            let first = tx.select_unique(&p)?.id();
+            tx.tree.get_mut(first).unwrap().detach();
+            Ok(first)
+        });
+        assert!(ok.is_ok());
+        assert_eq!(0, html.select(&p).count());
+    }
+
+    #[test]
+    fn detached_subtrees_are_not_selected() {
+        let mut html = Html::parse_fragment("<div><p>x</p></div><p>y</p>");
+        let div = html
+            .select(&Selector::parse("div").unwrap())
+            .next()
+            .unwrap()
+            .id();
+        html.detach(div);
+        let p = Selector::parse("p").unwrap();
+        assert_eq!(1, html.select(&p).count());
+        assert_eq!("y", html.select(&p).next_back().unwrap().inner_html());
+        assert_eq!("y", html.select_unique(&p).unwrap().inner_html());
+        assert_eq!("y", html.select_first(&p).unwrap().inner_html());
+    }
 }
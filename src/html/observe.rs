@@ -0,0 +1,352 @@
+//! Notifications of changes to a document.
+
+use std::any::Any;
+use std::fmt;
+use std::sync::{Arc, Mutex, Weak};
+
+use ego_tree::NodeId;
+use html5ever::{LocalName, QualName};
+
+use super::Html;
+use crate::node::Node;
+
+/// A change to a document, passed to the observers registered with [`Html::on_mutation`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mutation {
+    /// A node was added under `parent`.
+    Attached {
+        /// The added node.
+        node: NodeId,
+        /// Its new parent.
+        parent: NodeId,
+    },
+    /// A node and its subtree were taken out of the tree.
+    Detached {
+        /// The removed node.
+        node: NodeId,
+        /// Its parent before removal, or `None` if it already had none.
+        parent: Option<NodeId>,
+    },
+    /// An attribute of an element was set or removed.
+    AttributeChanged {
+        /// The element.
+        node: NodeId,
+        /// The attribute name.
+        name: String,
+        /// The value before the change, or `None` if it was not set.
+        old: Option<String>,
+        /// The value after the change, or `None` if it was removed.
+        new: Option<String>,
+    },
+}
+
+pub(crate) type Observer = Arc<dyn Fn(&Mutation) + Send + Sync>;
+
+/// A registered observer, with the value it serves when it is only needed while that
+/// value lives.
+#[derive(Clone)]
+struct Registration {
+    observer: Observer,
+    owner: Option<Weak<dyn Any + Send + Sync>>,
+}
+
+impl Registration {
+    fn is_live(&self) -> bool {
+        self.owner
+            .as_ref()
+            .is_none_or(|owner| owner.strong_count() > 0)
+    }
+}
+
+/// The observers of a document, shared by its clones.
+#[derive(Clone, Default)]
+pub(crate) struct Observers(Vec<Registration>);
+
+impl fmt::Debug for Observers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Observers({})", self.0.len())
+    }
+}
+
+impl Observers {
+    pub(crate) fn push(&mut self, observer: Observer) {
+        self.0.retain(Registration::is_live);
+        self.0.push(Registration {
+            observer,
+            owner: None,
+        });
+    }
+
+    /// Registers an observer that is dropped once `owner` is, at the next registration.
+    pub(crate) fn push_owned(&mut self, observer: Observer, owner: Weak<dyn Any + Send + Sync>) {
+        self.0.retain(Registration::is_live);
+        self.0.push(Registration {
+            observer,
+            owner: Some(owner),
+        });
+    }
+
+    /// Returns the number of registered observers, counting dropped owners not yet pruned.
+    #[cfg(test)]
+    pub(crate) fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns true if this observer is registered.
+    pub(crate) fn contains(&self, observer: &Observer) -> bool {
+        self.0.iter().any(|r| Arc::ptr_eq(&r.observer, observer))
+    }
+
+    fn notify(&self, mutation: &Mutation) {
+        for registration in self.0.iter().filter(|r| r.is_live()) {
+            (registration.observer)(mutation);
+        }
+    }
+
+    /// Replaces the observers with one that records every mutation, returning the
+    /// previous observers and the record.
+    pub(crate) fn record(&mut self) -> (Observers, Arc<Mutex<Vec<Mutation>>>) {
+        let record = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&record);
+        let recorder: Observer = Arc::new(move |mutation: &Mutation| {
+            if let Ok(mut record) = sink.lock() {
+                record.push(mutation.clone());
+            }
+        });
+        let recorder = Registration {
+            observer: recorder,
+            owner: None,
+        };
+        (std::mem::replace(self, Observers(vec![recorder])), record)
+    }
+
+    /// Notifies the observers of each recorded mutation, in order.
+    pub(crate) fn replay(&self, record: &Mutex<Vec<Mutation>>) {
+        if let Ok(record) = record.lock() {
+            record.iter().for_each(|mutation| self.notify(mutation));
+        }
+    }
+}
+
+impl Html {
+    /// Registers an observer notified of each change made through the mutation methods of
+    /// `Html`, such as [`Html::append_child`], [`Html::detach`] and [`Html::set_attr`], so
+    /// derived indexes can be kept in sync. Changes made through `tree` directly can be
+    /// reported with [`Html::notify`]. Clones of the document share its observers.
+    ///
+    /// ```
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// use scraper::html::Mutation;
+    /// use scraper::{Html, Selector};
+    ///
+    /// let mut html = Html::parse_fragment(r#"<p id="a">One</p>"#);
+    /// let ids = Arc::new(Mutex::new(Vec::new()));
+    /// let seen = Arc::clone(&ids);
+    /// html.on_mutation(move |mutation| {
+    ///     if let Mutation::AttributeChanged { name, new, .. } = mutation {
+    ///         if name == "id" {
+    ///             seen.lock().unwrap().push(new.clone());
+    ///         }
+    ///     }
+    /// });
+    ///
+    /// let p = html.select_first(&Selector::parse("p").unwrap()).unwrap().id();
+    /// html.set_attr(p, "id", "b");
+    /// html.remove_attr(p, "id");
+    /// assert_eq!(vec![Some("b".to_owned()), None], *ids.lock().unwrap());
+    /// ```
+    pub fn on_mutation(&mut self, observer: impl Fn(&Mutation) + Send + Sync + 'static) {
+        self.observers.push(Arc::new(observer));
+    }
+
+    /// Removes every observer.
+    pub fn clear_observers(&mut self) {
+        self.observers.0.clear();
+    }
+
+    /// Notifies the observers of a change made through `tree` directly.
+    pub fn notify(&self, mutation: &Mutation) {
+        self.observers.notify(mutation);
+    }
+
+    /// Appends a node to the children of `parent` and returns its ID, or `None` if there
+    /// is no such parent.
+    pub fn append_child(&mut self, parent: NodeId, node: Node) -> Option<NodeId> {
+        let node = self.tree.get_mut(parent)?.append(node).id();
+        self.notify(&Mutation::Attached { node, parent });
+        Some(node)
+    }
+
+    /// Inserts a node before `sibling` and returns its ID, or `None` if there is no such
+    /// sibling or it has no parent.
+    pub fn insert_before(&mut self, sibling: NodeId, node: Node) -> Option<NodeId> {
+        self.insert_sibling(sibling, node, true)
+    }
+
+    /// Inserts a node after `sibling` and returns its ID, or `None` if there is no such
+    /// sibling or it has no parent.
+    pub fn insert_after(&mut self, sibling: NodeId, node: Node) -> Option<NodeId> {
+        self.insert_sibling(sibling, node, false)
+    }
+
+    fn insert_sibling(&mut self, sibling: NodeId, node: Node, before: bool) -> Option<NodeId> {
+        let mut sibling = self.tree.get_mut(sibling)?;
+        let parent = sibling.parent()?.id();
+        let node = if before {
+            sibling.insert_before(node).id()
+        } else {
+            sibling.insert_after(node).id()
+        };
+        self.notify(&Mutation::Attached { node, parent });
+        Some(node)
+    }
+
+    /// Takes every child of a node, with its subtree, out of the tree.
+    pub fn remove_children(&mut self, parent: NodeId) {
+        let Some(node) = self.tree.get(parent) else {
+            return;
+        };
+        let children: Vec<NodeId> = node.children().map(|child| child.id()).collect();
+        for child in children {
+            self.detach(child);
+        }
+    }
+
+    /// Takes a node and its subtree out of the tree.
+    pub fn detach(&mut self, node_id: NodeId) {
+        let Some(mut node) = self.tree.get_mut(node_id) else {
+            return;
+        };
+        let parent = node.parent().map(|parent| parent.id());
+        node.detach();
+        self.notify(&Mutation::Detached {
+            node: node_id,
+            parent,
+        });
+    }
+
+    /// Sets an attribute of an element. Does nothing for other nodes.
+    pub fn set_attr(&mut self, node_id: NodeId, name: &str, value: &str) {
+        let Some(mut node) = self.tree.get_mut(node_id) else {
+            return;
+        };
+        let Some(element) = node.value().as_element_mut() else {
+            return;
+        };
+        let old = element.attr(name).map(str::to_owned);
+        element.set_attr(name, value);
+        self.notify(&Mutation::AttributeChanged {
+            node: node_id,
+            name: name.to_owned(),
+            old,
+            new: Some(value.to_owned()),
+        });
+    }
+
+    /// Removes an attribute of an element. Does nothing if it is not set.
+    pub fn remove_attr(&mut self, node_id: NodeId, name: &str) {
+        let Some(mut node) = self.tree.get_mut(node_id) else {
+            return;
+        };
+        let Some(element) = node.value().as_element_mut() else {
+            return;
+        };
+        let key = QualName::new(None, ns!(), LocalName::from(name));
+        let Some(old) = element.attrs.remove(&key) else {
+            return;
+        };
+        match name {
+            "id" => element.id = None,
+            "class" => element.classes.clear(),
+            _ => {}
+        }
+        self.notify(&Mutation::AttributeChanged {
+            node: node_id,
+            name: name.to_owned(),
+            old: Some(old.to_string()),
+            new: None,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::Mutation;
+    use crate::html::{Html, MergeStrategy};
+    use crate::node::{Node, Text};
+
+    #[test]
+    fn transactions_replay_committed_mutations_only() {
+        let mut html = Html::parse_fragment("<p>One</p>");
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&log);
+        html.on_mutation(move |mutation| sink.lock().unwrap().push(mutation.clone()));
+        let root = html.root_element().id();
+        let text = || Node::Text(Text { text: "Two".into() });
+
+        let failed: Result<(), ()> = html.transaction(|tx| {
+            tx.append_child(root, text());
+            Err(())
+        });
+        assert!(failed.is_err());
+        assert!(log.lock().unwrap().is_empty());
+
+        let node = html
+            .transaction(|tx| tx.append_child(root, text()).ok_or(()))
+            .unwrap();
+        html.detach(node);
+        assert_eq!(
+            vec![
+                Mutation::Attached { node, parent: root },
+                Mutation::Detached {
+                    node,
+                    parent: Some(root)
+                },
+            ],
+            *log.lock().unwrap()
+        );
+    }
+
+    #[test]
+    fn document_mutators_notify_observers() {
+        // Counts the attachments, detachments and attribute changes `mutate` reports.
+        fn observed(source: &str, mutate: impl FnOnce(&mut Html)) -> (usize, usize, usize) {
+            let mut html = Html::parse_document(source);
+            let log = Arc::new(Mutex::new(Vec::new()));
+            let sink = Arc::clone(&log);
+            html.on_mutation(move |mutation| sink.lock().unwrap().push(mutation.clone()));
+            mutate(&mut html);
+            let log = log.lock().unwrap();
+            let count = |f: fn(&Mutation) -> bool| log.iter().filter(|m| f(m)).count();
+            (
+                count(|m| matches!(m, Mutation::Attached { .. })),
+                count(|m| matches!(m, Mutation::Detached { .. })),
+                count(|m| matches!(m, Mutation::AttributeChanged { .. })),
+            )
+        }
+
+        let toc = observed("<h2>Intro</h2><h2 id='b'>B</h2>", |html| {
+            html.generate_toc(3);
+        });
+        assert_eq!((0, 0, 1), toc);
+        let highlight = observed("<p>a cat b</p>", |html| {
+            html.highlight(&["cat"], "mark");
+        });
+        assert_eq!((4, 1, 0), highlight);
+        let lazy = observed(
+            "<img data-src='/a.jpg'><amp-img src='/b.jpg'><i>b</i></amp-img>",
+            |html| {
+                html.resolve_lazy_images();
+            },
+        );
+        assert_eq!((0, 1, 1), lazy);
+        let amp = Html::parse_document("<h1>T</h1><p>One.</p><p>Two <b>x</b></p><p>Three.</p>");
+        let merge = observed("<h1>T</h1><p>One.</p><p>Three.</p>", |html| {
+            html.merge_variant(&amp, MergeStrategy::FillGaps);
+        });
+        assert_eq!((4, 0, 0), merge);
+    }
+}
@@ -0,0 +1,41 @@
+//! Options for parsing.
+
+use encoding_rs::Encoding;
+
+/// What happens to the text of a node beyond [`ParseOptions::max_text_len`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TextOverflow {
+    /// The text continues in sibling text nodes of at most the maximum length each.
+    #[default]
+    Split,
+    /// The text is kept out of the tree, in [`Html::spilled_text`](super::Html::spilled_text),
+    /// and only read through [`Html::full_text`](super::Html::full_text).
+    Spill,
+}
+
+/// Options for [`Html::parse_document_with`](super::Html::parse_document_with) and
+/// [`Html::parse_fragment_with`](super::Html::parse_fragment_with).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// The most bytes a text node holds, such as the contents of a multi-megabyte inline
+    /// `<script>` of JSON; unlimited if `None`. Text is cut at character boundaries, so a
+    /// node may hold slightly less.
+    pub max_text_len: Option<usize>,
+    /// What happens to the text beyond `max_text_len`.
+    pub text_overflow: TextOverflow,
+    /// Discard the text inside `<script>` and `<style>` elements, keeping the elements
+    /// themselves, for workloads that only extract text.
+    pub drop_script_and_style_content: bool,
+    /// The local names of HTML elements that are left out of the tree along with
+    /// everything inside them, such as `["iframe", "object", "embed"]`. Their text is
+    /// discarded as it is parsed rather than stored, and the elements nested in them are
+    /// never attached. Names are matched ASCII case-insensitively.
+    pub skip_elements: Vec<String>,
+    /// Record the markup errors the parser recovers from in
+    /// [`Html::recoveries`](super::Html::recoveries), with detailed messages.
+    pub record_recoveries: bool,
+    /// The encoding to decode documents given as bytes with, instead of detecting it, for
+    /// [`Html::parse_document_bytes_with`](super::Html::parse_document_bytes_with).
+    /// Ignored when parsing strings.
+    pub encoding: Option<&'static Encoding>,
+}
@@ -0,0 +1,102 @@
+//! Selection on a thread pool, for documents large enough that matching a complex selector
+//! against every element is the bottleneck.
+//!
+//! Matching reads the document from every thread of the pool, so it is done on a
+//! [`SharedHtml`], the `Sync` form of a document, rather than on an [`Html`](super::Html).
+
+use std::fmt;
+
+use ego_tree::NodeId;
+use rayon::prelude::*;
+
+use super::{is_attached, SharedHtml};
+use crate::element_ref::ElementRef;
+use crate::selector::Selector;
+
+/// An element matched by [`SharedHtml::par_select`]. Unlike an [`ElementRef`], it can be
+/// passed between threads.
+#[derive(Clone, Copy)]
+pub struct SharedElement<'a> {
+    html: &'a SharedHtml,
+    id: NodeId,
+}
+
+impl fmt::Debug for SharedElement<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("SharedElement").field(&self.id).finish()
+    }
+}
+
+impl<'a> SharedElement<'a> {
+    /// Returns the ID of the element.
+    pub fn id(&self) -> NodeId {
+        self.id
+    }
+
+    /// Returns the element, for use on the current thread.
+    pub fn element(&self) -> ElementRef<'a> {
+        self.html
+            .tree
+            .get(self.id)
+            .and_then(ElementRef::wrap)
+            .expect("matched node is an element")
+    }
+}
+
+impl SharedHtml {
+    /// Returns a parallel iterator over the elements matching a selector, which matches
+    /// the nodes of the document in chunks spread over the rayon thread pool. Collecting
+    /// it gives the elements [`Html::select`](super::Html::select) returns, in the same
+    /// order.
+    ///
+    /// ```
+    /// use rayon::prelude::*;
+    /// use scraper::{Html, Selector};
+    ///
+    /// let html = Html::parse_document(&"<p><a href=/x>x</a></p>".repeat(1000)).into_shared();
+    /// let selector = Selector::parse("p > a[href]").unwrap();
+    /// let hrefs: Vec<String> = html
+    ///     .par_select(&selector)
+    ///     .map(|a| a.element().attr("href").unwrap_or_default().to_owned())
+    ///     .collect();
+    /// assert_eq!(1000, hrefs.len());
+    /// ```
+    pub fn par_select<'a>(
+        &'a self,
+        selector: &'a Selector,
+    ) -> impl ParallelIterator<Item = SharedElement<'a>> + 'a {
+        let ids: Vec<NodeId> = self.tree.nodes().map(|node| node.id()).collect();
+        ids.into_par_iter().filter_map(move |id| {
+            let element = ElementRef::wrap(self.tree.get(id)?)?;
+            (selector.matches(&element) && is_attached(self, *element))
+                .then_some(SharedElement { html: self, id })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rayon::prelude::*;
+
+    use crate::html::Html;
+    use crate::selector::Selector;
+
+    #[test]
+    fn parallel_matches_equal_sequential_ones() {
+        let mut html = Html::parse_document(
+            &r#"<ul><li class="a">1</li><li>2<b class="a">3</b></li></ul>"#.repeat(500),
+        );
+        let detached = html
+            .select_first(&Selector::parse("li").unwrap())
+            .unwrap()
+            .id();
+        html.tree.get_mut(detached).unwrap().detach();
+        let html = html.into_shared();
+
+        let selector = Selector::parse("ul > li:nth-child(2) .a, li.a:first-child").unwrap();
+        let sequential: Vec<_> = html.select(&selector).map(|e| e.id()).collect();
+        let parallel: Vec<_> = html.par_select(&selector).map(|e| e.id()).collect();
+        assert_eq!(998, parallel.len());
+        assert_eq!(sequential, parallel);
+    }
+}
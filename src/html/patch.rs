@@ -0,0 +1,221 @@
+//! Compact patches between two versions of a document.
+
+use std::collections::BTreeMap;
+
+use super::{Html, SubtreeHashes};
+use crate::element_ref::ElementRef;
+use crate::node::Node;
+
+/// One change of a [`Patch`], addressed by the CSS path of the element in the old document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "op", rename_all = "snake_case"))]
+pub enum PatchOp {
+    /// The element's outer HTML is replaced.
+    Replace {
+        /// The [`ElementRef::css_path`] of the element.
+        path: String,
+        /// The new outer HTML.
+        html: String,
+    },
+    /// Only the element's attributes changed.
+    Attributes {
+        /// The [`ElementRef::css_path`] of the element.
+        path: String,
+        /// The attributes added or changed, with their new values.
+        set: BTreeMap<String, String>,
+        /// The names of the attributes removed.
+        removed: Vec<String>,
+    },
+}
+
+/// The changes turning one version of a document into another, from
+/// [`Html::serialize_patch`].
+///
+/// The operations touch disjoint elements and are listed innermost first, so applying them
+/// in order keeps the later paths valid even when an `id` they start from changes.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Patch {
+    /// The operations, in application order.
+    pub ops: Vec<PatchOp>,
+}
+
+impl Patch {
+    /// Returns true if both versions are the same.
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    /// Serializes the patch as compact JSON, such as
+    /// `{"ops":[{"op":"replace","path":"#price","html":"<b id=\"price\">$5</b>"}]}`.
+    #[cfg(feature = "serde_json")]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
+
+/// Returns true if the two elements have the same child nodes apart from the contents of
+/// child elements, so they can be compared child by child.
+fn same_shape(old: ElementRef, new: ElementRef) -> bool {
+    let mut old = old.children();
+    let mut new = new.children();
+    loop {
+        match (old.next(), new.next()) {
+            (None, None) => return true,
+            (Some(a), Some(b)) => {
+                let same = match (a.value(), b.value()) {
+                    (Node::Element(a), Node::Element(b)) => a.name == b.name,
+                    (Node::Text(a), Node::Text(b)) => **a == **b,
+                    (Node::Comment(a), Node::Comment(b)) => **a == **b,
+                    (Node::ProcessingInstruction(a), Node::ProcessingInstruction(b)) => {
+                        a.target == b.target && a.data == b.data
+                    }
+                    (Node::Doctype(_), Node::Doctype(_)) => true,
+                    _ => false,
+                };
+                if !same {
+                    return false;
+                }
+            }
+            _ => return false,
+        }
+    }
+}
+
+/// Builds the operations turning `old` into `new`, two elements with the same name.
+struct Differ {
+    old: SubtreeHashes,
+    new: SubtreeHashes,
+    ops: Vec<PatchOp>,
+}
+
+impl Differ {
+    fn diff(&mut self, old: ElementRef, new: ElementRef) {
+        if self.old.get(old.id()) == self.new.get(new.id()) {
+            return;
+        }
+        if !same_shape(old, new) {
+            self.ops.push(PatchOp::Replace {
+                path: old.css_path(),
+                html: new.html(),
+            });
+            return;
+        }
+
+        let children = old.children().zip(new.children());
+        for (a, b) in
+            children.filter_map(|(a, b)| Some((ElementRef::wrap(a)?, ElementRef::wrap(b)?)))
+        {
+            self.diff(a, b);
+        }
+
+        let (old_value, new_value) = (old.value(), new.value());
+        let set: BTreeMap<String, String> = new_value
+            .attrs()
+            .filter(|(name, value)| old_value.attr(name) != Some(value))
+            .map(|(name, value)| (name.to_owned(), value.to_owned()))
+            .collect();
+        let mut removed: Vec<String> = old_value
+            .attrs()
+            .filter(|(name, _)| new_value.attr(name).is_none())
+            .map(|(name, _)| name.to_owned())
+            .collect();
+        if !set.is_empty() || !removed.is_empty() {
+            removed.sort_unstable();
+            self.ops.push(PatchOp::Attributes {
+                path: old.css_path(),
+                set,
+                removed,
+            });
+        }
+    }
+}
+
+impl Html {
+    /// Returns the patch that turns `old` into this document.
+    ///
+    /// Unchanged subtrees are skipped by their [`SubtreeHashes`]. An element whose
+    /// attributes changed but whose children did not is patched attribute by attribute,
+    /// and where the child nodes themselves differ the element is replaced whole.
+    ///
+    /// ```
+    /// use scraper::html::PatchOp;
+    /// use scraper::Html;
+    ///
+    /// let old = Html::parse_document(r#"<ul><li class="a">One</li><li>Two</li></ul>"#);
+    /// let new = Html::parse_document(r#"<ul><li class="b">One</li><li>2</li></ul>"#);
+    /// let patch = new.serialize_patch(&old);
+    ///
+    /// assert_eq!(2, patch.ops.len());
+    /// assert!(matches!(&patch.ops[1], PatchOp::Replace { path, html }
+    ///     if path == "html > body > ul > li:nth-of-type(2)" && html == "<li>2</li>"));
+    /// ```
+    pub fn serialize_patch(&self, old: &Html) -> Patch {
+        let (old_root, new_root) = (old.root_element(), self.root_element());
+        if old_root.value().name != new_root.value().name {
+            return Patch {
+                ops: vec![PatchOp::Replace {
+                    path: old_root.css_path(),
+                    html: new_root.html(),
+                }],
+            };
+        }
+
+        let mut differ = Differ {
+            old: old.subtree_hashes(),
+            new: self.subtree_hashes(),
+            ops: Vec::new(),
+        };
+        differ.diff(old_root, new_root);
+        Patch { ops: differ.ops }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::PatchOp;
+    use crate::html::Html;
+
+    #[test]
+    fn attribute_changes_are_reported_innermost_first() {
+        let old = Html::parse_document(
+            r#"<div id="main" data-v="1"><p>Same</p><a href="/a" title="t">Link</a></div>"#,
+        );
+        let new = Html::parse_document(
+            r#"<div id="app" data-v="1"><p>Same</p><a href="/b">Link</a></div>"#,
+        );
+        let patch = new.serialize_patch(&old);
+        assert_eq!(
+            vec![
+                PatchOp::Attributes {
+                    path: "#main > a".into(),
+                    set: BTreeMap::from([("href".into(), "/b".into())]),
+                    removed: vec!["title".into()],
+                },
+                PatchOp::Attributes {
+                    path: "#main".into(),
+                    set: BTreeMap::from([("id".into(), "app".into())]),
+                    removed: Vec::new(),
+                },
+            ],
+            patch.ops
+        );
+        assert!(old.serialize_patch(&old).is_empty());
+    }
+
+    #[test]
+    fn paths_do_not_start_at_duplicate_ids() {
+        let old = Html::parse_document(r#"<div id="a"><p>x</p></div><div id="a"><p>y</p></div>"#);
+        let new = Html::parse_document(r#"<div id="a"><p>x</p></div><div id="a"><p>z</p></div>"#);
+        let patch = new.serialize_patch(&old);
+        let [PatchOp::Replace { path, .. }] = &patch.ops[..] else {
+            panic!("{:?}", patch.ops);
+        };
+        let selector = crate::selector::Selector::parse(path).unwrap();
+        let found: Vec<_> = old.select(&selector).map(|p| p.inner_html()).collect();
+        assert_eq!(vec!["y"], found);
+    }
+}
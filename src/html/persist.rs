@@ -0,0 +1,203 @@
+//! `serde` support for documents, to cache parsed documents without parsing them again.
+//!
+//! The tree is written as a flat list of nodes in document order, each after its parent,
+//! rather than nested, so deep documents neither overflow the stack nor hit the recursion
+//! limits of formats such as JSON. Detached nodes are left out and node IDs are not kept:
+//! a restored document numbers its nodes in document order.
+
+use std::collections::HashMap;
+
+use ego_tree::{NodeId, Tree};
+use encoding_rs::Encoding;
+use html5ever::tree_builder::QuirksMode;
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::{EncodingInfo, EncodingSource, FetchContext, Html, RecoveryEvent, SourceSpan};
+use crate::node::Node;
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum QuirksRepr {
+    Quirks,
+    LimitedQuirks,
+    NoQuirks,
+}
+
+/// An [`EncodingInfo`] with the encoding by name.
+#[derive(Serialize, Deserialize)]
+struct EncodingRepr {
+    encoding: String,
+    source: EncodingSource,
+    confidence: f32,
+    malformed: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+struct HtmlRepr<N> {
+    quirks_mode: QuirksRepr,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    lang: String,
+    /// The nodes in document order, starting with the root.
+    nodes: Vec<N>,
+    /// The index in `nodes` of the parent of every node but the root.
+    parents: Vec<usize>,
+    /// Spilled text by the index of its node.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    spilled_text: Vec<(usize, String)>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    recoveries: Vec<RecoveryEvent>,
+    /// Text spans by the index of their node.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    text_spans: Vec<(usize, SourceSpan)>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    fetch: Option<FetchContext>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    encoding: Option<EncodingRepr>,
+}
+
+impl Serialize for Html {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut index: HashMap<NodeId, usize> = HashMap::new();
+        let mut nodes = Vec::new();
+        let mut parents = Vec::new();
+        for node in self.tree.root().descendants() {
+            if let Some(parent) = node.parent() {
+                parents.push(index[&parent.id()]);
+            }
+            index.insert(node.id(), nodes.len());
+            nodes.push(node.value());
+        }
+        let mut spilled_text: Vec<_> = self
+            .spilled_text
+            .iter()
+            .filter_map(|(id, text)| Some((*index.get(id)?, text.clone())))
+            .collect();
+        spilled_text.sort_unstable();
+        let mut text_spans: Vec<_> = self
+            .text_spans
+            .iter()
+            .filter_map(|(id, span)| Some((*index.get(id)?, *span)))
+            .collect();
+        text_spans.sort_unstable_by_key(|&(index, _)| index);
+
+        HtmlRepr {
+            quirks_mode: match self.quirks_mode {
+                QuirksMode::Quirks => QuirksRepr::Quirks,
+                QuirksMode::LimitedQuirks => QuirksRepr::LimitedQuirks,
+                QuirksMode::NoQuirks => QuirksRepr::NoQuirks,
+            },
+            lang: self.lang.clone(),
+            nodes,
+            parents,
+            spilled_text,
+            recoveries: self.recoveries.clone(),
+            text_spans,
+            fetch: self.fetch.clone(),
+            encoding: self.encoding.map(|info| EncodingRepr {
+                encoding: info.encoding.name().to_owned(),
+                source: info.source,
+                confidence: info.confidence,
+                malformed: info.malformed,
+            }),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Html {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = HtmlRepr::<Node>::deserialize(deserializer)?;
+        if repr.parents.len() + 1 != repr.nodes.len() {
+            return Err(D::Error::custom(format!(
+                "{} nodes but {} parents",
+                repr.nodes.len(),
+                repr.parents.len()
+            )));
+        }
+
+        let mut nodes = repr.nodes.into_iter();
+        let mut tree = Tree::new(nodes.next().unwrap_or(Node::Document));
+        let mut ids = vec![tree.root().id()];
+        for (node, parent) in nodes.zip(repr.parents) {
+            let Some(&parent_id) = ids.get(parent) else {
+                return Err(D::Error::custom(format!(
+                    "node {} has parent {}, which does not come before it",
+                    ids.len(),
+                    parent
+                )));
+            };
+            let id = tree.get_mut(parent_id).unwrap().append(node).id();
+            ids.push(id);
+        }
+        let spilled_text = repr
+            .spilled_text
+            .into_iter()
+            .filter_map(|(index, text)| Some((*ids.get(index)?, text)))
+            .collect();
+        let encoding = match repr.encoding {
+            Some(info) => Some(EncodingInfo {
+                encoding: Encoding::for_label(info.encoding.as_bytes()).ok_or_else(|| {
+                    D::Error::custom(format!("unknown encoding {}", info.encoding))
+                })?,
+                source: info.source,
+                confidence: info.confidence,
+                malformed: info.malformed,
+            }),
+            None => None,
+        };
+        let text_spans = repr
+            .text_spans
+            .into_iter()
+            .filter_map(|(index, span)| Some((*ids.get(index)?, span)))
+            .collect();
+
+        let quirks_mode = match repr.quirks_mode {
+            QuirksRepr::Quirks => QuirksMode::Quirks,
+            QuirksRepr::LimitedQuirks => QuirksMode::LimitedQuirks,
+            QuirksRepr::NoQuirks => QuirksMode::NoQuirks,
+        };
+        Ok(Html {
+            lang: repr.lang,
+            spilled_text,
+            recoveries: repr.recoveries,
+            text_spans,
+            fetch: repr.fetch,
+            encoding,
+            ..Html::from_parts(quirks_mode, tree)
+        })
+    }
+}
+
+#[cfg(all(test, feature = "serde_json"))]
+mod tests {
+    use crate::html::Html;
+    use crate::selector::Selector;
+
+    #[test]
+    fn round_trips_deep_documents_and_names() {
+        let deep = "<div>".repeat(2000);
+        let source = format!(
+            r##"<!DOCTYPE html><p class="a b" id="x" data-k="v">Hi</p><svg xlink:href="#s"><foreignObject/></svg>{}"##,
+            deep
+        );
+        let html = Html::parse_document(&source);
+        let json = serde_json::to_string(&html).unwrap();
+        let restored: Html = serde_json::from_str(&json).unwrap();
+        assert_eq!(html.minify(), restored.minify());
+        let nodes = |html: &Html| {
+            let root = html.tree.root();
+            let elements = root
+                .descendants()
+                .filter_map(|node| node.value().as_element());
+            elements.cloned().collect::<Vec<_>>()
+        };
+        assert_eq!(nodes(&html), nodes(&restored));
+        assert_eq!(json, serde_json::to_string(&restored).unwrap());
+
+        let p = restored.select_first(&Selector::parse("p.b#x").unwrap());
+        assert_eq!(Some("v"), p.and_then(|p| p.value().attr("data-k")));
+        let bad = json.replacen(r#""parents":[0"#, r#""parents":[5"#, 1);
+        assert!(serde_json::from_str::<Html>(&bad).is_err());
+    }
+}
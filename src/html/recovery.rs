@@ -0,0 +1,110 @@
+//! Markup errors the parser recovered from.
+
+/// The kind of a [`RecoveryEvent`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum RecoveryKind {
+    /// A repeated attribute on a tag, which was dropped in favour of the first.
+    DuplicateAttribute,
+    /// A tag closed or opened out of order, which the tree builder rearranged.
+    MisnestedTag,
+    /// An element still open where it must be closed, such as at the end of the body.
+    UnclosedElement,
+    /// A malformed or unterminated character reference.
+    CharacterReference,
+    /// The input ended inside a tag, comment or other construct.
+    UnexpectedEof,
+    /// Any other recovery, such as a stray end tag that was ignored.
+    Other,
+}
+
+impl RecoveryKind {
+    /// Classifies a parser error message.
+    fn of(message: &str) -> Self {
+        const MISNESTED: &[&str] = &[
+            "Formatting element",
+            "Unexpected open element",
+            "Found special tag",
+            "Closing wrong heading",
+            "No matching tag",
+            "No <p> tag",
+            "No heading tag",
+            "expected to close",
+            "</body> with no",
+            "</html> with no",
+        ];
+        if message == "Duplicate attribute" {
+            RecoveryKind::DuplicateAttribute
+        } else if message.starts_with("Unexpected open tag") {
+            RecoveryKind::UnclosedElement
+        } else if MISNESTED.iter().any(|prefix| message.starts_with(prefix))
+            || message.to_ascii_lowercase().starts_with("nested ")
+        {
+            RecoveryKind::MisnestedTag
+        } else if message.contains("character reference") {
+            RecoveryKind::CharacterReference
+        } else if message.contains("EOF") {
+            RecoveryKind::UnexpectedEof
+        } else {
+            RecoveryKind::Other
+        }
+    }
+}
+
+/// A markup error the parser recovered from, recorded in [`Html::recoveries`] when parsing
+/// with [`ParseOptions::record_recoveries`].
+///
+/// [`Html::recoveries`]: super::Html::recoveries
+/// [`ParseOptions::record_recoveries`]: super::ParseOptions::record_recoveries
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RecoveryEvent {
+    /// What was wrong.
+    pub kind: RecoveryKind,
+    /// The line of the input the parser was on, counting from 1.
+    pub line: u64,
+    /// The parser's description, such as
+    /// `Unexpected open tag {http://www.w3.org/1999/xhtml}:div at end of body`.
+    pub message: String,
+}
+
+impl RecoveryEvent {
+    pub(crate) fn new(line: u64, message: &str) -> Self {
+        RecoveryEvent {
+            kind: RecoveryKind::of(message),
+            line,
+            message: message.to_owned(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RecoveryKind;
+    use crate::html::{Html, ParseOptions};
+
+    #[test]
+    fn records_recoveries_with_lines() {
+        let options = ParseOptions {
+            record_recoveries: true,
+            ..ParseOptions::default()
+        };
+        let html = Html::parse_document_with(
+            "<!DOCTYPE html><p class=a class=b>One\n<b><i>Two</b></i>\n<div>&#;<span>Three",
+            &options,
+        );
+        let kinds: Vec<_> = html.recoveries.iter().map(|r| (r.kind, r.line)).collect();
+        assert_eq!(
+            vec![
+                (RecoveryKind::DuplicateAttribute, 1),
+                (RecoveryKind::MisnestedTag, 2),
+                (RecoveryKind::MisnestedTag, 2),
+                (RecoveryKind::CharacterReference, 3),
+                (RecoveryKind::UnclosedElement, 3),
+            ],
+            kinds
+        );
+        assert!(Html::parse_document("<p a a>").recoveries.is_empty());
+    }
+}
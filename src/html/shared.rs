@@ -0,0 +1,133 @@
+//! Documents that can be read from several threads at once.
+
+use std::ops::Deref;
+
+use super::Html;
+use crate::node::{AtomicStrTendril, Node};
+
+/// A document that is `Sync` as well as `Send`, so it can be parsed on one thread and
+/// selected from on many, such as behind an [`Arc`](std::sync::Arc). Created with
+/// [`Html::into_shared`], it derefs to the [`Html`] for reading and is turned back into one
+/// with [`SharedHtml::into_inner`] for changes.
+///
+/// ```
+/// use std::sync::Arc;
+/// use std::thread;
+///
+/// use scraper::{Html, Selector};
+///
+/// let html = Arc::new(Html::parse_document("<p>One</p><p>Two</p>").into_shared());
+/// let workers: Vec<_> = ["p:first-child", "p:last-child"]
+///     .into_iter()
+///     .map(|selector| {
+///         let html = Arc::clone(&html);
+///         thread::spawn(move || {
+///             let selector = Selector::parse(selector).unwrap();
+///             html.select_first(&selector).unwrap().inner_html()
+///         })
+///     })
+///     .collect();
+/// let texts: Vec<String> = workers.into_iter().map(|w| w.join().unwrap()).collect();
+/// assert_eq!(vec!["One", "Two"], texts);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SharedHtml(Html);
+
+// SAFETY: the tendrils of the tree are the only part of `Html` that is not `Sync`. Their
+// reference counts are atomic, but cloning a tendril that owns its buffer flags the buffer
+// as shared through a `Cell`. `Html::into_shared` flags every buffer first, after which
+// reading and cloning tendrils through `&` only touches the atomic count, and `SharedHtml`
+// gives no `&mut` access that could give a tendril an owned buffer again. Clones of a shared
+// tendril are themselves shared, so clones of a `SharedHtml` keep this.
+unsafe impl Sync for SharedHtml {}
+
+/// Flags the buffer of a tendril as shared, if it has one.
+fn share(tendril: &AtomicStrTendril) {
+    drop(tendril.clone());
+}
+
+impl Html {
+    /// Converts the document into one that can be read from several threads at once. The
+    /// tree is not copied: the text of its nodes is flagged as shared in place.
+    pub fn into_shared(self) -> SharedHtml {
+        for node in self.tree.values() {
+            match node {
+                Node::Doctype(doctype) => {
+                    share(&doctype.name);
+                    share(&doctype.public_id);
+                    share(&doctype.system_id);
+                }
+                Node::Comment(comment) => share(&comment.comment),
+                Node::Text(text) => share(&text.text),
+                Node::Element(element) => element.attrs.values().for_each(share),
+                Node::ProcessingInstruction(pi) => {
+                    share(&pi.target);
+                    share(&pi.data);
+                }
+                Node::Document | Node::Fragment => {}
+            }
+        }
+        SharedHtml(self)
+    }
+}
+
+impl SharedHtml {
+    /// Returns the document, for changes.
+    pub fn into_inner(self) -> Html {
+        self.0
+    }
+}
+
+impl Deref for SharedHtml {
+    type Target = Html;
+
+    fn deref(&self) -> &Html {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::SharedHtml;
+    use crate::html::Html;
+    use crate::selector::Selector;
+
+    #[test]
+    fn shared_html_is_sync() {
+        fn assert_sync<T: Send + Sync>() {}
+        assert_sync::<SharedHtml>();
+    }
+
+    #[test]
+    fn shared_documents_are_read_concurrently() {
+        fn assert_sync<T: Send + Sync>(_: &T) {}
+
+        let long = "x".repeat(100);
+        let source = format!(r#"<p title="{long}">{long}</p>"#).repeat(50);
+        let html = Html::parse_document(&source).into_shared();
+        assert_sync(&html);
+
+        let p = Selector::parse("p").unwrap();
+        let copies: Vec<SharedHtml> = thread::scope(|scope| {
+            let workers: Vec<_> = (0..4)
+                .map(|_| {
+                    scope.spawn(|| {
+                        for element in html.select(&p) {
+                            assert_eq!(Some(&*long), element.attr("title"));
+                            assert_eq!(100, element.text().map(str::len).sum::<usize>());
+                        }
+                        html.clone()
+                    })
+                })
+                .collect();
+            workers.into_iter().map(|w| w.join().unwrap()).collect()
+        });
+        assert!(copies.iter().all(|copy| copy.html() == html.html()));
+
+        let mut html = html.into_inner();
+        html.set_language("en".into());
+        assert_eq!("en", html.get_lang());
+    }
+}
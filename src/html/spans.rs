@@ -0,0 +1,412 @@
+//! Source positions of parsed nodes, recorded when parsing with
+//! [`Html::parse_document_with_spans`] or [`Html::parse_fragment_with_spans`].
+//!
+//! html5ever reports no positions to the tree it builds, so recording parses drive the
+//! tokenizer here. A token sink in front of the tree builder notes how much of the input is
+//! consumed as each token arrives, which gives the byte range of the token, and the tree
+//! sink gives that range to the nodes the tree builder creates for it. Elements then extend
+//! over their children and the end tag that follows them. Elements the parser implies,
+//! such as a missing `<body>`, have no start tag and no span.
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use ego_tree::NodeId;
+use html5ever::tendril::StrTendril;
+use html5ever::tokenizer::{
+    BufferQueue, TagKind, Token, TokenSink, TokenSinkResult, Tokenizer, TokenizerOpts,
+};
+use html5ever::tree_builder::{create_element, TreeBuilder, TreeSink};
+use html5ever::{driver, LocalName, QualName, TokenizerResult};
+
+use super::tree_sink::HtmlBuilder;
+use super::{Html, ParseOptions};
+use crate::element_ref::ElementRef;
+use crate::node::Node;
+
+/// Where a node came from in the parsed input.
+///
+/// The range of an element runs from the `<` of its start tag to the `>` of its end tag,
+/// or to the end of its last child when the end tag is left out. The range of a text
+/// node covers the markup it was decoded from, so character references count at their
+/// source length.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SourceSpan {
+    /// The byte offset of the start of the node.
+    pub start: usize,
+    /// The byte offset just past the end of the node.
+    pub end: usize,
+    /// The line the node starts on, from 1.
+    pub line: usize,
+    /// The column the node starts at, from 1, counted in characters.
+    pub column: usize,
+}
+
+impl SourceSpan {
+    /// Returns the markup of the node, given the input it was parsed from.
+    pub fn source<'a>(&self, input: &'a str) -> Option<&'a str> {
+        input.get(self.start..self.end)
+    }
+}
+
+/// The kind of token being processed.
+#[derive(Debug, Default)]
+enum Current {
+    #[default]
+    Other,
+    Start(LocalName),
+    Text,
+}
+
+/// The byte ranges of nodes, collected while parsing.
+#[derive(Debug, Default)]
+pub(super) struct SpanRecorder {
+    current: RefCell<Current>,
+    /// The byte range of the token being processed.
+    token: Cell<(usize, usize)>,
+    /// The element created for the current start tag.
+    created: Cell<Option<NodeId>>,
+    ranges: RefCell<HashMap<NodeId, (usize, usize)>>,
+}
+
+impl SpanRecorder {
+    fn begin(&self, token: &Token, range: (usize, usize)) {
+        *self.current.borrow_mut() = match token {
+            Token::TagToken(tag) if tag.kind == TagKind::StartTag => {
+                Current::Start(tag.name.clone())
+            }
+            Token::CharacterTokens(_) | Token::NullCharacterToken => Current::Text,
+            _ => Current::Other,
+        };
+        self.token.set(range);
+        self.created.set(None);
+    }
+
+    /// Gives an element the range of the current token if it is the start tag of the
+    /// element. Elements the tree builder recreates while handling the tag, such as
+    /// reopened formatting elements, come before the one for the tag and lose it.
+    pub(super) fn element_created(&self, id: NodeId, name: &LocalName) {
+        if !matches!(&*self.current.borrow(), Current::Start(tag) if tag == name) {
+            return;
+        }
+        let mut ranges = self.ranges.borrow_mut();
+        if let Some(earlier) = self.created.replace(Some(id)) {
+            ranges.remove(&earlier);
+        }
+        ranges.insert(id, self.token.get());
+    }
+
+    /// Extends the range of a text node over the current token, if it is text.
+    pub(super) fn text_added(&self, id: NodeId) {
+        if !matches!(&*self.current.borrow(), Current::Text) {
+            return;
+        }
+        let (start, end) = self.token.get();
+        let mut ranges = self.ranges.borrow_mut();
+        let range = ranges.entry(id).or_insert((start, end));
+        *range = (range.0.min(start), range.1.max(end));
+    }
+}
+
+/// Returns the number of bytes left in the input queue.
+fn remaining(input: &BufferQueue) -> usize {
+    let mut buffers = Vec::new();
+    while let Some(buffer) = input.pop_front() {
+        buffers.push(buffer);
+    }
+    let len = buffers.iter().map(|buffer| buffer.len()).sum();
+    for buffer in buffers {
+        input.push_back(buffer);
+    }
+    len
+}
+
+/// A token sink in front of the tree builder that tells the span recorder where each
+/// token is.
+struct Positioned {
+    builder: TreeBuilder<NodeId, HtmlBuilder>,
+    input: Rc<BufferQueue>,
+    len: usize,
+    /// Where the previous token ended.
+    last_end: Cell<usize>,
+}
+
+impl TokenSink for Positioned {
+    type Handle = NodeId;
+
+    fn process_token(&self, token: Token, line_number: u64) -> TokenSinkResult<NodeId> {
+        // Errors are reported in the middle of tokens and consume nothing.
+        if !matches!(token, Token::ParseError(_)) {
+            let end = self.len.saturating_sub(remaining(&self.input));
+            let start = self.last_end.replace(end).min(end);
+            if let Some(spans) = &self.builder.sink.spans {
+                spans.begin(&token, (start, end));
+            }
+        }
+        self.builder.process_token(token, line_number)
+    }
+
+    fn end(&self) {
+        self.builder.end();
+    }
+
+    fn adjusted_current_node_present_but_not_in_html_namespace(&self) -> bool {
+        self.builder
+            .adjusted_current_node_present_but_not_in_html_namespace()
+    }
+}
+
+/// Returns the offset of the start of every line of `input`, counting `\r\n`, `\r` and `\n`
+/// as line ends.
+fn line_starts(input: &str) -> Vec<usize> {
+    let bytes = input.as_bytes();
+    let mut starts = vec![0];
+    for (i, &byte) in bytes.iter().enumerate() {
+        if byte == b'\n' || (byte == b'\r' && bytes.get(i + 1) != Some(&b'\n')) {
+            starts.push(i + 1);
+        }
+    }
+    starts
+}
+
+/// Returns the length of the end tag for `name` at the start of `rest`, along with the
+/// whitespace and comments before it.
+fn end_tag_len(rest: &str, name: &str) -> Option<usize> {
+    let mut tail = rest.trim_start_matches(|c: char| c.is_ascii_whitespace());
+    while let Some(comment) = tail.strip_prefix("<!--") {
+        let close = comment.find("-->")?;
+        tail = comment[close + 3..].trim_start_matches(|c: char| c.is_ascii_whitespace());
+    }
+    let tag = tail.strip_prefix("</")?;
+    if !tag.get(..name.len())?.eq_ignore_ascii_case(name) {
+        return None;
+    }
+    let after = tag[name.len()..].trim_start_matches(|c: char| c.is_ascii_whitespace());
+    let after = after.strip_prefix('>')?;
+    Some(rest.len() - after.len())
+}
+
+/// Extends the ranges of elements over their children and end tags, children first.
+fn close_elements(html: &Html, input: &str, ranges: &mut HashMap<NodeId, (usize, usize)>) {
+    let nodes: Vec<_> = html.tree.root().descendants().collect();
+    // Where the content of every element ends, including elements without a span.
+    let mut content_ends: HashMap<NodeId, usize> = HashMap::new();
+    for node in nodes.into_iter().rev() {
+        let Node::Element(element) = node.value() else {
+            continue;
+        };
+        let children = node.children().filter_map(|child| {
+            content_ends
+                .get(&child.id())
+                .copied()
+                .or_else(|| ranges.get(&child.id()).map(|range| range.1))
+        });
+        let content_end = children.max();
+        let end = match ranges.get_mut(&node.id()) {
+            Some(range) => {
+                let end = content_end.map_or(range.1, |end| end.max(range.1));
+                let rest = input.get(end..).unwrap_or_default();
+                range.1 = end + end_tag_len(rest, element.name()).unwrap_or(0);
+                range.1
+            }
+            None => match content_end {
+                Some(end) => end,
+                None => continue,
+            },
+        };
+        content_ends.insert(node.id(), end);
+    }
+}
+
+/// Parses `input` as a document, or as a fragment in `<body>`, recording spans.
+fn parse(input: &str, fragment: bool) -> Html {
+    let opts = driver::ParseOpts::default();
+    let root = if fragment {
+        Node::Fragment
+    } else {
+        Node::Document
+    };
+    let mut sink = HtmlBuilder::with_options(root, ParseOptions::default());
+    sink.spans = Some(SpanRecorder::default());
+
+    let (builder, tokenizer_opts) = if fragment {
+        let body = QualName::new(None, ns!(html), local_name!("body"));
+        let context = create_element(&sink, body, Vec::new());
+        let builder = TreeBuilder::new_for_fragment(sink, context, None, opts.tree_builder);
+        let tokenizer_opts = TokenizerOpts {
+            initial_state: Some(builder.tokenizer_state_for_context_elem(false)),
+            ..opts.tokenizer
+        };
+        (builder, tokenizer_opts)
+    } else {
+        (TreeBuilder::new(sink, opts.tree_builder), opts.tokenizer)
+    };
+
+    let queue = Rc::new(BufferQueue::default());
+    queue.push_back(StrTendril::from_slice(input));
+    let positioned = Positioned {
+        builder,
+        input: Rc::clone(&queue),
+        len: input.len(),
+        last_end: Cell::new(0),
+    };
+    let tokenizer = Tokenizer::new(positioned, tokenizer_opts);
+    while let TokenizerResult::Script(_) = tokenizer.feed(&queue) {}
+    tokenizer.end();
+
+    let mut sink = tokenizer.sink.builder.sink;
+    let mut ranges = sink
+        .spans
+        .take()
+        .map(|spans| spans.ranges.into_inner())
+        .unwrap_or_default();
+    let mut html = sink.finish();
+    close_elements(&html, input, &mut ranges);
+
+    let lines = line_starts(input);
+    for (id, (start, end)) in ranges {
+        let line = lines.partition_point(|&line_start| line_start <= start);
+        let line_start = lines[line - 1];
+        let column = input
+            .get(line_start..start)
+            .map_or(0, |s| s.chars().count())
+            + 1;
+        let span = SourceSpan {
+            start,
+            end,
+            line,
+            column,
+        };
+        let Some(mut node) = html.tree.get_mut(id) else {
+            continue;
+        };
+        match node.value() {
+            Node::Element(element) => element.span = Some(Box::new(span)),
+            Node::Text(_) => {
+                html.text_spans.insert(id, span);
+            }
+            _ => {}
+        }
+    }
+    html
+}
+
+impl Html {
+    /// Parses a string of HTML as a document, recording where every element and text node
+    /// came from in it, for [`ElementRef::source_span`] and [`Html::source_span`].
+    pub fn parse_document_with_spans(document: &str) -> Self {
+        parse(document, false)
+    }
+
+    /// Parses a string of HTML as a fragment, recording where every element and text node
+    /// came from in it, for [`ElementRef::source_span`] and [`Html::source_span`].
+    pub fn parse_fragment_with_spans(fragment: &str) -> Self {
+        parse(fragment, true)
+    }
+
+    /// Returns where an element or text node came from in the parsed input, when the
+    /// document was parsed with [`Html::parse_document_with_spans`] or
+    /// [`Html::parse_fragment_with_spans`].
+    pub fn source_span(&self, node_id: NodeId) -> Option<SourceSpan> {
+        match self.tree.get(node_id)?.value() {
+            Node::Element(element) => element.span.as_deref().copied(),
+            Node::Text(_) => self.text_spans.get(&node_id).copied(),
+            _ => None,
+        }
+    }
+}
+
+impl ElementRef<'_> {
+    /// Returns where the element came from in the parsed input, when the document was
+    /// parsed with [`Html::parse_document_with_spans`] or [`Html::parse_fragment_with_spans`].
+    ///
+    /// ```
+    /// use scraper::{Html, Selector};
+    ///
+    /// let source = "<ul>\n  <li>caf&eacute;</li>\n</ul>";
+    /// let html = Html::parse_fragment_with_spans(source);
+    /// let li = html.select_first(&Selector::parse("li").unwrap()).unwrap();
+    /// let span = li.source_span().unwrap();
+    /// assert_eq!((2, 3), (span.line, span.column));
+    /// assert_eq!(Some("<li>caf&eacute;</li>"), span.source(source));
+    /// ```
+    pub fn source_span(&self) -> Option<SourceSpan> {
+        self.value().span.as_deref().copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SourceSpan;
+    use crate::html::Html;
+    use crate::selector::Selector;
+
+    fn parse(source: &str) -> Html {
+        Html::parse_document_with_spans(source)
+    }
+
+    fn sources<'a>(html: &Html, source: &'a str, selector: &str) -> Vec<Option<&'a str>> {
+        let selector = Selector::parse(selector).unwrap();
+        html.select(&selector)
+            .map(|e| e.source_span().and_then(|span| span.source(source)))
+            .collect()
+    }
+
+    #[test]
+    fn spans_cover_elements_and_text() {
+        let source = "<!DOCTYPE html>\r\n<title>T</title><p class=a>é &amp; <span>bold</p>\n<p>x<br>y<script>if (a < b) {}</script>";
+        let html = parse(source);
+        assert_eq!(
+            vec![
+                Some("<p class=a>é &amp; <span>bold</p>"),
+                Some("<p>x<br>y<script>if (a < b) {}</script>")
+            ],
+            sources(&html, source, "p")
+        );
+        assert_eq!(vec![Some("<span>bold")], sources(&html, source, "span"));
+        assert_eq!(vec![Some("<br>")], sources(&html, source, "br"));
+        assert_eq!(vec![None, None], sources(&html, source, "head, body"));
+
+        let p = html.select_first(&Selector::parse("p").unwrap()).unwrap();
+        let text = p.first_child().unwrap();
+        assert_eq!(
+            Some(SourceSpan {
+                start: 44,
+                end: 53,
+                line: 2,
+                column: 28
+            }),
+            html.source_span(text.id())
+        );
+        assert_eq!(
+            Some("é &amp; "),
+            html.source_span(text.id()).unwrap().source(source)
+        );
+        assert_eq!(None, Html::parse_document(source).source_span(p.id()));
+
+        let source = "<table><tr><td>x</td></TR > <!-- rows --></table><i>a<p>b</i>";
+        let html = parse(source);
+        assert_eq!(
+            vec![Some("<table><tr><td>x</td></TR > <!-- rows --></table>")],
+            sources(&html, source, "table")
+        );
+        assert_eq!(vec![None], sources(&html, source, "tbody"));
+        assert_eq!(vec![Some("<i>a"), None], sources(&html, source, "i"));
+    }
+
+    #[test]
+    fn spans_do_not_affect_equality() {
+        let source = "<p class=a id=b title=c>x</p>";
+        let with = parse(source);
+        let without = Html::parse_document(source);
+        let p = Selector::parse("p").unwrap();
+        let (a, b) = (
+            with.select_first(&p).unwrap(),
+            without.select_first(&p).unwrap(),
+        );
+        assert!(a.source_span().is_some());
+        assert_eq!(a.value(), b.value());
+    }
+}
@@ -0,0 +1,95 @@
+//! Incremental parsing of documents that arrive in chunks.
+
+use std::fmt;
+use std::io;
+
+use html5ever::driver::{self, Parser};
+use tendril::stream::Utf8LossyDecoder;
+use tendril::{ByteTendril, TendrilSink};
+
+use super::tree_sink::HtmlBuilder;
+use super::Html;
+
+/// A document parser fed with chunks of UTF-8 bytes, returned by
+/// [`Html::parse_document_streaming`].
+///
+/// Chunks may split multi-byte characters; invalid sequences become U+FFFD. It also
+/// implements [`io::Write`], so a reader can be copied into it with [`io::copy`].
+pub struct StreamingParser {
+    inner: Utf8LossyDecoder<Parser<HtmlBuilder>>,
+}
+
+impl StreamingParser {
+    /// Parses the next chunk of the document.
+    pub fn feed(&mut self, chunk: &[u8]) {
+        self.inner.process(ByteTendril::from_slice(chunk));
+    }
+
+    /// Ends the input and returns the document.
+    pub fn finish(self) -> Html {
+        self.inner.finish()
+    }
+}
+
+impl fmt::Debug for StreamingParser {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StreamingParser").finish_non_exhaustive()
+    }
+}
+
+impl io::Write for StreamingParser {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.feed(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Html {
+    /// Starts parsing a document whose bytes arrive in chunks, such as a network response
+    /// body, without buffering it whole first.
+    ///
+    /// ```
+    /// use scraper::{Html, Selector};
+    ///
+    /// let mut parser = Html::parse_document_streaming();
+    /// for chunk in ["<title>Caf", "\u{e9}</title><p>sp", "lit</p>"] {
+    ///     parser.feed(chunk.as_bytes());
+    /// }
+    /// let html = parser.finish();
+    /// let p = Selector::parse("p").unwrap();
+    /// assert_eq!("split", html.select_first(&p).unwrap().inner_html());
+    ///
+    /// let mut parser = Html::parse_document_streaming();
+    /// std::io::copy(&mut &b"<h1>From a reader</h1>"[..], &mut parser).unwrap();
+    /// assert!(parser.finish().html().contains("<h1>From a reader</h1>"));
+    /// ```
+    pub fn parse_document_streaming() -> StreamingParser {
+        StreamingParser {
+            inner: driver::parse_document(HtmlBuilder::new_document(), Default::default())
+                .from_utf8(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::html::Html;
+
+    #[test]
+    fn characters_split_across_chunks() {
+        let bytes = "<p>naïve 日本</p>".as_bytes();
+        let mut parser = Html::parse_document_streaming();
+        for chunk in bytes.chunks(1) {
+            parser.feed(chunk);
+        }
+        let streamed = parser.finish();
+        assert_eq!(
+            Html::parse_document("<p>naïve 日本</p>").html(),
+            streamed.html()
+        );
+    }
+}
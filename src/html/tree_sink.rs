@@ -1,4 +1,5 @@
-use super::Html;
+use super::spans::SpanRecorder;
+use super::{Html, ParseOptions, RecoveryEvent, TextOverflow};
 use crate::node::{Doctype, Element, Node, ProcessingInstruction, Text};
 use ego_tree::{NodeId, Tree};
 use html5ever::interface::ElemName;
@@ -9,6 +10,7 @@ use html5ever::QualName;
 use html5ever::{LocalName, Namespace};
 use std::borrow::Cow;
 use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
 
 /// Owned `ElemName` implementation.
 ///
@@ -62,20 +64,125 @@ impl OwnedElemName {
 pub(crate) struct HtmlBuilder {
     quirks_mode: Cell<QuirksMode>,
     tree: RefCell<Tree<Node>>,
+    options: ParseOptions,
+    spilled_text: RefCell<HashMap<NodeId, String>>,
+    /// The elements left out of the tree by `skip_elements`, and the nodes parsed into them.
+    skipped: RefCell<HashSet<NodeId>>,
+    recoveries: RefCell<Vec<RecoveryEvent>>,
+    line: Cell<u64>,
+    /// Collects the byte ranges of nodes when parsing with spans.
+    pub(super) spans: Option<SpanRecorder>,
+}
+
+/// Returns the longest prefix of `text` no longer than `max` bytes that ends on a character
+/// boundary, or the first character if even that is longer.
+fn split_at_most(text: &str, max: usize) -> (&str, &str) {
+    let mut end = max.min(text.len());
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    if end == 0 {
+        end = text.chars().next().map_or(0, char::len_utf8);
+    }
+    text.split_at(end)
 }
 
 impl HtmlBuilder {
     pub(crate) fn new_document() -> Self {
+        Self::with_options(Node::Document, ParseOptions::default())
+    }
+
+    pub(crate) fn with_options(root: Node, options: ParseOptions) -> Self {
         HtmlBuilder {
             quirks_mode: Cell::new(QuirksMode::NoQuirks),
-            tree: RefCell::new(Tree::new(Node::Document)),
+            tree: RefCell::new(Tree::new(root)),
+            options,
+            spilled_text: RefCell::new(HashMap::new()),
+            skipped: RefCell::new(HashSet::new()),
+            recoveries: RefCell::new(Vec::new()),
+            line: Cell::new(1),
+            spans: None,
         }
     }
 
-    pub(crate) fn new_fragment() -> Self {
-        HtmlBuilder {
-            quirks_mode: Cell::new(QuirksMode::NoQuirks),
-            tree: RefCell::new(Tree::new(Node::Fragment)),
+    /// Returns true if `new_node`, about to be added next to or under `target`, is left
+    /// out of the tree, marking it as skipped if it is an element inside a skipped one.
+    fn skips(&self, target: NodeId, new_node: &NodeOrText<NodeId>) -> bool {
+        let mut skipped = self.skipped.borrow_mut();
+        match new_node {
+            NodeOrText::AppendNode(id) if skipped.contains(id) => true,
+            NodeOrText::AppendNode(id) if skipped.contains(&target) => {
+                skipped.insert(*id);
+                true
+            }
+            _ => skipped.contains(&target),
+        }
+    }
+
+    /// Adds parsed text, appending to the adjacent text node `existing` if there is one and
+    /// creating nodes with `create` otherwise, within the `max_text_len` option.
+    fn add_text(
+        &self,
+        tree: &mut Tree<Node>,
+        existing: Option<NodeId>,
+        text: StrTendril,
+        mut create: impl FnMut(&mut Tree<Node>, Node) -> Option<NodeId>,
+    ) {
+        let mut create = |tree: &mut Tree<Node>, node| {
+            let id = create(tree, node);
+            self.text_added(id);
+            id
+        };
+        let Some(max) = self.options.max_text_len else {
+            let text = text.into_send().into();
+            if let Some(mut node) = existing.and_then(|id| tree.get_mut(id)) {
+                if let Node::Text(ref mut t) = *node.value() {
+                    t.text.push_tendril(&text);
+                    self.text_added(existing);
+                    return;
+                }
+            }
+            create(tree, Node::Text(Text { text }));
+            return;
+        };
+
+        let mut rest: &str = &text;
+        let mut last = existing;
+        if let Some(id) = last {
+            if let Some(spilled) = self.spilled_text.borrow_mut().get_mut(&id) {
+                spilled.push_str(rest);
+                self.text_added(last);
+                return;
+            }
+            if let Some(mut node) = tree.get_mut(id) {
+                if let Node::Text(ref mut t) = *node.value() {
+                    let room = max.saturating_sub(t.text.len());
+                    let (head, tail) = match room {
+                        0 => ("", rest),
+                        _ => split_at_most(rest, room),
+                    };
+                    t.text.push_slice(head);
+                    rest = tail;
+                    self.text_added(last);
+                }
+            }
+        }
+        while !rest.is_empty() {
+            if let (Some(id), TextOverflow::Spill) = (last, self.options.text_overflow) {
+                self.spilled_text.borrow_mut().insert(id, rest.to_owned());
+                return;
+            }
+            let (head, tail) = split_at_most(rest, max);
+            last = create(tree, Node::Text(Text { text: head.into() }));
+            rest = tail;
+        }
+    }
+}
+
+impl HtmlBuilder {
+    fn text_added(&self, id: Option<NodeId>) {
+        if let (Some(spans), Some(id)) = (&self.spans, id) {
+            spans.text_added(id);
         }
     }
 }
@@ -90,13 +197,22 @@ impl TreeSink for HtmlBuilder {
 
     fn finish(self) -> Html {
         Html {
-            quirks_mode: self.quirks_mode.into_inner(),
-            tree: self.tree.into_inner(),
-            lang: String::new(),
+            spilled_text: self.spilled_text.into_inner(),
+            recoveries: self.recoveries.into_inner(),
+            ..Html::from_parts(self.quirks_mode.into_inner(), self.tree.into_inner())
         }
     }
 
-    fn parse_error(&self, _: Cow<'static, str>) {}
+    fn parse_error(&self, message: Cow<'static, str>) {
+        if self.options.record_recoveries {
+            let event = RecoveryEvent::new(self.line.get(), &message);
+            self.recoveries.borrow_mut().push(event);
+        }
+    }
+
+    fn set_current_line(&self, line_number: u64) {
+        self.line.set(line_number);
+    }
 
     fn set_quirks_mode(&self, mode: QuirksMode) {
         self.quirks_mode.set(mode);
@@ -130,11 +246,20 @@ impl TreeSink for HtmlBuilder {
         attrs: Vec<Attribute>,
         _flags: ElementFlags,
     ) -> Self::Handle {
+        let skip = name.ns == ns!(html)
+            && (self.options.skip_elements.iter())
+                .any(|skip| skip.eq_ignore_ascii_case(&name.local));
         let mut tree = self.tree.borrow_mut();
         let mut node = tree.orphan(Node::Element(Element::new(name.clone(), attrs)));
         if name.expanded() == expanded_name!(html "template") {
             node.append(Node::Fragment);
         }
+        if skip {
+            self.skipped.borrow_mut().insert(node.id());
+        }
+        if let Some(spans) = &self.spans {
+            spans.element_created(node.id(), &name.local);
+        }
         node.id()
     }
 
@@ -173,6 +298,9 @@ impl TreeSink for HtmlBuilder {
     }
 
     fn append(&self, parent: &Self::Handle, child: NodeOrText<Self::Handle>) {
+        if self.skips(*parent, &child) {
+            return;
+        }
         let mut tree = self.tree.borrow_mut();
         let Some(mut parent_node) = tree.get_mut(*parent) else {
             return;
@@ -184,30 +312,32 @@ impl TreeSink for HtmlBuilder {
             }
 
             NodeOrText::AppendText(text) => {
-                let can_concat = parent_node
-                    .last_child()
-                    .map_or(false, |mut n| n.value().is_text());
-
-                let text = text.into_send().into();
-
-                if can_concat {
-                    if let Some(mut last_child) = parent_node.last_child() {
-                        if let Node::Text(ref mut t) = *last_child.value() {
-                            t.text.push_tendril(&text);
-                            return;
-                        }
+                if self.options.drop_script_and_style_content {
+                    let raw = match parent_node.value() {
+                        Node::Element(element) => matches!(element.name(), "script" | "style"),
+                        _ => false,
+                    };
+                    if raw {
+                        return;
                     }
                 }
-                parent_node.append(Node::Text(Text { text }));
+                let existing = parent_node
+                    .last_child()
+                    .and_then(|mut n| n.value().is_text().then(|| n.id()));
+                let parent = *parent;
+                self.add_text(&mut tree, existing, text, |tree, node| {
+                    Some(tree.get_mut(parent)?.append(node).id())
+                });
             }
         }
     }
 
-    fn append_before_sibling(
-        &self,
-        sibling: &Self::Handle,
-        new_node: NodeOrText<Self::Handle>,
-    ) {
+    fn append_before_sibling(&self, sibling: &Self::Handle, new_node: NodeOrText<Self::Handle>) {
+        if let NodeOrText::AppendNode(id) = new_node {
+            if self.skipped.borrow().contains(&id) {
+                return;
+            }
+        }
         let mut tree = self.tree.borrow_mut();
 
         if let NodeOrText::AppendNode(id) = new_node {
@@ -228,20 +358,13 @@ impl TreeSink for HtmlBuilder {
                 sibling_node.insert_id_before(id);
             }
             NodeOrText::AppendText(text) => {
-                let text = text.into_send().into();
-                let can_concat = sibling_node
+                let existing = sibling_node
                     .prev_sibling()
-                    .map_or(false, |mut n| n.value().is_text());
-
-                if can_concat {
-                    if let Some(mut prev_sibling) = sibling_node.prev_sibling() {
-                        if let Node::Text(ref mut t) = *prev_sibling.value() {
-                            t.text.push_tendril(&text);
-                            return;
-                        }
-                    }
-                }
-                sibling_node.insert_before(Node::Text(Text { text }));
+                    .and_then(|mut n| n.value().is_text().then(|| n.id()));
+                let sibling = *sibling;
+                self.add_text(&mut tree, existing, text, |tree, node| {
+                    Some(tree.get_mut(sibling)?.insert_before(node).id())
+                });
             }
         }
     }
@@ -0,0 +1,208 @@
+//! Parsing of documents stored in WARC records, as written by web archives and crawlers.
+
+use std::io::Read;
+
+use flate2::read::{DeflateDecoder, MultiGzDecoder, ZlibDecoder};
+
+use super::encoding::{content_charset, parse_bytes};
+use super::{Html, ParseOptions};
+use crate::error::WarcError;
+
+/// Splits a header block from what follows it at the first empty line, accepting bare
+/// `\n` line ends.
+fn split_head(bytes: &[u8]) -> Option<(&[u8], &[u8])> {
+    let mut start = 0;
+    while let Some(end) = bytes[start..].iter().position(|b| *b == b'\n') {
+        let line = &bytes[start..start + end];
+        if line.is_empty() || line == b"\r" {
+            return Some((&bytes[..start], &bytes[start + end + 1..]));
+        }
+        start += end + 1;
+    }
+    None
+}
+
+/// Returns the value of a header, matching its name ignoring case.
+fn header<'a>(head: &'a str, name: &str) -> Option<&'a str> {
+    head.lines().skip(1).find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        key.trim().eq_ignore_ascii_case(name).then(|| value.trim())
+    })
+}
+
+/// Decodes a `Transfer-Encoding: chunked` body, ignoring chunk extensions and trailers.
+fn dechunk(mut body: &[u8]) -> Result<Vec<u8>, WarcError> {
+    let invalid = || WarcError::Decode("invalid chunked transfer encoding".to_owned());
+    let mut out = Vec::with_capacity(body.len());
+    loop {
+        let end = body.iter().position(|b| *b == b'\n').ok_or_else(invalid)?;
+        let line = std::str::from_utf8(&body[..end]).map_err(|_| invalid())?;
+        let size = line.split(';').next().unwrap_or_default().trim();
+        let size = usize::from_str_radix(size, 16).map_err(|_| invalid())?;
+        body = &body[end + 1..];
+        if size == 0 {
+            return Ok(out);
+        }
+        let chunk = body.get(..size).ok_or_else(invalid)?;
+        out.extend_from_slice(chunk);
+        body = &body[size..];
+        body = body
+            .strip_prefix(b"\r\n")
+            .or_else(|| body.strip_prefix(b"\n"))
+            .unwrap_or(body);
+    }
+}
+
+fn read_all(mut reader: impl Read, coding: &str) -> Result<Vec<u8>, WarcError> {
+    let mut out = Vec::new();
+    reader
+        .read_to_end(&mut out)
+        .map_err(|err| WarcError::Decode(format!("invalid {} content: {}", coding, err)))?;
+    Ok(out)
+}
+
+/// Reverses one content coding of an HTTP body.
+fn decode_content(body: Vec<u8>, coding: &str) -> Result<Vec<u8>, WarcError> {
+    match coding.to_ascii_lowercase().as_str() {
+        "" | "identity" => Ok(body),
+        "gzip" | "x-gzip" => read_all(MultiGzDecoder::new(&body[..]), coding),
+        // The coding is a zlib stream, but some servers send raw deflate data.
+        "deflate" => read_all(ZlibDecoder::new(&body[..]), coding)
+            .or_else(|_| read_all(DeflateDecoder::new(&body[..]), coding)),
+        "br" => read_all(
+            brotli_decompressor::Decompressor::new(&body[..], 4096),
+            coding,
+        ),
+        other => Err(WarcError::UnsupportedEncoding(other.to_owned())),
+    }
+}
+
+impl Html {
+    /// Parses the document archived in a WARC `response` record, such as one record of a
+    /// Common Crawl archive, gzip-compressed on its own or not.
+    ///
+    /// The HTTP payload is taken out of the record and its transfer and content codings
+    /// (`chunked`, `gzip`, `deflate` and `br`) are reversed. The encoding comes from a byte
+    /// order mark, else the `charset` of the `Content-Type` header, else the document
+    /// itself as in [`Html::parse_document_bytes`]. `resource` records, which store the
+    /// document without an HTTP response, are accepted too.
+    ///
+    /// ```
+    /// use scraper::{Html, Selector};
+    ///
+    /// let http = b"HTTP/1.1 200 OK\r\n\
+    ///     Content-Type: text/html; charset=ISO-8859-1\r\n\
+    ///     Transfer-Encoding: chunked\r\n\
+    ///     \r\n\
+    ///     7\r\n<p>Caf\xE9\r\n4\r\n</p>\r\n0\r\n\r\n";
+    /// let mut record = format!(
+    ///     "WARC/1.0\r\n\
+    ///      WARC-Type: response\r\n\
+    ///      WARC-Target-URI: https://example.com/\r\n\
+    ///      Content-Type: application/http; msgtype=response\r\n\
+    ///      Content-Length: {}\r\n\r\n",
+    ///     http.len(),
+    /// )
+    /// .into_bytes();
+    /// record.extend_from_slice(http);
+    /// record.extend_from_slice(b"\r\n\r\n");
+    ///
+    /// let html = Html::from_warc_record(&record).unwrap();
+    /// let p = html.select_first(&Selector::parse("p").unwrap()).unwrap();
+    /// assert_eq!("Café", p.text().collect::<String>());
+    /// ```
+    pub fn from_warc_record(bytes: &[u8]) -> Result<Self, WarcError> {
+        let inflated;
+        let bytes = if bytes.starts_with(&[0x1f, 0x8b]) {
+            inflated = decode_content(bytes.to_vec(), "gzip")?;
+            &inflated[..]
+        } else {
+            bytes
+        };
+
+        let (head, rest) = split_head(bytes).ok_or(WarcError::NotWarc)?;
+        let head = String::from_utf8_lossy(head);
+        if !head.starts_with("WARC/") {
+            return Err(WarcError::NotWarc);
+        }
+        let block = match header(&head, "Content-Length").map(str::parse::<usize>) {
+            Some(Ok(length)) => rest.get(..length).ok_or(WarcError::Truncated)?,
+            _ => rest,
+        };
+
+        let kind = header(&head, "WARC-Type").unwrap_or_default();
+        let (content_type, body) = if kind.eq_ignore_ascii_case("response") {
+            let (http, body) = split_head(block).ok_or(WarcError::NotHttp)?;
+            let http = String::from_utf8_lossy(http);
+            if !http.starts_with("HTTP/") {
+                return Err(WarcError::NotHttp);
+            }
+            let mut body = body.to_vec();
+            if let Some(codings) = header(&http, "Transfer-Encoding") {
+                if codings.to_ascii_lowercase().contains("chunked") {
+                    body = dechunk(&body)?;
+                }
+            }
+            if let Some(codings) = header(&http, "Content-Encoding") {
+                for coding in codings.rsplit(',') {
+                    body = decode_content(body, coding.trim())?;
+                }
+            }
+            (header(&http, "Content-Type").map(str::to_owned), body)
+        } else if kind.eq_ignore_ascii_case("resource") {
+            (
+                header(&head, "Content-Type").map(str::to_owned),
+                block.to_vec(),
+            )
+        } else {
+            return Err(WarcError::UnsupportedRecord(kind.to_owned()));
+        };
+
+        let declared =
+            content_type.and_then(|t| content_charset(t.to_ascii_lowercase().as_bytes()));
+        Ok(parse_bytes(&body, declared, &ParseOptions::default()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+
+    use crate::error::WarcError;
+    use crate::html::Html;
+    use crate::selector::Selector;
+
+    fn gzip(bytes: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(bytes).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    fn gzipped_record_and_body() {
+        let body = gzip("<meta charset=utf-8><title>Über</title>".as_bytes());
+        let mut http = b"HTTP/1.1 200 OK\nContent-Encoding: gzip\n\n".to_vec();
+        http.extend_from_slice(&body);
+        let mut record = format!(
+            "WARC/1.1\nWARC-Type: response\nContent-Length: {}\n\n",
+            http.len()
+        )
+        .into_bytes();
+        record.extend_from_slice(&http);
+
+        let html = Html::from_warc_record(&gzip(&record)).unwrap();
+        let title = html
+            .select_first(&Selector::parse("title").unwrap())
+            .unwrap();
+        assert_eq!("Über", title.inner_html());
+
+        let request = b"WARC/1.0\r\nWARC-Type: request\r\n\r\nGET / HTTP/1.1\r\n\r\n";
+        assert_eq!(
+            Err(WarcError::UnsupportedRecord("request".to_owned())),
+            Html::from_warc_record(request).map(|_| ())
+        );
+    }
+}
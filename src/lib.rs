@@ -129,16 +129,56 @@ pub use node::Node;
 pub use selector::Selector;
 pub use selectors::Element;
 
+pub mod annotations;
+#[cfg(any(feature = "proptest", feature = "quickcheck"))]
+pub mod arbitrary;
+pub mod barriers;
+pub mod builder;
+pub mod chunk;
+pub mod discovery;
 pub mod element_ref;
 pub mod error;
+#[cfg(feature = "export")]
+pub mod export;
+pub mod extract;
+pub mod footnotes;
+pub mod form;
 pub mod html;
+pub mod lint;
+pub mod markdown;
+pub mod media;
+pub mod metadata;
+pub mod mhtml;
+pub mod microdata;
 pub mod node;
+pub mod plaintext;
+pub mod presets;
+pub mod rewriter;
+pub mod router;
+pub mod rules;
+pub mod sanitize;
+pub mod search;
+pub mod security;
 pub mod selector;
+pub mod sniff;
+#[cfg(feature = "serde_json")]
+pub mod structured_data;
+pub mod table;
+#[cfg(feature = "testing")]
+pub mod testing;
+pub mod text;
+pub mod toc;
+pub mod url_utils;
 
 #[macro_use]
 pub extern crate lazy_static;
 #[macro_use]
 pub extern crate html5ever;
+pub extern crate encoding_rs;
+
+// Lets the code `#[derive(FromHtml)]` generates name this crate in its own tests.
+#[cfg(all(test, feature = "derive"))]
+extern crate self as scraper;
 
 #[cfg(test)]
 mod test;
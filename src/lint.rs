@@ -0,0 +1,186 @@
+//! Checks for common markup problems.
+
+use std::collections::HashSet;
+
+use crate::element_ref::ElementRef;
+use crate::html::Html;
+
+/// Elements that are obsolete in HTML and rendered inconsistently, if at all.
+const DEPRECATED: &[&str] = &[
+    "acronym",
+    "applet",
+    "basefont",
+    "big",
+    "blink",
+    "center",
+    "dir",
+    "font",
+    "frame",
+    "frameset",
+    "isindex",
+    "listing",
+    "marquee",
+    "nobr",
+    "noframes",
+    "plaintext",
+    "spacer",
+    "strike",
+    "tt",
+    "xmp",
+];
+
+/// What a [`LintFinding`] is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum LintRule {
+    /// An `id` already used by an earlier element.
+    DuplicateId,
+    /// An `img` without an `alt` attribute. An empty `alt` marks a decorative image and is
+    /// not reported.
+    MissingAlt,
+    /// A heading more than one level below the heading before it, such as an `h4` after an
+    /// `h2`.
+    HeadingSkip,
+    /// An interactive element inside a link or button, which browsers and assistive
+    /// technology handle unpredictably.
+    NestedInteractive,
+    /// An obsolete element such as `center` or `font`.
+    DeprecatedTag,
+}
+
+/// A problem found by [`Html::lint`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LintFinding<'a> {
+    /// The check that failed.
+    pub rule: LintRule,
+    /// The element the problem is on.
+    pub element: ElementRef<'a>,
+    /// A description of the problem, such as `id "main" is already used`.
+    pub message: String,
+}
+
+/// Returns true if the element is interactive content.
+fn is_interactive(element: ElementRef) -> bool {
+    let value = element.value();
+    match value.name() {
+        "a" => value.attr("href").is_some(),
+        "audio" | "video" => value.attr("controls").is_some(),
+        "img" => value.attr("usemap").is_some(),
+        "input" => !value
+            .attr("type")
+            .is_some_and(|t| t.eq_ignore_ascii_case("hidden")),
+        "button" | "details" | "embed" | "iframe" | "label" | "select" | "textarea" => true,
+        _ => false,
+    }
+}
+
+impl Html {
+    /// Checks the document for duplicate IDs, images without alternative text, skipped
+    /// heading levels, nested interactive elements and deprecated tags, and returns the
+    /// findings in document order.
+    ///
+    /// ```
+    /// use scraper::lint::LintRule;
+    /// use scraper::Html;
+    ///
+    /// let html = Html::parse_document(
+    ///     r#"<h1 id="top">Title</h1><h3>Section</h3><img src="a.png"><p id="top">Text</p>"#,
+    /// );
+    /// let rules: Vec<_> = html.lint().iter().map(|finding| finding.rule).collect();
+    /// assert_eq!(
+    ///     vec![LintRule::HeadingSkip, LintRule::MissingAlt, LintRule::DuplicateId],
+    ///     rules
+    /// );
+    /// ```
+    pub fn lint(&self) -> Vec<LintFinding<'_>> {
+        let mut findings = Vec::new();
+        let mut ids = HashSet::new();
+        let mut heading = None;
+
+        for element in self
+            .root_element()
+            .descendants()
+            .filter_map(ElementRef::wrap)
+        {
+            let value = element.value();
+            let name = value.name();
+            let mut report = |rule, message: String| {
+                findings.push(LintFinding {
+                    rule,
+                    element,
+                    message,
+                })
+            };
+
+            if let Some(id) = value.attr("id") {
+                if !ids.insert(id) {
+                    report(
+                        LintRule::DuplicateId,
+                        format!("id {:?} is already used", id),
+                    );
+                }
+            }
+            if name == "img" && value.attr("alt").is_none() {
+                report(LintRule::MissingAlt, "img has no alt attribute".into());
+            }
+            if let Some(level) = name
+                .strip_prefix('h')
+                .and_then(|level| level.parse::<u8>().ok())
+                .filter(|level| (1..=6).contains(level))
+            {
+                if let Some(previous) = heading.filter(|&previous| level > previous + 1) {
+                    report(
+                        LintRule::HeadingSkip,
+                        format!("h{} follows h{}", level, previous),
+                    );
+                }
+                heading = Some(level);
+            }
+            if is_interactive(element) {
+                let container = element
+                    .ancestors()
+                    .filter_map(ElementRef::wrap)
+                    .find(|a| matches!(a.value().name(), "a" | "button") && is_interactive(*a));
+                if let Some(container) = container {
+                    report(
+                        LintRule::NestedInteractive,
+                        format!("{} is inside {}", name, container.value().name()),
+                    );
+                }
+            }
+            if DEPRECATED.contains(&name) {
+                report(LintRule::DeprecatedTag, format!("{} is deprecated", name));
+            }
+        }
+        findings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LintRule;
+    use crate::html::Html;
+
+    #[test]
+    fn reports_nested_interactive_and_deprecated_elements() {
+        let html = Html::parse_document(
+            r#"<h2>A</h2><h3>B</h3><a href="/x"><button>Go</button></a>
+               <button><input type="hidden"><span>Ok</span></button>
+               <img alt=""><center><font>Old</font></center>"#,
+        );
+        let findings: Vec<_> = html
+            .lint()
+            .into_iter()
+            .map(|finding| (finding.rule, finding.message))
+            .collect();
+        assert_eq!(
+            vec![
+                (LintRule::NestedInteractive, "button is inside a".to_owned()),
+                (LintRule::DeprecatedTag, "center is deprecated".to_owned()),
+                (LintRule::DeprecatedTag, "font is deprecated".to_owned()),
+            ],
+            findings
+        );
+    }
+}
@@ -0,0 +1,1045 @@
+//! Conversion of documents to Markdown.
+
+use std::cell::RefCell;
+
+use ego_tree::NodeRef;
+
+use crate::element_ref::{bidi_mark, ElementRef};
+use crate::footnotes::{FootnoteIndex, FootnoteStyle};
+use crate::html::Html;
+use crate::metadata::PageMetadata;
+use crate::node::Node;
+use crate::selector::Selector;
+use crate::text::normalized::HIDDEN_TEXT;
+
+lazy_static! {
+    static ref BODY: Selector = Selector::parse("body").unwrap();
+}
+
+/// Elements that start a new block of output.
+pub(crate) const BLOCKS: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "body",
+    "caption",
+    "dd",
+    "details",
+    "dialog",
+    "div",
+    "dl",
+    "dt",
+    "fieldset",
+    "figcaption",
+    "figure",
+    "footer",
+    "form",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hgroup",
+    "hr",
+    "html",
+    "li",
+    "main",
+    "nav",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "summary",
+    "table",
+    "tbody",
+    "td",
+    "tfoot",
+    "th",
+    "thead",
+    "tr",
+    "ul",
+];
+
+/// How [`MarkdownOptions`] renders links.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum LinkStyle {
+    /// `[text](url "title")`, the title only when the link has one.
+    #[default]
+    Inline,
+    /// `[text][n]`, with the `[n]: url` definitions listed at the end.
+    Reference,
+    /// The link text alone.
+    TextOnly,
+}
+
+/// How [`MarkdownOptions`] renders images.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ImageStyle {
+    /// `![alt](src "title")`, the title only when the image has one.
+    #[default]
+    Inline,
+    /// The alt text alone.
+    AltText,
+    /// Nothing.
+    Drop,
+}
+
+/// How [`MarkdownOptions`] renders tables.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum TableStyle {
+    /// A GFM pipe table, using the first row as the header.
+    #[default]
+    Pipe,
+    /// The table's HTML, which Markdown renderers pass through, for tables that pipe
+    /// tables cannot express.
+    Html,
+    /// Nothing.
+    Drop,
+}
+
+/// How [`MarkdownOptions`] renders `<pre>` blocks.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum CodeBlockStyle {
+    /// Between backtick fences, tagged with the `language-*` class.
+    #[default]
+    Fenced,
+    /// Indented by four spaces, without the language.
+    Indented,
+}
+
+/// Options for [`Html::to_markdown_with`] and [`ElementRef::to_markdown_with`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MarkdownOptions {
+    /// Starts the output with YAML front matter built from [`Html::metadata`], for static
+    /// site generators.
+    pub front_matter: bool,
+    /// Starts paragraphs and headings with a left-to-right or right-to-left mark where
+    /// their [`ElementRef::direction`] differs from the direction of their first strong
+    /// character, so right-to-left text is not reordered by renderers that guess
+    /// direction per paragraph.
+    pub bidi_marks: bool,
+    /// How footnote references and note lists are rendered.
+    pub footnotes: FootnoteStyle,
+    /// How links are rendered.
+    pub links: LinkStyle,
+    /// How images are rendered.
+    pub images: ImageStyle,
+    /// How tables are rendered.
+    pub tables: TableStyle,
+    /// How preformatted blocks are rendered.
+    pub code_blocks: CodeBlockStyle,
+}
+
+/// State shared by the conversion of one document.
+struct Context<'o> {
+    options: &'o MarkdownOptions,
+    footnotes: FootnoteIndex,
+    /// The URLs of [`LinkStyle::Reference`] links, numbered from 1.
+    references: RefCell<Vec<String>>,
+}
+
+impl Context<'_> {
+    /// Returns the reference number of a URL, adding it if it is new.
+    fn reference(&self, url: &str) -> usize {
+        let mut references = self.references.borrow_mut();
+        let index = match references.iter().position(|known| known == url) {
+            Some(index) => index,
+            None => {
+                references.push(url.to_owned());
+                references.len() - 1
+            }
+        };
+        index + 1
+    }
+}
+
+impl PageMetadata {
+    /// Renders the metadata as a YAML front matter block, including the `---` delimiters
+    /// and a trailing blank line. Missing fields are left out.
+    ///
+    /// ```
+    /// use scraper::metadata::PageMetadata;
+    ///
+    /// let metadata = PageMetadata {
+    ///     title: Some("Say \"hi\"".into()),
+    ///     keywords: vec!["a".into()],
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!("---\ntitle: \"Say \\\"hi\\\"\"\ntags:\n  - \"a\"\n---\n\n", metadata.to_front_matter());
+    /// ```
+    pub fn to_front_matter(&self) -> String {
+        let mut out = String::from("---\n");
+        let fields = [
+            ("title", &self.title),
+            ("description", &self.description),
+            ("date", &self.published),
+            ("author", &self.author),
+            ("canonical", &self.canonical),
+        ];
+        for (key, value) in fields {
+            if let Some(value) = value {
+                out.push_str(&format!("{}: {}\n", key, yaml_string(value)));
+            }
+        }
+        if !self.keywords.is_empty() {
+            out.push_str("tags:\n");
+            for tag in &self.keywords {
+                out.push_str(&format!("  - {}\n", yaml_string(tag)));
+            }
+        }
+        out.push_str("---\n\n");
+        out
+    }
+}
+
+/// Quotes a YAML scalar as a double-quoted string.
+fn yaml_string(value: &str) -> String {
+    let mut out = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => out.push_str(&format!("\\x{:02x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Escapes characters that Markdown would otherwise interpret in running text, including
+/// the `<` and `&` that would start raw HTML or an entity.
+fn escape(text: &str, out: &mut String) {
+    for c in text.chars() {
+        match c {
+            '\\' | '*' | '_' | '`' | '[' | ']' | '<' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '&' => out.push_str("&amp;"),
+            c if c.is_whitespace() => out.push(' '),
+            c => out.push(c),
+        }
+    }
+}
+
+/// Writes a link or image destination, percent-encoding the spaces, parentheses and
+/// angle brackets that would end it early, followed by the `title` in quotes if any.
+fn destination(url: &str, title: Option<&str>) -> String {
+    let mut out = String::with_capacity(url.len());
+    for c in url.chars() {
+        match c {
+            c if c <= ' ' || matches!(c, '(' | ')' | '<' | '>') => {
+                out.push_str(&format!("%{:02X}", c as u32));
+            }
+            c => out.push(c),
+        }
+    }
+    let title = title.map(|title| title.split_whitespace().collect::<Vec<_>>().join(" "));
+    if let Some(title) = title.filter(|title| !title.is_empty()) {
+        out.push_str(" \"");
+        for c in title.chars() {
+            if matches!(c, '"' | '\\') {
+                out.push('\\');
+            }
+            out.push(c);
+        }
+        out.push('"');
+    }
+    out
+}
+
+/// Collapses whitespace in rendered inline text. Line breaks from `<br>` become Markdown
+/// hard breaks.
+fn collapse(inline: &str) -> String {
+    inline
+        .split('\n')
+        .map(|line| escape_line_start(line.split_whitespace().collect::<Vec<_>>().join(" ")))
+        .skip_while(String::is_empty)
+        .collect::<Vec<_>>()
+        .join("  \n")
+        .trim_end()
+        .to_owned()
+}
+
+/// Escapes the start of a line that Markdown would read as a heading, block quote, list
+/// item, code fence or thematic break.
+fn escape_line_start(mut line: String) -> String {
+    let marker_end = |rest: &str| rest.is_empty() || rest.starts_with(' ');
+    let hashes = line.len() - line.trim_start_matches('#').len();
+    let digits = line.len() - line.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    let at = if line.starts_with('>')
+        || line.starts_with("~~~")
+        || (1..=6).contains(&hashes) && marker_end(&line[hashes..])
+        || line.starts_with(['-', '+']) && marker_end(&line[1..])
+        || !line.is_empty()
+            && (line.trim_matches('-').is_empty() || line.trim_matches('=').is_empty())
+    {
+        0
+    } else if (1..=9).contains(&digits)
+        && line[digits..].starts_with(['.', ')'])
+        && marker_end(&line[digits + 1..])
+    {
+        digits
+    } else {
+        return line;
+    };
+    line.insert(at, '\\');
+    line
+}
+
+/// Appends `inner` to `out` between `open` and `close`, keeping surrounding whitespace
+/// outside the markers so they stay attached to the text.
+fn wrap(out: &mut String, inner: &str, open: &str, close: &str) {
+    let trimmed = inner.trim();
+    if trimmed.is_empty() {
+        out.push_str(inner);
+        return;
+    }
+    if inner.starts_with(char::is_whitespace) {
+        out.push(' ');
+    }
+    out.push_str(open);
+    out.push_str(trimmed);
+    out.push_str(close);
+    if inner.ends_with(char::is_whitespace) {
+        out.push(' ');
+    }
+}
+
+/// The raw text of a subtree, with `<br>` as a newline.
+pub(crate) fn raw_text(node: NodeRef<Node>, out: &mut String) {
+    for child in node.children() {
+        match child.value() {
+            Node::Text(text) => out.push_str(text),
+            Node::Element(e) if e.name() == "br" => out.push('\n'),
+            Node::Element(_) => raw_text(child, out),
+            _ => {}
+        }
+    }
+}
+
+/// Returns the trimmed `cite` attribute of a quotation, if it has one.
+pub(crate) fn quote_cite<'a>(node: NodeRef<'a, Node>) -> Option<&'a str> {
+    let cite = node.value().as_element()?.attr("cite")?.trim();
+    (!cite.is_empty()).then_some(cite)
+}
+
+/// Returns true for elements whose content is not rendered.
+pub(crate) fn is_skipped(name: &str) -> bool {
+    name == "head" || HIDDEN_TEXT.contains(&name)
+}
+
+/// Renders the inline content of `node` into `out`.
+fn inline(node: NodeRef<Node>, cx: &Context, out: &mut String) {
+    for child in node.children() {
+        inline_node(child, cx, out);
+    }
+}
+
+/// Renders one node as inline content.
+fn inline_node(node: NodeRef<Node>, cx: &Context, out: &mut String) {
+    let element = match node.value() {
+        Node::Text(text) => return escape(text, out),
+        Node::Element(e) => e,
+        _ => return,
+    };
+    if let Some(note) = cx.footnotes.reference(node.id()) {
+        match cx.options.footnotes {
+            FootnoteStyle::Inline => {
+                out.push_str(" (");
+                escape(&cx.footnotes.notes[note].text, out);
+                out.push(')');
+            }
+            _ => out.push_str(&format!("[^{}]", note + 1)),
+        }
+        return;
+    }
+    match element.name() {
+        name if is_skipped(name) || cx.footnotes.is_note(node.id()) => {}
+        "br" => out.push('\n'),
+        "img" => {
+            if let Some(src) = element.attr("src") {
+                let mut alt = String::new();
+                escape(element.attr("alt").unwrap_or_default().trim(), &mut alt);
+                match cx.options.images {
+                    ImageStyle::Inline => {
+                        let src = destination(src.trim(), element.attr("title"));
+                        out.push_str(&format!("![{}]({})", alt, src));
+                    }
+                    ImageStyle::AltText => out.push_str(&alt),
+                    ImageStyle::Drop => {}
+                }
+            }
+        }
+        "code" | "kbd" | "samp" => {
+            let mut code = String::new();
+            raw_text(node, &mut code);
+            let code = code.split_whitespace().collect::<Vec<_>>().join(" ");
+            if !code.is_empty() {
+                let longest = code
+                    .split(|c| c != '`')
+                    .map(str::len)
+                    .max()
+                    .unwrap_or_default();
+                let fence = "`".repeat(longest + 1);
+                let pad = if code.starts_with('`') || code.ends_with('`') {
+                    " "
+                } else {
+                    ""
+                };
+                out.push_str(&format!("{fence}{pad}{code}{pad}{fence}"));
+            }
+        }
+        name => {
+            let mut text = String::new();
+            inline(node, cx, &mut text);
+            match name {
+                "a" => match element.attr("href").map(str::trim) {
+                    Some(href) if !href.is_empty() => match cx.options.links {
+                        LinkStyle::Inline => {
+                            let href = destination(href, element.attr("title"));
+                            wrap(out, &text, "[", &format!("]({})", href));
+                        }
+                        LinkStyle::Reference if !text.trim().is_empty() => {
+                            let close = format!("][{}]", cx.reference(href));
+                            wrap(out, &text, "[", &close);
+                        }
+                        _ => out.push_str(&text),
+                    },
+                    _ => out.push_str(&text),
+                },
+                "strong" | "b" => wrap(out, &text, "**", "**"),
+                "em" | "i" | "cite" => wrap(out, &text, "*", "*"),
+                "q" => wrap(out, &text, "“", "”"),
+                "del" | "s" | "strike" => wrap(out, &text, "~~", "~~"),
+                name if BLOCKS.contains(&name) => {
+                    out.push(' ');
+                    out.push_str(&text);
+                    out.push(' ');
+                }
+                _ => out.push_str(&text),
+            }
+        }
+    }
+}
+
+/// Renders the children of `node` as Markdown blocks.
+fn blocks(node: NodeRef<Node>, cx: &Context, out: &mut Vec<String>) {
+    let mut text = String::new();
+    let flush = |text: &mut String, out: &mut Vec<String>| {
+        let paragraph = collapse(text);
+        if !paragraph.is_empty() {
+            out.push(marked(node, cx, paragraph));
+        }
+        text.clear();
+    };
+
+    for child in node.children() {
+        match child.value() {
+            Node::Element(e) if is_skipped(e.name()) || cx.footnotes.is_note(child.id()) => {}
+            Node::Element(e) if BLOCKS.contains(&e.name()) => {
+                flush(&mut text, out);
+                block(child, e.name(), cx, out);
+            }
+            _ => inline_node(child, cx, &mut text),
+        }
+    }
+    flush(&mut text, out);
+}
+
+/// Renders a block element.
+fn block(node: NodeRef<Node>, name: &str, cx: &Context, out: &mut Vec<String>) {
+    match name {
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+            let mut text = String::new();
+            inline(node, cx, &mut text);
+            let text = collapse(&text).replace("  \n", " ");
+            if !text.is_empty() {
+                let level = usize::from(name.as_bytes()[1] - b'0');
+                let text = marked(node, cx, text);
+                out.push(format!("{} {}", "#".repeat(level), text));
+            }
+        }
+        "hr" => out.push("---".to_owned()),
+        "pre" => {
+            let mut code = String::new();
+            raw_text(node, &mut code);
+            let code = code.strip_prefix('\n').unwrap_or(&code).trim_end();
+            if cx.options.code_blocks == CodeBlockStyle::Indented {
+                if !code.trim().is_empty() {
+                    out.push(prefix_lines(code, "    ", ""));
+                }
+                return;
+            }
+            let mut fence = String::from("```");
+            while code.contains(fence.as_str()) {
+                fence.push('`');
+            }
+            let language = code_language(node).unwrap_or_default();
+            out.push(format!("{fence}{language}\n{code}\n{fence}"));
+        }
+        "blockquote" => {
+            let mut inner = Vec::new();
+            blocks(node, cx, &mut inner);
+            if let Some(cite) = quote_cite(node) {
+                let url = url::Url::parse(cite).is_ok() && !cite.contains(['<', '>', ' ']);
+                let mut source = String::new();
+                if url {
+                    source = format!("<{}>", cite);
+                } else {
+                    escape(cite, &mut source);
+                }
+                inner.push(format!("— {}", source));
+            }
+            if !inner.is_empty() {
+                out.push(prefix_lines(&inner.join("\n\n"), "> ", ">"));
+            }
+        }
+        "ul" | "ol" => {
+            let items: Vec<String> = list_items(node)
+                .into_iter()
+                .filter(|(item, _)| !cx.footnotes.is_note(item.id()))
+                .map(|(item, number)| {
+                    let marker = match number {
+                        Some(n) => format!("{}. ", n),
+                        None => "- ".to_owned(),
+                    };
+                    let mut inner = Vec::new();
+                    blocks(item, cx, &mut inner);
+                    let indent = " ".repeat(marker.len());
+                    let body = prefix_lines(&inner.join("\n\n"), &indent, "");
+                    format!("{}{}", marker, &body[indent.len().min(body.len())..])
+                })
+                .collect();
+            if !items.is_empty() {
+                out.push(items.join("\n"));
+            }
+        }
+        "dl" => {
+            let groups: Vec<String> = definition_groups(node)
+                .into_iter()
+                .map(|(terms, definitions)| {
+                    let mut lines: Vec<String> = terms
+                        .into_iter()
+                        .map(|term| {
+                            let mut text = String::new();
+                            inline(term, cx, &mut text);
+                            collapse(&text).replace("  \n", " ")
+                        })
+                        .filter(|term| !term.is_empty())
+                        .collect();
+                    for definition in definitions {
+                        let mut inner = Vec::new();
+                        blocks(definition, cx, &mut inner);
+                        let body = prefix_lines(&inner.join("\n\n"), "    ", "");
+                        if !body.is_empty() {
+                            lines.push(format!(":   {}", &body[4..]));
+                        }
+                    }
+                    lines.join("\n")
+                })
+                .filter(|group| !group.is_empty())
+                .collect();
+            if !groups.is_empty() {
+                out.push(groups.join("\n\n"));
+            }
+        }
+        "table" => match cx.options.tables {
+            TableStyle::Pipe => out.extend(table(node, cx)),
+            TableStyle::Html => out.extend(ElementRef::wrap(node).map(|table| table.html())),
+            TableStyle::Drop => {}
+        },
+        _ => blocks(node, cx, out),
+    }
+}
+
+/// Returns the language of a `pre` block from a `language-*` or `lang-*` class on it or
+/// on its only `code` child.
+fn code_language(pre: NodeRef<Node>) -> Option<String> {
+    let code = pre
+        .children()
+        .filter(|c| !c.value().as_text().is_some_and(|t| t.trim().is_empty()))
+        .collect::<Vec<_>>();
+    let code = match code.as_slice() {
+        [only]
+            if only
+                .value()
+                .as_element()
+                .is_some_and(|e| e.name() == "code") =>
+        {
+            Some(*only)
+        }
+        _ => None,
+    };
+    [Some(pre), code].into_iter().flatten().find_map(|node| {
+        node.value().as_element()?.classes().find_map(|class| {
+            let language = class
+                .strip_prefix("language-")
+                .or_else(|| class.strip_prefix("lang-"))?;
+            let valid = !language.is_empty()
+                && language
+                    .chars()
+                    .all(|c| c.is_alphanumeric() || matches!(c, '-' | '+' | '#' | '.' | '_'));
+            valid.then(|| language.to_owned())
+        })
+    })
+}
+
+/// Prefixes text rendered from `node` with a bidi mark if the options ask for one.
+fn marked(node: NodeRef<Node>, cx: &Context, text: String) -> String {
+    let mark = ElementRef::wrap(node)
+        .filter(|_| cx.options.bidi_marks)
+        .and_then(|element| bidi_mark(element.direction(), &text));
+    match mark {
+        Some(mark) => format!("{}{}", mark, text),
+        None => text,
+    }
+}
+
+/// Returns the `li` children of a list with their numbers, or `None` for unordered lists.
+///
+/// Numbering follows the `start`, `reversed` and per-item `value` attributes of HTML.
+pub(crate) fn list_items(list: NodeRef<Node>) -> Vec<(NodeRef<Node>, Option<i64>)> {
+    let items: Vec<_> = list
+        .children()
+        .filter(|c| c.value().as_element().is_some_and(|e| e.name() == "li"))
+        .collect();
+    let Some(element) = list.value().as_element().filter(|e| e.name() == "ol") else {
+        return items.into_iter().map(|item| (item, None)).collect();
+    };
+
+    let number = |value: Option<&str>| value.and_then(|v| v.trim().parse::<i64>().ok());
+    let reversed = element.attr("reversed").is_some();
+    let step = if reversed { -1 } else { 1 };
+    let start =
+        number(element.attr("start")).unwrap_or(if reversed { items.len() as i64 } else { 1 });
+    let mut next = start;
+    items
+        .into_iter()
+        .map(|item| {
+            let value = item
+                .value()
+                .as_element()
+                .and_then(|e| number(e.attr("value")));
+            let current = value.unwrap_or(next);
+            next = current.saturating_add(step);
+            (item, Some(current))
+        })
+        .collect()
+}
+
+/// The terms of a description list group and their definitions.
+pub(crate) type DefinitionGroup<'a> = (Vec<NodeRef<'a, Node>>, Vec<NodeRef<'a, Node>>);
+
+/// Groups the terms and definitions of a description list, looking through `div`
+/// wrappers.
+pub(crate) fn definition_groups(dl: NodeRef<'_, Node>) -> Vec<DefinitionGroup<'_>> {
+    let mut groups: Vec<DefinitionGroup> = Vec::new();
+    let children = dl.children().flat_map(|child| {
+        let wrapper = child
+            .value()
+            .as_element()
+            .is_some_and(|e| e.name() == "div");
+        if wrapper {
+            child.children().collect::<Vec<_>>()
+        } else {
+            vec![child]
+        }
+    });
+    for child in children {
+        match child.value().as_element().map(|e| e.name()) {
+            Some("dt") => match groups.last_mut() {
+                Some((terms, definitions)) if definitions.is_empty() => terms.push(child),
+                _ => groups.push((vec![child], Vec::new())),
+            },
+            Some("dd") => match groups.last_mut() {
+                Some((_, definitions)) => definitions.push(child),
+                None => groups.push((Vec::new(), vec![child])),
+            },
+            _ => {}
+        }
+    }
+    groups
+}
+
+/// Prefixes every line of `text`, using `empty` for blank lines.
+pub(crate) fn prefix_lines(text: &str, prefix: &str, empty: &str) -> String {
+    text.lines()
+        .map(|line| {
+            if line.is_empty() {
+                empty.to_owned()
+            } else {
+                format!("{}{}", prefix, line)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Returns the `td` and `th` cells of each row of a table, skipping nested tables.
+pub(crate) fn table_rows(node: NodeRef<Node>) -> Vec<Vec<NodeRef<Node>>> {
+    let mut rows = Vec::new();
+    let mut stack: Vec<NodeRef<Node>> = node.children().collect();
+    stack.reverse();
+    while let Some(current) = stack.pop() {
+        let Some(element) = current.value().as_element() else {
+            continue;
+        };
+        match element.name() {
+            "tr" => rows.push(
+                current
+                    .children()
+                    .filter(|c| {
+                        c.value()
+                            .as_element()
+                            .is_some_and(|e| matches!(e.name(), "td" | "th"))
+                    })
+                    .collect(),
+            ),
+            "thead" | "tbody" | "tfoot" => {
+                let mut children: Vec<_> = current.children().collect();
+                children.reverse();
+                stack.extend(children);
+            }
+            _ => {}
+        }
+    }
+    rows
+}
+
+/// Returns the `body` of a document, or the root element of a fragment.
+pub(crate) fn content_root(html: &Html) -> NodeRef<'_, Node> {
+    *html
+        .select(&BODY)
+        .next()
+        .unwrap_or_else(|| html.root_element())
+}
+
+/// Renders a table as a GFM pipe table, using the first row as the header.
+fn table(node: NodeRef<Node>, cx: &Context) -> Option<String> {
+    let rows: Vec<Vec<String>> = table_rows(node)
+        .into_iter()
+        .map(|row| {
+            row.into_iter()
+                .map(|cell| {
+                    let mut text = String::new();
+                    inline(cell, cx, &mut text);
+                    collapse(&text).replace("  \n", " ").replace('|', "\\|")
+                })
+                .collect()
+        })
+        .collect();
+
+    let columns = rows.iter().map(Vec::len).max().filter(|&n| n > 0)?;
+    let line = |cells: &[String]| {
+        let mut line = String::from("|");
+        for i in 0..columns {
+            line.push_str(&format!(" {} |", cells.get(i).map_or("", String::as_str)));
+        }
+        line
+    };
+    let mut lines = vec![line(&rows[0]), line(&vec!["---".to_owned(); columns])];
+    lines.extend(rows[1..].iter().map(|row| line(row)));
+    Some(lines.join("\n"))
+}
+
+impl Html {
+    /// Converts the document body to Markdown.
+    pub fn to_markdown(&self) -> String {
+        self.to_markdown_with(&MarkdownOptions::default())
+    }
+
+    /// Converts the document body to Markdown.
+    ///
+    /// Headings, paragraphs, lists, block quotes, preformatted text and tables become their
+    /// Markdown equivalents, with code blocks tagged by their `language-*` class; links,
+    /// images, emphasis and inline code are kept inline. The text of `head`, `script`,
+    /// `style` and other non-rendered elements is dropped.
+    ///
+    /// ```
+    /// use scraper::markdown::MarkdownOptions;
+    /// use scraper::Html;
+    ///
+    /// let html = Html::parse_document(r#"
+    ///     <title>Notes</title>
+    ///     <meta name="keywords" content="rust">
+    ///     <h1>Notes</h1>
+    ///     <p>See <a href="/docs">the <em>docs</em></a>.</p>
+    /// "#);
+    /// let options = MarkdownOptions { front_matter: true, ..MarkdownOptions::default() };
+    /// assert_eq!(
+    ///     "---\ntitle: \"Notes\"\ntags:\n  - \"rust\"\n---\n\n# Notes\n\nSee [the *docs*](/docs).\n",
+    ///     html.to_markdown_with(&options)
+    /// );
+    /// ```
+    pub fn to_markdown_with(&self, options: &MarkdownOptions) -> String {
+        let mut out = String::new();
+        if options.front_matter {
+            out.push_str(&self.metadata().to_front_matter());
+        }
+        let footnotes = match options.footnotes {
+            FootnoteStyle::Keep => FootnoteIndex::default(),
+            _ => FootnoteIndex::new(self),
+        };
+        render(options, footnotes, &mut out, |cx, rendered| {
+            blocks(content_root(self), cx, rendered)
+        });
+        out
+    }
+}
+
+impl ElementRef<'_> {
+    /// Converts the element and its content to Markdown.
+    pub fn to_markdown(&self) -> String {
+        self.to_markdown_with(&MarkdownOptions::default())
+    }
+
+    /// Converts the element and its content to Markdown, as [`Html::to_markdown_with`]
+    /// converts a document body. Front matter is not written.
+    ///
+    /// ```
+    /// use scraper::markdown::{LinkStyle, MarkdownOptions};
+    /// use scraper::{Html, Selector};
+    ///
+    /// let html = Html::parse_fragment(
+    ///     r#"<nav>Skip</nav><article><p>Read <a href="/a">this</a> and <a href="/b">that</a>.</p></article>"#,
+    /// );
+    /// let article = html.select_first(&Selector::parse("article").unwrap()).unwrap();
+    /// let options = MarkdownOptions { links: LinkStyle::Reference, ..MarkdownOptions::default() };
+    /// assert_eq!(
+    ///     "Read [this][1] and [that][2].\n\n[1]: /a\n[2]: /b\n",
+    ///     article.to_markdown_with(&options)
+    /// );
+    /// ```
+    pub fn to_markdown_with(&self, options: &MarkdownOptions) -> String {
+        let footnotes = match options.footnotes {
+            FootnoteStyle::Keep => FootnoteIndex::default(),
+            _ => FootnoteIndex::from_root(self.ancestors().last().unwrap_or(**self)),
+        };
+        let mut out = String::new();
+        let node = **self;
+        let name = self.value().name();
+        render(options, footnotes, &mut out, |cx, rendered| {
+            if BLOCKS.contains(&name) {
+                block(node, name, cx, rendered);
+            } else {
+                let mut text = String::new();
+                inline_node(node, cx, &mut text);
+                let text = collapse(&text);
+                if !text.is_empty() {
+                    rendered.push(text);
+                }
+            }
+        });
+        out
+    }
+}
+
+/// Renders blocks with `build`, followed by the collected footnotes and link references,
+/// into `out`.
+fn render(
+    options: &MarkdownOptions,
+    footnotes: FootnoteIndex,
+    out: &mut String,
+    build: impl FnOnce(&Context, &mut Vec<String>),
+) {
+    let cx = Context {
+        options,
+        footnotes,
+        references: RefCell::new(Vec::new()),
+    };
+    let mut rendered = Vec::new();
+    build(&cx, &mut rendered);
+    if options.footnotes == FootnoteStyle::Collect && !cx.footnotes.notes.is_empty() {
+        let notes: Vec<String> = cx
+            .footnotes
+            .notes
+            .iter()
+            .enumerate()
+            .map(|(i, note)| {
+                let mut text = String::new();
+                escape(&note.text, &mut text);
+                format!("[^{}]: {}", i + 1, text)
+            })
+            .collect();
+        rendered.push(notes.join("\n"));
+    }
+    let references = cx.references.into_inner();
+    if !references.is_empty() {
+        let definitions: Vec<String> = references
+            .iter()
+            .enumerate()
+            .map(|(i, url)| format!("[{}]: {}", i + 1, destination(url, None)))
+            .collect();
+        rendered.push(definitions.join("\n"));
+    }
+    if !rendered.is_empty() {
+        out.push_str(&rendered.join("\n\n"));
+        out.push('\n');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CodeBlockStyle, ImageStyle, LinkStyle, MarkdownOptions, TableStyle};
+    use crate::footnotes::FootnoteStyle;
+    use crate::html::Html;
+
+    #[test]
+    fn converts_block_structure() {
+        let html = Html::parse_fragment(
+            "<h2>Steps</h2><ol><li>One</li><li>Two<ul><li>nested</li></ul></li></ol>\
+             <blockquote><p>Quoted *text*</p></blockquote>\
+             <pre>fn main() {\n    x();\n}</pre>\
+             <table><tr><th>A</th><th>B</th></tr><tr><td>1</td><td>x|y</td></tr></table>\
+             <script>ignored()</script><p>line<br>break</p>",
+        );
+        assert_eq!(
+            "## Steps\n\n\
+             1. One\n2. Two\n\n   - nested\n\n\
+             > Quoted \\*text\\*\n\n\
+             ```\nfn main() {\n    x();\n}\n```\n\n\
+             | A | B |\n| --- | --- |\n| 1 | x\\|y |\n\n\
+             line  \nbreak\n",
+            html.to_markdown()
+        );
+    }
+
+    #[test]
+    fn escapes_block_markers_and_fences_inline_code() {
+        let html = Html::parse_fragment(
+            "<p># not a heading</p><p>2. not<br>- a list<br>&gt; or quote</p><p>+</p><p>---</p>\
+             <p>#hashtag 3.5 -1</p><p><code>a``b</code> <code>`x</code></p>",
+        );
+        assert_eq!(
+            "\\# not a heading\n\n2\\. not  \n\\- a list  \n\\> or quote\n\n\\+\n\n\\---\n\n\
+             #hashtag 3.5 -1\n\n```a``b``` `` `x ``\n",
+            html.to_markdown()
+        );
+    }
+
+    #[test]
+    fn tags_code_blocks_with_their_language() {
+        let html = Html::parse_fragment(
+            "<pre class='lang-sh'>ls</pre>\
+             <pre>\n  <code class='hljs language-rust'>fn x() {}</code>\n</pre>\
+             <pre class='language-'>plain</pre>",
+        );
+        assert_eq!(
+            "```sh\nls\n```\n\n```rust\n  fn x() {}\n```\n\n```\nplain\n```\n",
+            html.to_markdown()
+        );
+    }
+
+    #[test]
+    fn keeps_list_numbering_and_definition_lists() {
+        let html = Html::parse_fragment(
+            "<ol start='3'><li>c</li><li value='7'>g<ul><li>x</li></ul></li><li>h</li></ol>\
+             <ul><li><p>First.</p><p>Second.</p></li></ul>\
+             <dl><dt>API</dt><dd><p>Interface.</p><p>More.</p></dd></dl>",
+        );
+        assert_eq!(
+            "3. c\n7. g\n\n   - x\n8. h\n\n- First.\n\n  Second.\n\nAPI\n:   Interface.\n\n    More.\n",
+            html.to_markdown()
+        );
+    }
+
+    #[test]
+    fn escapes_raw_html_in_text() {
+        let html = Html::parse_fragment("<p>a &lt;script&gt;x()&lt;/script&gt; &amp;amp; b</p>");
+        assert_eq!(
+            "a \\<script>x()\\</script> &amp;amp; b\n",
+            html.to_markdown()
+        );
+    }
+
+    #[test]
+    fn escapes_link_destinations_and_titles() {
+        let html = Html::parse_fragment(
+            r#"<p><a href="/a b (1)" title='Say "hi"'>A</a> <img src="/x).png" alt="X"></p>"#,
+        );
+        assert_eq!(
+            "[A](/a%20b%20%281%29 \"Say \\\"hi\\\"\") ![X](/x%29.png)\n",
+            html.to_markdown()
+        );
+    }
+
+    #[test]
+    fn keeps_quote_nesting_and_sources() {
+        let html = Html::parse_fragment(
+            "<blockquote cite='https://a.example/post'><p>Outer <q>said</q></p>\
+             <blockquote><p>Inner by <cite>Bob</cite></p></blockquote></blockquote>",
+        );
+        assert_eq!(
+            "> Outer “said”\n>\n> > Inner by *Bob*\n>\n> — <https://a.example/post>\n",
+            html.to_markdown()
+        );
+    }
+
+    #[test]
+    fn collects_or_inlines_footnotes() {
+        let html = Html::parse_fragment(
+            "<p>Claim<sup id='r1'><a href='#n1'>[1]</a></sup> and more<sup><a href='#n2'>[2]</a></sup>.</p>\
+             <h2>Notes</h2><ol><li id='n1'><a href='#r1'>^</a> First *source*.</li><li id='n2'>Second.</li></ol>",
+        );
+        let collect = MarkdownOptions {
+            footnotes: FootnoteStyle::Collect,
+            ..MarkdownOptions::default()
+        };
+        assert_eq!(
+            "Claim[^1] and more[^2].\n\n## Notes\n\n[^1]: First \\*source\\*.\n[^2]: Second.\n",
+            html.to_markdown_with(&collect)
+        );
+        let inline = MarkdownOptions {
+            footnotes: FootnoteStyle::Inline,
+            ..MarkdownOptions::default()
+        };
+        assert_eq!(
+            "Claim (First \\*source\\*.) and more (Second.).\n\n## Notes\n",
+            html.to_markdown_with(&inline)
+        );
+    }
+
+    #[test]
+    fn applies_link_image_table_and_code_styles() {
+        let html = Html::parse_fragment(
+            "<p><a href='/a'>A</a> <img src='/i.png' alt='Pic'> <a href='/a'>again</a></p>\
+             <table><tr><td>x</td></tr></table><pre>let x;\nlet y;</pre>",
+        );
+        let options = MarkdownOptions {
+            links: LinkStyle::Reference,
+            images: ImageStyle::AltText,
+            tables: TableStyle::Html,
+            code_blocks: CodeBlockStyle::Indented,
+            ..MarkdownOptions::default()
+        };
+        assert_eq!(
+            "[A][1] Pic [again][1]\n\n<table><tbody><tr><td>x</td></tr></tbody></table>\n\n    let x;\n    let y;\n\n[1]: /a\n",
+            html.to_markdown_with(&options)
+        );
+        let options = MarkdownOptions {
+            links: LinkStyle::TextOnly,
+            images: ImageStyle::Drop,
+            tables: TableStyle::Drop,
+            ..MarkdownOptions::default()
+        };
+        assert_eq!(
+            "A again\n\n```\nlet x;\nlet y;\n```\n",
+            html.to_markdown_with(&options)
+        );
+    }
+}
@@ -0,0 +1,334 @@
+//! Classification of links to downloadable files.
+
+use std::collections::HashSet;
+
+use url::Url;
+
+use crate::html::Html;
+use crate::selector::Selector;
+use crate::url_utils::{normalize, UrlPolicy};
+
+lazy_static! {
+    static ref LINKS: Selector = Selector::parse("a[href], area[href]").unwrap();
+}
+
+/// What kind of file a link downloads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DownloadCategory {
+    /// Documents such as PDF, Office and EPUB files.
+    Document,
+    /// Compressed archives.
+    Archive,
+    /// Images, audio and video.
+    Media,
+    /// Datasets: CSV, JSON, spreadsheets, Parquet and similar.
+    Data,
+    /// Installers and packages.
+    Software,
+    /// A `download` link of unknown type.
+    Other,
+}
+
+/// A link classified as a download.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DownloadLink {
+    /// The resolved URL.
+    pub url: Url,
+    /// The file category.
+    pub category: DownloadCategory,
+    /// The lowercased file extension that classified the link, such as `csv` or `tar.gz`.
+    pub extension: Option<String>,
+    /// The suggested file name from a non-empty `download` attribute.
+    pub filename: Option<String>,
+    /// The link text, with whitespace collapsed.
+    pub text: String,
+}
+
+const EXTENSIONS: &[(&str, DownloadCategory)] = {
+    use DownloadCategory::*;
+    &[
+        ("pdf", Document),
+        ("doc", Document),
+        ("docx", Document),
+        ("odt", Document),
+        ("rtf", Document),
+        ("txt", Document),
+        ("epub", Document),
+        ("ppt", Document),
+        ("pptx", Document),
+        ("odp", Document),
+        ("ps", Document),
+        ("zip", Archive),
+        ("rar", Archive),
+        ("7z", Archive),
+        ("tar", Archive),
+        ("tar.gz", Archive),
+        ("tgz", Archive),
+        ("tar.bz2", Archive),
+        ("tar.xz", Archive),
+        ("gz", Archive),
+        ("bz2", Archive),
+        ("xz", Archive),
+        ("zst", Archive),
+        ("mp3", Media),
+        ("wav", Media),
+        ("flac", Media),
+        ("ogg", Media),
+        ("m4a", Media),
+        ("aac", Media),
+        ("mp4", Media),
+        ("m4v", Media),
+        ("mkv", Media),
+        ("webm", Media),
+        ("mov", Media),
+        ("avi", Media),
+        ("jpg", Media),
+        ("jpeg", Media),
+        ("png", Media),
+        ("gif", Media),
+        ("webp", Media),
+        ("tif", Media),
+        ("tiff", Media),
+        ("csv", Data),
+        ("tsv", Data),
+        ("json", Data),
+        ("jsonl", Data),
+        ("ndjson", Data),
+        ("geojson", Data),
+        ("xml", Data),
+        ("xls", Data),
+        ("xlsx", Data),
+        ("ods", Data),
+        ("parquet", Data),
+        ("arrow", Data),
+        ("feather", Data),
+        ("avro", Data),
+        ("sqlite", Data),
+        ("db", Data),
+        ("h5", Data),
+        ("hdf5", Data),
+        ("nc", Data),
+        ("shp", Data),
+        ("kml", Data),
+        ("kmz", Data),
+        ("exe", Software),
+        ("msi", Software),
+        ("dmg", Software),
+        ("pkg", Software),
+        ("apk", Software),
+        ("deb", Software),
+        ("rpm", Software),
+        ("appimage", Software),
+    ]
+};
+
+/// Looks up the longest known extension of a file name, so `x.tar.gz` is `tar.gz`.
+fn classify_name(name: &str) -> Option<(&'static str, DownloadCategory)> {
+    let name = name.to_ascii_lowercase();
+    EXTENSIONS
+        .iter()
+        .filter(|(ext, _)| {
+            name.strip_suffix(ext)
+                .is_some_and(|stem| stem.len() > 1 && stem.ends_with('.'))
+        })
+        .max_by_key(|(ext, _)| ext.len())
+        .copied()
+}
+
+fn classify_mime(mime: &str) -> Option<DownloadCategory> {
+    let mime = mime.split(';').next()?.trim().to_ascii_lowercase();
+    let category = match mime.as_str() {
+        "application/pdf"
+        | "application/msword"
+        | "application/epub+zip"
+        | "application/rtf"
+        | "text/plain" => DownloadCategory::Document,
+        "application/zip"
+        | "application/gzip"
+        | "application/x-tar"
+        | "application/x-7z-compressed"
+        | "application/vnd.rar" => DownloadCategory::Archive,
+        "text/csv"
+        | "text/tab-separated-values"
+        | "application/json"
+        | "application/x-ndjson"
+        | "application/geo+json"
+        | "application/vnd.ms-excel"
+        | "application/vnd.apache.parquet" => DownloadCategory::Data,
+        "application/vnd.microsoft.portable-executable"
+        | "application/x-msdownload"
+        | "application/vnd.android.package-archive" => DownloadCategory::Software,
+        _ if mime.starts_with("image/")
+            || mime.starts_with("audio/")
+            || mime.starts_with("video/") =>
+        {
+            DownloadCategory::Media
+        }
+        _ if mime.starts_with("application/vnd.openxmlformats-officedocument.spreadsheetml")
+            || mime.starts_with("application/vnd.oasis.opendocument.spreadsheet") =>
+        {
+            DownloadCategory::Data
+        }
+        _ if mime.starts_with("application/vnd.openxmlformats-officedocument")
+            || mime.starts_with("application/vnd.oasis.opendocument") =>
+        {
+            DownloadCategory::Document
+        }
+        _ => return None,
+    };
+    Some(category)
+}
+
+impl Html {
+    /// Returns the links that point at downloadable files, resolved against the
+    /// [base URL](Html::base_url) of `base`, in document order and without duplicates.
+    ///
+    /// A link is classified by, in order: the extension of its `download` file name, the
+    /// extension of its URL path, and its `type` attribute. Links with a `download` attribute
+    /// that match none of these are reported as [`DownloadCategory::Other`]; other links are
+    /// skipped.
+    ///
+    /// ```
+    /// use scraper::media::DownloadCategory;
+    /// use scraper::Html;
+    /// use url::Url;
+    ///
+    /// let html = Html::parse_fragment(r#"
+    ///     <a href="/data/2024.csv">CSV</a>
+    ///     <a href="/about">About</a>
+    ///     <a href="/dl?id=7" download="report.pdf">Report</a>
+    /// "#);
+    /// let base = Url::parse("https://stats.example/").unwrap();
+    /// let links = html.downloadable_links(&base);
+    /// let categories: Vec<_> = links.iter().map(|l| l.category).collect();
+    /// assert_eq!(vec![DownloadCategory::Data, DownloadCategory::Document], categories);
+    /// ```
+    pub fn downloadable_links(&self, base: &Url) -> Vec<DownloadLink> {
+        self.downloadable_links_as(base, None)
+    }
+
+    /// Returns the links that point at downloadable files, as [`Html::downloadable_links`]
+    /// does, with each URL normalized by `policy` before duplicates are removed.
+    pub fn downloadable_links_with(&self, base: &Url, policy: &UrlPolicy) -> Vec<DownloadLink> {
+        self.downloadable_links_as(base, Some(policy))
+    }
+
+    fn downloadable_links_as(&self, base: &Url, policy: Option<&UrlPolicy>) -> Vec<DownloadLink> {
+        let resolve = self.base_url(base);
+        let mut links: Vec<DownloadLink> = Vec::new();
+        let mut seen = HashSet::new();
+
+        for anchor in self.select(&LINKS) {
+            let value = anchor.value();
+            let href = value.attr("href").unwrap_or_default().trim();
+            let Ok(url) = resolve.join(href) else {
+                continue;
+            };
+            if !matches!(url.scheme(), "http" | "https" | "ftp") || href.is_empty() {
+                continue;
+            }
+            let url = match policy {
+                Some(policy) => normalize(&url, policy),
+                None => url,
+            };
+            if seen.contains(&url) {
+                continue;
+            }
+
+            let filename = value
+                .attr("download")
+                .map(str::trim)
+                .filter(|name| !name.is_empty())
+                .map(str::to_owned);
+            let file = url.path_segments().and_then(|mut s| s.next_back());
+
+            let by_extension = filename
+                .as_deref()
+                .and_then(classify_name)
+                .or_else(|| file.and_then(classify_name));
+            let (extension, category) = match by_extension {
+                Some((ext, category)) => (Some(ext.to_owned()), category),
+                None => match value
+                    .attr("type")
+                    .and_then(classify_mime)
+                    .or_else(|| value.attr("download").map(|_| DownloadCategory::Other))
+                {
+                    Some(category) => (None, category),
+                    None => continue,
+                },
+            };
+
+            seen.insert(url.clone());
+            links.push(DownloadLink {
+                url,
+                category,
+                extension,
+                filename,
+                text: anchor
+                    .text()
+                    .collect::<String>()
+                    .split_whitespace()
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            });
+        }
+
+        links
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use url::Url;
+
+    use super::DownloadCategory;
+    use crate::html::Html;
+    use crate::url_utils::UrlPolicy;
+
+    #[test]
+    fn classifies_by_extension_type_and_attribute() {
+        let html = Html::parse_fragment(
+            r#"
+            <a href="release-1.0.TAR.GZ">Source</a>
+            <a href="/export" type="text/csv; charset=utf-8">Export</a>
+            <a href="/blob/42" download>  Get   it </a>
+            <a href="/page.html">Page</a>
+            <a href="mailto:a@b.c?subject=x.pdf">Mail</a>
+            <a href="/.zip">Hidden</a>
+            "#,
+        );
+        let base = Url::parse("https://example.com/files/").unwrap();
+        let links = html.downloadable_links(&base);
+        let found: Vec<_> = links
+            .iter()
+            .map(|l| (l.url.path(), l.category, l.extension.as_deref()))
+            .collect();
+        assert_eq!(
+            vec![
+                (
+                    "/files/release-1.0.TAR.GZ",
+                    DownloadCategory::Archive,
+                    Some("tar.gz")
+                ),
+                ("/export", DownloadCategory::Data, None),
+                ("/blob/42", DownloadCategory::Other, None),
+            ],
+            found
+        );
+        assert_eq!("Get it", links[2].text);
+    }
+
+    #[test]
+    fn policy_normalizes_before_deduplicating() {
+        let html = Html::parse_fragment(
+            r#"<a href="/r.pdf?utm_source=mail">A</a><a href="/r.pdf#page=2">B</a>"#,
+        );
+        let base = Url::parse("https://example.com/").unwrap();
+        assert_eq!(2, html.downloadable_links(&base).len());
+        let links = html.downloadable_links_with(&base, &UrlPolicy::default());
+        assert_eq!(
+            vec!["https://example.com/r.pdf"],
+            links.iter().map(|l| l.url.as_str()).collect::<Vec<_>>()
+        );
+    }
+}
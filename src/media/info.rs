@@ -0,0 +1,211 @@
+//! Sources and text tracks of `<video>` and `<audio>` elements.
+
+use crate::element_ref::ElementRef;
+
+/// Whether a media element is a video or an audio player.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MediaKind {
+    /// `<video>`.
+    Video,
+    /// `<audio>`.
+    Audio,
+}
+
+/// A playable source of a media element.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MediaSource {
+    /// The unresolved URL.
+    pub src: String,
+    /// The `type` attribute, e.g. `video/webm; codecs="vp9"`.
+    pub mime: Option<String>,
+    /// The `media` query the source applies to.
+    pub media: Option<String>,
+}
+
+/// A `<track>` of captions, subtitles or other timed text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MediaTrack {
+    /// The unresolved URL of the WebVTT file.
+    pub src: String,
+    /// The track kind, lowercased; `subtitles` when missing or invalid, as in browsers.
+    pub kind: String,
+    /// The track language.
+    pub srclang: Option<String>,
+    /// The user-visible label.
+    pub label: Option<String>,
+    /// Whether the track is enabled by default.
+    pub default: bool,
+}
+
+/// What a media element plays.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MediaInfo {
+    /// Video or audio.
+    pub kind: MediaKind,
+    /// The sources in priority order: the element's own `src`, then its `<source>`s.
+    pub sources: Vec<MediaSource>,
+    /// The poster image of a video.
+    pub poster: Option<String>,
+    /// The duration in seconds, from a `duration` or `data-duration` attribute written as
+    /// seconds or `[h:]mm:ss`.
+    pub duration: Option<f64>,
+    /// The text tracks.
+    pub tracks: Vec<MediaTrack>,
+}
+
+impl MediaInfo {
+    /// Returns the caption and subtitle tracks.
+    pub fn captions(&self) -> impl Iterator<Item = &MediaTrack> {
+        self.tracks
+            .iter()
+            .filter(|t| t.kind == "captions" || t.kind == "subtitles")
+    }
+}
+
+fn parse_duration(value: &str) -> Option<f64> {
+    let value = value.trim();
+    if !value.contains(':') {
+        return value
+            .parse()
+            .ok()
+            .filter(|d: &f64| d.is_finite() && *d >= 0.0);
+    }
+    let mut seconds = 0.0;
+    for part in value.split(':') {
+        let part: f64 = part.trim().parse().ok()?;
+        seconds = seconds * 60.0 + part;
+    }
+    Some(seconds)
+}
+
+fn non_empty(value: Option<&str>) -> Option<String> {
+    value
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(str::to_owned)
+}
+
+impl<'a> ElementRef<'a> {
+    /// Returns the sources, poster, duration and text tracks of a `<video>` or `<audio>`
+    /// element, or `None` for other elements.
+    ///
+    /// ```
+    /// use scraper::{Html, Selector};
+    ///
+    /// let html = Html::parse_fragment(r#"
+    ///     <video poster="/p.jpg">
+    ///       <source src="/v.webm" type="video/webm">
+    ///       <source src="/v.mp4" type="video/mp4">
+    ///       <track src="/en.vtt" kind="captions" srclang="en" default>
+    ///     </video>
+    /// "#);
+    /// let video = html.select(&Selector::parse("video").unwrap()).next().unwrap();
+    /// let info = video.media_info().unwrap();
+    /// assert_eq!(2, info.sources.len());
+    /// assert_eq!(Some("/p.jpg"), info.poster.as_deref());
+    /// assert_eq!("/en.vtt", info.captions().next().unwrap().src);
+    /// ```
+    pub fn media_info(&self) -> Option<MediaInfo> {
+        let element = self.value();
+        let kind = match element.name() {
+            "video" => MediaKind::Video,
+            "audio" => MediaKind::Audio,
+            _ => return None,
+        };
+
+        let mut sources = Vec::new();
+        if let Some(src) = non_empty(element.attr("src")) {
+            sources.push(MediaSource {
+                src,
+                mime: None,
+                media: None,
+            });
+        }
+        let mut tracks = Vec::new();
+
+        for child in self.children().filter_map(ElementRef::wrap) {
+            let value = child.value();
+            match value.name() {
+                "source" => {
+                    let Some(src) = non_empty(value.attr("src")) else {
+                        continue;
+                    };
+                    sources.push(MediaSource {
+                        src,
+                        mime: non_empty(value.attr("type")),
+                        media: non_empty(value.attr("media")),
+                    });
+                }
+                "track" => {
+                    let Some(src) = non_empty(value.attr("src")) else {
+                        continue;
+                    };
+                    let kind = value
+                        .attr("kind")
+                        .map(|k| k.trim().to_ascii_lowercase())
+                        .filter(|k| {
+                            matches!(
+                                k.as_str(),
+                                "subtitles" | "captions" | "descriptions" | "chapters" | "metadata"
+                            )
+                        })
+                        .unwrap_or_else(|| "subtitles".to_owned());
+                    tracks.push(MediaTrack {
+                        src,
+                        kind,
+                        srclang: non_empty(value.attr("srclang")),
+                        label: non_empty(value.attr("label")),
+                        default: value.attr("default").is_some(),
+                    });
+                }
+                _ => {}
+            }
+        }
+
+        Some(MediaInfo {
+            kind,
+            sources,
+            poster: match kind {
+                MediaKind::Video => non_empty(element.attr("poster")),
+                MediaKind::Audio => None,
+            },
+            duration: element
+                .attr("duration")
+                .or_else(|| element.attr("data-duration"))
+                .and_then(parse_duration),
+            tracks,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MediaKind;
+    use crate::html::Html;
+    use crate::selector::Selector;
+
+    #[test]
+    fn audio_with_src_and_tracks() {
+        let html = Html::parse_fragment(
+            r#"<audio src="/a.mp3" data-duration="1:02:03">
+                <source src="/a.ogg" type="audio/ogg">
+                <source src="">
+                <track src="/c.vtt" kind="Chapters" label="Parts">
+                <track src="/s.vtt" kind="bogus" srclang="de">
+            </audio><p>x</p>"#,
+        );
+        let selector = Selector::parse("audio, p").unwrap();
+        let mut elements = html.select(&selector);
+        let info = elements.next().unwrap().media_info().unwrap();
+
+        assert_eq!(MediaKind::Audio, info.kind);
+        let sources: Vec<_> = info.sources.iter().map(|s| s.src.as_str()).collect();
+        assert_eq!(vec!["/a.mp3", "/a.ogg"], sources);
+        assert_eq!(Some(3723.0), info.duration);
+        assert_eq!("chapters", info.tracks[0].kind);
+        let captions: Vec<_> = info.captions().map(|t| t.src.as_str()).collect();
+        assert_eq!(vec!["/s.vtt"], captions);
+
+        assert!(elements.next().unwrap().media_info().is_none());
+    }
+}
@@ -0,0 +1,187 @@
+//! Normalization of lazy-loaded and AMP images.
+
+use ego_tree::NodeId;
+use html5ever::QualName;
+
+use crate::html::Html;
+use crate::node::{Element, Node};
+use crate::selector::Selector;
+
+lazy_static! {
+    static ref IMAGES: Selector =
+        Selector::parse("img, picture > source, amp-img, amp-anim").unwrap();
+    static ref LAZY_SOURCES: Vec<String> = LazyImageOptions::default().src_attributes;
+}
+
+/// Where [`Html::resolve_lazy_images_with`] looks for the real image URLs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LazyImageOptions {
+    /// Attributes holding the real `src`, in priority order.
+    pub src_attributes: Vec<String>,
+    /// Attributes holding the real `srcset`, in priority order.
+    pub srcset_attributes: Vec<String>,
+    /// Rename `<amp-img>` and `<amp-anim>` to `<img>`, dropping their fallback content.
+    pub unwrap_amp: bool,
+}
+
+impl Default for LazyImageOptions {
+    fn default() -> Self {
+        let names = |names: &[&str]| names.iter().map(|name| (*name).to_owned()).collect();
+        LazyImageOptions {
+            src_attributes: names(&[
+                "data-src",
+                "data-lazy-src",
+                "data-original",
+                "data-lazy",
+                "data-url",
+                "data-echo",
+                "data-hi-res-src",
+            ]),
+            srcset_attributes: names(&["data-srcset", "data-lazy-srcset", "data-original-set"]),
+            unwrap_amp: true,
+        }
+    }
+}
+
+/// Returns true for a missing `src` or one that lazy loaders replace: empty, `about:blank`,
+/// a fragment or an inline `data:` placeholder.
+pub(crate) fn is_placeholder(value: Option<&str>) -> bool {
+    let value = value.map(str::trim).unwrap_or_default();
+    value.is_empty()
+        || value.starts_with('#')
+        || value.eq_ignore_ascii_case("about:blank")
+        || value
+            .get(..5)
+            .is_some_and(|scheme| scheme.eq_ignore_ascii_case("data:"))
+}
+
+/// Returns the real URL of an image: its `src` unless it is a placeholder, else the first
+/// lazy-loading attribute of the default options.
+pub(crate) fn image_source(element: &Element) -> Option<&str> {
+    let src = element.attr("src");
+    if !is_placeholder(src) {
+        return src.map(str::trim);
+    }
+    LAZY_SOURCES
+        .iter()
+        .filter_map(|name| element.attr(name))
+        .map(str::trim)
+        .find(|value| !value.is_empty())
+}
+
+impl Html {
+    /// Copies the real URLs of lazy-loaded images into `src` and `srcset` with the default
+    /// [`LazyImageOptions`], returning the number of elements changed.
+    ///
+    /// ```
+    /// use scraper::{Html, Selector};
+    ///
+    /// let mut html = Html::parse_fragment(
+    ///     r#"<img src="data:image/gif;base64,R0lGOD" data-src="/photo.jpg" loading="lazy">
+    ///     <amp-img src="/amp.jpg" width="4" height="3"><div fallback>Offline</div></amp-img>"#,
+    /// );
+    /// assert_eq!(2, html.resolve_lazy_images());
+    ///
+    /// let sources: Vec<_> = html
+    ///     .select(&Selector::parse("img").unwrap())
+    ///     .filter_map(|img| img.value().attr("src"))
+    ///     .collect();
+    /// assert_eq!(vec!["/photo.jpg", "/amp.jpg"], sources);
+    /// ```
+    pub fn resolve_lazy_images(&mut self) -> usize {
+        self.resolve_lazy_images_with(&LazyImageOptions::default())
+    }
+
+    /// Copies the real URLs of lazy-loaded images into `src` and `srcset`, returning the
+    /// number of elements changed.
+    ///
+    /// An `<img>` whose `src` is missing or a placeholder takes the first non-empty
+    /// attribute of `src_attributes`, and one without a `srcset` the first of
+    /// `srcset_attributes`. `<source>` elements of a `<picture>` only get a `srcset`. The
+    /// lazy attributes themselves are kept.
+    pub fn resolve_lazy_images_with(&mut self, options: &LazyImageOptions) -> usize {
+        let ids: Vec<NodeId> = self.select(&IMAGES).map(|image| image.id()).collect();
+        let mut changed = 0;
+        for id in ids {
+            let Some(Node::Element(element)) = self.tree.get(id).map(|node| node.value()) else {
+                continue;
+            };
+            let first = |names: &[String]| {
+                names
+                    .iter()
+                    .filter_map(|name| element.attr(name))
+                    .map(str::trim)
+                    .find(|value| !value.is_empty())
+                    .map(str::to_owned)
+            };
+
+            let is_amp = matches!(element.name(), "amp-img" | "amp-anim");
+            let src = match element.name() {
+                "source" => None,
+                _ if is_placeholder(element.attr("src")) => first(&options.src_attributes),
+                _ => None,
+            };
+            let srcset = match element.attr("srcset") {
+                Some(srcset) if !srcset.trim().is_empty() => None,
+                _ => first(&options.srcset_attributes),
+            };
+            let unwrap = is_amp && options.unwrap_amp;
+            if src.is_none() && srcset.is_none() && !unwrap {
+                continue;
+            }
+
+            if let Some(src) = src {
+                self.set_attr(id, "src", &src);
+            }
+            if let Some(srcset) = srcset {
+                self.set_attr(id, "srcset", &srcset);
+            }
+            if unwrap {
+                if let Some(element) = self.tree.get_mut(id).unwrap().value().as_element_mut() {
+                    element.name = QualName::new(None, ns!(html), local_name!("img"));
+                }
+                self.remove_children(id);
+            }
+            changed += 1;
+        }
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LazyImageOptions;
+    use crate::html::Html;
+    use crate::selector::Selector;
+
+    #[test]
+    fn honors_priority_and_existing_values() {
+        let mut html = Html::parse_fragment(
+            r#"<img src="/real.jpg" data-src="/lazy.jpg">
+            <img data-original="/b.jpg" data-src=" " data-lazy-src="/a.jpg">
+            <picture><source data-srcset="/c.webp 2x"><img srcset="/d.jpg 1x" data-srcset="/e.jpg"></picture>
+            <amp-img src="/amp.jpg"></amp-img>"#,
+        );
+        let options = LazyImageOptions {
+            unwrap_amp: false,
+            ..LazyImageOptions::default()
+        };
+        assert_eq!(2, html.resolve_lazy_images_with(&options));
+
+        let attrs = |selector: &str, name: &str| -> Vec<Option<String>> {
+            html.select(&Selector::parse(selector).unwrap())
+                .map(|e| e.value().attr(name).map(str::to_owned))
+                .collect()
+        };
+        assert_eq!(
+            vec![Some("/real.jpg".into()), Some("/a.jpg".into()), None],
+            attrs("img", "src")
+        );
+        assert_eq!(vec![Some("/c.webp 2x".into())], attrs("source", "srcset"));
+        assert_eq!(
+            vec![Some("/d.jpg 1x".into())],
+            attrs("picture img", "srcset")
+        );
+        assert_eq!(1, attrs("amp-img", "src").len());
+    }
+}
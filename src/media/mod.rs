@@ -0,0 +1,259 @@
+//! Embedded and linked media.
+
+mod downloads;
+mod info;
+pub(crate) mod lazy;
+mod oembed;
+
+pub use self::downloads::{DownloadCategory, DownloadLink};
+pub use self::info::{MediaInfo, MediaKind, MediaSource, MediaTrack};
+pub use self::lazy::LazyImageOptions;
+pub use self::oembed::{OEmbedEndpoint, OEmbedFormat};
+
+use selectors::attr::CaseSensitivity;
+use url::Url;
+
+use crate::element_ref::ElementRef;
+use crate::html::Html;
+use crate::selector::Selector;
+
+lazy_static! {
+    static ref EMBED_CANDIDATES: Selector = Selector::parse(
+        "iframe, lite-youtube, blockquote.twitter-tweet, blockquote.twitter-video, \
+         blockquote.instagram-media, blockquote.tiktok-embed"
+    )
+    .unwrap();
+    static ref ANCHORS: Selector = Selector::parse("a[href]").unwrap();
+}
+
+/// A social or video platform whose embeds are recognized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EmbedProvider {
+    /// YouTube videos, including `youtube-nocookie.com` and `<lite-youtube>`.
+    YouTube,
+    /// Vimeo videos.
+    Vimeo,
+    /// Twitter / X posts.
+    Twitter,
+    /// Instagram posts and reels.
+    Instagram,
+    /// TikTok videos.
+    TikTok,
+}
+
+/// A recognized embed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Embed {
+    /// The platform.
+    pub provider: EmbedProvider,
+    /// The platform's identifier of the embedded media.
+    pub id: String,
+    /// The canonical URL of the media on the platform.
+    pub url: String,
+}
+
+impl Embed {
+    fn new(provider: EmbedProvider, id: &str) -> Option<Self> {
+        let valid = !id.is_empty()
+            && id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid {
+            return None;
+        }
+        let url = match provider {
+            EmbedProvider::YouTube => format!("https://www.youtube.com/watch?v={}", id),
+            EmbedProvider::Vimeo => format!("https://vimeo.com/{}", id),
+            EmbedProvider::Twitter => format!("https://x.com/i/status/{}", id),
+            EmbedProvider::Instagram => format!("https://www.instagram.com/p/{}/", id),
+            EmbedProvider::TikTok => format!("https://www.tiktok.com/@/video/{}", id),
+        };
+        Some(Embed {
+            provider,
+            id: id.to_owned(),
+            url,
+        })
+    }
+}
+
+/// Parses a possibly protocol-relative URL from an embed attribute.
+fn parse_url(src: &str) -> Option<Url> {
+    let src = src.trim();
+    if let Some(rest) = src.strip_prefix("//") {
+        Url::parse(&format!("https://{}", rest)).ok()
+    } else {
+        Url::parse(src).ok()
+    }
+}
+
+fn host_is(url: &Url, domain: &str) -> bool {
+    url.host_str().is_some_and(|host| {
+        host == domain
+            || host
+                .strip_suffix(domain)
+                .is_some_and(|sub| sub.ends_with('.'))
+    })
+}
+
+/// Recognizes an embed or media URL.
+fn classify_url(url: &Url) -> Option<Embed> {
+    let segments: Vec<&str> = url
+        .path_segments()
+        .map(|s| s.filter(|s| !s.is_empty()).collect())
+        .unwrap_or_default();
+    let query = |key: &str| {
+        url.query_pairs()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.into_owned())
+    };
+
+    if host_is(url, "youtube.com") || host_is(url, "youtube-nocookie.com") {
+        let id = match segments.as_slice() {
+            ["embed" | "shorts" | "live" | "v", id, ..] => Some((*id).to_owned()),
+            ["watch"] => query("v"),
+            _ => None,
+        };
+        return Embed::new(EmbedProvider::YouTube, &id?);
+    }
+    if host_is(url, "youtu.be") {
+        return Embed::new(EmbedProvider::YouTube, segments.first()?);
+    }
+    if host_is(url, "vimeo.com") {
+        let id = match segments.as_slice() {
+            ["video", id, ..] => id,
+            [id, ..] => id,
+            [] => return None,
+        };
+        return id
+            .chars()
+            .all(|c| c.is_ascii_digit())
+            .then(|| Embed::new(EmbedProvider::Vimeo, id))
+            .flatten();
+    }
+    if host_is(url, "twitter.com") || host_is(url, "x.com") {
+        let id = match segments.as_slice() {
+            ["embed", "Tweet.html"] => query("id"),
+            [.., "status" | "statuses", id] => Some((*id).to_owned()),
+            _ => None,
+        };
+        return Embed::new(EmbedProvider::Twitter, &id?);
+    }
+    if host_is(url, "instagram.com") {
+        return match segments.as_slice() {
+            ["p" | "reel" | "tv", id, ..] => Embed::new(EmbedProvider::Instagram, id),
+            _ => None,
+        };
+    }
+    if host_is(url, "tiktok.com") {
+        return match segments.as_slice() {
+            ["embed", "v2", id, ..] | ["embed", id, ..] | [_, "video", id, ..] => {
+                Embed::new(EmbedProvider::TikTok, id)
+            }
+            _ => None,
+        };
+    }
+    None
+}
+
+fn classify_element(element: ElementRef) -> Option<Embed> {
+    let value = element.value();
+    let url_attr = |name: &str| value.attr(name).and_then(parse_url);
+
+    match value.name() {
+        "iframe" => url_attr("src")
+            .and_then(|url| classify_url(&url))
+            .or_else(|| url_attr("data-src").and_then(|url| classify_url(&url))),
+        "lite-youtube" => Embed::new(EmbedProvider::YouTube, value.attr("videoid")?.trim()),
+        _ if value.has_class("tiktok-embed", CaseSensitivity::CaseSensitive) => {
+            match value.attr("data-video-id") {
+                Some(id) => Embed::new(EmbedProvider::TikTok, id.trim()),
+                None => url_attr("cite").and_then(|url| classify_url(&url)),
+            }
+        }
+        _ if value.has_class("instagram-media", CaseSensitivity::CaseSensitive) => {
+            url_attr("data-instgrm-permalink").and_then(|url| classify_url(&url))
+        }
+        // The status link is the last anchor of the blockquote; earlier ones are mentions.
+        _ => element
+            .select(&ANCHORS)
+            .filter_map(|a| parse_url(a.value().attr("href")?))
+            .filter_map(|url| classify_url(&url))
+            .filter(|embed| embed.provider == EmbedProvider::Twitter)
+            .last(),
+    }
+}
+
+impl Html {
+    /// Returns the recognized social and video embeds, in document order and without
+    /// duplicates.
+    ///
+    /// Iframes (including lazy `data-src` ones) are matched by URL, and the blockquotes that
+    /// Twitter/X, Instagram and TikTok embed scripts replace are matched by class.
+    ///
+    /// ```
+    /// use scraper::media::EmbedProvider;
+    /// use scraper::Html;
+    ///
+    /// let html = Html::parse_fragment(
+    ///     r#"<iframe src="https://www.youtube-nocookie.com/embed/dQw4w9WgXcQ?start=4"></iframe>"#,
+    /// );
+    /// let embed = &html.embeds()[0];
+    /// assert_eq!(EmbedProvider::YouTube, embed.provider);
+    /// assert_eq!("https://www.youtube.com/watch?v=dQw4w9WgXcQ", embed.url);
+    /// ```
+    pub fn embeds(&self) -> Vec<Embed> {
+        let mut embeds: Vec<Embed> = Vec::new();
+        for element in self.select(&EMBED_CANDIDATES) {
+            if let Some(embed) = classify_element(element) {
+                if !embeds
+                    .iter()
+                    .any(|e| e.provider == embed.provider && e.id == embed.id)
+                {
+                    embeds.push(embed);
+                }
+            }
+        }
+        embeds
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EmbedProvider;
+    use crate::html::Html;
+
+    #[test]
+    fn recognizes_providers() {
+        let html = Html::parse_fragment(
+            r#"
+            <iframe data-src="//player.vimeo.com/video/76979871?h=8272103f6e"></iframe>
+            <lite-youtube videoid="abc_DEF-123"></lite-youtube>
+            <iframe src="https://www.youtube.com/embed/abc_DEF-123"></iframe>
+            <blockquote class="twitter-tweet"><p>Hi <a href="https://twitter.com/bob">@bob</a></p>
+              <a href="https://twitter.com/alice/status/1234567890?ref_src=twsrc">May 1, 2024</a></blockquote>
+            <blockquote class="instagram-media" data-instgrm-permalink="https://www.instagram.com/reel/C1xYz/?utm_source=ig_embed"></blockquote>
+            <blockquote class="tiktok-embed" cite="https://www.tiktok.com/@user/video/7301234567890"></blockquote>
+            <iframe src="https://example.com/embed/1"></iframe>
+            "#,
+        );
+        let found: Vec<_> = html
+            .embeds()
+            .into_iter()
+            .map(|e| (e.provider, e.id))
+            .collect();
+        assert_eq!(
+            vec![
+                (EmbedProvider::Vimeo, "76979871".to_owned()),
+                (EmbedProvider::YouTube, "abc_DEF-123".to_owned()),
+                (EmbedProvider::Twitter, "1234567890".to_owned()),
+                (EmbedProvider::Instagram, "C1xYz".to_owned()),
+                (EmbedProvider::TikTok, "7301234567890".to_owned()),
+            ],
+            found
+        );
+        assert_eq!(
+            "https://www.tiktok.com/@/video/7301234567890",
+            html.embeds()[4].url
+        );
+    }
+}
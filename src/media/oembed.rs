@@ -0,0 +1,139 @@
+//! oEmbed endpoint discovery.
+
+use url::Url;
+
+use crate::html::Html;
+use crate::selector::Selector;
+use crate::url_utils::{normalize, UrlPolicy};
+
+lazy_static! {
+    static ref TYPED_LINKS: Selector = Selector::parse("link[href][type]").unwrap();
+}
+
+/// The response format an oEmbed endpoint serves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OEmbedFormat {
+    /// `application/json+oembed`.
+    Json,
+    /// `text/xml+oembed`, or the non-standard `application/xml+oembed`.
+    Xml,
+}
+
+/// An oEmbed discovery link.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OEmbedEndpoint {
+    /// The resolved endpoint URL, including the provider's `url` and `format` parameters.
+    pub url: Url,
+    /// The response format.
+    pub format: OEmbedFormat,
+    /// The `title` attribute, if any.
+    pub title: Option<String>,
+}
+
+impl Html {
+    /// Returns the oEmbed discovery links of the document, resolved against the
+    /// [base URL](Html::base_url) of `base`, in document order and without duplicates.
+    ///
+    /// ```
+    /// use scraper::media::OEmbedFormat;
+    /// use scraper::Html;
+    /// use url::Url;
+    ///
+    /// let html = Html::parse_document(r#"
+    ///     <link rel="alternate" type="application/json+oembed" href="/oembed?url=%2Fv%2F1&format=json">
+    /// "#);
+    /// let endpoints = html.oembed_endpoints(&Url::parse("https://video.example/v/1").unwrap());
+    /// assert_eq!(OEmbedFormat::Json, endpoints[0].format);
+    /// assert_eq!("https://video.example/oembed?url=%2Fv%2F1&format=json", endpoints[0].url.as_str());
+    /// ```
+    pub fn oembed_endpoints(&self, base: &Url) -> Vec<OEmbedEndpoint> {
+        self.oembed_endpoints_as(base, None)
+    }
+
+    /// Returns the oEmbed discovery links of the document, as [`Html::oembed_endpoints`]
+    /// does, with each URL normalized by `policy` before duplicates are removed.
+    pub fn oembed_endpoints_with(&self, base: &Url, policy: &UrlPolicy) -> Vec<OEmbedEndpoint> {
+        self.oembed_endpoints_as(base, Some(policy))
+    }
+
+    fn oembed_endpoints_as(&self, base: &Url, policy: Option<&UrlPolicy>) -> Vec<OEmbedEndpoint> {
+        let resolve = self.base_url(base);
+        let mut endpoints: Vec<OEmbedEndpoint> = Vec::new();
+
+        for link in self.select(&TYPED_LINKS) {
+            let value = link.value();
+            let mime = value.attr("type").unwrap_or_default().trim();
+            let format = if mime.eq_ignore_ascii_case("application/json+oembed") {
+                OEmbedFormat::Json
+            } else if mime.eq_ignore_ascii_case("text/xml+oembed")
+                || mime.eq_ignore_ascii_case("application/xml+oembed")
+            {
+                OEmbedFormat::Xml
+            } else {
+                continue;
+            };
+
+            let href = value.attr("href").unwrap_or_default().trim();
+            let Some(url) = (!href.is_empty())
+                .then(|| resolve.join(href).ok())
+                .flatten()
+            else {
+                continue;
+            };
+            let url = match policy {
+                Some(policy) => normalize(&url, policy),
+                None => url,
+            };
+            if endpoints.iter().any(|e| e.url == url) {
+                continue;
+            }
+            endpoints.push(OEmbedEndpoint {
+                url,
+                format,
+                title: value.attr("title").map(str::to_owned),
+            });
+        }
+
+        endpoints
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use url::Url;
+
+    use super::OEmbedFormat;
+    use crate::html::Html;
+
+    #[test]
+    fn finds_json_and_xml_endpoints() {
+        let html = Html::parse_document(
+            r#"
+            <link rel="alternate" type="Application/JSON+oEmbed" href="//www.youtube.com/oembed?format=json" title="A">
+            <link rel="alternate" type="text/xml+oembed" href="https://www.youtube.com/oembed?format=xml">
+            <link rel="alternate" type="text/xml+oembed" href="https://www.youtube.com/oembed?format=xml">
+            <link rel="alternate" type="application/rss+xml" href="/feed">
+            "#,
+        );
+        let base = Url::parse("https://www.youtube.com/watch?v=1").unwrap();
+        let endpoints = html.oembed_endpoints(&base);
+        let found: Vec<_> = endpoints
+            .iter()
+            .map(|e| (e.format, e.url.as_str()))
+            .collect();
+        assert_eq!(
+            vec![
+                (
+                    OEmbedFormat::Json,
+                    "https://www.youtube.com/oembed?format=json"
+                ),
+                (
+                    OEmbedFormat::Xml,
+                    "https://www.youtube.com/oembed?format=xml"
+                ),
+            ],
+            found
+        );
+        assert_eq!(Some("A"), endpoints[0].title.as_deref());
+    }
+}
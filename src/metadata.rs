@@ -0,0 +1,595 @@
+//! Page-level metadata declared in the document head.
+
+use crate::discovery::has_rel;
+use crate::html::Html;
+use crate::selector::Selector;
+
+lazy_static! {
+    static ref TITLE: Selector = Selector::parse("title").unwrap();
+    static ref H1: Selector = Selector::parse("h1").unwrap();
+    static ref META: Selector = Selector::parse("meta[content]").unwrap();
+    static ref LINK: Selector = Selector::parse("link[rel][href], a[rel][href]").unwrap();
+    static ref TIME: Selector = Selector::parse("time[datetime]").unwrap();
+}
+
+/// Metadata describing a page, gathered from `<title>`, `<meta>` and `<link>` elements.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PageMetadata {
+    /// The `<title>`, falling back to `og:title`, `twitter:title`, a JSON-LD headline and
+    /// then the first `h1`.
+    pub title: Option<String>,
+    /// The `description`, `og:description`, `twitter:description` or JSON-LD description.
+    pub description: Option<String>,
+    /// The `author`, `article:author` or `dc.creator`, falling back to a `rel="author"`
+    /// link's text.
+    pub author: Option<String>,
+    /// The publication date as written, from `article:published_time`, `date`,
+    /// `datePublished` and similar, falling back to the first `<time datetime>`.
+    pub published: Option<String>,
+    /// The unresolved `rel="canonical"` URL, falling back to `og:url` and the JSON-LD `url`.
+    pub canonical: Option<String>,
+    /// The `keywords` and `article:tag` values, without duplicates.
+    pub keywords: Vec<String>,
+    /// Where each of the single-valued fields above came from and how much to trust it.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub evidence: Vec<FieldEvidence>,
+    /// The directives of `<meta name="robots">`, lowercased and without duplicates.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub robots: Vec<String>,
+    /// The `<meta name="viewport">` content.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub viewport: Option<String>,
+    /// The OpenGraph properties.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub open_graph: OpenGraph,
+    /// The Twitter card properties.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub twitter: TwitterCard,
+}
+
+impl PageMetadata {
+    /// Returns the evidence for a field, by its name in this struct.
+    pub fn evidence(&self, field: &str) -> Option<&FieldEvidence> {
+        self.evidence.iter().find(|e| e.field == field)
+    }
+
+    /// Returns false if the robots directives include `noindex` or `none`.
+    pub fn is_indexable(&self) -> bool {
+        !self.robots.iter().any(|d| d == "noindex" || d == "none")
+    }
+
+    /// Returns false if the robots directives include `nofollow` or `none`.
+    pub fn is_followable(&self) -> bool {
+        !self.robots.iter().any(|d| d == "nofollow" || d == "none")
+    }
+}
+
+/// An image, video or audio file of an OpenGraph object, built from a `og:image`,
+/// `og:video` or `og:audio` property and the structured properties that follow it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OpenGraphMedia {
+    /// The unresolved URL.
+    pub url: String,
+    /// The `:secure_url`, an HTTPS alternative.
+    pub secure_url: Option<String>,
+    /// The `:type`, a MIME type.
+    pub mime: Option<String>,
+    /// The `:width` in pixels.
+    pub width: Option<u32>,
+    /// The `:height` in pixels.
+    pub height: Option<u32>,
+    /// The `:alt` text of an image.
+    pub alt: Option<String>,
+}
+
+/// The OpenGraph (`og:*`) properties of a page, as declared.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct OpenGraph {
+    /// The `og:title`.
+    pub title: Option<String>,
+    /// The `og:description`.
+    pub description: Option<String>,
+    /// The `og:type`, such as `article` or `website`.
+    pub kind: Option<String>,
+    /// The unresolved `og:url`.
+    pub url: Option<String>,
+    /// The `og:site_name`.
+    pub site_name: Option<String>,
+    /// The `og:locale`.
+    pub locale: Option<String>,
+    /// The `og:locale:alternate` values.
+    pub alternate_locales: Vec<String>,
+    /// The images, in declaration order.
+    pub images: Vec<OpenGraphMedia>,
+    /// The videos, in declaration order.
+    pub videos: Vec<OpenGraphMedia>,
+    /// The audio files, in declaration order.
+    pub audio: Vec<OpenGraphMedia>,
+}
+
+/// The Twitter card (`twitter:*`) properties of a page.
+///
+/// Title, description and image fall back to their OpenGraph equivalents, as when the
+/// card is rendered.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct TwitterCard {
+    /// The `twitter:card` type, such as `summary` or `summary_large_image`.
+    pub card: Option<String>,
+    /// The `twitter:site` handle of the website.
+    pub site: Option<String>,
+    /// The `twitter:creator` handle of the author.
+    pub creator: Option<String>,
+    /// The `twitter:title`, else `og:title`.
+    pub title: Option<String>,
+    /// The `twitter:description`, else `og:description`.
+    pub description: Option<String>,
+    /// The unresolved `twitter:image`, else the first `og:image`.
+    pub image: Option<String>,
+    /// The `twitter:image:alt`, else the `alt` of the first `og:image`.
+    pub image_alt: Option<String>,
+}
+
+/// The kind of markup a metadata value was read from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum MetadataSource {
+    /// A JSON-LD `Article` item (with the `serde_json` feature).
+    JsonLd,
+    /// OpenGraph `og:*` and `article:*` properties.
+    OpenGraph,
+    /// Standard `<meta>` names, `<title>` and `<link rel>`.
+    Meta,
+    /// Twitter card `twitter:*` properties.
+    Twitter,
+    /// A guess from page content, such as the first `h1`.
+    Dom,
+}
+
+impl MetadataSource {
+    /// The confidence of a value from this source when nothing else corroborates it.
+    fn base_confidence(self) -> f64 {
+        match self {
+            MetadataSource::JsonLd => 0.9,
+            MetadataSource::OpenGraph | MetadataSource::Meta => 0.8,
+            MetadataSource::Twitter => 0.7,
+            MetadataSource::Dom => 0.5,
+        }
+    }
+}
+
+/// The provenance and confidence of one metadata field.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FieldEvidence {
+    /// The field name, e.g. `"title"`.
+    pub field: String,
+    /// The source the value was taken from.
+    pub source: MetadataSource,
+    /// How much to trust the value, from 0 to 1.
+    ///
+    /// It starts from a per-source base (JSON-LD above OpenGraph and `<meta>`, DOM
+    /// heuristics lowest), moves halfway to 1 for every other source giving the same value,
+    /// and drops by a fifth for every source that disagrees.
+    pub confidence: f64,
+    /// The other sources that gave the same value.
+    pub agreeing: Vec<MetadataSource>,
+    /// The other sources that gave a different value.
+    pub conflicting: Vec<MetadataSource>,
+}
+
+/// Collapses whitespace, returning `None` for blank text.
+fn clean(text: &str) -> Option<String> {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    (!text.is_empty()).then_some(text)
+}
+
+/// Picks the first candidate and scores it against the others.
+fn decide(
+    field: &str,
+    candidates: Vec<(MetadataSource, String)>,
+    evidence: &mut Vec<FieldEvidence>,
+) -> Option<String> {
+    let mut candidates = candidates.into_iter();
+    let (source, value) = candidates.next()?;
+    let mut found = FieldEvidence {
+        field: field.to_owned(),
+        source,
+        confidence: source.base_confidence(),
+        agreeing: Vec::new(),
+        conflicting: Vec::new(),
+    };
+    for (other, candidate) in candidates {
+        if candidate.to_lowercase() == value.to_lowercase() {
+            found.confidence += (1.0 - found.confidence) / 2.0;
+            found.agreeing.push(other);
+        } else {
+            found.confidence *= 0.8;
+            found.conflicting.push(other);
+        }
+    }
+    evidence.push(found);
+    Some(value)
+}
+
+/// Candidates from the first JSON-LD article, as `(field, value)` pairs.
+#[cfg(feature = "serde_json")]
+fn json_ld(html: &Html) -> Vec<(&'static str, String)> {
+    use crate::structured_data::schema::Article;
+
+    let Some(article) = html.structured_data().first_as::<Article>() else {
+        return Vec::new();
+    };
+    let author = article
+        .author
+        .iter()
+        .find_map(|a| a.name().map(str::to_owned));
+    [
+        ("title", article.headline.or(article.name)),
+        ("description", article.description),
+        ("author", author),
+        ("published", article.date_published),
+        ("canonical", article.url),
+    ]
+    .into_iter()
+    .filter_map(|(field, value)| Some((field, clean(&value?)?)))
+    .collect()
+}
+
+#[cfg(not(feature = "serde_json"))]
+fn json_ld(_: &Html) -> Vec<(&'static str, String)> {
+    Vec::new()
+}
+
+/// Collects the OpenGraph properties from `(key, content)` pairs in document order.
+fn open_graph(meta: &[(String, &str)]) -> OpenGraph {
+    let mut graph = OpenGraph::default();
+    for (key, content) in meta {
+        let Some(property) = key.strip_prefix("og:") else {
+            continue;
+        };
+        let Some(value) = clean(content) else {
+            continue;
+        };
+        let (kind, field) = property.split_once(':').unwrap_or((property, "url"));
+        let media = match kind {
+            "image" => &mut graph.images,
+            "video" => &mut graph.videos,
+            "audio" => &mut graph.audio,
+            _ => {
+                match property {
+                    "title" => graph.title.get_or_insert(value),
+                    "description" => graph.description.get_or_insert(value),
+                    "type" => graph.kind.get_or_insert(value),
+                    "url" => graph.url.get_or_insert(value),
+                    "site_name" => graph.site_name.get_or_insert(value),
+                    "locale" => graph.locale.get_or_insert(value),
+                    "locale:alternate" => {
+                        graph.alternate_locales.push(value);
+                        continue;
+                    }
+                    _ => continue,
+                };
+                continue;
+            }
+        };
+        // A URL starts a new item; the other properties describe the latest one.
+        if field == "url" {
+            media.push(OpenGraphMedia {
+                url: value,
+                ..Default::default()
+            });
+            continue;
+        }
+        let Some(item) = media.last_mut() else {
+            continue;
+        };
+        match field {
+            "secure_url" => item.secure_url = Some(value),
+            "type" => item.mime = Some(value),
+            "width" => item.width = value.parse().ok(),
+            "height" => item.height = value.parse().ok(),
+            "alt" => item.alt = Some(value),
+            _ => {}
+        }
+    }
+    graph
+}
+
+/// Collects the Twitter card properties, falling back to OpenGraph.
+fn twitter_card(meta: &[(String, &str)], graph: &OpenGraph) -> TwitterCard {
+    let get = |name: &str| {
+        meta.iter()
+            .filter(|(key, _)| key.strip_prefix("twitter:") == Some(name))
+            .find_map(|(_, content)| clean(content))
+    };
+    let image = graph.images.first();
+    TwitterCard {
+        card: get("card"),
+        site: get("site"),
+        creator: get("creator"),
+        title: get("title").or_else(|| graph.title.clone()),
+        description: get("description").or_else(|| graph.description.clone()),
+        image: get("image")
+            .or_else(|| get("image:src"))
+            .or_else(|| image.map(|i| i.url.clone())),
+        image_alt: get("image:alt").or_else(|| image?.alt.clone()),
+    }
+}
+
+impl Html {
+    /// Returns the page metadata declared in the document.
+    ///
+    /// Every field is taken from the first source that has it, in the order given on the
+    /// field, and [`PageMetadata::evidence`] records that source together with a confidence
+    /// score based on whether the other sources agree.
+    ///
+    /// ```
+    /// use scraper::metadata::MetadataSource;
+    /// use scraper::Html;
+    ///
+    /// let html = Html::parse_document(r#"
+    ///     <title> Release notes </title>
+    ///     <meta property="og:title" content="Release notes">
+    ///     <meta name="author" content="Jane Doe">
+    ///     <meta name="keywords" content="rust, html, rust">
+    ///     <link rel="canonical" href="https://example.com/notes">
+    ///     <h1>Version 2</h1>
+    /// "#);
+    /// let metadata = html.metadata();
+    /// assert_eq!(Some("Release notes"), metadata.title.as_deref());
+    /// assert_eq!(Some("Jane Doe"), metadata.author.as_deref());
+    /// assert_eq!(vec!["rust", "html"], metadata.keywords);
+    ///
+    /// let title = metadata.evidence("title").unwrap();
+    /// assert_eq!(MetadataSource::Meta, title.source);
+    /// assert_eq!(vec![MetadataSource::OpenGraph], title.agreeing);
+    /// assert_eq!(vec![MetadataSource::Dom], title.conflicting);
+    /// ```
+    pub fn metadata(&self) -> PageMetadata {
+        use MetadataSource::{Dom, JsonLd, Meta, OpenGraph, Twitter};
+
+        let meta: Vec<(String, &str)> = self
+            .select(&META)
+            .filter_map(|element| {
+                let value = element.value();
+                let key = value
+                    .attr("name")
+                    .or_else(|| value.attr("property"))
+                    .or_else(|| value.attr("itemprop"))?;
+                Some((key.trim().to_ascii_lowercase(), value.attr("content")?))
+            })
+            .collect();
+        let structured = json_ld(self);
+
+        // Builds the candidates of a field from `(source, key)` pairs, where the key names a
+        // `<meta>` element or, for JSON-LD, the field itself.
+        let candidates = |field: &str, keys: &[(MetadataSource, &str)]| {
+            keys.iter()
+                .filter_map(|&(source, key)| {
+                    let value = match source {
+                        JsonLd => structured
+                            .iter()
+                            .find(|(f, _)| *f == field)
+                            .map(|(_, v)| v.clone()),
+                        _ => meta
+                            .iter()
+                            .filter(|(k, _)| k == key)
+                            .find_map(|(_, content)| clean(content)),
+                    };
+                    Some((source, value?))
+                })
+                .collect::<Vec<_>>()
+        };
+        let rel = |token: &str| {
+            self.select(&LINK)
+                .find(|e| e.value().attr("rel").is_some_and(|rel| has_rel(rel, token)))
+        };
+        let text_of = |selector: &Selector| {
+            let element = self.select(selector).next()?;
+            clean(&element.text().collect::<String>())
+        };
+
+        let mut title = candidates(
+            "title",
+            &[
+                (OpenGraph, "og:title"),
+                (Twitter, "twitter:title"),
+                (JsonLd, ""),
+            ],
+        );
+        if let Some(text) = text_of(&TITLE) {
+            title.insert(0, (Meta, text));
+        }
+        title.extend(text_of(&H1).map(|text| (Dom, text)));
+
+        let description = candidates(
+            "description",
+            &[
+                (Meta, "description"),
+                (OpenGraph, "og:description"),
+                (Twitter, "twitter:description"),
+                (JsonLd, ""),
+            ],
+        );
+
+        let mut author = candidates(
+            "author",
+            &[
+                (Meta, "author"),
+                (OpenGraph, "article:author"),
+                (Meta, "dc.creator"),
+                (JsonLd, ""),
+            ],
+        );
+        author.extend(
+            rel("author")
+                .and_then(|link| clean(&link.text().collect::<String>()))
+                .map(|text| (Dom, text)),
+        );
+
+        let mut published = candidates(
+            "published",
+            &[
+                (OpenGraph, "article:published_time"),
+                (Meta, "datepublished"),
+                (Meta, "date"),
+                (Meta, "dc.date"),
+                (Meta, "pubdate"),
+                (JsonLd, ""),
+            ],
+        );
+        published.extend(
+            self.select(&TIME)
+                .next()
+                .and_then(|time| clean(time.value().attr("datetime")?))
+                .map(|datetime| (Dom, datetime)),
+        );
+
+        let mut canonical = candidates("canonical", &[(OpenGraph, "og:url"), (JsonLd, "")]);
+        if let Some(href) = rel("canonical").and_then(|link| clean(link.value().attr("href")?)) {
+            canonical.insert(0, (Meta, href));
+        }
+
+        let mut keywords: Vec<String> = Vec::new();
+        let declared = meta.iter().flat_map(|(key, content)| match key.as_str() {
+            "keywords" => content.split(',').collect(),
+            "article:tag" => vec![*content],
+            _ => Vec::new(),
+        });
+        for keyword in declared.filter_map(clean) {
+            if !keywords.contains(&keyword) {
+                keywords.push(keyword);
+            }
+        }
+
+        let mut robots: Vec<String> = Vec::new();
+        let directives = meta
+            .iter()
+            .filter(|(key, _)| key == "robots")
+            .flat_map(|(_, content)| content.split(','));
+        for directive in directives {
+            let directive = directive.trim().to_ascii_lowercase();
+            if !directive.is_empty() && !robots.contains(&directive) {
+                robots.push(directive);
+            }
+        }
+        let viewport = meta
+            .iter()
+            .filter(|(key, _)| key == "viewport")
+            .find_map(|(_, content)| clean(content));
+        let open_graph = open_graph(&meta);
+        let twitter = twitter_card(&meta, &open_graph);
+
+        let mut evidence = Vec::new();
+        PageMetadata {
+            title: decide("title", title, &mut evidence),
+            description: decide("description", description, &mut evidence),
+            author: decide("author", author, &mut evidence),
+            published: decide("published", published, &mut evidence),
+            canonical: decide("canonical", canonical, &mut evidence),
+            keywords,
+            evidence,
+            robots,
+            viewport,
+            open_graph,
+            twitter,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MetadataSource;
+    use crate::html::Html;
+
+    #[test]
+    fn falls_back_through_sources() {
+        let html = Html::parse_document(
+            r#"<meta property="og:title" content="OG title">
+            <meta property="og:url" content="https://example.com/a">
+            <meta property="article:tag" content="News"><meta property="article:tag" content="Rust">
+            <article><h1>Heading</h1><a rel="author" href="/jane">Jane</a>
+            <time datetime="2024-05-01">May 1</time></article>"#,
+        );
+        let metadata = html.metadata();
+        assert_eq!(Some("OG title"), metadata.title.as_deref());
+        assert_eq!(Some("Jane"), metadata.author.as_deref());
+        assert_eq!(Some("2024-05-01"), metadata.published.as_deref());
+        assert_eq!(Some("https://example.com/a"), metadata.canonical.as_deref());
+        assert_eq!(vec!["News", "Rust"], metadata.keywords);
+        assert_eq!(None, metadata.description);
+
+        let author = metadata.evidence("author").unwrap();
+        assert_eq!(MetadataSource::Dom, author.source);
+        assert_eq!(0.5, author.confidence);
+        assert!(metadata.evidence("description").is_none());
+    }
+
+    #[test]
+    fn open_graph_and_twitter_card() {
+        let html = Html::parse_document(
+            r#"<meta name="robots" content="NOINDEX, follow, noindex">
+            <meta name="viewport" content="width=device-width">
+            <meta property="og:title" content="Launch">
+            <meta property="og:image" content="/a.png"><meta property="og:image:width" content="1200">
+            <meta property="og:image:alt" content="Rocket">
+            <meta property="og:image:url" content="/b.png"><meta property="og:image:height" content="x">
+            <meta property="og:locale:alternate" content="fr_FR">
+            <meta name="twitter:card" content="summary_large_image">
+            <meta name="twitter:title" content="Launch day">"#,
+        );
+        let metadata = html.metadata();
+        assert_eq!(vec!["noindex", "follow"], metadata.robots);
+        assert!(!metadata.is_indexable() && metadata.is_followable());
+        assert_eq!(Some("width=device-width"), metadata.viewport.as_deref());
+
+        let images = &metadata.open_graph.images;
+        assert_eq!(2, images.len());
+        assert_eq!(
+            (Some(1200), Some("Rocket")),
+            (images[0].width, images[0].alt.as_deref())
+        );
+        assert_eq!(("/b.png", None), (images[1].url.as_str(), images[1].height));
+        assert_eq!(vec!["fr_FR"], metadata.open_graph.alternate_locales);
+
+        let twitter = &metadata.twitter;
+        assert_eq!(Some("Launch day"), twitter.title.as_deref());
+        assert_eq!(Some("/a.png"), twitter.image.as_deref());
+        assert_eq!(Some("Rocket"), twitter.image_alt.as_deref());
+        assert_eq!(
+            vec![MetadataSource::Twitter],
+            metadata.evidence("title").unwrap().conflicting
+        );
+    }
+
+    #[cfg(feature = "serde_json")]
+    #[test]
+    fn json_ld_corroborates_meta() {
+        let html = Html::parse_document(
+            r#"<meta name="author" content="Ada">
+            <script type="application/ld+json">
+            {"@type": "NewsArticle", "headline": "Launch", "author": {"@type": "Person", "name": "ada"}}
+            </script>"#,
+        );
+        let metadata = html.metadata();
+        assert_eq!(Some("Launch"), metadata.title.as_deref());
+        assert_eq!(
+            MetadataSource::JsonLd,
+            metadata.evidence("title").unwrap().source
+        );
+
+        let author = metadata.evidence("author").unwrap();
+        assert_eq!(vec![MetadataSource::JsonLd], author.agreeing);
+        assert!((author.confidence - 0.9).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,388 @@
+//! Loading of MHTML archives, the `multipart/related` files browsers write when saving a
+//! page as a single file.
+
+use std::collections::HashMap;
+
+use encoding_rs::Encoding;
+
+use crate::error::MhtmlError;
+use crate::html::encoding::parse_bytes;
+use crate::html::{Html, ParseOptions};
+
+/// A file bundled in an MHTML archive, such as an image or a stylesheet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MhtmlResource {
+    /// The MIME type, lowercased and without parameters.
+    pub content_type: String,
+    /// The `Content-ID`, without its angle brackets, which `cid:` URLs refer to.
+    pub content_id: Option<String>,
+    /// The decoded content.
+    pub data: Vec<u8>,
+}
+
+/// A parsed MHTML archive.
+#[derive(Debug, Clone)]
+pub struct MhtmlArchive {
+    /// The saved page.
+    pub document: Html,
+    /// The URL the page was saved from, if the archive records it.
+    pub location: Option<String>,
+    /// The other parts, keyed by their `Content-Location`, or by `cid:` and their
+    /// `Content-ID` when they have no location.
+    pub resources: HashMap<String, MhtmlResource>,
+}
+
+/// The headers of a MIME entity, with folded lines joined and names lowercased.
+struct Headers(Vec<(String, String)>);
+
+impl Headers {
+    fn parse(head: &[u8]) -> Self {
+        let head = String::from_utf8_lossy(head);
+        let mut headers: Vec<(String, String)> = Vec::new();
+        for line in head.lines() {
+            if line.starts_with([' ', '\t']) {
+                if let Some((_, value)) = headers.last_mut() {
+                    value.push(' ');
+                    value.push_str(line.trim());
+                }
+            } else if let Some((name, value)) = line.split_once(':') {
+                headers.push((name.trim().to_ascii_lowercase(), value.trim().to_owned()));
+            }
+        }
+        Headers(headers)
+    }
+
+    fn get(&self, name: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Returns the MIME type of `Content-Type`, lowercased, and its parameters.
+    fn content_type(&self) -> (String, Vec<(String, String)>) {
+        let value = self.get("content-type").unwrap_or("text/plain");
+        let mut fields = split_params(value).into_iter();
+        let mime = fields
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase();
+        let params = fields
+            .filter_map(|field| {
+                let (name, value) = field.split_once('=')?;
+                let value = value.trim();
+                let value = value
+                    .strip_prefix('"')
+                    .and_then(|v| v.strip_suffix('"'))
+                    .unwrap_or(value);
+                Some((name.trim().to_ascii_lowercase(), value.to_owned()))
+            })
+            .collect();
+        (mime, params)
+    }
+}
+
+/// Splits a header value at the semicolons outside of quoted strings.
+fn split_params(value: &str) -> Vec<&str> {
+    let mut fields = Vec::new();
+    let (mut start, mut quoted) = (0, false);
+    for (i, c) in value.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            ';' if !quoted => {
+                fields.push(&value[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    fields.push(&value[start..]);
+    fields
+}
+
+fn param<'a>(params: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    params
+        .iter()
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value.as_str())
+}
+
+/// Splits an entity into its header block and its body at the first empty line.
+fn split_head(bytes: &[u8]) -> (&[u8], &[u8]) {
+    let mut start = 0;
+    while let Some(end) = bytes[start..].iter().position(|b| *b == b'\n') {
+        let line = &bytes[start..start + end];
+        if line.is_empty() || line == b"\r" {
+            return (&bytes[..start], &bytes[start + end + 1..]);
+        }
+        start += end + 1;
+    }
+    (bytes, &[])
+}
+
+/// Splits a multipart body into its parts. The line break before a delimiter belongs to
+/// the delimiter.
+fn split_parts<'a>(body: &'a [u8], boundary: &str) -> Vec<&'a [u8]> {
+    let delimiter = format!("--{}", boundary);
+    let delimiter = delimiter.as_bytes();
+    let mut parts = Vec::new();
+    let mut part_start: Option<usize> = None;
+    let mut line_start = 0;
+    while line_start < body.len() {
+        let line_end = body[line_start..]
+            .iter()
+            .position(|b| *b == b'\n')
+            .map_or(body.len(), |i| line_start + i + 1);
+        let line = &body[line_start..line_end];
+        if let Some(rest) = line.strip_prefix(delimiter) {
+            if let Some(start) = part_start {
+                let mut end = line_start;
+                if body[..end].ends_with(b"\n") {
+                    end -= 1;
+                }
+                if body[..end].ends_with(b"\r") {
+                    end -= 1;
+                }
+                parts.push(&body[start..end.max(start)]);
+            }
+            if rest.starts_with(b"--") {
+                return parts;
+            }
+            part_start = Some(line_end);
+        }
+        line_start = line_end;
+    }
+    if let Some(start) = part_start {
+        parts.push(&body[start..]);
+    }
+    parts
+}
+
+fn decode_base64(data: &[u8]) -> Result<Vec<u8>, MhtmlError> {
+    let mut out = Vec::with_capacity(data.len() / 4 * 3);
+    let (mut buffer, mut bits) = (0u32, 0u32);
+    for &byte in data {
+        let value = match byte {
+            b'A'..=b'Z' => byte - b'A',
+            b'a'..=b'z' => byte - b'a' + 26,
+            b'0'..=b'9' => byte - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            b'=' => break,
+            byte if byte.is_ascii_whitespace() => continue,
+            _ => return Err(MhtmlError::Decode("invalid base64 content".to_owned())),
+        };
+        buffer = (buffer << 6) | u32::from(value);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+        }
+    }
+    Ok(out)
+}
+
+fn decode_quoted_printable(data: &[u8]) -> Vec<u8> {
+    let hex = |byte: u8| (byte as char).to_digit(16);
+    let mut out = Vec::with_capacity(data.len());
+    let mut i = 0;
+    while i < data.len() {
+        if data[i] == b'=' {
+            match &data[i + 1..] {
+                [b'\r', b'\n', ..] => {
+                    i += 3;
+                    continue;
+                }
+                [b'\n', ..] => {
+                    i += 2;
+                    continue;
+                }
+                &[a, b, ..] => {
+                    if let (Some(a), Some(b)) = (hex(a), hex(b)) {
+                        out.push((a * 16 + b) as u8);
+                        i += 3;
+                        continue;
+                    }
+                }
+                _ => {}
+            }
+        }
+        out.push(data[i]);
+        i += 1;
+    }
+    out
+}
+
+/// A decoded part of the archive.
+struct Part {
+    mime: String,
+    charset: Option<String>,
+    location: Option<String>,
+    content_id: Option<String>,
+    data: Vec<u8>,
+}
+
+impl Part {
+    fn parse(bytes: &[u8]) -> Result<Self, MhtmlError> {
+        let (head, body) = split_head(bytes);
+        let headers = Headers::parse(head);
+        let (mime, params) = headers.content_type();
+        let encoding = headers
+            .get("content-transfer-encoding")
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        let data = match encoding.as_str() {
+            "base64" => decode_base64(body)?,
+            "quoted-printable" => decode_quoted_printable(body),
+            _ => body.to_vec(),
+        };
+        Ok(Part {
+            mime,
+            charset: param(&params, "charset").map(str::to_owned),
+            location: headers.get("content-location").map(str::to_owned),
+            content_id: headers
+                .get("content-id")
+                .map(|id| id.trim_start_matches('<').trim_end_matches('>').to_owned()),
+            data,
+        })
+    }
+
+    fn html(&self) -> Html {
+        let declared = self
+            .charset
+            .as_deref()
+            .and_then(|c| Encoding::for_label(c.as_bytes()));
+        parse_bytes(&self.data, declared, &ParseOptions::default())
+    }
+}
+
+impl MhtmlArchive {
+    /// Parses an MHTML archive.
+    ///
+    /// The page is the part named by the `start` parameter of the archive, else its first
+    /// HTML part. Parts are decoded from base64 or quoted-printable, and the page from the
+    /// `charset` of its part, else as in [`Html::parse_document_bytes`]. An archive that is a
+    /// single HTML entity rather than a multipart one is accepted too.
+    ///
+    /// ```
+    /// use scraper::mhtml::MhtmlArchive;
+    /// use scraper::Selector;
+    ///
+    /// let archive = b"From: <Saved by Blink>\r
+    /// Snapshot-Content-Location: https://example.com/\r
+    /// MIME-Version: 1.0\r
+    /// Content-Type: multipart/related;\r
+    /// \ttype=\"text/html\";\r
+    /// \tboundary=\"----=_Part\"\r
+    /// \r
+    /// ------=_Part\r
+    /// Content-Type: text/html; charset=utf-8\r
+    /// Content-Transfer-Encoding: quoted-printable\r
+    /// Content-Location: https://example.com/\r
+    /// \r
+    /// <img src=3D\"https://example.com/logo.png\"><p>Caf=C3=A9</p>\r
+    /// ------=_Part\r
+    /// Content-Type: image/png\r
+    /// Content-Transfer-Encoding: base64\r
+    /// Content-Location: https://example.com/logo.png\r
+    /// \r
+    /// iVBORw0KGgo=\r
+    /// ------=_Part--\r
+    /// ";
+    /// let archive = MhtmlArchive::parse(archive).unwrap();
+    /// let p = archive.document.select_first(&Selector::parse("p").unwrap()).unwrap();
+    /// assert_eq!("Café", p.inner_html());
+    /// let logo = archive.resource("https://example.com/logo.png").unwrap();
+    /// assert_eq!(b"\x89PNG\r\n\x1a\n", &logo.data[..]);
+    /// ```
+    pub fn parse(bytes: &[u8]) -> Result<Self, MhtmlError> {
+        let (head, body) = split_head(bytes);
+        let headers = Headers::parse(head);
+        let (mime, params) = headers.content_type();
+        let location = headers.get("snapshot-content-location").map(str::to_owned);
+
+        let mut parts = if mime.starts_with("multipart/") {
+            let boundary = param(&params, "boundary").ok_or(MhtmlError::MissingBoundary)?;
+            split_parts(body, boundary)
+                .into_iter()
+                .map(Part::parse)
+                .collect::<Result<Vec<_>, _>>()?
+        } else {
+            vec![Part::parse(bytes)?]
+        };
+
+        let start =
+            param(&params, "start").map(|s| s.trim_start_matches('<').trim_end_matches('>'));
+        let main = start
+            .and_then(|start| {
+                parts
+                    .iter()
+                    .position(|p| p.content_id.as_deref() == Some(start))
+            })
+            .or_else(|| {
+                parts
+                    .iter()
+                    .position(|p| matches!(p.mime.as_str(), "text/html" | "application/xhtml+xml"))
+            })
+            .ok_or(MhtmlError::NoDocument)?;
+        let main = parts.remove(main);
+
+        let resources = parts
+            .into_iter()
+            .filter_map(|part| {
+                let key = part
+                    .location
+                    .or_else(|| Some(format!("cid:{}", part.content_id.as_deref()?)))?;
+                let resource = MhtmlResource {
+                    content_type: part.mime,
+                    content_id: part.content_id,
+                    data: part.data,
+                };
+                Some((key, resource))
+            })
+            .collect();
+
+        Ok(MhtmlArchive {
+            document: main.html(),
+            location: location.or(main.location),
+            resources,
+        })
+    }
+
+    /// Returns the bundled resource a URL of the page refers to, by `Content-Location` or,
+    /// for `cid:` URLs, by `Content-ID`.
+    pub fn resource(&self, url: &str) -> Option<&MhtmlResource> {
+        self.resources.get(url).or_else(|| {
+            let id = url.strip_prefix("cid:")?;
+            self.resources
+                .values()
+                .find(|resource| resource.content_id.as_deref() == Some(id))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_base64, decode_quoted_printable, MhtmlArchive};
+    use crate::error::MhtmlError;
+
+    #[test]
+    fn transfer_encodings() {
+        assert_eq!(
+            b"hello world".to_vec(),
+            decode_base64(b"aGVsbG8g\r\nd29ybGQ=").unwrap()
+        );
+        assert_eq!(
+            b"a=b c\xE9".to_vec(),
+            decode_quoted_printable(b"a=3Db =\r\nc=E9")
+        );
+
+        let no_page = b"Content-Type: multipart/related; boundary=b\n\n--b\n\
+                        Content-Type: image/gif\nContent-ID: <img1>\n\nGIF\n--b--\n";
+        assert_eq!(
+            Some(MhtmlError::NoDocument),
+            MhtmlArchive::parse(no_page).err()
+        );
+    }
+}
@@ -0,0 +1,366 @@
+//! Microdata and RDFa Lite items.
+//!
+//! [`Html::microdata`] walks the `itemscope`, `itemtype` and `itemprop` attributes of HTML
+//! microdata, and the `vocab`, `typeof` and `property` attributes of RDFa Lite, into nested
+//! [`Item`]s. Names and URLs are kept as written, without resolving them.
+//!
+//! ```
+//! use scraper::microdata::PropertyValue;
+//! use scraper::Html;
+//!
+//! let html = Html::parse_document(r#"
+//!     <div itemscope itemtype="https://schema.org/Recipe">
+//!       <h1 itemprop="name">Pancakes</h1>
+//!       <img itemprop="image" src="/pancakes.jpg">
+//!       <div itemprop="author" itemscope itemtype="https://schema.org/Person">
+//!         By <span itemprop="name">Ada</span>
+//!       </div>
+//!     </div>
+//! "#);
+//!
+//! let recipe = &html.microdata()[0];
+//! assert!(recipe.is_type("Recipe"));
+//! assert_eq!(Some("Pancakes"), recipe.text("name"));
+//! assert_eq!(Some(&PropertyValue::Url("/pancakes.jpg".into())), recipe.get("image"));
+//! assert_eq!(Some("Ada"), recipe.item("author").and_then(|author| author.text("name")));
+//! ```
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use ego_tree::NodeId;
+
+use crate::element_ref::ElementRef;
+use crate::html::Html;
+use crate::selector::Selector;
+
+lazy_static! {
+    static ref ROOTS: Selector =
+        Selector::parse("[itemscope]:not([itemprop]), [typeof]:not([property])").unwrap();
+    static ref WITH_ID: Selector = Selector::parse("[id]").unwrap();
+}
+
+/// Items nested deeper than this through `itemref` chains are cut off.
+const MAX_DEPTH: usize = 32;
+
+/// The markup an item was declared with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Syntax {
+    /// HTML microdata: `itemscope`, `itemtype`, `itemid`, `itemprop` and `itemref`.
+    Microdata,
+    /// RDFa Lite: `vocab`, `typeof`, `resource` and `property`.
+    Rdfa,
+}
+
+/// The value of a property.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PropertyValue {
+    /// A text value, from a `content`, `value` or `datetime` attribute or the element text.
+    Text(String),
+    /// An unresolved URL from the `href`, `src`, `data` or `resource` attribute.
+    Url(String),
+    /// A nested item.
+    Item(Item),
+}
+
+impl PropertyValue {
+    /// Returns the text or URL, or `None` for an item.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            PropertyValue::Text(value) | PropertyValue::Url(value) => Some(value),
+            PropertyValue::Item(_) => None,
+        }
+    }
+
+    /// Returns the nested item.
+    pub fn as_item(&self) -> Option<&Item> {
+        match self {
+            PropertyValue::Item(item) => Some(item),
+            _ => None,
+        }
+    }
+}
+
+/// A microdata or RDFa item.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Item {
+    /// The markup the item was declared with.
+    pub syntax: Syntax,
+    /// The types, such as `https://schema.org/Product`. RDFa terms are prefixed with the
+    /// `vocab` in scope.
+    pub types: Vec<String>,
+    /// The global identifier from `itemid` or `resource`.
+    pub id: Option<String>,
+    /// The values of each property name, in document order.
+    pub properties: BTreeMap<String, Vec<PropertyValue>>,
+}
+
+impl Item {
+    /// Returns true if one of the types is `ty`, compared with or without the vocabulary,
+    /// so `Product` matches `https://schema.org/Product` and `schema:Product`.
+    pub fn is_type(&self, ty: &str) -> bool {
+        self.types
+            .iter()
+            .any(|t| t == ty || t.rsplit_once(['/', '#', ':']).map(|(_, local)| local) == Some(ty))
+    }
+
+    /// Returns the first value of a property.
+    pub fn get(&self, name: &str) -> Option<&PropertyValue> {
+        self.all(name).first()
+    }
+
+    /// Returns every value of a property.
+    pub fn all(&self, name: &str) -> &[PropertyValue] {
+        self.properties.get(name).map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns the first text or URL value of a property.
+    pub fn text(&self, name: &str) -> Option<&str> {
+        self.all(name).iter().find_map(PropertyValue::as_str)
+    }
+
+    /// Returns the first item value of a property.
+    pub fn item(&self, name: &str) -> Option<&Item> {
+        self.all(name).iter().find_map(PropertyValue::as_item)
+    }
+}
+
+/// Builds the items of one document, looking up `itemref` targets by ID.
+struct Crawler<'a> {
+    ids: HashMap<&'a str, ElementRef<'a>>,
+}
+
+impl<'a> Crawler<'a> {
+    fn new(html: &'a Html) -> Self {
+        let mut ids = HashMap::new();
+        for element in html.select(&WITH_ID) {
+            if let Some(id) = element.value().id() {
+                ids.entry(id).or_insert(element);
+            }
+        }
+        Crawler { ids }
+    }
+
+    /// Returns the item of `root`. `memory` holds the items being crawled, whose elements
+    /// are left out as property values so that `itemref` cycles end.
+    fn item(&self, root: ElementRef<'a>, syntax: Syntax, memory: &mut Vec<NodeId>) -> Item {
+        let value = root.value();
+        let (types, id) = match syntax {
+            Syntax::Microdata => (
+                value
+                    .attr("itemtype")
+                    .map(|types| types.split_whitespace().map(str::to_owned).collect())
+                    .unwrap_or_default(),
+                value.attr("itemid").map(|id| id.trim().to_owned()),
+            ),
+            Syntax::Rdfa => {
+                let vocab = [root]
+                    .into_iter()
+                    .chain(root.ancestors().filter_map(ElementRef::wrap))
+                    .find_map(|e| e.value().attr("vocab"))
+                    .map(str::trim);
+                let types = value.attr("typeof").unwrap_or_default();
+                let types = types
+                    .split_whitespace()
+                    .map(|term| match vocab {
+                        Some(vocab) if !term.contains(':') => format!("{}{}", vocab, term),
+                        _ => term.to_owned(),
+                    })
+                    .collect();
+                (types, value.attr("resource").map(|id| id.trim().to_owned()))
+            }
+        };
+        let mut item = Item {
+            syntax,
+            types,
+            id,
+            properties: BTreeMap::new(),
+        };
+        if memory.len() >= MAX_DEPTH {
+            return item;
+        }
+
+        memory.push(root.id());
+        // Each element is crawled once per item, however many `itemref`s lead to it.
+        let mut seen = HashSet::from([root.id()]);
+        self.children(root, &mut item, &mut seen, memory);
+        if syntax == Syntax::Microdata {
+            let refs = value.attr("itemref").unwrap_or_default();
+            for target in refs.split_whitespace().filter_map(|id| self.ids.get(id)) {
+                self.visit(*target, &mut item, &mut seen, memory);
+            }
+        }
+        memory.pop();
+        item
+    }
+
+    fn children(
+        &self,
+        parent: ElementRef<'a>,
+        item: &mut Item,
+        seen: &mut HashSet<NodeId>,
+        memory: &mut Vec<NodeId>,
+    ) {
+        for child in parent.children().filter_map(ElementRef::wrap) {
+            self.visit(child, item, seen, memory);
+        }
+    }
+
+    /// Adds the properties of `element` to `item`, descending unless it starts an item.
+    fn visit(
+        &self,
+        element: ElementRef<'a>,
+        item: &mut Item,
+        seen: &mut HashSet<NodeId>,
+        memory: &mut Vec<NodeId>,
+    ) {
+        if !seen.insert(element.id()) {
+            return;
+        }
+        let value = element.value();
+        let (names, scope) = match item.syntax {
+            Syntax::Microdata => (value.attr("itemprop"), value.attr("itemscope").is_some()),
+            Syntax::Rdfa => (value.attr("property"), value.attr("typeof").is_some()),
+        };
+        let names = names.filter(|names| !names.trim().is_empty());
+        if let Some(names) = names.filter(|_| !memory.contains(&element.id())) {
+            let property = if scope {
+                PropertyValue::Item(self.item(element, item.syntax, memory))
+            } else {
+                property_value(element, item.syntax)
+            };
+            for name in names.split_whitespace() {
+                item.properties
+                    .entry(name.to_owned())
+                    .or_default()
+                    .push(property.clone());
+            }
+        }
+        if !scope {
+            self.children(element, item, seen, memory);
+        }
+    }
+}
+
+/// Returns the value of a property element that does not start an item.
+fn property_value(element: ElementRef, syntax: Syntax) -> PropertyValue {
+    let value = element.value();
+    let text = |value: &str| PropertyValue::Text(value.trim().to_owned());
+    let url = |value: &str| PropertyValue::Url(value.trim().to_owned());
+    // `content` is only defined on `<meta>` by microdata, but pages use it everywhere.
+    if let Some(content) = value.attr("content") {
+        return text(content);
+    }
+    if syntax == Syntax::Rdfa {
+        if let Some(resource) = value.attr("resource") {
+            return url(resource);
+        }
+    }
+    let attr = match value.name() {
+        "audio" | "embed" | "iframe" | "img" | "source" | "track" | "video" => Some("src"),
+        "a" | "area" | "link" => Some("href"),
+        "object" => Some("data"),
+        _ => None,
+    };
+    if let Some(href) = attr.and_then(|attr| value.attr(attr)) {
+        return url(href);
+    }
+    let attr = match value.name() {
+        "data" | "meter" => value.attr("value"),
+        "time" => value.attr("datetime"),
+        _ => None,
+    };
+    match attr {
+        Some(attr) => text(attr),
+        None => {
+            let content = element.text().collect::<String>();
+            text(&content.split_whitespace().collect::<Vec<_>>().join(" "))
+        }
+    }
+}
+
+impl Html {
+    /// Returns the top-level microdata and RDFa Lite items, in document order.
+    ///
+    /// Items that are the value of a property are nested in their parent rather than
+    /// listed; `itemref` pulls in properties declared elsewhere in the document.
+    pub fn microdata(&self) -> Vec<Item> {
+        let crawler = Crawler::new(self);
+        self.select(&ROOTS)
+            .map(|root| {
+                let value = root.value();
+                let syntax = match value.attr("itemscope") {
+                    Some(_) if value.attr("itemprop").is_none() => Syntax::Microdata,
+                    _ => Syntax::Rdfa,
+                };
+                crawler.item(root, syntax, &mut Vec::new())
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PropertyValue, Syntax};
+    use crate::html::Html;
+
+    #[test]
+    fn itemref_and_rdfa() {
+        let html = Html::parse_document(
+            r##"
+            <div itemscope itemtype="https://schema.org/Product" itemref="extra">
+              <span itemprop="name brand">Widget</span>
+              <meta itemprop="sku" content=" W-1 ">
+              <time itemprop="releaseDate" datetime="2024-01-02">January</time>
+              <div itemprop="offers" itemscope><data itemprop="price" value="9.99">$9.99</data></div>
+            </div>
+            <p id="extra" itemprop="description">A   fine
+              widget</p>
+            <div vocab="https://schema.org/" typeof="Person" resource="#ada">
+              <span property="name">Ada</span>
+              <a property="url" href="/ada">home</a>
+              <div property="address" typeof="PostalAddress"><span property="addressLocality">London</span></div>
+            </div>
+            "##,
+        );
+        let items = html.microdata();
+        assert_eq!(2, items.len());
+
+        let product = &items[0];
+        assert_eq!(Syntax::Microdata, product.syntax);
+        assert_eq!(Some("Widget"), product.text("brand"));
+        assert_eq!(Some("W-1"), product.text("sku"));
+        assert_eq!(Some("2024-01-02"), product.text("releaseDate"));
+        assert_eq!(Some("A fine widget"), product.text("description"));
+        let offer = product.item("offers").unwrap();
+        assert!(offer.types.is_empty());
+        assert_eq!(Some("9.99"), offer.text("price"));
+
+        let person = &items[1];
+        assert_eq!(Syntax::Rdfa, person.syntax);
+        assert_eq!(vec!["https://schema.org/Person"], person.types);
+        assert_eq!(Some("#ada"), person.id.as_deref());
+        assert_eq!(Some(&PropertyValue::Url("/ada".into())), person.get("url"));
+        let address = person.item("address").unwrap();
+        assert!(address.is_type("PostalAddress"));
+        assert_eq!(Some("London"), address.text("addressLocality"));
+    }
+
+    #[test]
+    fn itemref_cycles_are_crawled_once() {
+        let html = Html::parse_document(
+            r#"<div itemscope><div id="x"><div itemprop="p" itemscope itemref="x x y y">
+              <span itemprop="q">Q</span></div></div></div>
+            <div id="y" itemprop="r" itemscope itemref="x"></div>"#,
+        );
+        let items = html.microdata();
+        assert_eq!(1, items.len());
+        let p = items[0].item("p").unwrap();
+        assert_eq!(vec!["q", "r"], p.properties.keys().collect::<Vec<_>>());
+        assert_eq!(1, p.properties["q"].len());
+        // `r` reaches `p` again through `x`, which is left out instead of nesting forever.
+        assert!(p.item("r").unwrap().properties.is_empty());
+    }
+}
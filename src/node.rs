@@ -9,6 +9,8 @@ use std::ops::Deref;
 
 use html5ever::tendril::{fmt::UTF8, Atomic, Tendril};
 
+use crate::html::SourceSpan;
+
 /// Atomic StrTendril type
 pub type AtomicStrTendril = Tendril<UTF8, Atomic>;
 
@@ -103,6 +105,14 @@ impl Node {
         }
     }
 
+    /// Returns self as a mutable element.
+    pub fn as_element_mut(&mut self) -> Option<&mut Element> {
+        match *self {
+            Node::Element(ref mut e) => Some(e),
+            _ => None,
+        }
+    }
+
     /// Returns self as an element.
     pub fn as_processing_instruction(&self) -> Option<&ProcessingInstruction> {
         match *self {
@@ -217,7 +227,7 @@ impl fmt::Debug for Text {
 pub type Attributes = HashMap<QualName, AtomicStrTendril>;
 
 /// An HTML element.
-#[derive(Clone, PartialEq, Eq)]
+#[derive(Clone)]
 pub struct Element {
     /// The element name.
     pub name: QualName,
@@ -230,8 +240,23 @@ pub struct Element {
 
     /// The element attributes.
     pub attrs: Attributes,
+
+    /// Where the element came from in the parsed input, when parsing recorded spans.
+    pub(crate) span: Option<Box<SourceSpan>>,
 }
 
+// Where an element was parsed from does not make it a different element.
+impl PartialEq for Element {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+            && self.id == other.id
+            && self.classes == other.classes
+            && self.attrs == other.attrs
+    }
+}
+
+impl Eq for Element {}
+
 impl Element {
     #[doc(hidden)]
     pub fn new(name: QualName, attributes: Vec<Attribute>) -> Self {
@@ -258,6 +283,7 @@ impl Element {
             name,
             id,
             classes,
+            span: None,
         }
     }
 
@@ -278,7 +304,7 @@ impl Element {
     }
 
     /// Returns an iterator over the element's classes.
-    pub fn classes(&self) -> Classes {
+    pub fn classes(&self) -> Classes<'_> {
         Classes {
             inner: self.classes.iter(),
         }
@@ -291,11 +317,24 @@ impl Element {
     }
 
     /// Returns an iterator over the element's attributes.
-    pub fn attrs(&self) -> Attrs {
+    pub fn attrs(&self) -> Attrs<'_> {
         Attrs {
             inner: self.attrs.iter(),
         }
     }
+
+    /// Sets an attribute, keeping the cached ID and classes in sync.
+    pub fn set_attr(&mut self, attr: &str, value: &str) {
+        match attr {
+            "id" => self.id = Some(LocalName::from(value)),
+            "class" => {
+                self.classes = value.split_whitespace().map(LocalName::from).collect();
+            }
+            _ => (),
+        }
+        let qualname = QualName::new(None, ns!(), LocalName::from(attr));
+        self.attrs.insert(qualname, value.into());
+    }
 }
 
 /// Iterator over classes.
@@ -358,4 +397,7 @@ impl Deref for ProcessingInstruction {
     }
 }
 
+#[cfg(feature = "serde")]
+mod persist;
+pub(crate) mod pretty;
 pub(crate) mod serializable;
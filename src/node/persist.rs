@@ -0,0 +1,163 @@
+//! `serde` support for nodes.
+//!
+//! Names are written as `{ "local": .., "prefix": .., "ns": .. }`, leaving out the namespace
+//! when it is the usual one (HTML for elements, none for attributes). Attributes are written
+//! in name order, so equal elements give equal output.
+
+use std::ops::Deref;
+
+use html5ever::{Attribute, LocalName, Namespace, Prefix, QualName};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::{Comment, Doctype, Element, Node, ProcessingInstruction, Text};
+use crate::html::SourceSpan;
+
+#[derive(Serialize, Deserialize)]
+#[serde(bound(deserialize = "S: Deserialize<'de>"))]
+struct NameRepr<S> {
+    local: S,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    prefix: Option<S>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    ns: Option<S>,
+}
+
+impl<'a> NameRepr<&'a str> {
+    fn of(name: &'a QualName, usual: &Namespace) -> Self {
+        NameRepr {
+            local: &name.local,
+            prefix: name.prefix.as_deref(),
+            ns: (name.ns != *usual).then_some(&*name.ns),
+        }
+    }
+}
+
+impl NameRepr<String> {
+    fn into_name(self, usual: Namespace) -> QualName {
+        QualName::new(
+            self.prefix.map(Prefix::from),
+            self.ns.map_or(usual, Namespace::from),
+            LocalName::from(self.local),
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(bound(deserialize = "S: Deserialize<'de>"))]
+struct ElementRepr<S> {
+    name: NameRepr<S>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    attrs: Vec<(NameRepr<S>, S)>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    span: Option<SourceSpan>,
+}
+
+impl<'a> ElementRepr<&'a str> {
+    fn of(element: &'a Element) -> Self {
+        let mut attrs: Vec<_> = element
+            .attrs
+            .iter()
+            .map(|(name, value)| (NameRepr::of(name, &ns!()), value.deref()))
+            .collect();
+        attrs.sort_unstable_by(|(a, _), (b, _)| (a.ns, a.local).cmp(&(b.ns, b.local)));
+        ElementRepr {
+            name: NameRepr::of(&element.name, &ns!(html)),
+            attrs,
+            span: element.span.as_deref().copied(),
+        }
+    }
+}
+
+impl ElementRepr<String> {
+    fn into_element(self) -> Element {
+        let attrs = self
+            .attrs
+            .into_iter()
+            .map(|(name, value)| Attribute {
+                name: name.into_name(ns!()),
+                value: value.into(),
+            })
+            .collect();
+        let mut element = Element::new(self.name.into_name(ns!(html)), attrs);
+        element.span = self.span.map(Box::new);
+        element
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[serde(bound(deserialize = "S: Deserialize<'de>"))]
+enum NodeRepr<S> {
+    Document,
+    Fragment,
+    Doctype { name: S, public_id: S, system_id: S },
+    Comment { comment: S },
+    Text { text: S },
+    Element(ElementRepr<S>),
+    ProcessingInstruction { target: S, data: S },
+}
+
+impl Serialize for Element {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        ElementRepr::of(self).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Element {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        ElementRepr::<String>::deserialize(deserializer).map(ElementRepr::into_element)
+    }
+}
+
+impl Serialize for Node {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let repr = match self {
+            Node::Document => NodeRepr::Document,
+            Node::Fragment => NodeRepr::Fragment,
+            Node::Doctype(doctype) => NodeRepr::Doctype {
+                name: doctype.name(),
+                public_id: doctype.public_id(),
+                system_id: doctype.system_id(),
+            },
+            Node::Comment(comment) => NodeRepr::Comment {
+                comment: &**comment,
+            },
+            Node::Text(text) => NodeRepr::Text { text: &**text },
+            Node::Element(element) => NodeRepr::Element(ElementRepr::of(element)),
+            Node::ProcessingInstruction(pi) => NodeRepr::ProcessingInstruction {
+                target: &pi.target[..],
+                data: &pi.data[..],
+            },
+        };
+        repr.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Node {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(match NodeRepr::<String>::deserialize(deserializer)? {
+            NodeRepr::Document => Node::Document,
+            NodeRepr::Fragment => Node::Fragment,
+            NodeRepr::Doctype {
+                name,
+                public_id,
+                system_id,
+            } => Node::Doctype(Doctype {
+                name: name.into(),
+                public_id: public_id.into(),
+                system_id: system_id.into(),
+            }),
+            NodeRepr::Comment { comment } => Node::Comment(Comment {
+                comment: comment.into(),
+            }),
+            NodeRepr::Text { text } => Node::Text(Text { text: text.into() }),
+            NodeRepr::Element(element) => Node::Element(element.into_element()),
+            NodeRepr::ProcessingInstruction { target, data } => {
+                Node::ProcessingInstruction(ProcessingInstruction {
+                    target: target.into(),
+                    data: data.into(),
+                })
+            }
+        })
+    }
+}
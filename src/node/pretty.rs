@@ -0,0 +1,148 @@
+use std::io::{Error, Write};
+
+use ego_tree::NodeRef;
+use html5ever::serialize::{HtmlSerializer, SerializeOpts, Serializer, TraversalScope};
+
+use super::Node;
+
+/// Elements whose content is written as it is, since whitespace inside them is rendered
+/// or their children are not markup.
+const VERBATIM: &[&str] = &[
+    "pre",
+    "textarea",
+    "script",
+    "style",
+    "template",
+    "xmp",
+    "plaintext",
+    "listing",
+];
+
+/// Phrasing elements, which flow with the text around them, so whitespace next to them is
+/// significant.
+pub(crate) const PHRASING: &[&str] = &[
+    "a", "abbr", "audio", "b", "bdi", "bdo", "br", "button", "canvas", "cite", "code", "data",
+    "del", "dfn", "em", "embed", "font", "i", "iframe", "img", "input", "ins", "kbd", "label",
+    "mark", "meter", "object", "output", "picture", "progress", "q", "ruby", "s", "samp", "select",
+    "small", "span", "strong", "sub", "sup", "svg", "math", "textarea", "time", "u", "var",
+    "video", "wbr",
+];
+
+/// Returns true if the children of an element are laid out one per line: they are all
+/// block-level elements or comments, separated by whitespace only.
+fn has_block_layout(node: NodeRef<Node>) -> bool {
+    let element = match node.value() {
+        Node::Element(element) => element,
+        _ => return false,
+    };
+    !VERBATIM.contains(&element.name())
+        && node.has_children()
+        && node.children().all(|child| match child.value() {
+            Node::Text(text) => text.trim().is_empty(),
+            Node::Element(child) => !PHRASING.contains(&child.name()),
+            Node::Comment(_) => true,
+            _ => false,
+        })
+}
+
+/// Writes nodes one per line, each indented by `depth` levels.
+struct Pretty<W: Write> {
+    serializer: HtmlSerializer<W>,
+    indent: usize,
+}
+
+impl<W: Write> Pretty<W> {
+    fn line(&mut self, depth: usize) -> Result<(), Error> {
+        let width = depth * self.indent;
+        write!(self.serializer.writer, "\n{:width$}", "")
+    }
+
+    fn nodes<'a>(
+        &mut self,
+        nodes: impl Iterator<Item = NodeRef<'a, Node>>,
+        depth: usize,
+        mut first: bool,
+    ) -> Result<(), Error> {
+        for node in nodes {
+            if matches!(node.value(), Node::Text(text) if text.trim().is_empty()) {
+                continue;
+            }
+            if !first {
+                self.line(depth)?;
+            }
+            first = false;
+            self.node(node, depth)?;
+        }
+        Ok(())
+    }
+
+    fn node(&mut self, node: NodeRef<Node>, depth: usize) -> Result<(), Error> {
+        match node.value() {
+            Node::Element(element) if has_block_layout(node) => {
+                let attrs = element.attrs.iter().map(|(k, v)| (k, &v[..]));
+                self.serializer.start_elem(element.name.clone(), attrs)?;
+                self.line(depth + 1)?;
+                self.nodes(node.children(), depth + 1, true)?;
+                self.line(depth)?;
+                self.serializer.end_elem(element.name.clone())
+            }
+            Node::Text(text) => self.serializer.write_text(text.trim()),
+            _ => super::serializable::serialize(
+                node,
+                &mut self.serializer,
+                TraversalScope::IncludeNode,
+            ),
+        }
+    }
+}
+
+/// Serializes a node, or only its children, with one block-level element per line.
+pub(crate) fn pretty(node: NodeRef<Node>, indent: usize, include_node: bool) -> String {
+    let opts = SerializeOpts {
+        scripting_enabled: false,
+        traversal_scope: TraversalScope::IncludeNode,
+        create_missing_parent: false,
+    };
+    let mut pretty = Pretty {
+        serializer: HtmlSerializer::new(Vec::new(), opts),
+        indent,
+    };
+    let _ = if include_node {
+        pretty.nodes(std::iter::once(node), 0, true)
+    } else {
+        pretty.nodes(node.children(), 0, true)
+    };
+    let mut buf = pretty.serializer.writer;
+    buf.push(b'\n');
+    auto_encoder::auto_encode_bytes(&buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::html::Html;
+    use crate::selector::Selector;
+
+    #[test]
+    fn keeps_verbatim_and_phrasing_content() {
+        let html = Html::parse_fragment(
+            "<section>\n<div><p>A <i>b</i></p> <textarea>t  \n u</textarea></div>\
+             <table><tr><td>1</td></tr></table></section>",
+        );
+        let section = html
+            .select_first(&Selector::parse("section").unwrap())
+            .unwrap();
+        let expected = [
+            "<section>",
+            "  <div><p>A <i>b</i></p> <textarea>t  \n u</textarea></div>",
+            "  <table>",
+            "    <tbody>",
+            "      <tr>",
+            "        <td>1</td>",
+            "      </tr>",
+            "    </tbody>",
+            "  </table>",
+            "</section>\n",
+        ];
+        assert_eq!(expected.join("\n"), section.html_pretty(2));
+    }
+}
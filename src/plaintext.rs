@@ -0,0 +1,512 @@
+//! Conversion of documents to plain text for email and reports.
+
+use std::ops::Range;
+
+use ego_tree::NodeRef;
+
+use crate::element_ref::{bidi_mark, ElementRef};
+use crate::footnotes::{FootnoteIndex, FootnoteStyle};
+use crate::html::Html;
+use crate::markdown::{
+    content_root, definition_groups, is_skipped, list_items, prefix_lines, quote_cite, raw_text,
+    table_rows, BLOCKS,
+};
+use crate::node::Node;
+
+/// Options for [`Html::to_plaintext`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlaintextOptions {
+    /// Marks links with `[1]`-style references and lists their targets at the end. When
+    /// off, only the link text is kept.
+    pub link_footnotes: bool,
+    /// Renders images as `[image: alt]`. When off, only the alt text is kept.
+    pub image_placeholders: bool,
+    /// Starts paragraphs and headings with a left-to-right or right-to-left mark where
+    /// their [`ElementRef::direction`](crate::ElementRef::direction) differs from the
+    /// direction of their first strong character, so right-to-left text is not
+    /// reordered by consumers that guess direction per paragraph.
+    pub bidi_marks: bool,
+    /// How footnote references and note lists are rendered. Collected notes are listed
+    /// as `[^1] text` before the link targets.
+    pub footnotes: FootnoteStyle,
+}
+
+impl Default for PlaintextOptions {
+    fn default() -> Self {
+        PlaintextOptions {
+            link_footnotes: true,
+            image_placeholders: true,
+            bidi_marks: false,
+            footnotes: FootnoteStyle::Keep,
+        }
+    }
+}
+
+struct Renderer<'o> {
+    options: &'o PlaintextOptions,
+    /// Link targets in order of first reference.
+    links: Vec<String>,
+    footnotes: FootnoteIndex,
+}
+
+impl Renderer<'_> {
+    fn inline(&mut self, node: NodeRef<Node>, out: &mut Inline) {
+        for child in node.children() {
+            self.inline_node(child, out);
+        }
+    }
+
+    fn inline_node(&mut self, node: NodeRef<Node>, out: &mut Inline) {
+        let element = match node.value() {
+            Node::Text(text) => return out.push_str(&text.replace(char::is_whitespace, " ")),
+            Node::Element(e) => e,
+            _ => return,
+        };
+        if let Some(note) = self.footnotes.reference(node.id()) {
+            match self.options.footnotes {
+                FootnoteStyle::Inline => {
+                    out.push_str(&format!(" ({})", self.footnotes.notes[note].text));
+                }
+                _ => out.push_str(&format!("[^{}]", note + 1)),
+            }
+            return;
+        }
+        match element.name() {
+            name if is_skipped(name) || self.footnotes.is_note(node.id()) => {}
+            "br" => out.push_str("\n"),
+            "img" => {
+                let alt = element.attr("alt").unwrap_or_default().trim();
+                match (self.options.image_placeholders, alt.is_empty()) {
+                    (true, true) => out.push_str("[image]"),
+                    (true, false) => out.push_str(&format!("[image: {}]", alt)),
+                    (false, _) => out.push_str(alt),
+                }
+            }
+            "code" | "kbd" | "samp" => {
+                let mut code = String::new();
+                raw_text(node, &mut code);
+                out.push_kept(&code);
+            }
+            "a" => {
+                self.inline(node, out);
+                let href = element.attr("href").map(str::trim).unwrap_or_default();
+                let internal = href.is_empty() || href.starts_with('#');
+                let script = href
+                    .get(..11)
+                    .is_some_and(|s| s.eq_ignore_ascii_case("javascript:"));
+                if self.options.link_footnotes && !internal && !script {
+                    let number = match self.links.iter().position(|l| l == href) {
+                        Some(i) => i + 1,
+                        None => {
+                            self.links.push(href.to_owned());
+                            self.links.len()
+                        }
+                    };
+                    out.insert_before_trailing_space(&format!("[{}]", number));
+                }
+            }
+            "q" => {
+                out.push_str("\"");
+                self.inline(node, out);
+                out.push_str("\"");
+            }
+            name if BLOCKS.contains(&name) => {
+                out.push_str(" ");
+                self.inline(node, out);
+                out.push_str(" ");
+            }
+            _ => self.inline(node, out),
+        }
+    }
+
+    fn blocks(&mut self, node: NodeRef<Node>, out: &mut Vec<String>) {
+        let mut text = Inline::default();
+        for child in node.children() {
+            match child.value() {
+                Node::Element(e) if e.name() == "textarea" => {
+                    self.paragraph(node, &mut text, out);
+                    self.block(child, "pre", out);
+                }
+                Node::Element(e) if is_skipped(e.name()) || self.footnotes.is_note(child.id()) => {}
+                Node::Element(e) if BLOCKS.contains(&e.name()) => {
+                    self.paragraph(node, &mut text, out);
+                    self.block(child, e.name(), out);
+                }
+                _ => self.inline_node(child, &mut text),
+            }
+        }
+        self.paragraph(node, &mut text, out);
+    }
+
+    /// Pushes the collected inline text of `node` as a paragraph.
+    fn paragraph(&self, node: NodeRef<Node>, text: &mut Inline, out: &mut Vec<String>) {
+        let paragraph = text.collapse();
+        if !paragraph.is_empty() {
+            out.push(self.marked(node, paragraph));
+        }
+        text.clear();
+    }
+
+    /// Prefixes text rendered from `node` with a bidi mark if the options ask for one.
+    fn marked(&self, node: NodeRef<Node>, text: String) -> String {
+        let mark = ElementRef::wrap(node)
+            .filter(|_| self.options.bidi_marks)
+            .and_then(|element| bidi_mark(element.direction(), &text));
+        match mark {
+            Some(mark) => format!("{}{}", mark, text),
+            None => text,
+        }
+    }
+
+    fn block(&mut self, node: NodeRef<Node>, name: &str, out: &mut Vec<String>) {
+        match name {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                let mut text = Inline::default();
+                self.inline(node, &mut text);
+                let text = text.collapse().replace('\n', " ");
+                match name {
+                    _ if text.is_empty() => {}
+                    "h1" | "h2" => {
+                        let rule = if name == "h1" { "=" } else { "-" };
+                        let underline = rule.repeat(text.chars().count());
+                        let text = self.marked(node, text);
+                        out.push(format!("{}\n{}", text, underline));
+                    }
+                    _ => out.push(self.marked(node, text)),
+                }
+            }
+            "hr" => out.push("----".to_owned()),
+            "pre" => {
+                let mut code = String::new();
+                raw_text(node, &mut code);
+                let code = code.strip_prefix('\n').unwrap_or(&code).trim_end();
+                if !code.is_empty() {
+                    out.push(code.to_owned());
+                }
+            }
+            "blockquote" => {
+                let mut inner = Vec::new();
+                self.blocks(node, &mut inner);
+                if let Some(cite) = quote_cite(node) {
+                    inner.push(format!("-- {}", cite));
+                }
+                if !inner.is_empty() {
+                    out.push(prefix_lines(&inner.join("\n\n"), "> ", ">"));
+                }
+            }
+            "ul" | "ol" => {
+                let mut lines = Vec::new();
+                for (item, number) in list_items(node) {
+                    if self.footnotes.is_note(item.id()) {
+                        continue;
+                    }
+                    let marker = match number {
+                        Some(n) => format!("{}. ", n),
+                        None => "* ".to_owned(),
+                    };
+                    let mut inner = Vec::new();
+                    self.blocks(item, &mut inner);
+                    let indent = " ".repeat(marker.len());
+                    let body = prefix_lines(&inner.join("\n\n"), &indent, "");
+                    lines.push(format!(
+                        "{}{}",
+                        marker,
+                        &body[indent.len().min(body.len())..]
+                    ));
+                }
+                if !lines.is_empty() {
+                    out.push(lines.join("\n"));
+                }
+            }
+            "dl" => {
+                let mut groups = Vec::new();
+                for (terms, definitions) in definition_groups(node) {
+                    let mut lines = Vec::new();
+                    for term in terms {
+                        let mut text = Inline::default();
+                        self.inline(term, &mut text);
+                        let text = text.collapse().replace('\n', " ");
+                        if !text.is_empty() {
+                            lines.push(text);
+                        }
+                    }
+                    for definition in definitions {
+                        let mut inner = Vec::new();
+                        self.blocks(definition, &mut inner);
+                        if !inner.is_empty() {
+                            lines.push(prefix_lines(&inner.join("\n\n"), "    ", ""));
+                        }
+                    }
+                    if !lines.is_empty() {
+                        groups.push(lines.join("\n"));
+                    }
+                }
+                if !groups.is_empty() {
+                    out.push(groups.join("\n\n"));
+                }
+            }
+            "table" => {
+                if let Some(table) = self.table(node) {
+                    out.push(table);
+                }
+            }
+            _ => self.blocks(node, out),
+        }
+    }
+
+    /// Renders a table with space-padded columns and a rule under the first row.
+    fn table(&mut self, node: NodeRef<Node>) -> Option<String> {
+        let rows: Vec<Vec<String>> = table_rows(node)
+            .into_iter()
+            .map(|row| {
+                row.into_iter()
+                    .map(|cell| {
+                        let mut text = Inline::default();
+                        self.inline(cell, &mut text);
+                        text.collapse().replace('\n', " ")
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let columns = rows.iter().map(Vec::len).max().filter(|&n| n > 0)?;
+        let widths: Vec<usize> = (0..columns)
+            .map(|i| {
+                rows.iter()
+                    .filter_map(|row| row.get(i))
+                    .map(|cell| cell.chars().count())
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+        let line = |cells: &[String]| {
+            let padded: Vec<String> = widths
+                .iter()
+                .enumerate()
+                .map(|(i, &width)| {
+                    let cell = cells.get(i).map_or("", String::as_str);
+                    format!("{}{}", cell, " ".repeat(width - cell.chars().count()))
+                })
+                .collect();
+            padded.join("  ").trim_end().to_owned()
+        };
+
+        let mut lines = vec![line(&rows[0])];
+        let rule: Vec<String> = widths.iter().map(|&w| "-".repeat(w)).collect();
+        lines.push(rule.join("  "));
+        lines.extend(rows[1..].iter().map(|row| line(row)));
+        Some(lines.join("\n"))
+    }
+}
+
+/// Inline text collected for one block, with the byte ranges of inline code whose
+/// spacing `collapse` keeps.
+#[derive(Default)]
+struct Inline {
+    text: String,
+    kept: Vec<Range<usize>>,
+}
+
+impl Inline {
+    fn push_str(&mut self, text: &str) {
+        self.text.push_str(text);
+    }
+
+    fn push_kept(&mut self, text: &str) {
+        let start = self.text.len();
+        self.text.push_str(text);
+        self.kept.push(start..self.text.len());
+    }
+
+    /// Inserts `text` before the trailing whitespace, but after any inline code.
+    fn insert_before_trailing_space(&mut self, text: &str) {
+        let code_end = self.kept.last().map_or(0, |kept| kept.end);
+        let at = self.text.trim_end().len().max(code_end);
+        self.text.insert_str(at, text);
+    }
+
+    fn clear(&mut self) {
+        self.text.clear();
+        self.kept.clear();
+    }
+
+    /// Collapses whitespace within each line, keeping `<br>` line breaks and the spacing
+    /// of inline code, whose line breaks become spaces.
+    fn collapse(&self) -> String {
+        let mut lines = vec![String::new()];
+        let mut space = false;
+        let mut kept = self.kept.iter().peekable();
+        for (i, c) in self.text.char_indices() {
+            while kept.next_if(|range| range.end <= i).is_some() {}
+            let line = lines.last_mut().unwrap();
+            if kept.peek().is_some_and(|range| range.contains(&i)) || !c.is_whitespace() {
+                if space && !line.is_empty() {
+                    line.push(' ');
+                }
+                space = false;
+                line.push(if c == '\n' { ' ' } else { c });
+            } else if c == '\n' {
+                lines.push(String::new());
+                space = false;
+            } else {
+                space = true;
+            }
+        }
+        while lines.last().is_some_and(String::is_empty) {
+            lines.pop();
+        }
+        let start = lines.iter().take_while(|line| line.is_empty()).count();
+        lines[start..].join("\n")
+    }
+}
+
+impl Html {
+    /// Renders the document body as plain text.
+    ///
+    /// Blocks are separated by blank lines, `h1` and `h2` are underlined, lists keep their
+    /// markers and tables are laid out in aligned columns. The whitespace of `pre`, `code`
+    /// and `textarea` elements is kept as written. Links become numbered references
+    /// whose targets are listed at the end, and images become alt-text placeholders.
+    ///
+    /// ```
+    /// use scraper::plaintext::PlaintextOptions;
+    /// use scraper::Html;
+    ///
+    /// let html = Html::parse_fragment(
+    ///     r#"<h1>Report</h1><p>Read <a href="https://a.example/">this</a> and
+    ///     <a href="https://b.example/">that</a>.</p><img src="c.png" alt="Chart">"#,
+    /// );
+    /// assert_eq!(
+    ///     "Report\n======\n\nRead this[1] and that[2].\n\n[image: Chart]\n\n\
+    ///      [1] https://a.example/\n[2] https://b.example/\n",
+    ///     html.to_plaintext(&PlaintextOptions::default())
+    /// );
+    /// ```
+    pub fn to_plaintext(&self, options: &PlaintextOptions) -> String {
+        let footnotes = match options.footnotes {
+            FootnoteStyle::Keep => FootnoteIndex::default(),
+            _ => FootnoteIndex::new(self),
+        };
+        let mut renderer = Renderer {
+            options,
+            links: Vec::new(),
+            footnotes,
+        };
+        let mut rendered = Vec::new();
+        renderer.blocks(content_root(self), &mut rendered);
+
+        if options.footnotes == FootnoteStyle::Collect && !renderer.footnotes.notes.is_empty() {
+            let notes: Vec<String> = renderer
+                .footnotes
+                .notes
+                .iter()
+                .enumerate()
+                .map(|(i, note)| format!("[^{}] {}", i + 1, note.text))
+                .collect();
+            rendered.push(notes.join("\n"));
+        }
+
+        if !renderer.links.is_empty() {
+            let notes: Vec<String> = renderer
+                .links
+                .iter()
+                .enumerate()
+                .map(|(i, link)| format!("[{}] {}", i + 1, link))
+                .collect();
+            rendered.push(notes.join("\n"));
+        }
+        let mut out = rendered.join("\n\n");
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PlaintextOptions;
+    use crate::footnotes::FootnoteStyle;
+    use crate::html::Html;
+
+    #[test]
+    fn aligns_tables_and_reuses_link_numbers() {
+        let html = Html::parse_fragment(
+            "<table><thead><tr><th>Name</th><th>Qty</th></tr></thead>\
+             <tbody><tr><td>Apples</td><td>3</td></tr><tr><td>Kiwi</td><td>12</td></tr></tbody></table>\
+             <ul><li><a href='/x'>x</a></li><li><a href='/x'>again</a> <a href='#top'>top</a></li></ul>",
+        );
+        assert_eq!(
+            "Name    Qty\n------  ---\nApples  3\nKiwi    12\n\n* x[1]\n* again[1] top\n\n[1] /x\n",
+            html.to_plaintext(&PlaintextOptions::default())
+        );
+
+        let options = PlaintextOptions {
+            link_footnotes: false,
+            image_placeholders: false,
+            ..PlaintextOptions::default()
+        };
+        let html = Html::parse_fragment("<p><a href='/x'>x</a> <img src='a.png' alt='A'></p>");
+        assert_eq!("x A\n", html.to_plaintext(&options));
+    }
+
+    #[test]
+    fn marks_blocks_whose_first_strong_character_misleads() {
+        let html = Html::parse_fragment(
+            "<div dir='rtl'><h3>Rust مقدمة</h3><p>مرحبا</p><ul><li>CSS و HTML</li></ul></div>\
+             <p>plain</p>",
+        );
+        let options = PlaintextOptions {
+            bidi_marks: true,
+            ..PlaintextOptions::default()
+        };
+        assert_eq!(
+            "\u{200F}Rust مقدمة\n\nمرحبا\n\n* \u{200F}CSS و HTML\n\nplain\n",
+            html.to_plaintext(&options)
+        );
+    }
+
+    #[test]
+    fn keeps_preformatted_whitespace() {
+        let html = Html::parse_fragment(
+            "<p>Call <code>f(a,  b)</code>   now.</p>\
+             <pre>if x {\n\ty();\n}</pre><form>Notes: <textarea>  one\n  two</textarea></form>\
+             <p>\u{E000}  <a href='/g'><code>g( ) </code></a></p>",
+        );
+        assert_eq!(
+            "Call f(a,  b) now.\n\nif x {\n\ty();\n}\n\nNotes:\n\n  one\n  two\n\n\
+             \u{E000} g( ) [1]\n\n[1] /g\n",
+            html.to_plaintext(&PlaintextOptions::default())
+        );
+    }
+
+    #[test]
+    fn numbers_lists_and_indents_definitions() {
+        let html = Html::parse_fragment(
+            "<ol start='9'><li>nine</li><li>ten<ol reversed><li>b</li><li>a</li></ol></li>\
+             <li value='20'>twenty</li><li><p>next</p><p>more</p></li></ol>\
+             <dl><dt>Term</dt><dt>Alias</dt><dd>First</dd><div><dt>Other</dt><dd>Second</dd></div></dl>",
+        );
+        assert_eq!(
+            "9. nine\n10. ten\n\n    2. b\n    1. a\n20. twenty\n21. next\n\n    more\n\n\
+             Term\nAlias\n    First\n\nOther\n    Second\n",
+            html.to_plaintext(&PlaintextOptions::default())
+        );
+    }
+
+    #[test]
+    fn lists_collected_footnotes_before_links() {
+        let html = Html::parse_fragment(
+            "<p><a href='https://x.example/'>Source</a><sup><a href='#fn-a'>a</a></sup></p>\
+             <div class='footnotes'><ol><li id='fn-a'>Checked in 2024.</li></ol></div>",
+        );
+        let options = PlaintextOptions {
+            footnotes: FootnoteStyle::Collect,
+            ..PlaintextOptions::default()
+        };
+        assert_eq!(
+            "Source[1][^1]\n\n[^1] Checked in 2024.\n\n[1] https://x.example/\n",
+            html.to_plaintext(&options)
+        );
+    }
+}
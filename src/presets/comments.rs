@@ -0,0 +1,349 @@
+//! Comment thread extraction with a registry of known markup patterns.
+
+use ego_tree::NodeRef;
+
+use crate::element_ref::ElementRef;
+use crate::html::Html;
+use crate::node::Node;
+use crate::selector::Selector;
+
+/// A comment and its replies.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Comment {
+    /// The `id` of the comment element.
+    pub id: Option<String>,
+    /// The author's display name.
+    pub author: Option<String>,
+    /// When the comment was posted, from a `datetime` or `title` attribute if present.
+    pub published: Option<String>,
+    /// The link to the comment itself, as written in the page.
+    pub permalink: Option<String>,
+    /// The text of the comment, without its replies.
+    pub text: String,
+    /// The direct replies.
+    pub replies: Vec<Comment>,
+}
+
+impl Comment {
+    /// Returns the number of comments in this thread, including this one.
+    pub fn count(&self) -> usize {
+        1 + self.replies.iter().map(Comment::count).sum::<usize>()
+    }
+}
+
+/// Selectors describing the comment markup of one platform.
+///
+/// The field selectors match inside a comment element, ignoring matches inside its nested
+/// replies. When a selector list matches several elements, the first in document order
+/// is used.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommentPattern {
+    /// A name for the pattern, such as `wordpress`.
+    pub name: String,
+    /// Matches every comment element; replies are comments nested inside another.
+    pub comment: Selector,
+    /// The author name.
+    pub author: Option<Selector>,
+    /// The timestamp.
+    pub published: Option<Selector>,
+    /// A link whose `href` is the permalink.
+    pub permalink: Option<Selector>,
+    /// The comment text. Without it, the text of the whole comment is used.
+    pub body: Option<Selector>,
+}
+
+impl CommentPattern {
+    /// Creates a pattern from selector strings, where empty strings leave a field out.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a selector is invalid; patterns are meant to be written as literals.
+    pub fn new(
+        name: &str,
+        comment: &str,
+        author: &str,
+        published: &str,
+        permalink: &str,
+        body: &str,
+    ) -> Self {
+        let optional = |s: &str| (!s.is_empty()).then(|| Selector::parse(s).unwrap());
+        CommentPattern {
+            name: name.to_owned(),
+            comment: Selector::parse(comment).unwrap(),
+            author: optional(author),
+            published: optional(published),
+            permalink: optional(permalink),
+            body: optional(body),
+        }
+    }
+
+    /// Extracts the comment threads matching this pattern.
+    pub fn extract(&self, html: &Html) -> Vec<Comment> {
+        let root = html.root_element();
+        let elements: Vec<ElementRef> = root.select(&self.comment).collect();
+        let parent_of = |element: &ElementRef| {
+            element
+                .ancestors()
+                .find(|a| elements.iter().any(|e| e.id() == a.id()))
+                .map(|a| a.id())
+        };
+        let parents: Vec<_> = elements.iter().map(parent_of).collect();
+
+        fn build(
+            pattern: &CommentPattern,
+            elements: &[ElementRef],
+            parents: &[Option<ego_tree::NodeId>],
+            parent: Option<ego_tree::NodeId>,
+        ) -> Vec<Comment> {
+            elements
+                .iter()
+                .zip(parents)
+                .filter(|(_, p)| **p == parent)
+                .map(|(element, _)| {
+                    let mut comment = pattern.comment_of(*element);
+                    comment.replies = build(pattern, elements, parents, Some(element.id()));
+                    comment
+                })
+                .collect()
+        }
+        build(self, &elements, &parents, None)
+    }
+
+    /// Returns the first match of `selector` in `comment` that is not inside a reply.
+    fn own<'a>(&self, comment: ElementRef<'a>, selector: &Selector) -> Option<ElementRef<'a>> {
+        comment.select(selector).find(|found| {
+            !found
+                .ancestors()
+                .take_while(|a| a.id() != comment.id())
+                .filter_map(ElementRef::wrap)
+                .any(|a| self.comment.matches(&a))
+        })
+    }
+
+    fn comment_of(&self, element: ElementRef) -> Comment {
+        let field = |selector: &Option<Selector>| {
+            let found = self.own(element, selector.as_ref()?)?;
+            let text = found.inner_text();
+            (!text.is_empty()).then_some(text)
+        };
+        let published = self.published.as_ref().and_then(|selector| {
+            let found = self.own(element, selector)?;
+            let attr = found.attr("datetime").or_else(|| found.attr("title"));
+            let value = attr.map_or_else(|| found.inner_text(), |a| a.trim().to_owned());
+            (!value.is_empty()).then_some(value)
+        });
+        let permalink = self.permalink.as_ref().and_then(|selector| {
+            let href = self.own(element, selector)?.value().attr("href")?.trim();
+            (!href.is_empty()).then(|| href.to_owned())
+        });
+        let is_reply = |node: NodeRef<Node>| {
+            node.id() != element.id()
+                && ElementRef::wrap(node).is_some_and(|e| self.comment.matches(&e))
+        };
+        let text = match &self.body {
+            Some(body) => self
+                .own(element, body)
+                .map(|b| b.inner_text_except(is_reply))
+                .unwrap_or_default(),
+            None => element.inner_text_except(is_reply),
+        };
+
+        Comment {
+            id: element.value().id().map(str::to_owned),
+            author: field(&self.author),
+            published,
+            permalink,
+            text,
+            replies: Vec::new(),
+        }
+    }
+}
+
+/// An ordered registry of [`CommentPattern`]s; the first pattern that finds comments wins.
+///
+/// The default registry knows WordPress, Discourse, old Reddit, schema.org `Comment`
+/// microdata and microformats, with a generic `.comment` pattern last.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommentPatterns {
+    patterns: Vec<CommentPattern>,
+}
+
+impl Default for CommentPatterns {
+    fn default() -> Self {
+        CommentPatterns {
+            patterns: vec![
+                CommentPattern::new(
+                    "wordpress",
+                    "li.comment, article.comment, div.comment.depth-1, div.comment[id^=\"comment-\"]",
+                    ".comment-author .fn",
+                    ".comment-metadata time, .comment-meta time",
+                    ".comment-metadata a[href], .comment-meta a[href]",
+                    ".comment-content",
+                ),
+                CommentPattern::new(
+                    "schema.org",
+                    "[itemtype$=\"schema.org/Comment\"]",
+                    "[itemprop=\"author\"]",
+                    "[itemprop=\"dateCreated\"], [itemprop=\"datePublished\"]",
+                    "a[itemprop=\"url\"]",
+                    "[itemprop=\"text\"]",
+                ),
+                CommentPattern::new(
+                    "microformats",
+                    ".h-cite.p-comment, .h-entry.p-comment, .u-comment",
+                    ".p-author",
+                    ".dt-published",
+                    "a.u-url",
+                    ".e-content, .p-content",
+                ),
+                CommentPattern::new(
+                    "discourse",
+                    ".topic-post",
+                    ".names .username, .username",
+                    ".relative-date",
+                    "a.post-date",
+                    ".cooked",
+                ),
+                CommentPattern::new(
+                    "reddit",
+                    ".thing.comment",
+                    "a.author",
+                    "time",
+                    "a.bylink",
+                    ".usertext-body",
+                ),
+                CommentPattern::new(
+                    "generic",
+                    ".comment",
+                    ".author, .comment-author, [rel~=\"author\"]",
+                    "time",
+                    "a.permalink, a[rel=\"bookmark\"]",
+                    ".comment-body, .comment-text, .comment-content",
+                ),
+            ],
+        }
+    }
+}
+
+impl CommentPatterns {
+    /// Creates an empty registry.
+    pub fn empty() -> Self {
+        CommentPatterns {
+            patterns: Vec::new(),
+        }
+    }
+
+    /// Adds a pattern ahead of the ones already registered, so site-specific patterns
+    /// take precedence over the built-in ones.
+    pub fn register(&mut self, pattern: CommentPattern) -> &mut Self {
+        self.patterns.insert(0, pattern);
+        self
+    }
+
+    /// Returns the registered patterns in the order they are tried.
+    pub fn patterns(&self) -> &[CommentPattern] {
+        &self.patterns
+    }
+
+    /// Extracts comment threads with the first pattern that finds any.
+    pub fn extract(&self, html: &Html) -> Vec<Comment> {
+        self.patterns
+            .iter()
+            .map(|pattern| pattern.extract(html))
+            .find(|comments| !comments.is_empty())
+            .unwrap_or_default()
+    }
+}
+
+impl Html {
+    /// Extracts comment threads with the default [`CommentPatterns`].
+    ///
+    /// ```
+    /// use scraper::Html;
+    ///
+    /// let html = Html::parse_document(r##"
+    ///     <ol class="comment-list">
+    ///       <li id="comment-1" class="comment">
+    ///         <div class="comment-author"><b class="fn">Ada</b></div>
+    ///         <div class="comment-metadata">
+    ///           <a href="#comment-1"><time datetime="2024-05-01T10:00:00Z">May 1</time></a>
+    ///         </div>
+    ///         <div class="comment-content"><p>First!</p></div>
+    ///         <ol class="children">
+    ///           <li id="comment-2" class="comment">
+    ///             <div class="comment-author"><b class="fn">Grace</b></div>
+    ///             <div class="comment-content"><p>Welcome.</p></div>
+    ///           </li>
+    ///         </ol>
+    ///       </li>
+    ///     </ol>
+    /// "##);
+    /// let comments = html.extract_comments();
+    /// assert_eq!(1, comments.len());
+    /// assert_eq!(Some("Ada"), comments[0].author.as_deref());
+    /// assert_eq!(Some("2024-05-01T10:00:00Z"), comments[0].published.as_deref());
+    /// assert_eq!(Some("#comment-1"), comments[0].permalink.as_deref());
+    /// assert_eq!("First!", comments[0].text);
+    /// assert_eq!("Welcome.", comments[0].replies[0].text);
+    /// assert_eq!(None, comments[0].replies[0].published);
+    /// ```
+    pub fn extract_comments(&self) -> Vec<Comment> {
+        CommentPatterns::default().extract(self)
+    }
+
+    /// Extracts comment threads with a custom registry.
+    pub fn extract_comments_with(&self, patterns: &CommentPatterns) -> Vec<Comment> {
+        patterns.extract(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CommentPattern, CommentPatterns};
+    use crate::html::Html;
+
+    #[test]
+    fn registered_patterns_take_precedence() {
+        let html = Html::parse_document(
+            "<div class='comment'><span class='author'>generic</span></div>\
+             <div class='post' id='p1'><u>kim</u><div class='msg'>Top</div>\
+               <div class='post'><u>lee</u><div class='msg'>Reply</div></div></div>",
+        );
+        assert_eq!(
+            "generic",
+            html.extract_comments()[0].author.as_deref().unwrap()
+        );
+
+        let mut patterns = CommentPatterns::default();
+        patterns.register(CommentPattern::new("forum", ".post", "u", "", "", ".msg"));
+        let comments = html.extract_comments_with(&patterns);
+        assert_eq!(1, comments.len());
+        assert_eq!(2, comments[0].count());
+        assert_eq!(Some("p1"), comments[0].id.as_deref());
+        assert_eq!(
+            ("Top", Some("lee")),
+            (
+                comments[0].text.as_str(),
+                comments[0].replies[0].author.as_deref()
+            )
+        );
+    }
+
+    #[test]
+    fn comment_text_leaves_out_replies() {
+        let html = Html::parse_fragment(
+            "<div class='c'>Parent <div class='body'>says hi<div class='c'>Child</div></div></div>",
+        );
+        let mut patterns = CommentPatterns::empty();
+        patterns.register(CommentPattern::new("plain", ".c", "", "", "", ""));
+        let comments = html.extract_comments_with(&patterns);
+        assert_eq!("Parent says hi", comments[0].text);
+        assert_eq!("Child", comments[0].replies[0].text);
+
+        patterns = CommentPatterns::empty();
+        patterns.register(CommentPattern::new("body", ".c", "", "", "", ".body"));
+        let comments = html.extract_comments_with(&patterns);
+        assert_eq!("says hi", comments[0].text);
+    }
+}
@@ -0,0 +1,368 @@
+//! Extraction of product grids and other repeated listing items.
+
+use std::collections::HashMap;
+
+use ego_tree::NodeId;
+
+use crate::element_ref::ElementRef;
+use crate::html::Html;
+use crate::node::Node;
+
+/// Currency symbols that mark a price.
+const CURRENCY_SYMBOLS: &[char] = &[
+    '$', '€', '£', '¥', '₹', '₩', '₽', '₺', '₴', '₫', '฿', '₪', '₱', '¢', '₦',
+];
+
+/// ISO 4217 codes that mark a price when written next to the amount.
+const CURRENCY_CODES: &[&str] = &[
+    "USD", "EUR", "GBP", "JPY", "CNY", "CAD", "AUD", "CHF", "INR", "SEK", "NOK", "DKK", "PLN",
+    "BRL", "MXN",
+];
+
+/// The smallest number of similar cards that makes a listing.
+const MIN_ITEMS: usize = 3;
+
+/// A product card of a category or search results page.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ListingItem {
+    /// The product name.
+    pub title: Option<String>,
+    /// The link to the product page, as written in the page.
+    pub url: Option<String>,
+    /// The current price as displayed, such as `$19.99`, ignoring struck-out prices.
+    pub price: Option<String>,
+    /// The product image, from `src`, a lazy-loading `data-src` or the first `srcset`
+    /// candidate.
+    pub image: Option<String>,
+}
+
+impl ListingItem {
+    fn extract(card: ElementRef) -> Self {
+        let title = title_element(card);
+        let url = title
+            .and_then(|t| {
+                std::iter::once(*t)
+                    .chain(t.descendants())
+                    .chain(t.ancestors().take_while(|a| a.id() != card.id()))
+                    .filter_map(ElementRef::wrap)
+                    .find_map(link_href)
+            })
+            .or_else(|| {
+                card.descendants()
+                    .filter_map(ElementRef::wrap)
+                    .find_map(link_href)
+            });
+
+        ListingItem {
+            title: title
+                .map(|t| t.inner_text())
+                .filter(|t| !t.is_empty())
+                .or_else(|| {
+                    card.descendants()
+                        .filter_map(ElementRef::wrap)
+                        .filter(|e| e.value().name() == "img")
+                        .find_map(|img| img.value().attr("alt"))
+                        .map(str::trim)
+                        .filter(|alt| !alt.is_empty())
+                        .map(str::to_owned)
+                }),
+            url,
+            price: price(card),
+            image: image(card),
+        }
+    }
+
+    /// Scores how much the card looks like a product, with the price weighing most.
+    fn score(&self) -> usize {
+        if self.url.is_none() || self.title.is_none() {
+            return 0;
+        }
+        2 + usize::from(self.image.is_some()) + 2 * usize::from(self.price.is_some())
+    }
+}
+
+/// Returns the element holding the product name.
+fn title_element(card: ElementRef) -> Option<ElementRef> {
+    let elements = || card.descendants().filter_map(ElementRef::wrap);
+    elements()
+        .find(|e| e.value().attr("itemprop") == Some("name"))
+        .or_else(|| {
+            elements().find(|e| matches!(e.value().name(), "h1" | "h2" | "h3" | "h4" | "h5" | "h6"))
+        })
+        .or_else(|| {
+            elements().find(|e| {
+                e.value().classes().any(|class| {
+                    let class = class.to_ascii_lowercase();
+                    class.contains("title") || class.ends_with("name")
+                })
+            })
+        })
+        .or_else(|| {
+            elements()
+                .filter(|e| link_href(*e).is_some())
+                .max_by_key(|a| a.inner_text().chars().count())
+        })
+        .filter(|e| !e.inner_text().is_empty())
+}
+
+/// Returns the `href` of a link that goes somewhere.
+fn link_href(element: ElementRef) -> Option<String> {
+    if element.value().name() != "a" {
+        return None;
+    }
+    let href = element.value().attr("href")?.trim();
+    let dead = href.is_empty() || href.starts_with('#') || href.starts_with("javascript:");
+    (!dead).then(|| href.to_owned())
+}
+
+fn image(card: ElementRef) -> Option<String> {
+    let img = card
+        .descendants()
+        .filter_map(ElementRef::wrap)
+        .find(|e| e.value().name() == "img")?;
+    let img = img.value();
+    let attr = |name: &str| img.attr(name).map(str::trim).filter(|v| !v.is_empty());
+    let src = attr("src").filter(|src| !src.starts_with("data:"));
+    src.or_else(|| attr("data-src"))
+        .or_else(|| attr("srcset")?.split(',').next()?.split_whitespace().next())
+        .map(str::to_owned)
+}
+
+/// Returns true if the element is a struck-out former price.
+fn is_struck(element: ElementRef) -> bool {
+    matches!(element.value().name(), "del" | "s" | "strike")
+        || element.value().classes().any(|class| {
+            let class = class.to_ascii_lowercase();
+            [
+                "strike",
+                "old-price",
+                "price-old",
+                "was-price",
+                "price-was",
+                "original-price",
+            ]
+            .iter()
+            .any(|marker| class.contains(marker))
+        })
+}
+
+/// Collects the text of a node, skipping struck-out prices, scripts and styles.
+fn current_text(element: ElementRef, out: &mut String) {
+    for child in element.children() {
+        match child.value() {
+            Node::Text(text) => out.push_str(text),
+            Node::Element(e) if !matches!(e.name(), "script" | "style") => {
+                let child = ElementRef::wrap(child).unwrap();
+                if !is_struck(child) {
+                    current_text(child, out);
+                }
+                out.push(' ');
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Returns true if the text is an amount with a currency symbol or code.
+fn looks_like_price(text: &str) -> bool {
+    text.chars().any(|c| c.is_ascii_digit())
+        && (text.contains(CURRENCY_SYMBOLS)
+            || text
+                .split(|c: char| !c.is_ascii_alphabetic())
+                .any(|word| CURRENCY_CODES.contains(&word)))
+}
+
+fn price(card: ElementRef) -> Option<String> {
+    let elements = || card.descendants().filter_map(ElementRef::wrap);
+    let collapse = |text: &str| text.split_whitespace().collect::<Vec<_>>().join(" ");
+    let struck = |e: &ElementRef| {
+        std::iter::once(**e)
+            .chain(e.ancestors().take_while(|a| a.id() != card.id()))
+            .filter_map(ElementRef::wrap)
+            .any(is_struck)
+    };
+
+    if let Some(price) = elements().find(|e| e.value().attr("itemprop") == Some("price")) {
+        let value = price.value();
+        let text = value
+            .attr("content")
+            .map_or_else(|| price.inner_text(), collapse);
+        if !text.is_empty() {
+            return Some(text);
+        }
+    }
+    let classed = elements().find(|e| {
+        !struck(e)
+            && e.value()
+                .classes()
+                .any(|class| class.to_ascii_lowercase().contains("price"))
+    });
+    if let Some(classed) = classed {
+        let mut text = String::new();
+        current_text(classed, &mut text);
+        let text = collapse(&text);
+        if text.chars().any(|c| c.is_ascii_digit()) {
+            return Some(text);
+        }
+    }
+    card.descendants()
+        .filter(|n| {
+            n.parent()
+                .and_then(ElementRef::wrap)
+                .is_some_and(|p| !struck(&p))
+        })
+        .filter_map(|n| n.value().as_text())
+        .map(|text| collapse(text))
+        .find(|text| looks_like_price(text) && text.chars().count() <= 32)
+}
+
+/// The cards found under each parent, grouped by [`signature`] of the parent and tag of the
+/// cards.
+type Rows<'a> = HashMap<(String, &'a str), Vec<(NodeId, Vec<ListingItem>)>>;
+
+/// Returns a signature shared by the containers of one product grid split into rows.
+fn signature(element: ElementRef) -> String {
+    let mut classes: Vec<&str> = element.value().classes().collect();
+    classes.sort_unstable();
+    format!("{}.{}", element.value().name(), classes.join("."))
+}
+
+impl Html {
+    /// Finds the grid of product cards of a category or search results page and extracts
+    /// the title, link, price and image of each card, without site-specific selectors.
+    ///
+    /// Cards are the sibling elements with the same tag that most look like products;
+    /// grids split into rows of identical containers are read as one listing. Pages without
+    /// at least three such cards return nothing.
+    ///
+    /// ```
+    /// use scraper::Html;
+    ///
+    /// let html = Html::parse_document(r#"
+    ///     <nav><a href="/">Home</a> <a href="/shoes">Shoes</a> <a href="/bags">Bags</a></nav>
+    ///     <ul class="products">
+    ///       <li><a href="/p/1"><img src="1.jpg"><h3>Trail runner</h3></a>
+    ///         <span class="price"><del>$99</del> $79.00</span></li>
+    ///       <li><a href="/p/2"><img src="2.jpg"><h3>Road racer</h3></a>
+    ///         <span class="price">$120.00</span></li>
+    ///       <li><a href="/p/3"><img data-src="3.jpg"><h3>Daily trainer</h3></a>
+    ///         <span>€89,95</span></li>
+    ///     </ul>
+    /// "#);
+    /// let items = html.extract_listing();
+    /// assert_eq!(3, items.len());
+    /// assert_eq!(Some("Trail runner"), items[0].title.as_deref());
+    /// assert_eq!(Some("/p/1"), items[0].url.as_deref());
+    /// assert_eq!(Some("$79.00"), items[0].price.as_deref());
+    /// assert_eq!(Some("€89,95"), items[2].price.as_deref());
+    /// assert_eq!(Some("3.jpg"), items[2].image.as_deref());
+    /// ```
+    pub fn extract_listing(&self) -> Vec<ListingItem> {
+        // Each candidate is a parent and the tag of its repeated children.
+        let mut best: Option<(usize, ElementRef, &str)> = None;
+        let mut groups: Rows = HashMap::new();
+
+        for parent in self
+            .root_element()
+            .descendants()
+            .filter_map(ElementRef::wrap)
+        {
+            // Grouped in document order, so ties go to the first candidate.
+            let mut by_tag: Vec<(&str, Vec<ElementRef>)> = Vec::new();
+            for child in parent.children().filter_map(ElementRef::wrap) {
+                let tag = child.value().name();
+                match by_tag.iter_mut().find(|(t, _)| *t == tag) {
+                    Some((_, cards)) => cards.push(child),
+                    None => by_tag.push((tag, vec![child])),
+                }
+            }
+            for (tag, cards) in by_tag {
+                if cards.len() < MIN_ITEMS {
+                    continue;
+                }
+                let items: Vec<ListingItem> = cards.into_iter().map(ListingItem::extract).collect();
+                let products = items.iter().filter(|item| item.score() > 2).count();
+                if products < MIN_ITEMS {
+                    continue;
+                }
+                let score: usize = items.iter().map(ListingItem::score).sum();
+                if best.is_none_or(|(best, _, _)| score > best) {
+                    best = Some((score, parent, tag));
+                }
+                groups
+                    .entry((signature(parent), tag))
+                    .or_default()
+                    .push((parent.id(), items));
+            }
+        }
+
+        let Some((_, parent, tag)) = best else {
+            return Vec::new();
+        };
+        let mut rows = groups.remove(&(signature(parent), tag)).unwrap_or_default();
+        // Rows nested in another row are cards of their own, not part of this grid.
+        let ids: Vec<NodeId> = rows.iter().map(|(id, _)| *id).collect();
+        rows.retain(|(id, _)| {
+            let node = self.tree.get(*id).unwrap();
+            !node.ancestors().any(|a| ids.contains(&a.id()))
+        });
+        rows.into_iter()
+            .flat_map(|(_, items)| items)
+            .filter(|item| item.title.is_some() || item.url.is_some())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::html::Html;
+
+    #[test]
+    fn rows_of_a_grid_form_one_listing() {
+        let card = |n: usize| {
+            format!(
+                "<div class='card'><div class='product-title'><a href='/p/{n}'>Item {n}</a></div>\
+                 <img srcset='{n}-1x.jpg 1x, {n}-2x.jpg 2x'>\
+                 <div itemprop='price' content='{n}.00'>${n}.00</div></div>"
+            )
+        };
+        let row = |from: usize| {
+            let cards: String = (from..from + 3).map(card).collect();
+            format!("<div class='row'>{cards}</div>")
+        };
+        let html = Html::parse_document(&format!(
+            "<ul><li><a href='/a'>A</a></li><li><a href='/b'>B</a></li><li><a href='/c'>C</a></li></ul>\
+             <main>{}{}</main>",
+            row(1),
+            row(4)
+        ));
+        let items = html.extract_listing();
+        assert_eq!(6, items.len());
+        assert_eq!(Some("Item 6"), items[5].title.as_deref());
+        assert_eq!(Some("6.00"), items[5].price.as_deref());
+        assert_eq!(Some("6-1x.jpg"), items[5].image.as_deref());
+        assert!(Html::parse_document("<p>No products.</p>")
+            .extract_listing()
+            .is_empty());
+    }
+
+    #[test]
+    fn ties_go_to_the_first_group_in_document_order() {
+        let cards = |tag: &str, name: &str| -> String {
+            (1..=3)
+                .map(|n| {
+                    format!("<{tag}><a href='/{name}/{n}'><img src='{n}.jpg'>{name} {n}</a> $1.00</{tag}>")
+                })
+                .collect()
+        };
+        let html = Html::parse_document(&format!(
+            "<div>{}{}</div>",
+            cards("article", "First"),
+            cards("section", "Second")
+        ));
+        let items = html.extract_listing();
+        assert_eq!(3, items.len());
+        assert_eq!(Some("/First/1"), items[0].url.as_deref());
+    }
+}
@@ -0,0 +1,228 @@
+//! Cleanup and infobox extraction for MediaWiki articles.
+
+use ego_tree::NodeId;
+
+use crate::element_ref::ElementRef;
+use crate::html::Html;
+use crate::selector::Selector;
+
+lazy_static! {
+    static ref EDIT_LINKS: Selector = Selector::parse(".mw-editsection").unwrap();
+    static ref REFERENCES: Selector =
+        Selector::parse("sup.reference, sup.noprint, .mw-ref").unwrap();
+    static ref NAVBOXES: Selector = Selector::parse(
+        ".navbox, .vertical-navbox, .navbox-styles, .sidebar, .sistersitebox, .catlinks"
+    )
+    .unwrap();
+    static ref INFOBOX: Selector =
+        Selector::parse("table.infobox, table.infobox_v2, table.infobox_v3").unwrap();
+    static ref ROWS: Selector = Selector::parse("tr").unwrap();
+    static ref CELLS: Selector = Selector::parse(":scope > th, :scope > td").unwrap();
+    static ref CAPTION: Selector = Selector::parse(":scope > caption").unwrap();
+    static ref IMAGE: Selector = Selector::parse("img[src]").unwrap();
+}
+
+/// What [`MediaWikiPreset::apply`] removes from MediaWiki output, such as Wikipedia
+/// articles. Everything is removed by default.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MediaWikiPreset {
+    /// `[edit]` links next to headings.
+    pub edit_links: bool,
+    /// Bracketed reference markers such as `[1]` and `[citation needed]`.
+    pub references: bool,
+    /// Navigation boxes, sidebars, sister-project boxes and category links.
+    pub navboxes: bool,
+    /// The infobox, after extracting it, since it repeats facts from the article text.
+    pub infobox: bool,
+}
+
+impl Default for MediaWikiPreset {
+    fn default() -> Self {
+        MediaWikiPreset {
+            edit_links: true,
+            references: true,
+            navboxes: true,
+            infobox: true,
+        }
+    }
+}
+
+/// The key/value summary table of a MediaWiki article.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Infobox {
+    /// The caption or heading row, usually the article subject.
+    pub title: Option<String>,
+    /// The `src` of the first image.
+    pub image: Option<String>,
+    /// The label and value of each row, in order.
+    pub fields: Vec<(String, String)>,
+}
+
+impl Infobox {
+    /// Returns the value of the first field with this label, ignoring case.
+    pub fn get(&self, label: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(l, _)| l.eq_ignore_ascii_case(label))
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn extract(table: ElementRef) -> Self {
+        let mut infobox = Infobox {
+            title: table
+                .select(&CAPTION)
+                .next()
+                .map(|caption| caption.inner_text())
+                .filter(|title| !title.is_empty()),
+            image: table
+                .select(&IMAGE)
+                .next()
+                .and_then(|img| img.value().attr("src"))
+                .map(str::to_owned),
+            fields: Vec::new(),
+        };
+        // Rows of nested tables belong to the outer cell that holds them.
+        let rows = table.select(&ROWS).filter(|row| {
+            row.ancestors()
+                .filter_map(ElementRef::wrap)
+                .find(|a| a.value().name() == "table")
+                .is_some_and(|t| t.id() == table.id())
+        });
+        for row in rows {
+            let cells: Vec<ElementRef> = row.select(&CELLS).collect();
+            match cells.as_slice() {
+                [label, value] if label.value().name() == "th" => {
+                    let label = label.inner_text();
+                    let value = value.inner_text();
+                    if !label.is_empty() && !value.is_empty() {
+                        infobox.fields.push((label, value));
+                    }
+                }
+                [heading] if heading.value().name() == "th" && infobox.title.is_none() => {
+                    let title = heading.inner_text();
+                    infobox.title = (!title.is_empty()).then_some(title);
+                }
+                _ => {}
+            }
+        }
+        infobox
+    }
+}
+
+/// The outcome of [`MediaWikiPreset::apply`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MediaWikiCleanup {
+    /// The infobox of the article, extracted before it was removed.
+    pub infobox: Option<Infobox>,
+    /// The number of elements removed, not counting those inside other removed elements.
+    pub removed: usize,
+}
+
+impl MediaWikiPreset {
+    /// Creates the preset with every cleanup enabled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cleans MediaWiki markup in place and extracts the infobox.
+    ///
+    /// References are removed before the infobox is read, so its values carry no `[1]`
+    /// markers.
+    ///
+    /// ```
+    /// use scraper::presets::MediaWikiPreset;
+    /// use scraper::Html;
+    ///
+    /// let mut html = Html::parse_document(r##"
+    ///     <h2>History<span class="mw-editsection">[edit]</span></h2>
+    ///     <table class="infobox"><caption>Rust</caption>
+    ///       <tr><th>Designed by</th><td>Graydon Hoare<sup class="reference">[1]</sup></td></tr>
+    ///     </table>
+    ///     <p>Rust began in 2006.<sup class="reference"><a href="#cite-1">[2]</a></sup></p>
+    ///     <div class="navbox">Programming languages</div>
+    /// "##);
+    /// let cleanup = MediaWikiPreset::new().apply(&mut html);
+    /// let infobox = cleanup.infobox.unwrap();
+    /// assert_eq!(Some("Rust"), infobox.title.as_deref());
+    /// assert_eq!(Some("Graydon Hoare"), infobox.get("designed by"));
+    /// assert_eq!("History Rust began in 2006.", html.normalized_text().as_str());
+    /// ```
+    pub fn apply(&self, html: &mut Html) -> MediaWikiCleanup {
+        let mut removed = 0;
+        let mut remove = |html: &mut Html, selector: &Selector| {
+            // Matches inside an earlier match are removed with it and not counted.
+            let mut ids: Vec<NodeId> = Vec::new();
+            for element in html.root_element().select(selector) {
+                if !element.ancestors().any(|a| ids.contains(&a.id())) {
+                    ids.push(element.id());
+                }
+            }
+            removed += ids.len();
+            for id in ids {
+                html.remove_node(id);
+            }
+        };
+
+        if self.edit_links {
+            remove(html, &EDIT_LINKS);
+        }
+        if self.references {
+            remove(html, &REFERENCES);
+        }
+        let infobox = html
+            .root_element()
+            .select(&INFOBOX)
+            .next()
+            .map(Infobox::extract);
+        if self.infobox {
+            remove(html, &INFOBOX);
+        }
+        if self.navboxes {
+            remove(html, &NAVBOXES);
+        }
+        MediaWikiCleanup { infobox, removed }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MediaWikiPreset;
+    use crate::html::Html;
+
+    #[test]
+    fn nested_tables_and_disabled_cleanups() {
+        let mut html = Html::parse_document(
+            "<table class='infobox'><tr><th colspan='2'>Ada Lovelace</th></tr>\
+             <tr><td colspan='2'><img src='ada.jpg'></td></tr>\
+             <tr><th>Known for</th><td><table><tr><th>x</th><td>y</td></tr></table></td></tr>\
+             </table><p>Text<sup class='reference'>[1]</sup></p>",
+        );
+        let preset = MediaWikiPreset {
+            references: false,
+            infobox: false,
+            ..MediaWikiPreset::default()
+        };
+        let cleanup = preset.apply(&mut html);
+        let infobox = cleanup.infobox.unwrap();
+        assert_eq!(Some("Ada Lovelace"), infobox.title.as_deref());
+        assert_eq!(Some("ada.jpg"), infobox.image.as_deref());
+        assert_eq!(
+            vec![("Known for".to_owned(), "x y".to_owned())],
+            infobox.fields
+        );
+        assert_eq!(0, cleanup.removed);
+        assert!(html.html().contains("[1]"));
+    }
+
+    #[test]
+    fn counts_only_outermost_removals() {
+        let mut html = Html::parse_document(
+            "<div class='navbox'><div class='navbox'>Inner</div></div>\
+             <p>Text<sup class='reference'>[1]</sup><sup class='reference'>[2]</sup></p>",
+        );
+        let cleanup = MediaWikiPreset::new().apply(&mut html);
+        assert_eq!(3, cleanup.removed);
+        assert_eq!("Text", html.normalized_text().as_str());
+    }
+}
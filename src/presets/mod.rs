@@ -0,0 +1,13 @@
+//! Opt-in extractors and cleanups for widespread kinds of pages.
+
+mod comments;
+mod listing;
+mod mediawiki;
+#[cfg(feature = "serp")]
+mod serp;
+
+pub use self::comments::{Comment, CommentPattern, CommentPatterns};
+pub use self::listing::ListingItem;
+pub use self::mediawiki::{Infobox, MediaWikiCleanup, MediaWikiPreset};
+#[cfg(feature = "serp")]
+pub use self::serp::SerpResult;
@@ -0,0 +1,241 @@
+//! Organic results of search engine result pages.
+
+use std::collections::HashSet;
+
+use ego_tree::NodeId;
+use url::Url;
+
+use crate::element_ref::ElementRef;
+use crate::html::Html;
+use crate::selector::Selector;
+
+lazy_static! {
+    static ref TITLE_LINKS: Selector =
+        Selector::parse("a[href] h3, a[href] h2, h3 a[href], h2 a[href], a.result__a[href]")
+            .unwrap();
+    static ref SNIPPETS: Selector = Selector::parse(
+        ".VwiC3b, [data-sncf], .IsZvec, .b_caption p, .b_lineclamp2, .b_lineclamp3, \
+         .result__snippet, [data-result=\"snippet\"], .compText"
+    )
+    .unwrap();
+    static ref DISPLAY_URLS: Selector =
+        Selector::parse("cite, .result__url, .b_attribution").unwrap();
+    static ref TEXT: Selector = Selector::parse("p, span, div").unwrap();
+}
+
+/// An organic result of a search engine result page.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SerpResult {
+    /// The 1-based rank among the organic results of the page.
+    pub position: usize,
+    /// The result heading.
+    pub title: String,
+    /// The target of the result, unwrapped from the engine's redirect link.
+    pub url: Url,
+    /// The URL as shown under the title, such as `example.com › docs`.
+    pub display_url: Option<String>,
+    /// The description under the title.
+    pub snippet: Option<String>,
+}
+
+/// Returns true if the element is part of an ad block.
+fn is_ad(element: ElementRef) -> bool {
+    std::iter::once(*element)
+        .chain(element.ancestors())
+        .filter_map(|n| n.value().as_element())
+        .any(|e| {
+            matches!(e.id(), Some("tads" | "bottomads" | "tadsb"))
+                || e.attr("data-text-ad").is_some()
+                || e.classes()
+                    .any(|c| matches!(c, "b_ad" | "b_adTop" | "result--ad" | "ads-ad"))
+        })
+}
+
+/// Resolves a result link, unwrapping the redirects of Google (`/url?q=`), DuckDuckGo
+/// (`/l/?uddg=`) and Yahoo (`/RU=`). Links that stay on the engine are not results.
+fn target(base: &Url, href: &str) -> Option<Url> {
+    let url = base.join(href.trim()).ok()?;
+    let unwrapped = url
+        .query_pairs()
+        .find(|(key, _)| {
+            matches!(
+                (url.path(), &**key),
+                ("/url", "q" | "url") | ("/l/", "uddg") | ("/link", "url")
+            )
+        })
+        .and_then(|(_, value)| Url::parse(&value).ok())
+        .or_else(|| {
+            let (_, rest) = url.path().split_once("/RU=")?;
+            let encoded = rest.split('/').next()?;
+            let decoded: String = url::form_urlencoded::parse(format!("u={encoded}").as_bytes())
+                .next()?
+                .1
+                .into_owned();
+            Url::parse(&decoded).ok()
+        })
+        .unwrap_or(url);
+    let organic =
+        matches!(unwrapped.scheme(), "http" | "https") && unwrapped.host_str() != base.host_str();
+    organic.then_some(unwrapped)
+}
+
+/// Returns the link that is or contains a title match.
+fn link_of(element: ElementRef) -> Option<ElementRef> {
+    std::iter::once(*element)
+        .chain(element.ancestors())
+        .filter_map(ElementRef::wrap)
+        .find(|a| a.value().name() == "a")
+}
+
+fn text(element: ElementRef) -> Option<String> {
+    let text = element.inner_text();
+    (!text.is_empty()).then_some(text)
+}
+
+impl Html {
+    /// Extracts the organic results of a search engine result page, in rank order.
+    ///
+    /// Results are found from their headings rather than per-engine layouts: a heading
+    /// link, the largest element around it holding no other heading link, and the known
+    /// snippet classes of Google, Bing, DuckDuckGo and Yahoo or else the longest text in the
+    /// result. Ads, links back to the engine and repeated targets are skipped. `base` is the
+    /// URL of the result page, which resolves relative redirect links.
+    ///
+    /// ```
+    /// use scraper::Html;
+    /// use url::Url;
+    ///
+    /// let html = Html::parse_document(r#"
+    ///     <div id="tads"><div><a href="https://ads.example/"><h3>Sponsored</h3></a></div></div>
+    ///     <div id="search">
+    ///       <div class="g"><a href="/url?q=https://www.rust-lang.org/&amp;sa=U"><h3>Rust</h3>
+    ///         <cite>www.rust-lang.org</cite></a>
+    ///         <div class="VwiC3b">A language empowering everyone.</div></div>
+    ///       <div class="g"><a href="https://doc.rust-lang.org/book/"><h3>The Book</h3></a>
+    ///         <span>An introductory book about Rust, from first steps to advanced topics.</span></div>
+    ///       <a href="/search?q=rust&amp;start=10"><h3>Next</h3></a>
+    ///     </div>
+    /// "#);
+    /// let base = Url::parse("https://www.google.com/search?q=rust").unwrap();
+    /// let results = html.extract_serp_results(&base);
+    /// assert_eq!(2, results.len());
+    /// assert_eq!("https://www.rust-lang.org/", results[0].url.as_str());
+    /// assert_eq!(Some("www.rust-lang.org"), results[0].display_url.as_deref());
+    /// assert_eq!(Some("A language empowering everyone."), results[0].snippet.as_deref());
+    /// assert_eq!((2, "The Book"), (results[1].position, results[1].title.as_str()));
+    /// assert!(results[1].snippet.as_deref().unwrap().starts_with("An introductory"));
+    /// ```
+    pub fn extract_serp_results(&self, base: &Url) -> Vec<SerpResult> {
+        let root = self.root_element();
+        let links: HashSet<NodeId> = root
+            .select(&TITLE_LINKS)
+            .filter_map(link_of)
+            .map(|a| a.id())
+            .collect();
+        let title_links = |e: ElementRef| {
+            std::iter::once(*e)
+                .chain(e.descendants())
+                .filter(|n| links.contains(&n.id()))
+                .count()
+        };
+
+        let mut seen = HashSet::new();
+        let mut results = Vec::new();
+        let mut done = HashSet::new();
+        for found in root.select(&TITLE_LINKS) {
+            let Some(link) = link_of(found) else { continue };
+            if !done.insert(link.id()) || is_ad(link) {
+                continue;
+            }
+            let Some(url) = link.value().attr("href").and_then(|h| target(base, h)) else {
+                continue;
+            };
+            let heading = std::iter::once(*found)
+                .chain(found.descendants())
+                .filter_map(ElementRef::wrap)
+                .find(|e| matches!(e.value().name(), "h2" | "h3"))
+                .or_else(|| {
+                    found
+                        .ancestors()
+                        .filter_map(ElementRef::wrap)
+                        .find(|e| matches!(e.value().name(), "h2" | "h3"))
+                })
+                .unwrap_or(link);
+            let Some(title) = text(heading) else { continue };
+            if !seen.insert(url.clone()) {
+                continue;
+            }
+
+            let mut block = link;
+            while let Some(parent) = block.parent().and_then(ElementRef::wrap) {
+                if parent.id() == root.id() || title_links(parent) > 1 {
+                    break;
+                }
+                block = parent;
+            }
+            let in_title = |e: &ElementRef| {
+                std::iter::once(**e)
+                    .chain(e.ancestors())
+                    .any(|a| a.id() == heading.id())
+            };
+            let display = block.select(&DISPLAY_URLS).next();
+            let snippet = block
+                .select(&SNIPPETS)
+                .find(|e| !in_title(e))
+                .and_then(text)
+                .or_else(|| {
+                    block
+                        .select(&TEXT)
+                        .filter(|e| {
+                            title_links(*e) == 0
+                                && !e.ancestors().any(|a| a.id() == link.id())
+                                && e.select(&DISPLAY_URLS).next().is_none()
+                                && display.is_none_or(|d| !d.ancestors().any(|a| a.id() == e.id()))
+                        })
+                        .filter_map(text)
+                        .max_by_key(|t| t.chars().count())
+                });
+
+            results.push(SerpResult {
+                position: results.len() + 1,
+                title,
+                url,
+                display_url: display.and_then(text),
+                snippet,
+            });
+        }
+        results
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use url::Url;
+
+    use crate::html::Html;
+
+    #[test]
+    fn bing_and_duckduckgo_layouts() {
+        let bing = Html::parse_document(
+            "<ol id='b_results'><li class='b_ad'><h2><a href='https://ad.example/'>Ad</a></h2></li>\
+             <li class='b_algo'><h2><a href='https://example.com/a'>A</a></h2>\
+             <div class='b_caption'><div class='b_attribution'><cite>example.com/a</cite></div>\
+             <p>About A.</p></div></li>\
+             <li class='b_algo'><h2><a href='https://example.com/a'>A again</a></h2></li></ol>",
+        );
+        let base = Url::parse("https://www.bing.com/search?q=a").unwrap();
+        let results = bing.extract_serp_results(&base);
+        assert_eq!(1, results.len());
+        assert_eq!(Some("About A."), results[0].snippet.as_deref());
+
+        let ddg = Html::parse_document(
+            "<div class='result'><h2 class='result__title'><a class='result__a' \
+             href='//duckduckgo.com/l/?uddg=https%3A%2F%2Fexample.org%2Fb&amp;rut=x'>B</a></h2>\
+             <a class='result__snippet' href='#'>About B.</a></div>",
+        );
+        let base = Url::parse("https://html.duckduckgo.com/html/").unwrap();
+        let results = ddg.extract_serp_results(&base);
+        assert_eq!("https://example.org/b", results[0].url.as_str());
+        assert_eq!(Some("About B."), results[0].snippet.as_deref());
+    }
+}
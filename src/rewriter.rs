@@ -0,0 +1,913 @@
+//! Rewriting of documents in one streaming pass, without building a tree.
+//!
+//! A [`Rewriter`] holds selector and handler pairs. The input is tokenized and written back
+//! out as it is read; when a start tag matches a selector, its handler can change the
+//! attributes, insert markup around or inside the element, or remove it. Only the open
+//! elements and the element siblings preceding them are kept in memory, so large pages where
+//! a few elements need changing are rewritten without the cost of a full [`Html`] tree.
+//!
+//! Selectors are matched when the start tag is read, so they see the element's ancestors
+//! and preceding siblings but nothing after it. [`Rewriter::on`] rejects selectors that
+//! need more: `:empty`, `:has()`, `:last-child`, `:only-child`, `:nth-last-child()` and the
+//! `-of-type` variants of the last two.
+//!
+//! The output is the input re-serialized token by token. Markup that the handlers leave
+//! alone keeps its structure, but not its exact bytes: attributes are double-quoted and
+//! character references are written back only where they are needed.
+//!
+//! [`Html`]: crate::Html
+
+use std::cell::RefCell;
+use std::fmt;
+use std::io;
+
+use cssparser::ToCss;
+use html5ever::tendril::StrTendril;
+use html5ever::tokenizer::states::RawKind;
+use html5ever::tokenizer::{
+    BufferQueue, Doctype, Tag, TagKind, Token, TokenSink, TokenSinkResult, Tokenizer,
+};
+use html5ever::{Attribute, LocalName, Namespace, QualName};
+use selectors::attr::{AttrSelectorOperation, CaseSensitivity, NamespaceConstraint};
+use selectors::{matching, Element, OpaqueElement};
+
+use crate::error::RewriteError;
+use crate::selector::{CssLocalName, CssString, NonTSPseudoClass, PseudoElement, Selector, Simple};
+
+type Handler<'h> = Box<dyn FnMut(&mut RewriteElement) + 'h>;
+
+/// The elements that have no content and no end tag.
+const VOID: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "keygen", "link", "meta", "param",
+    "source", "track", "wbr",
+];
+
+/// The elements whose start tag ends an open `<p>`.
+const CLOSES_P: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "details",
+    "div",
+    "dl",
+    "fieldset",
+    "figcaption",
+    "figure",
+    "footer",
+    "form",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hr",
+    "main",
+    "menu",
+    "nav",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "table",
+    "ul",
+];
+
+/// A set of selector and handler pairs applied to documents in one streaming pass.
+///
+/// ```
+/// use scraper::rewriter::Rewriter;
+/// use scraper::Selector;
+///
+/// let mut rewriter = Rewriter::new();
+/// rewriter
+///     .on(Selector::parse("a[href^='http:']").unwrap(), |a| {
+///         let href = a.attr("href").unwrap().replacen("http:", "https:", 1);
+///         a.set_attr("href", &href);
+///     })?
+///     .on(Selector::parse("script").unwrap(), |script| script.remove())?;
+///
+/// let html = rewriter.rewrite(
+///     r#"<p><a href="http://example.com/">Example</a><script>track("<a>")</script></p>"#,
+/// );
+/// assert_eq!(r#"<p><a href="https://example.com/">Example</a></p>"#, html);
+/// # Ok::<(), scraper::error::RewriteError>(())
+/// ```
+#[derive(Default)]
+pub struct Rewriter<'h> {
+    handlers: Vec<(Selector, Handler<'h>)>,
+}
+
+impl<'h> Rewriter<'h> {
+    /// Creates a rewriter without handlers, which writes documents back unchanged.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a handler called with each element matching `selector`. Handlers of the
+    /// same element run in registration order, after all of them were matched against the
+    /// original start tag. Elements inside removed or replaced content are not matched.
+    ///
+    /// Fails for selectors that cannot be matched from the start tag, listed in the
+    /// [module documentation](self).
+    pub fn on<F>(&mut self, selector: Selector, handler: F) -> Result<&mut Self, RewriteError>
+    where
+        F: FnMut(&mut RewriteElement) + 'h,
+    {
+        if !selector.is_streamable() {
+            return Err(RewriteError::Unstreamable(selector.to_css_string()));
+        }
+        self.handlers.push((selector, Box::new(handler)));
+        Ok(self)
+    }
+
+    /// Rewrites a whole document.
+    pub fn rewrite(&mut self, html: &str) -> String {
+        let mut stream = self.stream(Vec::new());
+        stream
+            .feed(html.as_bytes())
+            .expect("writing to a Vec cannot fail");
+        let out = stream.finish().expect("writing to a Vec cannot fail");
+        String::from_utf8(out).expect("the output is built from strings")
+    }
+
+    /// Starts rewriting a document whose UTF-8 bytes arrive in chunks, writing the output to
+    /// `out` as the input is read.
+    pub fn stream<W: io::Write>(&mut self, out: W) -> RewriteStream<'_, 'h, W> {
+        let state = State {
+            handlers: &mut self.handlers,
+            out,
+            error: None,
+            stack: Vec::new(),
+            levels: vec![Vec::new()],
+            skip: None,
+        };
+        RewriteStream {
+            tokenizer: Tokenizer::new(
+                Sink {
+                    state: RefCell::new(state),
+                },
+                Default::default(),
+            ),
+            input: BufferQueue::default(),
+            partial: Vec::new(),
+        }
+    }
+}
+
+impl fmt::Debug for Rewriter<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let selectors: Vec<_> = self.handlers.iter().map(|(s, _)| s).collect();
+        f.debug_struct("Rewriter")
+            .field("selectors", &selectors)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A rewrite in progress, returned by [`Rewriter::stream`].
+///
+/// Chunks may split multi-byte characters; invalid sequences become U+FFFD. It also
+/// implements [`io::Write`], so a reader can be copied into it with [`io::copy`].
+pub struct RewriteStream<'r, 'h, W: io::Write> {
+    tokenizer: Tokenizer<Sink<'r, 'h, W>>,
+    input: BufferQueue,
+    /// The trailing bytes of an incomplete character.
+    partial: Vec<u8>,
+}
+
+impl<W: io::Write> RewriteStream<'_, '_, W> {
+    /// Rewrites the next chunk of the document. Output for markup at the end of the chunk
+    /// may be held back until the next chunk shows where the markup ends.
+    pub fn feed(&mut self, chunk: &[u8]) -> io::Result<()> {
+        self.partial.extend_from_slice(chunk);
+        let rest = self.partial.split_off(complete_len(&self.partial));
+        let text = String::from_utf8_lossy(&self.partial);
+        self.input.push_back(StrTendril::from_slice(&text));
+        self.partial = rest;
+        let _ = self.tokenizer.feed(&self.input);
+        match self.tokenizer.sink.state.borrow_mut().error.take() {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    /// Ends the input, writes the rest of the output and returns the writer.
+    pub fn finish(self) -> io::Result<W> {
+        if !self.partial.is_empty() {
+            let text = String::from_utf8_lossy(&self.partial).into_owned();
+            self.input.push_back(StrTendril::from(text));
+            let _ = self.tokenizer.feed(&self.input);
+        }
+        self.tokenizer.end();
+        let mut state = self.tokenizer.sink.state.into_inner();
+        match state.error {
+            Some(err) => Err(err),
+            None => state.out.flush().map(|()| state.out),
+        }
+    }
+}
+
+impl<W: io::Write> fmt::Debug for RewriteStream<'_, '_, W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RewriteStream").finish_non_exhaustive()
+    }
+}
+
+impl<W: io::Write> io::Write for RewriteStream<'_, '_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.feed(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.tokenizer.sink.state.borrow_mut().out.flush()
+    }
+}
+
+/// Returns the length of `bytes` without a trailing incomplete UTF-8 sequence.
+fn complete_len(bytes: &[u8]) -> usize {
+    for back in 1..=bytes.len().min(4) {
+        let byte = bytes[bytes.len() - back];
+        if byte & 0xC0 != 0x80 {
+            let width = match byte {
+                0xC0..=0xDF => 2,
+                0xE0..=0xEF => 3,
+                0xF0..=0xF7 => 4,
+                _ => 1,
+            };
+            return if width > back {
+                bytes.len() - back
+            } else {
+                bytes.len()
+            };
+        }
+    }
+    bytes.len()
+}
+
+/// An element matched by a handler of a [`Rewriter`], as seen at its start tag.
+///
+/// Markup given to the methods is written as is; [`set_inner_text`] escapes text instead.
+/// Content can only be inserted into elements that have content, so `prepend`, `append`
+/// and the `set_inner_` methods do nothing on void elements such as `<img>`.
+///
+/// [`set_inner_text`]: RewriteElement::set_inner_text
+#[derive(Debug)]
+pub struct RewriteElement {
+    name: LocalName,
+    attrs: Vec<Attribute>,
+    before: String,
+    prepend: String,
+    append: String,
+    after: String,
+    inner: Option<String>,
+    replacement: Option<String>,
+    keep_content: bool,
+}
+
+impl RewriteElement {
+    /// Returns the tag name, in lowercase.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the value of an attribute.
+    pub fn attr(&self, name: &str) -> Option<&str> {
+        self.attrs
+            .iter()
+            .find(|attr| &*attr.name.local == name)
+            .map(|attr| &*attr.value)
+    }
+
+    /// Returns an iterator over the attributes, in source order.
+    pub fn attrs(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.attrs
+            .iter()
+            .map(|attr| (&*attr.name.local, &*attr.value))
+    }
+
+    /// Sets an attribute, replacing its value if the element has it.
+    pub fn set_attr(&mut self, name: &str, value: &str) {
+        match self.attrs.iter_mut().find(|attr| &*attr.name.local == name) {
+            Some(attr) => attr.value = StrTendril::from_slice(value),
+            None => self.attrs.push(Attribute {
+                name: QualName::new(None, ns!(), LocalName::from(name)),
+                value: StrTendril::from_slice(value),
+            }),
+        }
+    }
+
+    /// Removes an attribute.
+    pub fn remove_attr(&mut self, name: &str) {
+        self.attrs.retain(|attr| &*attr.name.local != name);
+    }
+
+    /// Inserts markup before the start tag.
+    pub fn before(&mut self, html: &str) {
+        self.before.push_str(html);
+    }
+
+    /// Inserts markup after the end tag.
+    pub fn after(&mut self, html: &str) {
+        self.after.push_str(html);
+    }
+
+    /// Inserts markup at the start of the content.
+    pub fn prepend(&mut self, html: &str) {
+        self.prepend.push_str(html);
+    }
+
+    /// Inserts markup at the end of the content.
+    pub fn append(&mut self, html: &str) {
+        self.append.push_str(html);
+    }
+
+    /// Replaces the content with markup, dropping what was prepended or appended so far.
+    pub fn set_inner_html(&mut self, html: &str) {
+        self.prepend.clear();
+        self.append.clear();
+        self.inner = Some(html.to_owned());
+    }
+
+    /// Replaces the content with text.
+    pub fn set_inner_text(&mut self, text: &str) {
+        let mut html = String::new();
+        escape(text, false, &mut html);
+        self.set_inner_html(&html);
+    }
+
+    /// Replaces the element and its content with markup.
+    pub fn replace(&mut self, html: &str) {
+        self.replacement = Some(html.to_owned());
+    }
+
+    /// Removes the element and its content.
+    pub fn remove(&mut self) {
+        self.replace("");
+    }
+
+    /// Removes the start and end tags but keeps the content.
+    pub fn remove_and_keep_content(&mut self) {
+        self.keep_content = true;
+    }
+
+    /// Returns true if the element was removed or replaced.
+    pub fn is_removed(&self) -> bool {
+        self.replacement.is_some()
+    }
+}
+
+/// Escapes text or an attribute value the way the serializer of the [`Html`] tree does.
+///
+/// [`Html`]: crate::Html
+fn escape(text: &str, attribute: bool, out: &mut String) {
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '\u{a0}' => out.push_str("&nbsp;"),
+            '"' if attribute => out.push_str("&quot;"),
+            '<' if !attribute => out.push_str("&lt;"),
+            '>' if !attribute => out.push_str("&gt;"),
+            c => out.push(c),
+        }
+    }
+}
+
+/// What the selectors see of an element that was read.
+#[derive(Debug)]
+struct Info {
+    name: QualName,
+    attrs: Vec<Attribute>,
+}
+
+/// An element whose end has not been read yet.
+#[derive(Debug)]
+struct Open {
+    name: LocalName,
+    ns: Namespace,
+    /// The namespace of the children.
+    child_ns: Namespace,
+    /// Whether the text content is written without escaping.
+    raw: bool,
+    append: String,
+    after: String,
+    end_tag: bool,
+}
+
+struct State<'r, 'h, W> {
+    handlers: &'r mut [(Selector, Handler<'h>)],
+    out: W,
+    /// The first write error, reported by the next `feed` or `finish`.
+    error: Option<io::Error>,
+    stack: Vec<Open>,
+    /// The elements read so far at each depth that are children of the open element at
+    /// the depth above, or of the document at depth 0.
+    levels: Vec<Vec<Info>>,
+    /// Set inside removed or replaced content to the depth where it starts, the depth of
+    /// the children of the element whose content is dropped.
+    skip: Option<usize>,
+}
+
+impl<W: io::Write> State<'_, '_, W> {
+    fn write(&mut self, text: &str) {
+        if self.error.is_none() && !text.is_empty() {
+            if let Err(err) = self.out.write_all(text.as_bytes()) {
+                self.error = Some(err);
+            }
+        }
+    }
+
+    fn token(&mut self, token: Token) -> TokenSinkResult<()> {
+        match token {
+            Token::TagToken(tag) if tag.kind == TagKind::StartTag => return self.start_tag(tag),
+            Token::TagToken(tag) => self.end_tag(tag),
+            Token::CharacterTokens(text) if self.skip.is_none() => {
+                if self.stack.last().is_some_and(|open| open.raw) {
+                    self.write(&text);
+                } else {
+                    let mut escaped = String::with_capacity(text.len());
+                    escape(&text, false, &mut escaped);
+                    self.write(&escaped);
+                }
+            }
+            Token::CommentToken(text) if self.skip.is_none() => {
+                self.write(&format!("<!--{}-->", text));
+            }
+            Token::DoctypeToken(doctype) if self.skip.is_none() => {
+                self.write(&serialize_doctype(&doctype));
+            }
+            Token::EOFToken => {
+                while !self.stack.is_empty() {
+                    self.close(false);
+                }
+            }
+            _ => {}
+        }
+        TokenSinkResult::Continue
+    }
+
+    fn start_tag(&mut self, tag: Tag) -> TokenSinkResult<()> {
+        while let Some(open) = self.stack.last() {
+            if open.ns != ns!(html) || !implies_end(&open.name, &tag.name) {
+                break;
+            }
+            self.close(false);
+        }
+
+        let parent_ns = self
+            .stack
+            .last()
+            .map_or(ns!(html), |open| open.child_ns.clone());
+        let ns = match &*tag.name {
+            "svg" => ns!(svg),
+            "math" => ns!(mathml),
+            _ => parent_ns,
+        };
+        let html = ns == ns!(html);
+        let void = if html {
+            VOID.contains(&&*tag.name)
+        } else {
+            tag.self_closing
+        };
+
+        let depth = self.stack.len();
+        self.levels[depth].push(Info {
+            name: QualName::new(None, ns.clone(), tag.name.clone()),
+            attrs: tag.attrs.clone(),
+        });
+        let mut element = RewriteElement {
+            name: tag.name.clone(),
+            attrs: tag.attrs,
+            before: String::new(),
+            prepend: String::new(),
+            append: String::new(),
+            after: String::new(),
+            inner: None,
+            replacement: None,
+            keep_content: false,
+        };
+        if self.skip.is_none() {
+            let cursor = Cursor {
+                levels: &self.levels,
+                depth,
+                index: self.levels[depth].len() - 1,
+            };
+            let matched: Vec<usize> = self
+                .handlers
+                .iter()
+                .enumerate()
+                .filter(|(_, (selector, _))| selector.matches_element(&cursor, None))
+                .map(|(i, _)| i)
+                .collect();
+            for i in matched {
+                (self.handlers[i].1)(&mut element);
+            }
+        }
+
+        let skipping = self.skip.is_some();
+        if !skipping {
+            self.write(&element.before);
+            if let Some(replacement) = element.replacement.as_deref() {
+                self.write(replacement);
+            } else {
+                if !element.keep_content {
+                    self.write(&serialize_start_tag(
+                        &element.name,
+                        &element.attrs,
+                        tag.self_closing,
+                    ));
+                }
+                if !void {
+                    self.write(&element.prepend);
+                    if let Some(inner) = element.inner.as_deref() {
+                        self.write(inner);
+                    }
+                }
+            }
+        }
+
+        if void {
+            if !skipping {
+                self.write(&element.after);
+            }
+            return TokenSinkResult::Continue;
+        }
+
+        let child_ns = match &*tag.name {
+            "foreignobject" | "desc" | "title" if ns == ns!(svg) => ns!(html),
+            _ => ns.clone(),
+        };
+        let removed = element.replacement.is_some();
+        let raw = html
+            && matches!(
+                &*tag.name,
+                "script" | "style" | "xmp" | "iframe" | "noembed" | "noframes" | "plaintext"
+            );
+        self.stack.push(Open {
+            name: tag.name.clone(),
+            ns,
+            child_ns,
+            raw,
+            append: if removed {
+                String::new()
+            } else {
+                element.append
+            },
+            after: element.after,
+            end_tag: !removed && !element.keep_content,
+        });
+        self.levels.push(Vec::new());
+        if !skipping && (removed || element.inner.is_some()) {
+            self.skip = Some(self.stack.len());
+        }
+
+        if !html {
+            return TokenSinkResult::Continue;
+        }
+        match &*tag.name {
+            "script" => TokenSinkResult::RawData(RawKind::ScriptData),
+            "style" | "xmp" | "iframe" | "noembed" | "noframes" => {
+                TokenSinkResult::RawData(RawKind::Rawtext)
+            }
+            "title" | "textarea" => TokenSinkResult::RawData(RawKind::Rcdata),
+            "plaintext" => TokenSinkResult::Plaintext,
+            _ => TokenSinkResult::Continue,
+        }
+    }
+
+    fn end_tag(&mut self, tag: Tag) {
+        match self.stack.iter().rposition(|open| open.name == tag.name) {
+            Some(index) => {
+                while self.stack.len() > index + 1 {
+                    self.close(false);
+                }
+                self.close(true);
+            }
+            // A stray end tag is kept, as the tree builder makes something of some of them.
+            None if self.skip.is_none() => self.write(&format!("</{}>", tag.name)),
+            None => {}
+        }
+    }
+
+    /// Closes the innermost open element, writing its end tag if it was read.
+    fn close(&mut self, explicit: bool) {
+        let Some(open) = self.stack.pop() else {
+            return;
+        };
+        let depth = self.stack.len();
+        self.levels.truncate(depth + 1);
+        match self.skip {
+            Some(skip) if depth >= skip => return,
+            Some(skip) if depth + 1 == skip => self.skip = None,
+            _ => {}
+        }
+        self.write(&open.append);
+        if open.end_tag && explicit {
+            self.write(&format!("</{}>", open.name));
+        }
+        self.write(&open.after);
+    }
+}
+
+/// Returns true if a start tag implicitly ends the open element, as the tree builder
+/// would when the element is the current node.
+fn implies_end(open: &str, start: &str) -> bool {
+    match open {
+        "p" => CLOSES_P.contains(&start),
+        "li" => start == "li",
+        "dt" | "dd" => matches!(start, "dt" | "dd"),
+        "option" => matches!(start, "option" | "optgroup"),
+        "tr" => start == "tr",
+        "td" | "th" => matches!(start, "td" | "th" | "tr"),
+        _ => false,
+    }
+}
+
+fn serialize_start_tag(name: &str, attrs: &[Attribute], self_closing: bool) -> String {
+    let mut tag = format!("<{}", name);
+    for attr in attrs {
+        tag.push(' ');
+        tag.push_str(&attr.name.local);
+        tag.push_str("=\"");
+        escape(&attr.value, true, &mut tag);
+        tag.push('"');
+    }
+    if self_closing {
+        tag.push('/');
+    }
+    tag.push('>');
+    tag
+}
+
+fn serialize_doctype(doctype: &Doctype) -> String {
+    let mut out = String::from("<!DOCTYPE");
+    if let Some(name) = &doctype.name {
+        out.push(' ');
+        out.push_str(name);
+    }
+    match (&doctype.public_id, &doctype.system_id) {
+        (Some(public), system) => {
+            out.push_str(&format!(" PUBLIC \"{}\"", public));
+            if let Some(system) = system {
+                out.push_str(&format!(" \"{}\"", system));
+            }
+        }
+        (None, Some(system)) => out.push_str(&format!(" SYSTEM \"{}\"", system)),
+        (None, None) => {}
+    }
+    out.push('>');
+    out
+}
+
+struct Sink<'r, 'h, W> {
+    state: RefCell<State<'r, 'h, W>>,
+}
+
+impl<W: io::Write> TokenSink for Sink<'_, '_, W> {
+    type Handle = ();
+
+    fn process_token(&self, token: Token, _line_number: u64) -> TokenSinkResult<()> {
+        self.state.borrow_mut().token(token)
+    }
+
+    fn adjusted_current_node_present_but_not_in_html_namespace(&self) -> bool {
+        self.state
+            .borrow()
+            .stack
+            .last()
+            .is_some_and(|open| open.ns != ns!(html))
+    }
+}
+
+/// An element read by the rewriter, for matching selectors.
+#[derive(Debug, Clone, Copy)]
+struct Cursor<'a> {
+    levels: &'a [Vec<Info>],
+    depth: usize,
+    index: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn info(&self) -> &'a Info {
+        &self.levels[self.depth][self.index]
+    }
+
+    fn attr(&self, name: &str) -> Option<&'a str> {
+        self.info()
+            .attrs
+            .iter()
+            .find(|attr| &*attr.name.local == name)
+            .map(|attr| &*attr.value)
+    }
+}
+
+/// Note: will never match against non-tree-structure pseudo-classes, and knows nothing
+/// about the content or following siblings of an element.
+impl Element for Cursor<'_> {
+    type Impl = Simple;
+
+    fn opaque(&self) -> OpaqueElement {
+        OpaqueElement::new(self.info())
+    }
+
+    fn parent_element(&self) -> Option<Self> {
+        let depth = self.depth.checked_sub(1)?;
+        Some(Cursor {
+            levels: self.levels,
+            depth,
+            index: self.levels[depth].len() - 1,
+        })
+    }
+
+    fn parent_node_is_shadow_root(&self) -> bool {
+        false
+    }
+
+    fn containing_shadow_host(&self) -> Option<Self> {
+        None
+    }
+
+    fn is_pseudo_element(&self) -> bool {
+        false
+    }
+
+    fn prev_sibling_element(&self) -> Option<Self> {
+        Some(Cursor {
+            index: self.index.checked_sub(1)?,
+            ..*self
+        })
+    }
+
+    fn next_sibling_element(&self) -> Option<Self> {
+        None
+    }
+
+    fn first_element_child(&self) -> Option<Self> {
+        None
+    }
+
+    fn is_html_element_in_html_document(&self) -> bool {
+        self.info().name.ns == ns!(html)
+    }
+
+    fn has_local_name(&self, name: &CssLocalName) -> bool {
+        self.info().name.local == name.0
+    }
+
+    fn has_namespace(&self, namespace: &Namespace) -> bool {
+        &self.info().name.ns == namespace
+    }
+
+    fn is_same_type(&self, other: &Self) -> bool {
+        self.info().name == other.info().name
+    }
+
+    fn attr_matches(
+        &self,
+        ns: &NamespaceConstraint<&Namespace>,
+        local_name: &CssLocalName,
+        operation: &AttrSelectorOperation<&CssString>,
+    ) -> bool {
+        self.info().attrs.iter().any(|attr| {
+            !matches!(*ns, NamespaceConstraint::Specific(url) if *url != attr.name.ns)
+                && local_name.0 == attr.name.local
+                && operation.eval_str(&attr.value)
+        })
+    }
+
+    fn match_non_ts_pseudo_class(
+        &self,
+        _pc: &NonTSPseudoClass,
+        _context: &mut matching::MatchingContext<Self::Impl>,
+    ) -> bool {
+        false
+    }
+
+    fn match_pseudo_element(
+        &self,
+        _pe: &PseudoElement,
+        _context: &mut matching::MatchingContext<Self::Impl>,
+    ) -> bool {
+        false
+    }
+
+    fn apply_selector_flags(&self, _: matching::ElementSelectorFlags) {}
+
+    fn is_link(&self) -> bool {
+        &*self.info().name.local == "link"
+    }
+
+    fn is_html_slot_element(&self) -> bool {
+        true
+    }
+
+    fn has_id(&self, id: &CssLocalName, case_sensitivity: CaseSensitivity) -> bool {
+        self.attr("id")
+            .is_some_and(|value| case_sensitivity.eq(id.0.as_bytes(), value.as_bytes()))
+    }
+
+    fn has_class(&self, name: &CssLocalName, case_sensitivity: CaseSensitivity) -> bool {
+        self.attr("class").is_some_and(|classes| {
+            classes
+                .split_ascii_whitespace()
+                .any(|class| case_sensitivity.eq(name.0.as_bytes(), class.as_bytes()))
+        })
+    }
+
+    fn imported_part(&self, _: &CssLocalName) -> Option<CssLocalName> {
+        None
+    }
+
+    fn is_part(&self, _name: &CssLocalName) -> bool {
+        false
+    }
+
+    fn is_empty(&self) -> bool {
+        false
+    }
+
+    fn is_root(&self) -> bool {
+        self.depth == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Rewriter;
+    use crate::error::RewriteError;
+    use crate::selector::Selector;
+
+    #[test]
+    fn streaming_matches_whole_input() {
+        let input =
+            "<!DOCTYPE html><ul><li>One<li class=x>Tw\u{f6}<li>Three &amp; <b>four</b></ul>\
+                     <p>Intro<div id=ad><p>Gone</p><img src=a.png></div>\
+                     <script>if (a < b) { document.write('<li>') }</script>\
+                     <svg><circle r=1 /></svg><p>Last";
+        let mut count = 0;
+        let mut rewriter = Rewriter::new();
+        rewriter
+            .on(Selector::parse("li.x + li").unwrap(), |li| {
+                li.set_attr("class", "after-x");
+                li.prepend("<i>");
+                li.append("</i>");
+            })
+            .unwrap()
+            .on(Selector::parse("#ad").unwrap(), |ad| {
+                ad.replace("<!-- ad -->")
+            })
+            .unwrap()
+            .on(Selector::parse("li, #ad img").unwrap(), |_| count += 1)
+            .unwrap()
+            .on(Selector::parse("svg > circle").unwrap(), |c| {
+                c.set_attr("r", "2")
+            })
+            .unwrap()
+            .on(Selector::parse("ul ~ p").unwrap(), |p| {
+                p.remove_and_keep_content()
+            })
+            .unwrap();
+
+        let whole = rewriter.rewrite(input);
+        let mut stream = rewriter.stream(Vec::new());
+        for chunk in input.as_bytes().chunks(1) {
+            stream.feed(chunk).unwrap();
+        }
+        let streamed = String::from_utf8(stream.finish().unwrap()).unwrap();
+        drop(rewriter);
+
+        assert_eq!(
+            "<!DOCTYPE html><ul><li>One<li class=\"x\">Tw\u{f6}\
+             <li class=\"after-x\"><i>Three &amp; <b>four</b></i></ul>\
+             Intro<!-- ad -->\
+             <script>if (a < b) { document.write('<li>') }</script>\
+             <svg><circle r=\"2\"/></svg>Last",
+            whole
+        );
+        assert_eq!(whole, streamed);
+        assert_eq!(6, count);
+    }
+
+    #[test]
+    fn rejects_selectors_needing_later_content() {
+        let mut rewriter = Rewriter::new();
+        for selector in [
+            "p:has(b)",
+            "li:last-child",
+            "div:empty",
+            "ul > li:nth-last-child(2)",
+        ] {
+            assert_eq!(
+                Err(RewriteError::Unstreamable(selector.to_owned())),
+                rewriter
+                    .on(Selector::parse(selector).unwrap(), |_| {})
+                    .map(|_| ())
+            );
+        }
+        assert!(rewriter
+            .on(Selector::parse("li:first-child").unwrap(), |_| {})
+            .is_ok());
+    }
+}
@@ -0,0 +1,260 @@
+//! Site-profile routing.
+//!
+//! A [`Router`] maps URL patterns to [`RulePack`]s so a single service can scrape many sites,
+//! each with its own extraction recipe.
+//!
+//! Patterns have the form `host[/path]`:
+//!
+//! - `example.com` matches that host only (a leading `www.` on the URL is ignored).
+//! - `*.example.com` matches `example.com` and every subdomain.
+//! - `*` matches any host.
+//! - `example.com/products/*` additionally requires the path to match; `*` matches any run of
+//!   characters.
+//!
+//! When several routes match, the most specific one (the most literal characters) wins, and
+//! ties go to the route registered first.
+//!
+//! ```
+//! use scraper::router::Router;
+//! use scraper::rules::{Extract, Rule, RulePack};
+//! use scraper::Html;
+//!
+//! let mut products = RulePack::new("products", "1");
+//! products.push(Rule::new("name", "h1", Extract::Text).unwrap()).unwrap();
+//!
+//! let mut router = Router::new();
+//! router.route("*.shop.example/item/*", products);
+//!
+//! let html = Html::parse_document("<h1>Widget</h1>");
+//! let extraction = router
+//!     .extract_for_url("https://eu.shop.example/item/42", &html)
+//!     .unwrap();
+//! assert_eq!(Some("Widget"), extraction.get("name"));
+//! assert!(router.extract_for_url("https://other.example/", &html).is_none());
+//! ```
+
+use url::Url;
+
+use crate::html::Html;
+use crate::rules::{Extraction, RulePack};
+
+/// A `host[/path]` pattern matched against URLs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(from = "String", into = "String"))]
+pub struct UrlPattern {
+    source: String,
+    host: HostPattern,
+    path: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum HostPattern {
+    Any,
+    Exact(String),
+    Subdomains(String),
+}
+
+impl UrlPattern {
+    /// Parses a pattern. Patterns are lenient: anything is accepted, and a scheme prefix such as
+    /// `https://` is ignored.
+    pub fn parse(pattern: &str) -> Self {
+        let trimmed = pattern
+            .split_once("://")
+            .map_or(pattern, |(_, rest)| rest)
+            .trim();
+        let (host, path) = match trimmed.find('/') {
+            Some(idx) => (&trimmed[..idx], Some(trimmed[idx..].to_owned())),
+            None => (trimmed, None),
+        };
+        let host = host.to_ascii_lowercase();
+
+        let host = if host.is_empty() || host == "*" {
+            HostPattern::Any
+        } else if let Some(domain) = host.strip_prefix("*.") {
+            HostPattern::Subdomains(domain.to_owned())
+        } else {
+            HostPattern::Exact(host.strip_prefix("www.").unwrap_or(&host).to_owned())
+        };
+
+        UrlPattern {
+            source: pattern.to_owned(),
+            host,
+            path: path.filter(|p| p != "/*"),
+        }
+    }
+
+    /// Returns the pattern as it was written.
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// Returns true if the URL matches the pattern.
+    pub fn matches(&self, url: &Url) -> bool {
+        let host = url.host_str().unwrap_or_default().to_ascii_lowercase();
+        let host_matches = match &self.host {
+            HostPattern::Any => true,
+            HostPattern::Exact(expected) => host.strip_prefix("www.").unwrap_or(&host) == expected,
+            HostPattern::Subdomains(domain) => {
+                host == *domain
+                    || host
+                        .strip_suffix(domain.as_str())
+                        .is_some_and(|sub| sub.ends_with('.'))
+            }
+        };
+
+        host_matches
+            && self
+                .path
+                .as_deref()
+                .is_none_or(|path| glob_matches(path, url.path()))
+    }
+
+    /// Ranks matching patterns: the more literal characters, the more specific.
+    fn specificity(&self) -> usize {
+        let host = match &self.host {
+            HostPattern::Any => 0,
+            HostPattern::Subdomains(domain) => domain.len(),
+            // An exact host always beats a wildcard over the same domain.
+            HostPattern::Exact(host) => host.len() + 1,
+        };
+        let path = self
+            .path
+            .as_deref()
+            .map_or(0, |p| p.chars().filter(|c| *c != '*').count());
+        host + path
+    }
+}
+
+impl From<String> for UrlPattern {
+    fn from(pattern: String) -> Self {
+        UrlPattern::parse(&pattern)
+    }
+}
+
+impl From<UrlPattern> for String {
+    fn from(pattern: UrlPattern) -> Self {
+        pattern.source
+    }
+}
+
+/// Matches `text` against a glob where `*` matches any run of characters.
+fn glob_matches(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+
+    match parts.split_last() {
+        // No wildcard at all.
+        None => rest.is_empty(),
+        Some((last, middle)) => {
+            for part in middle {
+                match rest.find(part) {
+                    Some(idx) => rest = &rest[idx + part.len()..],
+                    None => return false,
+                }
+            }
+            rest.ends_with(last)
+        }
+    }
+}
+
+/// A pattern and the rule pack it selects.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Route {
+    /// The URL pattern.
+    pub pattern: UrlPattern,
+    /// The pack used for matching URLs.
+    pub pack: RulePack,
+}
+
+/// Maps URL patterns to rule packs.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Router {
+    /// The routes, in registration order.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub routes: Vec<Route>,
+    /// The pack used when no route matches.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub fallback: Option<RulePack>,
+}
+
+impl Router {
+    /// Creates a router without routes.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a pack for a URL pattern.
+    pub fn route(&mut self, pattern: &str, pack: RulePack) -> &mut Self {
+        self.routes.push(Route {
+            pattern: UrlPattern::parse(pattern),
+            pack,
+        });
+        self
+    }
+
+    /// Sets the pack used when no route matches.
+    pub fn fallback(&mut self, pack: RulePack) -> &mut Self {
+        self.fallback = Some(pack);
+        self
+    }
+
+    /// Returns the pack selected for a URL, if any.
+    pub fn pack_for_url(&self, url: &str) -> Option<&RulePack> {
+        let url = Url::parse(url).ok()?;
+        let mut best: Option<&Route> = None;
+
+        for route in self.routes.iter().filter(|r| r.pattern.matches(&url)) {
+            if best.is_none_or(|b| route.pattern.specificity() > b.pattern.specificity()) {
+                best = Some(route);
+            }
+        }
+
+        best.map(|r| &r.pack).or(self.fallback.as_ref())
+    }
+
+    /// Executes the pack selected for a URL against a document.
+    pub fn extract_for_url(&self, url: &str, html: &Html) -> Option<Extraction> {
+        self.pack_for_url(url).map(|pack| pack.execute(html))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{glob_matches, Router};
+    use crate::rules::RulePack;
+
+    #[test]
+    fn globs() {
+        assert!(glob_matches("/a/*", "/a/b/c"));
+        assert!(glob_matches("/a/*/c", "/a/b/c"));
+        assert!(!glob_matches("/a/*/c", "/a/b/d"));
+        assert!(glob_matches("/exact", "/exact"));
+        assert!(!glob_matches("/exact", "/exact/more"));
+    }
+
+    #[test]
+    fn most_specific_route_wins() {
+        let mut router = Router::new();
+        router
+            .route("*", RulePack::new("any", "1"))
+            .route("*.example.com", RulePack::new("site", "1"))
+            .route("example.com/blog/*", RulePack::new("blog", "1"));
+
+        let name = |url| router.pack_for_url(url).map(|p| p.name.as_str());
+        assert_eq!(Some("blog"), name("https://www.example.com/blog/post"));
+        assert_eq!(Some("site"), name("https://shop.example.com/blog/post"));
+        assert_eq!(Some("site"), name("https://example.com/"));
+        assert_eq!(Some("any"), name("https://example.org/"));
+        assert_eq!(None, name("not a url"));
+    }
+}
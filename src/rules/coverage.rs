@@ -0,0 +1,175 @@
+//! Dry-run coverage reports for rule packs.
+
+use super::{Rule, RulePack};
+use crate::html::Html;
+
+/// How many sample values a coverage report keeps per rule by default.
+pub const DEFAULT_SAMPLES: usize = 3;
+
+/// Sample values longer than this many characters are truncated.
+const MAX_SAMPLE_CHARS: usize = 120;
+
+/// Coverage of a single rule against one document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RuleCoverage {
+    /// The rule name.
+    pub rule: String,
+    /// Whether the rule is marked as required.
+    pub required: bool,
+    /// Whether the selector matched at least one element.
+    pub matched: bool,
+    /// How many elements the selector matched, or the fallback that produced the values.
+    pub hits: usize,
+    /// The first few values as [`RulePack::execute`] extracts them, truncated for display.
+    pub samples: Vec<String>,
+}
+
+/// Which rules of a pack matched a document, how often, and with what values.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CoverageReport {
+    /// The name of the pack.
+    pub pack: String,
+    /// The version of the pack.
+    pub version: String,
+    /// One entry per rule, in pack order.
+    pub rules: Vec<RuleCoverage>,
+}
+
+impl CoverageReport {
+    /// Returns an iterator over the rules that matched.
+    pub fn matched(&self) -> impl Iterator<Item = &RuleCoverage> {
+        self.rules.iter().filter(|r| r.matched)
+    }
+
+    /// Returns an iterator over the rules that did not match.
+    pub fn unmatched(&self) -> impl Iterator<Item = &RuleCoverage> {
+        self.rules.iter().filter(|r| !r.matched)
+    }
+
+    /// Returns the fraction of rules that matched, `1.0` for an empty pack.
+    pub fn ratio(&self) -> f64 {
+        if self.rules.is_empty() {
+            1.0
+        } else {
+            self.matched().count() as f64 / self.rules.len() as f64
+        }
+    }
+
+    /// Serializes the report to JSON for dashboards.
+    #[cfg(feature = "serde_json")]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
+
+impl RulePack {
+    /// Dry-runs every rule against a document, keeping [`DEFAULT_SAMPLES`] values per rule.
+    pub fn coverage(&self, html: &Html) -> CoverageReport {
+        self.coverage_with_samples(html, DEFAULT_SAMPLES)
+    }
+
+    /// Dry-runs every rule against a document, keeping up to `samples` values per rule.
+    ///
+    /// Rules are evaluated like [`RulePack::execute`] evaluates them, with fallbacks,
+    /// coercions and validators, except that every match is sampled.
+    pub fn coverage_with_samples(&self, html: &Html, samples: usize) -> CoverageReport {
+        CoverageReport {
+            pack: self.name.clone(),
+            version: self.version.clone(),
+            rules: self
+                .rules
+                .iter()
+                .map(|rule| rule_coverage(rule, html, samples))
+                .collect(),
+        }
+    }
+}
+
+fn rule_coverage(rule: &Rule, html: &Html, samples: usize) -> RuleCoverage {
+    // Sample every match, not only the first one a single-valued rule keeps.
+    let sampled = Rule {
+        multiple: true,
+        ..rule.clone()
+    };
+    let (output, _) = sampled.evaluate(|s| html.select(s).collect(), false);
+
+    RuleCoverage {
+        rule: rule.name.clone(),
+        required: rule.required,
+        matched: output.hits > 0,
+        hits: output.hits,
+        samples: output
+            .values
+            .into_iter()
+            .take(samples)
+            .map(truncate)
+            .collect(),
+    }
+}
+
+fn truncate(mut value: String) -> String {
+    if let Some((idx, _)) = value.char_indices().nth(MAX_SAMPLE_CHARS) {
+        value.truncate(idx);
+        value.push('…');
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::html::Html;
+    use crate::rules::{Coerce, Extract, Rule, RulePack, RuleSource, Validator};
+
+    #[test]
+    fn coverage_counts_and_samples() {
+        let mut pack = RulePack::new("list", "1");
+        pack.push(Rule::new("items", "li", Extract::Text).unwrap())
+            .unwrap();
+        pack.push(Rule::new("author", ".author", Extract::Text).unwrap())
+            .unwrap();
+
+        let html = Html::parse_fragment("<ul><li>a</li><li>b</li><li>c</li><li>d</li></ul>");
+        let report = pack.coverage_with_samples(&html, 2);
+
+        let items = &report.rules[0];
+        assert!(items.matched);
+        assert_eq!(4, items.hits);
+        assert_eq!(vec!["a", "b"], items.samples);
+        assert_eq!(
+            vec!["author"],
+            report
+                .unmatched()
+                .map(|r| r.rule.as_str())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(0.5, report.ratio());
+    }
+
+    #[test]
+    fn coverage_follows_fallbacks_and_validation() {
+        let mut pack = RulePack::new("shop", "1");
+        pack.push(
+            Rule::first_of(
+                "price",
+                [
+                    RuleSource::css(".sale").unwrap(),
+                    RuleSource::css(".price").unwrap(),
+                ],
+            )
+            .unwrap()
+            .coerce(Coerce::Number)
+            .validate(Validator::NonEmpty),
+        )
+        .unwrap();
+
+        let html = Html::parse_fragment(
+            r#"<span class="sale"> </span><b class="price">$1,200</b><b class="price">n/a</b>"#,
+        );
+        let price = &pack.coverage(&html).rules[0];
+        assert!(price.matched);
+        assert_eq!(2, price.hits);
+        assert_eq!(vec!["1200"], price.samples);
+    }
+}
@@ -0,0 +1,134 @@
+//! Differential extraction between two versions of a page.
+
+use super::RulePack;
+use crate::html::{Html, SubtreeHashes};
+
+/// A rule whose values differ between two documents.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FieldChange {
+    /// The rule name.
+    pub rule: String,
+    /// The values extracted from the old document.
+    pub old: Vec<String>,
+    /// The values extracted from the new document.
+    pub new: Vec<String>,
+}
+
+impl RulePack {
+    /// Executes the pack against two versions of a page and returns the rules whose values
+    /// changed, in pack order. Values are compared after the fallbacks, coercions and
+    /// validators of each rule, as [`RulePack::execute`] produces them.
+    ///
+    /// Extraction only reads the subtree of each matched element, so a rule whose matches
+    /// have the same [`SubtreeHashes`] in both documents is skipped without extracting
+    /// anything, and identical documents return immediately.
+    ///
+    /// ```
+    /// use scraper::rules::{Extract, Rule, RulePack};
+    /// use scraper::Html;
+    ///
+    /// let mut pack = RulePack::new("shop", "1");
+    /// pack.push(Rule::new("name", "h1", Extract::Text).unwrap()).unwrap();
+    /// pack.push(Rule::new("price", ".price", Extract::Text).unwrap()).unwrap();
+    ///
+    /// let old = Html::parse_document(r#"<h1>Kettle</h1><span class="price">20</span>"#);
+    /// let new = Html::parse_document(r#"<h1>Kettle</h1><span class="price">18</span>"#);
+    /// let changes = pack.extract_diff(&old, &new);
+    /// assert_eq!(1, changes.len());
+    /// assert_eq!(("price", "20", "18"), (&*changes[0].rule, &*changes[0].old[0], &*changes[0].new[0]));
+    /// ```
+    pub fn extract_diff(&self, old: &Html, new: &Html) -> Vec<FieldChange> {
+        let (old_hashes, new_hashes) = (old.subtree_hashes(), new.subtree_hashes());
+        if old_hashes.root() == new_hashes.root() {
+            return Vec::new();
+        }
+
+        let matched = |html: &Html, hashes: &SubtreeHashes, rule: &super::Rule| -> Vec<u64> {
+            rule.sources()
+                .flat_map(|(selector, _)| html.select(selector))
+                .filter_map(|element| hashes.get(element.id()))
+                .collect()
+        };
+
+        self.rules
+            .iter()
+            .filter(|rule| matched(old, &old_hashes, rule) != matched(new, &new_hashes, rule))
+            .filter_map(|rule| {
+                let before = rule.evaluate(|s| old.select(s).collect(), false).0.values;
+                let after = rule.evaluate(|s| new.select(s).collect(), false).0.values;
+                (before != after).then(|| FieldChange {
+                    rule: rule.name.clone(),
+                    old: before,
+                    new: after,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::html::Html;
+    use crate::rules::{Coerce, Extract, Rule, RulePack, RuleSource};
+
+    #[test]
+    fn ignores_changes_outside_extracted_values() {
+        let mut pack = RulePack::new("p", "1");
+        pack.push(Rule::new("stock", "#stock", Extract::Attr("data-n".into())).unwrap())
+            .unwrap();
+        pack.push(Rule::new("tags", "li", Extract::Text).unwrap().multiple())
+            .unwrap();
+
+        let old = Html::parse_document(
+            r#"<p id="stock" data-n="3">3 left</p><ul><li>a</li></ul><footer>2023</footer>"#,
+        );
+        let same_values = Html::parse_document(
+            r#"<p id="stock" data-n="3">only 3 left</p><ul><li>a</li></ul><footer>2024</footer>"#,
+        );
+        assert!(pack.extract_diff(&old, &same_values).is_empty());
+        assert!(pack.extract_diff(&old, &old).is_empty());
+
+        let changed = Html::parse_document(
+            r#"<p id="stock" data-n="3">3 left</p><ul><li>a</li><li>b</li></ul>"#,
+        );
+        let changes = pack.extract_diff(&old, &changed);
+        assert_eq!(
+            vec!["tags"],
+            changes.iter().map(|c| c.rule.as_str()).collect::<Vec<_>>()
+        );
+        assert_eq!(vec!["a", "b"], changes[0].new);
+    }
+
+    #[test]
+    fn compares_fallback_and_coerced_values() {
+        let mut pack = RulePack::new("p", "1");
+        pack.push(
+            Rule::new("price", ".price", Extract::Text)
+                .unwrap()
+                .coerce(Coerce::Number),
+        )
+        .unwrap();
+        pack.push(
+            Rule::first_of(
+                "title",
+                [
+                    RuleSource::css("h1").unwrap(),
+                    RuleSource::css("h2").unwrap(),
+                ],
+            )
+            .unwrap(),
+        )
+        .unwrap();
+
+        let old = Html::parse_document(r#"<h2>Kettle</h2><span class="price">$20</span>"#);
+        let new = Html::parse_document(r#"<h2>Kettle 2</h2><span class="price">20.00</span>"#);
+        let changes = pack.extract_diff(&old, &new);
+        assert_eq!(
+            vec!["title"],
+            changes.iter().map(|c| c.rule.as_str()).collect::<Vec<_>>()
+        );
+        assert_eq!(vec!["Kettle"], changes[0].old);
+        assert_eq!(vec!["Kettle 2"], changes[0].new);
+    }
+}
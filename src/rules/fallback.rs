@@ -0,0 +1,245 @@
+//! Fallback chains of extraction sources.
+
+use super::{Extract, Rule};
+use crate::error::RuleError;
+use crate::selector::Selector;
+
+#[cfg(feature = "serde_json")]
+use crate::element_ref::ElementRef;
+
+/// A selector with an extraction directive, one link of a [`Rule::first_of`] chain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RuleSource {
+    /// The selector locating the elements.
+    pub selector: Selector,
+    /// What to extract from each matched element.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub extract: Extract,
+}
+
+impl RuleSource {
+    /// Creates a source extracting the text of the elements matching `selector`.
+    ///
+    /// The rule name of an [`RuleError::InvalidSelector`] is left empty, since the source
+    /// does not belong to a rule yet.
+    pub fn css(selector: &str) -> Result<Self, RuleError> {
+        let parsed = Selector::parse(selector).map_err(|_| RuleError::InvalidSelector {
+            rule: String::new(),
+            selector: selector.to_owned(),
+        })?;
+        Ok(RuleSource {
+            selector: parsed,
+            extract: Extract::Text,
+        })
+    }
+
+    /// Creates a source reading a JSON-LD property by a dotted path such as
+    /// `author.name`.
+    ///
+    /// Arrays along the path, including a top-level `@graph`, are searched for the first
+    /// element that has the rest of the path.
+    #[cfg(feature = "serde_json")]
+    pub fn json_ld(path: &str) -> Self {
+        RuleSource {
+            selector: Selector::parse(r#"script[type="application/ld+json"]"#).unwrap(),
+            extract: Extract::JsonLd(path.to_owned()),
+        }
+    }
+
+    /// Extracts the value of an attribute instead.
+    pub fn attr(mut self, name: &str) -> Self {
+        self.extract = Extract::Attr(name.to_owned());
+        self
+    }
+
+    /// Extracts the text instead.
+    pub fn text(mut self) -> Self {
+        self.extract = Extract::Text;
+        self
+    }
+
+    /// Extracts the outer HTML instead.
+    pub fn html(mut self) -> Self {
+        self.extract = Extract::Html;
+        self
+    }
+}
+
+impl Rule {
+    /// Creates a rule that tries each source in order and keeps the values of the first one
+    /// that produces any. Empty and whitespace-only values are skipped, so a blank
+    /// `content` attribute falls through to the next source.
+    ///
+    /// The first source becomes the rule's `selector` and `extract`, the others its
+    /// `fallbacks`; [`RuleOutput::source_index`](super::RuleOutput::source_index) records
+    /// which one succeeded.
+    ///
+    /// ```
+    /// use scraper::rules::{Rule, RulePack, RuleSource};
+    /// use scraper::Html;
+    ///
+    /// let rule = Rule::first_of(
+    ///     "published",
+    ///     [
+    ///         RuleSource::css(r#"meta[property="article:published_time"]"#)?.attr("content"),
+    ///         RuleSource::css("time")?.attr("datetime"),
+    ///         RuleSource::css(".date")?.text(),
+    ///     ],
+    /// )?;
+    /// let mut pack = RulePack::new("news", "1");
+    /// pack.push(rule)?;
+    ///
+    /// let html = Html::parse_document(r#"<time>today</time><span class="date">2024-05-01</span>"#);
+    /// let extraction = pack.execute(&html);
+    /// assert_eq!(Some("2024-05-01"), extraction.get("published"));
+    /// assert_eq!(Some(2), extraction.output("published").unwrap().source_index);
+    /// # Ok::<(), scraper::error::RuleError>(())
+    /// ```
+    pub fn first_of(
+        name: &str,
+        sources: impl IntoIterator<Item = RuleSource>,
+    ) -> Result<Self, RuleError> {
+        let mut sources = sources.into_iter();
+        let first = sources
+            .next()
+            .ok_or_else(|| RuleError::NoSources(name.to_owned()))?;
+        Ok(Rule {
+            name: name.to_owned(),
+            selector: first.selector,
+            extract: first.extract,
+            fallbacks: sources.collect(),
+            coerce: Vec::new(),
+            validators: Vec::new(),
+            multiple: false,
+            required: false,
+            description: None,
+        })
+    }
+}
+
+/// Reads a property of the JSON-LD in a script element.
+#[cfg(feature = "serde_json")]
+pub(super) fn json_ld_property(element: &ElementRef, path: &str) -> Option<String> {
+    use serde_json::Value;
+
+    fn lookup<'v>(value: &'v Value, path: &[&str]) -> Option<&'v Value> {
+        match (value, path.split_first()) {
+            (Value::Array(items), _) => items.iter().find_map(|item| lookup(item, path)),
+            (_, None) => Some(value),
+            (Value::Object(object), Some((key, rest))) => {
+                let found = object.get(*key).and_then(|v| lookup(v, rest));
+                found.or_else(|| lookup(object.get("@graph")?, path))
+            }
+            _ => None,
+        }
+    }
+
+    let source: String = element
+        .children()
+        .filter_map(|child| child.value().as_text())
+        .map(|text| &**text)
+        .collect();
+    let json: Value = serde_json::from_str(&source).ok()?;
+    let path: Vec<&str> = path.split('.').collect();
+    match lookup(&json, &path)? {
+        Value::String(s) if !s.trim().is_empty() => Some(s.trim().to_owned()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        Value::Object(object) => {
+            let value = object.get("@value").or_else(|| object.get("name"))?;
+            value.as_str().map(str::to_owned)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RuleSource;
+    use crate::error::RuleError;
+    use crate::html::Html;
+    use crate::rules::{Rule, RulePack};
+
+    #[test]
+    fn primary_source_wins_and_empty_chains_fail() {
+        let mut pack = RulePack::new("p", "1");
+        pack.push(
+            Rule::first_of(
+                "title",
+                [
+                    RuleSource::css("h1").unwrap(),
+                    RuleSource::css("title").unwrap(),
+                ],
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let extraction = pack.execute(&Html::parse_document("<title>T</title><h1>H</h1>"));
+        assert_eq!(Some("H"), extraction.get("title"));
+        assert_eq!(Some(0), extraction.output("title").unwrap().source_index);
+
+        let extraction = pack.execute(&Html::parse_document("<p>none</p>"));
+        assert_eq!(None, extraction.output("title").unwrap().source_index);
+        assert_eq!(Some(0), extraction.hits("title"));
+
+        assert_eq!(
+            Err(RuleError::NoSources("x".into())),
+            Rule::first_of("x", [])
+        );
+    }
+
+    #[test]
+    fn blank_values_fall_through() {
+        let mut pack = RulePack::new("p", "1");
+        pack.push(
+            Rule::first_of(
+                "description",
+                [
+                    RuleSource::css(r#"meta[name="description"]"#)
+                        .unwrap()
+                        .attr("content"),
+                    RuleSource::css("p").unwrap(),
+                ],
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let html = Html::parse_document(
+            r#"<meta name="description" content=""><meta name="description" content="  "><p> </p><p>Body</p>"#,
+        );
+        let extraction = pack.execute(&html);
+        assert_eq!(Some("Body"), extraction.get("description"));
+        assert_eq!(
+            Some(1),
+            extraction.output("description").unwrap().source_index
+        );
+    }
+
+    #[cfg(feature = "serde_json")]
+    #[test]
+    fn json_ld_paths_search_graphs_and_arrays() {
+        let mut pack = RulePack::new("p", "1");
+        pack.push(
+            Rule::first_of(
+                "author",
+                [
+                    RuleSource::css(r#"meta[name="author"]"#)
+                        .unwrap()
+                        .attr("content"),
+                    RuleSource::json_ld("author.name"),
+                ],
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let html = Html::parse_document(
+            r#"<script type="application/ld+json">
+            {"@graph": [{"@type": "WebSite"}, {"@type": "Article", "author": [{"name": "Ada"}]}]}
+            </script>"#,
+        );
+        let extraction = pack.execute(&html);
+        assert_eq!(Some("Ada"), extraction.get("author"));
+        assert_eq!(Some(1), extraction.output("author").unwrap().source_index);
+    }
+}
@@ -0,0 +1,243 @@
+//! Joining extractions of related documents, such as listing and detail pages.
+
+use url::Url;
+
+use super::Extraction;
+
+/// One field of a [`JoinedRecord`] and the document it came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct JoinedField {
+    /// The rule name.
+    pub rule: String,
+    /// The extracted values.
+    pub values: Vec<String>,
+    /// The URL of the page the values were extracted from.
+    pub source: String,
+    /// The name of the pack that extracted them.
+    pub pack: String,
+}
+
+/// A record merged from a listing stub and the detail page it links to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct JoinedRecord {
+    /// The URL of the detail page.
+    pub url: String,
+    /// The fields, detail page fields first.
+    pub fields: Vec<JoinedField>,
+    /// Whether the detail page has been registered.
+    pub has_detail: bool,
+    /// Whether a listing stub references the detail page.
+    pub has_stub: bool,
+}
+
+impl JoinedRecord {
+    /// Returns a field by rule name.
+    pub fn field(&self, rule: &str) -> Option<&JoinedField> {
+        self.fields.iter().find(|f| f.rule == rule)
+    }
+
+    /// Returns the first value of a field.
+    pub fn get(&self, rule: &str) -> Option<&str> {
+        self.field(rule)?.values.first().map(String::as_str)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Stub {
+    listing: Url,
+    target: Url,
+    extraction: Extraction,
+}
+
+/// Collects extractions keyed by URL and joins listing stubs with their detail pages.
+///
+/// ```
+/// use scraper::rules::{DocumentJoin, Extract, Rule, RulePack};
+/// use scraper::{Html, Selector};
+/// use url::Url;
+///
+/// let mut card = RulePack::new("card", "1");
+/// card.push(Rule::new("link", "a", Extract::Attr("href".into())).unwrap()).unwrap();
+/// card.push(Rule::new("price", ".price", Extract::Text).unwrap()).unwrap();
+/// let mut detail = RulePack::new("detail", "1");
+/// detail.push(Rule::new("name", "h1", Extract::Text).unwrap()).unwrap();
+///
+/// let listing_url = Url::parse("https://shop.example/list?page=1").unwrap();
+/// let listing = Html::parse_document(
+///     r#"<div class="card"><a href="/p/1">Kettle</a><span class="price">20</span></div>"#,
+/// );
+/// let cards = Selector::parse(".card").unwrap();
+///
+/// let mut join = DocumentJoin::new();
+/// join.add_stubs(&listing_url, listing.select(&cards).map(|c| card.execute_element(c)), "link");
+///
+/// let detail_url = Url::parse("https://shop.example/p/1#reviews").unwrap();
+/// join.add_document(&detail_url, detail.execute(&Html::parse_document("<h1>Steel kettle</h1>")));
+///
+/// let records = join.records();
+/// assert_eq!("https://shop.example/p/1", records[0].url);
+/// assert_eq!(Some("Steel kettle"), records[0].get("name"));
+/// assert_eq!(Some("20"), records[0].get("price"));
+/// assert_eq!("https://shop.example/list?page=1", records[0].field("price").unwrap().source);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DocumentJoin {
+    documents: Vec<(Url, Extraction)>,
+    stubs: Vec<Stub>,
+}
+
+/// Drops the fragment, which never changes the document a URL refers to.
+fn key(url: &Url) -> Url {
+    let mut url = url.clone();
+    url.set_fragment(None);
+    url
+}
+
+fn fields<'a>(extraction: &'a Extraction, source: &Url) -> impl Iterator<Item = JoinedField> + 'a {
+    let source = source.to_string();
+    extraction
+        .outputs
+        .iter()
+        .filter(|o| !o.values.is_empty())
+        .map(move |o| JoinedField {
+            rule: o.rule.clone(),
+            values: o.values.clone(),
+            source: source.clone(),
+            pack: extraction.pack.clone(),
+        })
+}
+
+impl DocumentJoin {
+    /// Creates an empty join.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the extraction of a document, replacing an earlier one for the same URL.
+    pub fn add_document(&mut self, url: &Url, extraction: Extraction) {
+        let url = key(url);
+        match self.documents.iter_mut().find(|(u, _)| *u == url) {
+            Some(entry) => entry.1 = extraction,
+            None => self.documents.push((url, extraction)),
+        }
+    }
+
+    /// Returns the extraction registered for a URL.
+    pub fn document(&self, url: &Url) -> Option<&Extraction> {
+        let url = key(url);
+        self.documents
+            .iter()
+            .find(|(u, _)| *u == url)
+            .map(|(_, extraction)| extraction)
+    }
+
+    /// Registers the stubs of one listing page, typically one extraction per item card from
+    /// [`RulePack::execute_element`](super::RulePack::execute_element).
+    ///
+    /// Each stub references its detail page by the first value of `link_rule`, resolved
+    /// against the listing URL. Stubs without a valid link are ignored. Earlier stubs of
+    /// the same listing are replaced.
+    pub fn add_stubs(
+        &mut self,
+        listing: &Url,
+        stubs: impl IntoIterator<Item = Extraction>,
+        link_rule: &str,
+    ) {
+        let listing = key(listing);
+        self.stubs.retain(|s| s.listing != listing);
+        for extraction in stubs {
+            let target = extraction
+                .get(link_rule)
+                .and_then(|href| listing.join(href.trim()).ok());
+            if let Some(target) = target {
+                self.stubs.push(Stub {
+                    listing: listing.clone(),
+                    target: key(&target),
+                    extraction,
+                });
+            }
+        }
+    }
+
+    /// Merges every detail page with the stubs that reference it.
+    ///
+    /// Records come in stub order, followed by detail pages no stub references. A field
+    /// present on both the stub and the detail page keeps the detail page values; when
+    /// several stubs reference the same page, the first one wins.
+    pub fn records(&self) -> Vec<JoinedRecord> {
+        let mut order: Vec<&Url> = Vec::new();
+        for url in self
+            .stubs
+            .iter()
+            .map(|s| &s.target)
+            .chain(self.documents.iter().map(|(u, _)| u))
+        {
+            if !order.contains(&url) {
+                order.push(url);
+            }
+        }
+
+        order
+            .into_iter()
+            .map(|url| {
+                let detail = self.documents.iter().find(|(u, _)| u == url);
+                let stubs: Vec<&Stub> = self.stubs.iter().filter(|s| &s.target == url).collect();
+
+                let mut merged: Vec<JoinedField> = Vec::new();
+                let detail_fields = detail.into_iter().flat_map(|(u, e)| fields(e, u));
+                let stub_fields = stubs.iter().flat_map(|s| fields(&s.extraction, &s.listing));
+                for field in detail_fields.chain(stub_fields) {
+                    if !merged.iter().any(|f| f.rule == field.rule) {
+                        merged.push(field);
+                    }
+                }
+
+                JoinedRecord {
+                    url: url.to_string(),
+                    fields: merged,
+                    has_detail: detail.is_some(),
+                    has_stub: !stubs.is_empty(),
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use url::Url;
+
+    use super::DocumentJoin;
+    use crate::html::Html;
+    use crate::rules::{Extract, Rule, RulePack};
+
+    #[test]
+    fn pending_details_and_orphan_documents_are_kept() {
+        let mut pack = RulePack::new("p", "1");
+        pack.push(Rule::new("link", "a", Extract::Attr("href".into())).unwrap())
+            .unwrap();
+        pack.push(Rule::new("title", "b", Extract::Text).unwrap())
+            .unwrap();
+
+        let listing = Url::parse("https://x.example/list/").unwrap();
+        let stub = |html: &str| pack.execute(&Html::parse_fragment(html));
+        let mut join = DocumentJoin::new();
+        join.add_stubs(
+            &listing,
+            [stub("<a href='a'>A</a><b>Stub</b>"), stub("<b>no link</b>")],
+            "link",
+        );
+        let other = Url::parse("https://x.example/other").unwrap();
+        join.add_document(&other, stub("<b>Other</b>"));
+
+        let records = join.records();
+        assert_eq!(2, records.len());
+        assert_eq!("https://x.example/list/a", records[0].url);
+        assert!(records[0].has_stub && !records[0].has_detail);
+        assert_eq!(Some("Stub"), records[0].get("title"));
+        assert!(records[1].has_detail && !records[1].has_stub);
+        assert!(join.document(&other).is_some());
+    }
+}
@@ -0,0 +1,638 @@
+//! Named rule packs.
+//!
+//! A [`RulePack`] is a versioned, named collection of [`Rule`]s. Every rule pairs a CSS selector
+//! with an extraction directive, so a whole extraction recipe can be shared as a JSON (feature
+//! `serde_json`) or YAML (feature `yaml`) file and executed against any [`Html`].
+//!
+//! ```
+//! use scraper::rules::{Extract, Rule, RulePack};
+//! use scraper::Html;
+//!
+//! let mut pack = RulePack::new("blog", "1.0.0");
+//! pack.push(Rule::new("title", "h1", Extract::Text).unwrap()).unwrap();
+//! pack.push(Rule::new("links", "a", Extract::Attr("href".into())).unwrap().multiple())
+//!     .unwrap();
+//!
+//! let html = Html::parse_document(r#"<h1>Hello</h1><a href="/a">a</a><a href="/b">b</a>"#);
+//! let extraction = pack.execute(&html);
+//!
+//! assert_eq!(Some("Hello"), extraction.get("title"));
+//! assert_eq!(["/a", "/b"], extraction.get_all("links"));
+//! assert_eq!(Some(2), extraction.hits("links"));
+//! ```
+//!
+//! The serialized format mirrors the structs:
+//!
+//! ```json
+//! {
+//!   "format": 1,
+//!   "name": "blog",
+//!   "version": "1.0.0",
+//!   "rules": [
+//!     { "name": "title", "selector": "h1", "extract": "text", "required": true },
+//!     { "name": "links", "selector": "a", "extract": { "attr": "href" }, "multiple": true }
+//!   ]
+//! }
+//! ```
+
+pub use self::coverage::{CoverageReport, RuleCoverage, DEFAULT_SAMPLES};
+pub use self::diff::FieldChange;
+pub use self::fallback::RuleSource;
+pub use self::join::{DocumentJoin, JoinedField, JoinedRecord};
+pub use self::validate::{Coerce, ValidationError, ValidationFailure, Validator};
+
+use std::fmt;
+
+use crate::element_ref::ElementRef;
+use crate::error::RuleError;
+use crate::html::{FetchContext, Html};
+use crate::selector::Selector;
+
+/// The rule pack format version understood by this crate.
+pub const FORMAT_VERSION: u32 = 1;
+
+#[cfg(feature = "serde")]
+fn default_format() -> u32 {
+    FORMAT_VERSION
+}
+
+#[cfg(feature = "serde")]
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+/// What to pull out of an element matched by a rule.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Extract {
+    /// The descendent text, trimmed.
+    #[default]
+    Text,
+    /// The outer HTML.
+    Html,
+    /// The inner HTML.
+    InnerHtml,
+    /// The value of an attribute.
+    Attr(String),
+    /// A property of the JSON-LD in a `<script type="application/ld+json">` element, by a
+    /// dotted path such as `author.name`; see [`RuleSource::json_ld`].
+    #[cfg(feature = "serde_json")]
+    JsonLd(String),
+}
+
+impl Extract {
+    /// Applies the directive to an element. Returns `None` when the element has no such value.
+    pub fn apply(&self, element: &ElementRef) -> Option<String> {
+        match self {
+            Extract::Text => Some(element.text().collect::<String>().trim().to_owned()),
+            Extract::Html => Some(element.html()),
+            Extract::InnerHtml => Some(element.inner_html()),
+            Extract::Attr(name) => element.attr(name).map(str::to_owned),
+            #[cfg(feature = "serde_json")]
+            Extract::JsonLd(path) => fallback::json_ld_property(element, path),
+        }
+    }
+}
+
+impl fmt::Display for Extract {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Extract::Text => f.write_str("text"),
+            Extract::Html => f.write_str("html"),
+            Extract::InnerHtml => f.write_str("inner_html"),
+            Extract::Attr(name) => write!(f, "@{}", name),
+            #[cfg(feature = "serde_json")]
+            Extract::JsonLd(path) => write!(f, "json_ld({})", path),
+        }
+    }
+}
+
+/// Where an extracted value came from: the element and what was read from it.
+///
+/// Displays as the CSS path and the directive, e.g. `#main > p:nth-of-type(2) @href`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ValueSource {
+    /// The [`ElementRef::css_path`] of the matched element.
+    pub css_path: String,
+    /// The directive that produced the value.
+    pub extract: Extract,
+}
+
+impl fmt::Display for ValueSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.css_path, self.extract)
+    }
+}
+
+/// A named selector with an extraction directive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rule {
+    /// The rule name, unique within a pack.
+    pub name: String,
+    /// The selector locating the elements.
+    pub selector: Selector,
+    /// What to extract from each matched element.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub extract: Extract,
+    /// Collect every match instead of only the first.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub multiple: bool,
+    /// Sources tried in order when `selector` and `extract` produce no non-blank value.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub fallbacks: Vec<RuleSource>,
+    /// Transformations applied to every value, in order.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub coerce: Vec<Coerce>,
+    /// Checks every coerced value must pass; failing values are dropped and reported in
+    /// [`Extraction::invalid`].
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub validators: Vec<Validator>,
+    /// The rule is expected to produce a value on every page.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub required: bool,
+    /// Free-form documentation for maintainers.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub description: Option<String>,
+}
+
+impl Rule {
+    /// Creates a single-valued, optional rule.
+    pub fn new(name: &str, selector: &str, extract: Extract) -> Result<Self, RuleError> {
+        let selector = Selector::parse(selector).map_err(|_| RuleError::InvalidSelector {
+            rule: name.to_owned(),
+            selector: selector.to_owned(),
+        })?;
+
+        Ok(Rule {
+            name: name.to_owned(),
+            selector,
+            extract,
+            fallbacks: Vec::new(),
+            coerce: Vec::new(),
+            validators: Vec::new(),
+            multiple: false,
+            required: false,
+            description: None,
+        })
+    }
+
+    /// Marks the rule as collecting every match.
+    pub fn multiple(mut self) -> Self {
+        self.multiple = true;
+        self
+    }
+
+    /// Marks the rule as required.
+    pub fn required(mut self) -> Self {
+        self.required = true;
+        self
+    }
+
+    /// Sets the rule description.
+    pub fn describe(mut self, description: &str) -> Self {
+        self.description = Some(description.to_owned());
+        self
+    }
+
+    /// The primary source followed by the fallbacks.
+    fn sources(&self) -> impl Iterator<Item = (&Selector, &Extract)> {
+        Some((&self.selector, &self.extract))
+            .into_iter()
+            .chain(self.fallbacks.iter().map(|f| (&f.selector, &f.extract)))
+    }
+
+    fn run<'a>(
+        &self,
+        select: impl Fn(&Selector) -> Vec<ElementRef<'a>>,
+        track_sources: bool,
+    ) -> RuleOutput {
+        let mut output = RuleOutput {
+            rule: self.name.clone(),
+            multiple: self.multiple,
+            values: Vec::new(),
+            sources: Vec::new(),
+            source_index: None,
+            hits: 0,
+        };
+
+        let mut primary_hits = 0;
+        for (index, (selector, extract)) in self.sources().enumerate() {
+            output.hits = 0;
+            for element in select(selector) {
+                output.hits += 1;
+                if self.multiple || output.values.is_empty() {
+                    let value = extract.apply(&element);
+                    if let Some(value) = value.filter(|v| !v.trim().is_empty()) {
+                        output.values.push(value);
+                        if track_sources {
+                            output.sources.push(ValueSource {
+                                css_path: element.css_path(),
+                                extract: extract.clone(),
+                            });
+                        }
+                    }
+                }
+            }
+            if !output.values.is_empty() {
+                if !self.fallbacks.is_empty() {
+                    output.source_index = Some(index);
+                }
+                return output;
+            }
+            if index == 0 {
+                primary_hits = output.hits;
+            }
+        }
+        output.hits = primary_hits;
+        output
+    }
+
+    /// Runs the rule and coerces and validates its values, as [`RulePack::execute`] does.
+    fn evaluate<'a>(
+        &self,
+        select: impl Fn(&Selector) -> Vec<ElementRef<'a>>,
+        track_sources: bool,
+    ) -> (RuleOutput, Vec<ValidationError>) {
+        let mut output = self.run(select, track_sources);
+        let invalid = self.check(&mut output);
+        (output, invalid)
+    }
+}
+
+/// A versioned collection of rules.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RulePack {
+    /// The version of the pack format, see [`FORMAT_VERSION`].
+    #[cfg_attr(feature = "serde", serde(default = "default_format"))]
+    pub format: u32,
+    /// The pack name.
+    pub name: String,
+    /// The version of the recipe itself, bumped by its maintainers.
+    pub version: String,
+    /// Free-form documentation for maintainers.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub description: Option<String>,
+    /// The rules, executed in order.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub rules: Vec<Rule>,
+    /// Record a [`ValueSource`] for every extracted value.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "is_false"))]
+    pub sources: bool,
+}
+
+impl RulePack {
+    /// Creates an empty pack.
+    pub fn new(name: &str, version: &str) -> Self {
+        RulePack {
+            format: FORMAT_VERSION,
+            name: name.to_owned(),
+            version: version.to_owned(),
+            description: None,
+            rules: Vec::new(),
+            sources: false,
+        }
+    }
+
+    /// Makes executions record where every value came from, see [`RuleOutput::sources`].
+    pub fn with_sources(mut self) -> Self {
+        self.sources = true;
+        self
+    }
+
+    /// Adds a rule, rejecting duplicate names.
+    pub fn push(&mut self, rule: Rule) -> Result<(), RuleError> {
+        if self.rule(&rule.name).is_some() {
+            return Err(RuleError::DuplicateRule(rule.name));
+        }
+        self.rules.push(rule);
+        Ok(())
+    }
+
+    /// Returns the rule with the given name.
+    pub fn rule(&self, name: &str) -> Option<&Rule> {
+        self.rules.iter().find(|rule| rule.name == name)
+    }
+
+    /// Checks the invariants that deserialization alone cannot enforce.
+    pub fn validate(&self) -> Result<(), RuleError> {
+        if self.format > FORMAT_VERSION {
+            return Err(RuleError::UnsupportedFormat(self.format));
+        }
+        for (i, rule) in self.rules.iter().enumerate() {
+            if self.rules[..i].iter().any(|r| r.name == rule.name) {
+                return Err(RuleError::DuplicateRule(rule.name.clone()));
+            }
+            for validator in &rule.validators {
+                validator.check(&rule.name)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Loads and validates a pack from JSON.
+    #[cfg(feature = "serde_json")]
+    pub fn from_json(source: &str) -> Result<Self, RuleError> {
+        let pack: RulePack =
+            serde_json::from_str(source).map_err(|e| RuleError::Decode(e.to_string()))?;
+        pack.validate()?;
+        Ok(pack)
+    }
+
+    /// Serializes the pack to pretty-printed JSON.
+    #[cfg(feature = "serde_json")]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    /// Loads and validates a pack from YAML.
+    #[cfg(feature = "yaml")]
+    pub fn from_yaml(source: &str) -> Result<Self, RuleError> {
+        let pack: RulePack =
+            serde_yaml::from_str(source).map_err(|e| RuleError::Decode(e.to_string()))?;
+        pack.validate()?;
+        Ok(pack)
+    }
+
+    /// Serializes the pack to YAML.
+    #[cfg(feature = "yaml")]
+    pub fn to_yaml(&self) -> String {
+        serde_yaml::to_string(self).unwrap_or_default()
+    }
+
+    /// Executes every rule against a document.
+    pub fn execute(&self, html: &Html) -> Extraction {
+        let mut extraction =
+            self.collect(|rule| rule.evaluate(|s| html.select(s).collect(), self.sources));
+        extraction.fetch = html.fetch.clone();
+        extraction
+    }
+
+    /// Executes every rule against the descendants of an element.
+    pub fn execute_element(&self, element: ElementRef) -> Extraction {
+        self.collect(|rule| rule.evaluate(|s| element.select(s).collect(), self.sources))
+    }
+
+    fn collect(
+        &self,
+        evaluate: impl Fn(&Rule) -> (RuleOutput, Vec<ValidationError>),
+    ) -> Extraction {
+        let mut missing = Vec::new();
+        let mut invalid = Vec::new();
+        let outputs = self
+            .rules
+            .iter()
+            .map(|rule| {
+                let (output, errors) = evaluate(rule);
+                invalid.extend(errors);
+                if rule.required && output.values.is_empty() {
+                    missing.push(rule.name.clone());
+                }
+                output
+            })
+            .collect();
+
+        Extraction {
+            pack: self.name.clone(),
+            version: self.version.clone(),
+            outputs,
+            missing,
+            invalid,
+            fetch: None,
+        }
+    }
+}
+
+/// The values and hit count produced by one rule.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RuleOutput {
+    /// The rule name.
+    pub rule: String,
+    /// Whether the rule collects every match.
+    pub multiple: bool,
+    /// The extracted values, at most one unless the rule is `multiple`.
+    pub values: Vec<String>,
+    /// Where each value came from, parallel to `values`. Only recorded when the pack has
+    /// [`RulePack::sources`] set.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub sources: Vec<ValueSource>,
+    /// For rules with fallbacks, which source produced the values: `0` for the primary
+    /// selector, `1` for the first fallback and so on. `None` if no source produced a value.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub source_index: Option<usize>,
+    /// How many elements the selector of the producing source matched, or the primary
+    /// selector when none produced a value.
+    pub hits: usize,
+}
+
+/// The result of executing a [`RulePack`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Extraction {
+    /// The name of the executed pack.
+    pub pack: String,
+    /// The version of the executed pack.
+    pub version: String,
+    /// One output per rule, in pack order.
+    pub outputs: Vec<RuleOutput>,
+    /// Required rules that produced no value.
+    pub missing: Vec<String>,
+    /// Values that failed coercion or validation and were left out of the outputs.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub invalid: Vec<ValidationError>,
+    /// How the document was fetched, from [`Html::fetch`]. Always `None` for
+    /// [`RulePack::execute_element`].
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub fetch: Option<FetchContext>,
+}
+
+impl Extraction {
+    /// Returns the output of a rule.
+    pub fn output(&self, rule: &str) -> Option<&RuleOutput> {
+        self.outputs.iter().find(|o| o.rule == rule)
+    }
+
+    /// Returns the first value of a rule.
+    pub fn get(&self, rule: &str) -> Option<&str> {
+        self.output(rule)
+            .and_then(|o| o.values.first())
+            .map(String::as_str)
+    }
+
+    /// Returns every value of a rule.
+    pub fn get_all(&self, rule: &str) -> &[String] {
+        self.output(rule).map_or(&[], |o| &o.values)
+    }
+
+    /// Returns where the values of a rule came from, empty unless sources were recorded.
+    pub fn sources(&self, rule: &str) -> &[ValueSource] {
+        self.output(rule).map_or(&[], |o| &o.sources)
+    }
+
+    /// Returns how many elements a rule matched.
+    pub fn hits(&self, rule: &str) -> Option<usize> {
+        self.output(rule).map(|o| o.hits)
+    }
+
+    /// Returns an iterator over `(rule, hits)` pairs in pack order.
+    pub fn metrics(&self) -> impl Iterator<Item = (&str, usize)> {
+        self.outputs.iter().map(|o| (o.rule.as_str(), o.hits))
+    }
+
+    /// Returns true if every required rule produced a value.
+    pub fn is_complete(&self) -> bool {
+        self.missing.is_empty()
+    }
+
+    /// Flattens the values into a JSON object keyed by rule name. Multiple-valued rules become
+    /// arrays, single-valued rules a string or `null`.
+    #[cfg(feature = "serde_json")]
+    pub fn to_value(&self) -> serde_json::Value {
+        let fields = self
+            .outputs
+            .iter()
+            .map(|o| {
+                let value = if o.multiple {
+                    o.values
+                        .iter()
+                        .cloned()
+                        .map(serde_json::Value::String)
+                        .collect()
+                } else {
+                    o.values
+                        .first()
+                        .cloned()
+                        .map_or(serde_json::Value::Null, serde_json::Value::String)
+                };
+                (o.rule.clone(), value)
+            })
+            .collect();
+        serde_json::Value::Object(fields)
+    }
+}
+
+mod coverage;
+mod diff;
+mod fallback;
+mod join;
+mod validate;
+
+#[cfg(test)]
+mod tests {
+    use super::{Extract, Rule, RulePack};
+    use crate::error::RuleError;
+    use crate::html::Html;
+
+    #[test]
+    fn execute_reports_hits_and_missing() {
+        let mut pack = RulePack::new("shop", "2");
+        pack.push(
+            Rule::new("price", ".price", Extract::Text)
+                .unwrap()
+                .required(),
+        )
+        .unwrap();
+        pack.push(Rule::new("sku", "[data-sku]", Extract::Attr("data-sku".into())).unwrap())
+            .unwrap();
+
+        let html =
+            Html::parse_fragment(r#"<span class="price"> 9 </span><span class="price">10</span>"#);
+        let extraction = pack.execute(&html);
+
+        assert_eq!(Some("9"), extraction.get("price"));
+        assert_eq!(Some(2), extraction.hits("price"));
+        assert_eq!(Some(0), extraction.hits("sku"));
+        assert!(extraction.is_complete());
+
+        let extraction = pack.execute(&Html::parse_fragment("<p>none</p>"));
+        assert_eq!(vec!["price".to_owned()], extraction.missing);
+    }
+
+    #[test]
+    fn rejects_duplicates_and_bad_selectors() {
+        let mut pack = RulePack::new("p", "1");
+        pack.push(Rule::new("a", "a", Extract::Html).unwrap())
+            .unwrap();
+        assert_eq!(
+            Err(RuleError::DuplicateRule("a".into())),
+            pack.push(Rule::new("a", "b", Extract::Html).unwrap())
+        );
+        assert!(matches!(
+            Rule::new("bad", "<>", Extract::Text),
+            Err(RuleError::InvalidSelector { .. })
+        ));
+    }
+
+    #[test]
+    fn sources_trace_values_to_markup() {
+        let mut pack = RulePack::new("p", "1");
+        pack.push(Rule::new("link", "a", Extract::Attr("href".into())).unwrap())
+            .unwrap();
+        let html = Html::parse_document(r#"<div id="nav"><a>x</a><a href="/b">b</a></div>"#);
+        assert!(pack.execute(&html).sources("link").is_empty());
+
+        let extraction = pack.with_sources().execute(&html);
+        let sources = extraction.sources("link");
+        assert_eq!(1, sources.len());
+        assert_eq!("#nav > a:nth-of-type(2) @href", sources[0].to_string());
+    }
+
+    #[cfg(feature = "serde_json")]
+    #[test]
+    fn json_round_trip() {
+        let source = r#"{
+            "name": "blog",
+            "version": "1.0.0",
+            "rules": [
+                { "name": "title", "selector": "h1", "required": true },
+                { "name": "links", "selector": "a, link", "extract": { "attr": "href" }, "multiple": true }
+            ]
+        }"#;
+        let pack = RulePack::from_json(source).unwrap();
+        assert_eq!(super::FORMAT_VERSION, pack.format);
+        assert_eq!(pack, RulePack::from_json(&pack.to_json()).unwrap());
+        assert!(matches!(
+            RulePack::from_json(r#"{"format": 99, "name": "x", "version": "1"}"#),
+            Err(RuleError::UnsupportedFormat(99))
+        ));
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn yaml_load() {
+        let source = "name: blog\nversion: '1'\nrules:\n  - name: title\n    selector: h1\n    extract: inner_html\n";
+        let pack = RulePack::from_yaml(source).unwrap();
+        assert_eq!(super::Extract::InnerHtml, pack.rules[0].extract);
+    }
+}
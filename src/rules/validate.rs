@@ -0,0 +1,295 @@
+//! Per-rule coercion and validation of extracted values.
+
+use std::fmt;
+
+use super::{Rule, RuleOutput};
+use crate::error::RuleError;
+
+/// A transformation applied to every extracted value before validation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Coerce {
+    /// Collapses runs of whitespace to single spaces.
+    CollapseWhitespace,
+    /// Converts to lowercase.
+    Lowercase,
+    /// Converts to uppercase.
+    Uppercase,
+    /// Parses a number, ignoring currency symbols and thousands separators, so
+    /// `"$1,299.00"` and `"1.299,00 €"` both become `"1299"`.
+    Number,
+    /// Like [`Coerce::Number`], but the number must be whole.
+    Integer,
+    /// Maps `true`/`yes`/`on`/`1` and `false`/`no`/`off`/`0` to `"true"` and `"false"`.
+    Boolean,
+}
+
+impl Coerce {
+    fn apply(self, value: &str) -> Option<String> {
+        match self {
+            Coerce::CollapseWhitespace => {
+                Some(value.split_whitespace().collect::<Vec<_>>().join(" "))
+            }
+            Coerce::Lowercase => Some(value.to_lowercase()),
+            Coerce::Uppercase => Some(value.to_uppercase()),
+            Coerce::Number => parse_number(value).map(|n| n.to_string()),
+            Coerce::Integer => parse_number(value)
+                .filter(|n| n.fract() == 0.0 && n.abs() < 9.0e15)
+                .map(|n| (n as i64).to_string()),
+            Coerce::Boolean => match value.trim().to_ascii_lowercase().as_str() {
+                "true" | "yes" | "on" | "1" => Some("true".to_owned()),
+                "false" | "no" | "off" | "0" => Some("false".to_owned()),
+                _ => None,
+            },
+        }
+    }
+}
+
+/// Parses a number written with either `.` or `,` as the decimal separator.
+fn parse_number(value: &str) -> Option<f64> {
+    let kept: String = value
+        .chars()
+        .filter(|c| c.is_ascii_digit() || matches!(c, '.' | ',' | '-'))
+        .collect();
+    let kept = kept.trim_matches(|c| c == '.' || c == ',');
+    // The last separator is the decimal one if it is followed by at most two digits, or if
+    // both kinds of separator are present.
+    let decimal = kept.rfind(['.', ',']).filter(|&i| {
+        let both = kept.contains('.') && kept.contains(',');
+        let repeated = kept.matches(&kept[i..i + 1]).count() > 1;
+        both || (!repeated && kept.len() - i - 1 <= 2)
+    });
+    let normalized: String = kept
+        .char_indices()
+        .filter_map(|(i, c)| match c {
+            '.' | ',' if Some(i) == decimal => Some('.'),
+            '.' | ',' => None,
+            c => Some(c),
+        })
+        .collect();
+    normalized.parse().ok()
+}
+
+/// A check every extracted value of a rule must pass.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Validator {
+    /// The value is not blank.
+    NonEmpty,
+    /// The value matches a regular expression, anywhere unless anchored.
+    #[cfg(feature = "regex")]
+    Pattern(String),
+    /// The value is a number within the bounds, inclusive.
+    Range {
+        /// The smallest allowed value.
+        #[cfg_attr(feature = "serde", serde(default))]
+        min: Option<f64>,
+        /// The largest allowed value.
+        #[cfg_attr(feature = "serde", serde(default))]
+        max: Option<f64>,
+    },
+    /// The value is one of the listed strings.
+    OneOf(Vec<String>),
+    /// The value is an absolute URL.
+    Url,
+}
+
+// `RulePack::validate` rejects NaN bounds, the only values that would break reflexivity.
+impl Eq for Validator {}
+
+impl Validator {
+    fn accepts(&self, value: &str) -> bool {
+        match self {
+            Validator::NonEmpty => !value.trim().is_empty(),
+            #[cfg(feature = "regex")]
+            Validator::Pattern(pattern) => {
+                regex::Regex::new(pattern).is_ok_and(|re| re.is_match(value))
+            }
+            Validator::Range { min, max } => value
+                .trim()
+                .parse::<f64>()
+                .is_ok_and(|n| min.is_none_or(|min| n >= min) && max.is_none_or(|max| n <= max)),
+            Validator::OneOf(allowed) => allowed.iter().any(|a| a == value),
+            Validator::Url => url::Url::parse(value.trim()).is_ok(),
+        }
+    }
+
+    /// Checks that the validator itself is well-formed.
+    pub(super) fn check(&self, rule: &str) -> Result<(), RuleError> {
+        let invalid = |reason: String| RuleError::InvalidValidator {
+            rule: rule.to_owned(),
+            reason,
+        };
+        match self {
+            #[cfg(feature = "regex")]
+            Validator::Pattern(pattern) => regex::Regex::new(pattern)
+                .map(|_| ())
+                .map_err(|e| invalid(e.to_string())),
+            Validator::Range { min, max }
+                if min.is_some_and(f64::is_nan) || max.is_some_and(f64::is_nan) =>
+            {
+                Err(invalid("range bounds must be numbers".to_owned()))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Why a value was rejected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ValidationFailure {
+    /// A coercion could not transform the value.
+    Coerce(Coerce),
+    /// A validator rejected the (coerced) value.
+    Validator(Validator),
+}
+
+/// A value that failed coercion or validation and was dropped from the output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ValidationError {
+    /// The rule name.
+    pub rule: String,
+    /// The value as extracted, before coercion.
+    pub value: String,
+    /// The coercion or validator that failed.
+    pub failure: ValidationFailure,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.failure {
+            ValidationFailure::Coerce(coerce) => write!(
+                f,
+                "rule {:?}: cannot coerce {:?} with {:?}",
+                self.rule, self.value, coerce
+            ),
+            ValidationFailure::Validator(validator) => write!(
+                f,
+                "rule {:?}: {:?} fails {:?}",
+                self.rule, self.value, validator
+            ),
+        }
+    }
+}
+
+impl Rule {
+    /// Adds a coercion, applied in the order added.
+    pub fn coerce(mut self, coerce: Coerce) -> Self {
+        self.coerce.push(coerce);
+        self
+    }
+
+    /// Adds a validator.
+    pub fn validate(mut self, validator: Validator) -> Self {
+        self.validators.push(validator);
+        self
+    }
+
+    /// Coerces and validates the values of an output in place, dropping the ones that
+    /// fail and returning why.
+    pub(super) fn check(&self, output: &mut RuleOutput) -> Vec<ValidationError> {
+        if self.coerce.is_empty() && self.validators.is_empty() {
+            return Vec::new();
+        }
+
+        let mut errors = Vec::new();
+        let mut kept = Vec::new();
+        let mut sources = std::mem::take(&mut output.sources).into_iter();
+        for original in std::mem::take(&mut output.values) {
+            let source = sources.next();
+            let mut value = original.clone();
+            let failure = self
+                .coerce
+                .iter()
+                .find_map(|&coerce| match coerce.apply(&value) {
+                    Some(coerced) => {
+                        value = coerced;
+                        None
+                    }
+                    None => Some(ValidationFailure::Coerce(coerce)),
+                })
+                .or_else(|| {
+                    self.validators
+                        .iter()
+                        .find(|v| !v.accepts(&value))
+                        .map(|v| ValidationFailure::Validator(v.clone()))
+                });
+            match failure {
+                Some(failure) => errors.push(ValidationError {
+                    rule: self.name.clone(),
+                    value: original,
+                    failure,
+                }),
+                None => {
+                    kept.push(value);
+                    output.sources.extend(source);
+                }
+            }
+        }
+        output.values = kept;
+        errors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_number, Coerce, ValidationFailure, Validator};
+    use crate::html::Html;
+    use crate::rules::{Extract, Rule, RulePack};
+
+    #[test]
+    fn parses_localized_numbers() {
+        assert_eq!(Some(1299.0), parse_number("$1,299.00"));
+        assert_eq!(Some(1299.5), parse_number("1.299,50 €"));
+        assert_eq!(Some(12.5), parse_number("12,5"));
+        assert_eq!(Some(1_000_000.0), parse_number("1,000,000"));
+        assert_eq!(None, parse_number("n/a"));
+    }
+
+    #[test]
+    fn invalid_values_are_collected_not_fatal() {
+        let mut pack = RulePack::new("shop", "1");
+        pack.push(
+            Rule::new("price", ".price", Extract::Text)
+                .unwrap()
+                .multiple()
+                .coerce(Coerce::Number)
+                .validate(Validator::Range {
+                    min: Some(0.0),
+                    max: None,
+                })
+                .required(),
+        )
+        .unwrap();
+        pack.push(
+            Rule::new("stock", ".stock", Extract::Text)
+                .unwrap()
+                .coerce(Coerce::Lowercase)
+                .validate(Validator::OneOf(vec!["in stock".into(), "sold out".into()])),
+        )
+        .unwrap();
+
+        let html = Html::parse_fragment(
+            r#"<b class="price">€ 9,99</b><b class="price">call us</b><b class="price">-1</b>
+            <i class="stock">Backorder</i>"#,
+        );
+        let extraction = pack.execute(&html);
+        assert_eq!(["9.99"], extraction.get_all("price"));
+        assert_eq!(None, extraction.get("stock"));
+        assert!(extraction.is_complete());
+
+        let failures: Vec<_> = extraction.invalid.iter().map(|e| &e.failure).collect();
+        assert_eq!(3, failures.len());
+        assert_eq!(&ValidationFailure::Coerce(Coerce::Number), failures[0]);
+        assert!(matches!(
+            failures[1],
+            ValidationFailure::Validator(Validator::Range { .. })
+        ));
+        assert_eq!("Backorder", extraction.invalid[2].value);
+    }
+}
@@ -0,0 +1,316 @@
+//! Allowlist sanitizing of parsed documents.
+//!
+//! [`Html::sanitize`] copies a document into a new tree keeping only what a [`Policy`]
+//! allows, so scraped markup can be rendered again without carrying script along.
+//!
+//! ```
+//! use scraper::sanitize::Policy;
+//! use scraper::Html;
+//!
+//! let html = Html::parse_fragment(
+//!     r#"<p onclick="steal()">Hi <a href="javascript:steal()">there</a><script>steal()</script></p>"#,
+//! );
+//! let clean = html.sanitize(&Policy::basic());
+//! assert_eq!("<p>Hi <a>there</a></p>", clean.root_element().inner_html());
+//! ```
+
+use std::collections::{HashMap, HashSet};
+
+use ego_tree::{NodeMut, NodeRef, Tree};
+use html5ever::{Attribute, LocalName, QualName};
+
+use crate::html::Html;
+use crate::node::{Element, Node};
+use crate::security::{is_event_handler, URL_ATTRIBUTES};
+
+/// Elements that are removed along with their content when they are not allowed, rather
+/// than replaced by their children.
+const DROPPED_WITH_CONTENT: &[&str] = &[
+    "script", "style", "template", "noscript", "iframe", "object", "embed", "frameset", "title",
+    "textarea", "select", "head",
+];
+
+/// Elements that give a document its structure, kept without attributes when not allowed.
+const STRUCTURE: &[&str] = &["html", "body"];
+
+/// The elements [`Policy::basic`] keeps.
+const BASIC_TAGS: &[&str] = &[
+    "a",
+    "abbr",
+    "b",
+    "blockquote",
+    "br",
+    "caption",
+    "cite",
+    "code",
+    "dd",
+    "del",
+    "div",
+    "dl",
+    "dt",
+    "em",
+    "figcaption",
+    "figure",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "hr",
+    "i",
+    "img",
+    "ins",
+    "kbd",
+    "li",
+    "mark",
+    "ol",
+    "p",
+    "pre",
+    "q",
+    "s",
+    "small",
+    "span",
+    "strong",
+    "sub",
+    "sup",
+    "table",
+    "tbody",
+    "td",
+    "tfoot",
+    "th",
+    "thead",
+    "time",
+    "tr",
+    "u",
+    "ul",
+];
+
+/// What [`Html::sanitize`] keeps.
+///
+/// A policy starts from nothing with [`Policy::new`], keeping only text, or from the
+/// preset [`Policy::basic`], and is extended with the `allow_*` methods. Event handler
+/// attributes are never kept, and elements outside the HTML namespace, such as SVG, are
+/// always removed with their content.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Policy {
+    tags: HashSet<String>,
+    /// The attributes allowed per tag, with `*` for every allowed tag.
+    attributes: HashMap<String, HashSet<String>>,
+    url_schemes: HashSet<String>,
+    css_properties: HashSet<String>,
+}
+
+impl Policy {
+    /// Creates a policy that keeps text only.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a policy for formatted user content: paragraphs, headings, lists, quotes,
+    /// code, tables, links and images, with `http`, `https` and `mailto` URLs and no
+    /// styles.
+    pub fn basic() -> Self {
+        Self::new()
+            .allow_tags(BASIC_TAGS)
+            .allow_attributes("*", &["title", "lang", "dir"])
+            .allow_attributes("a", &["href"])
+            .allow_attributes("img", &["src", "alt", "width", "height"])
+            .allow_attributes("td", &["colspan", "rowspan"])
+            .allow_attributes("th", &["colspan", "rowspan", "scope"])
+            .allow_attributes("time", &["datetime"])
+            .allow_url_schemes(&["http", "https", "mailto"])
+    }
+
+    /// Keeps the given elements. Elements that are not allowed are replaced by their
+    /// content, except for those such as `script` and `style` whose content is not text,
+    /// which are removed whole.
+    pub fn allow_tags(mut self, tags: &[&str]) -> Self {
+        self.tags
+            .extend(tags.iter().map(|tag| tag.to_ascii_lowercase()));
+        self
+    }
+
+    /// Keeps the given attributes on an allowed tag, or on every allowed tag for `*`.
+    pub fn allow_attributes(mut self, tag: &str, attributes: &[&str]) -> Self {
+        self.attributes
+            .entry(tag.to_ascii_lowercase())
+            .or_default()
+            .extend(attributes.iter().map(|attr| attr.to_ascii_lowercase()));
+        self
+    }
+
+    /// Keeps URLs with the given schemes, such as `https`, in URL attributes. Relative
+    /// URLs are always kept, and an attribute with any other scheme is removed.
+    pub fn allow_url_schemes(mut self, schemes: &[&str]) -> Self {
+        self.url_schemes
+            .extend(schemes.iter().map(|scheme| scheme.to_ascii_lowercase()));
+        self
+    }
+
+    /// Keeps the given properties, such as `color`, in allowed `style` attributes. Other
+    /// declarations are removed, as are any whose value has a `url(`, `image-set(` or
+    /// `expression(`, a CSS escape or a comment.
+    pub fn allow_css_properties(mut self, properties: &[&str]) -> Self {
+        self.css_properties.extend(
+            properties
+                .iter()
+                .map(|property| property.to_ascii_lowercase()),
+        );
+        self
+    }
+
+    fn allows_attribute(&self, tag: &str, name: &str) -> bool {
+        let allowed = |key: &str| self.attributes.get(key).is_some_and(|a| a.contains(name));
+        allowed(tag) || allowed("*")
+    }
+
+    /// Returns true if a URL is relative or has an allowed scheme, reading it the way
+    /// browsers do: tabs and newlines are ignored and leading spaces trimmed.
+    pub(crate) fn allows_url(&self, url: &str) -> bool {
+        let url: String = url
+            .trim_start_matches(|c: char| c <= ' ')
+            .chars()
+            .filter(|c| !matches!(c, '\t' | '\n' | '\r'))
+            .collect();
+        let scheme_end = url.find([':', '/', '?', '#']);
+        match scheme_end {
+            Some(end) if url[end..].starts_with(':') => {
+                let scheme = url[..end].to_ascii_lowercase();
+                self.url_schemes.contains(&scheme)
+            }
+            _ => true,
+        }
+    }
+
+    /// Returns the declarations of a `style` attribute with allowed properties.
+    fn filter_style(&self, style: &str) -> String {
+        style
+            .split(';')
+            .filter_map(|declaration| {
+                let (property, value) = declaration.split_once(':')?;
+                let property = property.trim().to_ascii_lowercase();
+                let lower = value.to_ascii_lowercase();
+                // Escapes and comments could spell out the functions checked for here.
+                let unsafe_value = ["url(", "expression(", "image-set(", "\\", "/*"]
+                    .iter()
+                    .any(|needle| lower.contains(needle));
+                (self.css_properties.contains(&property) && !unsafe_value)
+                    .then(|| format!("{}: {}", property, value.trim()))
+            })
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+
+    /// Returns the attributes of `element` the policy keeps.
+    fn attributes(&self, element: &Element) -> Vec<Attribute> {
+        let tag = element.name();
+        element
+            .attrs()
+            .filter_map(|(name, value)| {
+                let name = name.to_ascii_lowercase();
+                if is_event_handler(&name) || !self.allows_attribute(tag, &name) {
+                    return None;
+                }
+                let value = match name.as_str() {
+                    "style" => Some(self.filter_style(value)).filter(|style| !style.is_empty())?,
+                    "srcset" => {
+                        let urls = value.split(',').filter_map(|c| c.split_whitespace().next());
+                        urls.clone()
+                            .all(|url| self.allows_url(url))
+                            .then(|| value.to_owned())?
+                    }
+                    _ if URL_ATTRIBUTES.contains(&name.as_str()) => {
+                        self.allows_url(value).then(|| value.to_owned())?
+                    }
+                    _ => value.to_owned(),
+                };
+                Some(Attribute {
+                    name: QualName::new(None, ns!(), LocalName::from(name)),
+                    value: value.into(),
+                })
+            })
+            .collect()
+    }
+
+    /// Appends what the policy keeps of the children of `from` to `to`.
+    fn copy_children(&self, from: NodeRef<Node>, to: &mut NodeMut<Node>) {
+        for child in from.children() {
+            match child.value() {
+                Node::Text(text) => {
+                    to.append(Node::Text(text.clone()));
+                }
+                Node::Element(element) if element.name.ns != ns!(html) => {}
+                Node::Element(element) => {
+                    let tag = element.name();
+                    if self.tags.contains(tag) {
+                        let kept = Element::new(element.name.clone(), self.attributes(element));
+                        self.copy_children(child, &mut to.append(Node::Element(kept)));
+                    } else if STRUCTURE.contains(&tag) {
+                        let kept = Element::new(element.name.clone(), Vec::new());
+                        self.copy_children(child, &mut to.append(Node::Element(kept)));
+                    } else if !DROPPED_WITH_CONTENT.contains(&tag) {
+                        self.copy_children(child, to);
+                    }
+                }
+                Node::Doctype(doctype) => {
+                    to.append(Node::Doctype(doctype.clone()));
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+impl Html {
+    /// Returns a copy of the document with only what `policy` allows.
+    ///
+    /// Text is kept and escaped again on serialization; comments and processing
+    /// instructions are dropped. A document keeps its `html` and `body` elements, while
+    /// the `head` is dropped unless allowed.
+    pub fn sanitize(&self, policy: &Policy) -> Html {
+        let mut tree = Tree::new(self.tree.root().value().clone());
+        policy.copy_children(self.tree.root(), &mut tree.root_mut());
+        Html {
+            lang: self.lang.clone(),
+            fetch: self.fetch.clone(),
+            encoding: self.encoding,
+            ..Html::from_parts(self.quirks_mode, tree)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Policy;
+    use crate::html::Html;
+
+    #[test]
+    fn filters_urls_styles_and_foreign_content() {
+        let html = Html::parse_document(
+            "<head><title>T</title><style>p{}</style></head>\
+             <body class=x><div><custom>Text</custom>\
+             <a href=' JaVa&#9;Script:x()'>bad</a><a href='/rel' target=_blank>rel</a>\
+             <img src='data:image/png;base64,AA' srcset='a.png 1x, https://c/b.png 2x'>\
+             <p style='color: red; background: url(https://t/p.gif); position:fixed'>Red</p>\
+             <p style='background:\\75rl(https://t/p.gif); color: \\75rl(https://t/p.gif)'>E</p>\
+             <p style='background: image-set(\"https://t/p.gif\" 1x); color: ur/**/l(x)'>S</p>\
+             <svg><script>x()</script><text>no</text></svg><!-- gone --></div></body>",
+        );
+        let policy = Policy::basic()
+            .allow_attributes("img", &["srcset"])
+            .allow_attributes("p", &["style"])
+            .allow_css_properties(&["color", "background"]);
+        assert_eq!(
+            "<html><body><div>Text<a>bad</a><a href=\"/rel\">rel</a>\
+             <img srcset=\"a.png 1x, https://c/b.png 2x\">\
+             <p style=\"color: red\">Red</p><p>E</p><p>S</p></div></body></html>",
+            html.sanitize(&policy).root_element().html()
+        );
+        let html = Html::parse_fragment("<b>Bold</b> <i>and</i> <script>x()</script>text");
+        assert_eq!(
+            "Bold and text",
+            html.sanitize(&Policy::new()).root_element().inner_html()
+        );
+    }
+}
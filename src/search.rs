@@ -0,0 +1,317 @@
+//! Finding and marking terms in the visible text of a document.
+
+use std::ops::Range;
+
+use ego_tree::NodeId;
+use html5ever::{LocalName, QualName};
+use unicode_normalization::char::is_combining_mark;
+
+use crate::element_ref::ElementRef;
+use crate::html::Html;
+use crate::node::{Element, Node, Text};
+use crate::text::compare::{fold_chars, CompareOptions};
+use crate::text::normalized::HIDDEN_TEXT;
+
+/// Returns the byte ranges of whole-word, case-insensitive matches of any of `terms` in
+/// `text`, sorted and without overlaps; at the same start the longest term wins.
+pub(crate) fn find_terms(text: &str, terms: &[&str]) -> Vec<Range<usize>> {
+    find_terms_with(text, terms, &CompareOptions::default())
+}
+
+/// Like [`find_terms`], comparing text and terms after [`normalize_for_compare_with`].
+///
+/// [`normalize_for_compare_with`]: crate::text::normalize_for_compare_with
+pub(crate) fn find_terms_with(
+    text: &str,
+    terms: &[&str],
+    options: &CompareOptions,
+) -> Vec<Range<usize>> {
+    let chars = fold_chars(text, options);
+    let terms: Vec<Vec<char>> = terms
+        .iter()
+        .map(|t| {
+            fold_chars(t.trim(), options)
+                .into_iter()
+                .map(|(_, c)| c)
+                .collect::<Vec<_>>()
+        })
+        .filter(|t| !t.is_empty())
+        .collect();
+
+    let is_word = |i: usize| {
+        chars
+            .get(i)
+            .is_some_and(|(_, c)| c.is_alphanumeric() || is_combining_mark(*c))
+    };
+    // Matches must not start or end inside the folding of a single source character.
+    let same_source = |i: usize, j: usize| match (chars.get(i), chars.get(j)) {
+        (Some((a, _)), Some((b, _))) => a == b,
+        _ => false,
+    };
+
+    let mut ranges = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let longest = terms
+            .iter()
+            .filter(|term| {
+                let end = i + term.len();
+                end <= chars.len()
+                    && term.iter().zip(&chars[i..]).all(|(t, (_, c))| c == t)
+                    && !(i > 0 && (same_source(i - 1, i) || is_word(i - 1) && is_word(i)))
+                    && !same_source(end - 1, end)
+                    && !(is_word(end - 1) && is_word(end))
+            })
+            .map(Vec::len)
+            .max();
+        match longest {
+            Some(len) => {
+                ranges.push(chars[i].0.start..chars[i + len - 1].0.end);
+                i += len;
+            }
+            None => i += 1,
+        }
+    }
+    ranges
+}
+
+/// Returns true if the text node is inside an element whose text is not displayed.
+pub(crate) fn in_hidden_text(html: &Html, id: NodeId) -> bool {
+    html.tree.get(id).is_some_and(|node| {
+        node.ancestors()
+            .filter_map(|a| a.value().as_element())
+            .any(|e| HIDDEN_TEXT.contains(&e.name()))
+    })
+}
+
+/// A match of [`Html::search_text`].
+#[derive(Debug, Clone)]
+pub struct SearchHit<'a> {
+    /// The matched text, with whitespace collapsed.
+    pub text: String,
+    /// Up to the requested number of characters of visible text before the match.
+    pub before: String,
+    /// Up to the requested number of characters of visible text after the match.
+    pub after: String,
+    /// The innermost element containing the whole match.
+    pub element: ElementRef<'a>,
+    /// The [`ElementRef::css_path`] of `element`.
+    pub css_path: String,
+}
+
+impl Html {
+    /// Searches the visible text for `query` with 40 characters of context on each side.
+    pub fn search_text(&self, query: &str) -> Vec<SearchHit<'_>> {
+        self.search_text_with(query, 40)
+    }
+
+    /// Searches the visible text for whole-word, case-insensitive matches of `query`,
+    /// returning `context` characters of surrounding text with each hit.
+    ///
+    /// Whitespace is collapsed in both the query and the text, and matches may span
+    /// elements, so a quote is found however the page splits it into tags.
+    ///
+    /// ```
+    /// use scraper::Html;
+    ///
+    /// let html = Html::parse_document(
+    ///     "<article><p>We choose to go to <em>the   moon</em> in this decade.</p></article>",
+    /// );
+    /// let hits = html.search_text_with("go to the Moon", 8);
+    /// assert_eq!("go to the moon", hits[0].text);
+    /// assert_eq!("oose to ", hits[0].before);
+    /// assert_eq!(" in this", hits[0].after);
+    /// assert_eq!("html > body > article > p", hits[0].css_path);
+    /// ```
+    pub fn search_text_with(&self, query: &str, context: usize) -> Vec<SearchHit<'_>> {
+        self.search_text_with_options(query, context, &CompareOptions::default())
+    }
+
+    /// Searches the visible text like [`Html::search_text_with`], comparing it with the
+    /// query after [`normalize_for_compare_with`](crate::text::normalize_for_compare_with).
+    ///
+    /// ```
+    /// use scraper::text::CompareOptions;
+    /// use scraper::Html;
+    ///
+    /// let html = Html::parse_document("<p>Nach Zürich über die Straße</p>");
+    /// let options = CompareOptions { strip_accents: true, ..CompareOptions::locale("de") };
+    /// let hits = html.search_text_with_options("zurich uber die STRASSE", 0, &options);
+    /// assert_eq!("Zürich über die Straße", hits[0].text);
+    /// ```
+    pub fn search_text_with_options(
+        &self,
+        query: &str,
+        context: usize,
+        options: &CompareOptions,
+    ) -> Vec<SearchHit<'_>> {
+        let query = query.split_whitespace().collect::<Vec<_>>().join(" ");
+        let visible = self.normalized_text();
+        let text = visible.as_str();
+
+        find_terms_with(text, &[&query], options)
+            .into_iter()
+            .filter_map(|range| {
+                let element = self.common_element(&visible.nodes(range.clone()))?;
+
+                let before = &text[..range.start];
+                let skip = before.chars().count().saturating_sub(context);
+                Some(SearchHit {
+                    text: text[range.clone()].to_owned(),
+                    before: before.chars().skip(skip).collect(),
+                    after: text[range.end..].chars().take(context).collect(),
+                    css_path: element.css_path(),
+                    element,
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the innermost element that contains all of `nodes`.
+    fn common_element(&self, nodes: &[NodeId]) -> Option<ElementRef<'_>> {
+        let (first, rest) = nodes.split_first()?;
+        let ancestors_of = |id: NodeId| -> Vec<NodeId> {
+            self.tree
+                .get(id)
+                .map(|n| n.ancestors().map(|a| a.id()).collect())
+                .unwrap_or_default()
+        };
+        let others: Vec<Vec<NodeId>> = rest.iter().map(|&id| ancestors_of(id)).collect();
+
+        ancestors_of(*first)
+            .into_iter()
+            .filter(|id| others.iter().all(|a| a.contains(id)))
+            .find_map(|id| ElementRef::wrap(self.tree.get(id)?))
+    }
+
+    /// Wraps whole-word, case-insensitive matches of `terms` in `wrapper_tag` elements, such
+    /// as `mark`, and returns the number of matches wrapped.
+    ///
+    /// Text inside `script`, `style`, `noscript`, `template`, `textarea` and `title` is left
+    /// alone, as is text already inside a `wrapper_tag` element, so highlighting twice does
+    /// not nest.
+    ///
+    /// ```
+    /// use scraper::Html;
+    ///
+    /// let mut html = Html::parse_fragment("<p>Rust is fast. Rusty is not rust.</p>");
+    /// assert_eq!(2, html.highlight(&["rust"], "mark"));
+    /// assert_eq!(
+    ///     "<p><mark>Rust</mark> is fast. Rusty is not <mark>rust</mark>.</p>",
+    ///     html.root_element().inner_html()
+    /// );
+    /// ```
+    pub fn highlight(&mut self, terms: &[&str], wrapper_tag: &str) -> usize {
+        // Walk from the root: `tree.nodes()` would also yield previously detached nodes.
+        let plan: Vec<(NodeId, String, Vec<Range<usize>>)> = self
+            .tree
+            .root()
+            .descendants()
+            .filter_map(|node| {
+                let text = node.value().as_text()?;
+                let wrapped = node
+                    .parent()
+                    .and_then(|p| p.value().as_element().map(|e| e.name() == wrapper_tag))
+                    .unwrap_or(false);
+                if wrapped || in_hidden_text(self, node.id()) {
+                    return None;
+                }
+                let ranges = find_terms(text, terms);
+                (!ranges.is_empty()).then(|| (node.id(), text.to_string(), ranges))
+            })
+            .collect();
+
+        let name = QualName::new(None, ns!(html), LocalName::from(wrapper_tag));
+        let text_node = |text: &str| Node::Text(Text { text: text.into() });
+        let mut count = 0;
+
+        for (id, text, ranges) in plan {
+            let mut cursor = 0;
+            for range in ranges {
+                if range.start > cursor {
+                    self.insert_before(id, text_node(&text[cursor..range.start]));
+                }
+                let wrapper = Node::Element(Element::new(name.clone(), Vec::new()));
+                if let Some(wrapper) = self.insert_before(id, wrapper) {
+                    self.append_child(wrapper, text_node(&text[range.clone()]));
+                }
+                cursor = range.end;
+                count += 1;
+            }
+            if cursor < text.len() {
+                self.insert_before(id, text_node(&text[cursor..]));
+            }
+            self.detach(id);
+        }
+        count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find_terms, find_terms_with};
+    use crate::html::Html;
+    use crate::text::CompareOptions;
+
+    #[test]
+    fn matches_whole_words_longest_first() {
+        let text = "New York, new-york and NEW YORKER";
+        let ranges = find_terms(text, &["new", "new york"]);
+        let found: Vec<_> = ranges.into_iter().map(|r| &text[r]).collect();
+        assert_eq!(vec!["New York", "new", "NEW"], found);
+        assert_eq!(vec![0..7], find_terms("Straße", &["STRASSE", ""]));
+    }
+
+    #[test]
+    fn folds_width_and_locale_cases() {
+        let text = "ＲＵＳＴ and diyarbakır, caf\u{e9}";
+        let found = |terms: &[&str], options: CompareOptions| -> Vec<&str> {
+            find_terms_with(text, terms, &options)
+                .into_iter()
+                .map(|r| &text[r])
+                .collect()
+        };
+        assert_eq!(
+            vec!["ＲＵＳＴ"],
+            found(&["rust", "diyarbakir"], CompareOptions::default())
+        );
+        assert_eq!(
+            vec!["diyarbakır"],
+            found(&["DİYARBAKIR"], CompareOptions::locale("az"))
+        );
+        assert!(found(&["DİYARBAKIR", "cafe"], CompareOptions::default()).is_empty());
+        assert_eq!(
+            vec!["café"],
+            found(&["cafe\u{301}"], CompareOptions::default())
+        );
+    }
+
+    #[test]
+    fn search_spans_elements_and_skips_hidden_text() {
+        let html = Html::parse_document(
+            "<title>a cat</title><div><p>One cat.</p><p>Two <b>big</b> <i>cats</i></p></div>",
+        );
+        let hits = html.search_text_with("cat", 5);
+        assert_eq!(1, hits.len());
+        assert_eq!("One ", hits[0].before);
+        assert_eq!(". Two", hits[0].after);
+
+        let hits = html.search_text("big cats");
+        assert_eq!("p", hits[0].element.value().name());
+        assert_eq!("html > body > div > p:nth-of-type(2)", hits[0].css_path);
+        let hits = html.search_text("cat. Two");
+        assert_eq!("div", hits[0].element.value().name());
+    }
+
+    #[test]
+    fn skips_scripts_and_existing_marks() {
+        let mut html =
+            Html::parse_document("<title>cat</title><p>cat <em>cat</em><script>cat</script></p>");
+        assert_eq!(2, html.highlight(&["cat"], "mark"));
+        assert_eq!(0, html.highlight(&["cat"], "mark"));
+        assert!(html
+            .html()
+            .contains("<p><mark>cat</mark> <em><mark>cat</mark></em><script>cat</script></p>"));
+        assert!(html.html().contains("<title>cat</title>"));
+    }
+}
@@ -0,0 +1,481 @@
+//! An inventory of markup that security audits look at.
+
+use url::Url;
+
+use crate::element_ref::ElementRef;
+use crate::html::Html;
+use crate::selector::Selector;
+
+lazy_static! {
+    static ref SUBRESOURCES: Selector = Selector::parse("script[src], link[href][rel]").unwrap();
+    static ref CSP_META: Selector = Selector::parse("meta[http-equiv][content]").unwrap();
+}
+
+/// Attributes that browsers navigate to or load, where a `javascript:` URL runs script.
+pub(crate) const URL_ATTRIBUTES: &[&str] = &[
+    "href",
+    "src",
+    "action",
+    "formaction",
+    "data",
+    "poster",
+    "background",
+    "xlink:href",
+];
+
+/// What a [`SecurityFinding`] is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum FindingKind {
+    /// An inline `on*` event handler attribute.
+    EventHandler,
+    /// A `javascript:` URL in a navigable or loaded attribute.
+    JavascriptUrl,
+    /// A `target="_blank"` link without `rel="noopener"` or `rel="noreferrer"`, which gives
+    /// the opened page access to `window.opener` in older browsers.
+    MissingNoopener,
+    /// A form that posts to an origin other than the page's.
+    ThirdPartyForm,
+}
+
+/// A place in the markup worth a security reviewer's attention.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SecurityFinding {
+    /// What was found.
+    pub kind: FindingKind,
+    /// The CSS path of the element.
+    pub path: String,
+    /// The attribute that was found, such as `onclick` or `href`.
+    pub attribute: String,
+    /// The value of the attribute; for forms, the resolved action URL.
+    pub value: String,
+}
+
+/// What kind of subresource a [`Subresource`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum SubresourceKind {
+    /// A `<script src>`.
+    Script,
+    /// A `<link rel="stylesheet">`.
+    Stylesheet,
+    /// A `<link rel="preload">` or `<link rel="modulepreload">`.
+    Preload,
+}
+
+/// The CORS mode of a `crossorigin` attribute.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum CrossOrigin {
+    /// `anonymous`, which an empty or invalid value also means.
+    Anonymous,
+    /// `use-credentials`.
+    UseCredentials,
+}
+
+/// One hash of an `integrity` attribute.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IntegrityHash {
+    /// `sha256`, `sha384` or `sha512`.
+    pub algorithm: String,
+    /// The base64 digest.
+    pub digest: String,
+}
+
+/// A script, stylesheet or preload and its Subresource Integrity metadata.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Subresource {
+    /// What kind of subresource it is.
+    pub kind: SubresourceKind,
+    /// The resolved URL.
+    pub url: Url,
+    /// The valid hashes of the `integrity` attribute; browsers ignore unknown algorithms.
+    pub integrity: Vec<IntegrityHash>,
+    /// The `crossorigin` attribute, if present.
+    pub crossorigin: Option<CrossOrigin>,
+    /// Whether the URL has another origin than the page.
+    pub third_party: bool,
+}
+
+/// A policy declared with `<meta http-equiv="Content-Security-Policy">`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ContentSecurityPolicy {
+    /// The directive names, lowercased, and their source lists, in order. Repeated
+    /// directives are dropped, as browsers ignore them.
+    pub directives: Vec<(String, Vec<String>)>,
+}
+
+impl ContentSecurityPolicy {
+    /// Parses a serialized policy.
+    pub fn parse(policy: &str) -> Self {
+        let mut directives: Vec<(String, Vec<String>)> = Vec::new();
+        for directive in policy.split(';') {
+            let mut tokens = directive.split_ascii_whitespace();
+            let Some(name) = tokens.next() else {
+                continue;
+            };
+            let name = name.to_ascii_lowercase();
+            if directives.iter().all(|(n, _)| *n != name) {
+                directives.push((name, tokens.map(str::to_owned).collect()));
+            }
+        }
+        ContentSecurityPolicy { directives }
+    }
+
+    /// Returns the source list of a directive, by its case-insensitive name.
+    pub fn directive(&self, name: &str) -> Option<&[String]> {
+        self.directives
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, sources)| sources.as_slice())
+    }
+}
+
+/// The Subresource Integrity and Content Security Policy metadata of a page.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntegrityReport {
+    /// The scripts, stylesheets and preloads, in document order.
+    pub subresources: Vec<Subresource>,
+    /// The policies of `Content-Security-Policy` meta elements, in document order.
+    pub policies: Vec<ContentSecurityPolicy>,
+}
+
+impl IntegrityReport {
+    /// Returns the third-party subresources that are loaded without an integrity check.
+    pub fn unverified(&self) -> impl Iterator<Item = &Subresource> {
+        self.subresources
+            .iter()
+            .filter(|s| s.third_party && s.integrity.is_empty())
+    }
+}
+
+/// Parses the hashes of an `integrity` attribute, skipping unknown algorithms.
+fn integrity_hashes(integrity: &str) -> Vec<IntegrityHash> {
+    integrity
+        .split_ascii_whitespace()
+        .filter_map(|token| {
+            let (algorithm, digest) = token.split_once('-')?;
+            let algorithm = algorithm.to_ascii_lowercase();
+            // Options follow the digest after a `?`.
+            let digest = digest.split('?').next()?;
+            (matches!(algorithm.as_str(), "sha256" | "sha384" | "sha512") && !digest.is_empty())
+                .then(|| IntegrityHash {
+                    algorithm,
+                    digest: digest.to_owned(),
+                })
+        })
+        .collect()
+}
+
+/// Returns true if a URL attribute value runs script, parsing it the way browsers do:
+/// tabs and newlines are ignored anywhere, and leading control characters and spaces are
+/// trimmed.
+fn is_javascript_url(value: &str) -> bool {
+    let url: String = value
+        .trim_start_matches(|c: char| c <= ' ')
+        .chars()
+        .filter(|c| !matches!(c, '\t' | '\n' | '\r'))
+        .take("javascript:".len())
+        .collect();
+    url.eq_ignore_ascii_case("javascript:")
+}
+
+pub(crate) fn is_event_handler(name: &str) -> bool {
+    name.len() > 2
+        && name
+            .get(..2)
+            .is_some_and(|on| on.eq_ignore_ascii_case("on"))
+        && name.bytes().all(|b| b.is_ascii_alphabetic())
+}
+
+/// Returns the form whose submission a form or submit button controls.
+fn form_of(element: ElementRef) -> Option<ElementRef> {
+    if element.value().name() == "form" {
+        return Some(element);
+    }
+    element
+        .ancestors()
+        .filter_map(ElementRef::wrap)
+        .find(|a| a.value().name() == "form")
+}
+
+impl Html {
+    /// Lists inline event handlers, `javascript:` URLs, `target="_blank"` links without
+    /// `noopener`, and forms posting to third-party origins, in document order.
+    ///
+    /// Form actions are resolved against the [base URL](Html::base_url) of `base`, the URL
+    /// of the page, and compared with the origin of `base`. Submit buttons with their own `formaction` or `formmethod` are checked too.
+    ///
+    /// ```
+    /// use scraper::security::FindingKind;
+    /// use scraper::Html;
+    /// use url::Url;
+    ///
+    /// let html = Html::parse_document(r#"
+    ///     <body onload="init()">
+    ///       <a href="JaVaScRiPt:void(0)">menu</a>
+    ///       <a href="https://partner.example/" target="_blank" rel="external">partner</a>
+    ///       <form method="post" action="https://collect.example/login">
+    ///         <input type="password" name="pass">
+    ///       </form>
+    ///     </body>
+    /// "#);
+    /// let base = Url::parse("https://shop.example/account").unwrap();
+    /// let findings = html.security_findings(&base);
+    /// let kinds: Vec<_> = findings.iter().map(|f| f.kind).collect();
+    /// assert_eq!(
+    ///     vec![
+    ///         FindingKind::EventHandler,
+    ///         FindingKind::JavascriptUrl,
+    ///         FindingKind::MissingNoopener,
+    ///         FindingKind::ThirdPartyForm,
+    ///     ],
+    ///     kinds
+    /// );
+    /// assert_eq!(("onload", "init()"), (findings[0].attribute.as_str(), findings[0].value.as_str()));
+    /// assert_eq!("https://collect.example/login", findings[3].value);
+    /// ```
+    pub fn security_findings(&self, base: &Url) -> Vec<SecurityFinding> {
+        let resolve = self.base_url(base);
+        let mut findings = Vec::new();
+        for element in self
+            .root_element()
+            .descendants()
+            .filter_map(ElementRef::wrap)
+        {
+            let value = element.value();
+            let finding = |kind, attribute: &str, value: &str| SecurityFinding {
+                kind,
+                path: element.css_path(),
+                attribute: attribute.to_owned(),
+                value: value.to_owned(),
+            };
+
+            for (name, attr) in value.attrs() {
+                if is_event_handler(name) {
+                    findings.push(finding(FindingKind::EventHandler, name, attr));
+                } else if URL_ATTRIBUTES.contains(&name) && is_javascript_url(attr) {
+                    findings.push(finding(FindingKind::JavascriptUrl, name, attr));
+                }
+            }
+
+            let target = value.attr("target").map(str::trim);
+            if target.is_some_and(|t| t.eq_ignore_ascii_case("_blank"))
+                && matches!(value.name(), "a" | "area")
+            {
+                let rel = value.attr("rel").unwrap_or_default();
+                let protected = rel.split_ascii_whitespace().any(|token| {
+                    token.eq_ignore_ascii_case("noopener")
+                        || token.eq_ignore_ascii_case("noreferrer")
+                });
+                if let Some(href) = value.attr("href").filter(|_| !protected) {
+                    findings.push(finding(FindingKind::MissingNoopener, "href", href));
+                }
+            }
+
+            let submitter = matches!(value.name(), "button" | "input")
+                && (value.attr("formaction").is_some() || value.attr("formmethod").is_some());
+            if value.name() == "form" || submitter {
+                let Some(form) = form_of(element) else {
+                    continue;
+                };
+                let (action_attr, method_attr) = if submitter {
+                    ("formaction", "formmethod")
+                } else {
+                    ("action", "method")
+                };
+                let method = value
+                    .attr(method_attr)
+                    .or_else(|| form.value().attr("method"))
+                    .unwrap_or("get");
+                let action = value
+                    .attr(action_attr)
+                    .or_else(|| form.value().attr("action"))
+                    .unwrap_or_default();
+                let Ok(url) = resolve.join(action.trim()) else {
+                    continue;
+                };
+                let third_party =
+                    matches!(url.scheme(), "http" | "https") && url.origin() != base.origin();
+                if method.trim().eq_ignore_ascii_case("post") && third_party {
+                    findings.push(finding(
+                        FindingKind::ThirdPartyForm,
+                        action_attr,
+                        url.as_str(),
+                    ));
+                }
+            }
+        }
+        findings
+    }
+
+    /// Collects the `integrity` and `crossorigin` attributes of scripts, stylesheets and
+    /// preloads, with URLs resolved against the [base URL](Html::base_url) of `base`, and the
+    /// policies of
+    /// `<meta http-equiv="Content-Security-Policy">` elements.
+    ///
+    /// ```
+    /// use scraper::security::{CrossOrigin, SubresourceKind};
+    /// use scraper::Html;
+    /// use url::Url;
+    ///
+    /// let html = Html::parse_document(r#"
+    ///     <meta http-equiv="content-security-policy" content="default-src 'self'; img-src *">
+    ///     <link rel="stylesheet" href="https://cdn.example/app.css"
+    ///           integrity="sha384-oqVuAfXRKap7fdgcCY5uykM6+R9GqQ8K/uxy9rx7HNQlGYl1kPzQho1wx4JwY8wC"
+    ///           crossorigin="anonymous">
+    ///     <script src="https://cdn.example/lib.js"></script>
+    ///     <script src="/app.js"></script>
+    /// "#);
+    /// let report = html.integrity_report(&Url::parse("https://shop.example/").unwrap());
+    /// let css = &report.subresources[0];
+    /// assert_eq!(SubresourceKind::Stylesheet, css.kind);
+    /// assert_eq!("sha384", css.integrity[0].algorithm);
+    /// assert_eq!(Some(CrossOrigin::Anonymous), css.crossorigin);
+    ///
+    /// let unverified: Vec<_> = report.unverified().map(|s| s.url.as_str()).collect();
+    /// assert_eq!(vec!["https://cdn.example/lib.js"], unverified);
+    /// let policy = &report.policies[0];
+    /// assert_eq!(Some(&["'self'".to_owned()][..]), policy.directive("default-src"));
+    /// ```
+    pub fn integrity_report(&self, base: &Url) -> IntegrityReport {
+        let resolve = self.base_url(base);
+        let root = self.root_element();
+        let mut subresources = Vec::new();
+        for element in root.select(&SUBRESOURCES) {
+            let value = element.value();
+            let (kind, href) = if value.name() == "script" {
+                (SubresourceKind::Script, value.attr("src"))
+            } else {
+                let rel = value.attr("rel").unwrap_or_default();
+                let has = |token: &str| {
+                    rel.split_ascii_whitespace()
+                        .any(|t| t.eq_ignore_ascii_case(token))
+                };
+                let kind = if has("stylesheet") {
+                    SubresourceKind::Stylesheet
+                } else if has("preload") || has("modulepreload") {
+                    SubresourceKind::Preload
+                } else {
+                    continue;
+                };
+                (kind, value.attr("href"))
+            };
+            let Some(Ok(url)) = href.map(|href| resolve.join(href.trim())) else {
+                continue;
+            };
+            subresources.push(Subresource {
+                kind,
+                third_party: url.origin() != base.origin(),
+                url,
+                integrity: integrity_hashes(value.attr("integrity").unwrap_or_default()),
+                crossorigin: value.attr("crossorigin").map(|mode| {
+                    if mode.trim().eq_ignore_ascii_case("use-credentials") {
+                        CrossOrigin::UseCredentials
+                    } else {
+                        CrossOrigin::Anonymous
+                    }
+                }),
+            });
+        }
+
+        let policies = root
+            .select(&CSP_META)
+            .filter(|meta| {
+                let name = meta.value().attr("http-equiv").unwrap_or_default();
+                name.trim().eq_ignore_ascii_case("content-security-policy")
+            })
+            .filter_map(|meta| meta.value().attr("content"))
+            .map(ContentSecurityPolicy::parse)
+            .collect();
+
+        IntegrityReport {
+            subresources,
+            policies,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use url::Url;
+
+    use super::FindingKind;
+    use crate::html::Html;
+
+    #[test]
+    fn browser_url_parsing_and_safe_markup() {
+        let html = Html::parse_document(
+            "<a href=' java\tscript:alert(1)'>x</a><a href='/javascript:'>y</a>\
+             <a href='https://a.example/' target='_blank' rel='NoOpener'>z</a>\
+             <form method='post' action='/login'><button formaction='//evil.example/'>go</button></form>\
+             <form action='https://search.example/'><input name='q'></form>\
+             <div data-online='true'></div>",
+        );
+        let base = Url::parse("https://site.example/").unwrap();
+        let findings = html.security_findings(&base);
+        let found: Vec<_> = findings
+            .iter()
+            .map(|f| (f.kind, f.attribute.as_str()))
+            .collect();
+        assert_eq!(
+            vec![
+                (FindingKind::JavascriptUrl, "href"),
+                (FindingKind::ThirdPartyForm, "formaction"),
+            ],
+            found
+        );
+        assert_eq!("https://evil.example/", findings[1].value);
+    }
+
+    #[test]
+    fn integrity_metadata_is_parsed_like_browsers() {
+        let html = Html::parse_document(
+            "<meta http-equiv='Content-Security-Policy-Report-Only' content='default-src none'>\
+             <meta http-equiv='Content-Security-Policy' content='Script-Src a; script-src b;;'>\
+             <link rel='icon' href='/i.png'>\
+             <link rel='modulepreload' href='/m.js' integrity='md5-x sha512-abc?opt' crossorigin='bogus'>",
+        );
+        let report = html.integrity_report(&Url::parse("https://site.example/").unwrap());
+        assert_eq!(1, report.policies.len());
+        assert_eq!(
+            Some(&["a".to_owned()][..]),
+            report.policies[0].directive("script-src")
+        );
+        assert_eq!(1, report.subresources.len());
+        let preload = &report.subresources[0];
+        assert_eq!(
+            ("sha512", "abc"),
+            (
+                preload.integrity[0].algorithm.as_str(),
+                preload.integrity[0].digest.as_str()
+            )
+        );
+        assert_eq!(Some(super::CrossOrigin::Anonymous), preload.crossorigin);
+        assert!(!preload.third_party);
+    }
+
+    #[test]
+    fn urls_resolve_against_the_base_element() {
+        let html = Html::parse_document(
+            "<base href='https://cdn.example/'><script src='lib.js'></script>\
+             <form method='post' action='login'></form>",
+        );
+        let page = Url::parse("https://site.example/").unwrap();
+        let report = html.integrity_report(&page);
+        assert_eq!(
+            "https://cdn.example/lib.js",
+            report.subresources[0].url.as_str()
+        );
+        assert!(report.subresources[0].third_party);
+        let findings = html.security_findings(&page);
+        assert_eq!(FindingKind::ThirdPartyForm, findings[0].kind);
+        assert_eq!("https://cdn.example/login", findings[0].value);
+    }
+}
@@ -5,7 +5,8 @@ use crate::error::SelectorErrorKind;
 use cssparser::ToCss;
 use html5ever::{LocalName, Namespace};
 use selectors::parser::SelectorParseErrorKind;
-use selectors::{matching, parser, NthIndexCache};
+use selectors::visitor::SelectorVisitor;
+use selectors::{matching, parser, NthIndexCache, OpaqueElement};
 use smallvec::SmallVec;
 use std::convert::TryFrom;
 use std::fmt;
@@ -36,7 +37,33 @@ impl Selector {
     }
 
     /// Parses a CSS selector group.
-    pub fn parse(selectors: &'_ str) -> Result<Self, SelectorErrorKind> {
+    ///
+    /// Attribute selectors accept the `i` and `s` flags of Selectors Level 4, which compare
+    /// the value ignoring ASCII case or exactly. Without a flag, the values of attributes that
+    /// HTML defines as case-insensitive, such as `type`, ignore case on HTML elements.
+    ///
+    /// ```
+    /// use scraper::{Html, Selector};
+    ///
+    /// let html = Html::parse_fragment(r#"<a href="a.pdf"></a><a href="B.PDF"></a>"#);
+    /// let pdfs = Selector::parse(r#"a[href$=".PDF" i]"#).unwrap();
+    /// assert_eq!(2, html.select(&pdfs).count());
+    /// ```
+    ///
+    /// The `:has()` pseudo-class matches elements by their descendants or following
+    /// siblings, with relative selectors starting with `>`, `+` or `~`:
+    ///
+    /// ```
+    /// use scraper::{Html, Selector};
+    ///
+    /// let html = Html::parse_fragment(
+    ///     r#"<div id="a"><a class="download"></a></div><div id="b"><p><a class="download"></a></p></div>"#,
+    /// );
+    /// let selector = Selector::parse("div:has(> a.download)").unwrap();
+    /// let ids: Vec<_> = html.select(&selector).filter_map(|d| d.value().attr("id")).collect();
+    /// assert_eq!(vec!["a"], ids);
+    /// ```
+    pub fn parse(selectors: &'_ str) -> Result<Self, SelectorErrorKind<'_>> {
         let mut parser_input = cssparser::ParserInput::new(selectors);
         let mut parser = cssparser::Parser::new(&mut parser_input);
 
@@ -54,6 +81,15 @@ impl Selector {
     /// The optional `scope` argument is used to specify which element has `:scope` pseudo-class.
     /// When it is `None`, `:scope` will match the root element.
     pub fn matches_with_scope(&self, element: &ElementRef, scope: Option<ElementRef>) -> bool {
+        self.matches_element(element, scope.map(|x| selectors::Element::opaque(&x)))
+    }
+
+    /// Returns true if an element of any [`selectors::Element`] implementation matches this
+    /// selector, with `scope` being the `:scope` element.
+    pub(crate) fn matches_element<E>(&self, element: &E, scope: Option<OpaqueElement>) -> bool
+    where
+        E: selectors::Element<Impl = Simple>,
+    {
         let mut binding = NthIndexCache::default();
         let mut context = matching::MatchingContext::new(
             matching::MatchingMode::Normal,
@@ -63,11 +99,35 @@ impl Selector {
             matching::NeedsSelectorFlags::No,
             matching::IgnoreNthChildForInvalidation::No,
         );
-        context.scope_element = scope.map(|x| selectors::Element::opaque(&x));
+        context.scope_element = scope;
         self.selectors
             .iter()
             .any(|s| matching::matches_selector(s, 0, None, element, &mut context))
     }
+
+    /// Returns true if matching only needs an element's start tag, its ancestors and its
+    /// preceding siblings, which is all a streaming pass has seen when it reaches the start
+    /// tag. `:empty`, `:has()` and the pseudo-classes counting following siblings, such as
+    /// `:last-child`, need more.
+    pub(crate) fn is_streamable(&self) -> bool {
+        struct Streamable;
+
+        impl SelectorVisitor for Streamable {
+            type Impl = Simple;
+
+            fn visit_simple_selector(&mut self, component: &parser::Component<Simple>) -> bool {
+                let nth = match component {
+                    parser::Component::Empty | parser::Component::Has(_) => return false,
+                    parser::Component::Nth(nth) => nth,
+                    parser::Component::NthOf(nth) => nth.nth_data(),
+                    _ => return true,
+                };
+                matches!(nth.ty, parser::NthType::Child | parser::NthType::OfType)
+            }
+        }
+
+        self.selectors.iter().all(|s| s.visit(&mut Streamable))
+    }
 }
 
 /// An implementation of `Parser` for `selectors`
@@ -75,6 +135,10 @@ struct Parser;
 impl<'i> parser::Parser<'i> for Parser {
     type Impl = Simple;
     type Error = SelectorParseErrorKind<'i>;
+
+    fn parse_has(&self) -> bool {
+        true
+    }
 }
 
 /// A simple implementation of `SelectorImpl` with no pseudo-classes or pseudo-elements.
@@ -183,6 +247,44 @@ impl ToCss for PseudoElement {
     }
 }
 
+impl ToCss for Selector {
+    fn to_css<W>(&self, dest: &mut W) -> fmt::Result
+    where
+        W: fmt::Write,
+    {
+        for (i, selector) in self.selectors.iter().enumerate() {
+            if i > 0 {
+                dest.write_str(", ")?;
+            }
+            selector.to_css(dest)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Selector {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.to_css_string())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Selector {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let source = <String as serde::Deserialize>::deserialize(deserializer)?;
+        Selector::parse(&source).map_err(|err| {
+            serde::de::Error::custom(format!("invalid selector {:?}: {:?}", source, err))
+        })
+    }
+}
+
 impl<'i> TryFrom<&'i str> for Selector {
     type Error = SelectorErrorKind<'i>;
 
@@ -194,6 +296,7 @@ impl<'i> TryFrom<&'i str> for Selector {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::html::Html;
     use std::convert::TryInto;
 
     #[test]
@@ -205,6 +308,35 @@ mod tests {
         let _sel: Selector = (*s).try_into().unwrap();
     }
 
+    #[test]
+    fn attribute_case_flags() {
+        let html = Html::parse_fragment(r#"<input type="TEXT" name="Query">"#);
+        let count = |selector: &str| html.select(&Selector::parse(selector).unwrap()).count();
+        assert_eq!(0, count(r#"[name="query"]"#));
+        assert_eq!(1, count(r#"[name^="QUE" i]"#));
+        assert_eq!(1, count(r#"[type="text"]"#));
+        assert_eq!(0, count(r#"[type="text" s]"#));
+        assert!(Selector::parse(r#"[name="q" x]"#).is_err());
+    }
+
+    #[test]
+    fn has_relative_selectors() {
+        let html = Html::parse_fragment(
+            r#"<section id="s"><h2 id="h">T</h2><p id="p"><b>x</b></p></section><ul id="u"><li></li></ul>"#,
+        );
+        let ids = |selector: &str| {
+            html.select(&Selector::parse(selector).unwrap())
+                .filter_map(|e| e.value().attr("id"))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(vec!["s"], ids("section:has(b)"));
+        assert_eq!(vec!["h"], ids("h2:has(+ p > b)"));
+        assert_eq!(vec!["s", "h"], ids("[id]:has(~ p, ~ ul)"));
+        assert_eq!(vec!["u"], ids(":not(:has(h2, p)):has(> li)"));
+        assert!(!Selector::parse("p:has(b)").unwrap().is_streamable());
+        assert!(Selector::parse(":has()").is_err());
+    }
+
     #[test]
     #[should_panic]
     fn invalid_selector_conversions() {
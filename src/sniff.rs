@@ -0,0 +1,255 @@
+//! Content sniffing, to decide how a fetched resource should be parsed.
+//!
+//! [`content_kind`] follows the parts of the WHATWG MIME Sniffing standard a crawler needs:
+//! the `Content-Type` is trusted when it names a specific type, and sniffed from the first
+//! bytes of the body when it is missing or generic. On top of the standard, resources
+//! labelled HTML or XML are checked for feeds, and resources labelled HTML or not labelled
+//! at all are checked for JSON, because both are commonly served with the wrong type.
+
+/// The number of bytes of the body that sniffing looks at, as in the standard.
+pub const SNIFF_BYTES: usize = 1445;
+
+/// What a resource contains, as decided by [`content_kind`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ContentKind {
+    /// An HTML document, for [`Html::parse_document`](crate::Html::parse_document).
+    Html,
+    /// An XML document other than a feed.
+    Xml,
+    /// An RSS, Atom or RDF feed.
+    Feed,
+    /// JSON.
+    Json,
+    /// Plain text, or another text format such as CSS or JavaScript.
+    Text,
+    /// Anything else, such as images, PDFs and archives.
+    Binary,
+}
+
+/// Signatures of HTML at the start of a body, each followed by a space or `>`.
+const HTML_SIGNATURES: &[&[u8]] = &[
+    b"<!DOCTYPE HTML",
+    b"<HTML",
+    b"<HEAD",
+    b"<SCRIPT",
+    b"<IFRAME",
+    b"<H1",
+    b"<DIV",
+    b"<FONT",
+    b"<TABLE",
+    b"<A",
+    b"<STYLE",
+    b"<TITLE",
+    b"<B",
+    b"<BODY",
+    b"<BR",
+    b"<P",
+];
+
+/// `Content-Type` values that Apache once sent for every file it could not identify, which
+/// are therefore not trusted to mean text.
+const APACHE_BUG_TYPES: &[&str] = &[
+    "text/plain",
+    "text/plain; charset=ISO-8859-1",
+    "text/plain; charset=iso-8859-1",
+    "text/plain; charset=UTF-8",
+];
+
+fn is_whitespace(byte: u8) -> bool {
+    matches!(byte, b'\t' | b'\n' | b'\x0C' | b'\r' | b' ')
+}
+
+/// Returns true for bytes that do not occur in text.
+fn is_binary_byte(byte: u8) -> bool {
+    matches!(byte, 0x00..=0x08 | 0x0B | 0x0E..=0x1A | 0x1C..=0x1F)
+}
+
+fn starts_with_ignore_case(bytes: &[u8], prefix: &[u8]) -> bool {
+    bytes.len() >= prefix.len() && bytes[..prefix.len()].eq_ignore_ascii_case(prefix)
+}
+
+/// Returns the body after any byte order mark and leading whitespace.
+fn trim_start(body: &[u8]) -> &[u8] {
+    let body = [&b"\xEF\xBB\xBF"[..], b"\xFE\xFF", b"\xFF\xFE"]
+        .iter()
+        .find_map(|bom| body.strip_prefix(*bom))
+        .unwrap_or(body);
+    let start = body
+        .iter()
+        .position(|&b| !is_whitespace(b))
+        .unwrap_or(body.len());
+    &body[start..]
+}
+
+fn is_html(body: &[u8]) -> bool {
+    let body = trim_start(body);
+    if body.starts_with(b"<!--") {
+        return true;
+    }
+    HTML_SIGNATURES.iter().any(|signature| {
+        starts_with_ignore_case(body, signature)
+            && matches!(body.get(signature.len()), Some(b' ' | b'>'))
+    })
+}
+
+fn is_json(body: &[u8]) -> bool {
+    matches!(trim_start(body).first(), Some(b'{' | b'['))
+}
+
+/// Returns true if the first element of the body, after comments, processing
+/// instructions and declarations, is the root of an RSS, Atom or RDF feed, as the
+/// standard's rules for distinguishing a feed from HTML decide.
+fn is_feed(body: &[u8]) -> bool {
+    let mut rest = trim_start(body);
+    loop {
+        let Some(after) = rest.strip_prefix(b"<") else {
+            return false;
+        };
+        let end = if after.starts_with(b"!--") {
+            find(after, b"-->").map(|i| i + 3)
+        } else if after.starts_with(b"!") || after.starts_with(b"?") {
+            find(after, b">").map(|i| i + 1)
+        } else if after.starts_with(b"rss") || after.starts_with(b"feed") {
+            return true;
+        } else if after.starts_with(b"rdf:RDF") {
+            let tag = &after[..find(after, b">").unwrap_or(after.len())];
+            return find(tag, b"http://purl.org/rss/1.0/").is_some()
+                && find(tag, b"http://www.w3.org/1999/02/22-rdf-syntax-ns#").is_some();
+        } else {
+            return false;
+        };
+        let Some(end) = end else {
+            return false;
+        };
+        rest = trim_start(&after[end..]);
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+/// Decides what a resource is from its `Content-Type` header, if any, and the start of its
+/// body. Only the first [`SNIFF_BYTES`] bytes of the body are looked at.
+///
+/// A missing, unknown, `*/*` or `application/octet-stream` type is sniffed from the body:
+/// HTML and XML signatures, JSON and then text or binary. A specific type is trusted, except
+/// that HTML and XML may turn out to be feeds, HTML may turn out to be JSON, and the
+/// `text/plain` types Apache used to send by default may turn out to be binary.
+///
+/// ```
+/// use scraper::sniff::{content_kind, ContentKind};
+///
+/// assert_eq!(ContentKind::Html, content_kind(None, b"  <!doctype html><p>Hi"));
+/// assert_eq!(
+///     ContentKind::Feed,
+///     content_kind(Some("text/html; charset=utf-8"), b"<?xml version=\"1.0\"?><rss>")
+/// );
+/// assert_eq!(ContentKind::Json, content_kind(Some("text/html"), b"{\"items\": []}"));
+/// assert_eq!(ContentKind::Binary, content_kind(Some("image/png"), b"\x89PNG"));
+/// ```
+pub fn content_kind(content_type: Option<&str>, body_prefix: &[u8]) -> ContentKind {
+    let body = &body_prefix[..body_prefix.len().min(SNIFF_BYTES)];
+    let supplied = content_type.map(str::trim).filter(|t| !t.is_empty());
+    let essence = supplied
+        .map(|t| {
+            t.split(';')
+                .next()
+                .unwrap_or_default()
+                .trim()
+                .to_ascii_lowercase()
+        })
+        .unwrap_or_default();
+
+    if supplied.is_some_and(|t| APACHE_BUG_TYPES.contains(&t)) {
+        return if body.iter().copied().any(is_binary_byte) {
+            ContentKind::Binary
+        } else {
+            ContentKind::Text
+        };
+    }
+    match essence.as_str() {
+        "" | "unknown/unknown" | "application/unknown" | "*/*" | "application/octet-stream" => {
+            sniff_unknown(body)
+        }
+        "text/html" if is_feed(body) => ContentKind::Feed,
+        "text/html" if is_json(body) => ContentKind::Json,
+        "text/html" => ContentKind::Html,
+        "application/rss+xml" | "application/atom+xml" | "application/rdf+xml" => ContentKind::Feed,
+        "text/xml" | "application/xml" if is_feed(body) => ContentKind::Feed,
+        "text/xml" | "application/xml" => ContentKind::Xml,
+        "application/xhtml+xml" => ContentKind::Html,
+        "application/json" | "text/json" => ContentKind::Json,
+        e if e.ends_with("+json") => ContentKind::Json,
+        e if e.ends_with("+xml") => ContentKind::Xml,
+        e if e.starts_with("text/") => ContentKind::Text,
+        "application/javascript" | "application/ecmascript" => ContentKind::Text,
+        _ => ContentKind::Binary,
+    }
+}
+
+/// Sniffs a body of unknown type.
+fn sniff_unknown(body: &[u8]) -> ContentKind {
+    let start = trim_start(body);
+    if is_html(body) {
+        ContentKind::Html
+    } else if start.starts_with(b"<?xml") {
+        if is_feed(body) {
+            ContentKind::Feed
+        } else {
+            ContentKind::Xml
+        }
+    } else if is_feed(body) {
+        ContentKind::Feed
+    } else if is_json(body) {
+        ContentKind::Json
+    } else if body.starts_with(b"%PDF-") || body.iter().copied().any(is_binary_byte) {
+        ContentKind::Binary
+    } else {
+        ContentKind::Text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{content_kind, ContentKind};
+
+    #[test]
+    fn sniffs_unknown_and_mislabelled_bodies() {
+        let atom = b"\xEF\xBB\xBF<?xml version='1.0'?><!-- c --><!DOCTYPE x><feed xmlns='x'>";
+        assert_eq!(ContentKind::Feed, content_kind(None, atom));
+        assert_eq!(
+            ContentKind::Feed,
+            content_kind(Some("application/xml"), atom)
+        );
+        assert_eq!(
+            ContentKind::Xml,
+            content_kind(Some("*/*"), b"<?xml version='1.0'?><svg/>")
+        );
+        let rdf = b"<rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\" \
+                    xmlns=\"http://purl.org/rss/1.0/\">";
+        assert_eq!(ContentKind::Feed, content_kind(Some("text/html"), rdf));
+        assert_eq!(
+            ContentKind::Html,
+            content_kind(Some("text/html"), b"<rdf:RDF>")
+        );
+
+        assert_eq!(ContentKind::Html, content_kind(Some(""), b"<!-- x --><p>"));
+        assert_eq!(ContentKind::Text, content_kind(None, b"<pre>not html"));
+        assert_eq!(ContentKind::Binary, content_kind(None, b"GIF89a\x01\x00"));
+        assert_eq!(
+            ContentKind::Binary,
+            content_kind(Some("text/plain; charset=UTF-8"), b"PK\x03\x04")
+        );
+        assert_eq!(
+            ContentKind::Text,
+            content_kind(Some("text/plain; charset=utf-16"), b"PK\x03\x04")
+        );
+        assert_eq!(
+            ContentKind::Json,
+            content_kind(Some("application/ld+json"), b"")
+        );
+    }
+}
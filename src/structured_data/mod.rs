@@ -0,0 +1,195 @@
+//! JSON-LD structured data.
+//!
+//! Requires the `serde_json` feature. [`Html::structured_data`] collects the JSON-LD items
+//! embedded in `<script type="application/ld+json">` blocks, which can then be inspected as
+//! [`serde_json::Value`]s or deserialized into the typed structs of the [`schema`] module.
+//!
+//! ```
+//! use scraper::structured_data::schema::Product;
+//! use scraper::Html;
+//!
+//! let html = Html::parse_document(r#"
+//!     <script type="application/ld+json">
+//!     {"@context": "https://schema.org", "@type": "Product", "name": "Widget",
+//!      "offers": {"@type": "Offer", "price": "9.99", "priceCurrency": "EUR"}}
+//!     </script>
+//! "#);
+//!
+//! let products = html.structured_data().deserialize_as::<Product>();
+//! assert_eq!(Some("Widget"), products[0].name.as_deref());
+//! assert_eq!(Some(9.99), products[0].offers.first().and_then(|o| o.price.as_ref()?.as_f64()));
+//! ```
+
+pub mod schema;
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::html::Html;
+use crate::selector::Selector;
+
+use self::schema::SchemaType;
+
+lazy_static! {
+    static ref JSON_LD_SELECTOR: Selector =
+        Selector::parse(r#"script[type="application/ld+json" i]"#).unwrap();
+}
+
+/// Removes the HTML comment and CDATA wrappers that some sites put around the JSON.
+fn unwrap_source(source: &str) -> &str {
+    let mut source = source.trim().trim_end_matches(';').trim_end();
+    for (open, close) in [
+        ("<!--", "-->"),
+        ("//<![CDATA[", "//]]>"),
+        ("<![CDATA[", "]]>"),
+    ] {
+        if let Some(inner) = source
+            .strip_prefix(open)
+            .and_then(|s| s.strip_suffix(close))
+        {
+            source = inner.trim();
+        }
+    }
+    source
+}
+
+/// Appends an item, replacing arrays and `@graph` containers by the items they hold. The
+/// nodes of a graph share its `@context`.
+fn push_items(items: &mut Vec<Value>, item: Value, context: Option<&Value>) {
+    match item {
+        Value::Array(list) => {
+            for item in list {
+                push_items(items, item, context);
+            }
+        }
+        Value::Object(mut object)
+            if object.contains_key("@graph") && object.get("@type").is_none() =>
+        {
+            let context = object.remove("@context").or_else(|| context.cloned());
+            let graph = object.remove("@graph").unwrap_or_default();
+            push_items(items, graph, context.as_ref());
+        }
+        Value::Object(mut object) => {
+            if let Some(context) = context {
+                object.entry("@context").or_insert_with(|| context.clone());
+            }
+            items.push(Value::Object(object));
+        }
+        item => items.push(item),
+    }
+}
+
+/// The JSON-LD items of a document.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StructuredData {
+    /// The top-level items, with arrays and `@graph` containers flattened.
+    pub items: Vec<Value>,
+    /// Parse errors of blocks that were not valid JSON.
+    pub errors: Vec<String>,
+}
+
+impl StructuredData {
+    /// Returns the `@type`s of an item; JSON-LD allows a single string or an array.
+    pub fn types(item: &Value) -> Vec<&str> {
+        match item.get("@type") {
+            Some(Value::String(ty)) => vec![ty.as_str()],
+            Some(Value::Array(types)) => types.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Returns an iterator over the items with one of the given `@type`s. Types match with or
+    /// without a `schema:` or `https://schema.org/` prefix.
+    pub fn of_type<'a>(&'a self, types: &'a [&'a str]) -> impl Iterator<Item = &'a Value> + 'a {
+        self.items.iter().filter(move |item| {
+            Self::types(item).into_iter().any(|ty| {
+                let ty = ty.rsplit_once(['/', ':']).map_or(ty, |(_, local)| local);
+                types.contains(&ty)
+            })
+        })
+    }
+
+    /// Deserializes every item of the schema.org type `T` (or one of its listed subtypes),
+    /// skipping items whose shape does not fit.
+    pub fn deserialize_as<T: SchemaType + DeserializeOwned>(&self) -> Vec<T> {
+        self.of_type(T::TYPES)
+            .filter_map(|item| T::deserialize(item).ok())
+            .collect()
+    }
+
+    /// Deserializes the first item of the schema.org type `T`.
+    pub fn first_as<T: SchemaType + DeserializeOwned>(&self) -> Option<T> {
+        self.of_type(T::TYPES)
+            .find_map(|item| T::deserialize(item).ok())
+    }
+}
+
+impl Html {
+    /// Collects the JSON-LD items of the document.
+    ///
+    /// Blocks holding an array or a `@graph` contribute each of their items, and the
+    /// `<!-- -->` and CDATA wrappers left over from old templates are tolerated.
+    pub fn structured_data(&self) -> StructuredData {
+        let mut data = StructuredData::default();
+
+        for script in self.select(&JSON_LD_SELECTOR) {
+            // `text()` skips script content, so read the raw text children.
+            let source: String = script
+                .children()
+                .filter_map(|child| child.value().as_text())
+                .map(|text| &**text)
+                .collect();
+            match serde_json::from_str::<Value>(unwrap_source(&source)) {
+                Ok(item) => push_items(&mut data.items, item, None),
+                Err(err) => data.errors.push(err.to_string()),
+            }
+        }
+
+        data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::schema::{Article, Entity, Person};
+    use crate::html::Html;
+
+    #[test]
+    fn collects_arrays_and_reports_errors() {
+        let html = Html::parse_document(
+            r#"
+            <script type="application/ld+json">[{"@type": "Person", "name": "A"}, {"@type": ["schema:NewsArticle"], "headline": "H", "author": "B"}]</script>
+            <script type="application/ld+json">{not json</script>
+            "#,
+        );
+        let data = html.structured_data();
+        assert_eq!(2, data.items.len());
+        assert_eq!(1, data.errors.len());
+
+        let people = data.deserialize_as::<Person>();
+        assert_eq!(Some("A"), people[0].name.as_deref());
+
+        let article = data.first_as::<Article>().unwrap();
+        assert_eq!(Some("H"), article.headline.as_deref());
+        assert_eq!(Some("B"), article.author.first().and_then(Entity::name));
+    }
+
+    #[test]
+    fn flattens_graph_and_unwraps_comments() {
+        let html = Html::parse_document(
+            r#"
+            <script type="Application/LD+JSON"><!--
+            {"@context": "https://schema.org", "@graph": [{"@type": "WebSite"}, [{"@type": "Person", "name": "A"}]]}
+            --></script>
+            "#,
+        );
+        let data = html.structured_data();
+        assert_eq!(Vec::<String>::new(), data.errors);
+        assert_eq!(2, data.items.len());
+        assert_eq!("https://schema.org", data.items[1]["@context"]);
+        assert_eq!(
+            Some("A"),
+            data.first_as::<Person>().unwrap().name.as_deref()
+        );
+    }
+}
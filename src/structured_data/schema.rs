@@ -0,0 +1,805 @@
+//! Typed structs for common schema.org types.
+//!
+//! The structs are deliberately lenient, matching how JSON-LD is written in the wild: every
+//! property is optional, properties that may repeat are `Vec`s that also accept a single value,
+//! and references to other entities accept either a plain name or a nested object (see
+//! [`Entity`]). Unknown properties are ignored.
+
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+
+/// A schema.org type that can be looked up with
+/// [`StructuredData::deserialize_as`](super::StructuredData::deserialize_as).
+pub trait SchemaType {
+    /// The `@type` names that deserialize into this struct, most general first.
+    const TYPES: &'static [&'static str];
+}
+
+/// A schema.org entity with a display name.
+pub trait Named {
+    /// Returns the entity name, if any.
+    fn name(&self) -> Option<&str>;
+}
+
+/// A reference to another entity, written either as a plain string or as a nested object.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Entity<T> {
+    /// The entity is only named, e.g. `"author": "Jane Doe"`.
+    Name(String),
+    /// The entity is a nested object.
+    Item(T),
+}
+
+impl<T: Named> Entity<T> {
+    /// Returns the name, whichever form the entity was written in.
+    pub fn name(&self) -> Option<&str> {
+        match self {
+            Entity::Name(name) => Some(name),
+            Entity::Item(item) => item.name(),
+        }
+    }
+
+    /// Returns the nested object, if the entity was written as one.
+    pub fn item(&self) -> Option<&T> {
+        match self {
+            Entity::Name(_) => None,
+            Entity::Item(item) => Some(item),
+        }
+    }
+}
+
+/// A number that sites often write as a string, e.g. `"price": "19.99"`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum NumberOrText {
+    /// A JSON number.
+    Number(f64),
+    /// A string, possibly numeric.
+    Text(String),
+}
+
+impl NumberOrText {
+    /// Returns the value as a number, parsing strings after dropping thousands separators.
+    ///
+    /// A comma is a decimal point when it comes after the last `.`, as in `"1.299,50"`, or
+    /// when there is no `.` and the digits after a comma are not groups of three, as in
+    /// `"9,99"`. Otherwise commas separate thousands, as in `"1,299"`.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            NumberOrText::Number(n) => Some(*n),
+            NumberOrText::Text(text) => {
+                let text = text.trim();
+                let decimal_comma = match (text.rfind(','), text.rfind('.')) {
+                    (Some(comma), Some(dot)) => comma > dot,
+                    (Some(_), None) => !text
+                        .split(',')
+                        .skip(1)
+                        .all(|group| group.len() == 3 && group.bytes().all(|b| b.is_ascii_digit())),
+                    _ => false,
+                };
+                let number = if decimal_comma {
+                    text.replace('.', "").replace(',', ".")
+                } else {
+                    text.replace(',', "")
+                };
+                number.parse().ok()
+            }
+        }
+    }
+}
+
+/// An image, written either as a URL or as an `ImageObject`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ImageRef {
+    /// A bare URL.
+    Url(String),
+    /// A nested `ImageObject`.
+    Object(ImageObject),
+}
+
+impl ImageRef {
+    /// Returns the image URL, whichever form the image was written in.
+    pub fn url(&self) -> Option<&str> {
+        match self {
+            ImageRef::Url(url) => Some(url),
+            ImageRef::Object(image) => image.url.as_deref().or(image.content_url.as_deref()),
+        }
+    }
+}
+
+/// Deserializes a property that may hold one value, an array of values, or `null`.
+fn one_or_many<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany<T> {
+        Many(Vec<T>),
+        One(T),
+        Null(()),
+    }
+
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::Many(values) => values,
+        OneOrMany::One(value) => vec![value],
+        OneOrMany::Null(()) => Vec::new(),
+    })
+}
+
+/// Deserializes an identifier that sites also write as a number, e.g. `"sku": 12345`.
+/// Values of other shapes are read as missing.
+fn text_or_number<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(match Value::deserialize(deserializer)? {
+        Value::String(text) => Some(text),
+        Value::Number(number) => Some(number.to_string()),
+        _ => None,
+    })
+}
+
+/// Any schema.org thing, for references whose type is not known in advance.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Thing {
+    /// The entity name.
+    pub name: Option<String>,
+    /// A description.
+    pub description: Option<String>,
+    /// The canonical URL of the entity.
+    pub url: Option<String>,
+}
+
+impl SchemaType for Thing {
+    const TYPES: &'static [&'static str] = &["Thing"];
+}
+
+/// A product.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Product {
+    /// The product name.
+    pub name: Option<String>,
+    /// A description.
+    pub description: Option<String>,
+    /// The product page URL.
+    pub url: Option<String>,
+    /// Product images.
+    #[serde(deserialize_with = "one_or_many")]
+    pub image: Vec<ImageRef>,
+    /// The merchant-specific identifier.
+    #[serde(deserialize_with = "text_or_number")]
+    pub sku: Option<String>,
+    /// A GTIN of any length.
+    #[serde(deserialize_with = "text_or_number")]
+    pub gtin: Option<String>,
+    /// The GTIN-13 / EAN.
+    #[serde(deserialize_with = "text_or_number")]
+    pub gtin13: Option<String>,
+    /// The manufacturer part number.
+    #[serde(deserialize_with = "text_or_number")]
+    pub mpn: Option<String>,
+    /// The brand.
+    pub brand: Option<Entity<Organization>>,
+    /// The product category.
+    pub category: Option<String>,
+    /// The offers to sell the product.
+    #[serde(deserialize_with = "one_or_many")]
+    pub offers: Vec<Offer>,
+    /// The overall rating.
+    pub aggregate_rating: Option<AggregateRating>,
+    /// Individual reviews.
+    #[serde(deserialize_with = "one_or_many")]
+    pub review: Vec<Review>,
+}
+
+impl SchemaType for Product {
+    const TYPES: &'static [&'static str] = &["Product", "ProductModel", "IndividualProduct"];
+}
+
+/// An offer to sell something; also covers `AggregateOffer` price ranges.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Offer {
+    /// The price.
+    pub price: Option<NumberOrText>,
+    /// The ISO 4217 currency code.
+    pub price_currency: Option<String>,
+    /// The lowest price of an aggregate offer.
+    pub low_price: Option<NumberOrText>,
+    /// The highest price of an aggregate offer.
+    pub high_price: Option<NumberOrText>,
+    /// The availability, usually a `https://schema.org/InStock` style URL.
+    pub availability: Option<String>,
+    /// The item condition.
+    pub item_condition: Option<String>,
+    /// The date after which the price is no longer valid.
+    pub price_valid_until: Option<String>,
+    /// The offer URL.
+    pub url: Option<String>,
+    /// The seller.
+    pub seller: Option<Entity<Organization>>,
+}
+
+impl SchemaType for Offer {
+    const TYPES: &'static [&'static str] = &["Offer", "AggregateOffer"];
+}
+
+/// The average rating of an item.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct AggregateRating {
+    /// The rating value.
+    pub rating_value: Option<NumberOrText>,
+    /// The number of ratings.
+    pub rating_count: Option<NumberOrText>,
+    /// The number of reviews.
+    pub review_count: Option<NumberOrText>,
+    /// The highest possible rating.
+    pub best_rating: Option<NumberOrText>,
+    /// The lowest possible rating.
+    pub worst_rating: Option<NumberOrText>,
+}
+
+impl SchemaType for AggregateRating {
+    const TYPES: &'static [&'static str] = &["AggregateRating"];
+}
+
+/// A single rating.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Rating {
+    /// The rating value.
+    pub rating_value: Option<NumberOrText>,
+    /// The highest possible rating.
+    pub best_rating: Option<NumberOrText>,
+    /// The lowest possible rating.
+    pub worst_rating: Option<NumberOrText>,
+}
+
+impl SchemaType for Rating {
+    const TYPES: &'static [&'static str] = &["Rating"];
+}
+
+/// A review of an item.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Review {
+    /// The review title.
+    pub name: Option<String>,
+    /// The review text.
+    pub review_body: Option<String>,
+    /// The authors.
+    #[serde(deserialize_with = "one_or_many")]
+    pub author: Vec<Entity<Person>>,
+    /// The rating given.
+    pub review_rating: Option<Rating>,
+    /// The publication date.
+    pub date_published: Option<String>,
+}
+
+impl SchemaType for Review {
+    const TYPES: &'static [&'static str] = &["Review", "UserReview", "CriticReview"];
+}
+
+/// An organization.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Organization {
+    /// The organization name.
+    pub name: Option<String>,
+    /// The homepage URL.
+    pub url: Option<String>,
+    /// The logo.
+    pub logo: Option<ImageRef>,
+    /// Profile URLs on other sites.
+    #[serde(deserialize_with = "one_or_many")]
+    pub same_as: Vec<String>,
+    /// The postal address.
+    pub address: Option<Entity<PostalAddress>>,
+    /// The telephone number.
+    pub telephone: Option<String>,
+    /// The email address.
+    pub email: Option<String>,
+}
+
+impl SchemaType for Organization {
+    const TYPES: &'static [&'static str] = &[
+        "Organization",
+        "Corporation",
+        "NGO",
+        "NewsMediaOrganization",
+        "EducationalOrganization",
+    ];
+}
+
+/// A person.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Person {
+    /// The full name.
+    pub name: Option<String>,
+    /// The profile URL.
+    pub url: Option<String>,
+    /// Pictures of the person.
+    #[serde(deserialize_with = "one_or_many")]
+    pub image: Vec<ImageRef>,
+    /// The job title.
+    pub job_title: Option<String>,
+    /// Profile URLs on other sites.
+    #[serde(deserialize_with = "one_or_many")]
+    pub same_as: Vec<String>,
+    /// The employer.
+    pub works_for: Option<Entity<Organization>>,
+}
+
+impl SchemaType for Person {
+    const TYPES: &'static [&'static str] = &["Person"];
+}
+
+/// An article, news story or blog post.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Article {
+    /// The headline.
+    pub headline: Option<String>,
+    /// The article name, often the same as the headline.
+    pub name: Option<String>,
+    /// A description or summary.
+    pub description: Option<String>,
+    /// The article URL.
+    pub url: Option<String>,
+    /// Article images.
+    #[serde(deserialize_with = "one_or_many")]
+    pub image: Vec<ImageRef>,
+    /// The authors.
+    #[serde(deserialize_with = "one_or_many")]
+    pub author: Vec<Entity<Person>>,
+    /// The publisher.
+    pub publisher: Option<Entity<Organization>>,
+    /// The publication date.
+    pub date_published: Option<String>,
+    /// The last modification date.
+    pub date_modified: Option<String>,
+    /// The sections the article belongs to.
+    #[serde(deserialize_with = "one_or_many")]
+    pub article_section: Vec<String>,
+    /// The keywords or tags.
+    #[serde(deserialize_with = "one_or_many")]
+    pub keywords: Vec<String>,
+    /// The number of words.
+    pub word_count: Option<NumberOrText>,
+    /// The full text.
+    pub article_body: Option<String>,
+}
+
+impl SchemaType for Article {
+    const TYPES: &'static [&'static str] = &[
+        "Article",
+        "NewsArticle",
+        "BlogPosting",
+        "Report",
+        "ScholarlyArticle",
+        "TechArticle",
+        "SocialMediaPosting",
+    ];
+}
+
+/// A breadcrumb trail.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct BreadcrumbList {
+    /// The crumbs, in order.
+    #[serde(deserialize_with = "one_or_many")]
+    pub item_list_element: Vec<ListItem>,
+}
+
+impl SchemaType for BreadcrumbList {
+    const TYPES: &'static [&'static str] = &["BreadcrumbList"];
+}
+
+/// An entry of a list such as a breadcrumb trail.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ListItem {
+    /// The 1-based position.
+    pub position: Option<NumberOrText>,
+    /// The entry name.
+    pub name: Option<String>,
+    /// The linked item, a URL string or an object with `@id`.
+    pub item: Option<Value>,
+}
+
+impl SchemaType for ListItem {
+    const TYPES: &'static [&'static str] = &["ListItem"];
+}
+
+/// A recipe.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Recipe {
+    /// The recipe name.
+    pub name: Option<String>,
+    /// A description.
+    pub description: Option<String>,
+    /// Pictures of the dish.
+    #[serde(deserialize_with = "one_or_many")]
+    pub image: Vec<ImageRef>,
+    /// The authors.
+    #[serde(deserialize_with = "one_or_many")]
+    pub author: Vec<Entity<Person>>,
+    /// The ingredients.
+    #[serde(deserialize_with = "one_or_many")]
+    pub recipe_ingredient: Vec<String>,
+    /// The instructions, as plain strings or `HowToStep`s.
+    #[serde(deserialize_with = "one_or_many")]
+    pub recipe_instructions: Vec<Entity<HowToStep>>,
+    /// The ISO 8601 preparation time.
+    pub prep_time: Option<String>,
+    /// The ISO 8601 cooking time.
+    pub cook_time: Option<String>,
+    /// The ISO 8601 total time.
+    pub total_time: Option<String>,
+    /// The yield, e.g. `4` or `"4 servings"`.
+    #[serde(deserialize_with = "one_or_many")]
+    pub recipe_yield: Vec<NumberOrText>,
+    /// The overall rating.
+    pub aggregate_rating: Option<AggregateRating>,
+}
+
+impl SchemaType for Recipe {
+    const TYPES: &'static [&'static str] = &["Recipe"];
+}
+
+/// A step of a recipe or how-to.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct HowToStep {
+    /// The step title.
+    pub name: Option<String>,
+    /// The step text.
+    pub text: Option<String>,
+}
+
+impl SchemaType for HowToStep {
+    const TYPES: &'static [&'static str] = &["HowToStep"];
+}
+
+/// An event.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Event {
+    /// The event name.
+    pub name: Option<String>,
+    /// A description.
+    pub description: Option<String>,
+    /// The event URL.
+    pub url: Option<String>,
+    /// Event images.
+    #[serde(deserialize_with = "one_or_many")]
+    pub image: Vec<ImageRef>,
+    /// The start date.
+    pub start_date: Option<String>,
+    /// The end date.
+    pub end_date: Option<String>,
+    /// Where the event happens.
+    #[serde(deserialize_with = "one_or_many")]
+    pub location: Vec<Entity<Place>>,
+    /// The organizers.
+    #[serde(deserialize_with = "one_or_many")]
+    pub organizer: Vec<Entity<Organization>>,
+    /// The ticket offers.
+    #[serde(deserialize_with = "one_or_many")]
+    pub offers: Vec<Offer>,
+    /// The status, e.g. `https://schema.org/EventScheduled`.
+    pub event_status: Option<String>,
+    /// Online, offline or mixed attendance.
+    pub event_attendance_mode: Option<String>,
+}
+
+impl SchemaType for Event {
+    const TYPES: &'static [&'static str] = &[
+        "Event",
+        "MusicEvent",
+        "SportsEvent",
+        "BusinessEvent",
+        "EducationEvent",
+        "TheaterEvent",
+    ];
+}
+
+/// A place.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Place {
+    /// The place name.
+    pub name: Option<String>,
+    /// The postal address.
+    pub address: Option<Entity<PostalAddress>>,
+    /// The coordinates.
+    pub geo: Option<GeoCoordinates>,
+    /// The place URL.
+    pub url: Option<String>,
+}
+
+impl SchemaType for Place {
+    const TYPES: &'static [&'static str] = &["Place", "VirtualLocation"];
+}
+
+/// A postal address.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct PostalAddress {
+    /// The street address.
+    pub street_address: Option<String>,
+    /// The city.
+    pub address_locality: Option<String>,
+    /// The region or state.
+    pub address_region: Option<String>,
+    /// The postal code.
+    #[serde(deserialize_with = "text_or_number")]
+    pub postal_code: Option<String>,
+    /// The country, as a code, a name or a `Country` object.
+    pub address_country: Option<Entity<Thing>>,
+}
+
+impl SchemaType for PostalAddress {
+    const TYPES: &'static [&'static str] = &["PostalAddress"];
+}
+
+/// Geographic coordinates.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct GeoCoordinates {
+    /// The latitude.
+    pub latitude: Option<NumberOrText>,
+    /// The longitude.
+    pub longitude: Option<NumberOrText>,
+}
+
+impl SchemaType for GeoCoordinates {
+    const TYPES: &'static [&'static str] = &["GeoCoordinates"];
+}
+
+/// A shop, restaurant or other local business.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct LocalBusiness {
+    /// The business name.
+    pub name: Option<String>,
+    /// The business URL.
+    pub url: Option<String>,
+    /// Pictures of the business.
+    #[serde(deserialize_with = "one_or_many")]
+    pub image: Vec<ImageRef>,
+    /// The postal address.
+    pub address: Option<Entity<PostalAddress>>,
+    /// The coordinates.
+    pub geo: Option<GeoCoordinates>,
+    /// The telephone number.
+    pub telephone: Option<String>,
+    /// Opening hours, e.g. `Mo-Fr 09:00-17:00`.
+    #[serde(deserialize_with = "one_or_many")]
+    pub opening_hours: Vec<String>,
+    /// The price range, e.g. `$$`.
+    pub price_range: Option<String>,
+    /// The overall rating.
+    pub aggregate_rating: Option<AggregateRating>,
+}
+
+impl SchemaType for LocalBusiness {
+    const TYPES: &'static [&'static str] = &[
+        "LocalBusiness",
+        "Restaurant",
+        "Store",
+        "Hotel",
+        "FoodEstablishment",
+        "ProfessionalService",
+        "AutomotiveBusiness",
+        "MedicalBusiness",
+    ];
+}
+
+/// An image.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ImageObject {
+    /// The image URL.
+    pub url: Option<String>,
+    /// The URL of the image file.
+    pub content_url: Option<String>,
+    /// The width.
+    pub width: Option<NumberOrText>,
+    /// The height.
+    pub height: Option<NumberOrText>,
+    /// The caption.
+    pub caption: Option<String>,
+}
+
+impl SchemaType for ImageObject {
+    const TYPES: &'static [&'static str] = &["ImageObject"];
+}
+
+/// A video.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct VideoObject {
+    /// The video title.
+    pub name: Option<String>,
+    /// A description.
+    pub description: Option<String>,
+    /// Thumbnail URLs.
+    #[serde(deserialize_with = "one_or_many")]
+    pub thumbnail_url: Vec<String>,
+    /// The upload date.
+    pub upload_date: Option<String>,
+    /// The ISO 8601 duration.
+    pub duration: Option<String>,
+    /// The URL of the video file.
+    pub content_url: Option<String>,
+    /// The URL of the embeddable player.
+    pub embed_url: Option<String>,
+}
+
+impl SchemaType for VideoObject {
+    const TYPES: &'static [&'static str] = &["VideoObject"];
+}
+
+/// A website.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct WebSite {
+    /// The site name.
+    pub name: Option<String>,
+    /// The site URL.
+    pub url: Option<String>,
+    /// An alternate name.
+    pub alternate_name: Option<String>,
+}
+
+impl SchemaType for WebSite {
+    const TYPES: &'static [&'static str] = &["WebSite"];
+}
+
+/// A page of frequently asked questions.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct FAQPage {
+    /// The questions.
+    #[serde(deserialize_with = "one_or_many")]
+    pub main_entity: Vec<Question>,
+}
+
+impl SchemaType for FAQPage {
+    const TYPES: &'static [&'static str] = &["FAQPage"];
+}
+
+/// A question.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Question {
+    /// The question text.
+    pub name: Option<String>,
+    /// The accepted answer.
+    pub accepted_answer: Option<Answer>,
+}
+
+impl SchemaType for Question {
+    const TYPES: &'static [&'static str] = &["Question"];
+}
+
+/// An answer to a question.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Answer {
+    /// The answer text.
+    pub text: Option<String>,
+}
+
+impl SchemaType for Answer {
+    const TYPES: &'static [&'static str] = &["Answer"];
+}
+
+macro_rules! named {
+    ($($ty:ident => |$this:ident| $name:expr),* $(,)?) => {
+        $(
+            impl Named for $ty {
+                fn name(&self) -> Option<&str> {
+                    let $this = self;
+                    $name
+                }
+            }
+        )*
+    };
+}
+
+named! {
+    Thing => |this| this.name.as_deref(),
+    Product => |this| this.name.as_deref(),
+    Review => |this| this.name.as_deref(),
+    Organization => |this| this.name.as_deref(),
+    Person => |this| this.name.as_deref(),
+    Article => |this| this.headline.as_deref().or(this.name.as_deref()),
+    ListItem => |this| this.name.as_deref(),
+    Recipe => |this| this.name.as_deref(),
+    Event => |this| this.name.as_deref(),
+    Place => |this| this.name.as_deref(),
+    PostalAddress => |this| this.street_address.as_deref(),
+    LocalBusiness => |this| this.name.as_deref(),
+    VideoObject => |this| this.name.as_deref(),
+    WebSite => |this| this.name.as_deref(),
+    Question => |this| this.name.as_deref(),
+    HowToStep => |this| this.text.as_deref().or(this.name.as_deref()),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Entity, ImageRef, PostalAddress, Product, Recipe};
+
+    #[test]
+    fn lenient_shapes() {
+        let product: Product = serde_json::from_value(serde_json::json!({
+            "@type": "Product",
+            "name": "Widget",
+            "image": "https://example.com/a.jpg",
+            "brand": {"@type": "Brand", "name": "Acme"},
+            "sku": 12345,
+            "gtin13": 4006381333931_u64,
+            "mpn": "X-1",
+            "offers": [
+                {"price": 10},
+                {"price": "1,299.50"},
+                {"price": "9,99"},
+                {"price": "1.299,50"},
+                {"price": "1,299"}
+            ],
+            "review": null
+        }))
+        .unwrap();
+
+        assert_eq!(
+            vec![Some("https://example.com/a.jpg")],
+            product.image.iter().map(ImageRef::url).collect::<Vec<_>>()
+        );
+        assert_eq!(Some("Acme"), product.brand.as_ref().and_then(Entity::name));
+        assert_eq!(Some("12345"), product.sku.as_deref());
+        assert_eq!(Some("4006381333931"), product.gtin13.as_deref());
+        assert_eq!(Some("X-1"), product.mpn.as_deref());
+        let prices: Vec<_> = product
+            .offers
+            .iter()
+            .map(|o| o.price.as_ref().and_then(|p| p.as_f64()))
+            .collect();
+        assert_eq!(
+            vec![
+                Some(10.0),
+                Some(1299.5),
+                Some(9.99),
+                Some(1299.5),
+                Some(1299.0)
+            ],
+            prices
+        );
+        assert!(product.review.is_empty());
+
+        let address: PostalAddress =
+            serde_json::from_value(serde_json::json!({"postalCode": 10115})).unwrap();
+        assert_eq!(Some("10115"), address.postal_code.as_deref());
+
+        let recipe: Recipe = serde_json::from_value(serde_json::json!({
+            "recipeInstructions": ["Mix.", {"@type": "HowToStep", "text": "Bake."}]
+        }))
+        .unwrap();
+        let steps: Vec<_> = recipe
+            .recipe_instructions
+            .iter()
+            .map(Entity::name)
+            .collect();
+        assert_eq!(vec![Some("Mix."), Some("Bake.")], steps);
+    }
+}
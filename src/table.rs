@@ -0,0 +1,321 @@
+//! Extraction of HTML tables into rows of text.
+
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+use crate::element_ref::ElementRef;
+use crate::markdown::table_rows;
+
+/// The text content of an HTML table.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Table {
+    /// The header of each column, from the `thead` or leading rows of `th` cells; empty if
+    /// the table has none.
+    pub headers: Vec<String>,
+    /// The body rows. Rows may have different lengths.
+    pub rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    /// Returns the number of columns, counting the widest row.
+    pub fn columns(&self) -> usize {
+        self.rows
+            .iter()
+            .map(Vec::len)
+            .chain(Some(self.headers.len()))
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Returns a name for each column: its header, else `column_1`, `column_2`, ... Repeated
+    /// headers get a `_2`, `_3`, ... suffix so the names are unique.
+    pub fn column_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = Vec::with_capacity(self.columns());
+        for i in 0..self.columns() {
+            let base = match self.headers.get(i) {
+                Some(header) if !header.is_empty() => header.clone(),
+                _ => format!("column_{}", i + 1),
+            };
+            let mut name = base.clone();
+            let mut n = 2;
+            while names.contains(&name) {
+                name = format!("{}_{}", base, n);
+                n += 1;
+            }
+            names.push(name);
+        }
+        names
+    }
+
+    /// Returns each row as a map from [`Table::column_names`] to cell text. Cells missing
+    /// from short rows are left out.
+    pub fn records(&self) -> Vec<HashMap<String, String>> {
+        let names = self.column_names();
+        self.rows
+            .iter()
+            .map(|row| names.iter().cloned().zip(row.iter().cloned()).collect())
+            .collect()
+    }
+
+    /// Writes the table as RFC 4180 CSV, with the header row first when there is one.
+    ///
+    /// Fields containing commas, quotes or line breaks are quoted, and records end with
+    /// CRLF. Short rows are written as they are, without padding.
+    ///
+    /// ```
+    /// use scraper::{Html, Selector};
+    ///
+    /// let html = Html::parse_fragment(
+    ///     r#"<table><tr><th>Name</th><th>Note</th></tr><tr><td>A, B</td><td>say "hi"</td></tr></table>"#,
+    /// );
+    /// let table = html.select(&Selector::parse("table").unwrap()).next().unwrap();
+    ///
+    /// let mut csv = Vec::new();
+    /// table.as_table().unwrap().to_csv(&mut csv).unwrap();
+    /// assert_eq!("Name,Note\r\n\"A, B\",\"say \"\"hi\"\"\"\r\n", String::from_utf8(csv).unwrap());
+    /// ```
+    pub fn to_csv<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let records = Some(&self.headers)
+            .filter(|h| !h.is_empty())
+            .into_iter()
+            .chain(&self.rows);
+        for record in records {
+            for (i, field) in record.iter().enumerate() {
+                if i > 0 {
+                    writer.write_all(b",")?;
+                }
+                if field.contains([',', '"', '\r', '\n']) {
+                    write!(writer, "\"{}\"", field.replace('"', "\"\""))?;
+                } else {
+                    writer.write_all(field.as_bytes())?;
+                }
+            }
+            writer.write_all(b"\r\n")?;
+        }
+        writer.flush()
+    }
+
+    /// Converts the table into an Arrow record batch of nullable string columns, ready to
+    /// be written as Parquet.
+    ///
+    /// Columns are named by [`Table::column_names`]. Cells missing from short rows are null.
+    #[cfg(feature = "arrow")]
+    pub fn to_record_batch(&self) -> Result<arrow_array::RecordBatch, arrow_schema::ArrowError> {
+        use std::sync::Arc;
+
+        use arrow_array::{ArrayRef, RecordBatch, StringArray};
+        use arrow_schema::{DataType, Field, Schema};
+
+        let columns = self.columns();
+        let fields: Vec<Field> = self
+            .column_names()
+            .into_iter()
+            .map(|name| Field::new(name, DataType::Utf8, true))
+            .collect();
+        let arrays: Vec<ArrayRef> = (0..columns)
+            .map(|i| {
+                let values: StringArray = self
+                    .rows
+                    .iter()
+                    .map(|row| row.get(i).map(String::as_str))
+                    .collect();
+                Arc::new(values) as ArrayRef
+            })
+            .collect();
+        RecordBatch::try_new(Arc::new(Schema::new(fields)), arrays)
+    }
+}
+
+/// The most columns a cell may span, as in browsers.
+const MAX_COLSPAN: usize = 1000;
+
+/// Reads a `colspan` or `rowspan`, where missing or invalid values mean 1. Zero is kept
+/// for the caller: a `rowspan` of zero spans to the end of its row group, while a
+/// `colspan` of zero means 1.
+fn span(cell: ElementRef, attr: &str) -> usize {
+    cell.value()
+        .attr(attr)
+        .and_then(|value| value.trim().parse::<usize>().ok())
+        .unwrap_or(1)
+}
+
+impl ElementRef<'_> {
+    /// Extracts the text of a `table` element's cells, row by row. Returns `None` for other
+    /// elements.
+    ///
+    /// Cells spanning several columns or rows (`colspan` and `rowspan`, the latter within
+    /// its `thead`, `tbody` or `tfoot`, to whose end `rowspan="0"` reaches) repeat their
+    /// text in every position they cover, so each column lines up. The rows of the `thead`, or else the leading rows made only of
+    /// `th` cells, become [`Table::headers`], stacked header cells being joined by `" / "`.
+    /// Rows of nested tables are not included.
+    ///
+    /// ```
+    /// use scraper::{Html, Selector};
+    ///
+    /// let html = Html::parse_fragment(
+    ///     r#"<table>
+    ///     <thead><tr><th rowspan="2">Item</th><th colspan="2">Price</th></tr>
+    ///            <tr><th>USD</th><th>EUR</th></tr></thead>
+    ///     <tr><td>Tea</td><td colspan="2">1</td></tr>
+    ///     </table>"#,
+    /// );
+    /// let table = html.select(&Selector::parse("table").unwrap()).next().unwrap();
+    /// let table = table.as_table().unwrap();
+    ///
+    /// assert_eq!(vec!["Item", "Price / USD", "Price / EUR"], table.headers);
+    /// assert_eq!(vec![vec!["Tea", "1", "1"]], table.rows);
+    /// assert_eq!("1", table.records()[0]["Price / EUR"]);
+    /// ```
+    pub fn as_table(&self) -> Option<Table> {
+        if self.value().name() != "table" {
+            return None;
+        }
+        let rows = table_rows(**self);
+        // Rows are grouped by their `thead`, `tbody` or `tfoot`, which bounds `rowspan`.
+        let groups: Vec<_> = rows
+            .iter()
+            .map(|cells| {
+                cells
+                    .first()
+                    .and_then(|cell| Some(cell.parent()?.parent()?.id()))
+            })
+            .collect();
+
+        let mut grid: Vec<Vec<Option<String>>> = vec![Vec::new(); rows.len()];
+        for (r, cells) in rows.iter().enumerate() {
+            let group_end = (r..rows.len())
+                .take_while(|&next| groups[next] == groups[r])
+                .count();
+            let mut col = 0;
+            for cell in cells.iter().copied().filter_map(ElementRef::wrap) {
+                while grid[r].get(col).is_some_and(Option::is_some) {
+                    col += 1;
+                }
+                let colspan = span(cell, "colspan").clamp(1, MAX_COLSPAN);
+                let rowspan = match span(cell, "rowspan") {
+                    0 => group_end,
+                    rowspan => rowspan.min(group_end),
+                };
+                let text: String = cell.text().collect();
+                let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+                for row in &mut grid[r..r + rowspan] {
+                    if row.len() < col + colspan {
+                        row.resize(col + colspan, None);
+                    }
+                    row[col..col + colspan].fill(Some(text.clone()));
+                }
+                col += colspan;
+            }
+        }
+
+        let is_header = |r: usize| {
+            let cells = &rows[r];
+            let in_thead = cells
+                .first()
+                .and_then(|cell| cell.parent()?.parent())
+                .and_then(|group| group.value().as_element().map(|e| e.name() == "thead"));
+            match in_thead {
+                Some(true) => true,
+                _ if (0..r).any(|r| groups[r] != groups[0]) => false,
+                _ => {
+                    !cells.is_empty()
+                        && cells
+                            .iter()
+                            .all(|cell| cell.value().as_element().is_some_and(|e| e.name() == "th"))
+                }
+            }
+        };
+        let header_rows = (0..rows.len()).take_while(|&r| is_header(r)).count();
+
+        let mut grid = grid.into_iter().map(|row| {
+            row.into_iter()
+                .map(Option::unwrap_or_default)
+                .collect::<Vec<_>>()
+        });
+        let mut headers: Vec<String> = Vec::new();
+        for row in grid.by_ref().take(header_rows) {
+            for (col, text) in row.into_iter().enumerate() {
+                if headers.len() <= col {
+                    headers.resize(col + 1, String::new());
+                }
+                let header = &mut headers[col];
+                if text.is_empty() || header.rsplit(" / ").next() == Some(text.as_str()) {
+                    continue;
+                }
+                if !header.is_empty() {
+                    header.push_str(" / ");
+                }
+                header.push_str(&text);
+            }
+        }
+        Some(Table {
+            headers,
+            rows: grid.collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::html::Html;
+    use crate::selector::Selector;
+
+    #[test]
+    fn extracts_rows_without_nested_tables() {
+        let html = Html::parse_fragment(
+            "<table><tbody><tr><td>a</td><td><table><tr><td>x</td></tr></table></td></tr>\
+             <tr><td> b\n c </td></tr></tbody></table>",
+        );
+        let selector = Selector::parse("table").unwrap();
+        let table = html.select(&selector).next().unwrap().as_table().unwrap();
+        assert!(table.headers.is_empty());
+        assert_eq!(vec![vec!["a", "x"], vec!["b c"]], table.rows);
+        assert_eq!(2, table.columns());
+
+        assert!(html.root_element().as_table().is_none());
+    }
+
+    #[test]
+    fn spans_fill_their_group_only() {
+        let html = Html::parse_fragment(
+            r#"<table><tbody><tr><td rowspan="5">a</td><td>b</td></tr><tr><td colspan="0">c</td></tr></tbody>
+             <tbody><tr><td>d</td><td>e</td><td>f</td></tr></tbody>
+             <tbody><tr><td rowspan="0">g</td><td>h</td></tr><tr><td>i</td></tr><tr><td>j</td></tr></tbody></table>"#,
+        );
+        let selector = Selector::parse("table").unwrap();
+        let table = html.select(&selector).next().unwrap().as_table().unwrap();
+        assert!(table.headers.is_empty());
+        assert_eq!(
+            vec![
+                vec!["a", "b"],
+                vec!["a", "c"],
+                vec!["d", "e", "f"],
+                vec!["g", "h"],
+                vec!["g", "i"],
+                vec!["g", "j"]
+            ],
+            table.rows
+        );
+        let records = table.records();
+        assert_eq!(2, records[0].len());
+        assert_eq!("f", records[2]["column_3"]);
+    }
+
+    #[cfg(feature = "arrow")]
+    #[test]
+    fn record_batch_pads_short_rows_with_nulls() {
+        use arrow_array::Array;
+
+        let html = Html::parse_fragment(
+            "<table><tr><th>k</th></tr><tr><td>1</td><td>2</td></tr><tr><td>3</td></tr></table>",
+        );
+        let selector = Selector::parse("table").unwrap();
+        let table = html.select(&selector).next().unwrap().as_table().unwrap();
+        let batch = table.to_record_batch().unwrap();
+        assert_eq!(2, batch.num_columns());
+        assert_eq!("k", batch.schema().field(0).name());
+        assert_eq!("column_2", batch.schema().field(1).name());
+        assert!(batch.column(1).is_null(1));
+    }
+}
@@ -0,0 +1,213 @@
+//! Golden-file helpers for testing extraction rules.
+//!
+//! Enabled with the `testing` feature, typically as a dev-dependency. The
+//! [`assert_extraction!`](crate::assert_extraction) macro runs a [`RulePack`] against a fixture
+//! and compares the flattened result (see [`Extraction::to_value`]) with the expected JSON,
+//! either inline or stored in a golden file.
+//!
+//! ```
+//! use scraper::assert_extraction;
+//! use scraper::rules::{Extract, Rule, RulePack};
+//! use scraper::testing::json;
+//!
+//! let mut pack = RulePack::new("blog", "1");
+//! pack.push(Rule::new("title", "h1", Extract::Text).unwrap()).unwrap();
+//!
+//! assert_extraction!("<h1>Hello</h1>", pack, json!({ "title": "Hello" }));
+//! ```
+//!
+//! With `golden = "path"` the expected value is read from a file relative to the calling
+//! crate's manifest directory. Running the tests with `SCRAPER_UPDATE_SNAPSHOTS=1` writes the
+//! actual output to the file instead, which is how golden files are created and refreshed.
+//!
+//! [`Extraction::to_value`]: crate::rules::Extraction::to_value
+
+use std::borrow::Cow;
+use std::fs;
+use std::path::Path;
+
+pub use serde_json::json;
+use serde_json::Value;
+
+use crate::html::Html;
+use crate::rules::RulePack;
+
+/// The environment variable that switches golden comparisons into update mode.
+pub const UPDATE_ENV: &str = "SCRAPER_UPDATE_SNAPSHOTS";
+
+/// Something that can be turned into a document for a test.
+pub trait Fixture {
+    /// Returns the parsed document.
+    fn to_html(&self) -> Cow<'_, Html>;
+}
+
+impl Fixture for str {
+    fn to_html(&self) -> Cow<'_, Html> {
+        Cow::Owned(Html::parse_document(self))
+    }
+}
+
+impl Fixture for String {
+    fn to_html(&self) -> Cow<'_, Html> {
+        self.as_str().to_html()
+    }
+}
+
+impl Fixture for Html {
+    fn to_html(&self) -> Cow<'_, Html> {
+        Cow::Borrowed(self)
+    }
+}
+
+impl<T: Fixture + ?Sized> Fixture for &T {
+    fn to_html(&self) -> Cow<'_, Html> {
+        (**self).to_html()
+    }
+}
+
+/// Runs `pack` against `fixture` and returns the flattened output.
+pub fn extract_value<F: Fixture + ?Sized>(fixture: &F, pack: &RulePack) -> Value {
+    pack.execute(&fixture.to_html()).to_value()
+}
+
+/// Compares the output of `pack` against `expected`, returning a readable report on mismatch.
+pub fn check_extraction<F: Fixture + ?Sized>(
+    fixture: &F,
+    pack: &RulePack,
+    expected: &Value,
+) -> Result<(), String> {
+    let actual = extract_value(fixture, pack);
+    if actual == *expected {
+        Ok(())
+    } else {
+        Err(mismatch(&pack.name, expected, &actual))
+    }
+}
+
+/// Compares the output of `pack` against the golden file at `path`, or rewrites the file when
+/// [`UPDATE_ENV`] is set.
+pub fn check_golden<F: Fixture + ?Sized>(
+    fixture: &F,
+    pack: &RulePack,
+    path: &Path,
+) -> Result<(), String> {
+    let actual = extract_value(fixture, pack);
+
+    if std::env::var_os(UPDATE_ENV).is_some_and(|v| !v.is_empty() && v != "0") {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+        }
+        let mut body = serde_json::to_string_pretty(&actual).unwrap_or_default();
+        body.push('\n');
+        return fs::write(path, body).map_err(|e| format!("{}: {}", path.display(), e));
+    }
+
+    let source = fs::read_to_string(path).map_err(|e| {
+        format!(
+            "golden file {} could not be read ({}); rerun with {}=1 to create it",
+            path.display(),
+            e,
+            UPDATE_ENV
+        )
+    })?;
+    let expected: Value = serde_json::from_str(&source)
+        .map_err(|e| format!("golden file {} is not JSON: {}", path.display(), e))?;
+
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(format!(
+            "{}\nrerun with {}=1 to accept the new output into {}",
+            mismatch(&pack.name, &expected, &actual),
+            UPDATE_ENV,
+            path.display()
+        ))
+    }
+}
+
+fn mismatch(pack: &str, expected: &Value, actual: &Value) -> String {
+    let mut report = format!("extraction with rule pack {:?} does not match\n", pack);
+
+    if let (Value::Object(expected), Value::Object(actual)) = (expected, actual) {
+        let mut keys: Vec<&String> = expected.keys().chain(actual.keys()).collect();
+        keys.sort();
+        keys.dedup();
+        for key in keys {
+            let (e, a) = (expected.get(key), actual.get(key));
+            if e != a {
+                report.push_str(&format!(
+                    "  {}: expected {}, got {}\n",
+                    key,
+                    e.map_or("<absent>".into(), Value::to_string),
+                    a.map_or("<absent>".into(), Value::to_string)
+                ));
+            }
+        }
+    } else {
+        report.push_str(&format!("  expected {}\n  got      {}\n", expected, actual));
+    }
+
+    report
+}
+
+/// Asserts that a rule pack extracts the expected JSON from a fixture.
+///
+/// ```ignore
+/// assert_extraction!(html, pack, json!({ "title": "Hello" }));
+/// assert_extraction!(html, pack, golden = "tests/golden/blog.json");
+/// ```
+#[macro_export]
+macro_rules! assert_extraction {
+    ($fixture:expr, $pack:expr, golden = $path:expr $(,)?) => {
+        if let Err(report) = $crate::testing::check_golden(
+            &$fixture,
+            &$pack,
+            &::std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join($path),
+        ) {
+            panic!("{}", report);
+        }
+    };
+    ($fixture:expr, $pack:expr, $expected:expr $(,)?) => {
+        if let Err(report) = $crate::testing::check_extraction(&$fixture, &$pack, &$expected) {
+            panic!("{}", report);
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_extraction, check_golden, json};
+    use crate::rules::{Extract, Rule, RulePack};
+
+    fn pack() -> RulePack {
+        let mut pack = RulePack::new("list", "1");
+        pack.push(Rule::new("title", "h1", Extract::Text).unwrap())
+            .unwrap();
+        pack.push(Rule::new("items", "li", Extract::Text).unwrap().multiple())
+            .unwrap();
+        pack
+    }
+
+    #[test]
+    fn reports_field_mismatches() {
+        let html = "<h1>T</h1><li>a</li><li>b</li>";
+        assert!(
+            check_extraction(html, &pack(), &json!({"title": "T", "items": ["a", "b"]})).is_ok()
+        );
+
+        let report = check_extraction(html, &pack(), &json!({"title": "X", "items": ["a", "b"]}))
+            .unwrap_err();
+        assert!(report.contains(r#"title: expected "X", got "T""#));
+        assert!(!report.contains("items"));
+    }
+
+    #[test]
+    fn golden_file_round_trip() {
+        let path = std::env::temp_dir().join(format!("scraper-golden-{}.json", std::process::id()));
+        std::fs::write(&path, r#"{"title": "T", "items": []}"#).unwrap();
+        assert!(check_golden("<h1>T</h1>", &pack(), &path).is_ok());
+        assert!(check_golden("<h1>U</h1>", &pack(), &path).is_err());
+        std::fs::remove_file(&path).unwrap();
+        assert!(check_golden("<h1>T</h1>", &pack(), &path).is_err());
+    }
+}
@@ -0,0 +1,134 @@
+//! Blocks of visible text and the quotations they belong to.
+
+use std::ops::Range;
+
+use crate::element_ref::ElementRef;
+use crate::html::Html;
+use crate::selector::Selector;
+
+lazy_static! {
+    static ref CITE: Selector = Selector::parse("cite").unwrap();
+    static ref FIGCAPTION: Selector = Selector::parse(":scope > figcaption").unwrap();
+}
+
+/// The quotation a [`TextBlock`] is part of.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Quote {
+    /// The number of `blockquote` elements around the block, at least 1.
+    pub depth: usize,
+    /// The `cite` attribute of the innermost `blockquote`, the URL of the source.
+    pub cite: Option<String>,
+    /// The text of a `cite` element inside the innermost `blockquote`, or of the
+    /// `figcaption` of a `figure` around it, usually the quoted author.
+    pub attribution: Option<String>,
+}
+
+/// A block of the normalized text, such as a paragraph or heading.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TextBlock {
+    /// The text of the block.
+    pub text: String,
+    /// The byte range of the block in [`Html::normalized_text`].
+    pub range: Range<usize>,
+    /// The quotation the block is part of, if any.
+    pub quote: Option<Quote>,
+}
+
+/// Describes the quotation around an element.
+fn quote_of(element: ElementRef) -> Option<Quote> {
+    let mut quotes = std::iter::once(*element)
+        .chain(element.ancestors())
+        .filter_map(ElementRef::wrap)
+        .filter(|e| e.value().name() == "blockquote");
+    let innermost = quotes.next()?;
+    let depth = quotes.count() + 1;
+
+    let text = |e: ElementRef| {
+        let text = e.inner_text();
+        (!text.is_empty()).then_some(text)
+    };
+    let figure = innermost
+        .parent()
+        .and_then(ElementRef::wrap)
+        .filter(|parent| parent.value().name() == "figure");
+    let attribution = innermost
+        .select(&CITE)
+        .next()
+        .and_then(text)
+        .or_else(|| figure?.select(&FIGCAPTION).next().and_then(text));
+
+    Some(Quote {
+        depth,
+        cite: innermost
+            .attr("cite")
+            .map(str::trim)
+            .filter(|cite| !cite.is_empty())
+            .map(str::to_owned),
+        attribution,
+    })
+}
+
+impl Html {
+    /// Splits the visible text into blocks, marking the ones inside block quotes so that
+    /// quoted text can be told apart from the author's own.
+    ///
+    /// ```
+    /// use scraper::Html;
+    ///
+    /// let html = Html::parse_document(r#"
+    ///     <blockquote cite="https://forum.example/t/1#p2">
+    ///       <p>Original claim.</p>
+    ///       <blockquote><p>Earlier quote.</p></blockquote>
+    ///       <footer><cite>ada</cite></footer>
+    ///     </blockquote>
+    ///     <p>My reply.</p>
+    /// "#);
+    /// let blocks = html.text_blocks();
+    /// let quote = blocks[0].quote.as_ref().unwrap();
+    /// assert_eq!(1, quote.depth);
+    /// assert_eq!(Some("https://forum.example/t/1#p2"), quote.cite.as_deref());
+    /// assert_eq!(Some("ada"), quote.attribution.as_deref());
+    /// assert_eq!(2, blocks[1].quote.as_ref().unwrap().depth);
+    /// assert_eq!(("My reply.", None), (blocks[3].text.as_str(), blocks[3].quote.as_ref()));
+    /// ```
+    pub fn text_blocks(&self) -> Vec<TextBlock> {
+        let normalized = self.normalized_text();
+        normalized
+            .blocks()
+            .into_iter()
+            .map(|range| {
+                let quote = normalized
+                    .nodes(range.clone())
+                    .first()
+                    .and_then(|&id| self.tree.get(id)?.parent())
+                    .and_then(ElementRef::wrap)
+                    .and_then(quote_of);
+                TextBlock {
+                    text: normalized.as_str()[range.clone()].to_owned(),
+                    range,
+                    quote,
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::html::Html;
+
+    #[test]
+    fn figure_captions_attribute_quotes() {
+        let html = Html::parse_document(
+            "<figure><blockquote><p>Stay hungry.</p></blockquote>\
+             <figcaption>Steve Jobs</figcaption></figure>",
+        );
+        let blocks = html.text_blocks();
+        let quote = blocks[0].quote.as_ref().unwrap();
+        assert_eq!(Some("Steve Jobs"), quote.attribution.as_deref());
+        assert_eq!(None, quote.cite);
+        assert!(blocks[1].quote.is_none());
+    }
+}
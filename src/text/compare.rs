@@ -0,0 +1,135 @@
+//! Normalizing scraped strings so that superficially different spellings compare equal.
+
+use std::ops::Range;
+
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+/// Options for [`normalize_for_compare_with`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CompareOptions {
+    /// A BCP 47 language tag such as `tr` or `de-CH`; only the language subtag is used.
+    /// Empty for locale-independent folding.
+    pub locale: String,
+    /// Drop diacritics from Latin, Greek and Cyrillic letters, so `café` equals `cafe`.
+    /// Marks in other scripts, such as Japanese voicing marks, are kept.
+    pub strip_accents: bool,
+}
+
+impl CompareOptions {
+    /// Creates options for a locale, keeping accents.
+    pub fn locale(locale: &str) -> Self {
+        CompareOptions {
+            locale: locale.to_owned(),
+            strip_accents: false,
+        }
+    }
+
+    fn language(&self) -> &str {
+        self.locale.split(['-', '_']).next().unwrap_or_default()
+    }
+}
+
+/// Returns true for characters that accents may be stripped from.
+fn is_alphabetic_base(c: char) -> bool {
+    c < '\u{0530}' || ('\u{1E00}'..='\u{1FFF}').contains(&c)
+}
+
+/// Folds `text`, returning every folded character with the byte range of the source
+/// character it came from.
+pub(crate) fn fold_chars(text: &str, options: &CompareOptions) -> Vec<(Range<usize>, char)> {
+    let language = options.language();
+    let turkic = matches!(language, "tr" | "az");
+
+    let mut folded = Vec::with_capacity(text.len());
+    let mut base = ' ';
+    for (start, c) in text.char_indices() {
+        let range = start..start + c.len_utf8();
+        let mapped: Option<&str> = match c {
+            'I' if turkic => Some("ı"),
+            'İ' if turkic => Some("i"),
+            'ß' | 'ẞ' => Some("ss"),
+            _ => None,
+        };
+        let mut push = |c: char| {
+            for c in std::iter::once(c).nfkd() {
+                if is_combining_mark(c) {
+                    if options.strip_accents && is_alphabetic_base(base) {
+                        continue;
+                    }
+                } else {
+                    base = c;
+                }
+                for c in c.to_lowercase() {
+                    let c = if c == 'ς' { 'σ' } else { c };
+                    folded.push((range.clone(), c));
+                }
+            }
+        };
+        match mapped {
+            Some(mapped) => mapped.chars().for_each(&mut push),
+            None => push(c),
+        }
+    }
+    folded
+}
+
+/// Normalizes text for locale-aware comparison: case folding, compatibility (width)
+/// normalization, so full-width `Ｒｕｓｔ` equals `rust`, and composition of accents.
+///
+/// The locale decides the foldings that differ between languages: Turkish and Azerbaijani
+/// fold `I` to dotless `ı`. As in Unicode full case folding, `ß` folds to `ss` in every
+/// locale.
+///
+/// ```
+/// use scraper::text::normalize_for_compare;
+///
+/// assert_eq!(normalize_for_compare("Ｒｕｓｔ", ""), normalize_for_compare("rust", ""));
+/// assert_eq!(normalize_for_compare("Cafe\u{301}", ""), normalize_for_compare("CAFÉ", ""));
+/// assert_eq!("ıstanbul", normalize_for_compare("ISTANBUL", "tr"));
+/// assert_eq!("strasse", normalize_for_compare("Straße", "de"));
+/// ```
+pub fn normalize_for_compare(text: &str, locale: &str) -> String {
+    normalize_for_compare_with(text, &CompareOptions::locale(locale))
+}
+
+/// Normalizes text like [`normalize_for_compare`], with the accent handling of `options`.
+///
+/// ```
+/// use scraper::text::{normalize_for_compare_with, CompareOptions};
+///
+/// let options = CompareOptions { strip_accents: true, ..CompareOptions::locale("fr") };
+/// assert_eq!("creme brulee", normalize_for_compare_with("Crème Brûlée", &options));
+/// ```
+pub fn normalize_for_compare_with(text: &str, options: &CompareOptions) -> String {
+    fold_chars(text, options)
+        .into_iter()
+        .map(|(_, c)| c)
+        .nfc()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{normalize_for_compare, normalize_for_compare_with, CompareOptions};
+
+    #[test]
+    fn locale_foldings_and_marks() {
+        assert_eq!("i̇stanbul", normalize_for_compare("İSTANBUL", ""));
+        assert_eq!("istanbul", normalize_for_compare("İSTANBUL", "tr-TR"));
+        assert_eq!("strasse", normalize_for_compare("STRAßE", "en"));
+        assert_eq!(
+            normalize_for_compare("STRASSE", ""),
+            normalize_for_compare("STRAẞE", "")
+        );
+        assert_eq!("όσοσ", normalize_for_compare("ΌΣΟΣ", "el"));
+        assert_eq!("ガ", normalize_for_compare("ｶﾞ", "ja"));
+
+        let strip = CompareOptions {
+            strip_accents: true,
+            ..CompareOptions::default()
+        };
+        assert_eq!("ガ", normalize_for_compare_with("ｶﾞ", &strip));
+        assert_eq!("zurich", normalize_for_compare_with("Zürich", &strip));
+    }
+}
@@ -0,0 +1,11 @@
+//! Text utilities shared by extraction and rewriting.
+
+mod blocks;
+pub(crate) mod compare;
+pub(crate) mod normalized;
+mod slug;
+
+pub use self::blocks::{Quote, TextBlock};
+pub use self::compare::{normalize_for_compare, normalize_for_compare_with, CompareOptions};
+pub use self::normalized::{NodeAnchor, NormalizedText, Ruby, TextOptions};
+pub use self::slug::{slugify, SlugOptions};
@@ -0,0 +1,597 @@
+//! The visible text of a document, with a mapping back to the tree.
+
+use std::ops::Range;
+
+use ego_tree::iter::Edge;
+use ego_tree::{NodeId, NodeRef};
+
+use crate::element_ref::ElementRef;
+use crate::html::Html;
+use crate::node::Node;
+
+/// Elements whose text is not rendered as page content.
+pub(crate) const HIDDEN_TEXT: &[&str] = &[
+    "script", "style", "noscript", "template", "textarea", "title",
+];
+
+/// Elements that flow inline with their surrounding text, so their text is not separated
+/// from it by a space.
+const INLINE: &[&str] = &[
+    "a", "abbr", "b", "bdi", "bdo", "cite", "code", "data", "del", "dfn", "em", "font", "i", "ins",
+    "kbd", "label", "mark", "q", "rb", "rp", "rt", "rtc", "ruby", "s", "samp", "small", "span",
+    "strong", "sub", "sup", "time", "u", "var",
+];
+
+/// How [`ElementRef::inner_text_with`] treats `ruby` annotations.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Ruby {
+    /// Keeps the base text only, so `<ruby>漢<rt>かん</rt></ruby>` gives `漢`.
+    #[default]
+    Base,
+    /// Follows the base text with each annotation in parentheses, giving `漢(かん)`.
+    Parenthesized,
+    /// Drops the whole `ruby` element.
+    Skip,
+}
+
+/// Elements whose whitespace [`TextOptions::preserve_preformatted`] keeps.
+const PREFORMATTED: &[&str] = &["code", "pre", "textarea"];
+
+/// Options for [`ElementRef::inner_text_with`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct TextOptions {
+    /// The treatment of ruby annotations. `rp` fallback parentheses are always dropped.
+    pub ruby: Ruby,
+    /// Keeps the whitespace of `pre`, `code` and `textarea` elements as written, and
+    /// includes the text of `textarea`, which is skipped otherwise.
+    pub preserve_preformatted: bool,
+    /// Lays the text out roughly as a browser's `innerText` does: block-level elements
+    /// start new lines, paragraphs are followed by a blank line, `br` breaks the line,
+    /// list items get a `• ` bullet, table cells are separated by tabs, and elements that
+    /// are `hidden` or styled `display: none` are skipped.
+    pub layout: bool,
+}
+
+impl Default for TextOptions {
+    fn default() -> Self {
+        TextOptions {
+            ruby: Ruby::default(),
+            preserve_preformatted: true,
+            layout: false,
+        }
+    }
+}
+
+impl TextOptions {
+    /// Returns true if the text of the element is not collected.
+    fn hides(&self, name: &str) -> bool {
+        (HIDDEN_TEXT.contains(&name) && !(self.preserve_preformatted && name == "textarea"))
+            || name == "rp"
+            || match self.ruby {
+                Ruby::Base => name == "rt",
+                Ruby::Parenthesized => false,
+                Ruby::Skip => name == "ruby",
+            }
+    }
+}
+
+/// Returns true if the element has the `hidden` attribute or an inline `display: none`.
+fn is_hidden(element: &crate::node::Element) -> bool {
+    element.attr("hidden").is_some()
+        || element.attr("style").is_some_and(|style| {
+            let style: String = style.split_whitespace().collect();
+            style.to_ascii_lowercase().contains("display:none")
+        })
+}
+
+/// Returns true for East Asian wide characters other than Hangul, between which a line
+/// break in the source is not rendered as a space.
+fn is_east_asian_wide(c: char) -> bool {
+    matches!(c,
+        '\u{2E80}'..='\u{303F}'
+        | '\u{3040}'..='\u{312F}'
+        | '\u{3190}'..='\u{31FF}'
+        | '\u{3400}'..='\u{4DBF}'
+        | '\u{4E00}'..='\u{9FFF}'
+        | '\u{F900}'..='\u{FAFF}'
+        | '\u{FE30}'..='\u{FE4F}'
+        | '\u{FF00}'..='\u{FF60}'
+        | '\u{FFE0}'..='\u{FFE6}'
+        | '\u{20000}'..='\u{3FFFF}')
+}
+
+/// A word of the normalized text and where it came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Segment {
+    /// Byte range in the normalized text.
+    range: Range<usize>,
+    /// The text node.
+    node: NodeId,
+    /// Byte offset of the word in the text node.
+    offset: usize,
+}
+
+/// A byte range within a single text node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NodeAnchor {
+    /// The position of the text node in a pre-order walk of the tree from the root, which is
+    /// stable across re-parsing the same markup.
+    pub node: usize,
+    /// The start byte offset in the node's text.
+    pub start: usize,
+    /// The end byte offset in the node's text.
+    pub end: usize,
+}
+
+/// The visible text of a document with whitespace collapsed to single spaces.
+///
+/// Text of `script`, `style`, `noscript`, `template`, `textarea` and `title` elements is
+/// skipped, as are ruby annotations, and block-level elements separate words. Every word
+/// keeps a mapping to the text node it came from, so ranges of the normalized text can be
+/// mapped back to the tree.
+#[derive(Debug, Clone)]
+pub struct NormalizedText {
+    text: String,
+    segments: Vec<Segment>,
+    /// The pre-order index of each collected text node.
+    order: Vec<(NodeId, usize)>,
+    /// Offsets where a block-level element starts or ends.
+    breaks: Vec<usize>,
+    /// Whether a line break follows East Asian text, which becomes a space only if the
+    /// next word does not start with East Asian text.
+    soft_break: bool,
+}
+
+impl NormalizedText {
+    fn collect(root: NodeRef<Node>, options: &TextOptions) -> Self {
+        Self::collect_except(root, options, |_| false)
+    }
+
+    /// Collects the text of `root`, leaving out the subtrees for which `skip` is true.
+    fn collect_except(
+        root: NodeRef<Node>,
+        options: &TextOptions,
+        skip: impl Fn(NodeRef<Node>) -> bool,
+    ) -> Self {
+        let mut normalized = NormalizedText {
+            text: String::new(),
+            segments: Vec::new(),
+            order: Vec::new(),
+            breaks: Vec::new(),
+            soft_break: false,
+        };
+        let mut hidden = 0;
+        let mut verbatim = 0;
+        let mut index = 0;
+
+        for edge in root.traverse() {
+            let (node, open) = match edge {
+                Edge::Open(node) => (node, true),
+                Edge::Close(node) => (node, false),
+            };
+            if open {
+                index += 1;
+            }
+            if let Node::Element(e) = node.value() {
+                if options.preserve_preformatted && PREFORMATTED.contains(&e.name()) {
+                    if open {
+                        verbatim += 1;
+                    } else {
+                        verbatim -= 1;
+                    }
+                }
+            }
+            match node.value() {
+                Node::Text(text) if open && hidden == 0 => {
+                    normalized.order.push((node.id(), index - 1));
+                    if verbatim > 0 {
+                        normalized.push_verbatim(text, node.id());
+                    } else {
+                        normalized.push(text, node.id());
+                    }
+                }
+                Node::Element(e)
+                    if options.hides(e.name()) || options.layout && is_hidden(e) || skip(node) =>
+                {
+                    if open {
+                        hidden += 1;
+                    } else {
+                        hidden -= 1;
+                    }
+                }
+                Node::Element(e) if e.name() == "rt" && hidden == 0 => {
+                    if open {
+                        normalized.literal('(');
+                    } else {
+                        normalized.literal(')');
+                    }
+                }
+                Node::Element(e) if options.layout && hidden == 0 => match e.name() {
+                    "br" if open => normalized.line_break(),
+                    "td" | "th" if open => normalized.column(),
+                    "li" if open => {
+                        normalized.lines(1);
+                        normalized.text.push_str("• ");
+                    }
+                    "p" => normalized.lines(2),
+                    name if !INLINE.contains(&name) && name != "td" && name != "th" => {
+                        normalized.lines(1)
+                    }
+                    _ => {}
+                },
+                Node::Element(e) if !INLINE.contains(&e.name()) => {
+                    normalized.separate();
+                    let at = normalized.text.len();
+                    if at > 0 && normalized.breaks.last() != Some(&at) {
+                        normalized.breaks.push(at);
+                    }
+                }
+                _ => {}
+            }
+        }
+        if options.layout {
+            let word_end = normalized.segments.last().map_or(0, |s| s.range.end);
+            let end = word_end.max(normalized.text.trim_end().len());
+            normalized.text.truncate(end);
+        } else if normalized.text.ends_with(' ') {
+            normalized.text.pop();
+        }
+        let len = normalized.text.len();
+        normalized.breaks.retain(|&at| at < len);
+        normalized
+    }
+
+    fn push(&mut self, text: &str, node: NodeId) {
+        for word in text.split_whitespace() {
+            let offset = word.as_ptr() as usize - text.as_ptr() as usize;
+            let space = &text[text[..offset].trim_end().len()..offset];
+            if !space.is_empty() {
+                self.gap(space);
+            }
+            self.resolve(word);
+            let start = self.text.len();
+            self.text.push_str(word);
+            self.segments.push(Segment {
+                range: start..self.text.len(),
+                node,
+                offset,
+            });
+        }
+        let space = &text[text.trim_end().len()..];
+        if !space.is_empty() {
+            self.gap(space);
+        }
+    }
+
+    /// Appends the text of a node as it is written.
+    fn push_verbatim(&mut self, text: &str, node: NodeId) {
+        if text.is_empty() {
+            return;
+        }
+        self.resolve(text);
+        let start = self.text.len();
+        self.text.push_str(text);
+        self.segments.push(Segment {
+            range: start..self.text.len(),
+            node,
+            offset: 0,
+        });
+    }
+
+    /// Ends the current word for a run of whitespace, deferring the decision for a line
+    /// break after East Asian text.
+    fn gap(&mut self, space: &str) {
+        let east_asian = self
+            .text
+            .chars()
+            .next_back()
+            .is_some_and(is_east_asian_wide);
+        if east_asian && space.contains('\n') {
+            self.soft_break = true;
+        } else {
+            self.separate();
+        }
+    }
+
+    /// Settles a deferred line break before `word`.
+    fn resolve(&mut self, word: &str) {
+        if std::mem::take(&mut self.soft_break)
+            && !word.chars().next().is_some_and(is_east_asian_wide)
+        {
+            self.separate();
+        }
+    }
+
+    /// Appends a character that belongs to no text node.
+    fn literal(&mut self, c: char) {
+        let word = c.to_string();
+        self.resolve(&word);
+        self.text.push(c);
+    }
+
+    /// Ends the current word, unless the text is empty or already ends with whitespace.
+    fn separate(&mut self) {
+        self.soft_break = false;
+        if !self.text.is_empty() && !self.text.ends_with(char::is_whitespace) {
+            self.text.push(' ');
+        }
+    }
+
+    /// Drops the space after the last word, outside any verbatim text.
+    fn trim_gap(&mut self) {
+        let word_end = self.segments.last().map_or(0, |s| s.range.end);
+        self.text
+            .truncate(word_end.max(self.text.trim_end_matches(' ').len()));
+    }
+
+    /// Ends the line with `count` line breaks, unless nothing precedes it.
+    fn lines(&mut self, count: usize) {
+        self.soft_break = false;
+        self.trim_gap();
+        if !self.text.is_empty() {
+            let present = self.text.len() - self.text.trim_end_matches('\n').len();
+            for _ in present..count {
+                self.text.push('\n');
+            }
+        }
+    }
+
+    /// Adds a line break for a `br` element.
+    fn line_break(&mut self) {
+        self.soft_break = false;
+        self.trim_gap();
+        self.text.push('\n');
+    }
+
+    /// Separates a table cell from the previous one in its row.
+    fn column(&mut self) {
+        self.soft_break = false;
+        self.trim_gap();
+        if !self.text.is_empty() && !self.text.ends_with('\n') {
+            self.text.push('\t');
+        }
+    }
+
+    /// Returns the normalized text.
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    /// Returns the byte ranges of the text of each block-level element, such as paragraphs,
+    /// headings and list items, without the separating spaces.
+    pub fn blocks(&self) -> Vec<Range<usize>> {
+        let mut blocks = Vec::new();
+        let mut start = 0;
+        for end in self.breaks.iter().copied().chain(Some(self.text.len())) {
+            let block = &self.text[start..end];
+            let trimmed = block.trim_end();
+            if !trimmed.trim_start().is_empty() {
+                let lead = block.len() - block.trim_start().len();
+                blocks.push(start + lead..start + trimmed.len());
+            }
+            start = end;
+        }
+        blocks
+    }
+
+    /// Returns the ids of the text nodes overlapping a byte range, in document order.
+    pub fn nodes(&self, range: Range<usize>) -> Vec<NodeId> {
+        let mut nodes: Vec<NodeId> = Vec::new();
+        for segment in self.overlapping(&range) {
+            if nodes.last() != Some(&segment.node) {
+                nodes.push(segment.node);
+            }
+        }
+        nodes
+    }
+
+    /// Maps a byte range of the normalized text to ranges within text nodes. Whitespace
+    /// between words of the same node is included; a range that covers only whitespace
+    /// maps to nothing.
+    pub fn anchors(&self, range: Range<usize>) -> Vec<NodeAnchor> {
+        let mut anchors: Vec<NodeAnchor> = Vec::new();
+        for segment in self.overlapping(&range) {
+            let start = segment.offset + range.start.saturating_sub(segment.range.start);
+            let end = segment.offset + range.end.min(segment.range.end) - segment.range.start;
+            let node = self.index_of(segment.node);
+            match anchors.last_mut() {
+                Some(last) if last.node == node => last.end = end,
+                _ => anchors.push(NodeAnchor { node, start, end }),
+            }
+        }
+        anchors
+    }
+
+    /// Maps a text node anchor back to a byte range of the normalized text, if the anchor
+    /// starts and ends inside words of that node.
+    pub fn locate(&self, anchor: &NodeAnchor) -> Option<Range<usize>> {
+        let &(node, _) = self.order.iter().find(|(_, i)| *i == anchor.node)?;
+        let words: Vec<&Segment> = self.segments.iter().filter(|s| s.node == node).collect();
+        let position = |offset: usize, end: bool| {
+            words.iter().find_map(|s| {
+                let len = s.range.len();
+                let inside = if end {
+                    offset > s.offset && offset <= s.offset + len
+                } else {
+                    offset >= s.offset && offset < s.offset + len
+                };
+                inside.then(|| s.range.start + offset - s.offset)
+            })
+        };
+        Some(position(anchor.start, false)?..position(anchor.end, true)?)
+    }
+
+    /// Converts a range of char offsets into a range of byte offsets.
+    pub fn char_range(&self, chars: Range<usize>) -> Option<Range<usize>> {
+        let byte = |n: usize| {
+            self.text
+                .char_indices()
+                .map(|(i, _)| i)
+                .chain(std::iter::once(self.text.len()))
+                .nth(n)
+        };
+        Some(byte(chars.start)?..byte(chars.end)?)
+    }
+
+    fn overlapping<'s>(&'s self, range: &'s Range<usize>) -> impl Iterator<Item = &'s Segment> {
+        self.segments
+            .iter()
+            .filter(move |s| s.range.start < range.end && range.start < s.range.end)
+    }
+
+    fn index_of(&self, node: NodeId) -> usize {
+        self.order
+            .iter()
+            .find(|(id, _)| *id == node)
+            .map_or(0, |&(_, index)| index)
+    }
+}
+
+impl Html {
+    /// Returns the visible text of the document, normalized for searching and annotation.
+    ///
+    /// ```
+    /// use scraper::Html;
+    ///
+    /// let html = Html::parse_document("<h1>Title</h1><p>Some  <b>bold</b>\ntext</p><script>x</script>");
+    /// let text = html.normalized_text();
+    /// assert_eq!("Title Some bold text", text.as_str());
+    ///
+    /// let anchors = text.anchors(11..20);
+    /// assert_eq!(2, anchors.len());
+    /// assert_eq!(Some(11..15), text.locate(&anchors[0]));
+    /// ```
+    pub fn normalized_text(&self) -> NormalizedText {
+        let options = TextOptions {
+            preserve_preformatted: false,
+            ..TextOptions::default()
+        };
+        NormalizedText::collect(self.tree.root(), &options)
+    }
+
+    /// Returns the node at a pre-order position, as used by [`NodeAnchor::node`].
+    pub fn node_at(&self, index: usize) -> Option<NodeRef<'_, Node>> {
+        self.tree.root().descendants().nth(index)
+    }
+}
+
+impl ElementRef<'_> {
+    /// Returns the visible text of the element with whitespace collapsed outside
+    /// preformatted elements. [`TextOptions::layout`] breaks it into lines instead.
+    pub fn inner_text(&self) -> String {
+        self.inner_text_with(&TextOptions::default())
+    }
+
+    /// Returns the visible text of the element with whitespace collapsed.
+    ///
+    /// Line breaks between East Asian characters are dropped rather than turned into
+    /// spaces, and ruby annotations and preformatted text are handled according to
+    /// `options`.
+    ///
+    /// ```
+    /// use scraper::text::{Ruby, TextOptions};
+    /// use scraper::Html;
+    ///
+    /// let html = Html::parse_fragment(
+    ///     "<p><ruby>漢<rp>(</rp><rt>かん</rt><rp>)</rp>字<rt>じ</rt></ruby>を\n学ぶ</p>",
+    /// );
+    /// let p = html.root_element();
+    /// assert_eq!("漢字を学ぶ", p.inner_text());
+    ///
+    /// let options = TextOptions { ruby: Ruby::Parenthesized, ..TextOptions::default() };
+    /// assert_eq!("漢(かん)字(じ)を学ぶ", p.inner_text_with(&options));
+    ///
+    /// let html = Html::parse_fragment("<p>Run</p>\n<pre>fn main() {\n    run();\n}</pre>");
+    /// assert_eq!("Run fn main() {\n    run();\n}", html.root_element().inner_text());
+    ///
+    /// let html = Html::parse_fragment(
+    ///     "<h1>Menu</h1><ul><li>Tea<li hidden>Gin<li>Coffee</ul><p>Open<br>daily</p><p>Cash</p>",
+    /// );
+    /// let options = TextOptions { layout: true, ..TextOptions::default() };
+    /// assert_eq!(
+    ///     "Menu\n• Tea\n• Coffee\n\nOpen\ndaily\n\nCash",
+    ///     html.root_element().inner_text_with(&options)
+    /// );
+    /// ```
+    pub fn inner_text_with(&self, options: &TextOptions) -> String {
+        NormalizedText::collect(**self, options).text
+    }
+
+    /// Returns the [`inner_text`](Self::inner_text) of the element without the subtrees
+    /// for which `skip` is true.
+    pub(crate) fn inner_text_except(&self, skip: impl Fn(NodeRef<Node>) -> bool) -> String {
+        NormalizedText::collect_except(**self, &TextOptions::default(), skip).text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Ruby, TextOptions};
+    use crate::html::Html;
+
+    #[test]
+    fn anchors_round_trip() {
+        let html = Html::parse_document("<p>  alpha   beta</p><div>gamma<i> delta</i></div>");
+        let text = html.normalized_text();
+        assert_eq!("alpha beta gamma delta", text.as_str());
+
+        let anchors = text.anchors(2..13);
+        assert_eq!(2, anchors.len());
+        let node = html.node_at(anchors[0].node).unwrap();
+        let source = &**node.value().as_text().unwrap();
+        assert_eq!("pha   beta", &source[anchors[0].start..anchors[0].end]);
+        assert_eq!(Some(2..10), text.locate(&anchors[0]));
+        assert_eq!(Some(11..13), text.locate(&anchors[1]));
+
+        assert_eq!(Some(6..10), text.char_range(6..10));
+        let blocks: Vec<_> = text
+            .blocks()
+            .into_iter()
+            .map(|b| &text.as_str()[b])
+            .collect();
+        assert_eq!(vec!["alpha beta", "gamma delta"], blocks);
+        assert!(text.anchors(5..6).is_empty());
+    }
+
+    #[test]
+    fn east_asian_line_breaks_and_ruby() {
+        let html = Html::parse_fragment(
+            "<p>東京\n<b>タワー</b>\nTokyo\nTower</p><p>서울\n타워</p>\
+             <p><ruby>明日<rt>あした</rt></ruby>は</p>",
+        );
+        let text = html.normalized_text();
+        assert_eq!("東京タワー Tokyo Tower 서울 타워 明日は", text.as_str());
+
+        let options = TextOptions {
+            ruby: Ruby::Skip,
+            ..TextOptions::default()
+        };
+        assert_eq!(
+            "東京タワー Tokyo Tower 서울 타워 は",
+            html.root_element().inner_text_with(&options)
+        );
+    }
+
+    #[test]
+    fn layout_breaks_lines_and_skips_hidden_elements() {
+        let html = Html::parse_fragment(
+            "<div>Intro <b>bold</b>\n text</div>\
+             <div style='DISPLAY : None'>Hidden</div>\
+             <table><tr><th>Name</th><th> Qty </th></tr><tr><td>Tea</td><td>2</td></tr></table>\
+             <pre>a  b\n  c</pre><p>End<br><br>here</p>",
+        );
+        let options = TextOptions {
+            layout: true,
+            ..TextOptions::default()
+        };
+        assert_eq!(
+            "Intro bold text\nName\tQty\nTea\t2\na  b\n  c\n\nEnd\n\nhere",
+            html.root_element().inner_text_with(&options)
+        );
+    }
+}
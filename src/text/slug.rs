@@ -0,0 +1,143 @@
+//! Slugs for ids and anchors.
+
+use unicode_normalization::UnicodeNormalization;
+
+/// Options for [`slugify`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlugOptions {
+    /// Joins the words of the slug.
+    pub separator: char,
+    /// Lowercase the slug.
+    pub lowercase: bool,
+    /// Fold accented Latin letters to ASCII and drop every other non-ASCII character.
+    pub ascii: bool,
+    /// Cut the slug to at most this many characters, preferring a word boundary.
+    pub max_len: Option<usize>,
+}
+
+impl Default for SlugOptions {
+    fn default() -> Self {
+        SlugOptions {
+            separator: '-',
+            lowercase: true,
+            ascii: false,
+            max_len: None,
+        }
+    }
+}
+
+/// Folds a letter to ASCII, for letters without a canonical decomposition.
+fn fold_letter(c: char) -> Option<&'static str> {
+    Some(match c {
+        'ß' => "ss",
+        'æ' => "ae",
+        'Æ' => "AE",
+        'œ' => "oe",
+        'Œ' => "OE",
+        'ø' => "o",
+        'Ø' => "O",
+        'đ' | 'ð' => "d",
+        'Đ' | 'Ð' => "D",
+        'ł' => "l",
+        'Ł' => "L",
+        'þ' => "th",
+        'Þ' => "TH",
+        'ı' => "i",
+        _ => return None,
+    })
+}
+
+/// Turns text into a slug such as `getting-started`.
+///
+/// Words are runs of Unicode letters and digits; everything else separates them, except
+/// apostrophes, which are dropped so that `Don't` becomes `dont`. Letters of any script are
+/// kept unless [`SlugOptions::ascii`] is set.
+///
+/// ```
+/// use scraper::text::{slugify, SlugOptions};
+///
+/// assert_eq!("whats-new-in-2-0", slugify("What's new in 2.0?", &SlugOptions::default()));
+/// assert_eq!("über-uns", slugify("Über uns", &SlugOptions::default()));
+///
+/// let ascii = SlugOptions { ascii: true, separator: '_', ..SlugOptions::default() };
+/// assert_eq!("uber_strasse", slugify("Über Straße", &ascii));
+/// ```
+pub fn slugify(text: &str, options: &SlugOptions) -> String {
+    let mut folded = String::with_capacity(text.len());
+    if options.ascii {
+        for c in text.nfd() {
+            match fold_letter(c) {
+                Some(ascii) => folded.push_str(ascii),
+                None if c.is_ascii() => folded.push(c),
+                None if c.is_alphanumeric() => folded.push(' '),
+                None => {}
+            }
+        }
+    } else {
+        folded.extend(text.nfc());
+    }
+
+    let mut slug = String::with_capacity(folded.len());
+    let mut pending = false;
+    for c in folded.chars() {
+        if c == '\'' || c == '\u{2019}' {
+            continue;
+        }
+        if !c.is_alphanumeric() {
+            pending = !slug.is_empty();
+            continue;
+        }
+        if pending {
+            slug.push(options.separator);
+            pending = false;
+        }
+        if options.lowercase {
+            slug.extend(c.to_lowercase());
+        } else {
+            slug.push(c);
+        }
+    }
+
+    if let Some(max) = options.max_len {
+        if let Some((cut, _)) = slug.char_indices().nth(max) {
+            let at_boundary = slug[cut..].starts_with(options.separator);
+            let cut = match slug[..cut].rfind(options.separator) {
+                Some(boundary) if !at_boundary && boundary > 0 => boundary,
+                _ => cut,
+            };
+            slug.truncate(cut);
+        }
+    }
+    slug
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{slugify, SlugOptions};
+
+    #[test]
+    fn unicode_and_options() {
+        let default = SlugOptions::default();
+        assert_eq!("dont-panic", slugify("  Don’t   Panic!! ", &default));
+        assert_eq!("日本語の見出し-2", slugify("日本語の見出し (2)", &default));
+        assert_eq!("", slugify("?!", &default));
+
+        let keep_case = SlugOptions {
+            lowercase: false,
+            max_len: Some(12),
+            ..SlugOptions::default()
+        };
+        assert_eq!("Rust-Crate", slugify("Rust Crate Overview", &keep_case));
+        assert_eq!("Supercalifra", slugify("Supercalifragilistic", &keep_case));
+
+        let ascii = SlugOptions {
+            ascii: true,
+            ..SlugOptions::default()
+        };
+        assert_eq!(
+            "creme-brulee-a-lancienne",
+            slugify("Crème brûlée à l'ancienne", &ascii)
+        );
+        assert_eq!("a-b", slugify("a Ω b", &ascii));
+    }
+}
@@ -0,0 +1,134 @@
+//! Table-of-contents generation.
+
+use std::collections::HashSet;
+
+use crate::element_ref::ElementRef;
+use crate::html::Html;
+use crate::selector::Selector;
+use crate::text::{slugify, SlugOptions};
+
+lazy_static! {
+    static ref HEADINGS: Selector = Selector::parse("h1, h2, h3, h4, h5, h6").unwrap();
+    static ref WITH_ID: Selector = Selector::parse("[id]").unwrap();
+}
+
+/// A heading in a table of contents.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TocEntry {
+    /// The heading text, with whitespace collapsed.
+    pub text: String,
+    /// The heading level, 1 for `<h1>` through 6 for `<h6>`.
+    pub level: u8,
+    /// The `id` of the heading element.
+    pub id: String,
+    /// The headings nested below this one.
+    pub children: Vec<TocEntry>,
+}
+
+impl TocEntry {
+    /// Returns the fragment link to the heading, e.g. `#getting-started`.
+    pub fn anchor(&self) -> String {
+        format!("#{}", self.id)
+    }
+}
+
+/// Appends `entry` below the last entry of `entries` whose level is lower.
+fn insert(entries: &mut Vec<TocEntry>, entry: TocEntry) {
+    match entries.last_mut() {
+        Some(last) if last.level < entry.level => insert(&mut last.children, entry),
+        _ => entries.push(entry),
+    }
+}
+
+impl Html {
+    /// Returns the nested table of contents of the `<h1>` to `<h{max_depth}>` headings.
+    ///
+    /// Headings without an `id` are given one derived from their text, made unique within
+    /// the document by a numeric suffix, so the returned anchors resolve in the tree.
+    ///
+    /// ```
+    /// use scraper::Html;
+    ///
+    /// let mut html = Html::parse_document("<h1>Guide</h1><h2>Install</h2><h2 id=use>Usage</h2>");
+    /// let toc = html.generate_toc(3);
+    /// assert_eq!("Guide", toc[0].text);
+    /// assert_eq!("#install", toc[0].children[0].anchor());
+    /// assert_eq!("#use", toc[0].children[1].anchor());
+    /// assert!(html.html().contains(r#"<h2 id="install">"#));
+    /// ```
+    pub fn generate_toc(&mut self, max_depth: u8) -> Vec<TocEntry> {
+        let mut ids: HashSet<String> = self
+            .select(&WITH_ID)
+            .filter_map(|e| e.value().id().map(str::to_owned))
+            .collect();
+
+        let headings: Vec<_> = self
+            .select(&HEADINGS)
+            .filter_map(|heading| {
+                let level = heading.value().name()[1..].parse::<u8>().ok()?;
+                (level <= max_depth).then_some((heading.id(), level))
+            })
+            .collect();
+
+        let mut entries = Vec::new();
+        for (node_id, level) in headings {
+            let heading = ElementRef::wrap(self.tree.get(node_id).unwrap()).unwrap();
+            let text = heading.text().collect::<String>();
+            let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+
+            let id = match heading.value().id() {
+                Some(id) => id.to_owned(),
+                None => {
+                    let mut base = slugify(&text, &SlugOptions::default());
+                    if base.is_empty() {
+                        base.push_str("section");
+                    }
+                    let mut id = base.clone();
+                    let mut n = 1;
+                    while ids.contains(&id) {
+                        id = format!("{}-{}", base, n);
+                        n += 1;
+                    }
+                    ids.insert(id.clone());
+
+                    self.set_attr(node_id, "id", &id);
+                    id
+                }
+            };
+
+            insert(
+                &mut entries,
+                TocEntry {
+                    text,
+                    level,
+                    id,
+                    children: Vec::new(),
+                },
+            );
+        }
+        entries
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::html::Html;
+    use crate::selector::Selector;
+
+    #[test]
+    fn nests_levels_and_dedupes_ids() {
+        let mut html = Html::parse_document(
+            r#"<p id="intro"></p><h2>Intro</h2><h3>A</h3><h4>Too deep</h4><h2>Intro</h2><h1>  ¿Qué   pasa? </h1>"#,
+        );
+        let toc = html.generate_toc(3);
+
+        let top: Vec<_> = toc.iter().map(|e| (e.level, e.id.as_str())).collect();
+        assert_eq!(vec![(2, "intro-1"), (2, "intro-2"), (1, "qué-pasa")], top);
+        assert_eq!(1, toc[0].children.len());
+        assert!(toc[0].children[0].children.is_empty());
+        assert_eq!("¿Qué pasa?", toc[2].text);
+
+        let selector = Selector::parse("#intro-2").unwrap();
+        assert_eq!(1, html.select(&selector).count());
+    }
+}
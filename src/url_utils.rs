@@ -0,0 +1,289 @@
+//! URL normalization, so the same page reached through different spellings of its URL is
+//! crawled once.
+//!
+//! [`normalize`] rewrites a URL according to a [`UrlPolicy`]. Parsing with [`Url`] already
+//! lowercases the scheme and host, removes default ports and resolves `.` and `..`
+//! segments; on top of that the policy removes fragments and query parameters, sorts the
+//! query and decodes needlessly percent-encoded characters. By default it removes the
+//! tracking parameters of [`TRACKING_PARAMS`]. The link extractors of
+//! [`Html`](crate::Html) that take a policy, such as
+//! [`Html::link_edges_with`](crate::Html::link_edges_with) and
+//! [`Html::downloadable_links_with`](crate::Html::downloadable_links_with), normalize every
+//! URL they return with it. [`Html::embeds`](crate::Html::embeds) takes no policy, since it
+//! already returns the canonical URL of each embedded post or video.
+
+use url::Url;
+
+/// Query parameters added by analytics and ad platforms to attribute visits, which never
+/// change the page served: `utm_*` campaign tags, ad click IDs such as `gclid`, `fbclid`
+/// and `msclkid`, and the IDs of mailing, marketing automation and social platforms.
+/// Names ending in `*` are prefixes.
+pub const TRACKING_PARAMS: &[&str] = &[
+    "utm_*",
+    "gclid",
+    "gclsrc",
+    "dclid",
+    "gbraid",
+    "wbraid",
+    "fbclid",
+    "msclkid",
+    "yclid",
+    "twclid",
+    "ttclid",
+    "li_fat_id",
+    "igshid",
+    "mc_cid",
+    "mc_eid",
+    "_hsenc",
+    "_hsmi",
+    "hsa_*",
+    "mkt_tok",
+    "vero_id",
+    "oly_anon_id",
+    "oly_enc_id",
+    "rb_clickid",
+    "s_cid",
+    "sc_cid",
+    "epik",
+    "wickedid",
+    "_gl",
+    "pk_*",
+    "mtm_*",
+];
+
+/// How [`normalize`] rewrites URLs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct UrlPolicy {
+    /// Remove the fragment, which never changes the page fetched.
+    pub strip_fragment: bool,
+    /// Query parameters to remove, by name ignoring ASCII case. A name ending in `*`
+    /// matches every name with that prefix, as `utm_*` does. Defaults to
+    /// [`TRACKING_PARAMS`].
+    pub remove_params: Vec<String>,
+    /// Sort the query parameters by name, keeping the order of repeated names.
+    pub sort_query: bool,
+    /// Remove a leading `www.` from the host, for sites that serve both.
+    pub strip_www: bool,
+    /// Decode percent-encoded letters, digits and `-._~` in the path and query, and write
+    /// the hex digits of the remaining escapes in uppercase.
+    pub normalize_escapes: bool,
+}
+
+impl Default for UrlPolicy {
+    fn default() -> Self {
+        UrlPolicy {
+            strip_fragment: true,
+            remove_params: TRACKING_PARAMS
+                .iter()
+                .map(|&name| name.to_owned())
+                .collect(),
+            sort_query: true,
+            strip_www: false,
+            normalize_escapes: true,
+        }
+    }
+}
+
+impl UrlPolicy {
+    /// A policy that only removes fragments, for callers that need the URLs as written.
+    pub fn minimal() -> Self {
+        UrlPolicy {
+            strip_fragment: true,
+            remove_params: Vec::new(),
+            sort_query: false,
+            strip_www: false,
+            normalize_escapes: false,
+        }
+    }
+
+    /// Adds the parameters of a list to those removed, skipping ones already there. The
+    /// list has one name or `*` prefix per line, or several separated by commas or
+    /// whitespace; text after `#` is a comment. This keeps up with platforms newer than
+    /// [`TRACKING_PARAMS`] without a release, from a list maintained by the crawler.
+    ///
+    /// ```
+    /// use scraper::url_utils::{normalize, UrlPolicy};
+    /// use url::Url;
+    ///
+    /// let mut policy = UrlPolicy::default();
+    /// policy.add_params("# site-specific\nref, src_*\n");
+    /// let url = Url::parse("https://a.example/?ref=x&src_page=2&id=7&gclid=c").unwrap();
+    /// assert_eq!("https://a.example/?id=7", normalize(&url, &policy).as_str());
+    /// ```
+    pub fn add_params(&mut self, list: &str) {
+        let names = list
+            .lines()
+            .flat_map(|line| line.split('#').next().unwrap_or_default().split(','))
+            .flat_map(str::split_whitespace);
+        for name in names {
+            if !self
+                .remove_params
+                .iter()
+                .any(|known| known.eq_ignore_ascii_case(name))
+            {
+                self.remove_params.push(name.to_owned());
+            }
+        }
+    }
+
+    /// Returns true if the policy removes the query parameter `name`.
+    pub fn removes_param(&self, name: &str) -> bool {
+        self.remove_params
+            .iter()
+            .any(|pattern| match pattern.strip_suffix('*') {
+                Some(prefix) => {
+                    name.len() >= prefix.len()
+                        && name.as_bytes()[..prefix.len()].eq_ignore_ascii_case(prefix.as_bytes())
+                }
+                None => name.eq_ignore_ascii_case(pattern),
+            })
+    }
+}
+
+fn hex_value(byte: u8) -> Option<u8> {
+    (byte as char).to_digit(16).map(|d| d as u8)
+}
+
+/// Decodes escapes of unreserved characters and uppercases the others.
+fn normalize_escapes(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut out = String::with_capacity(input.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = match (bytes[i], bytes.get(i + 1), bytes.get(i + 2)) {
+            (b'%', Some(&hi), Some(&lo)) => hex_value(hi).zip(hex_value(lo)),
+            _ => None,
+        };
+        match escaped {
+            Some((hi, lo)) => {
+                let byte = hi << 4 | lo;
+                if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+                    out.push(byte as char);
+                } else {
+                    out.push('%');
+                    out.push(bytes[i + 1].to_ascii_uppercase() as char);
+                    out.push(bytes[i + 2].to_ascii_uppercase() as char);
+                }
+                i += 3;
+            }
+            None => {
+                let c = input[i..].chars().next().unwrap_or_default();
+                out.push(c);
+                i += c.len_utf8();
+            }
+        }
+    }
+    out
+}
+
+/// Returns the name of a raw `name=value` query parameter, percent-decoded.
+fn param_name(param: &str) -> String {
+    url::form_urlencoded::parse(param.as_bytes())
+        .next()
+        .map(|(name, _)| name.into_owned())
+        .unwrap_or_default()
+}
+
+/// Normalizes a URL according to a policy.
+///
+/// ```
+/// use scraper::url_utils::{normalize, UrlPolicy};
+/// use url::Url;
+///
+/// let url = Url::parse("HTTPS://Example.COM:443/a/./b/../%7Ec?utm_source=x&b=2&a=1#top").unwrap();
+/// assert_eq!(
+///     "https://example.com/a/~c?a=1&b=2",
+///     normalize(&url, &UrlPolicy::default()).as_str()
+/// );
+/// ```
+pub fn normalize(url: &Url, policy: &UrlPolicy) -> Url {
+    let mut url = url.clone();
+    if policy.strip_fragment {
+        url.set_fragment(None);
+    }
+    if policy.strip_www {
+        if let Some(host) = url.host_str().and_then(|h| h.strip_prefix("www.")) {
+            if !host.is_empty() {
+                let host = host.to_owned();
+                let _ = url.set_host(Some(&host));
+            }
+        }
+    }
+    if policy.normalize_escapes && url.path().contains('%') {
+        let path = normalize_escapes(url.path());
+        url.set_path(&path);
+    }
+
+    if let Some(query) = url.query() {
+        let mut params: Vec<(String, String)> = query
+            .split('&')
+            .filter(|param| !param.is_empty())
+            .map(|param| {
+                let param = if policy.normalize_escapes {
+                    normalize_escapes(param)
+                } else {
+                    param.to_owned()
+                };
+                (param_name(&param), param)
+            })
+            .filter(|(name, _)| !policy.removes_param(name))
+            .collect();
+        if policy.sort_query {
+            params.sort_by(|(a, _), (b, _)| a.cmp(b));
+        }
+        if params.is_empty() {
+            url.set_query(None);
+        } else {
+            let query: Vec<String> = params.into_iter().map(|(_, param)| param).collect();
+            url.set_query(Some(&query.join("&")));
+        }
+    }
+    url
+}
+
+#[cfg(test)]
+mod tests {
+    use url::Url;
+
+    use super::{normalize, UrlPolicy};
+
+    #[test]
+    fn normalizes_by_policy() {
+        let url = Url::parse("http://www.a.example/x/%2e%2e/y%2fz%c3%a9?Q=1&UTM_Medium=m&&q=0#f")
+            .unwrap();
+        assert_eq!(
+            "http://www.a.example/y%2Fz%C3%A9?Q=1&q=0",
+            normalize(&url, &UrlPolicy::default()).as_str()
+        );
+
+        let policy = UrlPolicy {
+            strip_www: true,
+            remove_params: vec!["q".into()],
+            ..UrlPolicy::minimal()
+        };
+        assert_eq!(
+            "http://a.example/y%2fz%c3%a9?UTM_Medium=m",
+            normalize(&url, &policy).as_str()
+        );
+        assert!(policy.removes_param("Q"));
+        assert!(!UrlPolicy::minimal().removes_param("utm_source"));
+    }
+
+    #[test]
+    fn tracking_params_are_built_in_and_extensible() {
+        let url =
+            Url::parse("https://a.example/?FBCLID=1&hsa_acc=2&pk_kwd=3&page=4&ref=5").unwrap();
+        let mut policy = UrlPolicy::default();
+        assert_eq!(
+            "https://a.example/?page=4&ref=5",
+            normalize(&url, &policy).as_str()
+        );
+
+        let known = policy.remove_params.len();
+        policy.add_params("REF #\n gclid  page,\n\n# referrers\nref_*");
+        assert_eq!(known + 3, policy.remove_params.len());
+        assert_eq!("https://a.example/", normalize(&url, &policy).as_str());
+    }
+}